@@ -0,0 +1,21 @@
+-- Add participation_events table
+-- Records every status change made to a participation along with who made it and why
+
+CREATE TABLE participation_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    participation_id UUID NOT NULL REFERENCES participations(id) ON DELETE CASCADE,
+    activity_id UUID NOT NULL REFERENCES activities(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    from_status participation_status,
+    to_status participation_status NOT NULL,
+    source VARCHAR(50) NOT NULL,
+    reason TEXT,
+    changed_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_participation_events_participation_id ON participation_events(participation_id);
+CREATE INDEX idx_participation_events_activity_id ON participation_events(activity_id);
+
+COMMENT ON TABLE participation_events IS 'History of participation status changes (scan, admin override, ...)';
+COMMENT ON COLUMN participation_events.source IS 'Origin of the change, e.g. admin';
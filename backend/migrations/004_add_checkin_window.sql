@@ -0,0 +1,9 @@
+-- Add per-activity QR check-in window
+-- NULL means "use the server default" (CHECKIN_OPENS_BEFORE_MINUTES / CHECKIN_CLOSES_AFTER_MINUTES)
+
+ALTER TABLE activities
+    ADD COLUMN checkin_opens_before_minutes INTEGER CHECK (checkin_opens_before_minutes >= 0),
+    ADD COLUMN checkin_closes_after_minutes INTEGER CHECK (checkin_closes_after_minutes >= 0);
+
+COMMENT ON COLUMN activities.checkin_opens_before_minutes IS 'Minutes before start time when QR check-in opens';
+COMMENT ON COLUMN activities.checkin_closes_after_minutes IS 'Minutes after end time when QR check-in closes';
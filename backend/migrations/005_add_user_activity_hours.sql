@@ -0,0 +1,24 @@
+-- Add user_activity_hours table
+-- One row per (user, activity) crediting the activity's hours once the participation is completed
+
+CREATE TABLE user_activity_hours (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    activity_id UUID NOT NULL REFERENCES activities(id) ON DELETE CASCADE,
+    participation_id UUID NOT NULL REFERENCES participations(id) ON DELETE CASCADE,
+    hours INTEGER NOT NULL,
+    awarded_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE(user_id, activity_id)
+);
+
+CREATE INDEX idx_user_activity_hours_user_id ON user_activity_hours(user_id);
+
+COMMENT ON TABLE user_activity_hours IS 'Activity hours awarded to students for completed participations';
+
+-- Credit participations that were already completed before this table existed
+INSERT INTO user_activity_hours (user_id, activity_id, participation_id, hours)
+SELECT p.user_id, p.activity_id, p.id, a.hours
+FROM participations p
+JOIN activities a ON a.id = p.activity_id
+WHERE p.status = 'completed'
+ON CONFLICT (user_id, activity_id) DO NOTHING;
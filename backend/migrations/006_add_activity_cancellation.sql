@@ -0,0 +1,11 @@
+-- Add activity cancellation details
+-- Cancelled activities keep their participations (marked 'cancelled') for audit
+
+ALTER TYPE participation_status ADD VALUE IF NOT EXISTS 'cancelled';
+
+ALTER TABLE activities
+    ADD COLUMN cancellation_reason TEXT,
+    ADD COLUMN cancelled_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN cancelled_by UUID REFERENCES users(id) ON DELETE SET NULL;
+
+COMMENT ON COLUMN activities.cancellation_reason IS 'Reason given when the activity was cancelled';
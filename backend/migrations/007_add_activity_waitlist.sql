@@ -0,0 +1,16 @@
+-- Add optional waitlist for activities that reach max_participants
+-- Existing activities keep the old behavior (allow_waitlist = FALSE)
+
+ALTER TYPE participation_status ADD VALUE IF NOT EXISTS 'waitlisted';
+
+ALTER TABLE activities
+    ADD COLUMN allow_waitlist BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE participations
+    ADD COLUMN waitlist_position INTEGER;
+
+CREATE INDEX idx_participations_waitlist ON participations(activity_id, waitlist_position)
+    WHERE waitlist_position IS NOT NULL;
+
+COMMENT ON COLUMN activities.allow_waitlist IS 'Queue registrations as waitlisted once max_participants is reached';
+COMMENT ON COLUMN participations.waitlist_position IS 'Position in the waitlist (1 = next to be promoted); NULL when not waitlisted';
@@ -0,0 +1,7 @@
+-- Add registration deadline to activities
+-- NULL keeps the previous behavior: registration closes when the activity starts
+
+ALTER TABLE activities
+    ADD COLUMN registration_deadline TIMESTAMP WITH TIME ZONE;
+
+COMMENT ON COLUMN activities.registration_deadline IS 'Registration closes at this time; NULL means the activity start time';
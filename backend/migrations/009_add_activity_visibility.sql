@@ -0,0 +1,13 @@
+-- Add activity visibility levels
+-- public: everyone, faculty_only: students of faculty_id, private: creator and admins over the faculty
+
+CREATE TYPE activity_visibility AS ENUM ('public', 'faculty_only', 'private');
+
+ALTER TABLE activities
+    ADD COLUMN visibility activity_visibility NOT NULL DEFAULT 'public',
+    ADD CONSTRAINT activities_faculty_only_requires_faculty
+        CHECK (visibility <> 'faculty_only' OR faculty_id IS NOT NULL);
+
+CREATE INDEX idx_activities_visibility ON activities(visibility);
+
+COMMENT ON COLUMN activities.visibility IS 'Who can see the activity in listings (public, faculty_only, private)';
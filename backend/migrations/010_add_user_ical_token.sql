@@ -0,0 +1,7 @@
+-- Add per-user calendar subscription token
+-- Only the SHA-256 hash is stored; the token itself is shown once when it is generated
+
+ALTER TABLE users
+    ADD COLUMN ical_token_hash VARCHAR(64) UNIQUE;
+
+COMMENT ON COLUMN users.ical_token_hash IS 'SHA-256 (hex) of the secret token for /api/ical/{token}.ics';
@@ -0,0 +1,14 @@
+-- Add full-text search vector for activities
+-- Generated column, so existing rows are backfilled when the column is added
+
+ALTER TABLE activities
+    ADD COLUMN search_vector tsvector GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', coalesce(title, '')), 'A') ||
+        setweight(to_tsvector('simple', coalesce(organizer, '')), 'B') ||
+        setweight(to_tsvector('simple', coalesce(location, '')), 'C') ||
+        setweight(to_tsvector('simple', coalesce(description, '')), 'D')
+    ) STORED;
+
+CREATE INDEX idx_activities_search_vector ON activities USING GIN (search_vector);
+
+COMMENT ON COLUMN activities.search_vector IS 'Weighted tsvector over title, organizer, location and description';
@@ -0,0 +1,19 @@
+-- Add audit log for administrative actions
+-- Rows are append-only; actor_user_id is kept nullable so entries survive when the admin is deleted
+
+CREATE TABLE audit_logs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    actor_user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    action VARCHAR(100) NOT NULL,
+    target_type VARCHAR(50) NOT NULL,
+    target_id TEXT,
+    reason TEXT,
+    details JSONB NOT NULL DEFAULT '{}'
+);
+
+CREATE INDEX idx_audit_logs_created_at ON audit_logs(created_at DESC);
+CREATE INDEX idx_audit_logs_actor_user_id ON audit_logs(actor_user_id);
+CREATE INDEX idx_audit_logs_action ON audit_logs(action);
+
+COMMENT ON COLUMN audit_logs.target_type IS 'Kind of object acted on, e.g. session, admin_role, user';
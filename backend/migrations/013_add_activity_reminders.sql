@@ -0,0 +1,12 @@
+-- Track activity reminders already sent, one row per activity/user/offset
+-- The primary key makes sending idempotent across runs and concurrent workers
+
+CREATE TABLE activity_reminders (
+    activity_id UUID NOT NULL REFERENCES activities(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    offset_minutes INTEGER NOT NULL,
+    sent_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    PRIMARY KEY (activity_id, user_id, offset_minutes)
+);
+
+CREATE INDEX idx_activity_reminders_user_id ON activity_reminders(user_id);
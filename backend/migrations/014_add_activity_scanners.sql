@@ -0,0 +1,12 @@
+-- Admins assigned to scan QR codes for an activity
+-- The creator and SuperAdmins can always scan; everyone else needs a row here
+
+CREATE TABLE activity_scanners (
+    activity_id UUID NOT NULL REFERENCES activities(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    assigned_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    assigned_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    PRIMARY KEY (activity_id, user_id)
+);
+
+CREATE INDEX idx_activity_scanners_user_id ON activity_scanners(user_id);
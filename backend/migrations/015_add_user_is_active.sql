@@ -0,0 +1,5 @@
+-- Deactivating a user keeps their participations and history, unlike deleting them
+-- Separate from admin_roles.is_enabled, which only removes admin access
+-- Existing users are backfilled as active by the default
+
+ALTER TABLE users ADD COLUMN is_active BOOLEAN NOT NULL DEFAULT TRUE;
@@ -0,0 +1,8 @@
+-- Erasure requests anonymize the user row instead of deleting it,
+-- so participations and attendance counts stay intact
+
+ALTER TABLE users
+    ADD COLUMN anonymized_at TIMESTAMP WITH TIME ZONE,
+    ALTER COLUMN qr_secret DROP NOT NULL;
+
+COMMENT ON COLUMN users.anonymized_at IS 'Set when personal data was replaced with tombstone values; the account can no longer log in';
@@ -0,0 +1,8 @@
+-- Drafts with publish_at are published by the status updater once that time passes
+
+ALTER TABLE activities
+    ADD COLUMN publish_at TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX idx_activities_scheduled_publish ON activities(publish_at) WHERE status = 'draft';
+
+COMMENT ON COLUMN activities.publish_at IS 'When a draft activity becomes published; NULL keeps the previous behaviour';
@@ -0,0 +1,8 @@
+-- Deleting an activity only marks it, so participations and hours survive and it can be restored
+
+ALTER TABLE activities
+    ADD COLUMN deleted_at TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX idx_activities_deleted_at ON activities(deleted_at) WHERE deleted_at IS NOT NULL;
+
+COMMENT ON COLUMN activities.deleted_at IS 'Set by DELETE /api/activities/{id}; soft-deleted activities are hidden everywhere until restored';
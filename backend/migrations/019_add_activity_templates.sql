@@ -0,0 +1,22 @@
+-- Reusable activity templates holding the non-schedule fields of recurring activities
+-- faculty_id NULL means a shared template managed by SuperAdmins and usable by every faculty
+
+CREATE TABLE activity_templates (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(255) NOT NULL,
+    title_pattern VARCHAR(255) NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    location VARCHAR(255) NOT NULL,
+    activity_type activity_type,
+    hours INTEGER NOT NULL CHECK (hours > 0),
+    organizer VARCHAR(255) NOT NULL,
+    eligible_faculties JSONB NOT NULL DEFAULT '[]',
+    faculty_id UUID REFERENCES faculties(id) ON DELETE CASCADE,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX idx_activity_templates_faculty_id ON activity_templates(faculty_id);
+
+COMMENT ON COLUMN activity_templates.title_pattern IS 'Activity title; {date} is replaced with the start date (YYYY-MM-DD)';
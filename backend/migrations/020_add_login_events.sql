@@ -0,0 +1,14 @@
+-- Login attempts per account (successful logins and wrong passwords) for the login history
+-- Attempts for identifiers that match no account are not recorded
+
+CREATE TABLE login_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    ip_address VARCHAR(64),
+    user_agent TEXT,
+    device_info JSONB NOT NULL DEFAULT '{}',
+    success BOOLEAN NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX idx_login_events_user_id_created_at ON login_events(user_id, created_at DESC);
@@ -0,0 +1,23 @@
+-- Optional TOTP two-factor authentication for admins
+-- A row with enabled_at NULL is a pending enrollment that has not been confirmed with a code yet
+
+CREATE TABLE admin_two_factor (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    secret VARCHAR(64) NOT NULL,
+    enabled_at TIMESTAMP WITH TIME ZONE,
+    -- Last accepted TOTP time step; codes from this step or earlier are refused (no replay)
+    last_used_step BIGINT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+);
+
+-- Single-use recovery codes; only SHA-256 hashes are stored
+CREATE TABLE admin_recovery_codes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    code_hash VARCHAR(64) NOT NULL,
+    used_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX idx_admin_recovery_codes_user_id ON admin_recovery_codes(user_id);
@@ -0,0 +1,15 @@
+-- LINE Notify as an extra notification channel
+-- A token belongs to either one user (personal chat) or one faculty (group chat);
+-- categories lists which notification categories are sent to it
+
+CREATE TABLE line_notify_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID UNIQUE REFERENCES users(id) ON DELETE CASCADE,
+    faculty_id UUID UNIQUE REFERENCES faculties(id) ON DELETE CASCADE,
+    token VARCHAR(255) NOT NULL,
+    -- reminders, announcements, check_ins
+    categories TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    CONSTRAINT line_notify_tokens_one_owner CHECK ((user_id IS NULL) <> (faculty_id IS NULL))
+);
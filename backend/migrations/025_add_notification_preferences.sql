@@ -0,0 +1,13 @@
+-- Per-user notification opt-outs, one row per (user, category)
+-- Missing rows mean every channel is enabled, so existing users keep receiving everything
+
+CREATE TABLE notification_preferences (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- reminders, announcements, check_ins
+    category VARCHAR(32) NOT NULL,
+    email_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    sse_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    line_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    PRIMARY KEY (user_id, category)
+);
@@ -0,0 +1,26 @@
+-- In-app notification inbox
+-- A notification is addressed to one user or to every student of a faculty; read state is
+-- kept per user in notification_reads, which also covers subscription_notifications rows
+
+CREATE TABLE notifications (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    faculty_id UUID REFERENCES faculties(id) ON DELETE CASCADE,
+    -- reminders, announcements, check_ins
+    category VARCHAR(32) NOT NULL,
+    message TEXT NOT NULL,
+    data JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    CONSTRAINT notifications_one_audience CHECK ((user_id IS NULL) <> (faculty_id IS NULL))
+);
+
+CREATE INDEX idx_notifications_user_id_created_at ON notifications(user_id, created_at DESC) WHERE user_id IS NOT NULL;
+CREATE INDEX idx_notifications_faculty_id_created_at ON notifications(faculty_id, created_at DESC) WHERE faculty_id IS NOT NULL;
+
+CREATE TABLE notification_reads (
+    -- notifications.id or subscription_notifications.id, so no foreign key
+    notification_id UUID NOT NULL,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    read_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    PRIMARY KEY (user_id, notification_id)
+);
@@ -0,0 +1,24 @@
+-- System announcements scheduled by super admins
+-- The announcement worker flips scheduled rows to published at publish_at; a published
+-- announcement shows in every user's notification inbox until display_until
+
+CREATE TABLE scheduled_announcements (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    title VARCHAR(255) NOT NULL,
+    message TEXT NOT NULL,
+    publish_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    -- NULL keeps the announcement visible until it is cancelled
+    display_until TIMESTAMP WITH TIME ZONE,
+    -- scheduled, published or cancelled
+    status VARCHAR(20) NOT NULL DEFAULT 'scheduled',
+    published_at TIMESTAMP WITH TIME ZONE,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    cancelled_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    cancelled_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL,
+    CONSTRAINT scheduled_announcements_display_window CHECK (display_until IS NULL OR display_until > publish_at)
+);
+
+CREATE INDEX idx_scheduled_announcements_due ON scheduled_announcements(publish_at) WHERE status = 'scheduled';
+CREATE INDEX idx_scheduled_announcements_published ON scheduled_announcements(published_at DESC) WHERE status = 'published';
@@ -0,0 +1,7 @@
+-- Country/city of the login IP, resolved with the optional GeoIP database (GEOIP_DATABASE_PATH)
+-- NULL when GeoIP is not configured or the IP is unknown (e.g. private networks)
+
+ALTER TABLE login_events
+    ADD COLUMN country_code VARCHAR(2),
+    ADD COLUMN country VARCHAR(100),
+    ADD COLUMN city VARCHAR(100);
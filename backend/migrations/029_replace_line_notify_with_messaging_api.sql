@@ -0,0 +1,20 @@
+-- LINE Notify was shut down on 2025-03-31. LINE messages now go out through the
+-- Messaging API push endpoint of the school's LINE Official Account, addressed by the
+-- LINE user ID (U...) or group ID (C...) / room ID (R...) of the chat.
+-- Notify tokens cannot be turned into chat IDs, so existing rows are dropped and each
+-- user or faculty links their chat again.
+
+DELETE FROM line_notify_tokens;
+
+ALTER TABLE line_notify_tokens RENAME TO line_recipients;
+ALTER TABLE line_recipients RENAME COLUMN token TO line_id;
+ALTER TABLE line_recipients ALTER COLUMN line_id TYPE VARCHAR(33);
+
+ALTER INDEX line_notify_tokens_pkey RENAME TO line_recipients_pkey;
+ALTER INDEX line_notify_tokens_user_id_key RENAME TO line_recipients_user_id_key;
+ALTER INDEX line_notify_tokens_faculty_id_key RENAME TO line_recipients_faculty_id_key;
+ALTER TABLE line_recipients RENAME CONSTRAINT line_notify_tokens_one_owner TO line_recipients_one_owner;
+ALTER TABLE line_recipients RENAME CONSTRAINT line_notify_tokens_user_id_fkey TO line_recipients_user_id_fkey;
+ALTER TABLE line_recipients RENAME CONSTRAINT line_notify_tokens_faculty_id_fkey TO line_recipients_faculty_id_fkey;
+ALTER TABLE line_recipients
+    ADD CONSTRAINT line_recipients_line_id_format CHECK (line_id ~ '^[UCR][0-9a-f]{32}$');
@@ -0,0 +1,298 @@
+use axum::{
+    extract::{rejection::JsonRejection, FromRequest, OptionalFromRequest, Request},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use serde_json::{json, Map, Value};
+
+use crate::utils::validation::ValidationError;
+
+/// Error body shared by every `ApiError` variant
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorDetail {
+    /// Machine-readable, stable identifier the frontend can switch on (e.g. `activity_full`)
+    pub code: &'static str,
+    /// Human-readable message; wording may change between releases
+    pub message: String,
+    /// Extra top-level keys kept for responses that already carried them (e.g. `data`)
+    pub extra: Map<String, Value>,
+}
+
+/// Typed handler error rendered as
+/// `{"status": "error", "code": "...", "message": "..."}`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiError {
+    BadRequest(ErrorDetail),
+    Validation(ErrorDetail),
+    Unauthorized(ErrorDetail),
+    Forbidden(ErrorDetail),
+    NotFound(ErrorDetail),
+    Conflict(ErrorDetail),
+    PayloadTooLarge(ErrorDetail),
+    Internal(ErrorDetail),
+}
+
+pub type ApiResult<T> = Result<T, ApiError>;
+
+fn detail(code: &'static str, message: impl Into<String>) -> ErrorDetail {
+    ErrorDetail {
+        code,
+        message: message.into(),
+        extra: Map::new(),
+    }
+}
+
+impl ApiError {
+    pub fn bad_request(code: &'static str, message: impl Into<String>) -> Self {
+        Self::BadRequest(detail(code, message))
+    }
+
+    pub fn validation(code: &'static str, message: impl Into<String>) -> Self {
+        Self::Validation(detail(code, message))
+    }
+
+    pub fn unauthorized(code: &'static str, message: impl Into<String>) -> Self {
+        Self::Unauthorized(detail(code, message))
+    }
+
+    pub fn forbidden(code: &'static str, message: impl Into<String>) -> Self {
+        Self::Forbidden(detail(code, message))
+    }
+
+    pub fn not_found(code: &'static str, message: impl Into<String>) -> Self {
+        Self::NotFound(detail(code, message))
+    }
+
+    pub fn conflict(code: &'static str, message: impl Into<String>) -> Self {
+        Self::Conflict(detail(code, message))
+    }
+
+    pub fn payload_too_large(code: &'static str, message: impl Into<String>) -> Self {
+        Self::PayloadTooLarge(detail(code, message))
+    }
+
+    /// Internal errors share one code; the message should not leak driver errors
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::Internal(detail("internal_error", message))
+    }
+
+    /// Add an extra top-level key to the error body
+    pub fn with(mut self, key: &str, value: Value) -> Self {
+        self.detail_mut().extra.insert(key.to_string(), value);
+        self
+    }
+
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Self::BadRequest(_) | Self::Validation(_) => StatusCode::BAD_REQUEST,
+            Self::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            Self::Forbidden(_) => StatusCode::FORBIDDEN,
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::Conflict(_) => StatusCode::CONFLICT,
+            Self::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn detail(&self) -> &ErrorDetail {
+        match self {
+            Self::BadRequest(detail)
+            | Self::Validation(detail)
+            | Self::Unauthorized(detail)
+            | Self::Forbidden(detail)
+            | Self::NotFound(detail)
+            | Self::Conflict(detail)
+            | Self::PayloadTooLarge(detail)
+            | Self::Internal(detail) => detail,
+        }
+    }
+
+    fn detail_mut(&mut self) -> &mut ErrorDetail {
+        match self {
+            Self::BadRequest(detail)
+            | Self::Validation(detail)
+            | Self::Unauthorized(detail)
+            | Self::Forbidden(detail)
+            | Self::NotFound(detail)
+            | Self::Conflict(detail)
+            | Self::PayloadTooLarge(detail)
+            | Self::Internal(detail) => detail,
+        }
+    }
+
+    fn body(&self) -> Value {
+        let detail = self.detail();
+        let mut body = json!({
+            "status": "error",
+            "code": detail.code,
+            "message": detail.message
+        });
+        if let Value::Object(map) = &mut body {
+            for (key, value) in &detail.extra {
+                map.insert(key.clone(), value.clone());
+            }
+        }
+        body
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status_code(), Json(self.body())).into_response()
+    }
+}
+
+/// Lets handlers that still return `(StatusCode, Json<Value>)` call `ApiError` helpers with `?`
+impl From<ApiError> for (StatusCode, Json<Value>) {
+    fn from(error: ApiError) -> Self {
+        (error.status_code(), Json(error.body()))
+    }
+}
+
+impl From<ValidationError> for ApiError {
+    fn from(error: ValidationError) -> Self {
+        ApiError::validation("validation_failed", format!("Invalid {}", error.field))
+            .with("field", json!(error.field))
+            .with("errors", json!(error.violations))
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        match rejection {
+            JsonRejection::JsonDataError(e) => ApiError::validation("invalid_json_body", e.body_text()),
+            JsonRejection::JsonSyntaxError(e) => ApiError::bad_request("malformed_json", e.body_text()),
+            JsonRejection::MissingJsonContentType(e) => {
+                ApiError::bad_request("unsupported_content_type", e.body_text())
+            }
+            rejection if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE => body_too_large(),
+            rejection => ApiError::bad_request("invalid_body", rejection.body_text()),
+        }
+    }
+}
+
+fn body_too_large() -> ApiError {
+    ApiError::payload_too_large("payload_too_large", "Request body is too large")
+}
+
+/// Drop-in replacement for `axum::Json` as an extractor; malformed or oversized bodies are
+/// rejected with the standard error envelope instead of axum's plain-text message
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ApiJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for ApiJson<T>
+where
+    Json<T>: FromRequest<S, Rejection = JsonRejection>,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state).await?;
+        Ok(Self(value))
+    }
+}
+
+/// `Option<ApiJson<T>>` is `None` only when no JSON body was sent; a malformed body is still rejected
+impl<T, S> OptionalFromRequest<S> for ApiJson<T>
+where
+    Json<T>: OptionalFromRequest<S, Rejection = JsonRejection>,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Option<Self>, Self::Rejection> {
+        let value = <Json<T> as OptionalFromRequest<S>>::from_request(req, state).await?;
+        Ok(value.map(|Json(value)| Self(value)))
+    }
+}
+
+/// `RequestBodyLimitLayer` answers oversized requests with a plain-text 413 before any
+/// extractor runs; rewrite it into the standard envelope
+pub async fn body_limit_response(response: Response) -> Response {
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|value| value.as_bytes().starts_with(b"application/json"));
+
+    if response.status() == StatusCode::PAYLOAD_TOO_LARGE && !is_json {
+        return body_too_large().into_response();
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::validation::RuleViolation;
+    use axum::{body::Body, extract::DefaultBodyLimit, routing::post, Router};
+    use tower::ServiceExt;
+    use tower_http::limit::RequestBodyLimitLayer;
+
+    async fn post_json(body: &'static str) -> (StatusCode, Value) {
+        let app = Router::new()
+            .route("/echo", post(|ApiJson(value): ApiJson<Value>| async move { Json(value) }))
+            .layer(DefaultBodyLimit::disable())
+            .layer(RequestBodyLimitLayer::new(16))
+            .layer(axum::middleware::map_response(body_limit_response));
+
+        let request = axum::http::Request::builder()
+            .method("POST")
+            .uri("/echo")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[test]
+    fn test_error_body_keeps_envelope() {
+        let error = ApiError::conflict("activity_full", "Activity is full")
+            .with("data", json!({ "max_participants": 10 }));
+        let (status, Json(body)) = <(StatusCode, Json<Value>)>::from(error);
+
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["status"], "error");
+        assert_eq!(body["code"], "activity_full");
+        assert_eq!(body["message"], "Activity is full");
+        assert_eq!(body["data"]["max_participants"], 10);
+    }
+
+    #[test]
+    fn test_validation_error_conversion() {
+        let error: ApiError = ValidationError {
+            field: "password".to_string(),
+            violations: vec![RuleViolation {
+                rule: "digit".to_string(),
+                message: "Password must contain a number".to_string(),
+            }],
+        }
+        .into();
+
+        assert_eq!(error.status_code(), StatusCode::BAD_REQUEST);
+        let (_, Json(body)) = <(StatusCode, Json<Value>)>::from(error);
+        assert_eq!(body["code"], "validation_failed");
+        assert_eq!(body["message"], "Invalid password");
+        assert_eq!(body["errors"][0]["rule"], "digit");
+    }
+
+    #[tokio::test]
+    async fn test_json_rejections_use_envelope() {
+        let (status, body) = post_json(r#"{"a": 1}"#).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["a"], 1);
+
+        let (status, body) = post_json(r#"{"a": "#).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["status"], "error");
+        assert_eq!(body["code"], "malformed_json");
+
+        let (status, body) = post_json(r#"{"a": "this body is too long"}"#).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(body["status"], "error");
+        assert_eq!(body["code"], "payload_too_large");
+    }
+}
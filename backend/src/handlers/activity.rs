@@ -1,22 +1,41 @@
 use axum::{
+    body::Bytes,
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    http::{header, HeaderMap},
+    response::{IntoResponse, Json, Response},
 };
-use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use sqlx::Row;
+use sqlx::{FromRow, Row};
 use std::collections::HashMap;
 use uuid::Uuid;
 
-use crate::middleware::session::{AdminUser, SessionState};
-use crate::models::session::SessionUser;
+use crate::error::{ApiError, ApiJson, ApiResult};
+use crate::middleware::session::{
+    get_accessible_faculty_ids, has_faculty_access, AdminUser, CanCreateActivity, RequirePermission,
+    SessionState,
+};
+use crate::models::session::{Permission, SessionUser};
 use crate::models::{
-    activity::{ActivityStatus},
-    participation::{Participation, ParticipationStatus},
+    activity::{is_faculty_eligible, ActivityRecord, ActivityStatus, ActivityVisibility, CheckInWindow},
+    activity_feedback::{ActivityFeedback, SubmitActivityFeedback},
+    admin_role::AdminLevel,
+    notifications::NotificationCategory,
+    participation::{Participation, ParticipationStatus, UpdateParticipationNotes, UpdateParticipationStatus},
     user::UserPrefix,
+    webhook::WebhookEvent,
 };
+use crate::handlers::admin::invalidate_dashboard_cache;
+use crate::services::email_service::{EmailService, EmailTemplate};
+use crate::services::notification::{notify, notify_bulk_check_in, notify_participation_scan, NotificationTarget};
+use crate::services::notification_inbox::store_user_notifications;
+use crate::services::webhook::{enqueue_webhook_event, participation_event_data};
+use crate::utils::csv::{csv_attachment_stream, csv_row, parse_csv};
+use crate::utils::xlsx::{xlsx_attachment, xlsx_workbook};
+use crate::utils::pagination::ActivityCursor;
+use crate::utils::qr::parse_signed_qr_payload;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateActivityRequest {
@@ -28,6 +47,11 @@ pub struct CreateActivityRequest {
     pub max_participants: Option<i32>,
     pub faculty_id: Option<Uuid>,
     pub department_id: Option<Uuid>,
+    pub allow_waitlist: Option<bool>,
+    pub registration_deadline: Option<DateTime<Utc>>,
+    pub visibility: Option<ActivityVisibility>,
+    // Keep the activity a draft until this time, then publish it automatically
+    pub publish_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -41,28 +65,51 @@ pub struct UpdateActivityRequest {
     pub status: Option<ActivityStatus>,
     pub faculty_id: Option<Uuid>,
     pub department_id: Option<Uuid>,
+    pub allow_waitlist: Option<bool>,
+    pub registration_deadline: Option<DateTime<Utc>>,
+    pub visibility: Option<ActivityVisibility>,
+    pub publish_at: Option<DateTime<Utc>>,
+    // Optimistic locking: only update if the row still has this updated_at
+    pub expected_updated_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ParticipateRequest {
+    // Register even if the student already has an overlapping activity
+    pub allow_overlap: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct ActivityWithDetails {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub activity: ActivityRecord,
+    pub current_participants: i64,
+    pub faculty_name: Option<String>,
+    pub created_by_name: String,
+    pub is_registered: bool,
+    pub user_participation_status: Option<ParticipationStatus>,
+    /// Average feedback rating, only filled in for organizers by `get_activity`
+    #[sqlx(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub average_rating: Option<f64>,
+}
+
+/// Reduced activity view for the unauthenticated public listing
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct PublicActivity {
     pub id: Uuid,
     pub title: String,
     pub description: String,
     pub location: String,
     pub start_time: DateTime<Utc>,
     pub end_time: DateTime<Utc>,
+    pub activity_type: Option<String>,
+    pub status: ActivityStatus,
     pub max_participants: Option<i32>,
     pub current_participants: i64,
-    pub status: ActivityStatus,
-    pub activity_type: Option<String>,
-    pub faculty_id: Option<Uuid>,
+    pub registration_deadline: Option<DateTime<Utc>>,
     pub faculty_name: Option<String>,
-    pub created_by: Uuid,
-    pub created_by_name: String,
-    pub created_at: DateTime<Utc>,
-    pub updated_at: DateTime<Utc>,
-    pub is_registered: bool,
-    pub user_participation_status: Option<ParticipationStatus>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -82,6 +129,19 @@ pub struct ParticipationWithUser {
     pub notes: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CancelActivityRequest {
+    pub reason: String,
+}
+
+/// Dates for a cloned activity; times of day are kept from the source.
+/// When only `start_date` is given, `end_date` keeps the source's span.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CloneActivityRequest {
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct QrScanRequest {
     pub qr_data: String,
@@ -97,12 +157,39 @@ pub struct QrScanResponse {
     pub student_id: Option<String>,
 }
 
+/// SQL condition limiting activities to those the user may see.
+///
+/// `$user_param` binds the user id and `$faculty_ids_param` the admin's faculty ids
+/// (empty for students). SuperAdmins see everything and should skip this condition.
+fn visibility_condition(user_param: usize, faculty_ids_param: usize) -> String {
+    format!(
+        r#"(a.visibility = 'public'
+            OR a.created_by = ${user}
+            OR a.faculty_id = ANY(${faculty_ids})
+            OR (a.visibility = 'faculty_only' AND a.faculty_id = (
+                SELECT d.faculty_id FROM users vu
+                JOIN departments d ON vu.department_id = d.id
+                WHERE vu.id = ${user}
+            )))"#,
+        user = user_param,
+        faculty_ids = faculty_ids_param
+    )
+}
+
+/// Search terms shorter than this (single token) use substring ILIKE instead of full-text search
+const MIN_FULL_TEXT_TOKEN_LEN: usize = 3;
+
+/// A single short token like "AI" matches too little as a whole word, so it is searched as a substring
+fn is_short_search_token(term: &str) -> bool {
+    !term.contains(char::is_whitespace) && term.chars().count() < MIN_FULL_TEXT_TOKEN_LEN
+}
+
 /// Get activities with filtering and pagination
 pub async fn get_activities(
     State(session_state): State<SessionState>,
     user: SessionUser,
     Query(params): Query<HashMap<String, String>>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> ApiResult<Json<Value>> {
     let limit = params
         .get("limit")
         .and_then(|l| l.parse::<i64>().ok())
@@ -113,40 +200,89 @@ pub async fn get_activities(
         .and_then(|o| o.parse::<i64>().ok())
         .unwrap_or(0);
 
-    let search = params.get("search").cloned();
+    let search = params
+        .get("search")
+        .map(|term| term.trim().to_string())
+        .filter(|term| !term.is_empty());
+    let full_text_search = search.as_deref().is_some_and(|term| !is_short_search_token(term));
     let status_filter = params.get("status");
     let faculty_id = params
         .get("faculty_id")
         .and_then(|f| Uuid::parse_str(f).ok());
+    let activity_type = params.get("activity_type").filter(|t| !t.is_empty());
     // department filter removed
 
-    let mut query = r#"
+    // Date range on the activity start date (calendar view), both ends inclusive
+    let mut start_range = [None, None];
+    for (i, key) in ["start_from", "start_to"].into_iter().enumerate() {
+        if let Some(raw) = params.get(key).filter(|d| !d.is_empty()) {
+            match chrono::NaiveDate::parse_from_str(raw, "%Y-%m-%d") {
+                Ok(date) => start_range[i] = Some(date),
+                Err(_) => {
+                    return Err(ApiError::bad_request(
+                        "invalid_date",
+                        format!("Invalid {}: expected YYYY-MM-DD", key),
+                    ));
+                }
+            }
+        }
+    }
+    let [start_from, start_to] = start_range;
+
+    if let (Some(from), Some(to)) = (start_from, start_to) {
+        if from > to {
+            return Err(ApiError::bad_request("invalid_date_range", "start_from must be on or before start_to"));
+        }
+    }
+
+    // Keyset pagination: when a cursor is supplied, offset is ignored
+    let cursor = match params.get("cursor").filter(|c| !c.is_empty()) {
+        Some(raw) => match ActivityCursor::decode(raw) {
+            Ok(cursor) => Some(cursor),
+            Err(_) => {
+                return Err(ApiError::bad_request("invalid_cursor", "Invalid cursor"));
+            }
+        },
+        None => None,
+    };
+    // Full-text results are ordered by relevance, which keyset pagination can't follow
+    let cursor = if full_text_search { None } else { cursor };
+    let offset = if cursor.is_some() { 0 } else { offset };
+
+    let time_zone = session_state.app_config.activity_time_zone();
+    let mut query = format!(
+        r#"
         SELECT 
             a.id,
             a.title,
             a.description,
             a.location,
-            ((a.start_date::timestamp + a.start_time_only) AT TIME ZONE 'UTC') as start_time,
-            ((a.end_date::timestamp + a.end_time_only) AT TIME ZONE 'UTC') as end_time,
+            {} as start_time,
+            {} as end_time,
             a.max_participants,
+            a.registration_deadline,
+            a.publish_at,
             a.status,
-            a.activity_type,
+            a.visibility,
+            a.activity_type::text as activity_type,
             a.faculty_id,
             a.created_by,
             a.created_at,
             a.updated_at,
-            f.name as faculty_name,
-            u.first_name || ' ' || u.last_name as created_by_name,
-            COALESCE(COUNT(p.id), 0) as current_participants,
-            CASE WHEN up.id IS NOT NULL THEN true ELSE false END as is_registered,
+            NULLIF(f.name, '') as faculty_name,
+            COALESCE(u.first_name || ' ' || u.last_name, 'Unknown') as created_by_name,
+            COUNT(p.id) FILTER (WHERE p.status NOT IN ('waitlisted', 'cancelled')) as current_participants,
+            up.id IS NOT NULL as is_registered,
             up.status as user_participation_status
         FROM activities a
         LEFT JOIN faculties f ON a.faculty_id = f.id
         LEFT JOIN users u ON a.created_by = u.id
         LEFT JOIN participations p ON a.id = p.activity_id
         LEFT JOIN participations up ON a.id = up.activity_id AND up.user_id = $3
-    "#
-    .to_string();
+    "#,
+        time_zone.start_sql("a"),
+        time_zone.end_sql("a")
+    );
 
     let mut count_query = r#"
         SELECT COUNT(DISTINCT a.id) 
@@ -155,39 +291,114 @@ pub async fn get_activities(
     "#
     .to_string();
 
-    let mut conditions = Vec::new();
+    // Filters are shared by both queries, but the count query has no limit/offset/user params
+    let mut conditions = vec!["a.deleted_at IS NULL".to_string()];
+    let mut count_conditions = vec!["a.deleted_at IS NULL".to_string()];
     let mut param_count = 4;
+    let mut count_param_count = 1;
 
-    if let Some(_search_term) = &search {
+    let search_param = param_count;
+    if full_text_search {
+        conditions.push(format!(
+            "a.search_vector @@ websearch_to_tsquery('simple', ${})",
+            param_count
+        ));
+        count_conditions.push(format!(
+            "a.search_vector @@ websearch_to_tsquery('simple', ${})",
+            count_param_count
+        ));
+        param_count += 1;
+        count_param_count += 1;
+    } else if search.is_some() {
         conditions.push(format!(
             "(a.title ILIKE ${} OR a.description ILIKE ${} OR a.location ILIKE ${})",
             param_count, param_count, param_count
         ));
+        count_conditions.push(format!(
+            "(a.title ILIKE ${} OR a.description ILIKE ${} OR a.location ILIKE ${})",
+            count_param_count, count_param_count, count_param_count
+        ));
         param_count += 1;
+        count_param_count += 1;
     }
 
     if let Some(_status) = status_filter {
-        conditions.push(format!("a.status = ${}", param_count));
+        conditions.push(format!("a.status::text = ${}", param_count));
+        count_conditions.push(format!("a.status::text = ${}", count_param_count));
         param_count += 1;
+        count_param_count += 1;
     }
 
     if faculty_id.is_some() {
         conditions.push(format!("a.faculty_id = ${}", param_count));
+        count_conditions.push(format!("a.faculty_id = ${}", count_param_count));
+        param_count += 1;
+        count_param_count += 1;
+    }
+
+    if activity_type.is_some() {
+        conditions.push(format!("a.activity_type::text = ${}", param_count));
+        count_conditions.push(format!("a.activity_type::text = ${}", count_param_count));
+        param_count += 1;
+        count_param_count += 1;
+    }
+
+    if start_from.is_some() {
+        conditions.push(format!("a.start_date >= ${}", param_count));
+        count_conditions.push(format!("a.start_date >= ${}", count_param_count));
+        param_count += 1;
+        count_param_count += 1;
+    }
+
+    if start_to.is_some() {
+        conditions.push(format!("a.start_date <= ${}", param_count));
+        count_conditions.push(format!("a.start_date <= ${}", count_param_count));
+        param_count += 1;
+        count_param_count += 1;
+    }
+
+    // Hide faculty-only/private activities the user is not allowed to see
+    let visible_faculty_ids = get_accessible_faculty_ids(&user);
+    if visible_faculty_ids.is_some() {
+        conditions.push(visibility_condition(3, param_count));
+        count_conditions.push(visibility_condition(count_param_count, count_param_count + 1));
         param_count += 1;
     }
 
     // department filter removed
 
+    if !count_conditions.is_empty() {
+        count_query.push_str(&format!(" WHERE {}", count_conditions.join(" AND ")));
+    }
+
+    // The cursor only narrows the page; total_count still reflects the whole filtered set
+    // Cursors carry the start as UTC date and time, whatever zone the columns are in
+    if cursor.is_some() {
+        conditions.push(format!(
+            "({}, a.id) < ((${}::date + ${}::time) AT TIME ZONE 'UTC', ${})",
+            time_zone.start_sql("a"),
+            param_count,
+            param_count + 1,
+            param_count + 2
+        ));
+    }
+
     if !conditions.is_empty() {
-        let where_clause = format!(" WHERE {}", conditions.join(" AND "));
-        query.push_str(&where_clause);
-        count_query.push_str(&where_clause);
+        query.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
     }
 
-    query.push_str(" GROUP BY a.id, a.title, a.description, a.location, a.start_date, a.end_date, a.start_time_only, a.end_time_only, a.max_participants, a.status, a.faculty_id, a.created_by, a.created_at, a.updated_at, f.name, u.first_name, u.last_name, up.id, up.status");
-    query.push_str(" ORDER BY a.start_date DESC, a.start_time_only DESC LIMIT $1 OFFSET $2");
+    query.push_str(" GROUP BY a.id, a.title, a.description, a.location, a.start_date, a.end_date, a.start_time_only, a.end_time_only, a.max_participants, a.registration_deadline, a.publish_at, a.status, a.visibility, a.faculty_id, a.created_by, a.created_at, a.updated_at, f.name, u.first_name, u.last_name, up.id, up.status");
+    if full_text_search {
+        query.push_str(&format!(
+            " ORDER BY ts_rank(a.search_vector, websearch_to_tsquery('simple', ${})) DESC,",
+            search_param
+        ));
+    } else {
+        query.push_str(" ORDER BY");
+    }
+    query.push_str(" a.start_date DESC, a.start_time_only DESC, a.id DESC LIMIT $1 OFFSET $2");
 
-    let mut query_builder = sqlx::query(&query)
+    let mut query_builder = sqlx::query_as::<_, ActivityWithDetails>(&query)
         .bind(limit)
         .bind(offset)
         .bind(user.user_id);
@@ -195,9 +406,13 @@ pub async fn get_activities(
     let mut count_query_builder = sqlx::query_scalar::<_, i64>(&count_query);
 
     if let Some(search_term) = &search {
-        let search_pattern = format!("%{}%", search_term);
-        query_builder = query_builder.bind(search_pattern.clone());
-        count_query_builder = count_query_builder.bind(search_pattern);
+        let search_value = if full_text_search {
+            search_term.clone()
+        } else {
+            format!("%{}%", search_term)
+        };
+        query_builder = query_builder.bind(search_value.clone());
+        count_query_builder = count_query_builder.bind(search_value);
     }
 
     if let Some(status) = status_filter {
@@ -210,42 +425,56 @@ pub async fn get_activities(
         count_query_builder = count_query_builder.bind(f_id);
     }
 
+    if let Some(activity_type) = activity_type {
+        query_builder = query_builder.bind(activity_type);
+        count_query_builder = count_query_builder.bind(activity_type);
+    }
+
+    if let Some(from) = start_from {
+        query_builder = query_builder.bind(from);
+        count_query_builder = count_query_builder.bind(from);
+    }
+
+    if let Some(to) = start_to {
+        query_builder = query_builder.bind(to);
+        count_query_builder = count_query_builder.bind(to);
+    }
+
+    if let Some(faculty_ids) = &visible_faculty_ids {
+        query_builder = query_builder.bind(faculty_ids);
+        count_query_builder = count_query_builder
+            .bind(user.user_id)
+            .bind(faculty_ids);
+    }
+
+    if let Some(cursor) = &cursor {
+        query_builder = query_builder
+            .bind(cursor.start_date)
+            .bind(cursor.start_time)
+            .bind(cursor.id);
+    }
+
     // no department filter
 
     let activities_result = query_builder.fetch_all(&session_state.db_pool).await;
     let total_count_result = count_query_builder.fetch_one(&session_state.db_pool).await;
 
     match (activities_result, total_count_result) {
-        (Ok(rows), Ok(total_count)) => {
-            let mut activities_with_details = Vec::new();
-
-            for row in rows {
-                let activity_detail = ActivityWithDetails {
-                    id: row.get("id"),
-                    title: row.get("title"),
-                    description: row.get("description"),
-                    location: row.get("location"),
-                    start_time: row.get("start_time"),
-                    end_time: row.get("end_time"),
-                    max_participants: row.get::<Option<i32>, _>("max_participants"),
-                    current_participants: row
-                        .get::<Option<i64>, _>("current_participants")
-                        .unwrap_or(0),
-                    status: row.get::<ActivityStatus, _>("status"),
-                    activity_type: row.get::<Option<String>, _>("activity_type"),
-                    faculty_id: row.get::<Option<Uuid>, _>("faculty_id"),
-                    faculty_name: row.get::<Option<String>, _>("faculty_name"),
-                    created_by: row.get("created_by"),
-                    created_by_name: row.get("created_by_name"),
-                    created_at: row.get("created_at"),
-                    updated_at: row.get("updated_at"),
-                    is_registered: row.get::<Option<bool>, _>("is_registered").unwrap_or(false),
-                    user_participation_status: row
-                        .get::<Option<ParticipationStatus>, _>("user_participation_status"),
-                };
-
-                activities_with_details.push(activity_detail);
-            }
+        (Ok(activities_with_details), Ok(total_count)) => {
+            // Only hand out a cursor when the page is full (there may be more rows)
+            let next_cursor = if !full_text_search && activities_with_details.len() as i64 == limit {
+                activities_with_details.last().map(|last| {
+                    let start = last.activity.start_time.naive_utc();
+                    ActivityCursor {
+                        start_date: start.date(),
+                        start_time: start.time(),
+                        id: last.activity.id,
+                    }
+                    .encode()
+                })
+            } else {
+                None
+            };
 
             let response = json!({
                 "status": "success",
@@ -253,7 +482,8 @@ pub async fn get_activities(
                     "activities": activities_with_details,
                     "total_count": total_count,
                     "limit": limit,
-                    "offset": offset
+                    "offset": offset,
+                    "next_cursor": next_cursor
                 },
                 "message": "Activities retrieved successfully"
             });
@@ -261,11 +491,84 @@ pub async fn get_activities(
             Ok(Json(response))
         }
         _ => {
-            let error_response = json!({
-                "status": "error",
-                "message": "Failed to retrieve activities"
+            Err(ApiError::internal("Failed to retrieve activities"))
+        }
+    }
+}
+
+/// Public activity board (no login): upcoming Public activities, ordered by start time
+pub async fn get_public_activities(
+    State(session_state): State<SessionState>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ApiResult<Json<Value>> {
+    let limit = params
+        .get("limit")
+        .and_then(|l| l.parse::<i64>().ok())
+        .unwrap_or(20)
+        .clamp(1, 100);
+
+    let offset = params
+        .get("offset")
+        .and_then(|o| o.parse::<i64>().ok())
+        .unwrap_or(0)
+        .max(0);
+
+    let time_zone = session_state.app_config.activity_time_zone();
+    let activities = sqlx::query_as::<_, PublicActivity>(&format!(
+        r#"
+        SELECT
+            a.id,
+            a.title,
+            a.description,
+            a.location,
+            {} as start_time,
+            {} as end_time,
+            a.activity_type::text as activity_type,
+            a.status,
+            a.max_participants,
+            (
+                SELECT COUNT(*) FROM participations p
+                WHERE p.activity_id = a.id AND p.status NOT IN ('waitlisted', 'cancelled')
+            ) as current_participants,
+            a.registration_deadline,
+            NULLIF(f.name, '') as faculty_name
+        FROM activities a
+        LEFT JOIN faculties f ON a.faculty_id = f.id
+        WHERE a.visibility = 'public' AND a.status IN ('published', 'ongoing') AND a.deleted_at IS NULL
+        ORDER BY a.start_date ASC, a.start_time_only ASC, a.id ASC
+        LIMIT $1 OFFSET $2
+        "#,
+        time_zone.start_sql("a"),
+        time_zone.end_sql("a")
+    ))
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(&session_state.db_pool)
+    .await;
+
+    let total_count = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM activities
+         WHERE visibility = 'public' AND status IN ('published', 'ongoing') AND deleted_at IS NULL",
+    )
+    .fetch_one(&session_state.db_pool)
+    .await;
+
+    match (activities, total_count) {
+        (Ok(activities), Ok(total_count)) => {
+            let response = json!({
+                "status": "success",
+                "data": {
+                    "activities": activities,
+                    "total_count": total_count,
+                    "limit": limit,
+                    "offset": offset
+                },
+                "message": "Activities retrieved successfully"
             });
-            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+            Ok(Json(response))
+        }
+        _ => {
+            Err(ApiError::internal("Failed to retrieve activities"))
         }
     }
 }
@@ -275,72 +578,72 @@ pub async fn get_activity(
     State(session_state): State<SessionState>,
     user: SessionUser,
     Path(activity_id): Path<Uuid>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    let query_result = sqlx::query(
+) -> ApiResult<Json<Value>> {
+    let visible_faculty_ids = get_accessible_faculty_ids(&user);
+    let visibility_clause = match visible_faculty_ids {
+        Some(_) => format!("AND {}", visibility_condition(2, 3)),
+        None => String::new(),
+    };
+
+    let time_zone = session_state.app_config.activity_time_zone();
+    let query = format!(
         r#"
         SELECT 
             a.id,
             a.title,
             a.description,
             a.location,
-            ((a.start_date::timestamp + a.start_time_only) AT TIME ZONE 'UTC') as start_time,
-            ((a.end_date::timestamp + a.end_time_only) AT TIME ZONE 'UTC') as end_time,
+            {start_time} as start_time,
+            {end_time} as end_time,
             a.max_participants,
+            a.registration_deadline,
+            a.publish_at,
             a.status,
-            a.activity_type,
+            a.visibility,
+            a.activity_type::text as activity_type,
             a.faculty_id,
             a.created_by,
             a.created_at,
             a.updated_at,
-            f.name as faculty_name,
-            u.first_name || ' ' || u.last_name as created_by_name,
-            COALESCE(COUNT(p.id), 0) as current_participants,
-            CASE WHEN up.id IS NOT NULL THEN true ELSE false END as is_registered,
+            NULLIF(f.name, '') as faculty_name,
+            COALESCE(u.first_name || ' ' || u.last_name, 'Unknown') as created_by_name,
+            COUNT(p.id) FILTER (WHERE p.status NOT IN ('waitlisted', 'cancelled')) as current_participants,
+            up.id IS NOT NULL as is_registered,
             up.status as user_participation_status
         FROM activities a
         LEFT JOIN faculties f ON a.faculty_id = f.id
         LEFT JOIN users u ON a.created_by = u.id
         LEFT JOIN participations p ON a.id = p.activity_id
         LEFT JOIN participations up ON a.id = up.activity_id AND up.user_id = $2
-        WHERE a.id = $1
-        GROUP BY a.id, a.title, a.description, a.location, a.start_date, a.end_date, a.start_time_only, a.end_time_only, a.max_participants, a.status, a.activity_type, a.faculty_id, a.created_by, a.created_at, a.updated_at, f.name, u.first_name, u.last_name, up.id, up.status
-        "#
-    )
-    .bind(&activity_id)
-    .bind(&user.user_id)
-    .fetch_one(&session_state.db_pool)
-    .await;
+        WHERE a.id = $1 AND a.deleted_at IS NULL {visibility_clause}
+        GROUP BY a.id, a.title, a.description, a.location, a.start_date, a.end_date, a.start_time_only, a.end_time_only, a.max_participants, a.registration_deadline, a.publish_at, a.status, a.visibility, a.activity_type, a.faculty_id, a.created_by, a.created_at, a.updated_at, f.name, u.first_name, u.last_name, up.id, up.status
+        "#,
+        start_time = time_zone.start_sql("a"),
+        end_time = time_zone.end_sql("a"),
+        visibility_clause = visibility_clause
+    );
+
+    // Activities the user may not see are reported as not found
+    let mut query_builder = sqlx::query_as::<_, ActivityWithDetails>(&query)
+        .bind(activity_id)
+        .bind(user.user_id);
+    if let Some(faculty_ids) = &visible_faculty_ids {
+        query_builder = query_builder.bind(faculty_ids);
+    }
+    let query_result = query_builder.fetch_one(&session_state.db_pool).await;
 
     match query_result {
-        Ok(row) => {
-            let activity_detail = ActivityWithDetails {
-                id: row.get("id"),
-                title: row.get("title"),
-                description: row.get("description"),
-                location: row.get("location"),
-                start_time: row.get("start_time"),
-                end_time: row.get("end_time"),
-                max_participants: row.get("max_participants"),
-                current_participants: row.get::<i64, _>("current_participants"),
-                status: row.get("status"),
-                activity_type: row.get::<Option<String>, _>("activity_type"),
-                faculty_id: row.get("faculty_id"),
-                faculty_name: row
-                    .get::<Option<String>, _>("faculty_name")
-                    .filter(|s| !s.is_empty()),
-                created_by: row.get("created_by"),
-                created_by_name: row
-                    .get::<Option<String>, _>("created_by_name")
-                    .unwrap_or_else(|| "Unknown".to_string()),
-                created_at: row
-                    .get::<Option<DateTime<Utc>>, _>("created_at")
-                    .unwrap_or_else(|| Utc::now()),
-                updated_at: row
-                    .get::<Option<DateTime<Utc>>, _>("updated_at")
-                    .unwrap_or_else(|| Utc::now()),
-                is_registered: row.get::<Option<bool>, _>("is_registered").unwrap_or(false),
-                user_participation_status: row.get("user_participation_status"),
-            };
+        Ok(mut activity_detail) => {
+            let activity = &activity_detail.activity;
+            if can_manage_activity(&user, activity.created_by, activity.faculty_id) {
+                activity_detail.average_rating = sqlx::query_scalar(
+                    "SELECT AVG(rating)::float8 FROM activity_feedback WHERE activity_id = $1",
+                )
+                .bind(activity_id)
+                .fetch_one(&session_state.db_pool)
+                .await
+                .map_err(|_| ApiError::internal("Failed to retrieve activity rating"))?;
+            }
 
             let response = json!({
                 "status": "success",
@@ -351,18 +654,10 @@ pub async fn get_activity(
             Ok(Json(response))
         }
         Err(sqlx::Error::RowNotFound) => {
-            let error_response = json!({
-                "status": "error",
-                "message": "Activity not found"
-            });
-            Err((StatusCode::NOT_FOUND, Json(error_response)))
+            Err(ApiError::not_found("activity_not_found", "Activity not found"))
         }
         Err(_) => {
-            let error_response = json!({
-                "status": "error",
-                "message": "Failed to retrieve activity"
-            });
-            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+            Err(ApiError::internal("Failed to retrieve activity"))
         }
     }
 }
@@ -370,87 +665,78 @@ pub async fn get_activity(
 /// Create new activity
 pub async fn create_activity(
     State(session_state): State<SessionState>,
-    user: SessionUser,
-    Json(request): Json<CreateActivityRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    // Check if user has permission to create activities
-    if !user
-        .permissions
-        .iter()
-        .any(|p| p.contains("ManageActivities") || p.contains("CreateActivity"))
-    {
-        let error_response = json!({
-            "status": "error",
-            "message": "Access denied: You don't have permission to create activities"
-        });
-        return Err((StatusCode::FORBIDDEN, Json(error_response)));
-    }
-
+    RequirePermission { session_user: user, .. }: RequirePermission<CanCreateActivity>,
+    ApiJson(request): ApiJson<CreateActivityRequest>,
+) -> ApiResult<Json<Value>> {
     // Validate time range
     if request.start_time >= request.end_time {
-        let error_response = json!({
-            "status": "error",
-            "message": "Start time must be before end time"
-        });
-        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+        return Err(ApiError::bad_request("invalid_time_range", "Start time must be before end time"));
+    }
+
+    let visibility = request.visibility.unwrap_or_default();
+    if visibility == ActivityVisibility::FacultyOnly && request.faculty_id.is_none() {
+        return Err(ApiError::bad_request("faculty_required", "FacultyOnly visibility requires a faculty_id"));
+    }
+
+    if request.registration_deadline.is_some_and(|deadline| deadline > request.end_time) {
+        return Err(ApiError::bad_request("invalid_registration_deadline", "Registration deadline must not be after the activity end time"));
+    }
+
+    if request.publish_at.is_some_and(|publish_at| publish_at >= request.start_time) {
+        return Err(ApiError::bad_request("invalid_publish_at", "Publish time must be before the activity start time"));
     }
 
-    let start_naive = request.start_time.naive_utc();
-    let end_naive = request.end_time.naive_utc();
-    let create_result = sqlx::query(
+    let time_zone = session_state.app_config.activity_time_zone();
+    let create_result = sqlx::query_as::<_, ActivityRecord>(&format!(
         r#"
         INSERT INTO activities (
             title, description, location, max_participants, faculty_id, created_by,
-            start_date, end_date, start_time_only, end_time_only
+            start_date, end_date, start_time_only, end_time_only, allow_waitlist,
+            registration_deadline, visibility, publish_at
         )
-        VALUES ($1, $2, $3, $4, $5, $6, $7::date, $8::date, $9::time, $10::time)
+        VALUES ($1, $2, $3, $4, $5, $6, {start_local}::date, {end_local}::date,
+            {start_local}::time, {end_local}::time, $9, $10, $11, $12)
         RETURNING id, title, description, location,
-          ((start_date::timestamp + start_time_only) AT TIME ZONE 'UTC') as start_time,
-          ((end_date::timestamp + end_time_only) AT TIME ZONE 'UTC') as end_time,
-          max_participants, status, faculty_id, created_by, created_at, updated_at
-        "#
-    )
+          {start_time} as start_time,
+          {end_time} as end_time,
+          max_participants, registration_deadline, publish_at, status, visibility, activity_type::text as activity_type,
+          faculty_id, created_by, created_at, updated_at
+        "#,
+        start_local = time_zone.local_sql("$7::timestamptz"),
+        end_local = time_zone.local_sql("$8::timestamptz"),
+        start_time = time_zone.start_sql(""),
+        end_time = time_zone.end_sql("")
+    ))
     .bind(&request.title)
     .bind(&request.description)
     .bind(&request.location)
     .bind(request.max_participants)
     .bind(request.faculty_id)
     .bind(user.user_id)
-    .bind(start_naive.date())
-    .bind(end_naive.date())
-    .bind(start_naive.time())
-    .bind(end_naive.time())
+    .bind(request.start_time)
+    .bind(request.end_time)
+    .bind(request.allow_waitlist.unwrap_or(false))
+    .bind(request.registration_deadline)
+    .bind(visibility)
+    .bind(request.publish_at)
     .fetch_one(&session_state.db_pool)
     .await;
 
     match create_result {
-        Ok(row) => {
+        Ok(activity) => {
+            invalidate_dashboard_cache(&session_state).await;
+            enqueue_webhook_event(&session_state, WebhookEvent::ActivityCreated, json!(activity)).await;
+
             let response = json!({
                 "status": "success",
-                "data": {
-                    "id": row.get::<Uuid, _>("id"),
-                    "title": row.get::<String, _>("title"),
-                    "description": row.get::<String, _>("description"),
-                    "location": row.get::<String, _>("location"),
-                    "start_time": row.get::<DateTime<Utc>, _>("start_time"),
-                    "end_time": row.get::<DateTime<Utc>, _>("end_time"),
-                    "max_participants": row.get::<Option<i32>, _>("max_participants"),
-                    "status": row.get::<ActivityStatus, _>("status"),
-                    "faculty_id": row.get::<Option<Uuid>, _>("faculty_id"),
-                    "created_by": row.get::<Uuid, _>("created_by"),
-                    "created_at": row.get::<DateTime<Utc>, _>("created_at"),
-                    "updated_at": row.get::<DateTime<Utc>, _>("updated_at")
-                },
+                "data": activity,
                 "message": "Activity created successfully"
             });
             Ok(Json(response))
         }
         Err(e) => {
-            let error_response = json!({
-                "status": "error",
-                "message": format!("Failed to create activity: {}", e)
-            });
-            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+            tracing::error!("Failed to create activity: {}", e);
+            Err(ApiError::internal("Failed to create activity"))
         }
     }
 }
@@ -460,57 +746,65 @@ pub async fn update_activity(
     State(session_state): State<SessionState>,
     user: SessionUser,
     Path(activity_id): Path<Uuid>,
-    Json(request): Json<UpdateActivityRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    ApiJson(request): ApiJson<UpdateActivityRequest>,
+) -> ApiResult<Json<Value>> {
+    let time_zone = session_state.app_config.activity_time_zone();
+
     // Check if user has permission to update activities or is the creator
-    let activity_check = sqlx::query("SELECT created_by FROM activities WHERE id = $1")
-        .bind(&activity_id)
-        .fetch_one(&session_state.db_pool)
-        .await;
+    let activity_check = sqlx::query(&format!(
+        "SELECT created_by, faculty_id, publish_at, {} as start_time
+         FROM activities WHERE id = $1 AND deleted_at IS NULL",
+        time_zone.start_sql("")
+    ))
+    .bind(activity_id)
+    .fetch_one(&session_state.db_pool)
+    .await;
 
-    let can_update = match activity_check {
-        Ok(activity) => {
+    let (can_update, current_faculty_id, current_publish_at, current_start_time) = match activity_check {
+        Ok(activity) => (
             activity.get::<Uuid, _>("created_by") == user.user_id
-                || user
-                    .permissions
-                    .iter()
-                    .any(|p| p.contains("ManageActivities"))
-        }
+                || user.has_permission(&Permission::ManageActivities),
+            activity.get::<Option<Uuid>, _>("faculty_id"),
+            activity.get::<Option<DateTime<Utc>>, _>("publish_at"),
+            activity.get::<DateTime<Utc>, _>("start_time"),
+        ),
         Err(sqlx::Error::RowNotFound) => {
-            let error_response = json!({
-                "status": "error",
-                "message": "Activity not found"
-            });
-            return Err((StatusCode::NOT_FOUND, Json(error_response)));
+            return Err(ApiError::not_found("activity_not_found", "Activity not found"));
         }
         Err(_) => {
-            let error_response = json!({
-                "status": "error",
-                "message": "Failed to check activity"
-            });
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+            return Err(ApiError::internal("Failed to check activity"));
         }
     };
 
     if !can_update {
-        let error_response = json!({
-            "status": "error",
-            "message": "Access denied: You can only update your own activities or need ManageActivities permission"
-        });
-        return Err((StatusCode::FORBIDDEN, Json(error_response)));
+        return Err(ApiError::forbidden("permission_denied", "Access denied: You can only update your own activities or need ManageActivities permission"));
     }
 
     // Validate time range if both times are provided
     if let (Some(start_time), Some(end_time)) = (&request.start_time, &request.end_time) {
         if start_time >= end_time {
-            let error_response = json!({
-                "status": "error",
-                "message": "Start time must be before end time"
-            });
-            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+            return Err(ApiError::bad_request("invalid_time_range", "Start time must be before end time"));
+        }
+    }
+
+    if request.visibility == Some(ActivityVisibility::FacultyOnly)
+        && request.faculty_id.or(current_faculty_id).is_none()
+    {
+        return Err(ApiError::bad_request("faculty_required", "FacultyOnly visibility requires a faculty_id"));
+    }
+
+    if let (Some(deadline), Some(end_time)) = (&request.registration_deadline, &request.end_time) {
+        if deadline > end_time {
+            return Err(ApiError::bad_request("invalid_registration_deadline", "Registration deadline must not be after the activity end time"));
         }
     }
 
+    // Check the schedule against whichever of publish_at/start_time isn't being changed
+    let publish_at = request.publish_at.or(current_publish_at);
+    if publish_at.is_some_and(|publish_at| publish_at >= request.start_time.unwrap_or(current_start_time)) {
+        return Err(ApiError::bad_request("invalid_publish_at", "Publish time must be before the activity start time"));
+    }
+
     // Build dynamic update query
     let mut query = "UPDATE activities SET updated_at = NOW()".to_string();
     let mut param_count = 1;
@@ -531,13 +825,18 @@ pub async fn update_activity(
     }
 
     if request.start_time.is_some() {
-        query.push_str(&format!(", start_date = ${}, start_time_only = ${}", param_count, param_count + 1));
-        param_count += 2;
+        let start_local = time_zone.local_sql(&format!("${}::timestamptz", param_count));
+        query.push_str(&format!(
+            ", start_date = {}::date, start_time_only = {}::time",
+            start_local, start_local
+        ));
+        param_count += 1;
     }
 
     if request.end_time.is_some() {
-        query.push_str(&format!(", end_date = ${}, end_time_only = ${}", param_count, param_count + 1));
-        param_count += 2;
+        let end_local = time_zone.local_sql(&format!("${}::timestamptz", param_count));
+        query.push_str(&format!(", end_date = {}::date, end_time_only = {}::time", end_local, end_local));
+        param_count += 1;
     }
 
     if request.max_participants.is_some() {
@@ -555,12 +854,41 @@ pub async fn update_activity(
         param_count += 1;
     }
 
+    if request.allow_waitlist.is_some() {
+        query.push_str(&format!(", allow_waitlist = ${}", param_count));
+        param_count += 1;
+    }
+
+    if request.registration_deadline.is_some() {
+        query.push_str(&format!(", registration_deadline = ${}", param_count));
+        param_count += 1;
+    }
+
+    if request.visibility.is_some() {
+        query.push_str(&format!(", visibility = ${}", param_count));
+        param_count += 1;
+    }
+
+    if request.publish_at.is_some() {
+        query.push_str(&format!(", publish_at = ${}", param_count));
+        param_count += 1;
+    }
+
     // department removed
 
-    query.push_str(&format!(" WHERE id = ${} RETURNING id, title, description, location,
-        ((start_date::timestamp + start_time_only) AT TIME ZONE 'UTC') as start_time,
-        ((end_date::timestamp + end_time_only) AT TIME ZONE 'UTC') as end_time,
-        max_participants, status, faculty_id, created_by, created_at, updated_at", param_count));
+    query.push_str(&format!(" WHERE id = ${}", param_count));
+    param_count += 1;
+
+    if request.expected_updated_at.is_some() {
+        query.push_str(&format!(" AND updated_at = ${}", param_count));
+    }
+
+    query.push_str(&format!(
+        " RETURNING id, title, description, location, {} as start_time, {} as end_time,
+        max_participants, allow_waitlist, registration_deadline, publish_at, status, visibility, faculty_id, created_by, created_at, updated_at",
+        time_zone.start_sql(""),
+        time_zone.end_sql("")
+    ));
 
     // Execute query with proper parameter binding
     let mut query_builder = sqlx::query(&query);
@@ -575,12 +903,10 @@ pub async fn update_activity(
         query_builder = query_builder.bind(location);
     }
     if let Some(start_time) = request.start_time {
-        let st = start_time.naive_utc();
-        query_builder = query_builder.bind(st.date()).bind(st.time());
+        query_builder = query_builder.bind(start_time);
     }
     if let Some(end_time) = request.end_time {
-        let et = end_time.naive_utc();
-        query_builder = query_builder.bind(et.date()).bind(et.time());
+        query_builder = query_builder.bind(end_time);
     }
     if let Some(max_participants) = request.max_participants {
         query_builder = query_builder.bind(max_participants);
@@ -591,7 +917,22 @@ pub async fn update_activity(
     if let Some(faculty_id) = request.faculty_id {
         query_builder = query_builder.bind(faculty_id);
     }
+    if let Some(allow_waitlist) = request.allow_waitlist {
+        query_builder = query_builder.bind(allow_waitlist);
+    }
+    if let Some(registration_deadline) = request.registration_deadline {
+        query_builder = query_builder.bind(registration_deadline);
+    }
+    if let Some(visibility) = request.visibility {
+        query_builder = query_builder.bind(visibility);
+    }
+    if let Some(publish_at) = request.publish_at {
+        query_builder = query_builder.bind(publish_at);
+    }
     query_builder = query_builder.bind(activity_id);
+    if let Some(expected_updated_at) = request.expected_updated_at {
+        query_builder = query_builder.bind(expected_updated_at);
+    }
 
     match query_builder.fetch_one(&session_state.db_pool).await {
         Ok(row) => {
@@ -605,7 +946,11 @@ pub async fn update_activity(
                     "start_time": row.get::<DateTime<Utc>, _>("start_time"),
                     "end_time": row.get::<DateTime<Utc>, _>("end_time"),
                     "max_participants": row.get::<Option<i32>, _>("max_participants"),
+                    "allow_waitlist": row.get::<bool, _>("allow_waitlist"),
+                    "registration_deadline": row.get::<Option<DateTime<Utc>>, _>("registration_deadline"),
+                    "publish_at": row.get::<Option<DateTime<Utc>>, _>("publish_at"),
                     "status": row.get::<ActivityStatus, _>("status"),
+                    "visibility": row.get::<ActivityVisibility, _>("visibility"),
                     "faculty_id": row.get::<Option<Uuid>, _>("faculty_id"),
                     "created_by": row.get::<Uuid, _>("created_by"),
                     "created_at": row.get::<DateTime<Utc>, _>("created_at"),
@@ -613,14 +958,29 @@ pub async fn update_activity(
                 },
                 "message": "Activity updated successfully"
             });
+            enqueue_webhook_event(&session_state, WebhookEvent::ActivityUpdated, response["data"].clone()).await;
             Ok(Json(response))
         }
+        Err(sqlx::Error::RowNotFound) if request.expected_updated_at.is_some() => {
+            // Someone else updated the activity since the client loaded it
+            let current_updated_at = sqlx::query_scalar::<_, DateTime<Utc>>(
+                "SELECT updated_at FROM activities WHERE id = $1",
+            )
+            .bind(activity_id)
+            .fetch_optional(&session_state.db_pool)
+            .await
+            .ok()
+            .flatten();
+
+            Err(ApiError::conflict(
+                "stale_update",
+                "Activity was modified by someone else. Reload and try again.",
+            )
+            .with("current_updated_at", json!(current_updated_at)))
+        }
         Err(e) => {
-            let error_response = json!({
-                "status": "error",
-                "message": format!("Failed to update activity: {}", e)
-            });
-            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+            tracing::error!("Failed to update activity: {}", e);
+            Err(ApiError::internal("Failed to update activity"))
         }
     }
 }
@@ -630,9 +990,9 @@ pub async fn delete_activity(
     State(session_state): State<SessionState>,
     user: SessionUser,
     Path(activity_id): Path<Uuid>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> ApiResult<Json<Value>> {
     // Check if user has permission to delete activities or is the creator
-    let activity_check = sqlx::query("SELECT created_by FROM activities WHERE id = $1")
+    let activity_check = sqlx::query("SELECT created_by FROM activities WHERE id = $1 AND deleted_at IS NULL")
         .bind(&activity_id)
         .fetch_one(&session_state.db_pool)
         .await;
@@ -640,49 +1000,34 @@ pub async fn delete_activity(
     let can_delete = match activity_check {
         Ok(activity) => {
             activity.get::<Uuid, _>("created_by") == user.user_id
-                || user
-                    .permissions
-                    .iter()
-                    .any(|p| p.contains("ManageActivities"))
+                || user.has_permission(&Permission::ManageActivities)
         }
         Err(sqlx::Error::RowNotFound) => {
-            let error_response = json!({
-                "status": "error",
-                "message": "Activity not found"
-            });
-            return Err((StatusCode::NOT_FOUND, Json(error_response)));
+            return Err(ApiError::not_found("activity_not_found", "Activity not found"));
         }
         Err(_) => {
-            let error_response = json!({
-                "status": "error",
-                "message": "Failed to check activity"
-            });
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+            return Err(ApiError::internal("Failed to check activity"));
         }
     };
 
     if !can_delete {
-        let error_response = json!({
-            "status": "error",
-            "message": "Access denied: You can only delete your own activities or need ManageActivities permission"
-        });
-        return Err((StatusCode::FORBIDDEN, Json(error_response)));
+        return Err(ApiError::forbidden("permission_denied", "Access denied: You can only delete your own activities or need ManageActivities permission"));
     }
 
-    let delete_result = sqlx::query("DELETE FROM activities WHERE id = $1")
-        .bind(activity_id)
-        .execute(&session_state.db_pool)
-        .await;
+    // Soft delete: the row and its participations stay so the activity can be restored
+    let delete_result = sqlx::query(
+        "UPDATE activities SET deleted_at = NOW(), updated_at = NOW() WHERE id = $1 AND deleted_at IS NULL",
+    )
+    .bind(activity_id)
+    .execute(&session_state.db_pool)
+    .await;
 
     match delete_result {
         Ok(result) => {
             if result.rows_affected() == 0 {
-                let error_response = json!({
-                    "status": "error",
-                    "message": "Activity not found"
-                });
-                Err((StatusCode::NOT_FOUND, Json(error_response)))
+                Err(ApiError::not_found("activity_not_found", "Activity not found"))
             } else {
+                invalidate_dashboard_cache(&session_state).await;
                 let response = json!({
                     "status": "success",
                     "message": "Activity deleted successfully"
@@ -691,67 +1036,355 @@ pub async fn delete_activity(
             }
         }
         Err(e) => {
-            let error_response = json!({
-                "status": "error",
-                "message": format!("Failed to delete activity: {}", e)
-            });
-            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+            tracing::error!("Failed to delete activity: {}", e);
+            Err(ApiError::internal("Failed to delete activity"))
         }
     }
 }
 
-/// Get activity participations
-pub async fn get_activity_participations(
+/// Restore a soft-deleted activity - creator or ManageActivities permission
+pub async fn restore_activity(
     State(session_state): State<SessionState>,
     user: SessionUser,
     Path(activity_id): Path<Uuid>,
-    Query(params): Query<HashMap<String, String>>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    // Check if user can view participations (activity creator or admin)
-    let activity_check = sqlx::query("SELECT created_by FROM activities WHERE id = $1")
-        .bind(&activity_id)
-        .fetch_one(&session_state.db_pool)
-        .await;
+) -> ApiResult<Json<Value>> {
+    let activity = sqlx::query("SELECT created_by, deleted_at FROM activities WHERE id = $1")
+        .bind(activity_id)
+        .fetch_optional(&session_state.db_pool)
+        .await
+        .map_err(|_| ApiError::internal("Failed to check activity"))?
+        .ok_or_else(|| ApiError::not_found("activity_not_found", "Activity not found"))?;
 
-    let can_view = match activity_check {
-        Ok(activity) => {
-            activity.get::<Uuid, _>("created_by") == user.user_id
-                || user
-                    .permissions
-                    .iter()
-                    .any(|p| p.contains("ManageActivities") || p.contains("ViewParticipations"))
-        }
-        Err(sqlx::Error::RowNotFound) => {
-            let error_response = json!({
-                "status": "error",
-                "message": "Activity not found"
-            });
-            return Err((StatusCode::NOT_FOUND, Json(error_response)));
-        }
-        Err(_) => {
-            let error_response = json!({
-                "status": "error",
-                "message": "Failed to check activity"
-            });
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
-        }
-    };
+    if activity.get::<Uuid, _>("created_by") != user.user_id
+        && !user.has_permission(&Permission::ManageActivities)
+    {
+        return Err(ApiError::forbidden("permission_denied", "Access denied: You can only restore your own activities or need ManageActivities permission"));
+    }
+
+    if activity.get::<Option<DateTime<Utc>>, _>("deleted_at").is_none() {
+        return Err(ApiError::conflict("activity_not_deleted", "Activity is not deleted"));
+    }
+
+    let restored = sqlx::query(
+        "UPDATE activities SET deleted_at = NULL, updated_at = NOW() WHERE id = $1 AND deleted_at IS NOT NULL",
+    )
+    .bind(activity_id)
+    .execute(&session_state.db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to restore activity: {}", e);
+        ApiError::internal("Failed to restore activity")
+    })?;
+
+    // Restored concurrently by someone else between the check and the update
+    if restored.rows_affected() == 0 {
+        return Err(ApiError::conflict("activity_not_deleted", "Activity is not deleted"));
+    }
+
+    invalidate_dashboard_cache(&session_state).await;
+
+    Ok(Json(json!({
+        "status": "success",
+        "message": "Activity restored successfully"
+    })))
+}
+
+/// Clone an activity into a new draft without participations - creator or ManageActivities permission
+pub async fn clone_activity(
+    State(session_state): State<SessionState>,
+    user: SessionUser,
+    Path(activity_id): Path<Uuid>,
+    ApiJson(request): ApiJson<CloneActivityRequest>,
+) -> ApiResult<Json<Value>> {
+    let source = sqlx::query(
+        "SELECT created_by, start_date, end_date, start_time_only, end_time_only
+         FROM activities WHERE id = $1 AND deleted_at IS NULL",
+    )
+    .bind(activity_id)
+    .fetch_optional(&session_state.db_pool)
+    .await
+    .map_err(|_| ApiError::internal("Failed to check activity"))?
+    .ok_or_else(|| ApiError::not_found("activity_not_found", "Activity not found"))?;
+
+    if source.get::<Uuid, _>("created_by") != user.user_id
+        && !user.has_permission(&Permission::ManageActivities)
+    {
+        return Err(ApiError::forbidden("permission_denied", "Access denied: You can only clone your own activities or need ManageActivities permission"));
+    }
+
+    let source_start_date: NaiveDate = source.get("start_date");
+    let source_end_date: NaiveDate = source.get("end_date");
+    let start_date = request.start_date.unwrap_or(source_start_date);
+    let end_date = request
+        .end_date
+        .unwrap_or_else(|| start_date + (source_end_date - source_start_date));
+
+    let start = start_date.and_time(source.get("start_time_only"));
+    let end = end_date.and_time(source.get("end_time_only"));
+    if start >= end {
+        return Err(ApiError::bad_request("invalid_time_range", "Start time must be before end time"));
+    }
+
+    // Scheduling and per-run fields (status, deadline, publish_at, cancellation) are not copied
+    let time_zone = session_state.app_config.activity_time_zone();
+    let clone_result = sqlx::query_as::<_, ActivityRecord>(&format!(
+        r#"
+        INSERT INTO activities (
+            title, description, location, activity_type, academic_year, organizer,
+            eligible_faculties, start_date, end_date, start_time_only, end_time_only, hours,
+            max_participants, faculty_id, created_by, allow_waitlist, visibility,
+            checkin_opens_before_minutes, checkin_closes_after_minutes
+        )
+        SELECT title || ' (copy)', description, location, activity_type, academic_year, organizer,
+            eligible_faculties, $2, $3, start_time_only, end_time_only, hours,
+            max_participants, faculty_id, $4, allow_waitlist, visibility,
+            checkin_opens_before_minutes, checkin_closes_after_minutes
+        FROM activities WHERE id = $1
+        RETURNING id, title, description, location,
+          {} as start_time,
+          {} as end_time,
+          max_participants, registration_deadline, publish_at, status, visibility, activity_type::text as activity_type,
+          faculty_id, created_by, created_at, updated_at
+        "#,
+        time_zone.start_sql(""),
+        time_zone.end_sql("")
+    ))
+    .bind(activity_id)
+    .bind(start_date)
+    .bind(end_date)
+    .bind(user.user_id)
+    .fetch_one(&session_state.db_pool)
+    .await;
+
+    match clone_result {
+        Ok(activity) => {
+            invalidate_dashboard_cache(&session_state).await;
+
+            let response = json!({
+                "status": "success",
+                "data": activity,
+                "message": "Activity cloned successfully"
+            });
+            Ok(Json(response))
+        }
+        Err(e) => {
+            tracing::error!("Failed to clone activity: {}", e);
+            Err(ApiError::internal("Failed to clone activity"))
+        }
+    }
+}
+
+/// Cancel activity - keeps participations (marked cancelled) and notifies participants
+pub async fn cancel_activity(
+    State(session_state): State<SessionState>,
+    user: SessionUser,
+    Path(activity_id): Path<Uuid>,
+    ApiJson(request): ApiJson<CancelActivityRequest>,
+) -> ApiResult<Json<Value>> {
+    let reason = request.reason.trim();
+    if reason.is_empty() {
+        return Err(ApiError::bad_request("reason_required", "Cancellation reason is required"));
+    }
+
+    let mut tx = session_state
+        .db_pool
+        .begin()
+        .await
+        .map_err(|_| ApiError::internal("Failed to start transaction"))?;
+
+    let activity = sqlx::query("SELECT title, status, created_by, faculty_id FROM activities WHERE id = $1 AND deleted_at IS NULL FOR UPDATE")
+        .bind(activity_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|_| ApiError::internal("Failed to check activity"))?;
+
+    let Some(activity) = activity else {
+        return Err(ApiError::not_found("activity_not_found", "Activity not found"));
+    };
+
+    let can_cancel = activity.get::<Uuid, _>("created_by") == user.user_id
+        || user.has_permission(&Permission::ManageActivities);
+    if !can_cancel {
+        return Err(ApiError::forbidden("permission_denied", "Access denied: You can only cancel your own activities or need ManageActivities permission"));
+    }
+
+    match activity.get::<ActivityStatus, _>("status") {
+        ActivityStatus::Completed => {
+            return Err(ApiError::bad_request("activity_completed", "Cannot cancel an activity that has already completed"));
+        }
+        ActivityStatus::Cancelled => {
+            return Err(ApiError::conflict("activity_already_cancelled", "Activity is already cancelled"));
+        }
+        _ => {}
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE activities
+        SET status = 'cancelled', cancellation_reason = $2, cancelled_at = NOW(),
+            cancelled_by = $3, updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(activity_id)
+    .bind(reason)
+    .bind(user.user_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|_| ApiError::internal("Failed to cancel activity"))?;
+
+    // Mark open participations cancelled and record each change in participation_events
+    let affected_user_ids: Vec<Uuid> = sqlx::query_scalar(
+        r#"
+        WITH affected AS (
+            SELECT id, user_id, status FROM participations
+            WHERE activity_id = $1 AND status IN ('registered', 'checked_in')
+            FOR UPDATE
+        ), updated AS (
+            UPDATE participations p SET status = 'cancelled'
+            FROM affected WHERE p.id = affected.id
+            RETURNING p.id
+        )
+        INSERT INTO participation_events
+            (participation_id, activity_id, user_id, from_status, to_status, source, reason, changed_by)
+        SELECT affected.id, $1, affected.user_id, affected.status, 'cancelled', 'cancellation', $2, $3
+        FROM affected JOIN updated ON updated.id = affected.id
+        RETURNING user_id
+        "#,
+    )
+    .bind(activity_id)
+    .bind(reason)
+    .bind(user.user_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|_| ApiError::internal("Failed to cancel participations"))?;
+
+    tx.commit()
+        .await
+        .map_err(|_| ApiError::internal("Failed to cancel activity"))?;
+
+    // Notify participants after commit (unless they opted out of announcement emails);
+    // email failures must not undo the cancellation
+    let activity_title: String = activity.get("title");
+    let recipients = sqlx::query(
+        r#"
+        SELECT u.email, u.first_name, u.last_name FROM users u
+        WHERE u.id = ANY($1)
+          AND NOT EXISTS (
+              SELECT 1 FROM notification_preferences np
+              WHERE np.user_id = u.id AND np.category = 'announcements' AND NOT np.email_enabled
+          )
+        "#,
+    )
+    .bind(&affected_user_ids)
+    .fetch_all(&session_state.db_pool)
+    .await
+    .unwrap_or_default();
+
+    // Inbox entries and optional LINE notices go out alongside the emails
+    let announcement = format!("Activity cancelled: \"{}\"\nReason: {}", activity_title, reason);
+    store_user_notifications(
+        &session_state.db_pool,
+        &affected_user_ids,
+        NotificationCategory::Announcements,
+        &announcement,
+        serde_json::json!({ "activity_id": activity_id }),
+    )
+    .await;
+    for user_id in &affected_user_ids {
+        notify(&session_state, NotificationTarget::User(*user_id), NotificationCategory::Announcements, announcement.clone());
+    }
+    if let Some(faculty_id) = activity.get::<Option<Uuid>, _>("faculty_id") {
+        notify(&session_state, NotificationTarget::Faculty(faculty_id), NotificationCategory::Announcements, announcement);
+    }
+
+    let email_service = EmailService::new(session_state.clone());
+    let mut notified = 0;
+    for recipient in &recipients {
+        let user_name = format!(
+            "{} {}",
+            recipient.get::<String, _>("first_name"),
+            recipient.get::<String, _>("last_name")
+        );
+        let (subject, body_text, body_html) =
+            EmailTemplate::activity_cancelled_template(&user_name, &activity_title, reason).build();
+
+        match email_service
+            .queue_email(
+                &recipient.get::<String, _>("email"),
+                Some(&user_name),
+                &subject,
+                &body_text,
+                body_html.as_deref(),
+                5,
+                Some(json!({ "type": "activity_cancelled", "activity_id": activity_id })),
+            )
+            .await
+        {
+            Ok(_) => notified += 1,
+            Err(e) => tracing::error!("Failed to queue cancellation email: {}", e),
+        }
+    }
+
+    let response = json!({
+        "status": "success",
+        "data": {
+            "activity_id": activity_id,
+            "status": ActivityStatus::Cancelled,
+            "cancellation_reason": reason,
+            "cancelled_participations": affected_user_ids.len(),
+            "notified_participants": notified
+        },
+        "message": "Activity cancelled successfully"
+    });
+    enqueue_webhook_event(&session_state, WebhookEvent::ActivityCancelled, response["data"].clone()).await;
+
+    Ok(Json(response))
+}
+
+/// Get activity participations (`?format=csv` or `?format=xlsx` for a download)
+pub async fn get_activity_participations(
+    State(session_state): State<SessionState>,
+    user: SessionUser,
+    Path(activity_id): Path<Uuid>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ApiResult<Response> {
+    // Check if user can view participations (activity creator or admin)
+    let activity_check = sqlx::query("SELECT created_by FROM activities WHERE id = $1 AND deleted_at IS NULL")
+        .bind(&activity_id)
+        .fetch_one(&session_state.db_pool)
+        .await;
+
+    let can_view = match activity_check {
+        Ok(activity) => {
+            activity.get::<Uuid, _>("created_by") == user.user_id
+                || user.has_permission(&Permission::ManageActivities)
+                || user.has_permission(&Permission::ViewParticipations)
+        }
+        Err(sqlx::Error::RowNotFound) => {
+            return Err(ApiError::not_found("activity_not_found", "Activity not found"));
+        }
+        Err(_) => {
+            return Err(ApiError::internal("Failed to check activity"));
+        }
+    };
 
     if !can_view {
-        let error_response = json!({
-            "status": "error",
-            "message": "Access denied: You don't have permission to view participations"
-        });
-        return Err((StatusCode::FORBIDDEN, Json(error_response)));
+        return Err(ApiError::forbidden("permission_denied", "Access denied: You don't have permission to view participations"));
     }
 
-    let status_filter = params.get("status");
+    let status_filter = params.get("status").cloned();
+    let format = params.get("format").map(String::as_str).unwrap_or("json");
+    if !matches!(format, "json" | "csv" | "xlsx") {
+        return Err(ApiError::bad_request("unsupported_format", "Invalid format. Supported formats: json, csv, xlsx"));
+    }
 
     let mut query = r#"
         SELECT 
             p.id,
             p.user_id,
             p.status,
+            p.status::text as status_text,
             p.registered_at,
             p.checked_in_at,
             p.checked_out_at,
@@ -769,13 +1402,43 @@ pub async fn get_activity_participations(
     "#
     .to_string();
 
-    if let Some(_status) = status_filter {
-        query.push_str(" AND p.status = $2");
+    if status_filter.is_some() {
+        query.push_str(" AND p.status::text = $2");
     }
 
     query.push_str(" ORDER BY p.registered_at DESC");
 
-    let participations_result = if let Some(status) = status_filter {
+    if format == "csv" {
+        // Stream rows straight from the database cursor so large activities aren't buffered
+        let pool = session_state.db_pool.clone();
+        let rows = async_stream::stream! {
+            yield Ok(csv_row(&PARTICIPATION_EXPORT_HEADER));
+
+            let mut db_query = sqlx::query(&query).bind(activity_id);
+            if let Some(status) = &status_filter {
+                db_query = db_query.bind(status);
+            }
+            let mut db_rows = db_query.fetch(&pool);
+
+            while let Some(row) = db_rows.next().await {
+                match row {
+                    Ok(row) => yield Ok(csv_row(&participation_export_row(&row))),
+                    Err(e) => {
+                        tracing::error!("Failed to stream participations CSV: {}", e);
+                        yield Err(std::io::Error::other(e));
+                        break;
+                    }
+                }
+            }
+        };
+
+        return Ok(csv_attachment_stream(
+            &format!("participations_{}.csv", activity_id),
+            rows,
+        ));
+    }
+
+    let participations_result = if let Some(status) = &status_filter {
         sqlx::query(&query)
             .bind(activity_id)
             .bind(status)
@@ -789,6 +1452,14 @@ pub async fn get_activity_participations(
     };
 
     match participations_result {
+        Ok(rows) if format == "xlsx" => {
+            let rows: Vec<Vec<String>> = rows.iter().map(participation_export_row).collect();
+            let workbook = xlsx_workbook("Participations", &PARTICIPATION_EXPORT_HEADER, &rows).map_err(|e| {
+                tracing::error!("Failed to build participations workbook: {}", e);
+                ApiError::internal("Failed to build export")
+            })?;
+            Ok(xlsx_attachment(&format!("participations_{}.xlsx", activity_id), workbook))
+        }
         Ok(rows) => {
             let mut participations_with_users = Vec::new();
 
@@ -821,314 +1492,3078 @@ pub async fn get_activity_participations(
                 "message": "Participations retrieved successfully"
             });
 
-            Ok(Json(response))
+            Ok(Json(response).into_response())
         }
         Err(_) => {
-            let error_response = json!({
-                "status": "error",
-                "message": "Failed to retrieve participations"
-            });
-            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+            Err(ApiError::internal("Failed to retrieve participations"))
         }
     }
 }
 
-/// Participate in activity (register)
-pub async fn participate(
+/// Columns of the CSV and xlsx participation exports
+const PARTICIPATION_EXPORT_HEADER: [&str; 8] = [
+    "student_id", "name", "email", "department", "status",
+    "registered_at", "checked_in_at", "checked_out_at",
+];
+
+fn participation_export_row(row: &sqlx::postgres::PgRow) -> Vec<String> {
+    let format_time = |column: &str| {
+        row.get::<Option<DateTime<Utc>>, _>(column)
+            .map(|t| t.to_rfc3339())
+            .unwrap_or_default()
+    };
+    vec![
+        row.get::<String, _>("student_id"),
+        row.get::<String, _>("user_name"),
+        row.get::<String, _>("email"),
+        row.get::<Option<String>, _>("department_name").unwrap_or_default(),
+        row.get::<String, _>("status_text"),
+        format_time("registered_at"),
+        format_time("checked_in_at"),
+        format_time("checked_out_at"),
+    ]
+}
+
+/// Manually adjust a participation status (organizer/admin override, e.g. check-in without QR)
+///
+/// The path id may be the participation id or the student's user id.
+pub async fn update_participation_status(
     State(session_state): State<SessionState>,
     user: SessionUser,
-    Path(activity_id): Path<Uuid>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    // Check if activity exists and get details
-    let activity = sqlx::query(
-        "SELECT id, title, status, start_time, max_participants FROM activities WHERE id = $1",
+    Path((activity_id, participation_or_user_id)): Path<(Uuid, Uuid)>,
+    ApiJson(request): ApiJson<UpdateParticipationStatus>,
+) -> ApiResult<Json<Value>> {
+    let reason = request.reason.trim();
+    if reason.is_empty() {
+        return Err(ApiError::bad_request("reason_required", "A reason is required to change a participation status"));
+    }
+
+    ensure_can_manage_participations(&session_state, &user, activity_id).await?;
+
+    let mut tx = match session_state.db_pool.begin().await {
+        Ok(tx) => tx,
+        Err(_) => {
+            return Err(ApiError::internal("Failed to start transaction"));
+        }
+    };
+
+    // Lock the activity first, like participate and withdraw do, so seat changes below are
+    // serialized with concurrent registrations
+    let activity = sqlx::query_as::<_, (String, bool)>(
+        "SELECT title, allow_waitlist FROM activities WHERE id = $1 FOR UPDATE",
     )
-    .bind(&activity_id)
-    .fetch_one(&session_state.db_pool)
+    .bind(activity_id)
+    .fetch_one(&mut *tx)
     .await;
-
-    let activity = match activity {
+    let (activity_title, allow_waitlist) = match activity {
         Ok(activity) => activity,
-        Err(sqlx::Error::RowNotFound) => {
-            let error_response = json!({
-                "status": "error",
-                "message": "Activity not found"
-            });
-            return Err((StatusCode::NOT_FOUND, Json(error_response)));
-        }
         Err(_) => {
-            let error_response = json!({
-                "status": "error",
-                "message": "Failed to check activity"
-            });
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+            return Err(ApiError::internal("Failed to check activity"));
         }
     };
 
-    // Check if activity is open for registration
-    let status: String = activity.get("status");
-    if status != "published" && status != "ongoing" {
-        let error_response = json!({
-            "status": "error",
-            "message": "Activity is not open for registration"
-        });
-        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
-    }
-
-    // Check if user is already registered
-    let existing_participation =
-        sqlx::query("SELECT id FROM participations WHERE user_id = $1 AND activity_id = $2")
-            .bind(&user.user_id)
-            .bind(&activity_id)
-            .fetch_optional(&session_state.db_pool)
-            .await;
+    let current = sqlx::query_as::<_, Participation>(
+        r#"
+        SELECT id, user_id, activity_id, status, registered_at, checked_in_at, checked_out_at, notes, waitlist_position
+        FROM participations
+        WHERE (id = $1 OR user_id = $1) AND activity_id = $2
+        FOR UPDATE
+        "#,
+    )
+    .bind(participation_or_user_id)
+    .bind(activity_id)
+    .fetch_optional(&mut *tx)
+    .await;
 
-    match existing_participation {
-        Ok(Some(_)) => {
-            let error_response = json!({
-                "status": "error",
-                "message": "You are already registered for this activity"
-            });
-            return Err((StatusCode::CONFLICT, Json(error_response)));
+    let current = match current {
+        Ok(Some(participation)) => participation,
+        Ok(None) => {
+            return Err(ApiError::not_found("participation_not_found", "Participation not found"));
         }
         Err(_) => {
-            let error_response = json!({
-                "status": "error",
-                "message": "Failed to check existing participation"
-            });
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+            return Err(ApiError::internal("Failed to fetch participation"));
         }
-        _ => {}
-    }
+    };
 
-    // Check if activity has reached max participants
-    let max_participants: Option<i32> = activity.get("max_participants");
-    if let Some(max_participants) = max_participants {
-        let current_count = sqlx::query_scalar::<_, i64>(
-            "SELECT COUNT(*) FROM participations WHERE activity_id = $1",
-        )
-        .bind(activity_id)
-        .fetch_one(&session_state.db_pool)
-        .await
-        .unwrap_or(0);
+    let participation_id = current.id;
 
-        if current_count >= max_participants as i64 {
-            let error_response = json!({
-                "status": "error",
-                "message": "Activity has reached maximum number of participants"
-            });
-            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    if !current.status.can_transition_to(&request.status) {
+        return Err(ApiError::bad_request(
+            "illegal_status_transition",
+            format!(
+                "Illegal status transition from {:?} to {:?}",
+                current.status, request.status
+            ),
+        ));
+    }
+
+    // Taking a seat (off the waitlist or back from cancelled) needs a free one, exactly like
+    // participate; giving one up passes it on to the waitlist below
+    let takes_seat = !current.status.holds_seat() && request.status.holds_seat();
+    let frees_seat = current.status.holds_seat() && !request.status.holds_seat();
+    if takes_seat {
+        match has_free_seat(&mut tx, activity_id).await {
+            Ok(true) => {}
+            Ok(false) => {
+                return Err(ApiError::conflict("activity_full", "Activity has reached maximum number of participants"));
+            }
+            Err(_) => {
+                return Err(ApiError::internal("Failed to check activity capacity"));
+            }
         }
     }
 
-    // Create participation
-    let create_result = sqlx::query_as::<_, Participation>(
+    // Stamp the timestamp that belongs to the target status
+    let timestamp_clause = match request.status {
+        ParticipationStatus::CheckedIn => ", checked_in_at = NOW(), checked_out_at = NULL",
+        ParticipationStatus::CheckedOut => ", checked_out_at = NOW()",
+        ParticipationStatus::Completed => ", checked_out_at = COALESCE(checked_out_at, NOW())",
+        ParticipationStatus::Registered => {
+            ", checked_in_at = NULL, checked_out_at = NULL, waitlist_position = NULL"
+        }
+        ParticipationStatus::Cancelled => ", waitlist_position = NULL",
+        ParticipationStatus::NoShow | ParticipationStatus::Waitlisted => "",
+    };
+
+    let update_query = format!(
+        "UPDATE participations SET status = $1, notes = COALESCE($3, notes){} WHERE id = $2
+         RETURNING id, user_id, activity_id, status, registered_at, checked_in_at, checked_out_at, notes, waitlist_position",
+        timestamp_clause
+    );
+
+    let updated = match sqlx::query_as::<_, Participation>(&update_query)
+        .bind(&request.status)
+        .bind(participation_id)
+        .bind(&request.notes)
+        .fetch_one(&mut *tx)
+        .await
+    {
+        Ok(participation) => participation,
+        Err(e) => {
+            tracing::error!("Failed to update participation: {}", e);
+            return Err(ApiError::internal("Failed to update participation"));
+        }
+    };
+
+    let award_result = if updated.status == ParticipationStatus::Completed {
+        award_activity_hours(&mut tx, participation_id).await
+    } else if current.status == ParticipationStatus::Completed {
+        revoke_activity_hours(&mut tx, participation_id).await
+    } else {
+        Ok(())
+    };
+
+    let waitlist_result = if current.status == ParticipationStatus::Waitlisted {
+        compact_waitlist(&mut tx, activity_id).await.map(|_| None)
+    } else if frees_seat && allow_waitlist {
+        promote_next_waitlisted(&mut tx, activity_id).await
+    } else {
+        Ok(None)
+    };
+
+    let event_result = sqlx::query(
         r#"
-        INSERT INTO participations (user_id, activity_id, status)
-        VALUES ($1, $2, 'registered')
-        RETURNING id, user_id, activity_id, status, registered_at, checked_in_at, checked_out_at, notes
-        "#
+        INSERT INTO participation_events
+            (participation_id, activity_id, user_id, from_status, to_status, source, reason, changed_by)
+        VALUES ($1, $2, $3, $4, $5, 'admin', $6, $7)
+        "#,
     )
-    .bind(user.user_id)
+    .bind(participation_id)
     .bind(activity_id)
-    .fetch_one(&session_state.db_pool)
+    .bind(updated.user_id)
+    .bind(&current.status)
+    .bind(&updated.status)
+    .bind(reason)
+    .bind(user.user_id)
+    .execute(&mut *tx)
     .await;
 
-    match create_result {
-        Ok(participation) => {
-            let response = json!({
-                "status": "success",
-                "data": participation,
-                "message": "Successfully registered for activity"
-            });
-            Ok(Json(response))
-        }
-        Err(e) => {
-            let error_response = json!({
-                "status": "error",
-                "message": format!("Failed to register for activity: {}", e)
-            });
-            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+    let promoted_user_id = match (award_result, waitlist_result, event_result) {
+        (Ok(()), Ok(promoted_user_id), Ok(_)) => promoted_user_id,
+        _ => {
+            return Err(ApiError::internal("Failed to record participation status change"));
         }
+    };
+    if tx.commit().await.is_err() {
+        return Err(ApiError::internal("Failed to record participation status change"));
     }
-}
 
-/// Scan QR code for check-in/check-out
-pub async fn scan_qr(
-    State(session_state): State<SessionState>,
-    _admin: AdminUser, // Only admins or activity creators can scan QR codes
-    Path(activity_id): Path<Uuid>,
-    Json(request): Json<QrScanRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    // Parse QR data
-    let qr_data: Value = match serde_json::from_str(&request.qr_data) {
-        Ok(data) => data,
-        Err(_) => {
-            let error_response = json!({
-                "status": "error",
-                "message": "Invalid QR code format"
-            });
-            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
-        }
-    };
+    if let Some(promoted_user_id) = promoted_user_id {
+        notify_waitlist_promotion(&session_state, promoted_user_id, activity_id, &activity_title).await;
+    }
 
-    let user_id = match qr_data
-        .get("user_id")
-        .and_then(|id| id.as_str())
-        .and_then(|id| Uuid::parse_str(id).ok())
-    {
-        Some(id) => id,
-        None => {
-            let error_response = json!({
-                "status": "error",
-                "message": "Invalid user ID in QR code"
-            });
-            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
-        }
+    let webhook_event = match updated.status {
+        ParticipationStatus::CheckedIn => Some(WebhookEvent::ParticipationCheckedIn),
+        ParticipationStatus::CheckedOut => Some(WebhookEvent::ParticipationCheckedOut),
+        _ => None,
     };
+    if let Some(event) = webhook_event {
+        let data = participation_event_data(participation_id, activity_id, updated.user_id, "admin");
+        enqueue_webhook_event(&session_state, event, data).await;
+        let checked_out = updated.status == ParticipationStatus::CheckedOut;
+        notify_participation_scan(&session_state, activity_id, updated.user_id, checked_out);
+    }
 
-    let qr_secret = match qr_data.get("secret").and_then(|s| s.as_str()) {
-        Some(secret) => secret,
-        None => {
-            let error_response = json!({
-                "status": "error",
-                "message": "Missing secret in QR code"
-            });
-            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
-        }
+    let response = json!({
+        "status": "success",
+        "data": updated,
+        "message": "Participation status updated successfully"
+    });
+
+    Ok(Json(response))
+}
+
+/// Check if user can manage this activity's participations (activity creator or scoped admin)
+/// The creator, or a `ManageActivities` holder with access to the activity's faculty
+fn can_manage_activity(user: &SessionUser, created_by: Uuid, faculty_id: Option<Uuid>) -> bool {
+    let has_permission = user.has_permission(&Permission::ManageActivities);
+    let in_scope = match faculty_id {
+        Some(faculty_id) => has_faculty_access(user, faculty_id),
+        None => true,
     };
 
-    // Verify QR secret
-    let user_check =
-        sqlx::query("SELECT student_id, first_name, last_name, prefix, qr_secret FROM users WHERE id = $1")
-            .bind(&user_id)
-            .fetch_one(&session_state.db_pool)
-            .await;
+    created_by == user.user_id || (has_permission && in_scope)
+}
 
-    let user_data = match user_check {
-        Ok(user) => {
-            if user.get::<String, _>("qr_secret") != qr_secret {
-                let error_response = json!({
-                    "status": "error",
-                    "message": "Invalid QR code secret"
-                });
-                return Err((StatusCode::BAD_REQUEST, Json(error_response)));
-            }
-            user
-        }
+async fn ensure_can_manage_participations(
+    session_state: &SessionState,
+    user: &SessionUser,
+    activity_id: Uuid,
+) -> ApiResult<()> {
+    let activity_check = sqlx::query("SELECT created_by, faculty_id FROM activities WHERE id = $1 AND deleted_at IS NULL")
+        .bind(activity_id)
+        .fetch_one(&session_state.db_pool)
+        .await;
+
+    let can_manage = match activity_check {
+        Ok(activity) => can_manage_activity(user, activity.get("created_by"), activity.get("faculty_id")),
         Err(sqlx::Error::RowNotFound) => {
-            let error_response = json!({
-                "status": "error",
-                "message": "User not found"
-            });
-            return Err((StatusCode::NOT_FOUND, Json(error_response)));
+            return Err(ApiError::not_found("activity_not_found", "Activity not found"));
         }
         Err(_) => {
-            let error_response = json!({
-                "status": "error",
-                "message": "Failed to verify user"
-            });
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+            return Err(ApiError::internal("Failed to check activity"));
         }
     };
 
-    // Check if user is registered for this activity
-    let participation = sqlx::query(
-        "SELECT id, status FROM participations WHERE user_id = $1 AND activity_id = $2",
-    )
-    .bind(&user_id)
-    .bind(&activity_id)
-    .fetch_one(&session_state.db_pool)
-    .await;
+    if !can_manage {
+        return Err(ApiError::forbidden("permission_denied", "Access denied: You don't have permission to manage participations for this activity"));
+    }
 
-    let participation = match participation {
-        Ok(p) => p,
+    Ok(())
+}
+
+/// Largest student ID list accepted by bulk check-in
+const MAX_BULK_CHECKIN_ROWS: usize = 2000;
+
+/// Per-row outcome of a bulk check-in
+#[derive(Debug, Serialize)]
+pub struct BulkCheckInRow {
+    pub row: usize,
+    pub student_id: String,
+    /// `checked_in`, `already_checked_in` or `error`
+    pub result: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl BulkCheckInRow {
+    fn error(row: usize, student_id: &str, code: &'static str, message: &str) -> Self {
+        Self {
+            row,
+            student_id: student_id.to_string(),
+            result: "error",
+            code: Some(code),
+            message: Some(message.to_string()),
+        }
+    }
+}
+
+/// Student IDs from a `text/csv` body (first column, optional `student_id` header)
+/// or a JSON array of strings
+fn parse_bulk_checkin_body(headers: &HeaderMap, body: &[u8]) -> ApiResult<Vec<String>> {
+    let is_csv = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("text/csv"));
+
+    let student_ids: Vec<String> = if is_csv {
+        let text = std::str::from_utf8(body)
+            .map_err(|_| ApiError::bad_request("invalid_csv", "CSV must be UTF-8 encoded"))?;
+        let mut records = parse_csv(text).into_iter().peekable();
+        if records
+            .peek()
+            .and_then(|record| record.first())
+            .is_some_and(|field| field.trim().eq_ignore_ascii_case("student_id"))
+        {
+            records.next();
+        }
+        records
+            .map(|record| record.into_iter().next().unwrap_or_default())
+            .collect()
+    } else {
+        serde_json::from_slice(body).map_err(|_| {
+            ApiError::bad_request(
+                "invalid_json_body",
+                "Expected a JSON array of student IDs or a text/csv body",
+            )
+        })?
+    };
+
+    if student_ids.is_empty() {
+        return Err(ApiError::bad_request("empty_upload", "No student IDs were provided"));
+    }
+    if student_ids.len() > MAX_BULK_CHECKIN_ROWS {
+        return Err(ApiError::bad_request(
+            "too_many_rows",
+            format!("At most {} student IDs can be checked in at once", MAX_BULK_CHECKIN_ROWS),
+        ));
+    }
+
+    Ok(student_ids.into_iter().map(|id| id.trim().to_string()).collect())
+}
+
+/// Check in many registered students at once by student ID (fallback when QR scanning failed)
+///
+/// Rows that can't be checked in are reported individually; the rest are committed together.
+pub async fn bulk_checkin(
+    State(session_state): State<SessionState>,
+    user: SessionUser,
+    Path(activity_id): Path<Uuid>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> ApiResult<Json<Value>> {
+    ensure_can_manage_participations(&session_state, &user, activity_id).await?;
+    let student_ids = parse_bulk_checkin_body(&headers, &body)?;
+
+    let mut tx = session_state
+        .db_pool
+        .begin()
+        .await
+        .map_err(|_| ApiError::internal("Failed to start transaction"))?;
+
+    let users: HashMap<String, Uuid> = sqlx::query_as::<_, (String, Uuid)>(
+        "SELECT student_id, id FROM users WHERE student_id = ANY($1)",
+    )
+    .bind(&student_ids)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|_| ApiError::internal("Failed to look up students"))?
+    .into_iter()
+    .collect();
+
+    let participations: HashMap<Uuid, (Uuid, ParticipationStatus)> =
+        sqlx::query_as::<_, (Uuid, Uuid, ParticipationStatus)>(
+            r#"
+            SELECT user_id, id, status FROM participations
+            WHERE activity_id = $1 AND user_id = ANY($2)
+            FOR UPDATE
+            "#,
+        )
+        .bind(activity_id)
+        .bind(users.values().copied().collect::<Vec<_>>())
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|_| ApiError::internal("Failed to look up participations"))?
+        .into_iter()
+        .map(|(user_id, id, status)| (user_id, (id, status)))
+        .collect();
+
+    let mut results = Vec::with_capacity(student_ids.len());
+    let mut to_check_in = Vec::new();
+    let mut checked_in_users = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+
+    for (index, student_id) in student_ids.iter().enumerate() {
+        let row = index + 1;
+        if student_id.is_empty() {
+            results.push(BulkCheckInRow::error(row, student_id, "missing_student_id", "Student ID is empty"));
+            continue;
+        }
+        if !seen.insert(student_id.as_str()) {
+            results.push(BulkCheckInRow::error(row, student_id, "duplicate_row", "Student ID appears more than once"));
+            continue;
+        }
+
+        let Some(user_id) = users.get(student_id) else {
+            results.push(BulkCheckInRow::error(row, student_id, "user_not_found", "No user with this student ID"));
+            continue;
+        };
+
+        match participations.get(user_id) {
+            None => results.push(BulkCheckInRow::error(
+                row,
+                student_id,
+                "not_registered",
+                "Student is not registered for this activity",
+            )),
+            Some((participation_id, status)) => match status {
+                ParticipationStatus::CheckedIn
+                | ParticipationStatus::CheckedOut
+                | ParticipationStatus::Completed => results.push(BulkCheckInRow {
+                    row,
+                    student_id: student_id.clone(),
+                    result: "already_checked_in",
+                    code: None,
+                    message: None,
+                }),
+                status if status.can_transition_to(&ParticipationStatus::CheckedIn) => {
+                    to_check_in.push(*participation_id);
+                    checked_in_users.push((*participation_id, *user_id));
+                    results.push(BulkCheckInRow {
+                        row,
+                        student_id: student_id.clone(),
+                        result: "checked_in",
+                        code: None,
+                        message: None,
+                    });
+                }
+                status => results.push(BulkCheckInRow::error(
+                    row,
+                    student_id,
+                    "invalid_participation_status",
+                    &format!("Cannot check in a participation with status {:?}", status),
+                )),
+            },
+        }
+    }
+
+    sqlx::query(
+        r#"
+        WITH updated AS (
+            UPDATE participations p
+            SET status = 'checked_in', checked_in_at = NOW(), checked_out_at = NULL
+            FROM participations old
+            WHERE p.id = old.id AND p.id = ANY($1)
+            RETURNING p.id, p.user_id, old.status AS from_status
+        )
+        INSERT INTO participation_events
+            (participation_id, activity_id, user_id, from_status, to_status, source, reason, changed_by)
+        SELECT id, $2, user_id, from_status, 'checked_in', 'bulk_checkin', 'Bulk check-in', $3
+        FROM updated
+        "#,
+    )
+    .bind(&to_check_in)
+    .bind(activity_id)
+    .bind(user.user_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to bulk check in participations: {}", e);
+        ApiError::internal("Failed to check in participants")
+    })?;
+
+    tx.commit()
+        .await
+        .map_err(|_| ApiError::internal("Failed to check in participants"))?;
+
+    notify_bulk_check_in(
+        &session_state,
+        activity_id,
+        checked_in_users.iter().map(|(_, user_id)| *user_id).collect(),
+    );
+    for (participation_id, user_id) in checked_in_users {
+        enqueue_webhook_event(
+            &session_state,
+            WebhookEvent::ParticipationCheckedIn,
+            participation_event_data(participation_id, activity_id, user_id, "bulk_checkin"),
+        )
+        .await;
+    }
+
+    let count = |result: &str| results.iter().filter(|row| row.result == result).count();
+    let response = json!({
+        "status": "success",
+        "data": {
+            "total_rows": results.len(),
+            "checked_in": count("checked_in"),
+            "already_checked_in": count("already_checked_in"),
+            "failed": count("error"),
+            "results": results
+        },
+        "message": format!("Checked in {} of {} students", to_check_in.len(), results.len())
+    });
+
+    Ok(Json(response))
+}
+
+/// Participate in activity (register)
+pub async fn participate(
+    State(session_state): State<SessionState>,
+    user: SessionUser,
+    Path(activity_id): Path<Uuid>,
+    request: Option<ApiJson<ParticipateRequest>>, // body is optional
+) -> ApiResult<Json<Value>> {
+    let allow_overlap = request.is_some_and(|ApiJson(r)| r.allow_overlap.unwrap_or(false));
+
+    // Check if activity exists and get details; activities hidden from the user by their
+    // visibility are reported as not found, as in get_activity
+    let visible_faculty_ids = get_accessible_faculty_ids(&user);
+    let visibility_clause = match visible_faculty_ids {
+        Some(_) => format!("AND {}", visibility_condition(2, 3)),
+        None => String::new(),
+    };
+    let time_zone = session_state.app_config.activity_time_zone();
+    let query = format!(
+        r#"
+        SELECT a.id, a.title, a.status, a.max_participants, a.eligible_faculties,
+            COALESCE(a.registration_deadline, {}) as registration_closes_at
+        FROM activities a WHERE a.id = $1 AND a.deleted_at IS NULL {}
+        "#,
+        time_zone.start_sql("a"),
+        visibility_clause
+    );
+    let mut query_builder = sqlx::query(&query).bind(activity_id).bind(user.user_id);
+    if let Some(faculty_ids) = &visible_faculty_ids {
+        query_builder = query_builder.bind(faculty_ids);
+    }
+    let activity = query_builder.fetch_one(&session_state.db_pool).await;
+
+    let activity = match activity {
+        Ok(activity) => activity,
         Err(sqlx::Error::RowNotFound) => {
-            let error_response = json!({
-                "status": "error",
-                "message": "User is not registered for this activity"
-            });
-            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+            return Err(ApiError::not_found("activity_not_found", "Activity not found"));
         }
         Err(_) => {
-            let error_response = json!({
-                "status": "error",
-                "message": "Failed to check participation"
-            });
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+            return Err(ApiError::internal("Failed to check activity"));
         }
     };
 
-    // Determine next status based on current status
-    let status: String = participation.get("status");
-    let (new_status, field_to_update) = match status.as_str() {
-        "registered" => ("checked_in", "checked_in_at"),
-        "checked_in" => ("checked_out", "checked_out_at"),
-        "checked_out" => ("completed", ""), // No additional field to update
-        _ => {
-            let error_response = json!({
-                "status": "error",
-                "message": "Invalid participation status for QR scan"
-            });
-            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    // Check if activity is open for registration
+    let status: ActivityStatus = activity.get("status");
+    if status != ActivityStatus::Published && status != ActivityStatus::Ongoing {
+        return Err(ApiError::bad_request("registration_closed", "Activity is not open for registration"));
+    }
+
+    // Registration closes at the deadline, or when the activity starts if none is set
+    let registration_closes_at: DateTime<Utc> = activity.get("registration_closes_at");
+    if Utc::now() > registration_closes_at {
+        return Err(ApiError::bad_request(
+            "registration_deadline_passed",
+            "Registration deadline has passed",
+        )
+        .with("data", json!({ "registration_deadline": registration_closes_at })));
+    }
+
+    // Check if the student's faculty is eligible (empty list = open to all)
+    let eligible_faculties: Value = activity.get("eligible_faculties");
+    let student_faculty_id = match sqlx::query_scalar::<_, Option<Uuid>>(
+        "SELECT d.faculty_id FROM users u LEFT JOIN departments d ON u.department_id = d.id WHERE u.id = $1",
+    )
+    .bind(user.user_id)
+    .fetch_one(&session_state.db_pool)
+    .await
+    {
+        Ok(faculty_id) => faculty_id,
+        Err(_) => {
+            return Err(ApiError::internal("Failed to check student faculty"));
         }
     };
 
-    // Update participation status
-    let update_query = if field_to_update.is_empty() {
-        "UPDATE participations SET status = $1 WHERE id = $2".to_string()
-    } else {
-        format!(
-            "UPDATE participations SET status = $1, {} = NOW() WHERE id = $2",
-            field_to_update
-        )
+    if !is_faculty_eligible(&eligible_faculties, student_faculty_id) {
+        return Err(ApiError::forbidden("faculty_not_eligible", "Your faculty is not eligible to register for this activity"));
+    }
+
+    // Capacity check and insert must be atomic: lock the activity row so concurrent
+    // registrations for the same activity are serialized
+    let mut tx = match session_state.db_pool.begin().await {
+        Ok(tx) => tx,
+        Err(_) => {
+            return Err(ApiError::internal("Failed to start transaction"));
+        }
+    };
+
+    let (max_participants, allow_waitlist) = match sqlx::query_as::<_, (Option<i32>, bool)>(
+        "SELECT max_participants, allow_waitlist FROM activities WHERE id = $1 FOR UPDATE",
+    )
+    .bind(activity_id)
+    .fetch_one(&mut *tx)
+    .await
+    {
+        Ok(capacity) => capacity,
+        Err(_) => {
+            return Err(ApiError::internal("Failed to check activity"));
+        }
     };
 
-    let update_result = sqlx::query(&update_query)
-        .bind(new_status)
-        .bind(participation.get::<Uuid, _>("id"))
-        .execute(&session_state.db_pool)
+    // Check if user is already registered
+    let existing_participation =
+        sqlx::query("SELECT id FROM participations WHERE user_id = $1 AND activity_id = $2")
+            .bind(user.user_id)
+            .bind(activity_id)
+            .fetch_optional(&mut *tx)
+            .await;
+
+    match existing_participation {
+        Ok(Some(_)) => {
+            return Err(ApiError::conflict("already_registered", "You are already registered for this activity"));
+        }
+        Err(_) => {
+            return Err(ApiError::internal("Failed to check existing participation"));
+        }
+        _ => {}
+    }
+
+    // Check if activity has reached max participants (waitlisted/cancelled don't hold a seat)
+    let mut waitlisted = false;
+    if let Some(max_participants) = max_participants {
+        let current_count = match sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM participations WHERE activity_id = $1 AND status NOT IN ('waitlisted', 'cancelled')",
+        )
+        .bind(activity_id)
+        .fetch_one(&mut *tx)
+        .await
+        {
+            Ok(count) => count,
+            Err(_) => {
+                return Err(ApiError::internal("Failed to check activity capacity"));
+            }
+        };
+
+        if current_count >= max_participants as i64 {
+            if allow_waitlist {
+                waitlisted = true;
+            } else {
+                return Err(ApiError::conflict("activity_full", "Activity has reached maximum number of participants"));
+            }
+        }
+    }
+
+    // Reject double-booking: another registered/checked-in activity overlapping this one
+    if !allow_overlap {
+        let conflict = sqlx::query(&format!(
+            r#"
+            WITH target AS (
+                SELECT {target_start} as start_time, {target_end} as end_time
+                FROM activities WHERE id = $2
+            )
+            SELECT a.id, a.title, {start_time} as start_time, {end_time} as end_time
+            FROM participations p
+            JOIN activities a ON a.id = p.activity_id
+            CROSS JOIN target t
+            WHERE p.user_id = $1 AND p.activity_id <> $2
+              AND p.status IN ('registered', 'checked_in')
+              AND a.deleted_at IS NULL
+              AND {start_time} < t.end_time
+              AND {end_time} > t.start_time
+            ORDER BY start_time
+            LIMIT 1
+            "#,
+            target_start = time_zone.start_sql(""),
+            target_end = time_zone.end_sql(""),
+            start_time = time_zone.start_sql("a"),
+            end_time = time_zone.end_sql("a")
+        ))
+        .bind(user.user_id)
+        .bind(activity_id)
+        .fetch_optional(&mut *tx)
         .await;
 
-    match update_result {
-        Ok(_) => {
-            let response_data = QrScanResponse {
-                success: true,
-                message: format!("Successfully {} for activity", new_status.replace("_", " ")),
-                participation_status: Some(match new_status {
-                    "checked_in" => ParticipationStatus::CheckedIn,
-                    "checked_out" => ParticipationStatus::CheckedOut,
-                    "completed" => ParticipationStatus::Completed,
-                    _ => ParticipationStatus::Registered,
-                }),
-                user_name: Some(format!(
-                    "{} {}",
-                    user_data.get::<String, _>("first_name"),
-                    user_data.get::<String, _>("last_name")
-                )),
-                user_name_with_prefix: Some(format!(
-                    "{}{} {}",
-                    user_data.get::<UserPrefix, _>("prefix").to_thai_string(),
-                    user_data.get::<String, _>("first_name"),
-                    user_data.get::<String, _>("last_name")
-                )),
-                student_id: Some(user_data.get::<String, _>("student_id")),
-            };
+        match conflict {
+            Ok(Some(conflict)) => {
+                let title: String = conflict.get("title");
+                let data = json!({
+                    "conflicting_activity": {
+                        "id": conflict.get::<Uuid, _>("id"),
+                        "title": title,
+                        "start_time": conflict.get::<DateTime<Utc>, _>("start_time"),
+                        "end_time": conflict.get::<DateTime<Utc>, _>("end_time")
+                    }
+                });
+                return Err(ApiError::conflict(
+                    "schedule_conflict",
+                    format!("This activity overlaps with \"{}\" that you are already registered for", title),
+                )
+                .with("data", data));
+            }
+            Ok(None) => {}
+            Err(_) => {
+                return Err(ApiError::internal("Failed to check schedule conflicts"));
+            }
+        }
+    }
+
+    // Create participation; waitlisted users go to the back of the queue
+    let create_result = sqlx::query_as::<_, Participation>(
+        r#"
+        INSERT INTO participations (user_id, activity_id, status, waitlist_position)
+        SELECT $1, $2,
+            CASE WHEN $3 THEN 'waitlisted' ELSE 'registered' END::participation_status,
+            CASE WHEN $3 THEN COALESCE(MAX(waitlist_position), 0) + 1 END
+        FROM participations
+        WHERE activity_id = $2 AND status = 'waitlisted'
+        RETURNING id, user_id, activity_id, status, registered_at, checked_in_at, checked_out_at, notes, waitlist_position
+        "#
+    )
+    .bind(user.user_id)
+    .bind(activity_id)
+    .bind(waitlisted)
+    .fetch_one(&mut *tx)
+    .await;
+
+    let create_result = match create_result {
+        Ok(participation) => tx.commit().await.map(|_| participation),
+        Err(e) => Err(e),
+    };
 
+    match create_result {
+        Ok(participation) => {
+            let message = if waitlisted {
+                format!(
+                    "Activity is full; you are number {} on the waitlist",
+                    participation.waitlist_position.unwrap_or_default()
+                )
+            } else {
+                "Successfully registered for activity".to_string()
+            };
+            let mut data = participation_event_data(participation.id, activity_id, user.user_id, "self_registration");
+            data["status"] = json!(participation.status);
+            enqueue_webhook_event(&session_state, WebhookEvent::ParticipationRegistered, data).await;
             let response = json!({
                 "status": "success",
-                "data": response_data,
-                "message": "QR code scanned successfully"
+                "data": participation,
+                "message": message
             });
-
             Ok(Json(response))
         }
         Err(e) => {
-            let error_response = json!({
-                "status": "error",
-                "message": format!("Failed to update participation: {}", e)
-            });
-            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+            tracing::error!("Failed to register for activity: {}", e);
+            Err(ApiError::internal("Failed to register for activity"))
+        }
+    }
+}
+
+const MAX_PARTICIPATION_NOTES_LEN: usize = 500;
+
+const MAX_FEEDBACK_COMMENT_LEN: usize = 2000;
+
+/// Rate an activity after completing it; submitting again replaces the earlier feedback
+pub async fn submit_activity_feedback(
+    State(session_state): State<SessionState>,
+    user: SessionUser,
+    Path(activity_id): Path<Uuid>,
+    ApiJson(request): ApiJson<SubmitActivityFeedback>,
+) -> ApiResult<Json<Value>> {
+    if !(1..=5).contains(&request.rating) {
+        return Err(ApiError::validation("invalid_rating", "Rating must be between 1 and 5"));
+    }
+    let comment = request
+        .comment
+        .as_deref()
+        .map(str::trim)
+        .filter(|comment| !comment.is_empty());
+    if comment.is_some_and(|comment| comment.chars().count() > MAX_FEEDBACK_COMMENT_LEN) {
+        return Err(ApiError::validation(
+            "comment_too_long",
+            format!("Comment must be at most {} characters", MAX_FEEDBACK_COMMENT_LEN),
+        ));
+    }
+
+    let status = sqlx::query_scalar::<_, ParticipationStatus>(
+        r#"
+        SELECT p.status FROM participations p
+        JOIN activities a ON a.id = p.activity_id AND a.deleted_at IS NULL
+        WHERE p.user_id = $1 AND p.activity_id = $2
+        "#,
+    )
+    .bind(user.user_id)
+    .bind(activity_id)
+    .fetch_optional(&session_state.db_pool)
+    .await
+    .map_err(|_| ApiError::internal("Failed to check participation"))?;
+
+    match status {
+        Some(ParticipationStatus::Completed) => {}
+        Some(_) => {
+            return Err(ApiError::forbidden(
+                "activity_not_completed",
+                "Feedback can only be given after completing the activity",
+            ));
+        }
+        None => {
+            return Err(ApiError::not_found("not_registered", "You are not registered for this activity"));
         }
     }
+
+    let feedback = sqlx::query_as::<_, ActivityFeedback>(
+        r#"
+        INSERT INTO activity_feedback (activity_id, user_id, rating, comment)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (activity_id, user_id)
+        DO UPDATE SET rating = EXCLUDED.rating, comment = EXCLUDED.comment, updated_at = NOW()
+        RETURNING id, activity_id, user_id, rating, comment, created_at, updated_at
+        "#,
+    )
+    .bind(activity_id)
+    .bind(user.user_id)
+    .bind(request.rating)
+    .bind(comment)
+    .fetch_one(&session_state.db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to save activity feedback: {}", e);
+        ApiError::internal("Failed to save feedback")
+    })?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": feedback,
+        "message": "Feedback submitted"
+    })))
+}
+
+/// Feedback for an activity with its average rating (creator or `ManageActivities`)
+pub async fn get_activity_feedback(
+    State(session_state): State<SessionState>,
+    user: SessionUser,
+    Path(activity_id): Path<Uuid>,
+) -> ApiResult<Json<Value>> {
+    ensure_can_manage_participations(&session_state, &user, activity_id).await?;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT f.user_id, u.first_name || ' ' || u.last_name as user_name, f.rating, f.comment,
+               f.created_at, f.updated_at
+        FROM activity_feedback f
+        JOIN users u ON u.id = f.user_id
+        WHERE f.activity_id = $1
+        ORDER BY f.updated_at DESC
+        "#,
+    )
+    .bind(activity_id)
+    .fetch_all(&session_state.db_pool)
+    .await
+    .map_err(|_| ApiError::internal("Failed to retrieve feedback"))?;
+
+    let feedback: Vec<Value> = rows
+        .iter()
+        .map(|row| {
+            json!({
+                "user_id": row.get::<Uuid, _>("user_id"),
+                "user_name": row.get::<String, _>("user_name"),
+                "rating": row.get::<i16, _>("rating"),
+                "comment": row.get::<Option<String>, _>("comment"),
+                "created_at": row.get::<DateTime<Utc>, _>("created_at"),
+                "updated_at": row.get::<DateTime<Utc>, _>("updated_at"),
+            })
+        })
+        .collect();
+    let average_rating = (!rows.is_empty())
+        .then(|| rows.iter().map(|row| f64::from(row.get::<i16, _>("rating"))).sum::<f64>() / rows.len() as f64);
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {
+            "activity_id": activity_id,
+            "average_rating": average_rating,
+            "total": rows.len(),
+            "feedback": feedback
+        },
+        "message": "Feedback retrieved successfully"
+    })))
+}
+
+/// Set or clear the caller's own participation notes. Admins can still replace them through
+/// `update_participation_status`.
+pub async fn update_participation_notes(
+    State(session_state): State<SessionState>,
+    user: SessionUser,
+    Path(activity_id): Path<Uuid>,
+    ApiJson(request): ApiJson<UpdateParticipationNotes>,
+) -> ApiResult<Json<Value>> {
+    let notes = request
+        .notes
+        .as_deref()
+        .map(str::trim)
+        .filter(|notes| !notes.is_empty());
+    if notes.is_some_and(|notes| notes.chars().count() > MAX_PARTICIPATION_NOTES_LEN) {
+        return Err(ApiError::validation(
+            "notes_too_long",
+            format!("Notes must be at most {} characters", MAX_PARTICIPATION_NOTES_LEN),
+        ));
+    }
+
+    let updated = sqlx::query_as::<_, Participation>(
+        r#"
+        UPDATE participations SET notes = $3
+        WHERE user_id = $1 AND activity_id = $2 AND status <> 'cancelled'
+        RETURNING id, user_id, activity_id, status, registered_at, checked_in_at, checked_out_at, notes, waitlist_position
+        "#,
+    )
+    .bind(user.user_id)
+    .bind(activity_id)
+    .bind(notes)
+    .fetch_optional(&session_state.db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to update participation notes: {}", e);
+        ApiError::internal("Failed to update participation notes")
+    })?;
+
+    let Some(participation) = updated else {
+        return Err(ApiError::not_found("not_registered", "You are not registered for this activity"));
+    };
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": participation,
+        "message": "Participation notes updated"
+    })))
+}
+
+/// Withdraw own registration (or leave the waitlist); frees the seat for the next waitlisted user
+pub async fn withdraw_participation(
+    State(session_state): State<SessionState>,
+    user: SessionUser,
+    Path(activity_id): Path<Uuid>,
+) -> ApiResult<Json<Value>> {
+    let mut tx = session_state
+        .db_pool
+        .begin()
+        .await
+        .map_err(|_| ApiError::internal("Failed to start transaction"))?;
+
+    // Lock the activity row so promotion is serialized with concurrent registrations
+    let activity = sqlx::query("SELECT title, allow_waitlist FROM activities WHERE id = $1 AND deleted_at IS NULL FOR UPDATE")
+        .bind(activity_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|_| ApiError::internal("Failed to check activity"))?;
+
+    let Some(activity) = activity else {
+        return Err(ApiError::not_found("activity_not_found", "Activity not found"));
+    };
+
+    let participation = sqlx::query_as::<_, (Uuid, ParticipationStatus)>(
+        "SELECT id, status FROM participations WHERE user_id = $1 AND activity_id = $2 FOR UPDATE",
+    )
+    .bind(user.user_id)
+    .bind(activity_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|_| ApiError::internal("Failed to check existing participation"))?;
+
+    let Some((participation_id, status)) = participation else {
+        return Err(ApiError::not_found("not_registered", "You are not registered for this activity"));
+    };
+
+    if !matches!(status, ParticipationStatus::Registered | ParticipationStatus::Waitlisted) {
+        return Err(ApiError::bad_request(
+            "invalid_participation_status",
+            format!("Cannot withdraw a participation with status {:?}", status),
+        ));
+    }
+
+    sqlx::query("DELETE FROM participations WHERE id = $1")
+        .bind(participation_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|_| ApiError::internal("Failed to withdraw participation"))?;
+
+    let promoted_user_id = if status == ParticipationStatus::Waitlisted {
+        compact_waitlist(&mut tx, activity_id)
+            .await
+            .map_err(|_| ApiError::internal("Failed to update waitlist"))?;
+        None
+    } else if activity.get::<bool, _>("allow_waitlist") {
+        promote_next_waitlisted(&mut tx, activity_id)
+            .await
+            .map_err(|_| ApiError::internal("Failed to promote waitlisted participant"))?
+    } else {
+        None
+    };
+
+    tx.commit()
+        .await
+        .map_err(|_| ApiError::internal("Failed to withdraw participation"))?;
+
+    // Notify the promoted user after commit; email failures must not undo the promotion
+    if let Some(promoted_user_id) = promoted_user_id {
+        let activity_title: String = activity.get("title");
+        notify_waitlist_promotion(&session_state, promoted_user_id, activity_id, &activity_title).await;
+    }
+
+    let response = json!({
+        "status": "success",
+        "data": {
+            "activity_id": activity_id,
+            "previous_status": status,
+            "promoted_user_id": promoted_user_id
+        },
+        "message": "Successfully withdrawn from activity"
+    });
+
+    Ok(Json(response))
+}
+
+/// Email a user promoted from the waitlist. Best effort: failures are only logged.
+async fn notify_waitlist_promotion(
+    session_state: &SessionState,
+    promoted_user_id: Uuid,
+    activity_id: Uuid,
+    activity_title: &str,
+) {
+    let recipient = sqlx::query("SELECT email, first_name, last_name FROM users WHERE id = $1")
+        .bind(promoted_user_id)
+        .fetch_optional(&session_state.db_pool)
+        .await
+        .ok()
+        .flatten();
+
+    let Some(recipient) = recipient else {
+        return;
+    };
+    let user_name = format!(
+        "{} {}",
+        recipient.get::<String, _>("first_name"),
+        recipient.get::<String, _>("last_name")
+    );
+    let (subject, body_text, body_html) =
+        EmailTemplate::waitlist_promoted_template(&user_name, activity_title).build();
+
+    if let Err(e) = EmailService::new(session_state.clone())
+        .queue_email(
+            &recipient.get::<String, _>("email"),
+            Some(&user_name),
+            &subject,
+            &body_text,
+            body_html.as_deref(),
+            5,
+            Some(json!({ "type": "waitlist_promoted", "activity_id": activity_id })),
+        )
+        .await
+    {
+        tracing::error!("Failed to queue waitlist promotion email: {}", e);
+    }
+}
+
+/// Get activity waitlist (organizers only), ordered by position
+pub async fn get_activity_waitlist(
+    State(session_state): State<SessionState>,
+    user: SessionUser,
+    Path(activity_id): Path<Uuid>,
+) -> ApiResult<Json<Value>> {
+    let created_by = match sqlx::query_scalar::<_, Uuid>("SELECT created_by FROM activities WHERE id = $1 AND deleted_at IS NULL")
+        .bind(activity_id)
+        .fetch_optional(&session_state.db_pool)
+        .await
+    {
+        Ok(Some(created_by)) => created_by,
+        Ok(None) => {
+            return Err(ApiError::not_found("activity_not_found", "Activity not found"));
+        }
+        Err(_) => {
+            return Err(ApiError::internal("Failed to check activity"));
+        }
+    };
+
+    if created_by != user.user_id && !user.has_permission(&Permission::ManageActivities) {
+        return Err(ApiError::forbidden("permission_denied", "Access denied: You can only view the waitlist of your own activities or need ManageActivities permission"));
+    }
+
+    let rows = sqlx::query(
+        r#"
+        SELECT p.id, p.user_id, p.waitlist_position, p.registered_at,
+               u.student_id, u.first_name, u.last_name, u.email
+        FROM participations p
+        JOIN users u ON u.id = p.user_id
+        WHERE p.activity_id = $1 AND p.status = 'waitlisted'
+        ORDER BY p.waitlist_position, p.registered_at
+        "#,
+    )
+    .bind(activity_id)
+    .fetch_all(&session_state.db_pool)
+    .await;
+
+    match rows {
+        Ok(rows) => {
+            let waitlist: Vec<Value> = rows
+                .iter()
+                .map(|row| {
+                    json!({
+                        "id": row.get::<Uuid, _>("id"),
+                        "user_id": row.get::<Uuid, _>("user_id"),
+                        "waitlist_position": row.get::<Option<i32>, _>("waitlist_position"),
+                        "registered_at": row.get::<DateTime<Utc>, _>("registered_at"),
+                        "student_id": row.get::<String, _>("student_id"),
+                        "first_name": row.get::<String, _>("first_name"),
+                        "last_name": row.get::<String, _>("last_name"),
+                        "email": row.get::<String, _>("email")
+                    })
+                })
+                .collect();
+
+            let response = json!({
+                "status": "success",
+                "data": {
+                    "waitlist": waitlist,
+                    "total": waitlist.len()
+                },
+                "message": "Waitlist retrieved successfully"
+            });
+            Ok(Json(response))
+        }
+        Err(_) => {
+            Err(ApiError::internal("Failed to retrieve waitlist"))
+        }
+    }
+}
+
+/// Participation statuses in the order analytics report them
+const ANALYTICS_STATUSES: [&str; 7] = [
+    "registered",
+    "waitlisted",
+    "checked_in",
+    "checked_out",
+    "completed",
+    "no_show",
+    "cancelled",
+];
+
+/// Share of `count` in `base` as a percentage with one decimal; 0 when `base` is 0
+fn conversion_rate(count: i64, base: i64) -> f64 {
+    if base == 0 {
+        return 0.0;
+    }
+    (count as f64 * 1000.0 / base as f64).round() / 10.0
+}
+
+/// Participation funnel for one activity (creator or ManageActivities)
+///
+/// Status counts and the hourly check-in histogram (UTC) come from one
+/// `GROUPING SETS` query. Funnel stages are cumulative: a completed participant also
+/// counts as registered and checked in.
+pub async fn get_activity_analytics(
+    State(session_state): State<SessionState>,
+    user: SessionUser,
+    Path(activity_id): Path<Uuid>,
+) -> ApiResult<Json<Value>> {
+    ensure_can_manage_participations(&session_state, &user, activity_id).await?;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT GROUPING(p.status) = 0 AS by_status,
+               p.status::text AS status,
+               EXTRACT(HOUR FROM p.checked_in_at AT TIME ZONE 'UTC')::int AS checkin_hour,
+               COUNT(*) AS count
+        FROM participations p
+        WHERE p.activity_id = $1
+        GROUP BY GROUPING SETS ((p.status), (EXTRACT(HOUR FROM p.checked_in_at AT TIME ZONE 'UTC')::int))
+        "#,
+    )
+    .bind(activity_id)
+    .fetch_all(&session_state.db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to load analytics for activity {}: {}", activity_id, e);
+        ApiError::internal("Failed to load activity analytics")
+    })?;
+
+    let mut status_counts: HashMap<String, i64> = HashMap::new();
+    let mut checkins_by_hour = [0i64; 24];
+    for row in rows {
+        let count: i64 = row.get("count");
+        if row.get::<bool, _>("by_status") {
+            status_counts.insert(row.get("status"), count);
+        } else if let Some(hour) = row.get::<Option<i32>, _>("checkin_hour") {
+            checkins_by_hour[hour as usize] = count;
+        }
+    }
+
+    let count = |statuses: &[&str]| -> i64 {
+        statuses
+            .iter()
+            .map(|s| status_counts.get(*s).copied().unwrap_or(0))
+            .sum()
+    };
+    let registered = count(&["registered", "checked_in", "checked_out", "completed", "no_show"]);
+    let checked_in = count(&["checked_in", "checked_out", "completed"]);
+    let completed = count(&["completed"]);
+
+    let response = json!({
+        "status": "success",
+        "data": {
+            "activity_id": activity_id,
+            "total": status_counts.values().sum::<i64>(),
+            "status_counts": ANALYTICS_STATUSES
+                .iter()
+                .map(|status| json!({ "status": status, "count": count(&[status]) }))
+                .collect::<Vec<_>>(),
+            "funnel": [
+                { "stage": "registered", "count": registered, "conversion_rate": 100.0 },
+                { "stage": "checked_in", "count": checked_in, "conversion_rate": conversion_rate(checked_in, registered) },
+                { "stage": "completed", "count": completed, "conversion_rate": conversion_rate(completed, checked_in) }
+            ],
+            "overall_completion_rate": conversion_rate(completed, registered),
+            "no_show_count": count(&["no_show"]),
+            "no_show_rate": conversion_rate(count(&["no_show"]), registered),
+            "checkins_by_hour": checkins_by_hour
+                .iter()
+                .enumerate()
+                .map(|(hour, count)| json!({ "hour": hour, "count": count }))
+                .collect::<Vec<_>>()
+        },
+        "message": "Activity analytics retrieved successfully"
+    });
+    Ok(Json(response))
+}
+
+/// Scan QR code for check-in/check-out
+pub async fn scan_qr(
+    State(session_state): State<SessionState>,
+    admin: AdminUser, // Only assigned scanners or activity creators can scan QR codes
+    Path(activity_id): Path<Uuid>,
+    ApiJson(request): ApiJson<QrScanRequest>,
+) -> ApiResult<Json<Value>> {
+    ensure_scanner_assigned(&session_state, &admin, activity_id).await?;
+
+    // Parse signed QR payload
+    let qr_payload = match parse_signed_qr_payload(&request.qr_data) {
+        Some(payload) => payload,
+        None => {
+            return Err(ApiError::bad_request("invalid_qr_code", "Invalid QR code format"));
+        }
+    };
+    let user_id = qr_payload.user_id;
+
+    ensure_checkin_window_open(&session_state, activity_id).await?;
+
+    // Verify QR signature against the user's current secret
+    let user_check =
+        sqlx::query(
+            "SELECT student_id, first_name, last_name, prefix, qr_secret FROM users
+             WHERE id = $1 AND qr_secret IS NOT NULL",
+        )
+            .bind(user_id)
+            .fetch_one(&session_state.db_pool)
+            .await;
+
+    let user_data = match user_check {
+        Ok(user) => {
+            if let Err(e) = qr_payload.verify(
+                &user.get::<String, _>("qr_secret"),
+                &session_state.app_config.qr_signing_secret,
+            ) {
+                return Err(ApiError::bad_request("invalid_qr_code", e.to_string()));
+            }
+            user
+        }
+        Err(sqlx::Error::RowNotFound) => {
+            return Err(ApiError::not_found("user_not_found", "User not found"));
+        }
+        Err(_) => {
+            return Err(ApiError::internal("Failed to verify user"));
+        }
+    };
+
+    // Check if user is registered for this activity
+    let participation = sqlx::query(
+        "SELECT id, status::text as status FROM participations WHERE user_id = $1 AND activity_id = $2",
+    )
+    .bind(&user_id)
+    .bind(&activity_id)
+    .fetch_one(&session_state.db_pool)
+    .await;
+
+    let participation = match participation {
+        Ok(p) => p,
+        Err(sqlx::Error::RowNotFound) => {
+            return Err(ApiError::bad_request("not_registered", "User is not registered for this activity"));
+        }
+        Err(_) => {
+            return Err(ApiError::internal("Failed to check participation"));
+        }
+    };
+
+    // Determine next status based on current status
+    let status: String = participation.get("status");
+
+    ensure_scan_not_repeated(&session_state, activity_id, user_id, &status).await?;
+    let new_status =
+        advance_scanned_participation(&session_state, participation.get::<Uuid, _>("id"), &status, "qr_scan").await?;
+
+    let response_data = QrScanResponse {
+        success: true,
+        message: format!("Successfully {} for activity", new_status.replace("_", " ")),
+        participation_status: Some(scanned_participation_status(new_status)),
+        user_name: Some(format!(
+            "{} {}",
+            user_data.get::<String, _>("first_name"),
+            user_data.get::<String, _>("last_name")
+        )),
+        user_name_with_prefix: Some(format!(
+            "{}{} {}",
+            user_data.get::<UserPrefix, _>("prefix").to_thai_string(),
+            user_data.get::<String, _>("first_name"),
+            user_data.get::<String, _>("last_name")
+        )),
+        student_id: Some(user_data.get::<String, _>("student_id")),
+    };
+
+    let response = json!({
+        "status": "success",
+        "data": response_data,
+        "message": "QR code scanned successfully"
+    });
+
+    Ok(Json(response))
+}
+
+/// Debounce: scanner hardware (or a double tap) may fire several reads for a single QR presentation
+pub(crate) async fn ensure_scan_not_repeated(
+    session_state: &SessionState,
+    activity_id: Uuid,
+    user_id: Uuid,
+    status: &str,
+) -> ApiResult<()> {
+    match session_state
+        .session_store
+        .record_qr_scan(activity_id, user_id, session_state.config.scan_cooldown_seconds)
+        .await
+    {
+        Ok(Some(last_scanned_at)) => Err(ApiError::conflict(
+            "scan_cooldown",
+            format!(
+                "This QR code was already scanned {} seconds ago, please wait before scanning again",
+                (Utc::now() - last_scanned_at).num_seconds().max(0)
+            ),
+        )
+        .with(
+            "data",
+            json!({
+                "participation_status": status,
+                "last_scanned_at": last_scanned_at
+            }),
+        )),
+        Ok(None) => Ok(()),
+        Err(e) => {
+            tracing::warn!("Failed to record QR scan for debounce: {}", e);
+            Ok(())
+        }
+    }
+}
+
+/// Move a participation one step along registered → checked_in → checked_out → completed
+/// and credit hours on completion. Returns the new status; `source` goes into the webhook event.
+pub(crate) async fn advance_scanned_participation(
+    session_state: &SessionState,
+    participation_id: Uuid,
+    status: &str,
+    source: &str,
+) -> ApiResult<&'static str> {
+    let (new_status, field_to_update) = match status {
+        "registered" => ("checked_in", "checked_in_at"),
+        "checked_in" => ("checked_out", "checked_out_at"),
+        "checked_out" => ("completed", ""), // No additional field to update
+        _ => {
+            return Err(ApiError::bad_request("invalid_participation_status", "Invalid participation status for QR scan"));
+        }
+    };
+
+    // Update participation status
+    let update_query = if field_to_update.is_empty() {
+        "UPDATE participations SET status = $1::participation_status WHERE id = $2 RETURNING activity_id, user_id"
+            .to_string()
+    } else {
+        format!(
+            "UPDATE participations SET status = $1::participation_status, {} = NOW() WHERE id = $2 RETURNING activity_id, user_id",
+            field_to_update
+        )
+    };
+
+    let update_result = async {
+        let mut tx = session_state.db_pool.begin().await?;
+
+        let ids = sqlx::query_as::<_, (Uuid, Uuid)>(&update_query)
+            .bind(new_status)
+            .bind(participation_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        if new_status == "completed" {
+            award_activity_hours(&mut tx, participation_id).await?;
+        }
+
+        tx.commit().await.map(|_| ids)
+    }
+    .await;
+
+    match update_result {
+        Ok((activity_id, user_id)) => {
+            let event = match new_status {
+                "checked_in" => Some(WebhookEvent::ParticipationCheckedIn),
+                "checked_out" => Some(WebhookEvent::ParticipationCheckedOut),
+                _ => None,
+            };
+            if let Some(event) = event {
+                let data = participation_event_data(participation_id, activity_id, user_id, source);
+                enqueue_webhook_event(session_state, event, data).await;
+                notify_participation_scan(session_state, activity_id, user_id, new_status == "checked_out");
+            }
+            Ok(new_status)
+        }
+        Err(e) => {
+            tracing::error!("Failed to update participation: {}", e);
+            Err(ApiError::internal("Failed to update participation"))
+        }
+    }
+}
+
+pub(crate) fn scanned_participation_status(new_status: &str) -> ParticipationStatus {
+    match new_status {
+        "checked_in" => ParticipationStatus::CheckedIn,
+        "checked_out" => ParticipationStatus::CheckedOut,
+        "completed" => ParticipationStatus::Completed,
+        _ => ParticipationStatus::Registered,
+    }
+}
+
+/// Credit the activity's hours to a completed participation.
+///
+/// Idempotent: `user_activity_hours` is unique per (user, activity), so repeated calls are no-ops.
+pub(crate) async fn award_activity_hours(
+    conn: &mut sqlx::PgConnection,
+    participation_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO user_activity_hours (user_id, activity_id, participation_id, hours)
+        SELECT p.user_id, p.activity_id, p.id, a.hours
+        FROM participations p
+        JOIN activities a ON a.id = p.activity_id
+        WHERE p.id = $1 AND p.status = 'completed'
+        ON CONFLICT (user_id, activity_id) DO NOTHING
+        "#,
+    )
+    .bind(participation_id)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Take back the hours credited to a participation that is no longer completed
+pub(crate) async fn revoke_activity_hours(
+    conn: &mut sqlx::PgConnection,
+    participation_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM user_activity_hours WHERE participation_id = $1")
+        .bind(participation_id)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Move the earliest waitlisted participant into a free seat, if there is one.
+///
+/// Caller must hold the activity row lock (`SELECT ... FOR UPDATE`). Returns the promoted user's id.
+pub(crate) async fn promote_next_waitlisted(
+    conn: &mut sqlx::PgConnection,
+    activity_id: Uuid,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    if !has_free_seat(&mut *conn, activity_id).await? {
+        return Ok(None);
+    }
+
+    let next = sqlx::query_as::<_, (Uuid, Uuid)>(
+        r#"
+        SELECT id, user_id FROM participations
+        WHERE activity_id = $1 AND status = 'waitlisted'
+        ORDER BY waitlist_position, registered_at
+        LIMIT 1
+        FOR UPDATE
+        "#,
+    )
+    .bind(activity_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let Some((participation_id, user_id)) = next else {
+        return Ok(None);
+    };
+
+    sqlx::query(
+        "UPDATE participations SET status = 'registered', waitlist_position = NULL, registered_at = NOW() WHERE id = $1",
+    )
+    .bind(participation_id)
+    .execute(&mut *conn)
+    .await?;
+
+    compact_waitlist(&mut *conn, activity_id).await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO participation_events
+            (participation_id, activity_id, user_id, from_status, to_status, source, reason)
+        VALUES ($1, $2, $3, 'waitlisted', 'registered', 'waitlist', 'Promoted from waitlist')
+        "#,
+    )
+    .bind(participation_id)
+    .bind(activity_id)
+    .bind(user_id)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(Some(user_id))
+}
+
+/// Whether the activity has a seat left (waitlisted and cancelled participations don't hold one).
+///
+/// Caller must hold the activity row lock (`SELECT ... FOR UPDATE`).
+async fn has_free_seat(conn: &mut sqlx::PgConnection, activity_id: Uuid) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar::<_, bool>(
+        r#"
+        SELECT a.max_participants IS NULL OR COUNT(p.id) < a.max_participants
+        FROM activities a
+        LEFT JOIN participations p
+            ON p.activity_id = a.id AND p.status NOT IN ('waitlisted', 'cancelled')
+        WHERE a.id = $1
+        GROUP BY a.id
+        "#,
+    )
+    .bind(activity_id)
+    .fetch_one(conn)
+    .await
+}
+
+/// Renumber waitlist positions 1..n, keeping the existing order
+async fn compact_waitlist(conn: &mut sqlx::PgConnection, activity_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE participations p
+        SET waitlist_position = ranked.position
+        FROM (
+            SELECT id, ROW_NUMBER() OVER (ORDER BY waitlist_position, registered_at)::int AS position
+            FROM participations
+            WHERE activity_id = $1 AND status = 'waitlisted'
+        ) ranked
+        WHERE p.id = ranked.id
+        "#,
+    )
+    .bind(activity_id)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Reject QR scans outside the activity's check-in window
+pub(crate) async fn ensure_checkin_window_open(
+    session_state: &SessionState,
+    activity_id: Uuid,
+) -> ApiResult<()> {
+    let time_zone = session_state.app_config.activity_time_zone();
+    let activity = sqlx::query(&format!(
+        r#"
+        SELECT
+            {} as start_time,
+            {} as end_time,
+            checkin_opens_before_minutes,
+            checkin_closes_after_minutes
+        FROM activities WHERE id = $1 AND deleted_at IS NULL
+        "#,
+        time_zone.start_sql(""),
+        time_zone.end_sql("")
+    ))
+    .bind(activity_id)
+    .fetch_one(&session_state.db_pool)
+    .await;
+
+    let activity = match activity {
+        Ok(row) => row,
+        Err(sqlx::Error::RowNotFound) => {
+            return Err(ApiError::not_found("activity_not_found", "Activity not found"));
+        }
+        Err(_) => {
+            return Err(ApiError::internal("Failed to check activity"));
+        }
+    };
+
+    let config = &session_state.app_config;
+    let window = CheckInWindow::new(
+        activity.get("start_time"),
+        activity.get("end_time"),
+        activity
+            .get::<Option<i32>, _>("checkin_opens_before_minutes")
+            .map(i64::from)
+            .unwrap_or(config.checkin_opens_before_minutes),
+        activity
+            .get::<Option<i32>, _>("checkin_closes_after_minutes")
+            .map(i64::from)
+            .unwrap_or(config.checkin_closes_after_minutes),
+    );
+
+    if !window.contains(Utc::now()) {
+        return Err(ApiError::bad_request(
+            "checkin_window_closed",
+            format!(
+                "Check-in is only allowed between {} and {}",
+                window.opens_at.to_rfc3339(),
+                window.closes_at.to_rfc3339()
+            ),
+        )
+        .with("data", json!(window)));
+    }
+
+    Ok(())
+}
+
+/// Reject QR scans by admins who aren't assigned to the activity.
+/// SuperAdmins and the activity's creator can always scan.
+pub(crate) async fn ensure_scanner_assigned(
+    session_state: &SessionState,
+    admin: &AdminUser,
+    activity_id: Uuid,
+) -> ApiResult<()> {
+    if admin.admin_role.admin_level == AdminLevel::SuperAdmin {
+        return Ok(());
+    }
+
+    let allowed = sqlx::query_scalar::<_, bool>(
+        r#"
+        SELECT EXISTS (SELECT 1 FROM activity_scanners WHERE activity_id = $1 AND user_id = $2)
+            OR EXISTS (SELECT 1 FROM activities WHERE id = $1 AND created_by = $2)
+        "#,
+    )
+    .bind(activity_id)
+    .bind(admin.session_user.user_id)
+    .fetch_one(&session_state.db_pool)
+    .await
+    .map_err(|_| ApiError::internal("Failed to check scanner assignment"))?;
+
+    if !allowed {
+        return Err(ApiError::forbidden(
+            "scanner_not_assigned",
+            "You are not assigned to scan QR codes for this activity",
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{insert_user, test_state};
+    use std::sync::Arc;
+
+    fn student(user_id: Uuid, student_id: &str) -> SessionUser {
+        SessionUser {
+            user_id,
+            student_id: student_id.to_string(),
+            email: format!("{}@test.local", student_id),
+            first_name: "Test".to_string(),
+            last_name: "Student".to_string(),
+            department_id: None,
+            admin_role: None,
+            session_id: Uuid::new_v4().to_string(),
+            permissions: Vec::new(),
+            faculty_id: None,
+            is_impersonated: false,
+            impersonated_by: None,
+        }
+    }
+
+    async fn insert_activity(pool: &sqlx::PgPool, creator_id: Uuid, max_participants: Option<i32>) -> Uuid {
+        sqlx::query_scalar(
+            "INSERT INTO activities (title, description, location, academic_year, organizer,
+                start_date, end_date, start_time_only, end_time_only, hours, max_participants,
+                status, created_by)
+             VALUES ('Race', 'Race test', 'Lab', '2567', 'Test', CURRENT_DATE + 1, CURRENT_DATE + 1,
+                '09:00', '17:00', 1, $1, 'published', $2)
+             RETURNING id",
+        )
+        .bind(max_participants)
+        .bind(creator_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    #[test]
+    fn test_is_short_search_token() {
+        assert!(is_short_search_token("AI"));
+        assert!(is_short_search_token("ค่"));
+        assert!(!is_short_search_token("workshop"));
+        assert!(!is_short_search_token("a b"));
+    }
+
+    /// N+1 concurrent registrations against max_participants = N must admit exactly N
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_participate_capacity_under_concurrency() {
+        const CAPACITY: usize = 5;
+
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let tag = &Uuid::new_v4().simple().to_string()[..8];
+        let creator_id = insert_user(&pool, &format!("c{}", tag), None).await;
+        let activity_id = insert_activity(&pool, creator_id, Some(CAPACITY as i32)).await;
+
+        let mut handles = Vec::new();
+        for i in 0..=CAPACITY {
+            let student_id = format!("s{}{}", tag, i);
+            let user_id = insert_user(&pool, &student_id, None).await;
+            let state = state.clone();
+            handles.push(tokio::spawn(async move {
+                participate(State(state), student(user_id, &student_id), Path(activity_id), None).await
+            }));
+        }
+
+        let mut succeeded = 0;
+        let mut conflicts = 0;
+        for handle in handles {
+            match handle.await.unwrap() {
+                Ok(_) => succeeded += 1,
+                Err(ApiError::Conflict(_)) => conflicts += 1,
+                Err(error) => panic!("unexpected error: {:?}", error),
+            }
+        }
+
+        assert_eq!(succeeded, CAPACITY);
+        assert_eq!(conflicts, 1);
+
+        sqlx::query("DELETE FROM users WHERE student_id LIKE $1")
+            .bind(format!("%{}%", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    /// A stale expected_updated_at must be rejected with 409 and the current timestamp
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_update_activity_optimistic_locking() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let tag = &Uuid::new_v4().simple().to_string()[..8];
+        let creator_student_id = format!("c{}", tag);
+        let creator_id = insert_user(&pool, &creator_student_id, None).await;
+        let activity_id = insert_activity(&pool, creator_id, None).await;
+
+        let loaded_at: DateTime<Utc> =
+            sqlx::query_scalar("SELECT updated_at FROM activities WHERE id = $1")
+                .bind(activity_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+
+        let update = |title: &str| UpdateActivityRequest {
+            title: Some(title.to_string()),
+            description: None,
+            location: None,
+            start_time: None,
+            end_time: None,
+            max_participants: None,
+            status: None,
+            faculty_id: None,
+            department_id: None,
+            allow_waitlist: None,
+            registration_deadline: None,
+            visibility: None,
+            publish_at: None,
+            expected_updated_at: Some(loaded_at),
+        };
+
+        let first = update_activity(
+            State(state.clone()),
+            student(creator_id, &creator_student_id),
+            Path(activity_id),
+            ApiJson(update("First edit")),
+        )
+        .await;
+        assert!(first.is_ok());
+
+        let stale = update_activity(
+            State(state.clone()),
+            student(creator_id, &creator_student_id),
+            Path(activity_id),
+            ApiJson(update("Second edit")),
+        )
+        .await;
+        match stale {
+            Err(ApiError::Conflict(detail)) => {
+                assert_eq!(detail.code, "stale_update");
+                assert!(!detail.extra["current_updated_at"].is_null());
+            }
+            other => panic!("expected 409 conflict, got {:?}", other.map(|j| j.0)),
+        }
+
+        sqlx::query("DELETE FROM users WHERE student_id LIKE $1")
+            .bind(format!("%{}%", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    /// Completing a participation twice must only credit the activity's hours once
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_update_rejects_publish_at_after_start() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let tag = &Uuid::new_v4().simple().to_string()[..8];
+        let creator_student_id = format!("c{}", tag);
+        let creator_id = insert_user(&pool, &creator_student_id, None).await;
+        // Starts tomorrow at 09:00 UTC
+        let activity_id = insert_activity(&pool, creator_id, None).await;
+
+        let request = |publish_at| UpdateActivityRequest {
+            title: None,
+            description: None,
+            location: None,
+            start_time: None,
+            end_time: None,
+            max_participants: None,
+            status: None,
+            faculty_id: None,
+            department_id: None,
+            allow_waitlist: None,
+            registration_deadline: None,
+            visibility: None,
+            publish_at: Some(publish_at),
+            expected_updated_at: None,
+        };
+
+        let too_late = update_activity(
+            State(state.clone()),
+            student(creator_id, &creator_student_id),
+            Path(activity_id),
+            ApiJson(request(Utc::now() + chrono::Duration::days(3))),
+        )
+        .await;
+        let scheduled = update_activity(
+            State(state),
+            student(creator_id, &creator_student_id),
+            Path(activity_id),
+            ApiJson(request(Utc::now() + chrono::Duration::minutes(5))),
+        )
+        .await;
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(creator_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        match too_late {
+            Err(ApiError::BadRequest(detail)) => assert_eq!(detail.code, "invalid_publish_at"),
+            other => panic!("expected invalid_publish_at, got {:?}", other.map(|Json(body)| body)),
+        }
+        let Json(body) = scheduled.unwrap();
+        assert!(body["data"]["publish_at"].is_string());
+    }
+
+    /// Times are stored as wall-clock time in ACTIVITY_TIMEZONE and read back as the same instant
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_activity_times_use_configured_timezone() {
+        use chrono::SubsecRound;
+
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let utc_state = test_state(&pool);
+        let mut app_config = (*utc_state.app_config).clone();
+        app_config.activity_timezone = Some("Asia/Bangkok".to_string());
+        let state = SessionState {
+            app_config: Arc::new(app_config),
+            ..utc_state.clone()
+        };
+
+        let tag = &Uuid::new_v4().simple().to_string()[..8];
+        let creator_student_id = format!("c{}", tag);
+        let creator_id = insert_user(&pool, &creator_student_id, None).await;
+        let activity_id = insert_activity(&pool, creator_id, None).await;
+
+        let start_time = Utc::now().trunc_subsecs(0) - chrono::Duration::minutes(10);
+        let end_time = start_time + chrono::Duration::hours(1);
+        let updated = update_activity(
+            State(state.clone()),
+            student(creator_id, &creator_student_id),
+            Path(activity_id),
+            ApiJson(UpdateActivityRequest {
+                title: None,
+                description: None,
+                location: None,
+                start_time: Some(start_time),
+                end_time: Some(end_time),
+                max_participants: None,
+                status: None,
+                faculty_id: None,
+                department_id: None,
+                allow_waitlist: None,
+                registration_deadline: None,
+                visibility: None,
+                publish_at: None,
+                expected_updated_at: None,
+            }),
+        )
+        .await;
+        let stored_local: chrono::NaiveDateTime =
+            sqlx::query_scalar("SELECT start_date + start_time_only FROM activities WHERE id = $1")
+                .bind(activity_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        let window_in_bangkok = ensure_checkin_window_open(&state, activity_id).await;
+        let window_in_utc = ensure_checkin_window_open(&utc_state, activity_id).await;
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(creator_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let Json(body) = updated.unwrap();
+        assert_eq!(body["data"]["start_time"], json!(start_time));
+        assert_eq!(stored_local, (start_time + chrono::Duration::hours(7)).naive_utc());
+        assert!(window_in_bangkok.is_ok());
+        // Read as UTC the same columns start seven hours from now
+        assert!(window_in_utc.is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_award_activity_hours_is_idempotent() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let tag = &Uuid::new_v4().simple().to_string()[..8];
+        let creator_id = insert_user(&pool, &format!("c{}", tag), None).await;
+        let student_id = format!("s{}", tag);
+        let user_id = insert_user(&pool, &student_id, None).await;
+        let activity_id = insert_activity(&pool, creator_id, None).await;
+
+        let participation_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO participations (user_id, activity_id, status, checked_in_at, checked_out_at)
+             VALUES ($1, $2, 'completed', NOW() - INTERVAL '1 hour', NOW()) RETURNING id",
+        )
+        .bind(user_id)
+        .bind(activity_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        let mut conn = pool.acquire().await.unwrap();
+        award_activity_hours(&mut conn, participation_id).await.unwrap();
+        award_activity_hours(&mut conn, participation_id).await.unwrap();
+
+        let Json(body) = crate::handlers::user::get_user_hours(
+            State(state.clone()),
+            student(user_id, &student_id),
+            Path(user_id),
+        )
+        .await
+        .unwrap();
+        assert_eq!(body["data"]["total_hours"], 1);
+        assert_eq!(body["data"]["by_academic_year"][0]["academic_year"], "2567");
+
+        let transcript = crate::handlers::user::get_user_transcript(
+            State(state),
+            student(user_id, &student_id),
+            Path(user_id),
+            axum::extract::Query(HashMap::from([("format".to_string(), "csv".to_string())])),
+        )
+        .await
+        .unwrap();
+        let csv = axum::body::to_bytes(transcript.into_body(), usize::MAX).await.unwrap();
+        let csv = String::from_utf8(csv.to_vec()).unwrap();
+        assert!(csv.contains("Race,Test,"));
+        assert!(csv.ends_with("Total,,,,,1,\r\n"));
+
+        sqlx::query("DELETE FROM users WHERE student_id LIKE $1")
+            .bind(format!("%{}%", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_participations_csv_export() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let tag = &Uuid::new_v4().simple().to_string()[..8];
+        let creator_student_id = format!("c{}", tag);
+        let creator_id = insert_user(&pool, &creator_student_id, None).await;
+        let student_id = format!("s{}", tag);
+        let user_id = insert_user(&pool, &student_id, None).await;
+        let activity_id = insert_activity(&pool, creator_id, None).await;
+
+        sqlx::query("INSERT INTO participations (user_id, activity_id) VALUES ($1, $2)")
+            .bind(user_id)
+            .bind(activity_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let response = get_activity_participations(
+            State(state.clone()),
+            student(creator_id, &creator_student_id),
+            Path(activity_id),
+            Query(HashMap::from([
+                ("format".to_string(), "csv".to_string()),
+                ("status".to_string(), "registered".to_string()),
+            ])),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            response.headers()[axum::http::header::CONTENT_TYPE],
+            "text/csv; charset=utf-8"
+        );
+
+        let csv = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let csv = String::from_utf8(csv.to_vec()).unwrap();
+        let lines: Vec<&str> = csv.trim_start_matches('\u{feff}').lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("student_id,name,email,department,status"));
+        assert!(lines[1].starts_with(&format!("{},Test User,{}@test.local,,registered,", student_id, student_id)));
+
+        let response = get_activity_participations(
+            State(state),
+            student(creator_id, &creator_student_id),
+            Path(activity_id),
+            Query(HashMap::from([("format".to_string(), "xlsx".to_string())])),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            response.headers()[axum::http::header::CONTENT_TYPE],
+            crate::utils::xlsx::XLSX_CONTENT_TYPE
+        );
+        assert_eq!(
+            response.headers()[axum::http::header::CONTENT_DISPOSITION],
+            format!("attachment; filename=\"participations_{}.xlsx\"", activity_id).as_str()
+        );
+        let xlsx = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(xlsx.starts_with(b"PK\x03\x04"));
+
+        sqlx::query("DELETE FROM users WHERE student_id LIKE $1")
+            .bind(format!("%{}%", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_cancel_activity_keeps_participations() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let tag = &Uuid::new_v4().simple().to_string()[..8];
+        let creator_student_id = format!("c{}", tag);
+        let creator_id = insert_user(&pool, &creator_student_id, None).await;
+        let activity_id = insert_activity(&pool, creator_id, None).await;
+        for i in 0..2 {
+            let user_id = insert_user(&pool, &format!("s{}{}", tag, i), None).await;
+            sqlx::query("INSERT INTO participations (user_id, activity_id) VALUES ($1, $2)")
+                .bind(user_id)
+                .bind(activity_id)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        let cancel = || {
+            cancel_activity(
+                State(state.clone()),
+                student(creator_id, &creator_student_id),
+                Path(activity_id),
+                ApiJson(CancelActivityRequest {
+                    reason: "Venue unavailable".to_string(),
+                }),
+            )
+        };
+
+        let Json(body) = cancel().await.unwrap();
+        assert_eq!(body["data"]["cancelled_participations"], 2);
+
+        let cancelled: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM participations WHERE activity_id = $1 AND status = 'cancelled'",
+        )
+        .bind(activity_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(cancelled, 2);
+
+        match cancel().await {
+            Err(ApiError::Conflict(_)) => {}
+            other => panic!("expected 409 for repeated cancel, got {:?}", other.map(|j| j.0)),
+        }
+
+        sqlx::query("DELETE FROM email_queue WHERE metadata->>'activity_id' = $1")
+            .bind(activity_id.to_string())
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM users WHERE student_id LIKE $1")
+            .bind(format!("%{}%", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_soft_deleted_activity_can_be_restored() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let tag = &Uuid::new_v4().simple().to_string()[..8];
+        let creator_student_id = format!("c{}", tag);
+        let creator_id = insert_user(&pool, &creator_student_id, None).await;
+        let activity_id = insert_activity(&pool, creator_id, None).await;
+        let creator = || student(creator_id, &creator_student_id);
+
+        let Json(body) = delete_activity(State(state.clone()), creator(), Path(activity_id))
+            .await
+            .unwrap();
+        assert_eq!(body["status"], "success");
+        let deleted_at: Option<DateTime<Utc>> =
+            sqlx::query_scalar("SELECT deleted_at FROM activities WHERE id = $1")
+                .bind(activity_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert!(deleted_at.is_some());
+        match get_activity(State(state.clone()), creator(), Path(activity_id)).await {
+            Err(ApiError::NotFound(_)) => {}
+            other => panic!("expected 404 for a deleted activity, got {:?}", other.map(|j| j.0)),
+        }
+
+        let Json(body) = restore_activity(State(state.clone()), creator(), Path(activity_id))
+            .await
+            .unwrap();
+        assert_eq!(body["status"], "success");
+        assert!(get_activity(State(state.clone()), creator(), Path(activity_id))
+            .await
+            .is_ok());
+        match restore_activity(State(state.clone()), creator(), Path(activity_id)).await {
+            Err(ApiError::Conflict(_)) => {}
+            other => panic!("expected 409 for restoring a live activity, got {:?}", other.map(|j| j.0)),
+        }
+
+        sqlx::query("DELETE FROM users WHERE student_id LIKE $1")
+            .bind(format!("%{}%", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_clone_activity_creates_draft_on_new_dates() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let tag = &Uuid::new_v4().simple().to_string()[..8];
+        let creator_student_id = format!("c{}", tag);
+        let creator_id = insert_user(&pool, &creator_student_id, None).await;
+        let activity_id = insert_activity(&pool, creator_id, Some(30)).await;
+        sqlx::query("INSERT INTO participations (user_id, activity_id) VALUES ($1, $2)")
+            .bind(insert_user(&pool, &format!("s{}", tag), None).await)
+            .bind(activity_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let new_date = Utc::now().date_naive() + chrono::Duration::days(8);
+        let Json(body) = clone_activity(
+            State(state.clone()),
+            student(creator_id, &creator_student_id),
+            Path(activity_id),
+            ApiJson(CloneActivityRequest { start_date: Some(new_date), end_date: None }),
+        )
+        .await
+        .unwrap();
+        let clone_id: Uuid = serde_json::from_value(body["data"]["id"].clone()).unwrap();
+
+        let clone = sqlx::query(
+            "SELECT title, status::text AS status, start_date, end_date, max_participants,
+                    (SELECT COUNT(*) FROM participations WHERE activity_id = $1) AS participants
+             FROM activities WHERE id = $1",
+        )
+        .bind(clone_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(clone.get::<String, _>("title"), "Race (copy)");
+        assert_eq!(clone.get::<String, _>("status"), "draft");
+        assert_eq!(clone.get::<NaiveDate, _>("start_date"), new_date);
+        assert_eq!(clone.get::<NaiveDate, _>("end_date"), new_date);
+        assert_eq!(clone.get::<Option<i32>, _>("max_participants"), Some(30));
+        assert_eq!(clone.get::<i64, _>("participants"), 0);
+
+        let other_student_id = format!("o{}", tag);
+        let other_id = insert_user(&pool, &other_student_id, None).await;
+        match clone_activity(
+            State(state.clone()),
+            student(other_id, &other_student_id),
+            Path(activity_id),
+            ApiJson(CloneActivityRequest { start_date: None, end_date: None }),
+        )
+        .await
+        {
+            Err(ApiError::Forbidden(_)) => {}
+            other => panic!("expected 403 for a non-creator, got {:?}", other.map(|j| j.0)),
+        }
+
+        sqlx::query("DELETE FROM users WHERE student_id LIKE $1")
+            .bind(format!("%{}%", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    /// Full activity with allow_waitlist queues extra registrations and promotes them in order
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_waitlist_promotion_on_withdraw() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let tag = &Uuid::new_v4().simple().to_string()[..8];
+        let creator_id = insert_user(&pool, &format!("c{}", tag), None).await;
+        let activity_id = insert_activity(&pool, creator_id, Some(1)).await;
+        sqlx::query("UPDATE activities SET allow_waitlist = TRUE WHERE id = $1")
+            .bind(activity_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let mut students = Vec::new();
+        for i in 0..3 {
+            let student_id = format!("s{}{}", tag, i);
+            let user_id = insert_user(&pool, &student_id, None).await;
+            let Json(body) = participate(State(state.clone()), student(user_id, &student_id), Path(activity_id), None)
+                .await
+                .unwrap();
+            students.push((user_id, student_id, body));
+        }
+
+        assert_eq!(students[0].2["data"]["status"], "registered");
+        assert_eq!(students[1].2["data"]["status"], "waitlisted");
+        assert_eq!(students[1].2["data"]["waitlist_position"], 1);
+        assert_eq!(students[2].2["data"]["waitlist_position"], 2);
+
+        let (user_id, student_id, _) = &students[0];
+        let Json(body) = withdraw_participation(State(state.clone()), student(*user_id, student_id), Path(activity_id))
+            .await
+            .unwrap();
+        assert_eq!(body["data"]["promoted_user_id"], json!(students[1].0));
+
+        let Json(body) = get_activity_waitlist(
+            State(state.clone()),
+            student(creator_id, &format!("c{}", tag)),
+            Path(activity_id),
+        )
+        .await
+        .unwrap();
+        assert_eq!(body["data"]["total"], 1);
+        assert_eq!(body["data"]["waitlist"][0]["user_id"], json!(students[2].0));
+        assert_eq!(body["data"]["waitlist"][0]["waitlist_position"], 1);
+
+        sqlx::query("DELETE FROM email_queue WHERE metadata->>'activity_id' = $1")
+            .bind(activity_id.to_string())
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM users WHERE student_id LIKE $1")
+            .bind(format!("%{}%", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_admin_cancel_promotes_and_reinstate_needs_seat() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let tag = &Uuid::new_v4().simple().to_string()[..8];
+        let creator_student_id = format!("c{}", tag);
+        let creator_id = insert_user(&pool, &creator_student_id, None).await;
+        let activity_id = insert_activity(&pool, creator_id, Some(1)).await;
+        sqlx::query("UPDATE activities SET allow_waitlist = TRUE WHERE id = $1")
+            .bind(activity_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let mut user_ids = Vec::new();
+        for i in 0..2 {
+            let student_id = format!("s{}{}", tag, i);
+            let user_id = insert_user(&pool, &student_id, None).await;
+            let _ = participate(State(state.clone()), student(user_id, &student_id), Path(activity_id), None)
+                .await
+                .unwrap();
+            user_ids.push(user_id);
+        }
+
+        let set_status = |user_id: Uuid, status: ParticipationStatus| {
+            update_participation_status(
+                State(state.clone()),
+                student(creator_id, &creator_student_id),
+                Path((activity_id, user_id)),
+                ApiJson(UpdateParticipationStatus {
+                    status,
+                    reason: "Organizer change".to_string(),
+                    notes: None,
+                }),
+            )
+        };
+
+        // Cancelling the registered student hands the seat to the waitlisted one
+        let _ = set_status(user_ids[0], ParticipationStatus::Cancelled).await.unwrap();
+        let promoted: ParticipationStatus = sqlx::query_scalar(
+            "SELECT status FROM participations WHERE user_id = $1 AND activity_id = $2",
+        )
+        .bind(user_ids[1])
+        .bind(activity_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        // The activity is full again, so the cancelled student can't be reinstated
+        let reinstated = set_status(user_ids[0], ParticipationStatus::Registered).await;
+
+        let Json(activity) = get_activity(State(state.clone()), student(creator_id, &creator_student_id), Path(activity_id))
+            .await
+            .unwrap();
+
+        sqlx::query("DELETE FROM email_queue WHERE metadata->>'activity_id' = $1")
+            .bind(activity_id.to_string())
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM users WHERE student_id LIKE $1")
+            .bind(format!("%{}%", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(promoted, ParticipationStatus::Registered);
+        assert!(matches!(reinstated, Err(ApiError::Conflict(detail)) if detail.code == "activity_full"));
+        assert_eq!(activity["data"]["current_participants"], 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_participant_updates_own_notes() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let tag = &Uuid::new_v4().simple().to_string()[..8];
+        let creator_id = insert_user(&pool, &format!("c{}", tag), None).await;
+        let activity_id = insert_activity(&pool, creator_id, None).await;
+        let student_id = format!("s{}", tag);
+        let user_id = insert_user(&pool, &student_id, None).await;
+        let outsider_id = format!("o{}", tag);
+        let outsider_user_id = insert_user(&pool, &outsider_id, None).await;
+
+        let notes = |notes: &str| ApiJson(UpdateParticipationNotes { notes: Some(notes.to_string()) });
+
+        let not_registered = update_participation_notes(
+            State(state.clone()),
+            student(outsider_user_id, &outsider_id),
+            Path(activity_id),
+            notes("Vegetarian"),
+        )
+        .await;
+
+        let _ = participate(State(state.clone()), student(user_id, &student_id), Path(activity_id), None)
+            .await
+            .unwrap();
+        let Json(body) = update_participation_notes(
+            State(state.clone()),
+            student(user_id, &student_id),
+            Path(activity_id),
+            notes("  Vegetarian  "),
+        )
+        .await
+        .unwrap();
+        let too_long = update_participation_notes(
+            State(state.clone()),
+            student(user_id, &student_id),
+            Path(activity_id),
+            notes(&"x".repeat(MAX_PARTICIPATION_NOTES_LEN + 1)),
+        )
+        .await;
+        let Json(cleared) = update_participation_notes(
+            State(state.clone()),
+            student(user_id, &student_id),
+            Path(activity_id),
+            notes(" "),
+        )
+        .await
+        .unwrap();
+
+        sqlx::query("DELETE FROM users WHERE student_id LIKE $1")
+            .bind(format!("%{}%", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(matches!(not_registered, Err(ApiError::NotFound(detail)) if detail.code == "not_registered"));
+        assert_eq!(body["data"]["notes"], "Vegetarian");
+        assert!(matches!(too_long, Err(ApiError::Validation(detail)) if detail.code == "notes_too_long"));
+        assert_eq!(cleared["data"]["notes"], Value::Null);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_activity_feedback_upsert_and_average() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let tag = &Uuid::new_v4().simple().to_string()[..8];
+        let creator_student_id = format!("c{}", tag);
+        let creator_id = insert_user(&pool, &creator_student_id, None).await;
+        let activity_id = insert_activity(&pool, creator_id, None).await;
+
+        let mut students = Vec::new();
+        for (i, status) in ["completed", "completed", "registered"].iter().enumerate() {
+            let student_id = format!("s{}{}", tag, i);
+            let user_id = insert_user(&pool, &student_id, None).await;
+            sqlx::query("INSERT INTO participations (user_id, activity_id, status) VALUES ($1, $2, $3::participation_status)")
+                .bind(user_id)
+                .bind(activity_id)
+                .bind(status)
+                .execute(&pool)
+                .await
+                .unwrap();
+            students.push(student(user_id, &student_id));
+        }
+
+        let feedback = |rating: i16, comment: Option<&str>| {
+            ApiJson(SubmitActivityFeedback { rating, comment: comment.map(str::to_string) })
+        };
+        let submit = |user: &SessionUser, rating: i16, comment: Option<&str>| {
+            submit_activity_feedback(State(state.clone()), user.clone(), Path(activity_id), feedback(rating, comment))
+        };
+
+        let invalid = submit(&students[0], 6, None).await;
+        let _ = submit(&students[0], 2, None).await.unwrap();
+        let _ = submit(&students[0], 5, Some("Great workshop")).await.unwrap();
+        let _ = submit(&students[1], 4, None).await.unwrap();
+        let not_completed = submit(&students[2], 5, None).await;
+
+        let forbidden = get_activity_feedback(State(state.clone()), students[0].clone(), Path(activity_id)).await;
+        let creator = student(creator_id, &creator_student_id);
+        let Json(body) = get_activity_feedback(State(state.clone()), creator.clone(), Path(activity_id))
+            .await
+            .unwrap();
+        let Json(organizer_view) = get_activity(State(state.clone()), creator, Path(activity_id)).await.unwrap();
+        let Json(student_view) = get_activity(State(state.clone()), students[0].clone(), Path(activity_id))
+            .await
+            .unwrap();
+
+        sqlx::query("DELETE FROM users WHERE student_id LIKE $1")
+            .bind(format!("%{}%", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(matches!(invalid, Err(ApiError::Validation(detail)) if detail.code == "invalid_rating"));
+        assert!(matches!(not_completed, Err(ApiError::Forbidden(detail)) if detail.code == "activity_not_completed"));
+        assert!(matches!(forbidden, Err(ApiError::Forbidden(_))));
+        assert_eq!(body["data"]["total"], 2);
+        assert_eq!(body["data"]["average_rating"], 4.5);
+        assert!(body["data"]["feedback"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|f| f["rating"] == 5 && f["comment"] == "Great workshop"));
+        assert_eq!(organizer_view["data"]["average_rating"], 4.5);
+        assert!(student_view["data"].get("average_rating").is_none());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_participate_rejected_after_registration_deadline() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let tag = &Uuid::new_v4().simple().to_string()[..8];
+        let creator_id = insert_user(&pool, &format!("c{}", tag), None).await;
+        let activity_id = insert_activity(&pool, creator_id, None).await;
+        sqlx::query("UPDATE activities SET registration_deadline = NOW() - INTERVAL '1 minute' WHERE id = $1")
+            .bind(activity_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let student_id = format!("s{}", tag);
+        let user_id = insert_user(&pool, &student_id, None).await;
+        match participate(State(state.clone()), student(user_id, &student_id), Path(activity_id), None).await {
+            Err(ApiError::BadRequest(detail)) => assert_eq!(detail.code, "registration_deadline_passed"),
+            other => panic!("expected 400 after the deadline, got {:?}", other.map(|j| j.0)),
+        }
+
+        sqlx::query("DELETE FROM users WHERE student_id LIKE $1")
+            .bind(format!("%{}%", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_private_activity_hidden_from_other_students() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let tag = &Uuid::new_v4().simple().to_string()[..8];
+        let creator_student_id = format!("c{}", tag);
+        let creator_id = insert_user(&pool, &creator_student_id, None).await;
+        let activity_id = insert_activity(&pool, creator_id, None).await;
+        sqlx::query("UPDATE activities SET visibility = 'private', title = $2 WHERE id = $1")
+            .bind(activity_id)
+            .bind(format!("Private {}", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let other_student_id = format!("s{}", tag);
+        let other_id = insert_user(&pool, &other_student_id, None).await;
+
+        assert!(get_activity(State(state.clone()), student(creator_id, &creator_student_id), Path(activity_id))
+            .await
+            .is_ok());
+        match get_activity(State(state.clone()), student(other_id, &other_student_id), Path(activity_id)).await {
+            Err(ApiError::NotFound(_)) => {}
+            other => panic!("expected 404 for a private activity, got {:?}", other.map(|j| j.0)),
+        }
+        // Knowing the id is not enough to register
+        match participate(State(state.clone()), student(other_id, &other_student_id), Path(activity_id), None).await {
+            Err(ApiError::NotFound(_)) => {}
+            other => panic!("expected 404 registering for a private activity, got {:?}", other.map(|j| j.0)),
+        }
+
+        let search = HashMap::from([("search".to_string(), tag.to_string())]);
+        let Json(body) = get_activities(
+            State(state.clone()),
+            student(other_id, &other_student_id),
+            Query(search.clone()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(body["data"]["total_count"], 0);
+
+        let Json(body) = get_activities(
+            State(state.clone()),
+            student(creator_id, &creator_student_id),
+            Query(search),
+        )
+        .await
+        .unwrap();
+        assert_eq!(body["data"]["total_count"], 1);
+
+        sqlx::query("DELETE FROM users WHERE student_id LIKE $1")
+            .bind(format!("%{}%", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_public_activities_only_lists_public_published() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let tag = &Uuid::new_v4().simple().to_string()[..8];
+        let creator_id = insert_user(&pool, &format!("c{}", tag), None).await;
+        let public_id = insert_activity(&pool, creator_id, None).await;
+        let private_id = insert_activity(&pool, creator_id, None).await;
+        let draft_id = insert_activity(&pool, creator_id, None).await;
+        sqlx::query("UPDATE activities SET visibility = 'private' WHERE id = $1")
+            .bind(private_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE activities SET status = 'draft' WHERE id = $1")
+            .bind(draft_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let params = HashMap::from([("limit".to_string(), "100".to_string())]);
+        let Json(body) = get_public_activities(State(state.clone()), Query(params))
+            .await
+            .unwrap();
+        let ids: Vec<&str> = body["data"]["activities"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|a| a["id"].as_str().unwrap())
+            .collect();
+
+        assert!(ids.contains(&public_id.to_string().as_str()));
+        assert!(!ids.contains(&private_id.to_string().as_str()));
+        assert!(!ids.contains(&draft_id.to_string().as_str()));
+        assert!(body["data"]["activities"][0].get("created_by").is_none());
+
+        sqlx::query("DELETE FROM users WHERE student_id LIKE $1")
+            .bind(format!("%{}%", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_get_activities_start_date_range() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let tag = &Uuid::new_v4().simple().to_string()[..8];
+        let student_id = format!("c{}", tag);
+        let creator_id = insert_user(&pool, &student_id, None).await;
+        for offset_days in [1, 10] {
+            let activity_id = insert_activity(&pool, creator_id, None).await;
+            sqlx::query(
+                "UPDATE activities SET title = $2, start_date = CURRENT_DATE + $3, end_date = CURRENT_DATE + $3
+                 WHERE id = $1",
+            )
+            .bind(activity_id)
+            .bind(format!("Range {}", tag))
+            .bind(offset_days)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let today = Utc::now().date_naive();
+        let query = |from: i64, to: i64| {
+            Query(HashMap::from([
+                ("search".to_string(), tag.to_string()),
+                ("start_from".to_string(), (today + chrono::Duration::days(from)).to_string()),
+                ("start_to".to_string(), (today + chrono::Duration::days(to)).to_string()),
+            ]))
+        };
+
+        let Json(body) = get_activities(State(state.clone()), student(creator_id, &student_id), query(0, 5))
+            .await
+            .unwrap();
+        assert_eq!(body["data"]["total_count"], 1);
+        assert_eq!(body["data"]["activities"].as_array().unwrap().len(), 1);
+
+        match get_activities(State(state.clone()), student(creator_id, &student_id), query(5, 0)).await {
+            Err(ApiError::BadRequest(_)) => {}
+            other => panic!("expected 400 for an inverted range, got {:?}", other.map(|j| j.0)),
+        }
+
+        sqlx::query("DELETE FROM users WHERE student_id LIKE $1")
+            .bind(format!("%{}%", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_participate_detects_schedule_conflict() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let tag = &Uuid::new_v4().simple().to_string()[..8];
+        let creator_id = insert_user(&pool, &format!("c{}", tag), None).await;
+        // Both activities run 09:00-17:00 on the same day
+        let first_id = insert_activity(&pool, creator_id, None).await;
+        let second_id = insert_activity(&pool, creator_id, None).await;
+
+        let student_id = format!("s{}", tag);
+        let user_id = insert_user(&pool, &student_id, None).await;
+        let register = |activity_id: Uuid, allow_overlap: Option<bool>| {
+            participate(
+                State(state.clone()),
+                student(user_id, &student_id),
+                Path(activity_id),
+                Some(ApiJson(ParticipateRequest { allow_overlap })),
+            )
+        };
+
+        assert!(register(first_id, None).await.is_ok());
+
+        match register(second_id, None).await {
+            Err(ApiError::Conflict(detail)) => {
+                assert_eq!(detail.code, "schedule_conflict");
+                assert_eq!(detail.extra["data"]["conflicting_activity"]["id"], json!(first_id));
+            }
+            other => panic!("expected 409 for overlapping activity, got {:?}", other.map(|j| j.0)),
+        }
+
+        assert!(register(second_id, Some(true)).await.is_ok());
+
+        sqlx::query("DELETE FROM users WHERE student_id LIKE $1")
+            .bind(format!("%{}%", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_manual_check_in_by_user_id() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let tag = &Uuid::new_v4().simple().to_string()[..8];
+        let creator_student_id = format!("c{}", tag);
+        let creator_id = insert_user(&pool, &creator_student_id, None).await;
+        let activity_id = insert_activity(&pool, creator_id, None).await;
+        let user_id = insert_user(&pool, &format!("s{}", tag), None).await;
+        sqlx::query("INSERT INTO participations (user_id, activity_id) VALUES ($1, $2)")
+            .bind(user_id)
+            .bind(activity_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let Json(body) = update_participation_status(
+            State(state.clone()),
+            student(creator_id, &creator_student_id),
+            Path((activity_id, user_id)),
+            ApiJson(UpdateParticipationStatus {
+                status: ParticipationStatus::CheckedIn,
+                reason: "Phone battery died".to_string(),
+                notes: Some("Checked in at the front desk".to_string()),
+            }),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(body["data"]["status"], "checked_in");
+        assert_eq!(body["data"]["notes"], "Checked in at the front desk");
+        assert!(!body["data"]["checked_in_at"].is_null());
+
+        let changed_by: Option<Uuid> = sqlx::query_scalar(
+            "SELECT changed_by FROM participation_events WHERE activity_id = $1 AND to_status = 'checked_in'",
+        )
+        .bind(activity_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(changed_by, Some(creator_id));
+
+        sqlx::query("DELETE FROM users WHERE student_id LIKE $1")
+            .bind(format!("%{}%", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_undoing_completion_revokes_hours() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let tag = &Uuid::new_v4().simple().to_string()[..8];
+        let creator_student_id = format!("c{}", tag);
+        let creator_id = insert_user(&pool, &creator_student_id, None).await;
+        let activity_id = insert_activity(&pool, creator_id, None).await;
+        let user_id = insert_user(&pool, &format!("s{}", tag), None).await;
+        sqlx::query(
+            "INSERT INTO participations (user_id, activity_id, status, checked_in_at)
+             VALUES ($1, $2, 'checked_in', NOW() - INTERVAL '1 hour')",
+        )
+        .bind(user_id)
+        .bind(activity_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let mut awarded_hours = Vec::new();
+        for status in [ParticipationStatus::Completed, ParticipationStatus::CheckedOut] {
+            let _ = update_participation_status(
+                State(state.clone()),
+                student(creator_id, &creator_student_id),
+                Path((activity_id, user_id)),
+                ApiJson(UpdateParticipationStatus {
+                    status,
+                    reason: "Wrong student marked complete".to_string(),
+                    notes: None,
+                }),
+            )
+            .await
+            .unwrap();
+
+            let awarded: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM user_activity_hours WHERE user_id = $1")
+                .bind(user_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            awarded_hours.push(awarded);
+        }
+
+        sqlx::query("DELETE FROM users WHERE student_id LIKE $1")
+            .bind(format!("%{}%", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(awarded_hours, vec![1, 0]);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_scanner_assignment_required_to_scan() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+        let tag = Uuid::new_v4().simple().to_string()[..8].to_string();
+        let creator_student_id = format!("c{}", tag);
+        let scanner_student_id = format!("s{}", tag);
+        let creator_id = insert_user(&pool, &creator_student_id, None).await;
+        let scanner_id = insert_user(&pool, &scanner_student_id, None).await;
+        let activity_id = insert_activity(&pool, creator_id, None).await;
+
+        let admin = |user_id: Uuid, student_id: &str| AdminUser {
+            session_user: student(user_id, student_id),
+            admin_role: crate::models::admin_role::AdminRole {
+                id: Uuid::new_v4(),
+                user_id,
+                admin_level: AdminLevel::RegularAdmin,
+                faculty_id: None,
+                permissions: Vec::new(),
+                is_enabled: true,
+                created_at: None,
+                updated_at: None,
+            },
+        };
+
+        let scanner = admin(scanner_id, &scanner_student_id);
+        match ensure_scanner_assigned(&state, &scanner, activity_id).await {
+            Err(ApiError::Forbidden(detail)) => assert_eq!(detail.code, "scanner_not_assigned"),
+            other => panic!("expected 403 for an unassigned scanner, got {:?}", other),
+        }
+        assert!(ensure_scanner_assigned(&state, &admin(creator_id, &creator_student_id), activity_id)
+            .await
+            .is_ok());
+
+        sqlx::query("INSERT INTO activity_scanners (activity_id, user_id, assigned_by) VALUES ($1, $2, $3)")
+            .bind(activity_id)
+            .bind(scanner_id)
+            .bind(creator_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let assigned = ensure_scanner_assigned(&state, &scanner, activity_id).await;
+
+        sqlx::query("DELETE FROM users WHERE student_id LIKE $1")
+            .bind(format!("%{}%", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(assigned.is_ok());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_bulk_checkin_reports_each_row() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+        let tag = Uuid::new_v4().simple().to_string()[..8].to_string();
+        let creator_student_id = format!("c{}", tag);
+        let creator_id = insert_user(&pool, &creator_student_id, None).await;
+        let activity_id = insert_activity(&pool, creator_id, None).await;
+
+        let registered = format!("r{}", tag);
+        let checked_in = format!("k{}", tag);
+        let unregistered = format!("u{}", tag);
+        for (student_id, status) in [(&registered, Some("registered")), (&checked_in, Some("checked_in")), (&unregistered, None)] {
+            let user_id = insert_user(&pool, student_id, None).await;
+            if let Some(status) = status {
+                sqlx::query("INSERT INTO participations (user_id, activity_id, status) VALUES ($1, $2, $3::participation_status)")
+                    .bind(user_id)
+                    .bind(activity_id)
+                    .bind(status)
+                    .execute(&pool)
+                    .await
+                    .unwrap();
+            }
+        }
+
+        let csv = format!("student_id\r\n{}\r\n{}\r\n{}\r\nmissing-{}\r\n{}\r\n", registered, checked_in, unregistered, tag, registered);
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, "text/csv".parse().unwrap());
+        let result = bulk_checkin(
+            State(state.clone()),
+            student(creator_id, &creator_student_id),
+            Path(activity_id),
+            headers,
+            Bytes::from(csv),
+        )
+        .await;
+
+        let events: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM participation_events WHERE activity_id = $1 AND source = 'bulk_checkin'",
+        )
+        .bind(activity_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        sqlx::query("DELETE FROM users WHERE student_id LIKE $1")
+            .bind(format!("%{}%", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let Json(body) = result.unwrap();
+        let results: Vec<(String, Option<String>)> = body["data"]["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|row| (row["result"].as_str().unwrap().to_string(), row["code"].as_str().map(String::from)))
+            .collect();
+        assert_eq!(
+            results,
+            vec![
+                ("checked_in".to_string(), None),
+                ("already_checked_in".to_string(), None),
+                ("error".to_string(), Some("not_registered".to_string())),
+                ("error".to_string(), Some("user_not_found".to_string())),
+                ("error".to_string(), Some("duplicate_row".to_string())),
+            ]
+        );
+        assert_eq!(body["data"]["checked_in"], 1);
+        assert_eq!(body["data"]["failed"], 3);
+        assert_eq!(events, 1);
+    }
+
+    #[test]
+    fn test_parse_bulk_checkin_body() {
+        let json_ids = parse_bulk_checkin_body(&HeaderMap::new(), br#"[" 6401 ", "6402"]"#).unwrap();
+        assert_eq!(json_ids, vec!["6401", "6402"]);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, "text/csv; charset=utf-8".parse().unwrap());
+        let csv_ids = parse_bulk_checkin_body(&headers, b"6401,John\n6402,Jane\n").unwrap();
+        assert_eq!(csv_ids, vec!["6401", "6402"]);
+
+        assert!(parse_bulk_checkin_body(&HeaderMap::new(), b"[]").is_err());
+        assert!(parse_bulk_checkin_body(&HeaderMap::new(), b"{\"ids\": []}").is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_activity_analytics_funnel() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+        let tag = Uuid::new_v4().simple().to_string()[..8].to_string();
+        let creator_student_id = format!("c{}", tag);
+        let creator_id = insert_user(&pool, &creator_student_id, None).await;
+        let activity_id = insert_activity(&pool, creator_id, None).await;
+
+        let participants = [
+            ("registered", None),
+            ("no_show", None),
+            ("checked_in", Some("09:15")),
+            ("completed", Some("09:40")),
+            ("completed", Some("10:05")),
+        ];
+        for (i, (status, checked_in_at)) in participants.iter().enumerate() {
+            let user_id = insert_user(&pool, &format!("a{}{}", i, tag), None).await;
+            sqlx::query(
+                "INSERT INTO participations (user_id, activity_id, status, checked_in_at, checked_out_at)
+                 VALUES ($1, $2, $3::participation_status,
+                    (CURRENT_DATE + $4::time) AT TIME ZONE 'UTC',
+                    CASE WHEN $3 = 'completed' THEN (CURRENT_DATE + TIME '16:00') AT TIME ZONE 'UTC' END)",
+            )
+            .bind(user_id)
+            .bind(activity_id)
+            .bind(status)
+            .bind(checked_in_at)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let result = get_activity_analytics(
+            State(state.clone()),
+            student(creator_id, &creator_student_id),
+            Path(activity_id),
+        )
+        .await;
+        let outsider = get_activity_analytics(State(state), student(Uuid::new_v4(), "outsider"), Path(activity_id)).await;
+
+        sqlx::query("DELETE FROM users WHERE student_id LIKE $1")
+            .bind(format!("%{}%", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let Json(body) = result.unwrap();
+        let data = &body["data"];
+        assert_eq!(data["total"], 5);
+        assert_eq!(data["status_counts"][4], json!({ "status": "completed", "count": 2 }));
+        assert_eq!(data["funnel"][0]["count"], 5);
+        assert_eq!(data["funnel"][1]["count"], 3);
+        assert_eq!(data["funnel"][1]["conversion_rate"], 60.0);
+        assert_eq!(data["funnel"][2]["conversion_rate"], 66.7);
+        assert_eq!(data["no_show_count"], 1);
+        assert_eq!(data["no_show_rate"], 20.0);
+        assert_eq!(data["checkins_by_hour"][9]["count"], 2);
+        assert_eq!(data["checkins_by_hour"][10]["count"], 1);
+        assert_eq!(data["checkins_by_hour"].as_array().unwrap().len(), 24);
+        assert!(matches!(outsider, Err(ApiError::Forbidden(_))));
+    }
 }
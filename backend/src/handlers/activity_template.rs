@@ -0,0 +1,504 @@
+use axum::{
+    extract::{Path, State},
+    response::Json,
+};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::error::{ApiError, ApiJson, ApiResult};
+use crate::handlers::admin::invalidate_dashboard_cache;
+use crate::middleware::session::{
+    get_accessible_faculty_ids, has_faculty_access, AdminUser, CanCreateActivity, RequirePermission,
+    SessionState,
+};
+use crate::models::{
+    activity::ActivityRecord, activity_template::ActivityTemplate, admin_role::AdminLevel,
+    session::SessionUser,
+};
+
+const VALID_ACTIVITY_TYPES: [&str; 5] = ["Academic", "Sports", "Cultural", "Social", "Other"];
+
+const TEMPLATE_COLUMNS: &str = "id, name, title_pattern, description, location, \
+    activity_type::text as activity_type, hours, organizer, eligible_faculties, faculty_id, \
+    created_by, created_at, updated_at";
+
+/// Body for creating or replacing a template.
+///
+/// `faculty_id` is only honoured for SuperAdmins (`null` = shared template);
+/// other admins always get their own faculty.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActivityTemplateRequest {
+    pub name: String,
+    pub title_pattern: String,
+    #[serde(default)]
+    pub description: String,
+    pub location: String,
+    pub activity_type: Option<String>,
+    pub hours: i32,
+    pub organizer: String,
+    #[serde(default)]
+    pub eligible_faculties: Vec<Uuid>,
+    pub faculty_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateFromTemplateRequest {
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub academic_year: String,
+    pub max_participants: Option<i32>,
+}
+
+fn validate_template(request: &ActivityTemplateRequest) -> ApiResult<()> {
+    if request.name.trim().is_empty()
+        || request.title_pattern.trim().is_empty()
+        || request.location.trim().is_empty()
+        || request.organizer.trim().is_empty()
+    {
+        return Err(ApiError::bad_request("missing_fields", "name, title_pattern, location and organizer are required"));
+    }
+
+    if request.hours <= 0 {
+        return Err(ApiError::bad_request("invalid_hours", "Hours must be greater than 0"));
+    }
+
+    if let Some(activity_type) = &request.activity_type {
+        if !VALID_ACTIVITY_TYPES.contains(&activity_type.as_str()) {
+            return Err(ApiError::bad_request("invalid_activity_type", "Invalid activity type"));
+        }
+    }
+
+    Ok(())
+}
+
+/// Shared templates belong to SuperAdmins; faculty templates to that faculty's admins
+fn can_manage_template(user: &SessionUser, faculty_id: Option<Uuid>) -> bool {
+    match faculty_id {
+        Some(faculty_id) => has_faculty_access(user, faculty_id),
+        None => user
+            .admin_role
+            .as_ref()
+            .is_some_and(|role| role.admin_level == AdminLevel::SuperAdmin),
+    }
+}
+
+/// Shared templates are usable by everyone who can create activities
+fn can_use_template(user: &SessionUser, template: &ActivityTemplate) -> bool {
+    template
+        .faculty_id
+        .is_none_or(|faculty_id| has_faculty_access(user, faculty_id))
+}
+
+/// Faculty a template is saved under, following the rules on `ActivityTemplateRequest`
+fn template_faculty_id(admin: &AdminUser, request: &ActivityTemplateRequest) -> Option<Uuid> {
+    if admin.admin_role.admin_level == AdminLevel::SuperAdmin {
+        request.faculty_id
+    } else {
+        admin.session_user.faculty_id
+    }
+}
+
+async fn fetch_template(session_state: &SessionState, template_id: Uuid) -> ApiResult<ActivityTemplate> {
+    sqlx::query_as::<_, ActivityTemplate>(&format!(
+        "SELECT {} FROM activity_templates WHERE id = $1",
+        TEMPLATE_COLUMNS
+    ))
+    .bind(template_id)
+    .fetch_optional(&session_state.db_pool)
+    .await
+    .map_err(|_| ApiError::internal("Failed to load template"))?
+    .ok_or_else(|| ApiError::not_found("template_not_found", "Template not found"))
+}
+
+/// รายการ template ที่ admin ใช้ได้ (template ของคณะตัวเอง + template กลาง)
+pub async fn get_templates(
+    State(session_state): State<SessionState>,
+    admin: AdminUser,
+) -> ApiResult<Json<Value>> {
+    let faculty_scope = get_accessible_faculty_ids(&admin.session_user);
+
+    let templates = sqlx::query_as::<_, ActivityTemplate>(&format!(
+        "SELECT {} FROM activity_templates
+         WHERE $1::uuid[] IS NULL OR faculty_id IS NULL OR faculty_id = ANY($1)
+         ORDER BY name",
+        TEMPLATE_COLUMNS
+    ))
+    .bind(faculty_scope)
+    .fetch_all(&session_state.db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to list activity templates: {}", e);
+        ApiError::internal("Failed to retrieve templates")
+    })?;
+
+    let response = json!({
+        "status": "success",
+        "data": templates,
+        "message": "Templates retrieved successfully"
+    });
+    Ok(Json(response))
+}
+
+pub async fn get_template(
+    State(session_state): State<SessionState>,
+    admin: AdminUser,
+    Path(template_id): Path<Uuid>,
+) -> ApiResult<Json<Value>> {
+    let template = fetch_template(&session_state, template_id).await?;
+    if !can_use_template(&admin.session_user, &template) {
+        return Err(ApiError::not_found("template_not_found", "Template not found"));
+    }
+
+    let response = json!({
+        "status": "success",
+        "data": template,
+        "message": "Template retrieved successfully"
+    });
+    Ok(Json(response))
+}
+
+pub async fn create_template(
+    State(session_state): State<SessionState>,
+    admin: AdminUser,
+    ApiJson(request): ApiJson<ActivityTemplateRequest>,
+) -> ApiResult<Json<Value>> {
+    validate_template(&request)?;
+
+    let faculty_id = template_faculty_id(&admin, &request);
+    if !can_manage_template(&admin.session_user, faculty_id) {
+        return Err(ApiError::forbidden("permission_denied", "Access denied: You can only create templates for your own faculty"));
+    }
+
+    let template = sqlx::query_as::<_, ActivityTemplate>(&format!(
+        r#"
+        INSERT INTO activity_templates (
+            name, title_pattern, description, location, activity_type, hours, organizer,
+            eligible_faculties, faculty_id, created_by
+        )
+        VALUES ($1, $2, $3, $4, $5::activity_type, $6, $7, $8, $9, $10)
+        RETURNING {}
+        "#,
+        TEMPLATE_COLUMNS
+    ))
+    .bind(request.name.trim())
+    .bind(&request.title_pattern)
+    .bind(&request.description)
+    .bind(&request.location)
+    .bind(&request.activity_type)
+    .bind(request.hours)
+    .bind(&request.organizer)
+    .bind(json!(request.eligible_faculties))
+    .bind(faculty_id)
+    .bind(admin.session_user.user_id)
+    .fetch_one(&session_state.db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to create activity template: {}", e);
+        ApiError::internal("Failed to create template")
+    })?;
+
+    let response = json!({
+        "status": "success",
+        "data": template,
+        "message": "Template created successfully"
+    });
+    Ok(Json(response))
+}
+
+pub async fn update_template(
+    State(session_state): State<SessionState>,
+    admin: AdminUser,
+    Path(template_id): Path<Uuid>,
+    ApiJson(request): ApiJson<ActivityTemplateRequest>,
+) -> ApiResult<Json<Value>> {
+    validate_template(&request)?;
+
+    let current = fetch_template(&session_state, template_id).await?;
+    let faculty_id = template_faculty_id(&admin, &request);
+    if !can_manage_template(&admin.session_user, current.faculty_id)
+        || !can_manage_template(&admin.session_user, faculty_id)
+    {
+        return Err(ApiError::forbidden("permission_denied", "Access denied: You can only edit templates of your own faculty"));
+    }
+
+    let template = sqlx::query_as::<_, ActivityTemplate>(&format!(
+        r#"
+        UPDATE activity_templates
+        SET name = $2, title_pattern = $3, description = $4, location = $5,
+            activity_type = $6::activity_type, hours = $7, organizer = $8,
+            eligible_faculties = $9, faculty_id = $10, updated_at = NOW()
+        WHERE id = $1
+        RETURNING {}
+        "#,
+        TEMPLATE_COLUMNS
+    ))
+    .bind(template_id)
+    .bind(request.name.trim())
+    .bind(&request.title_pattern)
+    .bind(&request.description)
+    .bind(&request.location)
+    .bind(&request.activity_type)
+    .bind(request.hours)
+    .bind(&request.organizer)
+    .bind(json!(request.eligible_faculties))
+    .bind(faculty_id)
+    .fetch_optional(&session_state.db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to update activity template: {}", e);
+        ApiError::internal("Failed to update template")
+    })?
+    .ok_or_else(|| ApiError::not_found("template_not_found", "Template not found"))?;
+
+    let response = json!({
+        "status": "success",
+        "data": template,
+        "message": "Template updated successfully"
+    });
+    Ok(Json(response))
+}
+
+pub async fn delete_template(
+    State(session_state): State<SessionState>,
+    admin: AdminUser,
+    Path(template_id): Path<Uuid>,
+) -> ApiResult<Json<Value>> {
+    let current = fetch_template(&session_state, template_id).await?;
+    if !can_manage_template(&admin.session_user, current.faculty_id) {
+        return Err(ApiError::forbidden("permission_denied", "Access denied: You can only delete templates of your own faculty"));
+    }
+
+    sqlx::query("DELETE FROM activity_templates WHERE id = $1")
+        .bind(template_id)
+        .execute(&session_state.db_pool)
+        .await
+        .map_err(|_| ApiError::internal("Failed to delete template"))?;
+
+    let response = json!({
+        "status": "success",
+        "message": "Template deleted successfully"
+    });
+    Ok(Json(response))
+}
+
+/// สร้างกิจกรรม (draft) จาก template ด้วยวันเวลาที่ส่งมา
+pub async fn create_activity_from_template(
+    State(session_state): State<SessionState>,
+    RequirePermission { session_user: user, .. }: RequirePermission<CanCreateActivity>,
+    Path(template_id): Path<Uuid>,
+    ApiJson(request): ApiJson<CreateFromTemplateRequest>,
+) -> ApiResult<Json<Value>> {
+    if request.start_time >= request.end_time {
+        return Err(ApiError::bad_request("invalid_time_range", "Start time must be before end time"));
+    }
+
+    if request.academic_year.trim().is_empty() {
+        return Err(ApiError::bad_request("missing_fields", "academic_year is required"));
+    }
+
+    let template = fetch_template(&session_state, template_id).await?;
+    if !can_use_template(&user, &template) {
+        return Err(ApiError::not_found("template_not_found", "Template not found"));
+    }
+
+    // The title is rendered with the activity's local start date
+    let time_zone = session_state.app_config.activity_time_zone();
+    let (start_naive, end_naive): (NaiveDateTime, NaiveDateTime) = sqlx::query_as(&format!(
+        "SELECT {}, {}",
+        time_zone.local_sql("$1::timestamptz"),
+        time_zone.local_sql("$2::timestamptz")
+    ))
+    .bind(request.start_time)
+    .bind(request.end_time)
+    .fetch_one(&session_state.db_pool)
+    .await
+    .map_err(|_| ApiError::internal("Failed to create activity"))?;
+    let create_result = sqlx::query_as::<_, ActivityRecord>(&format!(
+        r#"
+        INSERT INTO activities (
+            title, description, location, activity_type, academic_year, organizer,
+            eligible_faculties, hours, max_participants, faculty_id, created_by,
+            start_date, end_date, start_time_only, end_time_only
+        )
+        VALUES ($1, $2, $3, $4::activity_type, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+        RETURNING id, title, description, location,
+          {} as start_time,
+          {} as end_time,
+          max_participants, registration_deadline, publish_at, status, visibility, activity_type::text as activity_type,
+          faculty_id, created_by, created_at, updated_at
+        "#,
+        time_zone.start_sql(""),
+        time_zone.end_sql("")
+    ))
+    .bind(template.render_title(start_naive.date()))
+    .bind(&template.description)
+    .bind(&template.location)
+    .bind(&template.activity_type)
+    .bind(request.academic_year.trim())
+    .bind(&template.organizer)
+    .bind(&template.eligible_faculties)
+    .bind(template.hours)
+    .bind(request.max_participants)
+    .bind(template.faculty_id.or(user.faculty_id))
+    .bind(user.user_id)
+    .bind(start_naive.date())
+    .bind(end_naive.date())
+    .bind(start_naive.time())
+    .bind(end_naive.time())
+    .fetch_one(&session_state.db_pool)
+    .await;
+
+    match create_result {
+        Ok(activity) => {
+            invalidate_dashboard_cache(&session_state).await;
+
+            let response = json!({
+                "status": "success",
+                "data": activity,
+                "message": "Activity created successfully"
+            });
+            Ok(Json(response))
+        }
+        Err(e) => {
+            tracing::error!("Failed to create activity from template: {}", e);
+            Err(ApiError::internal("Failed to create activity"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::admin_role::AdminRole;
+    use crate::models::session::Permission;
+    use crate::test_support::{insert_user, test_state};
+    use axum::extract::FromRequestParts;
+
+    fn faculty_admin(user_id: Uuid, student_id: &str, faculty_id: Uuid) -> AdminUser {
+        let admin_role = AdminRole {
+            id: Uuid::new_v4(),
+            user_id,
+            admin_level: AdminLevel::FacultyAdmin,
+            faculty_id: Some(faculty_id),
+            permissions: Vec::new(),
+            is_enabled: true,
+            created_at: None,
+            updated_at: None,
+        };
+        AdminUser {
+            session_user: SessionUser {
+                user_id,
+                student_id: student_id.to_string(),
+                email: format!("{}@test.local", student_id),
+                first_name: "Test".to_string(),
+                last_name: "Admin".to_string(),
+                department_id: None,
+                admin_role: Some(admin_role.clone()),
+                session_id: Uuid::new_v4().to_string(),
+                permissions: vec![Permission::CreateActivity],
+                faculty_id: Some(faculty_id),
+                is_impersonated: false,
+                impersonated_by: None,
+            },
+            admin_role,
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_templates_are_faculty_scoped() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+        let tag = Uuid::new_v4().simple().to_string()[..8].to_string();
+
+        let mut admins = Vec::new();
+        for i in 0..2 {
+            let faculty_id: Uuid = sqlx::query_scalar("INSERT INTO faculties (name, code) VALUES ($1, $2) RETURNING id")
+                .bind(format!("Templates {} {}", i, tag))
+                .bind(format!("{}{}", i, tag))
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            let student_id = format!("t{}{}", i, tag);
+            let user_id = insert_user(&pool, &student_id, None).await;
+            admins.push((faculty_id, user_id, student_id));
+        }
+        let owner = || faculty_admin(admins[0].1, &admins[0].2, admins[0].0);
+        let outsider = || faculty_admin(admins[1].1, &admins[1].2, admins[1].0);
+
+        // A FacultyAdmin can't place a template in another faculty
+        let Json(body) = create_template(
+            State(state.clone()),
+            owner(),
+            ApiJson(ActivityTemplateRequest {
+                name: "Weekly seminar".to_string(),
+                title_pattern: "Seminar {date}".to_string(),
+                description: "Weekly talk".to_string(),
+                location: "Room 101".to_string(),
+                activity_type: Some("Academic".to_string()),
+                hours: 2,
+                organizer: "CS Club".to_string(),
+                eligible_faculties: Vec::new(),
+                faculty_id: Some(admins[1].0),
+            }),
+        )
+        .await
+        .unwrap();
+        let template: ActivityTemplate = serde_json::from_value(body["data"].clone()).unwrap();
+        assert_eq!(template.faculty_id, Some(admins[0].0));
+
+        match get_template(State(state.clone()), outsider(), Path(template.id)).await {
+            Err(ApiError::NotFound(_)) => {}
+            other => panic!("expected 404 for another faculty's template, got {:?}", other.map(|j| j.0)),
+        }
+        match delete_template(State(state.clone()), outsider(), Path(template.id)).await {
+            Err(ApiError::Forbidden(_)) => {}
+            other => panic!("expected 403 for another faculty's template, got {:?}", other.map(|j| j.0)),
+        }
+
+        let (mut parts, _) = axum::http::Request::new(()).into_parts();
+        parts.extensions.insert(owner().session_user);
+        let creator = RequirePermission::<CanCreateActivity>::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+        let start_time = DateTime::parse_from_rfc3339("2030-02-14T09:00:00Z").unwrap().with_timezone(&Utc);
+        let Json(body) = create_activity_from_template(
+            State(state.clone()),
+            creator,
+            Path(template.id),
+            ApiJson(CreateFromTemplateRequest {
+                start_time,
+                end_time: start_time + chrono::Duration::hours(2),
+                academic_year: "2572".to_string(),
+                max_participants: Some(40),
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(body["data"]["title"], "Seminar 2030-02-14");
+        assert_eq!(body["data"]["status"], "Draft");
+        assert_eq!(body["data"]["faculty_id"], json!(admins[0].0));
+
+        let hours: i32 = sqlx::query_scalar("SELECT hours FROM activities WHERE id = $1")
+            .bind(serde_json::from_value::<Uuid>(body["data"]["id"].clone()).unwrap())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(hours, 2);
+
+        sqlx::query("DELETE FROM users WHERE student_id LIKE $1")
+            .bind(format!("%{}", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM faculties WHERE code LIKE $1")
+            .bind(format!("%{}", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
@@ -1,23 +1,35 @@
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
-    response::Json,
+    response::{IntoResponse, Json, Response},
 };
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use sqlx::Row;
 use std::collections::HashMap;
 use uuid::Uuid;
 
-use crate::middleware::session::{AdminUser, SessionState, SuperAdminUser, FacultyAdminUser};
+use crate::error::{ApiError, ApiJson, ApiResult};
+use crate::middleware::session::{
+    get_accessible_faculty_ids, AdminUser, FacultyAdminUser, FacultyScope, SessionState, SuperAdminUser,
+};
 use crate::models::{
-    activity::ActivityStatus,
+    activity::{ActivityStatus, ActivityVisibility},
     admin_role::{AdminLevel, AdminRole},
+    analytics::{RegistrationTrendPoint, TrendGranularity},
+    audit_log::NewAuditLog,
     session::AdminSessionInfo,
     user::{User, UserPrefix},
 };
+use crate::handlers::user::user_history_counts;
+use crate::services::audit_log::record_audit_log;
+use crate::services::email_service::EmailService;
+use crate::services::metrics::metrics;
 use crate::services::ActivityStatusUpdater;
+use crate::utils::password_hash::hash_password;
+use crate::utils::validation::{validate_password, PasswordPolicy};
+use crate::utils::xlsx::{xlsx_attachment, xlsx_workbook};
 
 // Helper function to parse prefix from string
 fn parse_user_prefix(prefix_str: &String) -> UserPrefix {
@@ -42,6 +54,8 @@ pub struct DashboardStats {
     pub ongoing_activities: i64,
     pub total_participations: i64,
     pub active_sessions: i64,
+    /// False when Redis could not be reached; `active_sessions` then comes from the `sessions` table
+    pub redis_available: bool,
     pub recent_activities: Vec<ActivitySummary>,
     pub user_registrations_today: i64,
     pub popular_activities: Vec<ActivitySummary>,
@@ -92,41 +106,148 @@ pub struct AdminActivityInfo {
     pub hours: Option<i32>,
     // Extra helpful fields
     pub faculty_id: Option<Uuid>,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+/// Redis cache prefix shared by every dashboard scope
+const DASHBOARD_CACHE_PREFIX: &str = "dashboard:";
+
+#[derive(Debug, Deserialize)]
+pub struct DashboardQuery {
+    pub refresh: Option<bool>,
+}
+
+/// Record a response-cache lookup and log the running hit rate
+fn record_cache_lookup(cache: &str, key: &str, hit: bool) {
+    let hit_rate = metrics().record_cache_lookup(cache, hit);
+    tracing::debug!(
+        "{} cache {} for {} (hit rate {:.1}%)",
+        cache,
+        if hit { "hit" } else { "miss" },
+        key,
+        hit_rate * 100.0
+    );
+}
+
+/// Drop cached dashboard stats so new users and activities show up immediately;
+/// Redis errors are only logged and the entries expire on their own
+pub async fn invalidate_dashboard_cache(session_state: &SessionState) {
+    if let Err(e) = session_state
+        .session_store
+        .invalidate_cached_prefix(DASHBOARD_CACHE_PREFIX)
+        .await
+    {
+        tracing::warn!("Failed to invalidate dashboard cache: {}", e);
+    }
 }
 
 /// Get admin dashboard statistics
+///
+/// Stats are cached in Redis per scope (system-wide, or per faculty for faculty admins)
+/// for `DASHBOARD_CACHE_TTL_SECONDS`; `?refresh=true` recomputes them.
 pub async fn get_dashboard(
     State(session_state): State<SessionState>,
     admin: AdminUser,
+    Query(query): Query<DashboardQuery>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let is_super_admin = admin.admin_role.admin_level == AdminLevel::SuperAdmin;
+    let message = if is_super_admin {
+        "Dashboard statistics retrieved successfully"
+    } else {
+        "Faculty dashboard statistics retrieved successfully"
+    };
+    let ttl = session_state.app_config.dashboard_cache_ttl_seconds;
+    let cache_key = if is_super_admin {
+        Some(format!("{}system", DASHBOARD_CACHE_PREFIX))
+    } else {
+        admin
+            .admin_role
+            .faculty_id
+            .map(|faculty_id| format!("{}faculty:{}", DASHBOARD_CACHE_PREFIX, faculty_id))
+    }
+    .filter(|_| ttl > 0);
+
+    if let Some(key) = cache_key.as_deref().filter(|_| !query.refresh.unwrap_or(false)) {
+        match session_state.session_store.get_cached_json(key).await {
+            Ok(Some(data)) => {
+                record_cache_lookup("dashboard", key, true);
+                return Ok(Json(json!({
+                    "status": "success",
+                    "data": data,
+                    "message": message
+                })));
+            }
+            Ok(None) => record_cache_lookup("dashboard", key, false),
+            Err(e) => tracing::warn!("Failed to read dashboard cache: {}", e),
+        }
+    }
+
     // Check admin level and get appropriate statistics
-    match admin.admin_role.admin_level {
+    let dashboard_stats = match admin.admin_role.admin_level {
         AdminLevel::SuperAdmin => {
-            get_super_admin_dashboard_stats(session_state).await
+            get_super_admin_dashboard_stats(&session_state).await?
         }
         AdminLevel::FacultyAdmin | AdminLevel::RegularAdmin => {
-            get_faculty_admin_dashboard_stats(session_state, admin.admin_role.faculty_id).await
+            get_faculty_admin_dashboard_stats(&session_state, admin.admin_role.faculty_id).await?
+        }
+    };
+    let data = json!(dashboard_stats);
+
+    if let Some(key) = cache_key.as_deref() {
+        if let Err(e) = session_state.session_store.set_cached_json(key, &data, ttl).await {
+            tracing::warn!("Failed to cache dashboard statistics: {}", e);
+        }
+    }
+
+    let response = json!({
+        "status": "success",
+        "data": data,
+        "message": message
+    });
+    Ok(Json(response))
+}
+
+/// Active sessions from Redis, or from active rows in the `sessions` table (written at login)
+/// when Redis is down, so an outage isn't reported as zero sessions
+async fn count_active_sessions(session_state: &SessionState) -> (i64, bool) {
+    let redis_count = session_state
+        .redis_health
+        .observe(session_state.session_store.get_session_count().await);
+
+    match redis_count {
+        Ok(count) => (count as i64, true),
+        Err(_) => {
+            let fallback = sqlx::query_scalar::<_, i64>(
+                "SELECT COUNT(*) FROM sessions WHERE is_active = TRUE AND expires_at > NOW()",
+            )
+            .fetch_one(&session_state.db_pool)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::error!("Failed to count sessions in the database: {}", e);
+                0
+            });
+            (fallback, false)
         }
     }
 }
 
 /// Get dashboard statistics for SuperAdmin (system-wide)
 async fn get_super_admin_dashboard_stats(
-    session_state: SessionState,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    session_state: &SessionState,
+) -> Result<DashboardStats, (StatusCode, Json<Value>)> {
     // Get total users count
     let total_users_result = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM users")
         .fetch_one(&session_state.db_pool)
         .await;
 
     // Get total activities count
-    let total_activities_result = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM activities")
+    let total_activities_result = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM activities WHERE deleted_at IS NULL")
         .fetch_one(&session_state.db_pool)
         .await;
 
     // Get ongoing activities count
     let ongoing_activities_result =
-        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM activities WHERE status = 'ongoing'")
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM activities WHERE status = 'ongoing' AND deleted_at IS NULL")
             .fetch_one(&session_state.db_pool)
             .await;
 
@@ -137,10 +258,7 @@ async fn get_super_admin_dashboard_stats(
             .await;
 
     // Get active sessions count from Redis
-    let active_sessions = match session_state.redis_store.get_session_count().await {
-        Ok(count) => count as i64,
-        Err(_) => 0,
-    };
+    let (active_sessions, redis_available) = count_active_sessions(session_state).await;
 
     // Get user registrations today
     let user_registrations_today_result =
@@ -148,42 +266,47 @@ async fn get_super_admin_dashboard_stats(
             .fetch_one(&session_state.db_pool)
             .await;
 
+    let start_time = session_state.app_config.activity_time_zone().start_sql("a");
+
     // Get recent activities (last 5)
-    let recent_activities_result = sqlx::query(
+    let recent_activities_result = sqlx::query(&format!(
         r#"
         SELECT 
             a.id,
             a.title,
-            a.start_time,
+            {} as start_time,
             a.status,
             COALESCE(COUNT(p.id), 0) as participant_count
         FROM activities a
         LEFT JOIN participations p ON a.id = p.activity_id
-        WHERE a.created_at >= NOW() - INTERVAL '7 days'
-        GROUP BY a.id, a.title, a.start_time, a.status
+        WHERE a.created_at >= NOW() - INTERVAL '7 days' AND a.deleted_at IS NULL
+        GROUP BY a.id, a.title, a.start_date, a.start_time_only, a.status
         ORDER BY a.created_at DESC
         LIMIT 5
         "#,
-    )
+        start_time
+    ))
     .fetch_all(&session_state.db_pool)
     .await;
 
     // Get popular activities (most participants)
-    let popular_activities_result = sqlx::query(
+    let popular_activities_result = sqlx::query(&format!(
         r#"
         SELECT 
             a.id,
             a.title,
-            a.start_time,
+            {} as start_time,
             a.status,
             COALESCE(COUNT(p.id), 0) as participant_count
         FROM activities a
         LEFT JOIN participations p ON a.id = p.activity_id
-        GROUP BY a.id, a.title, a.start_time, a.status
+        WHERE a.deleted_at IS NULL
+        GROUP BY a.id, a.title, a.start_date, a.start_time_only, a.status
         ORDER BY participant_count DESC
         LIMIT 5
         "#,
-    )
+        start_time
+    ))
     .fetch_all(&session_state.db_pool)
     .await;
 
@@ -228,24 +351,17 @@ async fn get_super_admin_dashboard_stats(
                 })
                 .collect();
 
-            let dashboard_stats = DashboardStats {
+            Ok(DashboardStats {
                 total_users,
                 total_activities,
                 ongoing_activities,
                 total_participations,
                 active_sessions,
+                redis_available,
                 recent_activities,
                 user_registrations_today,
                 popular_activities,
-            };
-
-            let response = json!({
-                "status": "success",
-                "data": dashboard_stats,
-                "message": "Dashboard statistics retrieved successfully"
-            });
-
-            Ok(Json(response))
+            })
         }
         _ => {
             let error_response = json!({
@@ -259,9 +375,9 @@ async fn get_super_admin_dashboard_stats(
 
 /// Get dashboard statistics for Faculty Admin (faculty-specific)
 async fn get_faculty_admin_dashboard_stats(
-    session_state: SessionState,
+    session_state: &SessionState,
     faculty_id: Option<Uuid>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<DashboardStats, (StatusCode, Json<Value>)> {
     // Ensure faculty_id is provided for FacultyAdmin
     let faculty_id = match faculty_id {
         Some(id) => id,
@@ -289,7 +405,7 @@ async fn get_faculty_admin_dashboard_stats(
 
     // Get faculty-specific activities count
     let total_activities_result = sqlx::query_scalar::<_, i64>(
-        "SELECT COUNT(*) FROM activities WHERE faculty_id = $1"
+        "SELECT COUNT(*) FROM activities WHERE faculty_id = $1 AND deleted_at IS NULL"
     )
     .bind(faculty_id)
     .fetch_one(&session_state.db_pool)
@@ -297,7 +413,7 @@ async fn get_faculty_admin_dashboard_stats(
 
     // Get faculty-specific ongoing activities count
     let ongoing_activities_result = sqlx::query_scalar::<_, i64>(
-        "SELECT COUNT(*) FROM activities WHERE faculty_id = $1 AND status = 'ongoing'"
+        "SELECT COUNT(*) FROM activities WHERE faculty_id = $1 AND status = 'ongoing' AND deleted_at IS NULL"
     )
     .bind(faculty_id)
     .fetch_one(&session_state.db_pool)
@@ -309,7 +425,7 @@ async fn get_faculty_admin_dashboard_stats(
         SELECT COUNT(DISTINCT p.id)
         FROM participations p
         JOIN activities a ON p.activity_id = a.id
-        WHERE a.faculty_id = $1
+        WHERE a.faculty_id = $1 AND a.deleted_at IS NULL
         "#
     )
     .bind(faculty_id)
@@ -318,10 +434,7 @@ async fn get_faculty_admin_dashboard_stats(
 
     // Get active sessions count from users in this faculty
     // Note: This is tricky with Redis, so we'll approximate or use all sessions for now
-    let active_sessions = match session_state.redis_store.get_session_count().await {
-        Ok(count) => count as i64,
-        Err(_) => 0,
-    };
+    let (active_sessions, redis_available) = count_active_sessions(session_state).await;
 
     // Get faculty-specific user registrations today
     let user_registrations_today_result = sqlx::query_scalar::<_, i64>(
@@ -336,44 +449,48 @@ async fn get_faculty_admin_dashboard_stats(
     .fetch_one(&session_state.db_pool)
     .await;
 
+    let start_time = session_state.app_config.activity_time_zone().start_sql("a");
+
     // Get recent activities for this faculty (last 5)
-    let recent_activities_result = sqlx::query(
+    let recent_activities_result = sqlx::query(&format!(
         r#"
         SELECT 
             a.id,
             a.title,
-            a.start_time,
+            {} as start_time,
             a.status,
             COALESCE(COUNT(p.id), 0) as participant_count
         FROM activities a
         LEFT JOIN participations p ON a.id = p.activity_id
-        WHERE a.faculty_id = $1 AND a.created_at >= NOW() - INTERVAL '7 days'
-        GROUP BY a.id, a.title, a.start_time, a.status
+        WHERE a.faculty_id = $1 AND a.created_at >= NOW() - INTERVAL '7 days' AND a.deleted_at IS NULL
+        GROUP BY a.id, a.title, a.start_date, a.start_time_only, a.status
         ORDER BY a.created_at DESC
         LIMIT 5
         "#,
-    )
+        start_time
+    ))
     .bind(faculty_id)
     .fetch_all(&session_state.db_pool)
     .await;
 
     // Get popular activities for this faculty (most participants)
-    let popular_activities_result = sqlx::query(
+    let popular_activities_result = sqlx::query(&format!(
         r#"
         SELECT 
             a.id,
             a.title,
-            a.start_time,
+            {} as start_time,
             a.status,
             COALESCE(COUNT(p.id), 0) as participant_count
         FROM activities a
         LEFT JOIN participations p ON a.id = p.activity_id
-        WHERE a.faculty_id = $1
-        GROUP BY a.id, a.title, a.start_time, a.status
+        WHERE a.faculty_id = $1 AND a.deleted_at IS NULL
+        GROUP BY a.id, a.title, a.start_date, a.start_time_only, a.status
         ORDER BY participant_count DESC
         LIMIT 5
         "#,
-    )
+        start_time
+    ))
     .bind(faculty_id)
     .fetch_all(&session_state.db_pool)
     .await;
@@ -419,41 +536,48 @@ async fn get_faculty_admin_dashboard_stats(
                 })
                 .collect();
 
-            let dashboard_stats = DashboardStats {
+            Ok(DashboardStats {
                 total_users,
                 total_activities,
                 ongoing_activities,
                 total_participations,
                 active_sessions,
+                redis_available,
                 recent_activities,
                 user_registrations_today,
                 popular_activities,
-            };
-
-            let response = json!({
-                "status": "success",
-                "data": dashboard_stats,
-                "message": "Faculty dashboard statistics retrieved successfully"
-            });
-
-            return Ok(Json(response));
+            })
         }
         _ => {
             let error_response = json!({
                 "status": "error",
                 "message": "Failed to retrieve faculty dashboard statistics"
             });
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
         }
     }
 }
 
-/// Get admin users list with detailed information
+/// Columns of the admin users xlsx export
+const ADMIN_USERS_EXPORT_HEADER: [&str; 9] = [
+    "student_id", "first_name", "last_name", "email", "admin_level",
+    "faculty_id", "is_enabled", "last_login", "created_at",
+];
+
+/// Get admin users list with detailed information (`?format=xlsx` for a download)
 pub async fn get_admin_users(
     State(session_state): State<SessionState>,
-    _admin: AdminUser,
+    admin: AdminUser,
     Query(params): Query<HashMap<String, String>>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    let export_xlsx = match params.get("format").map(String::as_str) {
+        None | Some("json") => false,
+        Some("xlsx") => true,
+        Some(_) => {
+            return Err(ApiError::bad_request("unsupported_format", "Invalid format. Supported formats: json, xlsx").into());
+        }
+    };
+
     let limit = params
         .get("limit")
         .and_then(|l| l.parse::<i64>().ok())
@@ -466,6 +590,33 @@ pub async fn get_admin_users(
 
     let search = params.get("search").cloned();
 
+    // SuperAdmin sees all admins, FacultyAdmin only admins of their own faculty
+    let faculty_scope = match admin.admin_role.admin_level {
+        AdminLevel::SuperAdmin => None,
+        AdminLevel::FacultyAdmin => Some(admin.session_user.faculty_id),
+        AdminLevel::RegularAdmin => {
+            let error_response = json!({
+                "status": "error",
+                "message": "Access denied: Insufficient permissions to view admin users"
+            });
+            return Err((StatusCode::FORBIDDEN, Json(error_response)));
+        }
+    };
+
+    if let Some(None) = faculty_scope {
+        let response = json!({
+            "status": "success",
+            "data": {
+                "users": [],
+                "total_count": 0,
+                "limit": limit,
+                "offset": offset
+            },
+            "message": "Admin users retrieved successfully"
+        });
+        return Ok(Json(response).into_response());
+    }
+
     let mut query = r#"
         SELECT DISTINCT
             u.id,
@@ -492,41 +643,46 @@ pub async fn get_admin_users(
 
     let mut count_query = "SELECT COUNT(*) FROM users u LEFT JOIN admin_roles ar ON u.id = ar.user_id WHERE ar.id IS NOT NULL".to_string();
 
-    if let Some(_search_term) = &search {
-        let where_clause = " AND (u.first_name ILIKE $3 OR u.last_name ILIKE $3 OR u.email ILIKE $3 OR u.student_id ILIKE $3)";
-        query.push_str(where_clause);
-        count_query.push_str(where_clause);
+    // The count query has no limit/offset params, so it is numbered separately
+    let mut param_count = 3;
+    let mut count_param_count = 1;
+
+    if faculty_scope.is_some() {
+        query.push_str(&format!(" AND ar.faculty_id = ${}", param_count));
+        count_query.push_str(&format!(" AND ar.faculty_id = ${}", count_param_count));
+        param_count += 1;
+        count_param_count += 1;
+    }
+
+    if search.is_some() {
+        query.push_str(&format!(
+            " AND (u.first_name ILIKE ${0} OR u.last_name ILIKE ${0} OR u.email ILIKE ${0} OR u.student_id ILIKE ${0})",
+            param_count
+        ));
+        count_query.push_str(&format!(
+            " AND (u.first_name ILIKE ${0} OR u.last_name ILIKE ${0} OR u.email ILIKE ${0} OR u.student_id ILIKE ${0})",
+            count_param_count
+        ));
     }
 
     query.push_str(" ORDER BY u.created_at DESC LIMIT $1 OFFSET $2");
 
-    let users_result = if let Some(search_term) = &search {
-        let search_pattern = format!("%{}%", search_term);
-        sqlx::query(&query)
-            .bind(limit)
-            .bind(offset)
-            .bind(search_pattern.clone())
-            .fetch_all(&session_state.db_pool)
-            .await
-    } else {
-        sqlx::query(&query)
-            .bind(limit)
-            .bind(offset)
-            .fetch_all(&session_state.db_pool)
-            .await
-    };
+    let mut query_builder = sqlx::query(&query).bind(limit).bind(offset);
+    let mut count_query_builder = sqlx::query_scalar::<_, i64>(&count_query);
 
-    let total_count_result = if let Some(search_term) = &search {
+    if let Some(Some(faculty_id)) = faculty_scope {
+        query_builder = query_builder.bind(faculty_id);
+        count_query_builder = count_query_builder.bind(faculty_id);
+    }
+
+    if let Some(search_term) = &search {
         let search_pattern = format!("%{}%", search_term);
-        sqlx::query_scalar::<_, i64>(&count_query)
-            .bind(search_pattern)
-            .fetch_one(&session_state.db_pool)
-            .await
-    } else {
-        sqlx::query_scalar::<_, i64>(&count_query)
-            .fetch_one(&session_state.db_pool)
-            .await
-    };
+        query_builder = query_builder.bind(search_pattern.clone());
+        count_query_builder = count_query_builder.bind(search_pattern);
+    }
+
+    let users_result = query_builder.fetch_all(&session_state.db_pool).await;
+    let total_count_result = count_query_builder.fetch_one(&session_state.db_pool).await;
 
     match (users_result, total_count_result) {
         (Ok(rows), Ok(total_count)) => {
@@ -568,6 +724,32 @@ pub async fn get_admin_users(
                 admin_users.push(admin_user);
             }
 
+            if export_xlsx {
+                let format_time = |time: Option<DateTime<Utc>>| time.map(|t| t.to_rfc3339()).unwrap_or_default();
+                let rows: Vec<Vec<String>> = admin_users
+                    .iter()
+                    .map(|user| {
+                        let role = user.admin_role.as_ref();
+                        vec![
+                            user.student_id.clone(),
+                            user.first_name.clone(),
+                            user.last_name.clone(),
+                            user.email.clone(),
+                            role.map(|r| format!("{:?}", r.admin_level)).unwrap_or_default(),
+                            role.and_then(|r| r.faculty_id).map(|id| id.to_string()).unwrap_or_default(),
+                            user.is_enabled.to_string(),
+                            format_time(user.last_login),
+                            format_time(user.created_at),
+                        ]
+                    })
+                    .collect();
+                let workbook = xlsx_workbook("Admin users", &ADMIN_USERS_EXPORT_HEADER, &rows).map_err(|e| {
+                    tracing::error!("Failed to build admin users workbook: {}", e);
+                    ApiError::internal("Failed to build export")
+                })?;
+                return Ok(xlsx_attachment("admin_users.xlsx", workbook));
+            }
+
             let response = json!({
                 "status": "success",
                 "data": {
@@ -579,7 +761,7 @@ pub async fn get_admin_users(
                 "message": "Admin users retrieved successfully"
             });
 
-            Ok(Json(response))
+            Ok(Json(response).into_response())
         }
         _ => {
             let error_response = json!({
@@ -594,7 +776,7 @@ pub async fn get_admin_users(
 /// Get admin activities with enhanced information
 pub async fn get_admin_activities(
     State(session_state): State<SessionState>,
-    _admin: AdminUser,
+    admin: AdminUser,
     Query(params): Query<HashMap<String, String>>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     let limit = params
@@ -610,6 +792,32 @@ pub async fn get_admin_activities(
     let status_filter = params.get("status");
     let search = params.get("search").cloned();
 
+    // Soft-deleted activities can only be listed by SuperAdmin
+    let include_deleted = params.get("include_deleted").is_some_and(|v| v == "true");
+    if include_deleted && admin.admin_role.admin_level != AdminLevel::SuperAdmin {
+        let error_response = json!({
+            "status": "error",
+            "message": "Access denied: Only super admins can list deleted activities"
+        });
+        return Err((StatusCode::FORBIDDEN, Json(error_response)));
+    }
+
+    // SuperAdmin sees every activity; other admins only their own faculty
+    let faculty_scope = get_accessible_faculty_ids(&admin.session_user);
+    if faculty_scope.as_ref().is_some_and(|ids| ids.is_empty()) {
+        let response = json!({
+            "status": "success",
+            "data": {
+                "activities": [],
+                "total_count": 0,
+                "limit": limit,
+                "offset": offset
+            },
+            "message": "Admin activities retrieved successfully"
+        });
+        return Ok(Json(response));
+    }
+
     let mut query = r#"
         SELECT 
             a.id,
@@ -629,7 +837,8 @@ pub async fn get_admin_activities(
             a.organizer,
             a.eligible_faculties,
             a.hours,
-            a.faculty_id
+            a.faculty_id,
+            a.deleted_at
         FROM activities a
     "#
     .to_string();
@@ -640,12 +849,28 @@ pub async fn get_admin_activities(
     "#
     .to_string();
 
+    // The count query has no limit/offset params, so it is numbered separately
     let mut conditions = Vec::new();
+    let mut count_conditions = Vec::new();
     let mut param_count = 3;
+    if !include_deleted {
+        conditions.push("a.deleted_at IS NULL".to_string());
+        count_conditions.push("a.deleted_at IS NULL".to_string());
+    }
+    let mut count_param_count = 1;
+
+    if faculty_scope.is_some() {
+        conditions.push(format!("a.faculty_id = ANY(${})", param_count));
+        count_conditions.push(format!("a.faculty_id = ANY(${})", count_param_count));
+        param_count += 1;
+        count_param_count += 1;
+    }
 
     if let Some(_status) = status_filter {
-        conditions.push(format!("a.status = ${}", param_count));
+        conditions.push(format!("a.status::text = ${}", param_count));
+        count_conditions.push(format!("a.status::text = ${}", count_param_count));
         param_count += 1;
+        count_param_count += 1;
     }
 
     if search.is_some() {
@@ -653,12 +878,15 @@ pub async fn get_admin_activities(
             "(a.title ILIKE ${} OR a.description ILIKE ${})",
             param_count, param_count
         ));
+        count_conditions.push(format!(
+            "(a.title ILIKE ${} OR a.description ILIKE ${})",
+            count_param_count, count_param_count
+        ));
     }
 
     if !conditions.is_empty() {
-        let where_clause = format!(" WHERE {}", conditions.join(" AND "));
-        query.push_str(&where_clause);
-        count_query.push_str(&where_clause);
+        query.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
+        count_query.push_str(&format!(" WHERE {}", count_conditions.join(" AND ")));
     }
 
     // No grouping needed since no aggregate
@@ -668,6 +896,11 @@ pub async fn get_admin_activities(
 
     let mut count_query_builder = sqlx::query_scalar::<_, i64>(&count_query);
 
+    if let Some(faculty_ids) = &faculty_scope {
+        query_builder = query_builder.bind(faculty_ids.clone());
+        count_query_builder = count_query_builder.bind(faculty_ids.clone());
+    }
+
     if let Some(status) = status_filter {
         query_builder = query_builder.bind(status);
         count_query_builder = count_query_builder.bind(status);
@@ -706,6 +939,7 @@ pub async fn get_admin_activities(
                     eligible_faculties: row.get::<Option<serde_json::Value>, _>("eligible_faculties"),
                     hours: row.get::<Option<i32>, _>("hours"),
                     faculty_id: row.get::<Option<Uuid>, _>("faculty_id"),
+                    deleted_at: row.get("deleted_at"),
                 };
 
                 admin_activities.push(admin_activity);
@@ -759,9 +993,10 @@ pub async fn get_admin_activity(
             a.organizer,
             a.eligible_faculties,
             a.hours,
-            a.faculty_id
+            a.faculty_id,
+            a.deleted_at
         FROM activities a
-        WHERE a.id = $1
+        WHERE a.id = $1 AND a.deleted_at IS NULL
     "#;
 
     let result = sqlx::query(query)
@@ -790,6 +1025,7 @@ pub async fn get_admin_activity(
                 eligible_faculties: row.get::<Option<serde_json::Value>, _>("eligible_faculties"),
                 hours: row.get::<Option<i32>, _>("hours"),
                 faculty_id: row.get::<Option<Uuid>, _>("faculty_id"),
+                deleted_at: row.get("deleted_at"),
             };
 
             let response = json!({
@@ -831,7 +1067,7 @@ pub async fn get_admin_sessions(
 
     // Get active session IDs from Redis
     let session_ids_result = session_state
-        .redis_store
+        .session_store
         .get_active_sessions(Some(limit * 2))
         .await;
 
@@ -840,7 +1076,7 @@ pub async fn get_admin_sessions(
             let mut admin_sessions = Vec::new();
 
             for session_id in &session_ids {
-                if let Ok(Some(session)) = session_state.redis_store.get_session(session_id).await {
+                if let Ok(Some(session)) = session_state.session_store.get_session(session_id).await {
                     // Get user info
                     if let Ok(Some(user)) = get_user_by_id(&session_state, session.user_id).await {
                         // Apply search filter
@@ -895,7 +1131,7 @@ pub async fn get_admin_sessions(
             }
 
             let total_count = session_state
-                .redis_store
+                .session_store
                 .get_session_count()
                 .await
                 .map_err(|_| {
@@ -971,9 +1207,12 @@ pub struct CreateAdminRequest {
 /// Create new admin account (user + admin role)
 pub async fn create_admin(
     State(session_state): State<SessionState>,
-    _admin: SuperAdminUser,
-    Json(request): Json<CreateAdminRequest>,
+    admin: SuperAdminUser,
+    ApiJson(request): ApiJson<CreateAdminRequest>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let policy = PasswordPolicy::from_config(&session_state.app_config);
+    validate_password(&request.password, &policy).map_err(|e| e.to_error_response())?;
+
     // Check if user already exists
     let existing_user = sqlx::query_scalar::<_, i64>(
         "SELECT COUNT(*) FROM users WHERE email = $1 OR student_id = $2",
@@ -1002,7 +1241,7 @@ pub async fn create_admin(
     }
 
     // Hash password
-    let password_hash = match bcrypt::hash(&request.password, bcrypt::DEFAULT_COST) {
+    let password_hash = match hash_password(&session_state.app_config, &request.password).await {
         Ok(hash) => hash,
         Err(_) => {
             let error_response = json!({
@@ -1036,7 +1275,7 @@ pub async fn create_admin(
         r#"
         INSERT INTO users (student_id, email, password_hash, prefix, first_name, last_name, qr_secret, department_id)
         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-        RETURNING id, student_id, email, password_hash, prefix, first_name, last_name, qr_secret, department_id, created_at, updated_at
+        RETURNING id, student_id, email, password_hash, prefix, first_name, last_name, qr_secret, department_id, is_active, anonymized_at, created_at, updated_at
         "#
     )
     .bind(&request.student_id)
@@ -1099,6 +1338,18 @@ pub async fn create_admin(
         return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
     }
 
+    record_audit_log(
+        &session_state.db_pool,
+        NewAuditLog::new(admin.session_user.user_id, "create_admin", "admin_role", admin_role.id)
+            .details(json!({
+                "user_id": user.id,
+                "admin_level": admin_role.admin_level,
+                "faculty_id": admin_role.faculty_id
+            })),
+    )
+    .await;
+    invalidate_dashboard_cache(&session_state).await;
+
     let response = json!({
         "status": "success",
         "data": {
@@ -1127,6 +1378,45 @@ pub async fn create_admin(
     Ok(Json(response))
 }
 
+const LAST_SUPER_ADMIN_MESSAGE: &str =
+    "Cannot remove the last enabled SuperAdmin. Enable or promote another SuperAdmin first.";
+
+/// Ids of the enabled SuperAdmin roles held by active users, row-locked so concurrent
+/// demotions and deactivations queue up behind each other
+async fn lock_enabled_super_admins(conn: &mut sqlx::PgConnection) -> Result<Vec<Uuid>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT ar.id FROM admin_roles ar
+         JOIN users u ON u.id = ar.user_id
+         WHERE ar.admin_level = 'super_admin' AND ar.is_enabled = TRUE AND u.is_active = TRUE
+         FOR UPDATE OF ar, u",
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Whether demoting, disabling or deleting `removed` would leave no enabled SuperAdmin
+fn removes_last_super_admin(enabled_super_admins: &[Uuid], removed: &[Uuid]) -> bool {
+    enabled_super_admins.iter().any(|id| removed.contains(id))
+        && enabled_super_admins.iter().all(|id| removed.contains(id))
+}
+
+/// Lock the enabled SuperAdmins and reject with 409 if `removed` covers all of them
+pub(crate) async fn ensure_super_admin_remains(
+    conn: &mut sqlx::PgConnection,
+    removed: &[Uuid],
+) -> ApiResult<()> {
+    let enabled_super_admins = lock_enabled_super_admins(conn).await.map_err(|e| {
+        tracing::error!("Failed to check SuperAdmins: {}", e);
+        ApiError::internal("Failed to check SuperAdmins")
+    })?;
+
+    if removes_last_super_admin(&enabled_super_admins, removed) {
+        return Err(ApiError::conflict("last_super_admin", LAST_SUPER_ADMIN_MESSAGE));
+    }
+
+    Ok(())
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ToggleAdminStatusRequest {
     pub is_enabled: bool,  // Changed from is_active to is_enabled
@@ -1135,9 +1425,9 @@ pub struct ToggleAdminStatusRequest {
 /// Toggle admin enabled status (not login activity status)
 pub async fn toggle_admin_status(
     State(session_state): State<SessionState>,
-    _admin: SuperAdminUser,
+    admin: SuperAdminUser,
     Path(admin_role_id): Path<Uuid>,
-    Json(request): Json<ToggleAdminStatusRequest>,
+    ApiJson(request): ApiJson<ToggleAdminStatusRequest>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     // Get the admin role first to verify it exists
     let admin_role_result = sqlx::query_as::<_, AdminRole>(
@@ -1165,6 +1455,21 @@ pub async fn toggle_admin_status(
         }
     };
 
+    let mut tx = match session_state.db_pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            let error_response = json!({
+                "status": "error",
+                "message": format!("Failed to start transaction: {}", e)
+            });
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+        }
+    };
+
+    if !request.is_enabled {
+        ensure_super_admin_remains(&mut tx, &[admin_role_id]).await?;
+    }
+
     // Update the is_enabled field directly (don't modify permissions)
     let update_result = sqlx::query_as::<_, AdminRole>(
         r#"
@@ -1176,17 +1481,29 @@ pub async fn toggle_admin_status(
     )
     .bind(request.is_enabled)
     .bind(admin_role_id)
-    .fetch_one(&session_state.db_pool)
+    .fetch_one(&mut *tx)
     .await;
+    let update_result = match update_result {
+        Ok(updated_role) => tx.commit().await.map(|_| updated_role),
+        Err(e) => Err(e),
+    };
 
     match update_result {
         Ok(updated_role) => {
-            // If disabling admin, optionally revoke active sessions for this user
-            if !request.is_enabled {
-                // TODO: Implement session revocation for disabled admins
-                // This could be done by calling redis_session.revoke_user_sessions(user_id)
-                // For now, we'll leave sessions active but the middleware should check is_enabled
-            }
+            // No session revocation needed: the admin extractors re-read is_enabled on every
+            // request, so a disabled admin gets 403 on their next admin call
+
+            record_audit_log(
+                &session_state.db_pool,
+                NewAuditLog::new(
+                    admin.session_user.user_id,
+                    if request.is_enabled { "enable_admin" } else { "disable_admin" },
+                    "admin_role",
+                    admin_role_id,
+                )
+                .details(json!({ "user_id": updated_role.user_id })),
+            )
+            .await;
 
             let response = json!({
                 "status": "success",
@@ -1222,8 +1539,11 @@ pub struct BootstrapAdminRequest {
 /// Bootstrap initial super admin account (only works when no super admin exists)
 pub async fn bootstrap_admin(
     State(session_state): State<SessionState>,
-    Json(request): Json<BootstrapAdminRequest>,
+    ApiJson(request): ApiJson<BootstrapAdminRequest>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let policy = PasswordPolicy::from_config(&session_state.app_config);
+    validate_password(&request.password, &policy).map_err(|e| e.to_error_response())?;
+
     // Check if any super admin already exists
     let super_admin_count = sqlx::query_scalar::<_, i64>(
         "SELECT COUNT(*) FROM admin_roles WHERE admin_level = 'super_admin'",
@@ -1277,7 +1597,7 @@ pub async fn bootstrap_admin(
     }
 
     // Hash password
-    let password_hash = match bcrypt::hash(&request.password, bcrypt::DEFAULT_COST) {
+    let password_hash = match hash_password(&session_state.app_config, &request.password).await {
         Ok(hash) => hash,
         Err(_) => {
             let error_response = json!({
@@ -1308,7 +1628,7 @@ pub async fn bootstrap_admin(
         r#"
         INSERT INTO users (student_id, email, password_hash, first_name, last_name, qr_secret, department_id)
         VALUES ($1, $2, $3, $4, $5, $6, NULL)
-        RETURNING id, student_id, email, password_hash, prefix, first_name, last_name, qr_secret, department_id, created_at, updated_at
+        RETURNING id, student_id, email, password_hash, prefix, first_name, last_name, qr_secret, department_id, is_active, anonymized_at, created_at, updated_at
         "#
     )
     .bind(&request.student_id)
@@ -1378,6 +1698,13 @@ pub async fn bootstrap_admin(
         return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
     }
 
+    // No session exists yet, so the new SuperAdmin is recorded as its own actor
+    record_audit_log(
+        &session_state.db_pool,
+        NewAuditLog::new(user.id, "bootstrap_admin", "admin_role", admin_role.id),
+    )
+    .await;
+
     let response = json!({
         "status": "success",
         "data": {
@@ -1411,19 +1738,11 @@ pub async fn bootstrap_admin(
 pub async fn get_faculty_admins(
     State(session_state): State<SessionState>,
     Path(faculty_id): Path<Uuid>,
-    admin: FacultyAdminUser,
+    _admin: FacultyAdminUser,
+    scope: FacultyScope,
     Query(params): Query<HashMap<String, String>>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    // Check authorization - FacultyAdmin can only access their own faculty
-    if admin.admin_role.admin_level != AdminLevel::SuperAdmin {
-        if admin.admin_role.faculty_id != Some(faculty_id) {
-            let error_response = json!({
-                "status": "error",
-                "message": "Access denied: You can only access admins in your faculty"
-            });
-            return Err((StatusCode::FORBIDDEN, Json(error_response)));
-        }
-    }
+    scope.authorize(faculty_id)?;
 
     // Verify faculty exists
     let faculty_exists = sqlx::query_scalar::<_, bool>(
@@ -1589,19 +1908,11 @@ pub async fn get_faculty_admins(
 pub async fn get_faculty_users(
     State(session_state): State<SessionState>,
     Path(faculty_id): Path<Uuid>,
-    admin: FacultyAdminUser,
+    _admin: FacultyAdminUser,
+    scope: FacultyScope,
     Query(params): Query<HashMap<String, String>>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    // Check authorization - FacultyAdmin can only access their own faculty
-    if admin.admin_role.admin_level != AdminLevel::SuperAdmin {
-        if admin.admin_role.faculty_id != Some(faculty_id) {
-            let error_response = json!({
-                "status": "error",
-                "message": "Access denied: You can only access users in your faculty"
-            });
-            return Err((StatusCode::FORBIDDEN, Json(error_response)));
-        }
-    }
+    scope.authorize(faculty_id)?;
 
     // Verify faculty exists
     let faculty_exists = sqlx::query_scalar::<_, bool>(
@@ -1776,7 +2087,7 @@ pub async fn create_faculty_admin(
     State(session_state): State<SessionState>,
     Path(faculty_id): Path<Uuid>,
     admin: FacultyAdminUser,
-    Json(mut request): Json<CreateAdminRequest>,
+    ApiJson(mut request): ApiJson<CreateAdminRequest>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     // Verify faculty exists
     let faculty_exists = sqlx::query_scalar::<_, bool>(
@@ -1805,13 +2116,7 @@ pub async fn create_faculty_admin(
         }
         AdminLevel::FacultyAdmin => {
             // FacultyAdmin can only create RegularAdmin in their own faculty
-            if admin.admin_role.faculty_id != Some(faculty_id) {
-                let error_response = json!({
-                    "status": "error",
-                    "message": "Faculty Admin can only create admins in their own faculty"
-                });
-                return Err((StatusCode::FORBIDDEN, Json(error_response)));
-            }
+            FacultyScope::for_admin(&admin.admin_role)?.authorize(faculty_id)?;
             if request.admin_level != AdminLevel::RegularAdmin {
                 let error_response = json!({
                     "status": "error",
@@ -1844,6 +2149,9 @@ pub async fn create_faculty_admin(
         }
     }
 
+    let policy = PasswordPolicy::from_config(&session_state.app_config);
+    validate_password(&request.password, &policy).map_err(|e| e.to_error_response())?;
+
     // Check if user already exists
     let existing_user = sqlx::query_scalar::<_, i64>(
         "SELECT COUNT(*) FROM users WHERE email = $1 OR student_id = $2",
@@ -1872,7 +2180,7 @@ pub async fn create_faculty_admin(
     }
 
     // Hash password
-    let password_hash = match bcrypt::hash(&request.password, bcrypt::DEFAULT_COST) {
+    let password_hash = match hash_password(&session_state.app_config, &request.password).await {
         Ok(hash) => hash,
         Err(_) => {
             let error_response = json!({
@@ -1903,7 +2211,7 @@ pub async fn create_faculty_admin(
         r#"
         INSERT INTO users (student_id, email, password_hash, first_name, last_name, qr_secret, department_id)
         VALUES ($1, $2, $3, $4, $5, $6, $7)
-        RETURNING id, student_id, email, password_hash, prefix, first_name, last_name, qr_secret, department_id, created_at, updated_at
+        RETURNING id, student_id, email, password_hash, prefix, first_name, last_name, qr_secret, department_id, is_active, anonymized_at, created_at, updated_at
         "#
     )
     .bind(&request.student_id)
@@ -1965,6 +2273,18 @@ pub async fn create_faculty_admin(
         return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
     }
 
+    record_audit_log(
+        &session_state.db_pool,
+        NewAuditLog::new(admin.session_user.user_id, "create_admin", "admin_role", admin_role.id)
+            .details(json!({
+                "user_id": user.id,
+                "admin_level": admin_role.admin_level,
+                "faculty_id": admin_role.faculty_id
+            })),
+    )
+    .await;
+    invalidate_dashboard_cache(&session_state).await;
+
     let response = json!({
         "status": "success",
         "data": {
@@ -1998,14 +2318,16 @@ pub struct UpdateAdminRoleRequest {
     pub admin_level: Option<AdminLevel>,
     pub faculty_id: Option<Option<Uuid>>, // Option<Option<T>> to distinguish between not provided vs explicitly setting to null
     pub permissions: Option<Vec<String>>,
+    /// `updated_at` the client last saw; the update is rejected with 409 if the role changed since
+    pub expected_updated_at: Option<DateTime<Utc>>,
 }
 
 /// Update admin role (SuperAdmin only)
 pub async fn update_admin_role(
     State(session_state): State<SessionState>,
     Path(admin_role_id): Path<Uuid>,
-    _admin: SuperAdminUser,
-    Json(request): Json<UpdateAdminRoleRequest>,
+    admin: SuperAdminUser,
+    ApiJson(request): ApiJson<UpdateAdminRoleRequest>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     // Get the current admin role
     let current_role = sqlx::query_as::<_, AdminRole>(
@@ -2065,6 +2387,21 @@ pub async fn update_admin_role(
         }
     }
 
+    let mut tx = match session_state.db_pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            let error_response = json!({
+                "status": "error",
+                "message": format!("Failed to start transaction: {}", e)
+            });
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+        }
+    };
+
+    if request.admin_level.as_ref().is_some_and(|level| *level != AdminLevel::SuperAdmin) {
+        ensure_super_admin_remains(&mut tx, &[admin_role_id]).await?;
+    }
+
     // Build dynamic update query
     let mut query = "UPDATE admin_roles SET updated_at = NOW()".to_string();
     let mut param_count = 1;
@@ -2085,10 +2422,14 @@ pub async fn update_admin_role(
         param_count += 1;
     }
 
-    query.push_str(&format!(
-        " WHERE id = ${} RETURNING id, user_id, admin_level, faculty_id, permissions, created_at, updated_at",
-        param_count
-    ));
+    query.push_str(&format!(" WHERE id = ${}", param_count));
+    param_count += 1;
+
+    if request.expected_updated_at.is_some() {
+        query.push_str(&format!(" AND updated_at = ${}", param_count));
+    }
+
+    query.push_str(" RETURNING id, user_id, admin_level, faculty_id, permissions, is_enabled, created_at, updated_at");
 
     query_builder = sqlx::query_as::<_, AdminRole>(&query);
 
@@ -2105,9 +2446,34 @@ pub async fn update_admin_role(
     }
 
     query_builder = query_builder.bind(admin_role_id);
+    if let Some(expected_updated_at) = request.expected_updated_at {
+        query_builder = query_builder.bind(expected_updated_at);
+    }
+
+    let update_result = match query_builder.fetch_one(&mut *tx).await {
+        Ok(updated_role) => tx.commit().await.map(|_| updated_role),
+        Err(e) => Err(e),
+    };
 
-    match query_builder.fetch_one(&session_state.db_pool).await {
+    match update_result {
         Ok(updated_role) => {
+            record_audit_log(
+                &session_state.db_pool,
+                NewAuditLog::new(admin.session_user.user_id, "update_admin_role", "admin_role", admin_role_id)
+                    .details(json!({
+                        "user_id": updated_role.user_id,
+                        "previous": {
+                            "admin_level": current_role.admin_level,
+                            "faculty_id": current_role.faculty_id,
+                            "permissions": current_role.permissions
+                        },
+                        "admin_level": updated_role.admin_level,
+                        "faculty_id": updated_role.faculty_id,
+                        "permissions": updated_role.permissions
+                    })),
+            )
+            .await;
+
             let response = json!({
                 "status": "success",
                 "data": {
@@ -2122,6 +2488,35 @@ pub async fn update_admin_role(
             });
             Ok(Json(response))
         }
+        Err(sqlx::Error::RowNotFound) if request.expected_updated_at.is_some() => {
+            // Either another SuperAdmin edited the role since the client loaded it, or it was deleted
+            let current_updated_at = sqlx::query_scalar::<_, Option<DateTime<Utc>>>(
+                "SELECT updated_at FROM admin_roles WHERE id = $1",
+            )
+            .bind(admin_role_id)
+            .fetch_optional(&session_state.db_pool)
+            .await
+            .ok()
+            .flatten();
+
+            match current_updated_at {
+                Some(current_updated_at) => {
+                    let error_response = json!({
+                        "status": "error",
+                        "message": "Admin role was modified by someone else. Reload and try again.",
+                        "current_updated_at": current_updated_at
+                    });
+                    Err((StatusCode::CONFLICT, Json(error_response)))
+                }
+                None => {
+                    let error_response = json!({
+                        "status": "error",
+                        "message": "Admin role not found"
+                    });
+                    Err((StatusCode::NOT_FOUND, Json(error_response)))
+                }
+            }
+        }
         Err(sqlx::Error::RowNotFound) => {
             let error_response = json!({
                 "status": "error",
@@ -2296,8 +2691,8 @@ pub struct BulkAdminOperationRequest {
 
 pub async fn bulk_admin_operations(
     State(session_state): State<SessionState>,
-    _admin: SuperAdminUser,
-    Json(request): Json<BulkAdminOperationRequest>,
+    admin: SuperAdminUser,
+    ApiJson(request): ApiJson<BulkAdminOperationRequest>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     if request.admin_role_ids.is_empty() {
         let error_response = json!({
@@ -2318,111 +2713,18 @@ pub async fn bulk_admin_operations(
         }
     };
 
+    if matches!(request.operation.as_str(), "deactivate" | "delete") {
+        ensure_super_admin_remains(&mut tx, &request.admin_role_ids).await?;
+    }
+
     let mut results = Vec::new();
+    let mut revoked_user_ids = Vec::new();
 
-    match request.operation.as_str() {
-        "activate" => {
-            for role_id in &request.admin_role_ids {
-                let result = sqlx::query_as::<_, AdminRole>(
-                    r#"
-                    UPDATE admin_roles 
-                    SET permissions = CASE 
-                        WHEN admin_level = 'super_admin' THEN $2
-                        WHEN admin_level = 'faculty_admin' THEN $3
-                        ELSE $4
-                    END, updated_at = NOW()
-                    WHERE id = $1
-                    RETURNING id, user_id, admin_level, faculty_id, permissions, created_at, updated_at
-                    "#
-                )
-                .bind(role_id)
-                .bind(&vec!["ManageUsers".to_string(), "ManageAdmins".to_string(), "ManageActivities".to_string(), "ViewDashboard".to_string(), "ManageFaculties".to_string(), "ManageSessions".to_string()])
-                .bind(&vec!["ViewDashboard".to_string(), "ManageActivities".to_string(), "ManageUsers".to_string()])
-                .bind(&vec!["ViewDashboard".to_string(), "ManageActivities".to_string()])
-                .fetch_one(&mut *tx)
-                .await;
-
-                match result {
-                    Ok(admin_role) => results.push(json!({
-                        "role_id": role_id,
-                        "status": "success",
-                        "message": "Admin activated successfully",
-                        "admin_role": admin_role
-                    })),
-                    Err(e) => results.push(json!({
-                        "role_id": role_id,
-                        "status": "error",
-                        "message": format!("Failed to activate admin: {}", e)
-                    }))
-                }
-            }
-        }
-        "deactivate" => {
-            for role_id in &request.admin_role_ids {
-                let result = sqlx::query_as::<_, AdminRole>(
-                    r#"
-                    UPDATE admin_roles 
-                    SET permissions = '{}', updated_at = NOW()
-                    WHERE id = $1
-                    RETURNING id, user_id, admin_level, faculty_id, permissions, created_at, updated_at
-                    "#
-                )
-                .bind(role_id)
-                .fetch_one(&mut *tx)
-                .await;
-
-                match result {
-                    Ok(admin_role) => results.push(json!({
-                        "role_id": role_id,
-                        "status": "success",
-                        "message": "Admin deactivated successfully",
-                        "admin_role": admin_role
-                    })),
-                    Err(e) => results.push(json!({
-                        "role_id": role_id,
-                        "status": "error",
-                        "message": format!("Failed to deactivate admin: {}", e)
-                    }))
-                }
-            }
-        }
-        "update_faculty" => {
-            let new_faculty_id = request.parameters
-                .as_ref()
-                .and_then(|p| p.get("faculty_id"))
-                .and_then(|v| v.as_str())
-                .and_then(|s| if s == "null" { None } else { Some(Uuid::parse_str(s).ok()) })
-                .flatten();
-
-            for role_id in &request.admin_role_ids {
-                let result = sqlx::query_as::<_, AdminRole>(
-                    r#"
-                    UPDATE admin_roles 
-                    SET faculty_id = $2, updated_at = NOW()
-                    WHERE id = $1
-                    RETURNING id, user_id, admin_level, faculty_id, permissions, created_at, updated_at
-                    "#
-                )
-                .bind(role_id)
-                .bind(new_faculty_id)
-                .fetch_one(&mut *tx)
-                .await;
-
-                match result {
-                    Ok(admin_role) => results.push(json!({
-                        "role_id": role_id,
-                        "status": "success", 
-                        "message": "Faculty assignment updated successfully",
-                        "admin_role": admin_role
-                    })),
-                    Err(e) => results.push(json!({
-                        "role_id": role_id,
-                        "status": "error",
-                        "message": format!("Failed to update faculty assignment: {}", e)
-                    }))
-                }
-            }
-        }
+    let (success_message, failure_message) = match request.operation.as_str() {
+        "activate" => ("Admin activated successfully", "Failed to activate admin"),
+        "deactivate" => ("Admin deactivated successfully", "Failed to deactivate admin"),
+        "update_faculty" => ("Faculty assignment updated successfully", "Failed to update faculty assignment"),
+        "delete" => ("Admin deleted successfully", "Failed to delete admin"),
         _ => {
             let error_response = json!({
                 "status": "error",
@@ -2430,6 +2732,95 @@ pub async fn bulk_admin_operations(
             });
             return Err((StatusCode::BAD_REQUEST, Json(error_response)));
         }
+    };
+
+    let new_faculty_id = request.parameters
+        .as_ref()
+        .and_then(|p| p.get("faculty_id"))
+        .and_then(|v| v.as_str())
+        .and_then(|s| if s == "null" { None } else { Some(Uuid::parse_str(s).ok()) })
+        .flatten();
+    // Optionally remove the underlying user account as well (admin_roles is one per user)
+    let delete_user = request.parameters
+        .as_ref()
+        .and_then(|p| p.get("delete_user"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    // The last enabled SuperAdmin was already protected above
+    for role_id in &request.admin_role_ids {
+        // Each item runs in its own savepoint: a failed item is rolled back on its own instead of
+        // aborting the transaction and silently discarding the items reported as successful
+        let mut item_tx = match sqlx::Connection::begin(&mut *tx).await {
+            Ok(item_tx) => item_tx,
+            Err(e) => {
+                let error_response = json!({
+                    "status": "error",
+                    "message": format!("Failed to start transaction: {}", e)
+                });
+                return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+            }
+        };
+
+        let result = match request.operation.as_str() {
+            // Only flip is_enabled; permissions are preserved across deactivate/activate
+            "activate" | "deactivate" => sqlx::query_as::<_, AdminRole>(
+                r#"
+                UPDATE admin_roles 
+                SET is_enabled = $2, updated_at = NOW()
+                WHERE id = $1
+                RETURNING id, user_id, admin_level, faculty_id, permissions, is_enabled, created_at, updated_at
+                "#
+            )
+            .bind(role_id)
+            .bind(request.operation == "activate")
+            .fetch_one(&mut *item_tx)
+            .await
+            .map(|admin_role| (admin_role, success_message)),
+            "update_faculty" => sqlx::query_as::<_, AdminRole>(
+                r#"
+                UPDATE admin_roles 
+                SET faculty_id = $2, updated_at = NOW()
+                WHERE id = $1
+                RETURNING id, user_id, admin_level, faculty_id, permissions, is_enabled, created_at, updated_at
+                "#
+            )
+            .bind(role_id)
+            .bind(new_faculty_id)
+            .fetch_one(&mut *item_tx)
+            .await
+            .map(|admin_role| (admin_role, success_message)),
+            _ => delete_admin_role(&mut item_tx, *role_id, delete_user).await,
+        };
+
+        let result = match result {
+            Ok(done) => item_tx.commit().await.map(|_| done),
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok((admin_role, message)) => {
+                if request.operation == "delete" {
+                    revoked_user_ids.push(admin_role.user_id);
+                }
+                results.push(json!({
+                    "role_id": role_id,
+                    "status": "success",
+                    "message": message,
+                    "admin_role": admin_role
+                }));
+            }
+            Err(sqlx::Error::RowNotFound) => results.push(json!({
+                "role_id": role_id,
+                "status": "error",
+                "message": "Admin role not found"
+            })),
+            Err(e) => results.push(json!({
+                "role_id": role_id,
+                "status": "error",
+                "message": format!("{}: {}", failure_message, e)
+            })),
+        }
     }
 
     if let Err(e) = tx.commit().await {
@@ -2440,6 +2831,30 @@ pub async fn bulk_admin_operations(
         return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
     }
 
+    for result in results.iter().filter(|r| r["status"] == "success") {
+        record_audit_log(
+            &session_state.db_pool,
+            NewAuditLog::new(
+                admin.session_user.user_id,
+                &format!("bulk_{}", request.operation),
+                "admin_role",
+                result["role_id"].as_str().unwrap_or_default(),
+            )
+            .details(json!({
+                "parameters": request.parameters,
+                "admin_role": result["admin_role"]
+            })),
+        )
+        .await;
+    }
+
+    // Redis sessions are outside the transaction, so revoke them only once the delete is committed
+    for user_id in revoked_user_ids {
+        if let Err(e) = session_state.session_store.delete_user_sessions(user_id).await {
+            tracing::warn!("Failed to revoke sessions for deleted admin {}: {}", user_id, e);
+        }
+    }
+
     let successful_count = results.iter().filter(|r| r["status"] == "success").count();
     let failed_count = results.len() - successful_count;
 
@@ -2461,6 +2876,343 @@ pub async fn bulk_admin_operations(
     Ok(Json(response))
 }
 
+/// Delete one admin role for the bulk "delete" operation. With `delete_user` the account goes
+/// too, unless the user has participations or created activities: deleting those would cascade
+/// into other students' records, so the account is deactivated instead (as `delete_user` refuses).
+async fn delete_admin_role(
+    conn: &mut sqlx::PgConnection,
+    role_id: Uuid,
+    delete_user: bool,
+) -> Result<(AdminRole, &'static str), sqlx::Error> {
+    let admin_role = sqlx::query_as::<_, AdminRole>(
+        r#"
+        DELETE FROM admin_roles
+        WHERE id = $1
+        RETURNING id, user_id, admin_level, faculty_id, permissions, is_enabled, created_at, updated_at
+        "#
+    )
+    .bind(role_id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    sqlx::query("UPDATE sessions SET is_active = false WHERE user_id = $1")
+        .bind(admin_role.user_id)
+        .execute(&mut *conn)
+        .await?;
+
+    if !delete_user {
+        return Ok((admin_role, "Admin deleted successfully"));
+    }
+
+    let (participation_count, activity_count) = user_history_counts(&mut *conn, admin_role.user_id).await?;
+    if participation_count > 0 || activity_count > 0 {
+        sqlx::query("UPDATE users SET is_active = false, updated_at = NOW() WHERE id = $1")
+            .bind(admin_role.user_id)
+            .execute(&mut *conn)
+            .await?;
+        return Ok((
+            admin_role,
+            "Admin deleted; the user account has participations or activities and was deactivated instead of deleted",
+        ));
+    }
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(admin_role.user_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok((admin_role, "Admin and user account deleted successfully"))
+}
+
+/// Email queue statistics (SuperAdmin only)
+pub async fn get_email_stats(
+    State(session_state): State<SessionState>,
+    _admin: SuperAdminUser,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    match EmailService::new(session_state).get_email_stats().await {
+        Ok(stats) => {
+            let response = json!({
+                "status": "success",
+                "data": stats,
+                "message": "Email statistics retrieved successfully"
+            });
+            Ok(Json(response))
+        }
+        Err(e) => {
+            tracing::error!("Failed to load email stats: {}", e);
+            let error_response = json!({
+                "status": "error",
+                "message": "Failed to retrieve email statistics"
+            });
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+/// Faculty filter for admin analytics: SuperAdmin may pick any faculty (or none),
+/// FacultyAdmin is pinned to their own
+fn analytics_faculty_scope(admin: &FacultyAdminUser, requested: Option<Uuid>) -> ApiResult<Option<Uuid>> {
+    let scope = FacultyScope::for_admin(&admin.admin_role)?;
+    if let Some(requested) = requested {
+        scope.authorize(requested)?;
+    }
+    Ok(requested.or(scope.faculty_id()))
+}
+
+/// Largest `limit` accepted by the leaderboard
+const MAX_LEADERBOARD_LIMIT: i64 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LeaderboardMetric {
+    Hours,
+    Activities,
+    Checkins,
+}
+
+impl LeaderboardMetric {
+    /// Aggregate column the ranking is ordered by
+    fn column(self) -> &'static str {
+        match self {
+            LeaderboardMetric::Hours => "total_hours",
+            LeaderboardMetric::Activities => "completed_activities",
+            LeaderboardMetric::Checkins => "checkins",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LeaderboardQuery {
+    pub metric: Option<LeaderboardMetric>,
+    pub academic_year: Option<String>,
+    pub faculty_id: Option<Uuid>,
+    pub limit: Option<i64>,
+}
+
+/// Most active students ranked by completed hours, completed activities or check-ins
+///
+/// FacultyAdmin is always limited to their own faculty. Results are cached in Redis for
+/// `LEADERBOARD_CACHE_TTL_SECONDS`; a Redis failure only skips the cache.
+pub async fn get_leaderboard(
+    State(session_state): State<SessionState>,
+    admin: FacultyAdminUser,
+    Query(query): Query<LeaderboardQuery>,
+) -> ApiResult<Json<Value>> {
+    let metric = query.metric.unwrap_or(LeaderboardMetric::Hours);
+    let limit = query.limit.unwrap_or(10);
+    if !(1..=MAX_LEADERBOARD_LIMIT).contains(&limit) {
+        return Err(ApiError::validation(
+            "invalid_limit",
+            format!("limit must be between 1 and {}", MAX_LEADERBOARD_LIMIT),
+        ));
+    }
+
+    let faculty_id = analytics_faculty_scope(&admin, query.faculty_id)?;
+    let academic_year = query
+        .academic_year
+        .map(|year| year.trim().to_string())
+        .filter(|year| !year.is_empty());
+
+    let cache_key = format!(
+        "leaderboard:{}:{}:{}:{}",
+        metric.column(),
+        academic_year.as_deref().unwrap_or("*"),
+        faculty_id.map(|id| id.to_string()).unwrap_or_else(|| "*".to_string()),
+        limit
+    );
+    let ttl = session_state.app_config.leaderboard_cache_ttl_seconds;
+    if ttl > 0 {
+        match session_state.session_store.get_cached_json(&cache_key).await {
+            Ok(Some(data)) => {
+                record_cache_lookup("leaderboard", &cache_key, true);
+                return Ok(Json(json!({
+                    "status": "success",
+                    "data": data,
+                    "message": "Leaderboard retrieved successfully"
+                })));
+            }
+            Ok(None) => record_cache_lookup("leaderboard", &cache_key, false),
+            Err(e) => tracing::warn!("Failed to read leaderboard cache: {}", e),
+        }
+    }
+
+    let sql = format!(
+        r#"
+        WITH totals AS (
+            SELECT u.id AS user_id, u.student_id, u.first_name, u.last_name,
+                   d.name AS department_name, f.id AS faculty_id, f.name AS faculty_name,
+                   COALESCE(SUM(a.hours) FILTER (WHERE p.status = 'completed'), 0)::bigint AS total_hours,
+                   COUNT(*) FILTER (WHERE p.status = 'completed') AS completed_activities,
+                   COUNT(p.checked_in_at) AS checkins
+            FROM participations p
+            JOIN users u ON u.id = p.user_id
+            JOIN activities a ON a.id = p.activity_id
+            LEFT JOIN departments d ON d.id = u.department_id
+            LEFT JOIN faculties f ON f.id = d.faculty_id
+            WHERE u.anonymized_at IS NULL
+              AND a.deleted_at IS NULL
+              AND NOT EXISTS (SELECT 1 FROM admin_roles ar WHERE ar.user_id = u.id)
+              AND ($1::text IS NULL OR a.academic_year = $1)
+              AND ($2::uuid IS NULL OR d.faculty_id = $2)
+            GROUP BY u.id, d.name, f.id, f.name
+        )
+        SELECT *, RANK() OVER (ORDER BY {column} DESC)::bigint AS rank
+        FROM totals
+        WHERE {column} > 0
+        ORDER BY {column} DESC, student_id
+        LIMIT $3
+        "#,
+        column = metric.column()
+    );
+
+    let rows = sqlx::query(&sql)
+        .bind(&academic_year)
+        .bind(faculty_id)
+        .bind(limit)
+        .fetch_all(&session_state.db_pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load leaderboard: {}", e);
+            ApiError::internal("Failed to load leaderboard")
+        })?;
+
+    let entries: Vec<Value> = rows
+        .iter()
+        .map(|row| {
+            json!({
+                "rank": row.get::<i64, _>("rank"),
+                "user_id": row.get::<Uuid, _>("user_id"),
+                "student_id": row.get::<String, _>("student_id"),
+                "first_name": row.get::<String, _>("first_name"),
+                "last_name": row.get::<String, _>("last_name"),
+                "department_name": row.get::<Option<String>, _>("department_name"),
+                "faculty_id": row.get::<Option<Uuid>, _>("faculty_id"),
+                "faculty_name": row.get::<Option<String>, _>("faculty_name"),
+                "total_hours": row.get::<i64, _>("total_hours"),
+                "completed_activities": row.get::<i64, _>("completed_activities"),
+                "checkins": row.get::<i64, _>("checkins")
+            })
+        })
+        .collect();
+
+    let data = json!({
+        "metric": metric,
+        "academic_year": academic_year,
+        "faculty_id": faculty_id,
+        "entries": entries,
+        "generated_at": Utc::now()
+    });
+
+    if ttl > 0 {
+        if let Err(e) = session_state.session_store.set_cached_json(&cache_key, &data, ttl).await {
+            tracing::warn!("Failed to cache leaderboard: {}", e);
+        }
+    }
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": data,
+        "message": "Leaderboard retrieved successfully"
+    })))
+}
+
+/// Longest date range accepted by the registration trend
+const MAX_TREND_RANGE_DAYS: i64 = 731;
+
+#[derive(Debug, Deserialize)]
+pub struct RegistrationTrendQuery {
+    pub date_from: Option<NaiveDate>,
+    pub date_to: Option<NaiveDate>,
+    pub granularity: Option<TrendGranularity>,
+    pub faculty_id: Option<Uuid>,
+}
+
+/// New users and new participations per day/week/month (UTC) between `date_from` and `date_to`
+///
+/// Defaults to the last 30 days by day. Empty buckets are returned with zero counts so charts
+/// get a continuous axis. Both series are scoped by the student's faculty.
+pub async fn get_registration_trends(
+    State(session_state): State<SessionState>,
+    admin: FacultyAdminUser,
+    Query(query): Query<RegistrationTrendQuery>,
+) -> ApiResult<Json<Value>> {
+    let granularity = query.granularity.unwrap_or_default();
+    let date_to = query.date_to.unwrap_or_else(|| Utc::now().date_naive());
+    let date_from = query.date_from.unwrap_or(date_to - chrono::Duration::days(29));
+    if date_from > date_to {
+        return Err(ApiError::validation("invalid_date_range", "date_from must not be after date_to"));
+    }
+    if (date_to - date_from).num_days() >= MAX_TREND_RANGE_DAYS {
+        return Err(ApiError::validation(
+            "invalid_date_range",
+            format!("Date range must be shorter than {} days", MAX_TREND_RANGE_DAYS),
+        ));
+    }
+    let faculty_id = analytics_faculty_scope(&admin, query.faculty_id)?;
+
+    let points = sqlx::query_as::<_, RegistrationTrendPoint>(
+        r#"
+        WITH periods AS (
+            SELECT generate_series(
+                date_trunc($1, $2::date::timestamp),
+                date_trunc($1, $3::date::timestamp),
+                ('1 ' || $1)::interval
+            ) AS period_start
+        ),
+        new_users AS (
+            SELECT date_trunc($1, u.created_at AT TIME ZONE 'UTC') AS period_start, COUNT(*) AS count
+            FROM users u
+            LEFT JOIN departments d ON d.id = u.department_id
+            WHERE u.created_at >= $2::date::timestamp AT TIME ZONE 'UTC'
+              AND u.created_at < ($3::date + 1)::timestamp AT TIME ZONE 'UTC'
+              AND ($4::uuid IS NULL OR d.faculty_id = $4)
+            GROUP BY 1
+        ),
+        new_participations AS (
+            SELECT date_trunc($1, p.registered_at AT TIME ZONE 'UTC') AS period_start, COUNT(*) AS count
+            FROM participations p
+            JOIN users u ON u.id = p.user_id
+            LEFT JOIN departments d ON d.id = u.department_id
+            WHERE p.registered_at >= $2::date::timestamp AT TIME ZONE 'UTC'
+              AND p.registered_at < ($3::date + 1)::timestamp AT TIME ZONE 'UTC'
+              AND ($4::uuid IS NULL OR d.faculty_id = $4)
+            GROUP BY 1
+        )
+        SELECT periods.period_start::date AS period_start,
+               COALESCE(nu.count, 0) AS new_users,
+               COALESCE(np.count, 0) AS new_participations
+        FROM periods
+        LEFT JOIN new_users nu ON nu.period_start = periods.period_start
+        LEFT JOIN new_participations np ON np.period_start = periods.period_start
+        ORDER BY periods.period_start
+        "#,
+    )
+    .bind(granularity.as_str())
+    .bind(date_from)
+    .bind(date_to)
+    .bind(faculty_id)
+    .fetch_all(&session_state.db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to load registration trends: {}", e);
+        ApiError::internal("Failed to load registration trends")
+    })?;
+
+    let response = json!({
+        "status": "success",
+        "data": {
+            "granularity": granularity,
+            "date_from": date_from,
+            "date_to": date_to,
+            "faculty_id": faculty_id,
+            "total_new_users": points.iter().map(|p| p.new_users).sum::<i64>(),
+            "total_new_participations": points.iter().map(|p| p.new_participations).sum::<i64>(),
+            "points": points
+        },
+        "message": "Registration trends retrieved successfully"
+    });
+    Ok(Json(response))
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateAdminActivityRequest {
     pub activity_name: String,
@@ -2476,13 +3228,16 @@ pub struct CreateAdminActivityRequest {
     pub eligible_faculties: Vec<Uuid>,
     pub academic_year: String,
     pub hours: i32,
+    pub registration_deadline: Option<DateTime<Utc>>,
+    pub visibility: Option<ActivityVisibility>,
+    pub publish_at: Option<DateTime<Utc>>,
 }
 
 /// Create new activity via admin interface with enhanced fields
 pub async fn create_admin_activity(
     State(session_state): State<SessionState>,
     admin: FacultyAdminUser,
-    Json(request): Json<CreateAdminActivityRequest>,
+    ApiJson(request): ApiJson<CreateAdminActivityRequest>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     // Validate activity type
     let valid_activity_types = vec!["Academic", "Sports", "Cultural", "Social", "Other"];
@@ -2540,9 +3295,28 @@ pub async fn create_admin_activity(
         }
     };
 
-    // Combine date and time for start_time and end_time
-    let start_datetime = start_date.and_time(start_time).and_utc();
-    let end_datetime = end_date.and_time(end_time).and_utc();
+    // Combine date and time for start_time and end_time; they are wall-clock times in the
+    // activity time zone
+    let time_zone = session_state.app_config.activity_time_zone();
+    let instants = sqlx::query_as::<_, (DateTime<Utc>, DateTime<Utc>)>(&format!(
+        "SELECT {}, {}",
+        time_zone.instant_sql("$1::timestamp"),
+        time_zone.instant_sql("$2::timestamp")
+    ))
+    .bind(start_date.and_time(start_time))
+    .bind(end_date.and_time(end_time))
+    .fetch_one(&session_state.db_pool)
+    .await;
+    let (start_datetime, end_datetime) = match instants {
+        Ok(instants) => instants,
+        Err(_) => {
+            let error_response = json!({
+                "status": "error",
+                "message": "เกิดข้อผิดพลาดในการสร้างกิจกรรม"
+            });
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+        }
+    };
 
     // Validate time range
     if start_datetime >= end_datetime {
@@ -2553,6 +3327,22 @@ pub async fn create_admin_activity(
         return Err((StatusCode::BAD_REQUEST, Json(error_response)));
     }
 
+    if request.registration_deadline.is_some_and(|deadline| deadline > end_datetime) {
+        let error_response = json!({
+            "status": "error",
+            "message": "วันปิดรับสมัครต้องไม่เกินเวลาสิ้นสุดกิจกรรม"
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    if request.publish_at.is_some_and(|publish_at| publish_at >= start_datetime) {
+        let error_response = json!({
+            "status": "error",
+            "message": "เวลาเผยแพร่ต้องอยู่ก่อนเวลาเริ่มกิจกรรม"
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
     // Validate required textual fields non-empty
     if request.activity_name.trim().is_empty()
         || request.activity_type.trim().is_empty()
@@ -2586,6 +3376,15 @@ pub async fn create_admin_activity(
         _ => admin.admin_role.faculty_id
     };
 
+    let visibility = request.visibility.unwrap_or_default();
+    if visibility == ActivityVisibility::FacultyOnly && faculty_id.is_none() {
+        let error_response = json!({
+            "status": "error",
+            "message": "กิจกรรมเฉพาะคณะต้องระบุคณะ"
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
     // Convert eligible_faculties to JSONB format
     let eligible_faculties_json = serde_json::to_value(&request.eligible_faculties)
         .map_err(|_| {
@@ -2600,26 +3399,28 @@ pub async fn create_admin_activity(
     let user_id = admin.session_user.user_id;
 
     // Create the activity with enhanced fields
-    let create_result = sqlx::query(
+    let create_result = sqlx::query(&format!(
         r#"
         INSERT INTO activities (
             title, description, location, max_participants, 
             faculty_id, created_by, academic_year, organizer, 
             eligible_faculties, activity_type, start_date, end_date, 
-            start_time_only, end_time_only, hours
+            start_time_only, end_time_only, hours, registration_deadline, visibility, publish_at
         )
         VALUES (
             $1, $2, $3, $4, $5, $6, $7, $8,
-            $9::jsonb, $10::activity_type, $11, $12, $13, $14, $15
+            $9::jsonb, $10::activity_type, $11, $12, $13, $14, $15, $16, $17, $18
         )
         RETURNING id, title, description, location,
-                  ((start_date::timestamp + start_time_only) AT TIME ZONE 'UTC') as start_time,
-                  ((end_date::timestamp + end_time_only) AT TIME ZONE 'UTC') as end_time,
-                  max_participants, 
+                  {} as start_time,
+                  {} as end_time,
+                  max_participants, registration_deadline, publish_at, visibility,
                   status, faculty_id, created_by, created_at, updated_at,
                   academic_year, organizer, eligible_faculties, activity_type::text as activity_type, hours
-        "#
-    )
+        "#,
+        time_zone.start_sql(""),
+        time_zone.end_sql("")
+    ))
     .bind(&request.activity_name)  // title
     .bind(&request.description)  // description
     .bind(&request.location)  // location
@@ -2635,6 +3436,9 @@ pub async fn create_admin_activity(
     .bind(start_time)  // start_time_only
     .bind(end_time)  // end_time_only
     .bind(request.hours) // hours
+    .bind(request.registration_deadline) // registration_deadline
+    .bind(visibility) // visibility
+    .bind(request.publish_at) // publish_at
     .fetch_one(&session_state.db_pool)
     .await;
 
@@ -2648,6 +3452,9 @@ pub async fn create_admin_activity(
                 "start_time": row.get::<DateTime<Utc>, _>("start_time"),
                 "end_time": row.get::<DateTime<Utc>, _>("end_time"),
                 "max_participants": row.get::<Option<i32>, _>("max_participants"),
+                "registration_deadline": row.get::<Option<DateTime<Utc>>, _>("registration_deadline"),
+                "publish_at": row.get::<Option<DateTime<Utc>>, _>("publish_at"),
+                "visibility": row.get::<ActivityVisibility, _>("visibility"),
                 "status": row.get::<ActivityStatus, _>("status"),
                 "faculty_id": row.get::<Option<Uuid>, _>("faculty_id"),
                 "created_by": row.get::<Uuid, _>("created_by"),
@@ -2660,6 +3467,8 @@ pub async fn create_admin_activity(
                 "hours": row.get::<Option<i32>, _>("hours")
             });
 
+            invalidate_dashboard_cache(&session_state).await;
+
             let response = json!({
                 "status": "success",
                 "data": activity,
@@ -2770,3 +3579,564 @@ pub async fn get_activity_status_statistics(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::session::SessionUser;
+    use crate::services::RedisSessionStore;
+    use crate::test_support::{insert_user, test_state};
+    use std::sync::Arc;
+
+    fn admin(admin_level: AdminLevel, faculty_id: Option<Uuid>) -> FacultyAdminUser {
+        let user_id = Uuid::new_v4();
+        let admin_role = AdminRole {
+            id: Uuid::new_v4(),
+            user_id,
+            admin_level,
+            faculty_id,
+            permissions: Vec::new(),
+            is_enabled: true,
+            created_at: None,
+            updated_at: None,
+        };
+        FacultyAdminUser {
+            session_user: SessionUser {
+                user_id,
+                student_id: "admin".to_string(),
+                email: "admin@test.local".to_string(),
+                first_name: "Test".to_string(),
+                last_name: "Admin".to_string(),
+                department_id: None,
+                admin_role: Some(admin_role.clone()),
+                session_id: Uuid::new_v4().to_string(),
+                permissions: Vec::new(),
+                faculty_id,
+                is_impersonated: false,
+                impersonated_by: None,
+            },
+            admin_role,
+            faculty_id,
+        }
+    }
+
+    fn leaderboard_query(metric: LeaderboardMetric, academic_year: &str, faculty_id: Option<Uuid>) -> LeaderboardQuery {
+        LeaderboardQuery {
+            metric: Some(metric),
+            academic_year: Some(academic_year.to_string()),
+            faculty_id,
+            limit: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_leaderboard_faculty_scope() {
+        let pool = sqlx::PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let own_faculty = Uuid::new_v4();
+
+        let other_faculty = get_leaderboard(
+            State(test_state(&pool)),
+            admin(AdminLevel::FacultyAdmin, Some(own_faculty)),
+            Query(leaderboard_query(LeaderboardMetric::Hours, "2567", Some(Uuid::new_v4()))),
+        )
+        .await;
+        assert!(matches!(other_faculty, Err(ApiError::Forbidden(_))));
+
+        let bad_limit = get_leaderboard(
+            State(test_state(&pool)),
+            admin(AdminLevel::SuperAdmin, None),
+            Query(LeaderboardQuery { limit: Some(0), ..leaderboard_query(LeaderboardMetric::Hours, "2567", None) }),
+        )
+        .await;
+        assert!(matches!(bad_limit, Err(ApiError::Validation(_))));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_leaderboard_ranks_students() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let tag = Uuid::new_v4().simple().to_string()[..8].to_string();
+        let academic_year = format!("Y{}", tag);
+
+        let faculty_id: Uuid = sqlx::query_scalar("INSERT INTO faculties (name, code) VALUES ($1, $2) RETURNING id")
+            .bind(format!("Leaderboard {}", tag))
+            .bind(&tag)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let department_id: Uuid =
+            sqlx::query_scalar("INSERT INTO departments (name, code, faculty_id) VALUES ('Dept', 'D', $1) RETURNING id")
+                .bind(faculty_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+
+        let mut students = Vec::new();
+        for (i, department) in [Some(department_id), Some(department_id), Some(department_id), None]
+            .into_iter()
+            .enumerate()
+        {
+            let student_id = format!("lb{}{}", i, tag);
+            let user_id = insert_user(&pool, &student_id, None).await;
+            sqlx::query("UPDATE users SET department_id = $2 WHERE id = $1")
+                .bind(user_id)
+                .bind(department)
+                .execute(&pool)
+                .await
+                .unwrap();
+            students.push(user_id);
+        }
+
+        let mut activities = Vec::new();
+        for hours in [3, 2] {
+            let activity_id: Uuid = sqlx::query_scalar(
+                "INSERT INTO activities (title, description, location, academic_year, organizer,
+                    start_date, end_date, start_time_only, end_time_only, hours, status, created_by)
+                 VALUES ('Leaderboard', 'Leaderboard test', 'Lab', $1, 'Test', CURRENT_DATE, CURRENT_DATE,
+                    '09:00', '17:00', $2, 'completed', $3)
+                 RETURNING id",
+            )
+            .bind(&academic_year)
+            .bind(hours)
+            .bind(students[0])
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            activities.push(activity_id);
+        }
+
+        // student 0: both activities (5h), student 1: first (3h), student 2: checked in only,
+        // student 3: outside the faculty
+        for (student, activity, status) in [
+            (0, 0, "completed"),
+            (0, 1, "completed"),
+            (1, 0, "completed"),
+            (2, 1, "checked_in"),
+            (3, 0, "completed"),
+        ] {
+            sqlx::query(
+                "INSERT INTO participations (user_id, activity_id, status, checked_in_at)
+                 VALUES ($1, $2, $3::participation_status, NOW())",
+            )
+            .bind(students[student])
+            .bind(activities[activity])
+            .bind(status)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let by_hours = get_leaderboard(
+            State(test_state(&pool)),
+            admin(AdminLevel::SuperAdmin, None),
+            Query(leaderboard_query(LeaderboardMetric::Hours, &academic_year, Some(faculty_id))),
+        )
+        .await;
+        let by_checkins = get_leaderboard(
+            State(test_state(&pool)),
+            admin(AdminLevel::FacultyAdmin, Some(faculty_id)),
+            Query(leaderboard_query(LeaderboardMetric::Checkins, &academic_year, None)),
+        )
+        .await;
+
+        sqlx::query("DELETE FROM activities WHERE academic_year = $1")
+            .bind(&academic_year)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM users WHERE student_id LIKE $1")
+            .bind(format!("%{}", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM faculties WHERE id = $1")
+            .bind(faculty_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let Json(body) = by_hours.unwrap();
+        let entries = body["data"]["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["user_id"], json!(students[0]));
+        assert_eq!(entries[0]["total_hours"], 5);
+        assert_eq!(entries[0]["rank"], 1);
+        assert_eq!(entries[1]["user_id"], json!(students[1]));
+        assert_eq!(entries[1]["total_hours"], 3);
+
+        let Json(body) = by_checkins.unwrap();
+        let entries = body["data"]["entries"].as_array().unwrap();
+        assert_eq!(body["data"]["faculty_id"], json!(faculty_id));
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0]["checkins"], 2);
+        assert_eq!(entries[1]["rank"], 2);
+        assert_eq!(entries[2]["rank"], 2);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_registration_trends_buckets() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let tag = Uuid::new_v4().simple().to_string()[..8].to_string();
+
+        let faculty_id: Uuid = sqlx::query_scalar("INSERT INTO faculties (name, code) VALUES ($1, $2) RETURNING id")
+            .bind(format!("Trends {}", tag))
+            .bind(&tag)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let department_id: Uuid =
+            sqlx::query_scalar("INSERT INTO departments (name, code, faculty_id) VALUES ('Dept', 'D', $1) RETURNING id")
+                .bind(faculty_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+
+        let mut users = Vec::new();
+        for (i, created_at) in ["2024-01-03 10:00", "2024-01-03 23:30", "2024-01-20 08:00", "2024-03-01 00:10"]
+            .into_iter()
+            .enumerate()
+        {
+            let student_id = format!("tr{}{}", i, tag);
+            let user_id = insert_user(&pool, &student_id, None).await;
+            sqlx::query("UPDATE users SET department_id = $2, created_at = $3::timestamp AT TIME ZONE 'UTC' WHERE id = $1")
+                .bind(user_id)
+                .bind(department_id)
+                .bind(created_at)
+                .execute(&pool)
+                .await
+                .unwrap();
+            users.push(user_id);
+        }
+        let activity_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO activities (title, description, location, academic_year, organizer,
+                start_date, end_date, start_time_only, end_time_only, hours, status, created_by)
+             VALUES ('Trends', 'Trends test', 'Lab', '2567', 'Test', '2024-01-25', '2024-01-25',
+                '09:00', '17:00', 1, 'completed', $1)
+             RETURNING id",
+        )
+        .bind(users[0])
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        for user_id in &users[..2] {
+            sqlx::query(
+                "INSERT INTO participations (user_id, activity_id, registered_at)
+                 VALUES ($1, $2, TIMESTAMPTZ '2024-01-21 12:00:00+00')",
+            )
+            .bind(user_id)
+            .bind(activity_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let trends = |granularity, date_from: &str, date_to: &str| {
+            get_registration_trends(
+                State(test_state(&pool)),
+                admin(AdminLevel::FacultyAdmin, Some(faculty_id)),
+                Query(RegistrationTrendQuery {
+                    date_from: Some(date_from.parse().unwrap()),
+                    date_to: Some(date_to.parse().unwrap()),
+                    granularity: Some(granularity),
+                    faculty_id: None,
+                }),
+            )
+        };
+        let by_month = trends(TrendGranularity::Month, "2024-01-01", "2024-03-31").await;
+        let by_day = trends(TrendGranularity::Day, "2024-01-02", "2024-01-04").await;
+        let reversed = trends(TrendGranularity::Day, "2024-01-04", "2024-01-02").await;
+
+        sqlx::query("DELETE FROM activities WHERE id = $1")
+            .bind(activity_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM users WHERE student_id LIKE $1")
+            .bind(format!("%{}", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM faculties WHERE id = $1")
+            .bind(faculty_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let Json(body) = by_month.unwrap();
+        assert_eq!(body["data"]["faculty_id"], json!(faculty_id));
+        assert_eq!(
+            body["data"]["points"],
+            json!([
+                { "period_start": "2024-01-01", "new_users": 3, "new_participations": 2 },
+                { "period_start": "2024-02-01", "new_users": 0, "new_participations": 0 },
+                { "period_start": "2024-03-01", "new_users": 1, "new_participations": 0 }
+            ])
+        );
+
+        let Json(body) = by_day.unwrap();
+        let new_users: Vec<i64> = body["data"]["points"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["new_users"].as_i64().unwrap())
+            .collect();
+        assert_eq!(new_users, vec![0, 2, 0]);
+        assert_eq!(body["data"]["total_new_users"], 2);
+
+        assert!(matches!(reversed, Err(ApiError::Validation(_))));
+    }
+
+    /// Without Redis every lookup misses, the stats are computed from the database and
+    /// `active_sessions` falls back to the `sessions` table
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_dashboard_falls_back_without_cache() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let total_users: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+        // Nothing listens on port 1, so every Redis call fails
+        let state = SessionState {
+            session_store: Arc::new(RedisSessionStore::new("redis://127.0.0.1:1/").unwrap()),
+            ..test_state(&pool)
+        };
+        let super_admin = admin(AdminLevel::SuperAdmin, None);
+        let result = get_dashboard(
+            State(state.clone()),
+            AdminUser {
+                session_user: super_admin.session_user,
+                admin_role: super_admin.admin_role,
+            },
+            Query(DashboardQuery { refresh: None }),
+        )
+        .await;
+
+        let Json(body) = result.unwrap();
+        assert_eq!(body["message"], "Dashboard statistics retrieved successfully");
+        assert!(body["data"]["total_users"].as_i64().unwrap() >= total_users);
+        assert_eq!(body["data"]["redis_available"], false);
+        assert!(!state.redis_health.is_available());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_update_admin_role_permissions_round_trip() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+        let tag = Uuid::new_v4().simple().to_string()[..8].to_string();
+
+        let faculty_id: Uuid = sqlx::query_scalar("INSERT INTO faculties (name, code) VALUES ($1, $2) RETURNING id")
+            .bind(format!("Role {}", tag))
+            .bind(&tag)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let user_ids = [
+            insert_user(&pool, &format!("ra{}", tag), Some(AdminLevel::FacultyAdmin)).await,
+            insert_user(&pool, &format!("rs{}", tag), None).await,
+        ];
+        let (role_id, loaded_at): (Uuid, DateTime<Utc>) = sqlx::query_as(
+            "UPDATE admin_roles SET faculty_id = $2 WHERE user_id = $1 RETURNING id, updated_at",
+        )
+        .bind(user_ids[0])
+        .bind(faculty_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        let super_admin = || {
+            let FacultyAdminUser { mut session_user, mut admin_role, .. } = admin(AdminLevel::SuperAdmin, None);
+            session_user.user_id = user_ids[1];
+            admin_role.user_id = user_ids[1];
+            SuperAdminUser { session_user, admin_role }
+        };
+        let update = |expected_updated_at| {
+            update_admin_role(
+                State(state.clone()),
+                Path(role_id),
+                super_admin(),
+                ApiJson(UpdateAdminRoleRequest {
+                    admin_level: None,
+                    faculty_id: None,
+                    permissions: Some(vec!["ManageActivities".to_string()]),
+                    expected_updated_at: Some(expected_updated_at),
+                }),
+            )
+        };
+
+        let Json(body) = update(loaded_at).await.unwrap();
+        let role: AdminRole = serde_json::from_value(body["data"]["admin_role"].clone()).unwrap();
+        assert_eq!(role.id, role_id);
+        assert_eq!(role.permissions, vec!["ManageActivities".to_string()]);
+        assert_eq!(role.faculty_id, Some(faculty_id));
+        assert!(role.is_enabled);
+
+        // The first update moved updated_at, so the same expected value is now stale
+        let stale = update(loaded_at).await;
+
+        sqlx::query("DELETE FROM users WHERE student_id LIKE $1")
+            .bind(format!("%{}", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM faculties WHERE id = $1")
+            .bind(faculty_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        match stale {
+            Err((status, Json(body))) => {
+                assert_eq!(status, StatusCode::CONFLICT);
+                assert_eq!(body["current_updated_at"], json!(role.updated_at));
+            }
+            Ok(Json(body)) => panic!("expected 409 for a stale update, got {}", body),
+        }
+    }
+
+    #[test]
+    fn test_removes_last_super_admin() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        assert!(removes_last_super_admin(&[a], &[a]));
+        assert!(removes_last_super_admin(&[a, b], &[a, b, Uuid::new_v4()]));
+        assert!(!removes_last_super_admin(&[a, b], &[a]));
+        // Touching only non-SuperAdmin roles never counts as removing one
+        assert!(!removes_last_super_admin(&[], &[a]));
+        assert!(!removes_last_super_admin(&[a], &[b]));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_disabling_last_super_admin_is_rejected() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+        let tag = Uuid::new_v4().simple().to_string()[..8].to_string();
+
+        let student_id = format!("sa{}", tag);
+        let user_id = insert_user(&pool, &student_id, Some(AdminLevel::SuperAdmin)).await;
+        let role_id: Uuid = sqlx::query_scalar("SELECT id FROM admin_roles WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        // Whatever other SuperAdmins the database holds, disabling all of them must fail
+        let enabled_super_admins: Vec<Uuid> = sqlx::query_scalar(
+            "SELECT id FROM admin_roles WHERE admin_level = 'super_admin' AND is_enabled = TRUE",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+
+        let FacultyAdminUser { mut session_user, mut admin_role, .. } = admin(AdminLevel::SuperAdmin, None);
+        session_user.user_id = user_id;
+        admin_role.id = role_id;
+        admin_role.user_id = user_id;
+        let result = bulk_admin_operations(
+            State(state.clone()),
+            SuperAdminUser { session_user, admin_role },
+            ApiJson(BulkAdminOperationRequest {
+                operation: "deactivate".to_string(),
+                admin_role_ids: enabled_super_admins,
+                parameters: None,
+            }),
+        )
+        .await;
+        let still_enabled: bool = sqlx::query_scalar("SELECT is_enabled FROM admin_roles WHERE id = $1")
+            .bind(role_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        match result {
+            Err((status, _)) => assert_eq!(status, StatusCode::CONFLICT),
+            Ok(Json(body)) => panic!("expected 409 when disabling every SuperAdmin, got {}", body),
+        }
+        assert!(still_enabled);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_bulk_delete_keeps_users_with_activities() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let tag = Uuid::new_v4().simple().to_string()[..8].to_string();
+        let mut user_ids = Vec::new();
+        let mut role_ids = Vec::new();
+        for prefix in ["bd", "be"] {
+            let user_id = insert_user(&pool, &format!("{}{}", prefix, tag), Some(AdminLevel::RegularAdmin)).await;
+            let role_id: Uuid = sqlx::query_scalar("SELECT id FROM admin_roles WHERE user_id = $1")
+                .bind(user_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            user_ids.push(user_id);
+            role_ids.push(role_id);
+        }
+        let activity_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO activities (title, description, location, academic_year, organizer,
+                start_date, end_date, start_time_only, end_time_only, hours, status, created_by)
+             VALUES ('Bulk delete', 'Bulk delete test', 'Lab', '2567', 'Test', CURRENT_DATE, CURRENT_DATE,
+                '09:00', '12:00', 1, 'published', $1)
+             RETURNING id",
+        )
+        .bind(user_ids[0])
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        let mut tx = pool.begin().await.unwrap();
+        let (_, kept_message) = delete_admin_role(&mut tx, role_ids[0], true).await.unwrap();
+        let (_, deleted_message) = delete_admin_role(&mut tx, role_ids[1], true).await.unwrap();
+        tx.commit().await.unwrap();
+
+        let activity_exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM activities WHERE id = $1)")
+            .bind(activity_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let creator_active: Option<bool> = sqlx::query_scalar("SELECT is_active FROM users WHERE id = $1")
+            .bind(user_ids[0])
+            .fetch_optional(&pool)
+            .await
+            .unwrap()
+            .flatten();
+        let other_exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE id = $1)")
+            .bind(user_ids[1])
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+        sqlx::query("DELETE FROM users WHERE id = ANY($1)")
+            .bind(&user_ids)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(activity_exists);
+        assert_eq!(creator_active, Some(false));
+        assert!(kept_message.contains("deactivated"));
+        assert!(!other_exists);
+        assert_eq!(deleted_message, "Admin and user account deleted successfully");
+    }
+
+}
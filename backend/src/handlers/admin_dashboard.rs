@@ -306,7 +306,7 @@ async fn get_system_health_stats(
 ) -> Result<SystemHealthStats, (StatusCode, Json<serde_json::Value>)> {
     // Get active sessions count
     let active_sessions = session_state
-        .redis_store
+        .session_store
         .get_active_sessions(None)
         .await
         .map(|sessions| sessions.len())
@@ -322,7 +322,7 @@ async fn get_system_health_stats(
     };
 
     // Check Redis status - simple ping check
-    let redis_status = match session_state.redis_store.get_active_sessions(Some(1)).await {
+    let redis_status = match session_state.session_store.get_active_sessions(Some(1)).await {
         Ok(_) => "healthy",
         Err(_) => "unhealthy",
     };
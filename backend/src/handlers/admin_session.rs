@@ -10,12 +10,17 @@ use std::collections::HashMap;
 use uuid::Uuid;
 // use sqlx::Row; // Removed unused import
 
+use crate::error::ApiJson;
 use crate::middleware::session::{AdminUser, FacultyAdminUser, SessionState, SuperAdminUser};
 use crate::models::{
     admin_role::{AdminLevel, AdminRole},
+    audit_log::NewAuditLog,
+    department::Department,
     faculty::Faculty,
+    session::{GeoLocation, Session},
     user::User,
 };
+use crate::services::audit_log::insert_audit_log;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AdminSessionListResponse {
@@ -39,6 +44,8 @@ pub struct EnhancedAdminSessionInfo {
     pub department_name: Option<String>,
     pub device_info: HashMap<String, Value>,
     pub ip_address: Option<String>,
+    /// Country/city of `ip_address` at login; `None` without GeoIP
+    pub geo_location: Option<GeoLocation>,
     pub user_agent: Option<String>,
     pub created_at: DateTime<Utc>,
     pub last_accessed: DateTime<Utc>,
@@ -86,19 +93,22 @@ pub struct BulkSessionActionResponse {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SessionAuditLogResponse {
     pub logs: Vec<SessionAuditEntry>,
-    pub total_count: usize,
+    pub total_count: i64,
+    pub limit: i64,
+    pub offset: i64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
 pub struct SessionAuditEntry {
+    pub id: Uuid,
     pub timestamp: DateTime<Utc>,
     pub action: String,
-    pub session_id: String,
-    pub user_id: Uuid,
-    pub admin_id: Uuid,
-    pub admin_name: String,
+    pub target_type: String,
+    pub target_id: Option<String>,
+    pub admin_id: Option<Uuid>,
+    pub admin_name: Option<String>,
     pub reason: Option<String>,
-    pub details: HashMap<String, Value>,
+    pub details: Value,
 }
 
 // Super Admin: Get comprehensive session overview
@@ -120,68 +130,70 @@ pub async fn get_admin_sessions_overview(
 
     // Get all active sessions
     let session_ids = session_state
-        .redis_store
+        .session_store
         .get_active_sessions(Some(limit * 2))
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
+    let active_sessions = load_sessions(&session_state, &session_ids).await;
+    let directory = SessionDirectory::load(&session_state, &active_sessions)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
     let mut sessions = Vec::new();
     let mut faculty_breakdown = HashMap::new();
     let mut admin_level_breakdown = HashMap::new();
 
-    for session_id in &session_ids {
-        if let Ok(Some(session)) = session_state.redis_store.get_session(session_id).await {
-            // Get detailed user info
-            if let Ok(Some(enhanced_info)) =
-                get_enhanced_session_info(&session_state, &admin.session_user.session_id, &session)
-                    .await
-            {
-                // Apply filters
-                if let Some(faculty_id) = faculty_filter {
-                    if enhanced_info.faculty_id != Some(faculty_id) {
-                        continue;
-                    }
-                }
-
-                if let Some(level_filter) = admin_level_filter {
-                    let session_level = enhanced_info
-                        .admin_level
-                        .as_ref()
-                        .map(|l| format!("{:?}", l))
-                        .unwrap_or_else(|| "Student".to_string());
-
-                    if session_level != *level_filter {
-                        continue;
-                    }
-                }
-
-                // Update breakdowns
-                if let Some(faculty_name) = &enhanced_info.faculty_name {
-                    *faculty_breakdown.entry(faculty_name.clone()).or_insert(0) += 1;
-                } else {
-                    *faculty_breakdown
-                        .entry("No Faculty".to_string())
-                        .or_insert(0) += 1;
+    for session in &active_sessions {
+        // Get detailed user info
+        if let Some(enhanced_info) =
+            directory.enhanced_session_info(&admin.session_user.session_id, session)
+        {
+            // Apply filters
+            if let Some(faculty_id) = faculty_filter {
+                if enhanced_info.faculty_id != Some(faculty_id) {
+                    continue;
                 }
+            }
 
-                let level_name = enhanced_info
+            if let Some(level_filter) = admin_level_filter {
+                let session_level = enhanced_info
                     .admin_level
                     .as_ref()
                     .map(|l| format!("{:?}", l))
                     .unwrap_or_else(|| "Student".to_string());
-                *admin_level_breakdown.entry(level_name).or_insert(0) += 1;
-
-                sessions.push(enhanced_info);
 
-                if sessions.len() >= limit {
-                    break;
+                if session_level != *level_filter {
+                    continue;
                 }
             }
+
+            // Update breakdowns
+            if let Some(faculty_name) = &enhanced_info.faculty_name {
+                *faculty_breakdown.entry(faculty_name.clone()).or_insert(0) += 1;
+            } else {
+                *faculty_breakdown
+                    .entry("No Faculty".to_string())
+                    .or_insert(0) += 1;
+            }
+
+            let level_name = enhanced_info
+                .admin_level
+                .as_ref()
+                .map(|l| format!("{:?}", l))
+                .unwrap_or_else(|| "Student".to_string());
+            *admin_level_breakdown.entry(level_name).or_insert(0) += 1;
+
+            sessions.push(enhanced_info);
+
+            if sessions.len() >= limit {
+                break;
+            }
         }
     }
 
     let total_count = session_state
-        .redis_store
+        .session_store
         .get_session_count()
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -214,34 +226,36 @@ pub async fn get_faculty_sessions(
     };
 
     let session_ids = session_state
-        .redis_store
+        .session_store
         .get_active_sessions(None)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
+    let active_sessions = load_sessions(&session_state, &session_ids).await;
+    let directory = SessionDirectory::load(&session_state, &active_sessions)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
     let mut sessions = Vec::new();
     let mut admin_level_breakdown = HashMap::new();
 
-    for session_id in &session_ids {
-        if let Ok(Some(session)) = session_state.redis_store.get_session(session_id).await {
-            if let Ok(Some(enhanced_info)) =
-                get_enhanced_session_info(&session_state, &admin.session_user.session_id, &session)
-                    .await
-            {
-                // Only include sessions from the same faculty
-                if enhanced_info.faculty_id == Some(faculty_id) {
-                    let level_name = enhanced_info
-                        .admin_level
-                        .as_ref()
-                        .map(|l| format!("{:?}", l))
-                        .unwrap_or_else(|| "Student".to_string());
-                    *admin_level_breakdown.entry(level_name).or_insert(0) += 1;
-
-                    sessions.push(enhanced_info);
-
-                    if sessions.len() >= limit {
-                        break;
-                    }
+    for session in &active_sessions {
+        if let Some(enhanced_info) =
+            directory.enhanced_session_info(&admin.session_user.session_id, session)
+        {
+            // Only include sessions from the same faculty
+            if enhanced_info.faculty_id == Some(faculty_id) {
+                let level_name = enhanced_info
+                    .admin_level
+                    .as_ref()
+                    .map(|l| format!("{:?}", l))
+                    .unwrap_or_else(|| "Student".to_string());
+                *admin_level_breakdown.entry(level_name).or_insert(0) += 1;
+
+                sessions.push(enhanced_info);
+
+                if sessions.len() >= limit {
+                    break;
                 }
             }
         }
@@ -271,7 +285,7 @@ pub async fn get_session_stats(
     _admin: SuperAdminUser,
 ) -> Result<Json<SessionStatsResponse>, StatusCode> {
     let session_ids = session_state
-        .redis_store
+        .session_store
         .get_active_sessions(None)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -284,76 +298,72 @@ pub async fn get_session_stats(
     let mut user_session_counts: HashMap<Uuid, (User, AdminRole, usize, DateTime<Utc>)> =
         HashMap::new();
 
-    for session_id in &session_ids {
-        if let Ok(Some(session)) = session_state.redis_store.get_session(session_id).await {
-            // Get user info
-            if let Ok(Some(user)) = get_user_by_id(&session_state, session.user_id).await {
-                // Get admin role
-                let admin_role = get_user_admin_role(&session_state, user.id)
-                    .await
-                    .ok()
-                    .flatten();
-
-                // Count by admin status
-                if admin_role.is_some() {
-                    admin_sessions += 1;
-                } else {
-                    student_sessions += 1;
-                }
+    let active_sessions = load_sessions(&session_state, &session_ids).await;
+    let directory = SessionDirectory::load(&session_state, &active_sessions)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-                // Count by admin level
-                let level_name = admin_role
-                    .as_ref()
-                    .map(|r| format!("{:?}", r.admin_level))
-                    .unwrap_or_else(|| "Student".to_string());
-                *sessions_by_level.entry(level_name).or_insert(0) += 1;
+    for session in active_sessions {
+        // Get user info
+        if let Some(user) = directory.users.get(&session.user_id).cloned() {
+            // Get admin role
+            let admin_role = directory.admin_roles.get(&user.id).cloned();
+
+            // Count by admin status
+            if admin_role.is_some() {
+                admin_sessions += 1;
+            } else {
+                student_sessions += 1;
+            }
 
-                // Count by faculty
-                if let Some(ref role) = admin_role {
-                    if let Some(faculty_id) = role.faculty_id {
-                        if let Ok(Some(faculty)) =
-                            get_faculty_by_id(&session_state, faculty_id).await
-                        {
-                            *sessions_by_faculty.entry(faculty.name).or_insert(0) += 1;
-                        }
-                    }
-                } else {
-                    *sessions_by_faculty
-                        .entry("No Faculty".to_string())
-                        .or_insert(0) += 1;
+            // Count by admin level
+            let level_name = admin_role
+                .as_ref()
+                .map(|r| format!("{:?}", r.admin_level))
+                .unwrap_or_else(|| "Student".to_string());
+            *sessions_by_level.entry(level_name).or_insert(0) += 1;
+
+            // Count by faculty
+            if let Some(ref role) = admin_role {
+                if let Some(faculty) = role.faculty_id.and_then(|id| directory.faculties.get(&id)) {
+                    *sessions_by_faculty.entry(faculty.name.clone()).or_insert(0) += 1;
                 }
-
-                // Count by device type
-                let device_type = session
-                    .device_info
-                    .get("device_type")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("unknown");
-                *sessions_by_device
-                    .entry(device_type.to_string())
+            } else {
+                *sessions_by_faculty
+                    .entry("No Faculty".to_string())
                     .or_insert(0) += 1;
+            }
 
-                // Track user session counts
-                let user_id = user.id;
-                let entry = user_session_counts.entry(user_id).or_insert((
-                    user,
-                    admin_role.unwrap_or_else(|| AdminRole {
-                        id: Uuid::new_v4(),
-                        user_id,
-                        admin_level: crate::models::admin_role::AdminLevel::RegularAdmin,
-                        faculty_id: None,
-                        permissions: vec![],
-                        is_enabled: true,
-                        created_at: Some(chrono::Utc::now()),
-                        updated_at: Some(chrono::Utc::now()),
-                    }),
-                    0,
-                    session.last_accessed,
-                ));
-                entry.2 += 1;
-                if session.last_accessed > entry.3 {
-                    entry.3 = session.last_accessed;
-                }
+            // Count by device type
+            let device_type = session
+                .device_info
+                .get("device_type")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown");
+            *sessions_by_device
+                .entry(device_type.to_string())
+                .or_insert(0) += 1;
+
+            // Track user session counts
+            let user_id = user.id;
+            let entry = user_session_counts.entry(user_id).or_insert((
+                user,
+                admin_role.unwrap_or_else(|| AdminRole {
+                    id: Uuid::new_v4(),
+                    user_id,
+                    admin_level: crate::models::admin_role::AdminLevel::RegularAdmin,
+                    faculty_id: None,
+                    permissions: vec![],
+                    is_enabled: true,
+                    created_at: Some(chrono::Utc::now()),
+                    updated_at: Some(chrono::Utc::now()),
+                }),
+                0,
+                session.last_accessed,
+            ));
+            entry.2 += 1;
+            if session.last_accessed > entry.3 {
+                entry.3 = session.last_accessed;
             }
         }
     }
@@ -392,7 +402,7 @@ pub async fn get_session_stats(
 pub async fn bulk_session_action(
     State(session_state): State<SessionState>,
     admin: AdminUser,
-    Json(req): Json<BulkSessionActionRequest>,
+    ApiJson(req): ApiJson<BulkSessionActionRequest>,
 ) -> Result<Json<BulkSessionActionResponse>, StatusCode> {
     let mut processed_count = 0;
     let mut failed_count = 0;
@@ -406,7 +416,7 @@ pub async fn bulk_session_action(
 
             for session_id in &req.session_ids {
                 match session_state
-                    .redis_store
+                    .session_store
                     .revoke_session(session_id, Some(reason.clone()))
                     .await
                 {
@@ -439,7 +449,7 @@ pub async fn bulk_session_action(
 
             for session_id in &req.session_ids {
                 match session_state
-                    .redis_store
+                    .session_store
                     .extend_session(session_id, new_expiry)
                     .await
                 {
@@ -485,53 +495,104 @@ pub async fn bulk_session_action(
 }
 
 // Helper functions
-async fn get_enhanced_session_info(
-    session_state: &SessionState,
-    current_session_id: &str,
-    session: &crate::models::session::Session,
-) -> Result<Option<EnhancedAdminSessionInfo>, anyhow::Error> {
-    // Get user info
-    let user = match get_user_by_id(session_state, session.user_id).await? {
-        Some(user) => user,
-        None => return Ok(None),
-    };
 
-    // Get admin role
-    let admin_role = get_user_admin_role(session_state, user.id).await?;
-
-    // Get faculty and department info
-    let (faculty_name, department_name) = if let Some(department_id) = user.department_id {
-        let department = get_department_by_id(session_state, department_id).await?;
-        let faculty_name = if let Some(dept) = &department {
-            get_faculty_by_id(session_state, dept.faculty_id)
-                .await?
-                .map(|f| f.name)
-        } else {
-            None
-        };
-        (faculty_name, department.map(|d| d.name))
-    } else {
-        (None, None)
-    };
+/// Fetch session records from Redis, skipping ids that have expired or fail to load
+async fn load_sessions(session_state: &SessionState, session_ids: &[String]) -> Vec<Session> {
+    let mut sessions = Vec::with_capacity(session_ids.len());
+    for session_id in session_ids {
+        if let Ok(Some(session)) = session_state.session_store.get_session(session_id).await {
+            sessions.push(session);
+        }
+    }
+    sessions
+}
 
-    Ok(Some(EnhancedAdminSessionInfo {
-        session_id: session.id.clone(),
-        user_id: user.id,
-        user_name: format!("{} {}", user.first_name, user.last_name),
-        student_id: user.student_id,
-        email: user.email,
-        admin_level: admin_role.as_ref().map(|r| r.admin_level.clone()),
-        faculty_id: admin_role.as_ref().and_then(|r| r.faculty_id),
-        faculty_name,
-        department_name,
-        device_info: session.device_info.clone(),
-        ip_address: session.ip_address.clone(),
-        user_agent: session.user_agent.clone(),
-        created_at: session.created_at,
-        last_accessed: session.last_accessed,
-        expires_at: session.expires_at,
-        is_current_session: session.id == current_session_id,
-    }))
+/// Users, admin roles, departments and faculties behind a batch of sessions, loaded with one
+/// `= ANY($1)` query per table instead of four lookups per session
+#[derive(Debug, Default)]
+struct SessionDirectory {
+    users: HashMap<Uuid, User>,
+    /// Keyed by user id
+    admin_roles: HashMap<Uuid, AdminRole>,
+    departments: HashMap<Uuid, Department>,
+    faculties: HashMap<Uuid, Faculty>,
+}
+
+impl SessionDirectory {
+    async fn load(session_state: &SessionState, sessions: &[Session]) -> Result<Self, anyhow::Error> {
+        let pool = &session_state.db_pool;
+        let mut user_ids: Vec<Uuid> = sessions.iter().map(|s| s.user_id).collect();
+        user_ids.sort_unstable();
+        user_ids.dedup();
+
+        let users = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = ANY($1)")
+            .bind(&user_ids)
+            .fetch_all(pool)
+            .await?;
+        let admin_roles = sqlx::query_as::<_, AdminRole>("SELECT * FROM admin_roles WHERE user_id = ANY($1)")
+            .bind(&user_ids)
+            .fetch_all(pool)
+            .await?;
+
+        let department_ids: Vec<Uuid> = users.iter().filter_map(|u| u.department_id).collect();
+        let departments = sqlx::query_as::<_, Department>("SELECT * FROM departments WHERE id = ANY($1)")
+            .bind(&department_ids)
+            .fetch_all(pool)
+            .await?;
+
+        let faculty_ids: Vec<Uuid> = departments
+            .iter()
+            .map(|d| d.faculty_id)
+            .chain(admin_roles.iter().filter_map(|r| r.faculty_id))
+            .collect();
+        let faculties = sqlx::query_as::<_, Faculty>("SELECT * FROM faculties WHERE id = ANY($1)")
+            .bind(&faculty_ids)
+            .fetch_all(pool)
+            .await?;
+
+        Ok(Self {
+            users: users.into_iter().map(|u| (u.id, u)).collect(),
+            admin_roles: admin_roles.into_iter().map(|r| (r.user_id, r)).collect(),
+            departments: departments.into_iter().map(|d| (d.id, d)).collect(),
+            faculties: faculties.into_iter().map(|f| (f.id, f)).collect(),
+        })
+    }
+
+    /// `None` when the session's user no longer exists
+    fn enhanced_session_info(
+        &self,
+        current_session_id: &str,
+        session: &Session,
+    ) -> Option<EnhancedAdminSessionInfo> {
+        let user = self.users.get(&session.user_id)?;
+        let admin_role = self.admin_roles.get(&user.id);
+
+        // Faculty and department come from the user's department
+        let department = user.department_id.and_then(|id| self.departments.get(&id));
+        let faculty_name = department
+            .and_then(|d| self.faculties.get(&d.faculty_id))
+            .map(|f| f.name.clone());
+
+        Some(EnhancedAdminSessionInfo {
+            session_id: session.id.clone(),
+            user_id: user.id,
+            user_name: format!("{} {}", user.first_name, user.last_name),
+            student_id: user.student_id.clone(),
+            email: user.email.clone(),
+            admin_level: admin_role.map(|r| r.admin_level.clone()),
+            faculty_id: admin_role.and_then(|r| r.faculty_id),
+            faculty_name,
+            department_name: department.map(|d| d.name.clone()),
+            device_info: session.device_info.clone(),
+            ip_address: session.ip_address.clone(),
+            geo_location: session.geo_location.clone(),
+            user_agent: session.user_agent.clone(),
+            created_at: session.created_at,
+            last_accessed: session.last_accessed,
+            expires_at: session.expires_at,
+            is_current_session: session.id == current_session_id,
+        })
+    }
 }
 
 async fn get_user_by_id(
@@ -570,20 +631,6 @@ async fn get_faculty_by_id(
     Ok(faculty)
 }
 
-async fn get_department_by_id(
-    session_state: &SessionState,
-    department_id: Uuid,
-) -> Result<Option<crate::models::department::Department>, anyhow::Error> {
-    let department = sqlx::query_as::<_, crate::models::department::Department>(
-        "SELECT * FROM departments WHERE id = $1",
-    )
-    .bind(department_id)
-    .fetch_optional(&session_state.db_pool)
-    .await?;
-
-    Ok(department)
-}
-
 // Main handlers for routes
 
 /// Get sessions (simplified endpoint for admin_session routes)
@@ -601,7 +648,7 @@ pub async fn get_sessions(
 
     // Get active session IDs from Redis
     let session_ids_result = session_state
-        .redis_store
+        .session_store
         .get_active_sessions(Some(limit * 2))
         .await;
 
@@ -610,7 +657,7 @@ pub async fn get_sessions(
             let mut sessions = Vec::new();
 
             for session_id in &session_ids {
-                if let Ok(Some(session)) = session_state.redis_store.get_session(session_id).await {
+                if let Ok(Some(session)) = session_state.session_store.get_session(session_id).await {
                     // Get user info
                     if let Ok(Some(user)) = get_user_by_id(&session_state, session.user_id).await {
                         // Apply search filter
@@ -658,7 +705,7 @@ pub async fn get_sessions(
             }
 
             let total_count = session_state
-                .redis_store
+                .session_store
                 .get_session_count()
                 .await
                 .map_err(|_| {
@@ -699,7 +746,7 @@ pub async fn revoke_session(
     axum::extract::Path(session_id): axum::extract::Path<String>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     // Check if the session exists
-    let session_exists = match session_state.redis_store.get_session(&session_id).await {
+    let session_exists = match session_state.session_store.get_session(&session_id).await {
         Ok(Some(_)) => true,
         Ok(None) => false,
         Err(_) => {
@@ -731,7 +778,7 @@ pub async fn revoke_session(
     let reason = format!("Revoked by admin: {}", admin.session_user.email);
 
     match session_state
-        .redis_store
+        .session_store
         .revoke_session(&session_id, Some(reason.clone()))
         .await
     {
@@ -775,7 +822,7 @@ pub async fn cleanup_expired(
     admin: SuperAdminUser,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     // Get all active sessions
-    let session_ids_result = session_state.redis_store.get_active_sessions(None).await;
+    let session_ids_result = session_state.session_store.get_active_sessions(None).await;
 
     match session_ids_result {
         Ok(session_ids) => {
@@ -784,11 +831,11 @@ pub async fn cleanup_expired(
             let current_time = Utc::now();
 
             for session_id in &session_ids {
-                if let Ok(Some(session)) = session_state.redis_store.get_session(session_id).await {
+                if let Ok(Some(session)) = session_state.session_store.get_session(session_id).await {
                     // Check if session has expired
                     if session.expires_at <= current_time {
                         match session_state
-                            .redis_store
+                            .session_store
                             .revoke_session(session_id, Some("Expired session cleanup".to_string()))
                             .await
                         {
@@ -873,7 +920,7 @@ pub async fn get_active_admin_sessions(
         .unwrap_or(50);
 
     // Get active session IDs from Redis
-    let session_ids_result = session_state.redis_store.get_active_sessions(None).await;
+    let session_ids_result = session_state.session_store.get_active_sessions(None).await;
 
     match session_ids_result {
         Ok(session_ids) => {
@@ -882,7 +929,7 @@ pub async fn get_active_admin_sessions(
             let mut faculty_breakdown = HashMap::new();
 
             for session_id in &session_ids {
-                if let Ok(Some(session)) = session_state.redis_store.get_session(session_id).await {
+                if let Ok(Some(session)) = session_state.session_store.get_session(session_id).await {
                     // Get user info
                     if let Ok(Some(user)) = get_user_by_id(&session_state, session.user_id).await {
                         // Only include sessions with admin roles
@@ -979,15 +1026,138 @@ pub async fn get_active_admin_sessions(
     }
 }
 
+/// Super Admin: List audit log entries (newest first)
+/// Filters: actor_id, action, target_type, from/to (YYYY-MM-DD, inclusive)
+pub async fn get_audit_logs(
+    State(session_state): State<SessionState>,
+    _admin: SuperAdminUser,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let bad_request = |message: String| {
+        let error_response = serde_json::json!({
+            "status": "error",
+            "message": message
+        });
+        (StatusCode::BAD_REQUEST, Json(error_response))
+    };
+
+    let limit = params
+        .get("limit")
+        .and_then(|l| l.parse::<i64>().ok())
+        .unwrap_or(50)
+        .clamp(1, 200);
+    let offset = params
+        .get("offset")
+        .and_then(|o| o.parse::<i64>().ok())
+        .unwrap_or(0)
+        .max(0);
+
+    let actor_id = match params.get("actor_id").filter(|a| !a.is_empty()) {
+        Some(raw) => Some(
+            Uuid::parse_str(raw).map_err(|_| bad_request("Invalid actor_id".to_string()))?,
+        ),
+        None => None,
+    };
+    let action = params.get("action").filter(|a| !a.is_empty());
+    let target_type = params.get("target_type").filter(|t| !t.is_empty());
+
+    let mut date_range = [None, None];
+    for (i, key) in ["from", "to"].into_iter().enumerate() {
+        if let Some(raw) = params.get(key).filter(|d| !d.is_empty()) {
+            let date = chrono::NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+                .map_err(|_| bad_request(format!("Invalid {}: expected YYYY-MM-DD", key)))?;
+            date_range[i] = Some(date);
+        }
+    }
+    let [from, to] = date_range;
+
+    if let (Some(from), Some(to)) = (from, to) {
+        if from > to {
+            return Err(bad_request("from must be on or before to".to_string()));
+        }
+    }
+
+    let filter = r#"
+        WHERE ($1::uuid IS NULL OR l.actor_user_id = $1)
+          AND ($2::text IS NULL OR l.action = $2)
+          AND ($3::text IS NULL OR l.target_type = $3)
+          AND ($4::date IS NULL OR l.created_at >= $4::date)
+          AND ($5::date IS NULL OR l.created_at < $5::date + 1)
+    "#;
+
+    let logs_query = format!(
+        r#"
+        SELECT
+            l.id,
+            l.created_at as timestamp,
+            l.action,
+            l.target_type,
+            l.target_id,
+            l.actor_user_id as admin_id,
+            CASE WHEN u.id IS NULL THEN NULL ELSE u.first_name || ' ' || u.last_name END as admin_name,
+            l.reason,
+            l.details
+        FROM audit_logs l
+        LEFT JOIN users u ON u.id = l.actor_user_id
+        {}
+        ORDER BY l.created_at DESC, l.id DESC
+        LIMIT $6 OFFSET $7
+        "#,
+        filter
+    );
+    let count_query = format!("SELECT COUNT(*) FROM audit_logs l {}", filter);
+
+    let logs = sqlx::query_as::<_, SessionAuditEntry>(&logs_query)
+        .bind(actor_id)
+        .bind(action)
+        .bind(target_type)
+        .bind(from)
+        .bind(to)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&session_state.db_pool)
+        .await;
+
+    let total_count = sqlx::query_scalar::<_, i64>(&count_query)
+        .bind(actor_id)
+        .bind(action)
+        .bind(target_type)
+        .bind(from)
+        .bind(to)
+        .fetch_one(&session_state.db_pool)
+        .await;
+
+    match (logs, total_count) {
+        (Ok(logs), Ok(total_count)) => {
+            let response = serde_json::json!({
+                "status": "success",
+                "data": SessionAuditLogResponse {
+                    logs,
+                    total_count,
+                    limit,
+                    offset,
+                },
+                "message": "Audit logs retrieved successfully"
+            });
+            Ok(Json(response))
+        }
+        _ => {
+            let error_response = serde_json::json!({
+                "status": "error",
+                "message": "Failed to retrieve audit logs"
+            });
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
 async fn log_session_action(
-    _session_state: &SessionState,
+    session_state: &SessionState,
     action: &str,
     session_id: &str,
     admin_id: &Uuid,
     reason: Option<String>,
 ) -> Result<(), anyhow::Error> {
-    // In a real implementation, you might want to store this in a separate audit log table
-    // For now, we'll just log it to the application logs
     tracing::info!(
         action = action,
         session_id = session_id,
@@ -996,5 +1166,192 @@ async fn log_session_action(
         "Admin session action performed"
     );
 
+    let entry = NewAuditLog::new(*admin_id, action, "session", session_id).reason(reason);
+    insert_audit_log(&session_state.db_pool, &entry).await?;
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::session::SessionUser;
+    use crate::test_support::{insert_user, test_state};
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_session_actions_are_written_to_audit_log() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let tag = Uuid::new_v4().simple().to_string()[..8].to_string();
+        let admin_id = insert_user(&pool, &format!("audit-{}", tag), None).await;
+
+        log_session_action(&state, "revoke", &format!("s1-{}", tag), &admin_id, Some("test".to_string()))
+            .await
+            .unwrap();
+        log_session_action(&state, "bulk_extend", &format!("s2-{}", tag), &admin_id, None)
+            .await
+            .unwrap();
+
+        let admin = SuperAdminUser {
+            session_user: SessionUser {
+                user_id: admin_id,
+                student_id: format!("audit-{}", tag),
+                email: format!("audit-{}@test.local", tag),
+                first_name: "Audit".to_string(),
+                last_name: "Admin".to_string(),
+                department_id: None,
+                admin_role: None,
+                session_id: Uuid::new_v4().to_string(),
+                permissions: Vec::new(),
+                faculty_id: None,
+                is_impersonated: false,
+                impersonated_by: None,
+            },
+            admin_role: AdminRole {
+                id: Uuid::new_v4(),
+                user_id: admin_id,
+                admin_level: AdminLevel::SuperAdmin,
+                faculty_id: None,
+                permissions: Vec::new(),
+                is_enabled: true,
+                created_at: None,
+                updated_at: None,
+            },
+        };
+        let params = HashMap::from([
+            ("actor_id".to_string(), admin_id.to_string()),
+            ("action".to_string(), "revoke".to_string()),
+        ]);
+        let Json(body) = get_audit_logs(State(state.clone()), admin, Query(params))
+            .await
+            .unwrap();
+
+        sqlx::query("DELETE FROM audit_logs WHERE target_id LIKE $1")
+            .bind(format!("%{}", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(admin_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(body["data"]["total_count"], 1);
+        let log = &body["data"]["logs"][0];
+        assert_eq!(log["target_type"], "session");
+        assert_eq!(log["target_id"], format!("s1-{}", tag));
+        assert_eq!(log["admin_name"], "Test User");
+        assert_eq!(log["reason"], "test");
+    }
+
+    fn session_for(user_id: Uuid) -> Session {
+        let now = Utc::now();
+        Session {
+            id: Uuid::new_v4().to_string(),
+            user_id,
+            expires_at: now + chrono::Duration::hours(1),
+            created_at: now,
+            last_accessed: now,
+            ip_address: None,
+            user_agent: None,
+            device_info: HashMap::new(),
+            is_active: true,
+            session_type: crate::models::session::SessionType::Student,
+            admin_level: None,
+            faculty_id: None,
+            permissions: Vec::new(),
+            revoked_by: None,
+            revoked_at: None,
+            revocation_reason: None,
+            login_method: crate::models::session::LoginMethod::StudentId,
+            sse_connections: Vec::new(),
+            activity_log: Vec::new(),
+            impersonated_by: None,
+            impersonator_session_id: None,
+            geo_location: None,
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_session_directory_batches_lookups() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let tag = Uuid::new_v4().simple().to_string()[..8].to_string();
+        let faculty_id: Uuid = sqlx::query_scalar("INSERT INTO faculties (name, code) VALUES ($1, $2) RETURNING id")
+            .bind(format!("Sessions {}", tag))
+            .bind(&tag)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let department_id: Uuid =
+            sqlx::query_scalar("INSERT INTO departments (name, code, faculty_id) VALUES ('Dept', 'D', $1) RETURNING id")
+                .bind(faculty_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+
+        let user_ids = vec![
+            insert_user(&pool, &format!("sd0{}", tag), Some(AdminLevel::FacultyAdmin)).await,
+            insert_user(&pool, &format!("sd1{}", tag), None).await,
+        ];
+        sqlx::query("UPDATE users SET department_id = $1 WHERE id = ANY($2)")
+            .bind(department_id)
+            .bind(&user_ids)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE admin_roles SET faculty_id = $2 WHERE user_id = $1")
+            .bind(user_ids[0])
+            .bind(faculty_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // Two sessions for the admin, one for the student and one for a deleted user
+        let sessions = vec![
+            session_for(user_ids[0]),
+            session_for(user_ids[0]),
+            session_for(user_ids[1]),
+            session_for(Uuid::new_v4()),
+        ];
+        let directory = SessionDirectory::load(&state, &sessions).await;
+
+        sqlx::query("DELETE FROM users WHERE student_id LIKE $1")
+            .bind(format!("%{}", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM faculties WHERE id = $1")
+            .bind(faculty_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let directory = directory.unwrap();
+        assert_eq!(directory.users.len(), 2);
+        assert_eq!(directory.faculties.len(), 1);
+
+        let admin_info = directory.enhanced_session_info(&sessions[1].id, &sessions[1]).unwrap();
+        assert_eq!(admin_info.admin_level, Some(AdminLevel::FacultyAdmin));
+        assert_eq!(admin_info.faculty_id, Some(faculty_id));
+        assert_eq!(admin_info.faculty_name, Some(format!("Sessions {}", tag)));
+        assert_eq!(admin_info.department_name.as_deref(), Some("Dept"));
+        assert!(admin_info.is_current_session);
+
+        let student_info = directory.enhanced_session_info(&sessions[1].id, &sessions[2]).unwrap();
+        assert_eq!(student_info.admin_level, None);
+        assert_eq!(student_info.faculty_name, Some(format!("Sessions {}", tag)));
+        assert!(!student_info.is_current_session);
+
+        assert!(directory.enhanced_session_info(&sessions[1].id, &sessions[3]).is_none());
+    }
+}
@@ -8,9 +8,11 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
 
+use crate::error::ApiJson;
 use crate::middleware::session::{AdminUser, FacultyAdminUser, SessionState, SuperAdminUser};
 use crate::models::{
     admin_role::AdminLevel,
+    audit_log::NewAuditLog,
     faculty::Faculty,
     session::{
         AdminSessionInfo, AdminSessionMonitor, BatchSessionRevocationRequest,
@@ -19,6 +21,7 @@ use crate::models::{
     },
     user::User,
 };
+use crate::services::audit_log::record_audit_log;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AdminSessionResponse {
@@ -63,7 +66,7 @@ pub async fn get_active_admin_sessions(
         });
 
     let admin_session_ids = session_state
-        .redis_store
+        .session_store
         .get_admin_sessions(admin_level_filter)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -76,7 +79,7 @@ pub async fn get_active_admin_sessions(
             break;
         }
 
-        if let Ok(Some(session)) = session_state.redis_store.get_session(session_id).await {
+        if let Ok(Some(session)) = session_state.session_store.get_session(session_id).await {
             // Get user info
             if let Ok(Some(user)) = get_user_by_id(&session_state, session.user_id).await {
                 // Get faculty name if applicable
@@ -118,7 +121,7 @@ pub async fn get_active_admin_sessions(
     }
 
     let total_count = session_state
-        .redis_store
+        .session_store
         .get_session_count()
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -141,25 +144,25 @@ pub async fn get_session_monitor(
 ) -> Result<Json<SessionMonitorResponse>, StatusCode> {
     // Get session counts by admin level
     let super_admin_count = session_state
-        .redis_store
+        .session_store
         .get_admin_session_count_by_level(&AdminLevel::SuperAdmin)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     let faculty_admin_count = session_state
-        .redis_store
+        .session_store
         .get_admin_session_count_by_level(&AdminLevel::FacultyAdmin)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     let regular_admin_count = session_state
-        .redis_store
+        .session_store
         .get_admin_session_count_by_level(&AdminLevel::RegularAdmin)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     let total_sessions = session_state
-        .redis_store
+        .session_store
         .get_session_count()
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -169,7 +172,7 @@ pub async fn get_session_monitor(
 
     // Get recent admin logins (last 10)
     let admin_session_ids = session_state
-        .redis_store
+        .session_store
         .get_admin_sessions(None)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -178,7 +181,7 @@ pub async fn get_session_monitor(
     let mut processed = 0;
 
     for session_id in admin_session_ids.iter().take(10) {
-        if let Ok(Some(session)) = session_state.redis_store.get_session(session_id).await {
+        if let Ok(Some(session)) = session_state.session_store.get_session(session_id).await {
             if let Ok(Some(user)) = get_user_by_id(&session_state, session.user_id).await {
                 let faculty_name = if let Some(faculty_id) = session.faculty_id {
                     get_faculty_name(&session_state, faculty_id)
@@ -244,11 +247,11 @@ pub async fn force_logout_session(
     State(session_state): State<SessionState>,
     admin_user: AdminUser,
     Path(session_id): Path<String>,
-    Json(req): Json<SessionRevocationRequest>,
+    ApiJson(req): ApiJson<SessionRevocationRequest>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
     // Check if admin has permission to revoke this session
     let target_session = session_state
-        .redis_store
+        .session_store
         .get_session(&session_id)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -275,7 +278,7 @@ pub async fn force_logout_session(
         .unwrap_or_else(|| "Force logout by admin".to_string());
 
     // Get the session to get user_id before revoking
-    let session_user_id = match session_state.redis_store.get_session(&session_id).await {
+    let session_user_id = match session_state.session_store.get_session(&session_id).await {
         Ok(Some(session)) => session.user_id,
         _ => {
             return Ok(Json(serde_json::json!({
@@ -286,7 +289,7 @@ pub async fn force_logout_session(
     };
 
     let success = session_state
-        .redis_store
+        .session_store
         .revoke_session_by_admin(
             &session_id,
             Some(reason.clone()),
@@ -296,6 +299,14 @@ pub async fn force_logout_session(
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     if success {
+        record_audit_log(
+            &session_state.db_pool,
+            NewAuditLog::new(admin_user.session_user.user_id, "force_logout", "session", &session_id)
+                .reason(Some(reason))
+                .details(serde_json::json!({ "user_id": session_user_id })),
+        )
+        .await;
+
         Ok(Json(serde_json::json!({
             "success": true,
             "message": "Session revoked successfully"
@@ -311,15 +322,25 @@ pub async fn force_logout_session(
 // Batch force logout sessions (Super Admin only)
 pub async fn batch_force_logout_sessions(
     State(session_state): State<SessionState>,
-    _super_admin: SuperAdminUser,
-    Json(req): Json<BatchSessionRevocationRequest>,
+    super_admin: SuperAdminUser,
+    ApiJson(req): ApiJson<BatchSessionRevocationRequest>,
 ) -> Result<Json<BatchSessionRevocationResponse>, StatusCode> {
+    let reason = req.reason.clone();
     let response = session_state
-        .redis_store
+        .session_store
         .batch_revoke_sessions(req)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
+    for session_id in &response.revoked_sessions {
+        record_audit_log(
+            &session_state.db_pool,
+            NewAuditLog::new(super_admin.session_user.user_id, "batch_force_logout", "session", session_id)
+                .reason(reason.clone()),
+        )
+        .await;
+    }
+
     // SSE notifications for batch operations are handled by individual session revocation
 
     Ok(Json(response))
@@ -330,7 +351,7 @@ pub async fn force_logout_user_sessions(
     State(session_state): State<SessionState>,
     admin_user: AdminUser,
     Path(user_id): Path<Uuid>,
-    Json(mut req): Json<ForceLogoutUserRequest>,
+    ApiJson(mut req): ApiJson<ForceLogoutUserRequest>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
     // Override the user_id from path
     req.user_id = user_id;
@@ -341,12 +362,21 @@ pub async fn force_logout_user_sessions(
         // For now, allow faculty admin to logout users in their faculty
     }
 
+    let reason = req.reason.clone();
     let revoked_sessions = session_state
-        .redis_store
+        .session_store
         .force_logout_user(req)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
+    record_audit_log(
+        &session_state.db_pool,
+        NewAuditLog::new(admin_user.session_user.user_id, "force_logout_user", "user", user_id)
+            .reason(reason)
+            .details(serde_json::json!({ "revoked_sessions": revoked_sessions })),
+    )
+    .await;
+
     Ok(Json(serde_json::json!({
         "success": true,
         "message": format!("Revoked {} sessions", revoked_sessions.len()),
@@ -359,7 +389,7 @@ pub async fn force_logout_faculty_sessions(
     State(session_state): State<SessionState>,
     admin_user: FacultyAdminUser,
     Path(faculty_id): Path<Uuid>,
-    Json(mut req): Json<ForceLogoutFacultyRequest>,
+    ApiJson(mut req): ApiJson<ForceLogoutFacultyRequest>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
     // Override the faculty_id from path
     req.faculty_id = faculty_id;
@@ -371,12 +401,21 @@ pub async fn force_logout_faculty_sessions(
         }
     }
 
+    let reason = req.reason.clone();
     let revoked_sessions = session_state
-        .redis_store
+        .session_store
         .force_logout_faculty(req)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
+    record_audit_log(
+        &session_state.db_pool,
+        NewAuditLog::new(admin_user.session_user.user_id, "force_logout_faculty", "faculty", faculty_id)
+            .reason(reason)
+            .details(serde_json::json!({ "revoked_sessions": revoked_sessions })),
+    )
+    .await;
+
     Ok(Json(serde_json::json!({
         "success": true,
         "message": format!("Revoked {} faculty sessions", revoked_sessions.len()),
@@ -398,7 +437,7 @@ pub async fn get_session_analytics(
     // TODO: Implement comprehensive analytics
     // For now, return basic session counts
     let current_active_sessions = session_state
-        .redis_store
+        .session_store
         .get_session_count()
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -447,3 +486,112 @@ async fn get_faculty_name(
 
     Ok(faculty.map(|f| f.name))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{admin_role::AdminRole, session::{CreateSession, LoginMethod, SessionType, SessionUser}};
+    use crate::services::{MemorySessionStore, SessionStore};
+    use crate::test_support::test_state;
+    use std::sync::Arc;
+
+    fn faculty_admin(faculty_id: Uuid) -> FacultyAdminUser {
+        let user_id = Uuid::new_v4();
+        FacultyAdminUser {
+            session_user: SessionUser {
+                user_id,
+                student_id: "admin".to_string(),
+                email: "admin@test.local".to_string(),
+                first_name: "Faculty".to_string(),
+                last_name: "Admin".to_string(),
+                department_id: None,
+                admin_role: None,
+                session_id: Uuid::new_v4().to_string(),
+                permissions: Vec::new(),
+                faculty_id: Some(faculty_id),
+                is_impersonated: false,
+                impersonated_by: None,
+            },
+            admin_role: AdminRole {
+                id: Uuid::new_v4(),
+                user_id,
+                admin_level: AdminLevel::FacultyAdmin,
+                faculty_id: Some(faculty_id),
+                permissions: Vec::new(),
+                is_enabled: true,
+                created_at: None,
+                updated_at: None,
+            },
+            faculty_id: Some(faculty_id),
+        }
+    }
+
+    async fn admin_session(store: &MemorySessionStore, faculty_id: Uuid) -> String {
+        store
+            .create_admin_session(
+                CreateSession {
+                    user_id: Uuid::new_v4(),
+                    expires_at: Utc::now() + chrono::Duration::hours(1),
+                    ip_address: None,
+                    user_agent: None,
+                    device_info: HashMap::new(),
+                    impersonated_by: None,
+                    impersonator_session_id: None,
+                },
+                SessionType::AdminFaculty,
+                AdminLevel::FacultyAdmin,
+                Some(faculty_id),
+                Vec::new(),
+                LoginMethod::Email,
+            )
+            .await
+            .unwrap()
+            .id
+    }
+
+    #[tokio::test]
+    async fn test_force_logout_faculty_only_revokes_own_faculty() {
+        let store = Arc::new(MemorySessionStore::default());
+        let (faculty_a, faculty_b) = (Uuid::new_v4(), Uuid::new_v4());
+        let session_a = admin_session(&store, faculty_a).await;
+        let session_b = admin_session(&store, faculty_b).await;
+
+        // Audit log writes fail fast and are only logged
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .acquire_timeout(std::time::Duration::from_millis(100))
+            .connect_lazy("postgres://localhost:1/unused")
+            .unwrap();
+        let state = SessionState {
+            session_store: store.clone(),
+            ..test_state(&pool)
+        };
+        let request = |faculty_id| ForceLogoutFacultyRequest {
+            faculty_id,
+            reason: Some("test".to_string()),
+            admin_level_filter: None,
+            notify_users: false,
+        };
+
+        let Json(body) = force_logout_faculty_sessions(
+            State(state.clone()),
+            faculty_admin(faculty_a),
+            Path(faculty_a),
+            ApiJson(request(faculty_a)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(body["revoked_sessions"], serde_json::json!([session_a]));
+
+        let denied = force_logout_faculty_sessions(
+            State(state),
+            faculty_admin(faculty_a),
+            Path(faculty_b),
+            ApiJson(request(faculty_b)),
+        )
+        .await;
+        assert_eq!(denied.unwrap_err(), StatusCode::FORBIDDEN);
+
+        assert!(!store.get_session(&session_a).await.unwrap().unwrap().is_active);
+        assert!(store.get_session(&session_b).await.unwrap().unwrap().is_active);
+    }
+}
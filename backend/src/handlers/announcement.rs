@@ -0,0 +1,188 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::Json,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::error::{ApiError, ApiJson, ApiResult};
+use crate::middleware::session::{SessionState, SuperAdminUser};
+use crate::models::announcement::{CreateAnnouncementRequest, ScheduledAnnouncement};
+use crate::models::audit_log::NewAuditLog;
+use crate::services::audit_log::record_audit_log;
+
+const MAX_TITLE_LEN: usize = 255;
+const MAX_MESSAGE_LEN: usize = 2000;
+
+#[derive(Debug, Deserialize)]
+pub struct AnnouncementListQuery {
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+// GET /api/admin/announcements?status=&limit=&offset=
+pub async fn list_announcements(
+    State(session_state): State<SessionState>,
+    _admin: SuperAdminUser,
+    Query(query): Query<AnnouncementListQuery>,
+) -> ApiResult<Json<Value>> {
+    if let Some(status) = query.status.as_deref() {
+        if !["scheduled", "published", "cancelled"].contains(&status) {
+            return Err(ApiError::validation(
+                "invalid_status",
+                "status must be scheduled, published or cancelled",
+            ));
+        }
+    }
+    let limit = query.limit.unwrap_or(50).clamp(1, 200);
+    let offset = query.offset.unwrap_or(0).max(0);
+
+    let announcements = sqlx::query_as::<_, ScheduledAnnouncement>(
+        r#"
+        SELECT * FROM scheduled_announcements
+        WHERE $1::TEXT IS NULL OR status = $1
+        ORDER BY publish_at DESC
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind(&query.status)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(&session_state.db_pool)
+    .await
+    .map_err(|_| ApiError::internal("Failed to load announcements"))?;
+
+    let total: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM scheduled_announcements WHERE $1::TEXT IS NULL OR status = $1")
+            .bind(&query.status)
+            .fetch_one(&session_state.db_pool)
+            .await
+            .map_err(|_| ApiError::internal("Failed to load announcements"))?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {
+            "announcements": announcements,
+            "total": total,
+            "limit": limit,
+            "offset": offset,
+        }
+    })))
+}
+
+// POST /api/admin/announcements - published by the announcement task at publish_at
+pub async fn create_announcement(
+    State(session_state): State<SessionState>,
+    admin: SuperAdminUser,
+    ApiJson(request): ApiJson<CreateAnnouncementRequest>,
+) -> ApiResult<Json<Value>> {
+    let title = request.title.trim();
+    let message = request.message.trim();
+    if title.is_empty() || title.chars().count() > MAX_TITLE_LEN {
+        return Err(ApiError::validation(
+            "invalid_title",
+            format!("Title is required and must be at most {} characters", MAX_TITLE_LEN),
+        ));
+    }
+    if message.is_empty() || message.chars().count() > MAX_MESSAGE_LEN {
+        return Err(ApiError::validation(
+            "invalid_message",
+            format!("Message is required and must be at most {} characters", MAX_MESSAGE_LEN),
+        ));
+    }
+
+    let now = Utc::now();
+    // A publish_at in the past means "now"
+    let publish_at = request.publish_at.unwrap_or(now).max(now);
+    if let Some(display_until) = request.display_until {
+        if display_until <= publish_at {
+            return Err(ApiError::validation(
+                "invalid_display_window",
+                "display_until must be after publish_at",
+            ));
+        }
+    }
+
+    let announcement = sqlx::query_as::<_, ScheduledAnnouncement>(
+        r#"
+        INSERT INTO scheduled_announcements (title, message, publish_at, display_until, created_by)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING *
+        "#,
+    )
+    .bind(title)
+    .bind(message)
+    .bind(publish_at)
+    .bind(request.display_until)
+    .bind(admin.session_user.user_id)
+    .fetch_one(&session_state.db_pool)
+    .await
+    .map_err(|_| ApiError::internal("Failed to schedule announcement"))?;
+
+    record_audit_log(
+        &session_state.db_pool,
+        NewAuditLog::new(admin.session_user.user_id, "schedule_announcement", "announcement", announcement.id)
+            .details(json!({
+                "title": announcement.title,
+                "publish_at": announcement.publish_at,
+                "display_until": announcement.display_until,
+            })),
+    )
+    .await;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": announcement,
+        "message": "Announcement scheduled successfully"
+    })))
+}
+
+// DELETE /api/admin/announcements/{id} - cancels it; a published announcement is withdrawn from inboxes
+pub async fn cancel_announcement(
+    State(session_state): State<SessionState>,
+    admin: SuperAdminUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<Value>> {
+    let announcement = sqlx::query_as::<_, ScheduledAnnouncement>(
+        r#"
+        UPDATE scheduled_announcements
+        SET status = 'cancelled', cancelled_by = $2, cancelled_at = NOW(), updated_at = NOW()
+        WHERE id = $1 AND status <> 'cancelled'
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .bind(admin.session_user.user_id)
+    .fetch_optional(&session_state.db_pool)
+    .await
+    .map_err(|_| ApiError::internal("Failed to cancel announcement"))?;
+
+    let Some(announcement) = announcement else {
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM scheduled_announcements WHERE id = $1)")
+            .bind(id)
+            .fetch_one(&session_state.db_pool)
+            .await
+            .map_err(|_| ApiError::internal("Failed to cancel announcement"))?;
+        return Err(if exists {
+            ApiError::conflict("announcement_already_cancelled", "Announcement is already cancelled")
+        } else {
+            ApiError::not_found("announcement_not_found", "Announcement not found")
+        });
+    };
+
+    record_audit_log(
+        &session_state.db_pool,
+        NewAuditLog::new(admin.session_user.user_id, "cancel_announcement", "announcement", id)
+            .details(json!({ "title": announcement.title, "was_published": announcement.published_at.is_some() })),
+    )
+    .await;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": announcement,
+        "message": "Announcement cancelled successfully"
+    })))
+}
@@ -2,7 +2,7 @@ use axum::{
     debug_handler,
     extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
-    response::Json,
+    response::{IntoResponse, Json, Response},
 };
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
@@ -11,11 +11,14 @@ use std::collections::HashMap;
 use tower_cookies::Cookies;
 use uuid::Uuid;
 
+use crate::error::{ApiError, ApiJson, ApiResult};
 use crate::middleware::session::{
-    delete_session_cookie, extract_session_id, validate_and_get_session_user, AdminUser, SessionState, SuperAdminUser,
+    create_login_cookie, delete_session_cookie, extract_session_id, get_accessible_faculty_ids, validate_and_get_session_user,
+    AdminUser, SessionState, SuperAdminUser,
 };
 use crate::models::{
     admin_role::{AdminLevel, AdminRole},
+    login_event::LoginEvent,
     session::{
         AdminSessionInfo, CreateSession, DeviceInfo, LoginMethod, Permission, SessionActivityType,
         SessionLoginRequest, SessionResponse, SessionRevocationRequest, SessionType, SessionUser,
@@ -23,12 +26,29 @@ use crate::models::{
     },
     user::User,
 };
+use crate::handlers::admin::invalidate_dashboard_cache;
+use crate::handlers::two_factor::{issue_login_challenge, PendingAdminLogin};
+use crate::services::email_service::{EmailService, EmailTemplate};
+use crate::services::geoip::lookup_ip;
+use crate::services::new_device_login::check_login_device;
+use crate::services::notification_inbox::unread_notification_count;
+use crate::services::two_factor::{is_two_factor_enabled, CHALLENGE_TTL_SECONDS};
+use crate::utils::{
+    get_client_info,
+    PeerAddr,
+    password_hash::{hash_password, needs_rehash, verify_password},
+    rate_limit::rate_limited,
+    validation::{validate_password, PasswordPolicy, RuleViolation},
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LoginResponse {
     pub success: bool,
     pub session: Option<SessionResponse>,
     pub message: String,
+    /// Set when the admin still has to pass POST /api/admin/auth/2fa/verify
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub two_factor_challenge: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -47,6 +67,26 @@ pub struct RegisterResponse {
     pub success: bool,
     pub user_id: Option<Uuid>,
     pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors: Option<Vec<RuleViolation>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ForgotPasswordRequest {
+    pub email: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    pub new_password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+    pub logout_other_sessions: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -78,20 +118,12 @@ pub async fn student_login(
     State(session_state): State<SessionState>,
     cookies: Cookies,
     headers: HeaderMap,
-    Json(login_req): Json<StudentLoginRequest>,
-) -> Result<Json<LoginResponse>, StatusCode> {
-    // Get IP address from headers first
-    let ip_address = headers
-        .get("x-forwarded-for")
-        .or_else(|| headers.get("x-real-ip"))
-        .and_then(|h| h.to_str().ok())
-        .map(|s| s.split(',').next().unwrap_or(s).trim().to_string());
-
-    // Extract device info from headers
-    let user_agent = headers
-        .get("user-agent")
-        .and_then(|h| h.to_str().ok())
-        .unwrap_or("Unknown");
+    peer: PeerAddr,
+    ApiJson(login_req): ApiJson<StudentLoginRequest>,
+) -> Result<Json<LoginResponse>, Response> {
+    // Get IP address and user agent from headers first
+    let (ip_address, user_agent) = get_client_info(&headers, peer, &session_state.app_config.trusted_proxies);
+    let user_agent = user_agent.as_deref().unwrap_or("Unknown");
 
     let accept_language = headers.get("accept-language").and_then(|h| h.to_str().ok());
 
@@ -122,6 +154,10 @@ pub async fn student_login(
     device_info_obj.generate_fingerprint(ip_address.as_deref());
     device_info.extend(device_info_obj.to_json());
 
+    // Reject early while this student ID + IP pair is locked out
+    let client_ip = ip_address.as_deref().unwrap_or("unknown");
+    check_login_lockout(&session_state, &login_req.student_id, client_ip).await?;
+
     // Authenticate user by student ID
     let user = match authenticate_user_by_student_id(
         &session_state,
@@ -132,40 +168,60 @@ pub async fn student_login(
     {
         Ok(Some(user)) => user,
         Ok(None) => {
+            record_login_event(
+                &session_state,
+                LoginIdentifier::StudentId(&login_req.student_id),
+                ip_address.as_deref(),
+                user_agent,
+                &device_info,
+                false,
+            )
+            .await;
+            record_failed_login(&session_state, &login_req.student_id, client_ip).await?;
             return Ok(Json(LoginResponse {
                 success: false,
                 session: None,
                 message: "Invalid student ID or password".to_string(),
+                two_factor_challenge: None,
             }));
         }
         Err(_) => {
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response());
         }
     };
 
+    // Password verified - reset failed attempt counter
+    let _ = session_state
+        .session_store
+        .clear_failed_logins(&login_req.student_id, client_ip)
+        .await;
+
+    ensure_account_active(&user).map_err(IntoResponse::into_response)?;
+
     // For student login, ensure user is NOT an admin
     let admin_role = get_user_admin_role(&session_state, user.id)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
 
     if admin_role.is_some() {
         return Ok(Json(LoginResponse {
             success: false,
             session: None,
             message: "Admin users must use admin login portal".to_string(),
+            two_factor_challenge: None,
         }));
     }
 
     // Check if the user's faculty is active
     let user_faculty_id = get_user_faculty_id(&session_state, user.id)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
 
     println!("Student login - User {} faculty_id: {:?}", user.email, user_faculty_id);
 
     let faculty_is_active = check_faculty_is_active(&session_state, user_faculty_id)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
 
     println!("Student login - Faculty active: {}", faculty_is_active);
 
@@ -175,21 +231,33 @@ pub async fn student_login(
             success: false,
             session: None,
             message: "Access denied. Your faculty is currently inactive.".to_string(),
+            two_factor_challenge: None,
         }));
     }
 
+    check_login_device(
+        &session_state,
+        &user,
+        ip_address.as_deref(),
+        user_agent,
+        login_req.device_verification_token.as_deref(),
+        "/login",
+    )
+    .await
+    .map_err(IntoResponse::into_response)?;
+
     // Check existing sessions and enforce limits
     let existing_sessions = session_state
-        .redis_store
+        .session_store
         .get_user_sessions(user.id)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
 
     if existing_sessions.len() >= session_state.config.max_sessions_per_user {
         // Remove oldest session
         if let Some(oldest_session) = existing_sessions.iter().min_by_key(|s| s.created_at) {
             let _ = session_state
-                .redis_store
+                .session_store
                 .delete_session(&oldest_session.id)
                 .await;
         }
@@ -197,7 +265,7 @@ pub async fn student_login(
 
     // Create new session
     let remember_me = login_req.remember_me.unwrap_or(false);
-    let expires_at = session_state.config.get_session_expiry(remember_me);
+    let expires_at = session_state.config.get_session_expiry(None, remember_me);
 
     let create_session = CreateSession {
         user_id: user.id,
@@ -205,14 +273,16 @@ pub async fn student_login(
         ip_address: ip_address.clone(),
         user_agent: Some(user_agent.to_string()),
         device_info: device_info.clone(),
+        impersonated_by: None,
+        impersonator_session_id: None,
     };
 
     // Create regular student session
     let mut session = session_state
-        .redis_store
+        .session_store
         .create_session(create_session)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
 
     // Update session with student-specific details
     session.session_type = SessionType::Student;
@@ -224,7 +294,7 @@ pub async fn student_login(
 
     // Log the successful login activity
     session_state
-        .redis_store
+        .session_store
         .add_session_activity(
             &session.id,
             SessionActivityType::Login,
@@ -244,7 +314,16 @@ pub async fn student_login(
     // Build session user
     let session_user = build_session_user(&user, &admin_role, &session.id);
 
-    // Frontend will handle cookie setting
+    record_login_event(
+        &session_state,
+        LoginIdentifier::StudentId(&login_req.student_id),
+        ip_address.as_deref(),
+        user_agent,
+        &device_info,
+        true,
+    )
+    .await;
+    cookies.add(create_login_cookie(&session.id, expires_at));
 
     let response = SessionResponse {
         session_id: session.id,
@@ -256,6 +335,7 @@ pub async fn student_login(
         success: true,
         session: Some(response),
         message: "Login successful".to_string(),
+        two_factor_challenge: None,
     }))
 }
 
@@ -265,20 +345,12 @@ pub async fn admin_login(
     State(session_state): State<SessionState>,
     cookies: Cookies,
     headers: HeaderMap,
-    Json(login_req): Json<SessionLoginRequest>,
-) -> Result<Json<LoginResponse>, StatusCode> {
-    // Get IP address from headers first
-    let ip_address = headers
-        .get("x-forwarded-for")
-        .or_else(|| headers.get("x-real-ip"))
-        .and_then(|h| h.to_str().ok())
-        .map(|s| s.split(',').next().unwrap_or(s).trim().to_string());
-
-    // Extract device info from headers
-    let user_agent = headers
-        .get("user-agent")
-        .and_then(|h| h.to_str().ok())
-        .unwrap_or("Unknown");
+    peer: PeerAddr,
+    ApiJson(login_req): ApiJson<SessionLoginRequest>,
+) -> Result<Json<LoginResponse>, Response> {
+    // Get IP address and user agent from headers first
+    let (ip_address, user_agent) = get_client_info(&headers, peer, &session_state.app_config.trusted_proxies);
+    let user_agent = user_agent.as_deref().unwrap_or("Unknown");
 
     let accept_language = headers.get("accept-language").and_then(|h| h.to_str().ok());
 
@@ -309,32 +381,56 @@ pub async fn admin_login(
     device_info_obj.generate_fingerprint(ip_address.as_deref());
     device_info.extend(device_info_obj.to_json());
 
+    // Reject early while this email + IP pair is locked out
+    let client_ip = ip_address.as_deref().unwrap_or("unknown");
+    check_login_lockout(&session_state, &login_req.email, client_ip).await?;
+
     // Authenticate user
     let user = match authenticate_user(&session_state, &login_req.email, &login_req.password).await
     {
         Ok(Some(user)) => user,
         Ok(None) => {
+            record_login_event(
+                &session_state,
+                LoginIdentifier::Email(&login_req.email),
+                ip_address.as_deref(),
+                user_agent,
+                &device_info,
+                false,
+            )
+            .await;
+            record_failed_login(&session_state, &login_req.email, client_ip).await?;
             return Ok(Json(LoginResponse {
                 success: false,
                 session: None,
                 message: "Invalid email or password".to_string(),
+                two_factor_challenge: None,
             }));
         }
         Err(_) => {
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response());
         }
     };
 
+    // Password verified - reset failed attempt counter
+    let _ = session_state
+        .session_store
+        .clear_failed_logins(&login_req.email, client_ip)
+        .await;
+
+    ensure_account_active(&user).map_err(IntoResponse::into_response)?;
+
     // For admin login, ensure user IS an admin
     let admin_role = get_user_admin_role(&session_state, user.id)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
 
     if admin_role.is_none() {
         return Ok(Json(LoginResponse {
             success: false,
             session: None,
             message: "Access denied. Admin privileges required.".to_string(),
+            two_factor_challenge: None,
         }));
     }
 
@@ -343,37 +439,95 @@ pub async fn admin_login(
     if admin_role_ref.faculty_id.is_some() {
         let faculty_is_active = check_faculty_is_active(&session_state, admin_role_ref.faculty_id)
             .await
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
 
         if !faculty_is_active {
             return Ok(Json(LoginResponse {
                 success: false,
                 session: None,
                 message: "Access denied. Your faculty is currently inactive.".to_string(),
+                two_factor_challenge: None,
             }));
         }
     }
 
+    check_login_device(
+        &session_state,
+        &user,
+        ip_address.as_deref(),
+        user_agent,
+        login_req.device_verification_token.as_deref(),
+        "/admin/login",
+    )
+    .await
+    .map_err(IntoResponse::into_response)?;
+
+    let login = PendingAdminLogin {
+        user_id: user.id,
+        remember_me: login_req.remember_me.unwrap_or(false),
+        ip_address,
+        user_agent: user_agent.to_string(),
+        device_info,
+        attempts: 0,
+        expires_at: Utc::now() + chrono::Duration::seconds(CHALLENGE_TTL_SECONDS as i64),
+    };
+
+    // With two-factor authentication the session is only created by POST /api/admin/auth/2fa/verify
+    let two_factor_enabled = is_two_factor_enabled(&session_state.db_pool, user.id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+    if two_factor_enabled {
+        let challenge_token = issue_login_challenge(&session_state, &login)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+        return Ok(Json(LoginResponse {
+            success: false,
+            session: None,
+            message: "Two-factor authentication required".to_string(),
+            two_factor_challenge: Some(challenge_token),
+        }));
+    }
+
+    finish_admin_login(&session_state, &cookies, &user, admin_role, login).await
+}
+
+/// Create the admin session once every login check (and the second factor, when enabled) passed
+pub(crate) async fn finish_admin_login(
+    session_state: &SessionState,
+    cookies: &Cookies,
+    user: &User,
+    admin_role: Option<AdminRole>,
+    login: PendingAdminLogin,
+) -> Result<Json<LoginResponse>, Response> {
+    let PendingAdminLogin {
+        remember_me,
+        ip_address,
+        user_agent,
+        device_info,
+        ..
+    } = login;
+    let user_agent = user_agent.as_str();
+
     // Check existing sessions and enforce limits
     let existing_sessions = session_state
-        .redis_store
+        .session_store
         .get_user_sessions(user.id)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
 
     if existing_sessions.len() >= session_state.config.max_sessions_per_user {
         // Remove oldest session
         if let Some(oldest_session) = existing_sessions.iter().min_by_key(|s| s.created_at) {
             let _ = session_state
-                .redis_store
+                .session_store
                 .delete_session(&oldest_session.id)
                 .await;
         }
     }
 
-    // Create new admin session
-    let remember_me = login_req.remember_me.unwrap_or(false);
-    let expires_at = session_state.config.get_session_expiry(remember_me);
+    // Create new admin session; its lifetime follows the admin level's TTL when one is set
+    let admin_level = admin_role.as_ref().map(|role| &role.admin_level);
+    let expires_at = session_state.config.get_session_expiry(admin_level, remember_me);
 
     let create_session = CreateSession {
         user_id: user.id,
@@ -381,6 +535,8 @@ pub async fn admin_login(
         ip_address: ip_address.clone(),
         user_agent: Some(user_agent.to_string()),
         device_info: device_info.clone(),
+        impersonated_by: None,
+        impersonator_session_id: None,
     };
 
     // Determine session type based on admin level
@@ -400,7 +556,7 @@ pub async fn admin_login(
     .collect();
 
     let session = session_state
-        .redis_store
+        .session_store
         .create_admin_session(
             create_session,
             session_type,
@@ -410,15 +566,24 @@ pub async fn admin_login(
             LoginMethod::Email,
         )
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
 
     // Store session metadata in database
-    let _ = store_session_metadata(&session_state, &session).await;
+    let _ = store_session_metadata(session_state, &session).await;
 
     // Build session user with admin role
-    let session_user = build_session_user(&user, &admin_role, &session.id);
-
-    // Frontend will handle cookie setting
+    let session_user = build_session_user(user, &admin_role, &session.id);
+
+    record_login_event(
+        session_state,
+        LoginIdentifier::Email(&user.email),
+        ip_address.as_deref(),
+        user_agent,
+        &device_info,
+        true,
+    )
+    .await;
+    cookies.add(create_login_cookie(&session.id, expires_at));
 
     let response = SessionResponse {
         session_id: session.id,
@@ -430,14 +595,25 @@ pub async fn admin_login(
         success: true,
         session: Some(response),
         message: "Admin login successful".to_string(),
+        two_factor_challenge: None,
     }))
 }
 
 // Student registration - no admin privileges
 pub async fn student_register(
     State(session_state): State<SessionState>,
-    Json(register_req): Json<RegisterRequest>,
+    ApiJson(register_req): ApiJson<RegisterRequest>,
 ) -> Result<Json<RegisterResponse>, StatusCode> {
+    let policy = PasswordPolicy::from_config(&session_state.app_config);
+    if let Err(validation_error) = validate_password(&register_req.password, &policy) {
+        return Ok(Json(RegisterResponse {
+            success: false,
+            user_id: None,
+            message: "Password does not meet the password policy".to_string(),
+            errors: Some(validation_error.violations),
+        }));
+    }
+
     // Check if user already exists
     let existing_user =
         sqlx::query_as::<_, User>("SELECT * FROM users WHERE email = $1 OR student_id = $2")
@@ -452,11 +628,13 @@ pub async fn student_register(
             success: false,
             user_id: None,
             message: "User with this email or student ID already exists".to_string(),
+            errors: None,
         }));
     }
 
     // Hash password
-    let password_hash = bcrypt::hash(&register_req.password, bcrypt::DEFAULT_COST)
+    let password_hash = hash_password(&session_state.app_config, &register_req.password)
+        .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     // Generate QR secret
@@ -502,13 +680,214 @@ pub async fn student_register(
     .await
     .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
+    invalidate_dashboard_cache(&session_state).await;
+
     Ok(Json(RegisterResponse {
         success: true,
         user_id: Some(user_id),
         message: "User registered successfully".to_string(),
+        errors: None,
     }))
 }
 
+// Forgot password - always answers the same way so emails can't be enumerated
+pub async fn forgot_password(
+    State(session_state): State<SessionState>,
+    ApiJson(request): ApiJson<ForgotPasswordRequest>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let response = Json(serde_json::json!({
+        "success": true,
+        "message": "If an account with that email exists, a password reset link has been sent"
+    }));
+
+    let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE email = $1")
+        .bind(request.email.trim())
+        .fetch_optional(&session_state.db_pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let Some(user) = user else {
+        return Ok(response);
+    };
+
+    let config = &session_state.app_config;
+    let token = session_state
+        .session_store
+        .create_password_reset_token(user.id, config.password_reset_ttl_seconds)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let reset_link = format!(
+        "{}/reset-password?token={}",
+        config.frontend_url.trim_end_matches('/'),
+        token
+    );
+    let user_name = format!("{} {}", user.first_name, user.last_name);
+    let (subject, body_text, body_html) = EmailTemplate::password_reset_template(
+        &user_name,
+        &reset_link,
+        config.password_reset_ttl_seconds / 60,
+    )
+    .build();
+
+    let email_service = EmailService::new(session_state.clone());
+    if let Err(e) = email_service
+        .queue_email(
+            &user.email,
+            Some(&user_name),
+            &subject,
+            &body_text,
+            body_html.as_deref(),
+            10,
+            Some(serde_json::json!({ "type": "password_reset", "user_id": user.id })),
+        )
+        .await
+    {
+        tracing::error!("Failed to queue password reset email: {}", e);
+    }
+
+    Ok(response)
+}
+
+// Reset password with a token from forgot_password, then revoke all sessions
+pub async fn reset_password(
+    State(session_state): State<SessionState>,
+    ApiJson(request): ApiJson<ResetPasswordRequest>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let policy = PasswordPolicy::from_config(&session_state.app_config);
+    if let Err(validation_error) = validate_password(&request.new_password, &policy) {
+        return Ok(Json(serde_json::json!({
+            "success": false,
+            "message": "Password does not meet the password policy",
+            "errors": validation_error.violations
+        })));
+    }
+
+    let user_id = session_state
+        .session_store
+        .consume_password_reset_token(&request.token)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let Some(user_id) = user_id else {
+        return Ok(Json(serde_json::json!({
+            "success": false,
+            "message": "Invalid or expired reset token"
+        })));
+    };
+
+    let password_hash = hash_password(&session_state.app_config, &request.new_password)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let updated = sqlx::query("UPDATE users SET password_hash = $1, updated_at = NOW() WHERE id = $2")
+        .bind(&password_hash)
+        .bind(user_id)
+        .execute(&session_state.db_pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if updated.rows_affected() == 0 {
+        return Ok(Json(serde_json::json!({
+            "success": false,
+            "message": "Invalid or expired reset token"
+        })));
+    }
+
+    // Log out every device that was signed in with the old password
+    session_state
+        .session_store
+        .delete_user_sessions(user_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let _ = sqlx::query("UPDATE sessions SET is_active = false WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&session_state.db_pool)
+        .await;
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "message": "Password has been reset. Please log in with your new password."
+    })))
+}
+
+// Change password for the logged-in user
+pub async fn change_password(
+    State(session_state): State<SessionState>,
+    session_user: SessionUser,
+    ApiJson(request): ApiJson<ChangePasswordRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    let internal_error = || {
+        let error_response = serde_json::json!({
+            "status": "error",
+            "message": "Failed to change password"
+        });
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    };
+
+    let password_hash: String = sqlx::query_scalar("SELECT password_hash FROM users WHERE id = $1")
+        .bind(session_user.user_id)
+        .fetch_one(&session_state.db_pool)
+        .await
+        .map_err(|_| internal_error())?;
+
+    let current_ok = verify_password(&request.current_password, &password_hash)
+        .await
+        .map_err(|_| internal_error())?;
+    if !current_ok {
+        let error_response = serde_json::json!({
+            "status": "error",
+            "message": "Current password is incorrect"
+        });
+        return Err((StatusCode::UNAUTHORIZED, Json(error_response)));
+    }
+
+    let policy = PasswordPolicy::from_config(&session_state.app_config);
+    validate_password(&request.new_password, &policy).map_err(|e| e.to_error_response())?;
+
+    let new_hash = hash_password(&session_state.app_config, &request.new_password)
+        .await
+        .map_err(|_| internal_error())?;
+
+    sqlx::query("UPDATE users SET password_hash = $1, updated_at = NOW() WHERE id = $2")
+        .bind(&new_hash)
+        .bind(session_user.user_id)
+        .execute(&session_state.db_pool)
+        .await
+        .map_err(|_| internal_error())?;
+
+    // Optionally sign out every other device, keeping the current session alive
+    let mut revoked_sessions = 0;
+    if request.logout_other_sessions.unwrap_or(false) {
+        let sessions = session_state
+            .session_store
+            .get_user_sessions(session_user.user_id)
+            .await
+            .map_err(|_| internal_error())?;
+
+        for session in sessions.iter().filter(|s| s.id != session_user.session_id) {
+            if session_state.session_store.delete_session(&session.id).await.is_ok() {
+                revoked_sessions += 1;
+            }
+        }
+
+        let _ = sqlx::query("UPDATE sessions SET is_active = false WHERE user_id = $1 AND id <> $2")
+            .bind(session_user.user_id)
+            .bind(&session_user.session_id)
+            .execute(&session_state.db_pool)
+            .await;
+    }
+
+    Ok(Json(serde_json::json!({
+        "status": "success",
+        "data": {
+            "revoked_sessions": revoked_sessions
+        },
+        "message": "Password changed successfully"
+    })))
+}
+
 // Logout - revoke current session
 #[debug_handler]
 pub async fn logout(
@@ -518,7 +897,7 @@ pub async fn logout(
 ) -> Result<Json<serde_json::Value>, StatusCode> {
     // Delete session from Redis
     session_state
-        .redis_store
+        .session_store
         .delete_session(&session_user.session_id)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -538,17 +917,26 @@ pub async fn me(
     State(session_state): State<SessionState>,
     cookies: Cookies,
     headers: HeaderMap,
+    peer: PeerAddr,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
     // Extract session ID from cookie or header
     let session_id = extract_session_id(&cookies, &headers);
 
     if let Some(session_id) = session_id {
+        let (client_ip, _) = get_client_info(&headers, peer, &session_state.app_config.trusted_proxies);
         // Validate session and get user data
-        match validate_and_get_session_user(&session_state, &session_id).await {
+        match validate_and_get_session_user(&session_state, &session_id, client_ip.as_deref()).await {
             Ok(crate::models::session::SessionValidation::Valid(session_user)) => {
+                // Badge count for the notification bell; a failed count shouldn't fail /me
+                let unread_count = unread_notification_count(&session_state.db_pool, session_user.user_id)
+                    .await
+                    .unwrap_or(0);
+                let mut data = serde_json::json!(session_user);
+                data["unread_notification_count"] = serde_json::json!(unread_count);
+
                 return Ok(Json(serde_json::json!({
                     "success": true,
-                    "data": session_user
+                    "data": data
                 })));
             }
             Ok(crate::models::session::SessionValidation::Expired) => {
@@ -564,6 +952,30 @@ pub async fn me(
                     }
                 })));
             }
+            Ok(crate::models::session::SessionValidation::IdleTimeout) => {
+                let cookie = delete_session_cookie();
+                cookies.add(cookie);
+
+                return Ok(Json(serde_json::json!({
+                    "success": false,
+                    "error": {
+                        "code": "SESSION_IDLE_TIMEOUT",
+                        "message": "Session timed out due to inactivity"
+                    }
+                })));
+            }
+            Ok(crate::models::session::SessionValidation::IpMismatch) => {
+                let cookie = delete_session_cookie();
+                cookies.add(cookie);
+
+                return Ok(Json(serde_json::json!({
+                    "success": false,
+                    "error": {
+                        "code": "SESSION_IP_MISMATCH",
+                        "message": "Session is bound to a different IP address"
+                    }
+                })));
+            }
             Ok(crate::models::session::SessionValidation::Revoked) => {
                 // Session was revoked by admin
                 let cookie = delete_session_cookie();
@@ -612,7 +1024,7 @@ pub async fn admin_logout(
 ) -> Result<Json<serde_json::Value>, StatusCode> {
     // Delete session from Redis
     session_state
-        .redis_store
+        .session_store
         .delete_session(&admin_user.session_user.session_id)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -642,7 +1054,7 @@ pub async fn get_my_sessions(
     session_user: SessionUser,
 ) -> Result<Json<UserSessionsResponse>, StatusCode> {
     let sessions = session_state
-        .redis_store
+        .session_store
         .get_user_sessions(session_user.user_id)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -676,7 +1088,7 @@ pub async fn revoke_my_session(
 ) -> Result<Json<serde_json::Value>, StatusCode> {
     // Verify the session belongs to the user
     let user_sessions = session_state
-        .redis_store
+        .session_store
         .get_user_sessions(session_user.user_id)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -689,7 +1101,7 @@ pub async fn revoke_my_session(
 
     // Revoke the session
     let success = session_state
-        .redis_store
+        .session_store
         .revoke_session(&target_session_id, Some("User revoked".to_string()))
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -719,7 +1131,7 @@ pub async fn get_all_sessions(
         .unwrap_or(50);
 
     let session_ids = session_state
-        .redis_store
+        .session_store
         .get_active_sessions(Some(limit))
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -727,7 +1139,7 @@ pub async fn get_all_sessions(
     let mut sessions = Vec::new();
 
     for session_id in &session_ids {
-        if let Ok(Some(session)) = session_state.redis_store.get_session(session_id).await {
+        if let Ok(Some(session)) = session_state.session_store.get_session(session_id).await {
             // Get user info
             if let Ok(Some(user)) = get_user_by_id(&session_state, session.user_id).await {
                 // Get admin role
@@ -765,7 +1177,7 @@ pub async fn get_all_sessions(
     }
 
     let total_count = session_state
-        .redis_store
+        .session_store
         .get_session_count()
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -781,12 +1193,12 @@ pub async fn admin_revoke_session(
     State(session_state): State<SessionState>,
     _admin: AdminUser,
     Path(session_id): Path<String>,
-    Json(req): Json<SessionRevocationRequest>,
+    ApiJson(req): ApiJson<SessionRevocationRequest>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
     let reason = req.reason.unwrap_or_else(|| "Revoked by admin".to_string());
 
     let success = session_state
-        .redis_store
+        .session_store
         .revoke_session(&session_id, Some(reason))
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -809,14 +1221,14 @@ pub async fn admin_revoke_user_sessions(
     State(session_state): State<SessionState>,
     _admin: AdminUser,
     Path(user_id): Path<Uuid>,
-    Json(req): Json<SessionRevocationRequest>,
+    ApiJson(req): ApiJson<SessionRevocationRequest>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
     let reason = req
         .reason
         .unwrap_or_else(|| "All sessions revoked by admin".to_string());
 
     let user_sessions = session_state
-        .redis_store
+        .session_store
         .get_user_sessions(user_id)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -824,7 +1236,7 @@ pub async fn admin_revoke_user_sessions(
     let mut revoked_count = 0;
     for session in user_sessions {
         if session_state
-            .redis_store
+            .session_store
             .revoke_session(&session.id, Some(reason.clone()))
             .await
             .unwrap_or(false)
@@ -844,14 +1256,32 @@ pub async fn admin_revoke_user_sessions(
 pub async fn extend_session(
     State(session_state): State<SessionState>,
     session_user: SessionUser,
-    Json(params): Json<HashMap<String, Value>>,
+    ApiJson(params): ApiJson<HashMap<String, Value>>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
+    // Impersonation sessions keep their short fixed lifetime
+    if session_user.is_impersonated {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
     let hours = params.get("hours").and_then(|h| h.as_i64()).unwrap_or(24);
 
-    let new_expiry = Utc::now() + chrono::Duration::hours(hours);
+    let session = session_state
+        .session_store
+        .get_session(&session_user.session_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    // Extending never goes beyond the role's TTL or past the session's absolute lifetime
+    let new_expiry = session_state.config.extended_expiry(
+        session.admin_level.as_ref(),
+        chrono::Duration::hours(hours),
+        session.created_at,
+        Utc::now(),
+    );
 
     let success = session_state
-        .redis_store
+        .session_store
         .extend_session(&session_user.session_id, new_expiry)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -870,7 +1300,227 @@ pub async fn extend_session(
     }
 }
 
+// Extend the current session by the standard TTL once it is within the refresh window,
+// so an active client can keep it alive without extending on every call
+pub async fn refresh_session(
+    State(session_state): State<SessionState>,
+    cookies: Cookies,
+    session_user: SessionUser,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let session = session_state
+        .session_store
+        .get_session(&session_user.session_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    // Impersonation sessions keep their short fixed lifetime
+    let new_expiry = match session.impersonated_by {
+        Some(_) => None,
+        None => session_state
+            .config
+            .refreshed_expiry(session.admin_level.as_ref(), session.expires_at, session.created_at, Utc::now()),
+    };
+
+    let expires_at = match new_expiry {
+        Some(new_expiry) => {
+            let extended = session_state
+                .session_store
+                .extend_session(&session.id, new_expiry)
+                .await
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            if !extended {
+                return Err(StatusCode::UNAUTHORIZED);
+            }
+            cookies.add(create_login_cookie(&session.id, new_expiry));
+            new_expiry
+        }
+        None => session.expires_at,
+    };
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "refreshed": new_expiry.is_some(),
+        "expires_at": expires_at
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LoginHistoryQuery {
+    pub limit: Option<i64>,
+}
+
+async fn fetch_login_history(
+    session_state: &SessionState,
+    user_id: Uuid,
+    limit: Option<i64>,
+) -> ApiResult<Vec<LoginEvent>> {
+    sqlx::query_as::<_, LoginEvent>(
+        "SELECT * FROM login_events WHERE user_id = $1 ORDER BY created_at DESC LIMIT $2",
+    )
+    .bind(user_id)
+    .bind(limit.unwrap_or(50).clamp(1, 200))
+    .fetch_all(&session_state.db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to load login history for {}: {}", user_id, e);
+        ApiError::internal("Failed to load login history")
+    })
+}
+
+// Recent login attempts on the current user's account
+pub async fn get_my_login_history(
+    State(session_state): State<SessionState>,
+    session_user: SessionUser,
+    Query(query): Query<LoginHistoryQuery>,
+) -> ApiResult<Json<serde_json::Value>> {
+    let events = fetch_login_history(&session_state, session_user.user_id, query.limit).await?;
+
+    Ok(Json(serde_json::json!({
+        "status": "success",
+        "data": events
+    })))
+}
+
+// Recent login attempts on any account (faculty admins: users of their own faculty)
+pub async fn get_user_login_history(
+    State(session_state): State<SessionState>,
+    admin: AdminUser,
+    Path(user_id): Path<Uuid>,
+    Query(query): Query<LoginHistoryQuery>,
+) -> ApiResult<Json<serde_json::Value>> {
+    ensure_user_in_admin_scope(&session_state, &admin.session_user, user_id).await?;
+
+    let events = fetch_login_history(&session_state, user_id, query.limit).await?;
+
+    Ok(Json(serde_json::json!({
+        "status": "success",
+        "data": events
+    })))
+}
+
 // Helper functions
+
+/// Faculty-scoped admins may only look at users of their own faculty
+pub(crate) async fn ensure_user_in_admin_scope(
+    session_state: &SessionState,
+    admin: &SessionUser,
+    user_id: Uuid,
+) -> ApiResult<()> {
+    if let Some(faculty_ids) = get_accessible_faculty_ids(admin) {
+        let user_faculty_id = get_user_faculty_id(session_state, user_id)
+            .await
+            .map_err(|_| ApiError::internal("Failed to load user"))?;
+        if !user_faculty_id.is_some_and(|id| faculty_ids.contains(&id)) {
+            return Err(ApiError::forbidden(
+                "permission_denied",
+                "Access denied: user is outside your faculty",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// The identifier a login form was submitted with
+enum LoginIdentifier<'a> {
+    Email(&'a str),
+    StudentId(&'a str),
+}
+
+/// Best effort: a failure here is logged and never blocks the login
+async fn record_login_event(
+    session_state: &SessionState,
+    identifier: LoginIdentifier<'_>,
+    ip_address: Option<&str>,
+    user_agent: &str,
+    device_info: &HashMap<String, Value>,
+    success: bool,
+) {
+    let (lookup, value) = match identifier {
+        LoginIdentifier::Email(email) => ("email", email),
+        LoginIdentifier::StudentId(student_id) => ("student_id", student_id),
+    };
+
+    let geo_location = lookup_ip(ip_address).unwrap_or_default();
+    let result = sqlx::query(&format!(
+        r#"
+        INSERT INTO login_events (user_id, ip_address, user_agent, device_info, success, country_code, country, city)
+        SELECT id, $2, $3, $4, $5, $6, $7, $8 FROM users WHERE {} = $1
+        "#,
+        lookup
+    ))
+    .bind(value)
+    .bind(ip_address)
+    .bind(user_agent)
+    .bind(serde_json::json!(device_info))
+    .bind(success)
+    .bind(&geo_location.country_code)
+    .bind(&geo_location.country)
+    .bind(&geo_location.city)
+    .execute(&session_state.db_pool)
+    .await;
+
+    if let Err(e) = result {
+        tracing::warn!("Failed to record login event: {}", e);
+    }
+}
+
+// Login lockout is keyed by (identifier, IP) and answers the same way whether
+// or not the account exists, so it can't be used to probe for valid emails.
+/// Deactivated and anonymized accounts keep their data but can't sign in
+pub(crate) fn ensure_account_active(user: &User) -> Result<(), ApiError> {
+    if user.is_active && user.anonymized_at.is_none() {
+        return Ok(());
+    }
+    Err(ApiError::forbidden("account_inactive", "This account has been deactivated"))
+}
+
+async fn check_login_lockout(
+    session_state: &SessionState,
+    identifier: &str,
+    ip_address: &str,
+) -> Result<(), Response> {
+    match session_state
+        .session_store
+        .get_login_lockout(identifier, ip_address)
+        .await
+    {
+        Ok(Some(retry_after)) => Err(rate_limited(retry_after)),
+        Ok(None) => Ok(()),
+        Err(e) => {
+            // Fail open - Redis outage should not block every login
+            tracing::warn!("Failed to check login lockout: {}", e);
+            Ok(())
+        }
+    }
+}
+
+async fn record_failed_login(
+    session_state: &SessionState,
+    identifier: &str,
+    ip_address: &str,
+) -> Result<(), Response> {
+    let config = &session_state.app_config;
+    match session_state
+        .session_store
+        .record_failed_login(
+            identifier,
+            ip_address,
+            config.max_login_attempts,
+            config.login_attempt_window_seconds,
+            config.login_lockout_seconds,
+        )
+        .await
+    {
+        Ok(Some(retry_after)) => Err(rate_limited(retry_after)),
+        Ok(None) => Ok(()),
+        Err(e) => {
+            tracing::warn!("Failed to record failed login attempt: {}", e);
+            Ok(())
+        }
+    }
+}
+
 async fn authenticate_user(
     session_state: &SessionState,
     email: &str,
@@ -883,7 +1533,8 @@ async fn authenticate_user(
 
     match user {
         Some(user) => {
-            if bcrypt::verify(password, &user.password_hash)? {
+            if verify_password(password, &user.password_hash).await? {
+                upgrade_password_hash(session_state, &user, password).await;
                 Ok(Some(user))
             } else {
                 Ok(None)
@@ -905,7 +1556,8 @@ async fn authenticate_user_by_student_id(
 
     match user {
         Some(user) => {
-            if bcrypt::verify(password, &user.password_hash)? {
+            if verify_password(password, &user.password_hash).await? {
+                upgrade_password_hash(session_state, &user, password).await;
                 Ok(Some(user))
             } else {
                 Ok(None)
@@ -915,7 +1567,34 @@ async fn authenticate_user_by_student_id(
     }
 }
 
-async fn get_user_by_id(
+/// Re-hash a just-verified password when its stored hash uses another algorithm or other
+/// cost settings than configured (e.g. bcrypt -> argon2id); failures only log, the login goes on
+async fn upgrade_password_hash(session_state: &SessionState, user: &User, password: &str) {
+    let config = &session_state.app_config;
+    if !needs_rehash(config, &user.password_hash) {
+        return;
+    }
+
+    let new_hash = match hash_password(config, password).await {
+        Ok(hash) => hash,
+        Err(e) => {
+            tracing::warn!("Failed to re-hash password for user {}: {}", user.id, e);
+            return;
+        }
+    };
+    // Skip if the password changed since it was read
+    if let Err(e) = sqlx::query("UPDATE users SET password_hash = $1 WHERE id = $2 AND password_hash = $3")
+        .bind(&new_hash)
+        .bind(user.id)
+        .bind(&user.password_hash)
+        .execute(&session_state.db_pool)
+        .await
+    {
+        tracing::warn!("Failed to store upgraded password hash for user {}: {}", user.id, e);
+    }
+}
+
+pub(crate) async fn get_user_by_id(
     session_state: &SessionState,
     user_id: Uuid,
 ) -> Result<Option<User>, anyhow::Error> {
@@ -927,7 +1606,7 @@ async fn get_user_by_id(
     Ok(user)
 }
 
-async fn get_user_admin_role(
+pub(crate) async fn get_user_admin_role(
     session_state: &SessionState,
     user_id: Uuid,
 ) -> Result<Option<AdminRole>, anyhow::Error> {
@@ -937,11 +1616,11 @@ async fn get_user_admin_role(
         .fetch_optional(&session_state.db_pool)
         .await?;
 
-    // If admin role exists, check if it's active (has permissions)
+    // If admin role exists, check if it's active (enabled and has permissions)
     match admin_role {
         Some(role) => {
-            if role.permissions.is_empty() {
-                // Admin is disabled (no permissions)
+            if !role.is_enabled || role.permissions.is_empty() {
+                // Admin is disabled
                 Ok(None)
             } else {
                 Ok(Some(role))
@@ -951,7 +1630,7 @@ async fn get_user_admin_role(
     }
 }
 
-async fn check_faculty_is_active(
+pub(crate) async fn check_faculty_is_active(
     session_state: &SessionState,
     faculty_id: Option<Uuid>,
 ) -> Result<bool, anyhow::Error> {
@@ -1030,19 +1709,7 @@ fn build_session_user(
     admin_role: &Option<AdminRole>,
     session_id: &str,
 ) -> SessionUser {
-    let permissions = match admin_role {
-        Some(role) => {
-            let perms = crate::models::session::Permission::from_admin_level(
-                &role.admin_level,
-                role.faculty_id,
-            );
-            let mut perm_strings: Vec<String> =
-                perms.into_iter().map(|p| format!("{:?}", p)).collect();
-            perm_strings.extend(role.permissions.iter().cloned());
-            perm_strings
-        }
-        None => vec!["ViewProfile".to_string(), "UpdateProfile".to_string()],
-    };
+    let permissions = Permission::for_role(admin_role.as_ref());
 
     SessionUser {
         user_id: user.id,
@@ -1055,5 +1722,322 @@ fn build_session_user(
         session_id: session_id.to_string(),
         permissions,
         faculty_id: admin_role.as_ref().and_then(|r| r.faculty_id),
+        is_impersonated: false,
+        impersonated_by: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{MemorySessionStore, SessionConfig, SessionStore};
+    use crate::test_support::{insert_user, test_state};
+    use std::sync::Arc;
+
+    async fn refresh_with_expiry(minutes_left: i64) -> (Value, chrono::DateTime<Utc>) {
+        refresh_as(minutes_left, None, SessionConfig::default()).await
+    }
+
+    /// Refresh a session of `admin_level` (`None` = student) expiring in `minutes_left`
+    async fn refresh_as(
+        minutes_left: i64,
+        admin_level: Option<AdminLevel>,
+        config: SessionConfig,
+    ) -> (Value, chrono::DateTime<Utc>) {
+        let session_store = Arc::new(MemorySessionStore::default());
+        let user_id = Uuid::new_v4();
+        let create = CreateSession {
+            user_id,
+            expires_at: Utc::now() + chrono::Duration::minutes(minutes_left),
+            ip_address: None,
+            user_agent: None,
+            device_info: HashMap::new(),
+            impersonated_by: None,
+            impersonator_session_id: None,
+        };
+        let session = match admin_level {
+            Some(level) => session_store
+                .create_admin_session(create, SessionType::AdminSuper, level, None, Vec::new(), LoginMethod::Email)
+                .await
+                .unwrap(),
+            None => session_store.create_session(create).await.unwrap(),
+        };
+        let state = SessionState {
+            session_store: session_store.clone(),
+            config,
+            ..test_state(&sqlx::PgPool::connect_lazy("postgres://localhost/unused").unwrap())
+        };
+        let session_user = SessionUser {
+            user_id,
+            student_id: "6400000000".to_string(),
+            email: "student@test.local".to_string(),
+            first_name: "Test".to_string(),
+            last_name: "Student".to_string(),
+            department_id: None,
+            admin_role: None,
+            session_id: session.id.clone(),
+            permissions: Vec::new(),
+            faculty_id: None,
+            is_impersonated: false,
+            impersonated_by: None,
+        };
+
+        let Json(body) = refresh_session(State(state), Cookies::default(), session_user)
+            .await
+            .unwrap();
+        let stored = session_store.get_session(&session.id).await.unwrap().unwrap();
+        (body, stored.expires_at)
+    }
+
+    #[tokio::test]
+    async fn test_refresh_extends_session_near_expiry() {
+        let (body, expires_at) = refresh_with_expiry(10).await;
+        assert_eq!(body["refreshed"], true);
+        assert!(expires_at > Utc::now() + chrono::Duration::hours(23));
+        assert_eq!(body["expires_at"], serde_json::json!(expires_at));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_leaves_fresh_session_unchanged() {
+        let (body, expires_at) = refresh_with_expiry(6 * 60).await;
+        assert_eq!(body["refreshed"], false);
+        assert!(expires_at < Utc::now() + chrono::Duration::hours(7));
+        assert_eq!(body["expires_at"], serde_json::json!(expires_at));
+    }
+
+    fn admin_ttl_config() -> SessionConfig {
+        SessionConfig {
+            role_ttls: crate::services::redis_session::RoleSessionTtls {
+                super_admin: Some(chrono::Duration::hours(2)),
+                ..Default::default()
+            },
+            ..SessionConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refresh_uses_admin_ttl() {
+        let (admin_body, admin_expiry) = refresh_as(10, Some(AdminLevel::SuperAdmin), admin_ttl_config()).await;
+        let (_, student_expiry) = refresh_as(10, None, admin_ttl_config()).await;
+
+        assert_eq!(admin_body["refreshed"], true);
+        assert!(admin_expiry <= Utc::now() + chrono::Duration::hours(2));
+        assert!(admin_expiry > Utc::now() + chrono::Duration::minutes(119));
+        assert!(student_expiry > Utc::now() + chrono::Duration::hours(23));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_admin_login_gets_admin_ttl() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = SessionState {
+            config: admin_ttl_config(),
+            ..test_state(&pool)
+        };
+        let tag = Uuid::new_v4().simple().to_string()[..8].to_string();
+        let user_id = insert_user(&pool, &format!("tt{}", tag), None).await;
+        let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let admin_role = AdminRole {
+            id: Uuid::new_v4(),
+            user_id: user.id,
+            admin_level: AdminLevel::SuperAdmin,
+            faculty_id: None,
+            permissions: Vec::new(),
+            is_enabled: true,
+            created_at: None,
+            updated_at: None,
+        };
+
+        let cookies = Cookies::default();
+        let login = PendingAdminLogin {
+            user_id: user.id,
+            // The role TTL applies even to remember-me logins
+            remember_me: true,
+            ip_address: None,
+            user_agent: "test".to_string(),
+            device_info: HashMap::new(),
+            attempts: 0,
+            expires_at: Utc::now(),
+        };
+        let result = finish_admin_login(&state, &cookies, &user, Some(admin_role), login).await;
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let Ok(Json(response)) = result else { panic!("admin login failed") };
+        let expires_at = response.session.unwrap().expires_at;
+        assert!(expires_at <= Utc::now() + chrono::Duration::hours(2));
+        assert!(expires_at > Utc::now() + chrono::Duration::minutes(119));
+        let max_age = cookies.get("session_id").unwrap().max_age().unwrap().whole_seconds();
+        assert!((2 * 3600 - 5..=2 * 3600).contains(&max_age));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_login_history_records_failures_and_successes() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let tag = Uuid::new_v4().simple().to_string()[..8].to_string();
+        let faculty_id: Uuid = sqlx::query_scalar("INSERT INTO faculties (name, code, status) VALUES ($1, $2, true) RETURNING id")
+            .bind(format!("Logins {}", tag))
+            .bind(&tag)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let department_id: Uuid =
+            sqlx::query_scalar("INSERT INTO departments (name, code, faculty_id) VALUES ('Dept', 'D', $1) RETURNING id")
+                .bind(faculty_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        let student_id = format!("lh{}", tag);
+        let user_id = insert_user(&pool, &student_id, None).await;
+        sqlx::query("UPDATE users SET password_hash = $2, department_id = $3 WHERE id = $1")
+            .bind(user_id)
+            .bind(bcrypt::hash("correct-password", 4).unwrap())
+            .bind(department_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let login = |password: &str| {
+            student_login(
+                State(state.clone()),
+                Cookies::default(),
+                HeaderMap::new(),
+                PeerAddr::default(),
+                ApiJson(StudentLoginRequest {
+                    student_id: student_id.clone(),
+                    password: password.to_string(),
+                    remember_me: None,
+                    device_info: None,
+                    device_verification_token: None,
+                }),
+            )
+        };
+        let Ok(Json(failed)) = login("wrong-password").await else { panic!("login errored") };
+        let Ok(Json(succeeded)) = login("correct-password").await else { panic!("login errored") };
+
+        let session_user = |user_id, admin_role: Option<AdminRole>| SessionUser {
+            user_id,
+            student_id: student_id.clone(),
+            email: "login@test.local".to_string(),
+            first_name: "Login".to_string(),
+            last_name: "History".to_string(),
+            department_id: None,
+            faculty_id: admin_role.as_ref().and_then(|r| r.faculty_id),
+            admin_role,
+            session_id: Uuid::new_v4().to_string(),
+            permissions: Vec::new(),
+            is_impersonated: false,
+            impersonated_by: None,
+        };
+        let own = get_my_login_history(
+            State(state.clone()),
+            session_user(user_id, None),
+            Query(LoginHistoryQuery { limit: None }),
+        )
+        .await;
+
+        let other_faculty_admin = AdminRole {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            admin_level: AdminLevel::FacultyAdmin,
+            faculty_id: Some(Uuid::new_v4()),
+            permissions: Vec::new(),
+            is_enabled: true,
+            created_at: None,
+            updated_at: None,
+        };
+        let admin_user = session_user(other_faculty_admin.user_id, Some(other_faculty_admin.clone()));
+        let out_of_scope = get_user_login_history(
+            State(state.clone()),
+            AdminUser {
+                session_user: admin_user,
+                admin_role: other_faculty_admin,
+            },
+            Path(user_id),
+            Query(LoginHistoryQuery { limit: None }),
+        )
+        .await;
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM faculties WHERE id = $1")
+            .bind(faculty_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(!failed.success);
+        assert!(succeeded.success);
+        let Json(own) = own.unwrap();
+        let events = own["data"].as_array().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["success"], true);
+        assert_eq!(events[1]["success"], false);
+        assert_eq!(events[0]["user_agent"], "Unknown");
+        assert!(matches!(out_of_scope, Err(ApiError::Forbidden(_))));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_login_upgrades_bcrypt_hash_to_argon2id() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let mut config = crate::config::Config::from_env().unwrap();
+        config.password_hash_algorithm = crate::config::PasswordHashAlgorithm::Argon2id;
+        config.argon2_memory_kib = 64;
+        config.argon2_iterations = 1;
+        let state = SessionState {
+            app_config: Arc::new(config),
+            ..test_state(&pool)
+        };
+
+        let student_id = format!("up{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let user_id = insert_user(&pool, &student_id, None).await;
+        sqlx::query("UPDATE users SET password_hash = $2 WHERE id = $1")
+            .bind(user_id)
+            .bind(bcrypt::hash("correct-password", 4).unwrap())
+            .execute(&pool)
+            .await
+            .unwrap();
+        let stored_hash = || {
+            sqlx::query_scalar::<_, String>("SELECT password_hash FROM users WHERE id = $1")
+                .bind(user_id)
+                .fetch_one(&pool)
+        };
+
+        let upgraded = authenticate_user_by_student_id(&state, &student_id, "correct-password").await;
+        let after_first_login = stored_hash().await.unwrap();
+        let again = authenticate_user_by_student_id(&state, &student_id, "correct-password").await;
+        let wrong = authenticate_user_by_student_id(&state, &student_id, "wrong-password").await;
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(upgraded.unwrap().is_some());
+        assert!(after_first_login.starts_with("$argon2id$v=19$m=64,t=1,p=1$"));
+        assert!(again.unwrap().is_some());
+        assert!(wrong.unwrap().is_none());
     }
 }
@@ -0,0 +1,422 @@
+use axum::{
+    extract::{Path, State},
+    http::header,
+    response::{IntoResponse, Json, Response},
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use qrcode::{Color, EcLevel, QrCode};
+use serde::Serialize;
+use serde_json::{json, Value};
+use sqlx::FromRow;
+use std::sync::OnceLock;
+use uuid::Uuid;
+
+use crate::error::{ApiError, ApiResult};
+use crate::middleware::session::SessionState;
+use crate::models::participation::ParticipationStatus;
+use crate::models::session::SessionUser;
+use crate::utils::pdf::{EmbeddedFont, PdfPage};
+use crate::utils::qr::{generate_certificate_token, verify_certificate_token};
+
+/// A4 landscape in points
+const PAGE_WIDTH: f32 = 842.0;
+const PAGE_HEIGHT: f32 = 595.0;
+const MARGIN: f32 = 36.0;
+const QR_SIZE: f32 = 110.0;
+
+static CERTIFICATE_FONT: OnceLock<EmbeddedFont> = OnceLock::new();
+
+#[derive(Debug, FromRow)]
+struct CertificateRecord {
+    participation_id: Uuid,
+    status: ParticipationStatus,
+    first_name: String,
+    last_name: String,
+    student_id: String,
+    activity_id: Uuid,
+    activity_title: String,
+    hours: i32,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    completed_at: Option<DateTime<Utc>>,
+}
+
+const CERTIFICATE_SELECT: &str = r#"
+    SELECT p.id AS participation_id, p.status, u.first_name, u.last_name, u.student_id,
+           a.id AS activity_id, a.title AS activity_title, COALESCE(uah.hours, a.hours) AS hours,
+           a.start_date, a.end_date, uah.awarded_at AS completed_at
+    FROM participations p
+    JOIN users u ON u.id = p.user_id
+    JOIN activities a ON a.id = p.activity_id AND a.deleted_at IS NULL
+    LEFT JOIN user_activity_hours uah ON uah.participation_id = p.id
+"#;
+
+/// Public certificate details returned by the verification endpoint
+#[derive(Debug, Serialize)]
+struct CertificateDetails {
+    certificate_id: Uuid,
+    student_name: String,
+    student_id: String,
+    activity_id: Uuid,
+    activity_title: String,
+    hours: i32,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    completed_at: Option<DateTime<Utc>>,
+}
+
+impl From<CertificateRecord> for CertificateDetails {
+    fn from(record: CertificateRecord) -> Self {
+        Self {
+            certificate_id: record.participation_id,
+            student_name: format!("{} {}", record.first_name, record.last_name),
+            student_id: record.student_id,
+            activity_id: record.activity_id,
+            activity_title: record.activity_title,
+            hours: record.hours,
+            start_date: record.start_date,
+            end_date: record.end_date,
+            completed_at: record.completed_at,
+        }
+    }
+}
+
+/// ดาวน์โหลดใบรับรองการเข้าร่วมกิจกรรม (PDF) ของตนเอง; ออกให้เฉพาะผู้ที่สถานะเป็น completed
+pub async fn get_activity_certificate(
+    State(session_state): State<SessionState>,
+    user: SessionUser,
+    Path(activity_id): Path<Uuid>,
+) -> ApiResult<Response> {
+    let record = sqlx::query_as::<_, CertificateRecord>(&format!(
+        "{} WHERE p.user_id = $1 AND p.activity_id = $2",
+        CERTIFICATE_SELECT
+    ))
+    .bind(user.user_id)
+    .bind(activity_id)
+    .fetch_optional(&session_state.db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to load certificate data: {}", e);
+        ApiError::internal("Failed to load certificate")
+    })?;
+
+    let Some(record) = record else {
+        return Err(ApiError::not_found("not_registered", "You are not registered for this activity"));
+    };
+    if record.status != ParticipationStatus::Completed {
+        return Err(ApiError::forbidden(
+            "activity_not_completed",
+            "Certificates are only issued after completing the activity",
+        ));
+    }
+
+    let config = &session_state.app_config;
+    let token = generate_certificate_token(&record.participation_id, &config.qr_signing_secret)
+        .map_err(|_| ApiError::internal("Failed to sign certificate"))?;
+    let verify_url = format!(
+        "{}/api/verify/certificate/{}",
+        config.frontend_url.trim_end_matches('/'),
+        token
+    );
+
+    let font = CERTIFICATE_FONT
+        .get()
+        .ok_or_else(|| ApiError::internal("Certificate font is not loaded"))?;
+    let pdf = render_certificate(&record, &verify_url, font).map_err(|e| {
+        tracing::error!("Failed to render certificate: {}", e);
+        ApiError::internal("Failed to render certificate")
+    })?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/pdf".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"certificate_{}.pdf\"", activity_id),
+            ),
+        ],
+        pdf,
+    )
+        .into_response())
+}
+
+/// ตรวจสอบความถูกต้องของใบรับรองจาก token ใน QR (ไม่ต้องล็อกอิน)
+pub async fn verify_certificate(
+    State(session_state): State<SessionState>,
+    Path(token): Path<String>,
+) -> ApiResult<Json<Value>> {
+    let not_found = || ApiError::not_found("certificate_not_found", "Certificate not found or no longer valid");
+
+    let participation_id =
+        verify_certificate_token(&token, &session_state.app_config.qr_signing_secret).ok_or_else(not_found)?;
+
+    let record = sqlx::query_as::<_, CertificateRecord>(&format!("{} WHERE p.id = $1", CERTIFICATE_SELECT))
+        .bind(participation_id)
+        .fetch_optional(&session_state.db_pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load certificate data: {}", e);
+            ApiError::internal("Failed to verify certificate")
+        })?
+        .filter(|record| record.status == ParticipationStatus::Completed)
+        .ok_or_else(not_found)?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {
+            "valid": true,
+            "certificate": CertificateDetails::from(record)
+        }
+    })))
+}
+
+/// โหลดฟอนต์จาก CERTIFICATE_FONT_PATH ครั้งเดียวตอนเริ่มเซิร์ฟเวอร์; ไม่มีฟอนต์หรือฟอนต์เสียจะเริ่มไม่ได้
+pub fn init_certificate_font(path: Option<&str>) -> anyhow::Result<()> {
+    let path = path.ok_or_else(|| {
+        anyhow::anyhow!("CERTIFICATE_FONT_PATH must point at a TrueType font with Thai glyphs, e.g. Sarabun")
+    })?;
+    let font = EmbeddedFont::load(path)?;
+    if !font.has_glyph('ก') {
+        tracing::warn!("Certificate font {} has no Thai glyphs; Thai names will not print", path);
+    }
+    let _ = CERTIFICATE_FONT.set(font);
+    tracing::info!("Certificate font loaded from {}", path);
+    Ok(())
+}
+
+fn format_activity_dates(start: NaiveDate, end: NaiveDate) -> String {
+    if start == end {
+        start.format("%-d %B %Y").to_string()
+    } else {
+        format!("{} - {}", start.format("%-d %B %Y"), end.format("%-d %B %Y"))
+    }
+}
+
+/// Largest size up to `max_size` at which `text` fits in `max_width`
+fn fitted_size(document: &PdfPage, text: &str, max_size: f32, max_width: f32) -> f32 {
+    let width = document.text_width(text, max_size);
+    if width > max_width {
+        max_size * max_width / width
+    } else {
+        max_size
+    }
+}
+
+fn render_certificate(record: &CertificateRecord, verify_url: &str, font: &EmbeddedFont) -> anyhow::Result<Vec<u8>> {
+    let document = PdfPage::new("Certificate of Participation", PAGE_WIDTH, PAGE_HEIGHT, font)?;
+    let center = document.width() / 2.0;
+    let text_width = PAGE_WIDTH - 4.0 * MARGIN;
+
+    document.stroke_rect(MARGIN, MARGIN, PAGE_WIDTH - 2.0 * MARGIN, PAGE_HEIGHT - 2.0 * MARGIN, 2.0);
+    document.stroke_rect(
+        MARGIN + 6.0,
+        MARGIN + 6.0,
+        PAGE_WIDTH - 2.0 * MARGIN - 12.0,
+        PAGE_HEIGHT - 2.0 * MARGIN - 12.0,
+        0.5,
+    );
+
+    document.text_centered(center, 470.0, 30.0, "Certificate of Participation");
+    document.text_centered(center, 425.0, 13.0, "This certifies that");
+
+    let name = format!("{} {}", record.first_name, record.last_name);
+    let name_size = fitted_size(&document, &name, 26.0, text_width);
+    document.text_centered(center, 385.0, name_size, &name);
+    document.text_centered(center, 360.0, 12.0, &format!("Student ID {}", record.student_id));
+
+    document.text_centered(center, 318.0, 13.0, "has successfully completed the activity");
+    let title_size = fitted_size(&document, &record.activity_title, 20.0, text_width);
+    document.text_centered(center, 282.0, title_size, &record.activity_title);
+    document.text_centered(
+        center,
+        252.0,
+        12.0,
+        &format!(
+            "{}  |  {} hour{}",
+            format_activity_dates(record.start_date, record.end_date),
+            record.hours,
+            if record.hours == 1 { "" } else { "s" }
+        ),
+    );
+
+    let left = MARGIN + 30.0;
+    document.text(left, 92.0, 9.0, &format!("Certificate ID: {}", record.participation_id));
+    if let Some(completed_at) = record.completed_at {
+        document.text(left, 78.0, 9.0, &format!("Completed: {}", completed_at.format("%-d %B %Y")));
+    }
+
+    let qr_x = PAGE_WIDTH - MARGIN - 30.0 - QR_SIZE;
+    let qr_y = MARGIN + 40.0;
+    draw_qr(&document, verify_url, qr_x, qr_y)?;
+    document.text_centered(qr_x + QR_SIZE / 2.0, qr_y - 14.0, 8.0, "Scan to verify");
+
+    document.render()
+}
+
+/// Draw the QR code as filled squares, one rectangle per horizontal run of dark modules
+fn draw_qr(document: &PdfPage, data: &str, x: f32, y: f32) -> anyhow::Result<()> {
+    let code = QrCode::with_error_correction_level(data.as_bytes(), EcLevel::M)?;
+    let width = code.width();
+    let module = QR_SIZE / width as f32;
+    let colors = code.to_colors();
+
+    for row in 0..width {
+        // PDF y grows upwards, QR rows go down
+        let row_y = y + QR_SIZE - (row + 1) as f32 * module;
+        let mut col = 0;
+        while col < width {
+            if colors[row * width + col] != Color::Dark {
+                col += 1;
+                continue;
+            }
+            let run_start = col;
+            while col < width && colors[row * width + col] == Color::Dark {
+                col += 1;
+            }
+            document.fill_rect(
+                x + run_start as f32 * module,
+                row_y,
+                (col - run_start) as f32 * module,
+                module,
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{insert_user, test_state};
+
+    fn record(status: ParticipationStatus) -> CertificateRecord {
+        CertificateRecord {
+            participation_id: Uuid::new_v4(),
+            status,
+            first_name: "Somchai".to_string(),
+            last_name: "Jaidee".to_string(),
+            student_id: "6401234567".to_string(),
+            activity_id: Uuid::new_v4(),
+            activity_title: "Volunteer Day".to_string(),
+            hours: 3,
+            start_date: NaiveDate::from_ymd_opt(2024, 5, 1).unwrap(),
+            end_date: NaiveDate::from_ymd_opt(2024, 5, 2).unwrap(),
+            completed_at: None,
+        }
+    }
+
+    #[test]
+    fn test_render_certificate() {
+        // Systems without DejaVu have no font to render with
+        let Ok(font) = EmbeddedFont::load("/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf") else {
+            return;
+        };
+        let pdf = render_certificate(
+            &record(ParticipationStatus::Completed),
+            "http://localhost:5173/api/verify/certificate/x.y",
+            &font,
+        )
+        .unwrap();
+        assert!(pdf.starts_with(b"%PDF-"));
+        assert_eq!(
+            format_activity_dates(NaiveDate::from_ymd_opt(2024, 5, 1).unwrap(), NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()),
+            "1 May 2024"
+        );
+    }
+
+    #[test]
+    fn test_certificate_font_is_required() {
+        assert!(init_certificate_font(None).is_err());
+        assert!(init_certificate_font(Some("/nonexistent/font.ttf")).is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database and CERTIFICATE_FONT_PATH"]
+    async fn test_certificate_requires_completion_and_verifies() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+        init_certificate_font(state.app_config.certificate_font_path.as_deref()).unwrap();
+
+        let tag = &Uuid::new_v4().simple().to_string()[..8];
+        let student_id = format!("cert{}", tag);
+        let user_id = insert_user(&pool, &student_id, None).await;
+        let activity_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO activities (title, description, location, academic_year, organizer,
+                start_date, end_date, start_time_only, end_time_only, hours, status, created_by)
+             VALUES ('Certificate', 'Certificate test', 'Lab', '2567', 'Test', CURRENT_DATE - 1, CURRENT_DATE - 1,
+                '09:00', '17:00', 2, 'completed', $1)
+             RETURNING id",
+        )
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        let user = SessionUser {
+            user_id,
+            student_id: student_id.clone(),
+            email: format!("{}@test.local", student_id),
+            first_name: "Test".to_string(),
+            last_name: "Student".to_string(),
+            department_id: None,
+            admin_role: None,
+            session_id: Uuid::new_v4().to_string(),
+            permissions: Vec::new(),
+            faculty_id: None,
+            is_impersonated: false,
+            impersonated_by: None,
+        };
+
+        // Not registered at all
+        let result = get_activity_certificate(State(state.clone()), user.clone(), Path(activity_id)).await;
+        assert!(matches!(result, Err(ApiError::NotFound(_))));
+
+        let participation_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO participations (user_id, activity_id, status) VALUES ($1, $2, 'registered') RETURNING id",
+        )
+        .bind(user_id)
+        .bind(activity_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let result = get_activity_certificate(State(state.clone()), user.clone(), Path(activity_id)).await;
+        assert!(matches!(result, Err(ApiError::Forbidden(_))));
+
+        sqlx::query("UPDATE participations SET status = 'completed' WHERE id = $1")
+            .bind(participation_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let response = get_activity_certificate(State(state.clone()), user.clone(), Path(activity_id))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/pdf");
+
+        let token = generate_certificate_token(&participation_id, &state.app_config.qr_signing_secret).unwrap();
+        let Json(body) = verify_certificate(State(state.clone()), Path(token.clone())).await.unwrap();
+        assert_eq!(body["data"]["valid"], json!(true));
+        assert_eq!(body["data"]["certificate"]["student_id"], json!(student_id));
+        assert_eq!(body["data"]["certificate"]["hours"], json!(2));
+
+        let forged = generate_certificate_token(&participation_id, "not-the-server-secret").unwrap();
+        let result = verify_certificate(State(state.clone()), Path(forged)).await;
+        assert!(matches!(result, Err(ApiError::NotFound(_))));
+
+        // Revoking the completion invalidates the certificate
+        sqlx::query("UPDATE participations SET status = 'checked_out' WHERE id = $1")
+            .bind(participation_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let result = verify_certificate(State(state.clone()), Path(token)).await;
+        assert!(matches!(result, Err(ApiError::NotFound(_))));
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
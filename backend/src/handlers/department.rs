@@ -8,11 +8,9 @@ use serde_json::{json, Value};
 use sqlx::Row;
 use uuid::Uuid;
 
-use crate::middleware::session::{SessionState, FacultyAdminUser, SuperAdminUser};
-use crate::models::{
-    department::Department,
-    admin_role::AdminLevel,
-};
+use crate::error::ApiJson;
+use crate::middleware::session::{FacultyAdminUser, FacultyScope, SessionState, SuperAdminUser};
+use crate::models::department::Department;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateDepartmentRequest {
@@ -33,18 +31,10 @@ pub struct UpdateDepartmentRequest {
 pub async fn get_faculty_departments(
     State(session_state): State<SessionState>,
     Path(faculty_id): Path<Uuid>,
-    admin: FacultyAdminUser,
+    _admin: FacultyAdminUser,
+    scope: FacultyScope,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    // Check authorization - FacultyAdmin can only access their own faculty
-    if admin.admin_role.admin_level != AdminLevel::SuperAdmin {
-        if admin.admin_role.faculty_id != Some(faculty_id) {
-            let error_response = json!({
-                "status": "error",
-                "message": "Access denied: You can only access departments in your faculty"
-            });
-            return Err((StatusCode::FORBIDDEN, Json(error_response)));
-        }
-    }
+    scope.authorize(faculty_id)?;
 
     // Verify faculty exists
     let faculty_exists = sqlx::query_scalar::<_, bool>(
@@ -194,19 +184,11 @@ pub async fn get_all_departments_admin(
 pub async fn create_faculty_department(
     State(session_state): State<SessionState>,
     Path(faculty_id): Path<Uuid>,
-    admin: FacultyAdminUser,
-    Json(request): Json<CreateDepartmentRequest>,
+    _admin: FacultyAdminUser,
+    scope: FacultyScope,
+    ApiJson(request): ApiJson<CreateDepartmentRequest>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    // Check authorization - FacultyAdmin can only create in their own faculty
-    if admin.admin_role.admin_level != AdminLevel::SuperAdmin {
-        if admin.admin_role.faculty_id != Some(faculty_id) {
-            let error_response = json!({
-                "status": "error",
-                "message": "Access denied: You can only create departments in your faculty"
-            });
-            return Err((StatusCode::FORBIDDEN, Json(error_response)));
-        }
-    }
+    scope.authorize(faculty_id)?;
 
     // Verify faculty exists
     let faculty_exists = sqlx::query_scalar::<_, bool>(
@@ -280,8 +262,9 @@ pub async fn create_faculty_department(
 pub async fn update_department(
     State(session_state): State<SessionState>,
     Path(department_id): Path<Uuid>,
-    admin: FacultyAdminUser,
-    Json(request): Json<UpdateDepartmentRequest>,
+    _admin: FacultyAdminUser,
+    scope: FacultyScope,
+    ApiJson(request): ApiJson<UpdateDepartmentRequest>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     // First, get the department to check faculty ownership
     let department_info = sqlx::query(
@@ -314,16 +297,7 @@ pub async fn update_department(
 
     let department_faculty_id: Uuid = department_info.get("faculty_id");
 
-    // Check authorization - FacultyAdmin can only update departments in their faculty
-    if admin.admin_role.admin_level != AdminLevel::SuperAdmin {
-        if admin.admin_role.faculty_id != Some(department_faculty_id) {
-            let error_response = json!({
-                "status": "error",
-                "message": "Access denied: You can only update departments in your faculty"
-            });
-            return Err((StatusCode::FORBIDDEN, Json(error_response)));
-        }
-    }
+    scope.authorize(department_faculty_id)?;
 
     // Build dynamic update query
     let mut query = "UPDATE departments SET updated_at = NOW()".to_string();
@@ -397,7 +371,8 @@ pub async fn update_department(
 pub async fn delete_department(
     State(session_state): State<SessionState>,
     Path(department_id): Path<Uuid>,
-    admin: FacultyAdminUser,
+    _admin: FacultyAdminUser,
+    scope: FacultyScope,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     // First, get the department to check faculty ownership
     let department_info = sqlx::query(
@@ -430,16 +405,7 @@ pub async fn delete_department(
 
     let department_faculty_id: Uuid = department_info.get("faculty_id");
 
-    // Check authorization - FacultyAdmin can only delete departments in their faculty
-    if admin.admin_role.admin_level != AdminLevel::SuperAdmin {
-        if admin.admin_role.faculty_id != Some(department_faculty_id) {
-            let error_response = json!({
-                "status": "error",
-                "message": "Access denied: You can only delete departments in your faculty"
-            });
-            return Err((StatusCode::FORBIDDEN, Json(error_response)));
-        }
-    }
+    scope.authorize(department_faculty_id)?;
 
     // Check if department has users - prevent deletion if users exist
     let user_count = sqlx::query_scalar::<_, i64>(
@@ -495,7 +461,8 @@ pub async fn delete_department(
 pub async fn toggle_department_status(
     State(session_state): State<SessionState>,
     Path(department_id): Path<Uuid>,
-    admin: FacultyAdminUser,
+    _admin: FacultyAdminUser,
+    scope: FacultyScope,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     // First, get the department to check faculty ownership and current status
     let department_info = sqlx::query(
@@ -529,16 +496,7 @@ pub async fn toggle_department_status(
     let department_faculty_id: Uuid = department_info.get("faculty_id");
     let current_status: bool = department_info.get("status");
 
-    // Check authorization - FacultyAdmin can only toggle departments in their faculty
-    if admin.admin_role.admin_level != AdminLevel::SuperAdmin {
-        if admin.admin_role.faculty_id != Some(department_faculty_id) {
-            let error_response = json!({
-                "status": "error",
-                "message": "Access denied: You can only toggle departments in your faculty"
-            });
-            return Err((StatusCode::FORBIDDEN, Json(error_response)));
-        }
-    }
+    scope.authorize(department_faculty_id)?;
 
     // Toggle the status
     let new_status = !current_status;
@@ -8,6 +8,7 @@ use serde_json::json;
 use sqlx::Row;
 use uuid::Uuid;
 
+use crate::error::ApiJson;
 use crate::middleware::session::{SessionState, SuperAdminUser, FacultyAdminUser};
 use crate::models::{
     faculty::Faculty,
@@ -136,7 +137,7 @@ pub async fn get_faculty(
 pub async fn create_faculty(
     State(session_state): State<SessionState>,
     _admin: SuperAdminUser,
-    Json(request): Json<CreateFacultyRequest>,
+    ApiJson(request): ApiJson<CreateFacultyRequest>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
     let query_result = sqlx::query_as::<_, Faculty>(
         "INSERT INTO faculties (name, code, description, status) VALUES ($1, $2, $3, $4) RETURNING id, name, code, description, status, created_at, updated_at"
@@ -172,7 +173,7 @@ pub async fn update_faculty(
     State(session_state): State<SessionState>,
     Path(id): Path<Uuid>,
     _admin: SuperAdminUser,
-    Json(request): Json<UpdateFacultyRequest>,
+    ApiJson(request): ApiJson<UpdateFacultyRequest>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
     // Build dynamic update query
     let mut query = "UPDATE faculties SET updated_at = NOW()".to_string();
@@ -371,7 +372,7 @@ pub struct CreateDepartmentRequest {
 pub async fn create_faculty_department(
     State(session_state): State<SessionState>,
     Path(faculty_id): Path<Uuid>,
-    Json(request): Json<CreateDepartmentRequest>,
+    ApiJson(request): ApiJson<CreateDepartmentRequest>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
     // Verify faculty exists
     let faculty_exists = sqlx::query_scalar::<_, bool>(
@@ -649,7 +650,7 @@ pub async fn get_faculties_with_stats(
         LEFT JOIN departments d ON f.id = d.faculty_id
         LEFT JOIN users u ON d.id = u.department_id
         LEFT JOIN admin_roles ar ON (ar.faculty_id = f.id AND ar.admin_level IN ('faculty_admin', 'regular_admin'))
-        LEFT JOIN activities a ON f.id = a.faculty_id
+        LEFT JOIN activities a ON f.id = a.faculty_id AND a.deleted_at IS NULL
         GROUP BY f.id, f.name, f.code, f.description, f.status, f.created_at, f.updated_at
         ORDER BY f.name
         "#,
@@ -723,7 +724,7 @@ pub async fn get_faculty_overview(
         LEFT JOIN departments d ON f.id = d.faculty_id
         LEFT JOIN users u ON d.id = u.department_id
         LEFT JOIN admin_roles ar ON ar.faculty_id IS NOT NULL
-        LEFT JOIN activities a ON f.id = a.faculty_id
+        LEFT JOIN activities a ON f.id = a.faculty_id AND a.deleted_at IS NULL
         "#
     )
     .fetch_one(&session_state.db_pool)
@@ -748,7 +749,7 @@ pub async fn get_faculty_overview(
         FROM faculties f
         LEFT JOIN departments d ON f.id = d.faculty_id
         LEFT JOIN users u ON d.id = u.department_id
-        LEFT JOIN activities a ON f.id = a.faculty_id
+        LEFT JOIN activities a ON f.id = a.faculty_id AND a.deleted_at IS NULL
         LEFT JOIN participations p ON a.id = p.activity_id
         GROUP BY f.id, f.name, f.code, f.status
         ORDER BY activity_count DESC, participation_rate DESC
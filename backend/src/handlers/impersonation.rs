@@ -0,0 +1,267 @@
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    response::Json,
+};
+use chrono::{Duration, Utc};
+use serde_json::{json, Value};
+use tower_cookies::Cookies;
+use uuid::Uuid;
+
+use crate::error::{ApiError, ApiResult};
+use crate::handlers::auth::{ensure_account_active, get_user_admin_role, get_user_by_id};
+use crate::middleware::session::{create_login_cookie, delete_session_cookie, SessionState, SuperAdminUser};
+use crate::models::admin_role::AdminLevel;
+use crate::models::audit_log::NewAuditLog;
+use crate::models::session::{CreateSession, SessionActivityType, SessionUser};
+use crate::services::audit_log::record_audit_log;
+use crate::utils::{get_client_info, PeerAddr};
+
+/// Impersonation sessions are short-lived and never extended by "remember me"
+const IMPERSONATION_TTL_MINUTES: i64 = 30;
+
+// POST /api/admin/impersonate/{user_id} - switch the cookie to a session acting as the user
+pub async fn start_impersonation(
+    State(session_state): State<SessionState>,
+    cookies: Cookies,
+    headers: HeaderMap,
+    peer: PeerAddr,
+    admin: SuperAdminUser,
+    Path(user_id): Path<Uuid>,
+) -> ApiResult<Json<Value>> {
+    let admin_id = admin.session_user.user_id;
+    if user_id == admin_id {
+        return Err(ApiError::bad_request("cannot_impersonate_self", "You cannot impersonate yourself"));
+    }
+
+    let user = get_user_by_id(&session_state, user_id)
+        .await
+        .map_err(|_| ApiError::internal("Failed to load user"))?
+        .ok_or_else(|| ApiError::not_found("user_not_found", "User not found"))?;
+    ensure_account_active(&user)?;
+
+    let target_role = get_user_admin_role(&session_state, user_id)
+        .await
+        .map_err(|_| ApiError::internal("Failed to load user"))?;
+    if target_role.is_some_and(|role| role.admin_level == AdminLevel::SuperAdmin) {
+        return Err(ApiError::forbidden(
+            "cannot_impersonate_super_admin",
+            "Super admins cannot be impersonated",
+        ));
+    }
+
+    let (ip_address, user_agent) = get_client_info(&headers, peer, &session_state.app_config.trusted_proxies);
+    let expires_at = Utc::now() + Duration::minutes(IMPERSONATION_TTL_MINUTES);
+    let session = session_state
+        .session_store
+        .create_session(CreateSession {
+            user_id,
+            expires_at,
+            ip_address,
+            user_agent,
+            device_info: Default::default(),
+            impersonated_by: Some(admin_id),
+            impersonator_session_id: Some(admin.session_user.session_id.clone()),
+        })
+        .await
+        .map_err(|_| ApiError::internal("Failed to start impersonation"))?;
+
+    let _ = session_state
+        .session_store
+        .add_session_activity(
+            &admin.session_user.session_id,
+            SessionActivityType::AdminAction,
+            Some(format!("Started impersonating user {}", user_id)),
+            None,
+            None,
+        )
+        .await;
+    record_audit_log(
+        &session_state.db_pool,
+        NewAuditLog::new(admin_id, "start_impersonation", "user", user_id)
+            .details(json!({ "session_id": session.id, "expires_at": expires_at })),
+    )
+    .await;
+
+    cookies.add(create_login_cookie(&session.id, expires_at));
+
+    Ok(Json(json!({
+        "status": "success",
+        "message": format!("Now acting as {} {}", user.first_name, user.last_name),
+        "data": {
+            "session_id": session.id,
+            "user_id": user_id,
+            "impersonated_by": admin_id,
+            "expires_at": expires_at,
+        }
+    })))
+}
+
+// POST /api/admin/impersonate/stop - called from the impersonated session, whoever the user is
+pub async fn stop_impersonation(
+    State(session_state): State<SessionState>,
+    cookies: Cookies,
+    session_user: SessionUser,
+) -> ApiResult<Json<Value>> {
+    let Some(admin_id) = session_user.impersonated_by else {
+        return Err(ApiError::bad_request("not_impersonating", "This session is not an impersonation session"));
+    };
+
+    let session = session_state
+        .session_store
+        .get_session(&session_user.session_id)
+        .await
+        .map_err(|_| ApiError::internal("Failed to stop impersonation"))?;
+    session_state
+        .session_store
+        .delete_session(&session_user.session_id)
+        .await
+        .map_err(|_| ApiError::internal("Failed to stop impersonation"))?;
+
+    record_audit_log(
+        &session_state.db_pool,
+        NewAuditLog::new(admin_id, "stop_impersonation", "user", session_user.user_id)
+            .details(json!({ "session_id": session_user.session_id })),
+    )
+    .await;
+
+    // Back to the admin's own session if it is still usable, otherwise they have to log in again
+    let admin_session = match session.and_then(|s| s.impersonator_session_id) {
+        Some(admin_session_id) => session_state
+            .session_store
+            .get_session(&admin_session_id)
+            .await
+            .map_err(|_| ApiError::internal("Failed to stop impersonation"))?
+            .filter(|s| s.is_active && s.user_id == admin_id && s.expires_at > Utc::now()),
+        None => None,
+    };
+
+    match admin_session {
+        Some(admin_session) => {
+            cookies.add(create_login_cookie(&admin_session.id, admin_session.expires_at));
+            Ok(Json(json!({
+                "status": "success",
+                "message": "Impersonation stopped",
+                "data": { "session_restored": true }
+            })))
+        }
+        None => {
+            cookies.add(delete_session_cookie());
+            Ok(Json(json!({
+                "status": "success",
+                "message": "Impersonation stopped; please log in again",
+                "data": { "session_restored": false }
+            })))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::session::validate_and_get_session_user;
+    use crate::models::session::SessionValidation;
+    use crate::test_support::{insert_user, test_state};
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_impersonation_round_trip() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let tag = Uuid::new_v4().simple().to_string()[..8].to_string();
+        let admin_id = insert_user(&pool, &format!("ia{}", tag), Some(AdminLevel::SuperAdmin)).await;
+        let student_id = insert_user(&pool, &format!("is{}", tag), None).await;
+        let other_super_id = insert_user(&pool, &format!("io{}", tag), Some(AdminLevel::SuperAdmin)).await;
+
+        let admin_session = state
+            .session_store
+            .create_session(CreateSession {
+                user_id: admin_id,
+                expires_at: Utc::now() + Duration::hours(1),
+                ip_address: None,
+                user_agent: None,
+                device_info: Default::default(),
+                impersonated_by: None,
+                impersonator_session_id: None,
+            })
+            .await
+            .unwrap();
+        let SessionValidation::Valid(admin_user) = validate_and_get_session_user(&state, &admin_session.id, None)
+            .await
+            .unwrap()
+        else {
+            panic!("admin session invalid")
+        };
+        let super_admin = || SuperAdminUser {
+            session_user: admin_user.clone(),
+            admin_role: admin_user.admin_role.clone().unwrap(),
+        };
+
+        let blocked = start_impersonation(
+            State(state.clone()),
+            Cookies::default(),
+            HeaderMap::new(),
+            PeerAddr::default(),
+            super_admin(),
+            Path(other_super_id),
+        )
+        .await;
+
+        let cookies = Cookies::default();
+        let started = start_impersonation(
+            State(state.clone()),
+            cookies.clone(),
+            HeaderMap::new(),
+            PeerAddr::default(),
+            super_admin(),
+            Path(student_id),
+        )
+        .await;
+        let impersonation_session_id = cookies.get("session_id").unwrap().value().to_string();
+        let SessionValidation::Valid(impersonated) = validate_and_get_session_user(&state, &impersonation_session_id, None)
+            .await
+            .unwrap()
+        else {
+            panic!("impersonation session invalid")
+        };
+
+        let stopped = stop_impersonation(State(state.clone()), cookies.clone(), impersonated.clone()).await;
+        let restored_session_id = cookies.get("session_id").unwrap().value().to_string();
+        let impersonation_gone = state.session_store.get_session(&impersonation_session_id).await.unwrap();
+
+        let actions: Vec<String> = sqlx::query_scalar(
+            "SELECT action FROM audit_logs WHERE actor_user_id = $1 AND target_id = $2 ORDER BY created_at",
+        )
+        .bind(admin_id)
+        .bind(student_id.to_string())
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+
+        sqlx::query("DELETE FROM audit_logs WHERE actor_user_id = $1")
+            .bind(admin_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM users WHERE id = ANY($1)")
+            .bind(vec![admin_id, student_id, other_super_id])
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(matches!(blocked, Err(ApiError::Forbidden(_))));
+        assert!(started.is_ok());
+        assert_eq!(impersonated.user_id, student_id);
+        assert!(impersonated.is_impersonated);
+        assert_eq!(impersonated.impersonated_by, Some(admin_id));
+        assert!(impersonated.admin_role.is_none());
+        let Json(stopped) = stopped.unwrap();
+        assert_eq!(stopped["data"]["session_restored"], true);
+        assert_eq!(restored_session_id, admin_session.id);
+        assert!(impersonation_gone.is_none());
+        assert_eq!(actions, vec!["start_impersonation", "stop_impersonation"]);
+    }
+}
@@ -0,0 +1,280 @@
+use axum::{
+    extract::{Path, State},
+    response::Json,
+};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::error::{ApiError, ApiJson, ApiResult};
+use crate::middleware::session::{has_faculty_access, AdminUser, SessionState};
+use crate::models::admin_role::AdminLevel;
+use crate::models::audit_log::NewAuditLog;
+use crate::models::notifications::{LineRecipientSettings, NotificationCategory, SetLineRecipientRequest};
+use crate::models::session::SessionUser;
+use crate::services::audit_log::record_audit_log;
+use crate::services::notification::NotificationTarget;
+
+const SETTINGS_COLUMNS: &str = "id, user_id, faculty_id, line_id, categories, created_at, updated_at";
+
+/// LINE user (`U`), group (`C`) or room (`R`) ID: the prefix and 32 lowercase hex digits,
+/// as delivered to the Official Account's webhook
+fn is_valid_line_id(line_id: &str) -> bool {
+    let mut chars = line_id.chars();
+    matches!(chars.next(), Some('U' | 'C' | 'R'))
+        && line_id.len() == 33
+        && chars.all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+}
+
+fn owner_column(target: NotificationTarget) -> (&'static str, Uuid) {
+    match target {
+        NotificationTarget::User(id) => ("user_id", id),
+        NotificationTarget::Faculty(id) => ("faculty_id", id),
+    }
+}
+
+fn available_categories() -> Vec<&'static str> {
+    NotificationCategory::ALL.iter().map(|category| category.as_str()).collect()
+}
+
+async fn load_settings(session_state: &SessionState, target: NotificationTarget) -> ApiResult<Option<LineRecipientSettings>> {
+    let (column, id) = owner_column(target);
+    sqlx::query_as::<_, LineRecipientSettings>(&format!(
+        "SELECT {} FROM line_recipients WHERE {} = $1",
+        SETTINGS_COLUMNS, column
+    ))
+    .bind(id)
+    .fetch_optional(&session_state.db_pool)
+    .await
+    .map_err(|_| ApiError::internal("Failed to load LINE settings"))
+}
+
+fn settings_response(settings: Option<LineRecipientSettings>, message: &str) -> Json<Value> {
+    Json(json!({
+        "status": "success",
+        "data": {
+            "configured": settings.is_some(),
+            "settings": settings,
+            "available_categories": available_categories(),
+        },
+        "message": message
+    }))
+}
+
+/// Insert or update the LINE chat of `target`. A newly linked chat defaults to every
+/// category; without `line_id` only the categories of an existing one are changed.
+async fn save_settings(
+    session_state: &SessionState,
+    target: NotificationTarget,
+    request: SetLineRecipientRequest,
+) -> ApiResult<LineRecipientSettings> {
+    let line_id = request.line_id.as_deref().map(str::trim);
+    if let Some(line_id) = line_id {
+        if !is_valid_line_id(line_id) {
+            return Err(ApiError::validation(
+                "invalid_line_id",
+                "LINE ID must be a user, group or room ID (U, C or R followed by 32 hex digits)",
+            ));
+        }
+    }
+    let categories: Option<Vec<String>> = request.categories.map(|categories| {
+        let mut names: Vec<String> = Vec::new();
+        for category in categories {
+            if !names.iter().any(|name| name == category.as_str()) {
+                names.push(category.as_str().to_string());
+            }
+        }
+        names
+    });
+
+    let (column, id) = owner_column(target);
+    let result = match line_id {
+        Some(line_id) => {
+            let categories = categories.unwrap_or_else(|| available_categories().into_iter().map(String::from).collect());
+            sqlx::query_as::<_, LineRecipientSettings>(&format!(
+                r#"
+                INSERT INTO line_recipients ({column}, line_id, categories)
+                VALUES ($1, $2, $3)
+                ON CONFLICT ({column}) DO UPDATE
+                SET line_id = EXCLUDED.line_id, categories = EXCLUDED.categories, updated_at = NOW()
+                RETURNING {columns}
+                "#,
+                column = column,
+                columns = SETTINGS_COLUMNS
+            ))
+            .bind(id)
+            .bind(line_id)
+            .bind(&categories)
+            .fetch_one(&session_state.db_pool)
+            .await
+            .map(Some)
+        }
+        None => {
+            let Some(categories) = categories else {
+                return Err(ApiError::validation(
+                    "nothing_to_update",
+                    "Provide a line_id, categories, or both",
+                ));
+            };
+            sqlx::query_as::<_, LineRecipientSettings>(&format!(
+                "UPDATE line_recipients SET categories = $2, updated_at = NOW() WHERE {} = $1 RETURNING {}",
+                column, SETTINGS_COLUMNS
+            ))
+            .bind(id)
+            .bind(&categories)
+            .fetch_optional(&session_state.db_pool)
+            .await
+        }
+    };
+
+    result
+        .map_err(|_| ApiError::internal("Failed to save LINE settings"))?
+        .ok_or_else(|| ApiError::not_found("line_recipient_not_found", "No LINE chat is linked"))
+}
+
+async fn delete_settings(session_state: &SessionState, target: NotificationTarget) -> ApiResult<bool> {
+    let (column, id) = owner_column(target);
+    sqlx::query(&format!("DELETE FROM line_recipients WHERE {} = $1", column))
+        .bind(id)
+        .execute(&session_state.db_pool)
+        .await
+        .map(|result| result.rows_affected() > 0)
+        .map_err(|_| ApiError::internal("Failed to unlink LINE chat"))
+}
+
+fn ensure_own_account(user: &SessionUser, user_id: Uuid) -> ApiResult<()> {
+    if user.user_id != user_id {
+        return Err(ApiError::forbidden(
+            "permission_denied",
+            "Access denied: You can only manage your own LINE chat",
+        ));
+    }
+    Ok(())
+}
+
+/// Faculty chats are managed by super admins and the faculty's own faculty admins
+fn ensure_faculty_manager(admin: &AdminUser, faculty_id: Uuid) -> ApiResult<()> {
+    let can_manage = admin.admin_role.admin_level != AdminLevel::RegularAdmin
+        && has_faculty_access(&admin.session_user, faculty_id);
+    if !can_manage {
+        return Err(ApiError::forbidden(
+            "permission_denied",
+            "Access denied: You can only manage the LINE chat of your own faculty",
+        ));
+    }
+    Ok(())
+}
+
+// GET /api/users/{id}/line
+pub async fn get_user_line(
+    State(session_state): State<SessionState>,
+    user: SessionUser,
+    Path(user_id): Path<Uuid>,
+) -> ApiResult<Json<Value>> {
+    ensure_own_account(&user, user_id)?;
+    let settings = load_settings(&session_state, NotificationTarget::User(user_id)).await?;
+    Ok(settings_response(settings, "LINE settings retrieved successfully"))
+}
+
+// PUT /api/users/{id}/line
+pub async fn set_user_line(
+    State(session_state): State<SessionState>,
+    user: SessionUser,
+    Path(user_id): Path<Uuid>,
+    ApiJson(request): ApiJson<SetLineRecipientRequest>,
+) -> ApiResult<Json<Value>> {
+    ensure_own_account(&user, user_id)?;
+    let settings = save_settings(&session_state, NotificationTarget::User(user_id), request).await?;
+    Ok(settings_response(Some(settings), "LINE settings saved successfully"))
+}
+
+// DELETE /api/users/{id}/line
+pub async fn delete_user_line(
+    State(session_state): State<SessionState>,
+    user: SessionUser,
+    Path(user_id): Path<Uuid>,
+) -> ApiResult<Json<Value>> {
+    ensure_own_account(&user, user_id)?;
+    if !delete_settings(&session_state, NotificationTarget::User(user_id)).await? {
+        return Err(ApiError::not_found("line_recipient_not_found", "No LINE chat is linked"));
+    }
+    Ok(settings_response(None, "LINE chat unlinked successfully"))
+}
+
+// GET /api/faculties/{faculty_id}/line
+pub async fn get_faculty_line(
+    State(session_state): State<SessionState>,
+    admin: AdminUser,
+    Path(faculty_id): Path<Uuid>,
+) -> ApiResult<Json<Value>> {
+    ensure_faculty_manager(&admin, faculty_id)?;
+    let settings = load_settings(&session_state, NotificationTarget::Faculty(faculty_id)).await?;
+    Ok(settings_response(settings, "LINE settings retrieved successfully"))
+}
+
+// PUT /api/faculties/{faculty_id}/line
+pub async fn set_faculty_line(
+    State(session_state): State<SessionState>,
+    admin: AdminUser,
+    Path(faculty_id): Path<Uuid>,
+    ApiJson(request): ApiJson<SetLineRecipientRequest>,
+) -> ApiResult<Json<Value>> {
+    ensure_faculty_manager(&admin, faculty_id)?;
+    let line_id_changed = request.line_id.is_some();
+
+    let faculty_exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM faculties WHERE id = $1)")
+        .bind(faculty_id)
+        .fetch_one(&session_state.db_pool)
+        .await
+        .map_err(|_| ApiError::internal("Failed to check faculty"))?;
+    if !faculty_exists {
+        return Err(ApiError::not_found("faculty_not_found", "Faculty not found"));
+    }
+
+    let settings = save_settings(&session_state, NotificationTarget::Faculty(faculty_id), request).await?;
+    record_audit_log(
+        &session_state.db_pool,
+        NewAuditLog::new(admin.session_user.user_id, "set_faculty_line", "faculty", faculty_id)
+            .details(json!({ "line_id_changed": line_id_changed, "categories": settings.categories })),
+    )
+    .await;
+
+    Ok(settings_response(Some(settings), "LINE settings saved successfully"))
+}
+
+// DELETE /api/faculties/{faculty_id}/line
+pub async fn delete_faculty_line(
+    State(session_state): State<SessionState>,
+    admin: AdminUser,
+    Path(faculty_id): Path<Uuid>,
+) -> ApiResult<Json<Value>> {
+    ensure_faculty_manager(&admin, faculty_id)?;
+    if !delete_settings(&session_state, NotificationTarget::Faculty(faculty_id)).await? {
+        return Err(ApiError::not_found("line_recipient_not_found", "No LINE chat is linked"));
+    }
+    record_audit_log(
+        &session_state.db_pool,
+        NewAuditLog::new(admin.session_user.user_id, "delete_faculty_line", "faculty", faculty_id),
+    )
+    .await;
+
+    Ok(settings_response(None, "LINE chat unlinked successfully"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_line_id() {
+        assert!(is_valid_line_id("U4af4980629a0b5c1e0b9d9d8e3f7a2b1"));
+        assert!(is_valid_line_id("Ca56f94637cc4347f90a25382909b24b9"));
+        assert!(is_valid_line_id("R0123456789abcdef0123456789abcdef"));
+
+        assert!(!is_valid_line_id("X4af4980629a0b5c1e0b9d9d8e3f7a2b1"));
+        assert!(!is_valid_line_id("U4AF4980629A0B5C1E0B9D9D8E3F7A2B1"));
+        assert!(!is_valid_line_id("U4af4980629a0b5c1e0b9d9d8e3f7a2b"));
+        assert!(!is_valid_line_id("U4af4980629a0b5c1e0b9d9d8e3f7a2b1f"));
+        assert!(!is_valid_line_id("Uกขค"));
+        assert!(!is_valid_line_id(""));
+    }
+}
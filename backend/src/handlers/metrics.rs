@@ -0,0 +1,109 @@
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+
+use crate::middleware::session::SessionState;
+use crate::services::metrics::{metrics, Gauge};
+
+/// Prometheus scrape endpoint
+/// ต้องตั้งค่า METRICS_TOKEN และส่ง `Authorization: Bearer <token>` มาด้วย; ถ้าไม่ตั้งค่าไว้ endpoint จะถูกปิด (404)
+pub async fn get_metrics(
+    State(session_state): State<SessionState>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    check_metrics_access(session_state.app_config.metrics_token.as_deref(), &headers)?;
+
+    let pool = &session_state.db_pool;
+    let mut gauges = vec![
+        Gauge {
+            name: "db_pool_connections",
+            help: "Open database connections",
+            value: pool.size() as f64,
+        },
+        Gauge {
+            name: "db_pool_idle_connections",
+            help: "Idle database connections",
+            value: pool.num_idle() as f64,
+        },
+        Gauge {
+            name: "db_pool_max_connections",
+            help: "Configured maximum database connections",
+            value: pool.options().get_max_connections() as f64,
+        },
+    ];
+
+    // Redis being down shouldn't fail the scrape; the gauge is just omitted
+    let session_count = session_state
+        .redis_health
+        .observe(session_state.session_store.get_session_count().await);
+    match session_count {
+        Ok(count) => gauges.push(Gauge {
+            name: "redis_active_sessions",
+            help: "Non-expired sessions in the Redis session store",
+            value: count as f64,
+        }),
+        Err(e) => tracing::warn!("Failed to read session count for metrics: {}", e),
+    }
+    gauges.push(Gauge {
+        name: "redis_available",
+        help: "1 when the last Redis call succeeded, 0 while in degraded mode",
+        value: if session_state.redis_health.is_available() { 1.0 } else { 0.0 },
+    });
+
+    Ok((
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
+        metrics().render(&gauges),
+    )
+        .into_response())
+}
+
+/// Per-route traffic and Redis health are not public: without a configured token the
+/// endpoint answers 404 as if it didn't exist
+fn check_metrics_access(expected: Option<&str>, headers: &HeaderMap) -> Result<(), StatusCode> {
+    let Some(expected) = expected else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+
+    let provided = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .unwrap_or_default();
+
+    if token_matches(provided, expected) {
+        Ok(())
+    } else {
+        Err(StatusCode::UNAUTHORIZED)
+    }
+}
+
+/// Compare digests so the check doesn't short-circuit on the first differing byte of the token
+fn token_matches(provided: &str, expected: &str) -> bool {
+    Sha256::digest(provided.as_bytes()) == Sha256::digest(expected.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_matches() {
+        assert!(token_matches("s3cret", "s3cret"));
+        assert!(!token_matches("s3cre", "s3cret"));
+        assert!(!token_matches("", "s3cret"));
+    }
+
+    #[test]
+    fn test_metrics_closed_without_token() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(check_metrics_access(None, &headers), Err(StatusCode::NOT_FOUND));
+        assert_eq!(check_metrics_access(Some("s3cret"), &headers), Err(StatusCode::UNAUTHORIZED));
+
+        headers.insert(header::AUTHORIZATION, "Bearer s3cret".parse().unwrap());
+        assert_eq!(check_metrics_access(Some("s3cret"), &headers), Ok(()));
+        assert_eq!(check_metrics_access(None, &headers), Err(StatusCode::NOT_FOUND));
+    }
+}
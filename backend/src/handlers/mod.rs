@@ -1,14 +1,24 @@
 pub mod activity;
+pub mod activity_template;
 pub mod admin;
+pub mod announcement;
 pub mod admin_dashboard;
 pub mod admin_session;
 pub mod admin_session_mgmt;
 pub mod auth;
+pub mod certificate;
 pub mod department;
 pub mod faculty;
+pub mod impersonation;
+pub mod line;
+pub mod metrics;
+pub mod notification_preferences;
+pub mod notifications;
 pub mod qr_activity;
+pub mod two_factor;
 pub mod user;
 pub mod user_management;
+pub mod webhook;
 
 // pub use user::*;
 // pub use activity::*;
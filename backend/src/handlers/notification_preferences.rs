@@ -0,0 +1,41 @@
+use axum::{extract::State, response::Json};
+use serde_json::{json, Value};
+
+use crate::error::{ApiError, ApiJson, ApiResult};
+use crate::middleware::session::SessionState;
+use crate::models::notifications::UpdateNotificationPreferences;
+use crate::models::session::SessionUser;
+use crate::services::notification::{get_notification_preferences, update_notification_preferences};
+
+// GET /api/auth/notification-preferences
+pub async fn get_preferences(
+    State(session_state): State<SessionState>,
+    user: SessionUser,
+) -> ApiResult<Json<Value>> {
+    let preferences = get_notification_preferences(&session_state.db_pool, user.user_id)
+        .await
+        .map_err(|_| ApiError::internal("Failed to load notification preferences"))?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": preferences,
+        "message": "Notification preferences retrieved successfully"
+    })))
+}
+
+// PUT /api/auth/notification-preferences - only the categories/channels sent are changed
+pub async fn update_preferences(
+    State(session_state): State<SessionState>,
+    user: SessionUser,
+    ApiJson(request): ApiJson<UpdateNotificationPreferences>,
+) -> ApiResult<Json<Value>> {
+    let preferences = update_notification_preferences(&session_state.db_pool, user.user_id, request)
+        .await
+        .map_err(|_| ApiError::internal("Failed to save notification preferences"))?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": preferences,
+        "message": "Notification preferences updated successfully"
+    })))
+}
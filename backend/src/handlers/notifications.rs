@@ -0,0 +1,91 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::Json,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::error::{ApiError, ApiResult};
+use crate::middleware::session::SessionState;
+use crate::models::session::SessionUser;
+use crate::services::notification_inbox::{
+    count_notifications, list_notifications, mark_all_notifications_read, mark_notification_read,
+    unread_notification_count,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct NotificationListQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    pub unread_only: Option<bool>,
+}
+
+// GET /api/notifications?limit=&offset=&unread_only=
+pub async fn get_notifications(
+    State(session_state): State<SessionState>,
+    user: SessionUser,
+    Query(query): Query<NotificationListQuery>,
+) -> ApiResult<Json<Value>> {
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+    let offset = query.offset.unwrap_or(0).max(0);
+    let unread_only = query.unread_only.unwrap_or(false);
+
+    let notifications = list_notifications(&session_state.db_pool, user.user_id, unread_only, limit, offset)
+        .await
+        .map_err(|_| ApiError::internal("Failed to load notifications"))?;
+    let (total, unread_count) = count_notifications(&session_state.db_pool, user.user_id)
+        .await
+        .map_err(|_| ApiError::internal("Failed to load notifications"))?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {
+            "notifications": notifications,
+            "unread_count": unread_count,
+            "total": if unread_only { unread_count } else { total },
+            "limit": limit,
+            "offset": offset,
+        }
+    })))
+}
+
+// POST /api/notifications/{id}/read
+pub async fn mark_read(
+    State(session_state): State<SessionState>,
+    user: SessionUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<Value>> {
+    let found = mark_notification_read(&session_state.db_pool, user.user_id, id)
+        .await
+        .map_err(|_| ApiError::internal("Failed to update notification"))?;
+    if !found {
+        return Err(ApiError::not_found("notification_not_found", "Notification not found"));
+    }
+
+    let unread_count = unread_notification_count(&session_state.db_pool, user.user_id)
+        .await
+        .map_err(|_| ApiError::internal("Failed to load notifications"))?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": { "unread_count": unread_count },
+        "message": "Notification marked as read"
+    })))
+}
+
+// POST /api/notifications/read-all
+pub async fn mark_all_read(
+    State(session_state): State<SessionState>,
+    user: SessionUser,
+) -> ApiResult<Json<Value>> {
+    let marked = mark_all_notifications_read(&session_state.db_pool, user.user_id)
+        .await
+        .map_err(|_| ApiError::internal("Failed to update notifications"))?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": { "marked": marked, "unread_count": 0 },
+        "message": "All notifications marked as read"
+    })))
+}
@@ -1,22 +1,39 @@
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::Json,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
 };
 use chrono::{DateTime, Utc};
+use qrcode::EcLevel;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use sqlx::Row;
+use sqlx::{FromRow, Row};
+use std::collections::HashMap;
 use uuid::Uuid;
 
+use crate::error::{ApiError, ApiJson, ApiResult};
+use crate::handlers::activity::{
+    advance_scanned_participation, ensure_checkin_window_open, ensure_scan_not_repeated,
+    ensure_scanner_assigned, scanned_participation_status,
+};
 use crate::middleware::session::{AdminUser, SessionState};
-use crate::models::session::SessionUser;
+use crate::models::session::{Permission, SessionUser};
 use crate::models::{
     activity::ActivityStatus,
+    admin_role::AdminLevel,
+    audit_log::NewAuditLog,
     participation::ParticipationStatus,
     user::User,
+    webhook::WebhookEvent,
+};
+use crate::services::audit_log::record_audit_log;
+use crate::services::notification::notify_participation_scan;
+use crate::services::webhook::{enqueue_webhook_event, participation_event_data};
+use crate::utils::qr::{
+    generate_activity_checkin_token, generate_client_qr_data, parse_activity_checkin_token,
+    render_qr_png, render_qr_svg, validate_client_qr_data, QrOutputFormat,
+    ACTIVITY_CHECKIN_TTL_SECONDS, QR_PAYLOAD_VERSION,
 };
-use crate::utils::qr::{generate_client_qr_data, validate_client_qr_data};
 
 /// Request สำหรับ QR check-in
 #[derive(Debug, Serialize, Deserialize)]
@@ -35,18 +52,51 @@ pub struct QrCheckInResponse {
     pub checked_in_at: DateTime<Utc>,
 }
 
-/// Request สำหรับ activity assignment
+/// Request สำหรับนักศึกษาที่สแกน QR ของกิจกรรมเพื่อ check-in ด้วยตัวเอง
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SelfCheckInRequest {
+    pub token: String,
+}
+
+/// Request สำหรับมอบหมาย admin ให้สแกน QR ของกิจกรรม
 #[derive(Debug, Serialize, Deserialize)]
-pub struct ActivityAssignmentRequest {
-    pub admin_id: Uuid,
-    pub activity_ids: Vec<Uuid>,
+pub struct ScannerAssignmentRequest {
+    pub user_id: Uuid,
+}
+
+/// Admin ที่ได้รับมอบหมายให้สแกน QR ของกิจกรรม
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct ActivityScanner {
+    pub user_id: Uuid,
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub assigned_by: Option<Uuid>,
+    pub assigned_at: DateTime<Utc>,
 }
 
 /// สร้าง QR code data สำหรับ user
+///
+/// รองรับ `?format=png|svg|json`; ถ้าไม่ระบุจะตอบกลับเป็น JSON แบบเดิม (มี qr_svg)
 pub async fn generate_user_qr(
     State(session_state): State<SessionState>,
     user: SessionUser,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    let format = match params.get("format") {
+        Some(value) => match QrOutputFormat::parse(value) {
+            Some(format) => Some(format),
+            None => {
+                let error_response = json!({
+                    "status": "error",
+                    "message": "Invalid format. Supported formats: png, svg, json"
+                });
+                return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+            }
+        },
+        None => None,
+    };
+
     // ดึงข้อมูล user และ qr_secret
     let user_data = sqlx::query_as::<_, User>(
         "SELECT * FROM users WHERE id = $1"
@@ -57,17 +107,64 @@ pub async fn generate_user_qr(
 
     match user_data {
         Ok(user_data) => {
+            // ผู้ใช้ที่ถูก anonymize แล้วไม่มี qr_secret
+            let Some(qr_secret) = user_data.qr_secret.as_deref() else {
+                let error_response = json!({
+                    "status": "error",
+                    "message": "QR code is not available for this account"
+                });
+                return Err((StatusCode::NOT_FOUND, Json(error_response)));
+            };
+
             match generate_client_qr_data(
                 &user_data.id,
                 &user_data.student_id,
-                &user_data.qr_secret
+                qr_secret
             ) {
                 Ok(qr_response) => {
+                    match format {
+                        Some(QrOutputFormat::Png) => {
+                            return match render_qr_png(&qr_response.qr_data, 256, EcLevel::M) {
+                                Ok(png) => Ok(([(header::CONTENT_TYPE, "image/png")], png).into_response()),
+                                Err(e) => {
+                                    let error_response = json!({
+                                        "status": "error",
+                                        "message": format!("Failed to render QR code: {}", e)
+                                    });
+                                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+                                }
+                            };
+                        }
+                        Some(QrOutputFormat::Svg) => {
+                            return match render_qr_svg(&qr_response.qr_data, 256) {
+                                Ok(svg) => Ok(([(header::CONTENT_TYPE, "image/svg+xml")], svg).into_response()),
+                                Err(e) => {
+                                    let error_response = json!({
+                                        "status": "error",
+                                        "message": format!("Failed to render QR code: {}", e)
+                                    });
+                                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+                                }
+                            };
+                        }
+                        Some(QrOutputFormat::Json) => {
+                            // Raw payload สำหรับ native client ที่ render QR เอง
+                            let response = json!({
+                                "status": "success",
+                                "data": {
+                                    "payload": qr_response.qr_data,
+                                    "version": QR_PAYLOAD_VERSION,
+                                    "expires_at": qr_response.expires_at
+                                },
+                                "message": "QR payload generated successfully"
+                            });
+                            return Ok(Json(response).into_response());
+                        }
+                        None => {}
+                    }
+
                     // Render SVG for client to display proper QR without client-side lib
-                    let qr_svg = match crate::utils::qr::render_qr_svg(&qr_response.qr_data, 256) {
-                        Ok(svg) => Some(svg),
-                        Err(_) => None,
-                    };
+                    let qr_svg = render_qr_svg(&qr_response.qr_data, 256).ok();
                     let qr_id = uuid::Uuid::new_v4();
                     let response = json!({
                         "status": "success",
@@ -89,7 +186,7 @@ pub async fn generate_user_qr(
                         },
                         "message": "QR code generated successfully"
                     });
-                    Ok(Json(response))
+                    Ok(Json(response).into_response())
                 }
                 Err(e) => {
                     let error_response = json!({
@@ -153,13 +250,13 @@ pub async fn refresh_qr_secret(
 /// QR check-in endpoint สำหรับ admins
 pub async fn qr_checkin(
     State(session_state): State<SessionState>,
-    admin: AdminUser, // Only admins can scan QR codes
+    admin: AdminUser, // Only assigned scanners can scan QR codes
     Path(activity_id): Path<Uuid>,
-    Json(request): Json<QrCheckInRequest>,
+    ApiJson(request): ApiJson<QrCheckInRequest>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     // ตรวจสอบว่า activity มีอยู่จริงและมีสถานะเป็น 'ongoing'
     let activity_check = sqlx::query(
-        "SELECT id, status FROM activities WHERE id = $1"
+        "SELECT id, status FROM activities WHERE id = $1 AND deleted_at IS NULL"
     )
     .bind(&activity_id)
     .fetch_optional(&session_state.db_pool)
@@ -202,6 +299,10 @@ pub async fn qr_checkin(
         }
     }
 
+    // ตรวจสอบว่า admin ได้รับมอบหมายให้สแกนกิจกรรมนี้ และอยู่ในช่วงเวลาที่เปิดให้ check-in
+    ensure_scanner_assigned(&session_state, &admin, activity_id).await?;
+    ensure_checkin_window_open(&session_state, activity_id).await?;
+
     // Parse QR data
     let client_data: crate::utils::qr::ClientQrData = match serde_json::from_str(&request.qr_data) {
         Ok(data) => data,
@@ -240,8 +341,16 @@ pub async fn qr_checkin(
         }
     };
 
+    let Some(qr_secret) = user_data.qr_secret.as_deref() else {
+        let error_response = json!({
+            "status": "error",
+            "message": "User not found in QR code"
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+
     // ตรวจสอบ QR code validity
-    let validation_result = validate_client_qr_data(&request.qr_data, &user_data.qr_secret, 300); // 5 minutes
+    let validation_result = validate_client_qr_data(&request.qr_data, qr_secret, 300); // 5 minutes
     
     if !validation_result.is_valid {
         let error_response = json!({
@@ -253,7 +362,7 @@ pub async fn qr_checkin(
 
     // ตรวจสอบว่า user ลงทะเบียนกิจกรรมนี้แล้วหรือยัง
     let existing_participation = sqlx::query(
-        "SELECT id, status FROM participations WHERE user_id = $1 AND activity_id = $2"
+        "SELECT id, status::text as status FROM participations WHERE user_id = $1 AND activity_id = $2"
     )
     .bind(&client_data.user_id)
     .bind(&activity_id)
@@ -272,17 +381,38 @@ pub async fn qr_checkin(
                 });
                 return Err((StatusCode::CONFLICT, Json(error_response)));
             }
+            // Waitlisted and cancelled participations hold no seat; they get one through
+            // registration or an admin status change, which check capacity first
+            if status == "waitlisted" || status == "cancelled" {
+                return Err(ApiError::conflict(
+                    "no_seat",
+                    format!("User is {} for this activity and has no seat", status),
+                )
+                .into());
+            }
+            // Moving back to checked_in would leave the credited hours in place
+            if status == "completed" {
+                return Err(ApiError::conflict(
+                    "participation_completed",
+                    "User has already completed this activity",
+                )
+                .into());
+            }
 
+            let participation_id: Uuid = participation.get("id");
             let update_result = sqlx::query(
                 "UPDATE participations SET status = 'checked_in', checked_in_at = NOW() WHERE id = $1 RETURNING checked_in_at"
             )
-            .bind(participation.get::<Uuid, _>("id"))
+            .bind(participation_id)
             .fetch_one(&session_state.db_pool)
             .await;
 
             match update_result {
                 Ok(updated_participation) => {
                     let checked_in_at: DateTime<Utc> = updated_participation.get("checked_in_at");
+                    let data = participation_event_data(participation_id, activity_id, client_data.user_id, "qr_checkin");
+                    enqueue_webhook_event(&session_state, WebhookEvent::ParticipationCheckedIn, data).await;
+                    notify_participation_scan(&session_state, activity_id, client_data.user_id, false);
                     
                     let response_data = QrCheckInResponse {
                         success: true,
@@ -317,7 +447,7 @@ pub async fn qr_checkin(
                 r#"
                 INSERT INTO participations (user_id, activity_id, status, checked_in_at)
                 VALUES ($1, $2, 'checked_in', NOW())
-                RETURNING checked_in_at
+                RETURNING id, checked_in_at
                 "#
             )
             .bind(&client_data.user_id)
@@ -328,6 +458,9 @@ pub async fn qr_checkin(
             match create_result {
                 Ok(new_participation) => {
                     let checked_in_at: DateTime<Utc> = new_participation.get("checked_in_at");
+                    let data = participation_event_data(new_participation.get("id"), activity_id, client_data.user_id, "qr_checkin");
+                    enqueue_webhook_event(&session_state, WebhookEvent::ParticipationCheckedIn, data).await;
+                    notify_participation_scan(&session_state, activity_id, client_data.user_id, false);
 
                     let response_data = QrCheckInResponse {
                         success: true,
@@ -372,60 +505,63 @@ pub async fn get_assigned_activities(
     admin: AdminUser,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     // ตรวจสอบสิทธิ์ - ถ้าเป็น SuperAdmin ให้ดูได้ทั้งหมด
-    let is_super_admin = admin.session_user.permissions.iter()
-        .any(|p| p.contains("SuperAdmin") || p.contains("ManageAllActivities"));
+    let is_super_admin = admin.admin_role.admin_level == AdminLevel::SuperAdmin;
+    let time_zone = session_state.app_config.activity_time_zone();
+    let (start_time, end_time) = (time_zone.start_sql("a"), time_zone.end_sql("a"));
 
     let activities_result = if is_super_admin {
         // SuperAdmin ดูได้ทั้งหมด
-        sqlx::query(
+        sqlx::query(&format!(
             r#"
             SELECT 
                 a.id,
                 a.title,
                 a.description,
                 a.location,
-                ((a.start_date::timestamp + a.start_time_only) AT TIME ZONE 'UTC') as start_time,
-                ((a.end_date::timestamp + a.end_time_only) AT TIME ZONE 'UTC') as end_time,
+                {start_time} as start_time,
+                {end_time} as end_time,
                 a.status,
                 a.max_participants,
                 f.name as faculty_name,
-                COUNT(p.id) as current_participants
+                COUNT(p.id) FILTER (WHERE p.status NOT IN ('waitlisted', 'cancelled')) as current_participants
             FROM activities a
             LEFT JOIN faculties f ON a.faculty_id = f.id
             LEFT JOIN participations p ON a.id = p.activity_id
-            WHERE a.status = 'ongoing'
+            WHERE a.status = 'ongoing' AND a.deleted_at IS NULL
             GROUP BY a.id, a.title, a.description, a.location, a.start_date, a.end_date, a.start_time_only, a.end_time_only, a.status, a.max_participants, f.name
             ORDER BY a.start_date ASC, a.start_time_only ASC
             "#
-        )
+        ))
         .fetch_all(&session_state.db_pool)
         .await
     } else {
-        // Regular admin ดูเฉพาะที่สร้างเอง หรือในขอบเขตที่มีสิทธิ์
-        sqlx::query(
+        // Admin อื่นดูเฉพาะกิจกรรมที่สร้างเอง หรือได้รับมอบหมายให้สแกน
+        sqlx::query(&format!(
             r#"
             SELECT 
                 a.id,
                 a.title,
                 a.description,
                 a.location,
-                ((a.start_date::timestamp + a.start_time_only) AT TIME ZONE 'UTC') as start_time,
-                ((a.end_date::timestamp + a.end_time_only) AT TIME ZONE 'UTC') as end_time,
+                {start_time} as start_time,
+                {end_time} as end_time,
                 a.status,
                 a.max_participants,
                 f.name as faculty_name,
-                COUNT(p.id) as current_participants
+                COUNT(p.id) FILTER (WHERE p.status NOT IN ('waitlisted', 'cancelled')) as current_participants
             FROM activities a
             LEFT JOIN faculties f ON a.faculty_id = f.id
             LEFT JOIN participations p ON a.id = p.activity_id
-            WHERE a.status = 'ongoing'
-            AND (a.created_by = $1 OR a.faculty_id = $2)
+            WHERE a.status = 'ongoing' AND a.deleted_at IS NULL
+            AND (
+                a.created_by = $1
+                OR EXISTS (SELECT 1 FROM activity_scanners s WHERE s.activity_id = a.id AND s.user_id = $1)
+            )
             GROUP BY a.id, a.title, a.description, a.location, a.start_date, a.end_date, a.start_time_only, a.end_time_only, a.status, a.max_participants, f.name
             ORDER BY a.start_date ASC, a.start_time_only ASC
             "#
-        )
+        ))
         .bind(admin.session_user.user_id)
-        .bind(admin.admin_role.faculty_id.unwrap_or(Uuid::nil())) // Use nil UUID if no faculty
         .fetch_all(&session_state.db_pool)
         .await
     };
@@ -468,3 +604,500 @@ pub async fn get_assigned_activities(
         }
     }
 }
+
+/// Only the activity's creator, SuperAdmins or admins with ManageActivities may change its scanners
+async fn ensure_can_manage_scanners(
+    session_state: &SessionState,
+    admin: &AdminUser,
+    activity_id: Uuid,
+) -> ApiResult<()> {
+    let created_by = sqlx::query_scalar::<_, Uuid>("SELECT created_by FROM activities WHERE id = $1 AND deleted_at IS NULL")
+        .bind(activity_id)
+        .fetch_optional(&session_state.db_pool)
+        .await
+        .map_err(|_| ApiError::internal("Failed to check activity"))?
+        .ok_or_else(|| ApiError::not_found("activity_not_found", "Activity not found"))?;
+
+    let can_manage = created_by == admin.session_user.user_id
+        || admin.admin_role.admin_level == AdminLevel::SuperAdmin
+        || admin.session_user.has_permission(&Permission::ManageActivities);
+
+    if !can_manage {
+        return Err(ApiError::forbidden(
+            "permission_denied",
+            "Access denied: only the activity creator or admins with ManageActivities can manage scanners",
+        ));
+    }
+
+    Ok(())
+}
+
+/// ดูรายชื่อ admin ที่ได้รับมอบหมายให้สแกน QR ของกิจกรรม
+pub async fn get_activity_scanners(
+    State(session_state): State<SessionState>,
+    admin: AdminUser,
+    Path(activity_id): Path<Uuid>,
+) -> ApiResult<Json<Value>> {
+    ensure_can_manage_scanners(&session_state, &admin, activity_id).await?;
+
+    let scanners = sqlx::query_as::<_, ActivityScanner>(
+        r#"
+        SELECT s.user_id, u.email, u.first_name, u.last_name, s.assigned_by, s.assigned_at
+        FROM activity_scanners s
+        JOIN users u ON u.id = s.user_id
+        WHERE s.activity_id = $1
+        ORDER BY s.assigned_at ASC
+        "#,
+    )
+    .bind(activity_id)
+    .fetch_all(&session_state.db_pool)
+    .await
+    .map_err(|_| ApiError::internal("Failed to retrieve scanners"))?;
+
+    let response = json!({
+        "status": "success",
+        "data": {
+            "scanners": scanners,
+            "total_count": scanners.len()
+        },
+        "message": "Scanners retrieved successfully"
+    });
+    Ok(Json(response))
+}
+
+/// มอบหมาย admin ให้สแกน QR ของกิจกรรม
+pub async fn assign_activity_scanner(
+    State(session_state): State<SessionState>,
+    admin: AdminUser,
+    Path(activity_id): Path<Uuid>,
+    ApiJson(request): ApiJson<ScannerAssignmentRequest>,
+) -> ApiResult<Json<Value>> {
+    ensure_can_manage_scanners(&session_state, &admin, activity_id).await?;
+
+    // ต้องเป็น admin ที่ยังเปิดใช้งานอยู่เท่านั้นถึงจะสแกนได้
+    let is_admin = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS (SELECT 1 FROM admin_roles WHERE user_id = $1 AND is_enabled = TRUE)",
+    )
+    .bind(request.user_id)
+    .fetch_one(&session_state.db_pool)
+    .await
+    .map_err(|_| ApiError::internal("Failed to check admin role"))?;
+
+    if !is_admin {
+        return Err(ApiError::bad_request(
+            "scanner_not_admin",
+            "Only enabled admins can be assigned as scanners",
+        ));
+    }
+
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO activity_scanners (activity_id, user_id, assigned_by)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (activity_id, user_id) DO NOTHING
+        "#,
+    )
+    .bind(activity_id)
+    .bind(request.user_id)
+    .bind(admin.session_user.user_id)
+    .execute(&session_state.db_pool)
+    .await
+    .map_err(|_| ApiError::internal("Failed to assign scanner"))?
+    .rows_affected()
+        > 0;
+
+    if inserted {
+        record_audit_log(
+            &session_state.db_pool,
+            NewAuditLog::new(admin.session_user.user_id, "assign_scanner", "activity", activity_id)
+                .details(json!({ "scanner_user_id": request.user_id })),
+        )
+        .await;
+    }
+
+    let response = json!({
+        "status": "success",
+        "data": {
+            "activity_id": activity_id,
+            "user_id": request.user_id
+        },
+        "message": if inserted { "Scanner assigned successfully" } else { "Scanner was already assigned" }
+    });
+    Ok(Json(response))
+}
+
+/// ยกเลิกการมอบหมาย admin ให้สแกน QR ของกิจกรรม
+pub async fn unassign_activity_scanner(
+    State(session_state): State<SessionState>,
+    admin: AdminUser,
+    Path((activity_id, user_id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<Json<Value>> {
+    ensure_can_manage_scanners(&session_state, &admin, activity_id).await?;
+
+    let deleted = sqlx::query("DELETE FROM activity_scanners WHERE activity_id = $1 AND user_id = $2")
+        .bind(activity_id)
+        .bind(user_id)
+        .execute(&session_state.db_pool)
+        .await
+        .map_err(|_| ApiError::internal("Failed to unassign scanner"))?;
+
+    if deleted.rows_affected() == 0 {
+        return Err(ApiError::not_found(
+            "scanner_not_found",
+            "This admin is not assigned to the activity",
+        ));
+    }
+
+    record_audit_log(
+        &session_state.db_pool,
+        NewAuditLog::new(admin.session_user.user_id, "unassign_scanner", "activity", activity_id)
+            .details(json!({ "scanner_user_id": user_id })),
+    )
+    .await;
+
+    let response = json!({
+        "status": "success",
+        "message": "Scanner unassigned successfully"
+    });
+    Ok(Json(response))
+}
+
+/// สร้าง QR ของกิจกรรมสำหรับแสดงบน kiosk ให้นักศึกษาสแกน check-in ด้วยตัวเอง
+///
+/// รองรับ `?format=png|json` (ค่าเริ่มต้น json); token มีอายุสั้น kiosk ต้องขอใหม่เรื่อย ๆ
+pub async fn get_activity_checkin_qr(
+    State(session_state): State<SessionState>,
+    admin: AdminUser,
+    Path(activity_id): Path<Uuid>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ApiResult<Response> {
+    let format = match params.get("format").map(|value| QrOutputFormat::parse(value)) {
+        None | Some(Some(QrOutputFormat::Json)) => QrOutputFormat::Json,
+        Some(Some(QrOutputFormat::Png)) => QrOutputFormat::Png,
+        _ => {
+            return Err(ApiError::bad_request(
+                "unsupported_format",
+                "Invalid format. Supported formats: png, json",
+            ));
+        }
+    };
+
+    let exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS (SELECT 1 FROM activities WHERE id = $1 AND deleted_at IS NULL)")
+        .bind(activity_id)
+        .fetch_one(&session_state.db_pool)
+        .await
+        .map_err(|_| ApiError::internal("Failed to check activity"))?;
+    if !exists {
+        return Err(ApiError::not_found("activity_not_found", "Activity not found"));
+    }
+
+    // ผู้ที่เปิด kiosk ต้องมีสิทธิ์สแกนกิจกรรมนี้เหมือนการสแกน QR ของนักศึกษา
+    ensure_scanner_assigned(&session_state, &admin, activity_id).await?;
+
+    let token = generate_activity_checkin_token(
+        &activity_id,
+        &session_state.app_config.qr_signing_secret,
+        ACTIVITY_CHECKIN_TTL_SECONDS,
+    )
+    .map_err(|_| ApiError::internal("Failed to generate check-in token"))?;
+
+    if format == QrOutputFormat::Png {
+        let png = render_qr_png(&token.qr_data, 512, EcLevel::M)
+            .map_err(|_| ApiError::internal("Failed to render QR code"))?;
+        return Ok((
+            [
+                (header::CONTENT_TYPE, "image/png"),
+                (header::CACHE_CONTROL, "no-store"),
+            ],
+            png,
+        )
+            .into_response());
+    }
+
+    let response = json!({
+        "status": "success",
+        "data": {
+            "activity_id": activity_id,
+            "token": token.qr_data,
+            "expires_at": token.expires_at
+        },
+        "message": "Check-in token generated successfully"
+    });
+    Ok(Json(response).into_response())
+}
+
+/// นักศึกษาสแกน QR ของกิจกรรมเพื่อ check-in/check-out ด้วยตัวเอง
+pub async fn self_checkin(
+    State(session_state): State<SessionState>,
+    user: SessionUser,
+    Path(activity_id): Path<Uuid>,
+    ApiJson(request): ApiJson<SelfCheckInRequest>,
+) -> ApiResult<Json<Value>> {
+    let token = parse_activity_checkin_token(&request.token)
+        .ok_or_else(|| ApiError::bad_request("invalid_qr_code", "Invalid QR code format"))?;
+    token
+        .verify(&activity_id, &session_state.app_config.qr_signing_secret)
+        .map_err(|e| ApiError::bad_request("invalid_qr_code", e.to_string()))?;
+
+    ensure_checkin_window_open(&session_state, activity_id).await?;
+
+    let participation = sqlx::query(
+        "SELECT id, status::text as status FROM participations WHERE user_id = $1 AND activity_id = $2",
+    )
+    .bind(user.user_id)
+    .bind(activity_id)
+    .fetch_optional(&session_state.db_pool)
+    .await
+    .map_err(|_| ApiError::internal("Failed to check participation"))?
+    .ok_or_else(|| ApiError::bad_request("not_registered", "You are not registered for this activity"))?;
+
+    let status: String = participation.get("status");
+    // Completion credits hours, so it is left to staff scans and the status updater
+    if status != "registered" && status != "checked_in" {
+        return Err(ApiError::bad_request(
+            "invalid_participation_status",
+            format!("Self check-in is not available once you are {}", status.replace('_', " ")),
+        ));
+    }
+    ensure_scan_not_repeated(&session_state, activity_id, user.user_id, &status).await?;
+    let new_status =
+        advance_scanned_participation(&session_state, participation.get::<Uuid, _>("id"), &status, "self_checkin").await?;
+
+    let response = json!({
+        "status": "success",
+        "data": {
+            "activity_id": activity_id,
+            "participation_status": scanned_participation_status(new_status)
+        },
+        "message": format!("Successfully {} for activity", new_status.replace('_', " "))
+    });
+    Ok(Json(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::SessionConfig;
+    use crate::test_support::{insert_user, test_state};
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_self_checkin_with_activity_token() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = SessionState {
+            // Successive scans below would otherwise hit the cooldown
+            config: SessionConfig {
+                scan_cooldown_seconds: 0,
+                ..SessionConfig::default()
+            },
+            ..test_state(&pool)
+        };
+
+        let tag = Uuid::new_v4().simple().to_string()[..8].to_string();
+        let student_id = format!("self{}", tag);
+        let user_id = insert_user(&pool, &student_id, None).await;
+
+        // Started 10 minutes ago and ends in an hour, so the check-in window is open
+        let activity_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO activities (title, description, location, academic_year, organizer,
+                start_date, end_date, start_time_only, end_time_only, hours, status, created_by)
+             SELECT 'Kiosk', 'Kiosk test', 'Hall', '2567', 'Test', s::date, e::date,
+                s::time, e::time, 1, 'ongoing', $1
+             FROM (SELECT (NOW() AT TIME ZONE 'UTC') - INTERVAL '10 minutes' AS s,
+                          (NOW() AT TIME ZONE 'UTC') + INTERVAL '1 hour' AS e) t
+             RETURNING id",
+        )
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        let user = SessionUser {
+            user_id,
+            student_id: student_id.clone(),
+            email: format!("{}@test.local", student_id),
+            first_name: "Test".to_string(),
+            last_name: "Student".to_string(),
+            department_id: None,
+            admin_role: None,
+            session_id: Uuid::new_v4().to_string(),
+            permissions: Vec::new(),
+            faculty_id: None,
+            is_impersonated: false,
+            impersonated_by: None,
+        };
+        let token = generate_activity_checkin_token(&activity_id, &state.app_config.qr_signing_secret, 60)
+            .unwrap()
+            .qr_data;
+        let checkin = |token: String| {
+            self_checkin(
+                State(state.clone()),
+                user.clone(),
+                Path(activity_id),
+                ApiJson(SelfCheckInRequest { token }),
+            )
+        };
+
+        let unregistered = checkin(token.clone()).await;
+
+        sqlx::query("INSERT INTO participations (user_id, activity_id, status) VALUES ($1, $2, 'registered')")
+            .bind(user_id)
+            .bind(activity_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let tampered = checkin(token.replace("\"expires_at\":", "\"expires_at\":1")).await;
+        let checked_in = checkin(token.clone()).await;
+        let checked_out = checkin(token.clone()).await;
+        let completed = checkin(token).await;
+        let status: String = sqlx::query_scalar(
+            "SELECT status::text FROM participations WHERE user_id = $1 AND activity_id = $2",
+        )
+        .bind(user_id)
+        .bind(activity_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        match unregistered {
+            Err(ApiError::BadRequest(detail)) => assert_eq!(detail.code, "not_registered"),
+            other => panic!("expected 400 for an unregistered student, got {:?}", other.map(|j| j.0)),
+        }
+        match tampered {
+            Err(ApiError::BadRequest(detail)) => assert_eq!(detail.code, "invalid_qr_code"),
+            other => panic!("expected 400 for a tampered token, got {:?}", other.map(|j| j.0)),
+        }
+        let Json(body) = checked_in.unwrap();
+        assert_eq!(body["data"]["participation_status"], "checked_in");
+        let Json(body) = checked_out.unwrap();
+        assert_eq!(body["data"]["participation_status"], "checked_out");
+        match completed {
+            Err(ApiError::BadRequest(detail)) => assert_eq!(detail.code, "invalid_participation_status"),
+            other => panic!("expected 400 for a self-completion, got {:?}", other.map(|j| j.0)),
+        }
+        assert_eq!(status, "checked_out");
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_qr_checkin_refuses_seatless_and_completed_participations() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let tag = Uuid::new_v4().simple().to_string()[..8].to_string();
+        let mut students = Vec::new();
+        for status in ["registered", "waitlisted", "cancelled", "completed"] {
+            let student_id = format!("{}{}", &status[..4], tag);
+            let user_id = insert_user(&pool, &student_id, None).await;
+            let qr_secret: String = sqlx::query_scalar("SELECT qr_secret FROM users WHERE id = $1")
+                .bind(user_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            students.push((status, user_id, student_id, qr_secret));
+        }
+        let admin_id = students[0].1;
+
+        let activity_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO activities (title, description, location, academic_year, organizer,
+                start_date, end_date, start_time_only, end_time_only, hours, status, created_by)
+             SELECT 'Gate', 'Gate test', 'Hall', '2567', 'Test', s::date, e::date,
+                s::time, e::time, 1, 'ongoing', $1
+             FROM (SELECT (NOW() AT TIME ZONE 'UTC') - INTERVAL '10 minutes' AS s,
+                          (NOW() AT TIME ZONE 'UTC') + INTERVAL '1 hour' AS e) t
+             RETURNING id",
+        )
+        .bind(admin_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        for (status, user_id, _, _) in &students {
+            sqlx::query(
+                "INSERT INTO participations (user_id, activity_id, status) VALUES ($1, $2, $3::participation_status)",
+            )
+            .bind(user_id)
+            .bind(activity_id)
+            .bind(status)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let admin = || AdminUser {
+            session_user: SessionUser {
+                user_id: admin_id,
+                student_id: "admin".to_string(),
+                email: "admin@test.local".to_string(),
+                first_name: "Test".to_string(),
+                last_name: "Admin".to_string(),
+                department_id: None,
+                admin_role: None,
+                session_id: Uuid::new_v4().to_string(),
+                permissions: Vec::new(),
+                faculty_id: None,
+                is_impersonated: false,
+                impersonated_by: None,
+            },
+            admin_role: crate::models::admin_role::AdminRole {
+                id: Uuid::new_v4(),
+                user_id: admin_id,
+                admin_level: AdminLevel::SuperAdmin,
+                faculty_id: None,
+                permissions: Vec::new(),
+                is_enabled: true,
+                created_at: None,
+                updated_at: None,
+            },
+        };
+        let mut results = Vec::new();
+        for (_, user_id, student_id, qr_secret) in &students {
+            let qr_data = generate_client_qr_data(user_id, student_id, qr_secret).unwrap().qr_data;
+            let request = ApiJson(QrCheckInRequest { qr_data });
+            results.push(qr_checkin(State(state.clone()), admin(), Path(activity_id), request).await);
+        }
+        let statuses: Vec<String> = sqlx::query_scalar(
+            "SELECT p.status::text FROM participations p JOIN users u ON u.id = p.user_id
+             WHERE p.activity_id = $1 ORDER BY u.student_id",
+        )
+        .bind(activity_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+
+        sqlx::query("DELETE FROM activities WHERE id = $1")
+            .bind(activity_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        for (_, user_id, _, _) in &students {
+            sqlx::query("DELETE FROM users WHERE id = $1")
+                .bind(user_id)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        assert!(results[0].is_ok());
+        for (result, code) in results[1..].iter().zip(["no_seat", "no_seat", "participation_completed"]) {
+            match result {
+                Err((status, Json(body))) => {
+                    assert_eq!(*status, StatusCode::CONFLICT);
+                    assert_eq!(body["code"], code);
+                }
+                Ok(Json(body)) => panic!("expected 409 {}, got {}", code, body),
+            }
+        }
+        // Ordered by student_id: canc…, comp…, regi…, wait…
+        assert_eq!(statuses, vec!["cancelled", "completed", "checked_in", "waitlisted"]);
+    }
+}
@@ -0,0 +1,447 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use tower_cookies::Cookies;
+use uuid::Uuid;
+
+use crate::error::{ApiError, ApiJson, ApiResult};
+use crate::handlers::auth::{
+    check_faculty_is_active, ensure_account_active, finish_admin_login, get_user_admin_role, get_user_by_id,
+    LoginResponse,
+};
+use crate::middleware::session::{AdminUser, SessionState};
+use crate::models::audit_log::NewAuditLog;
+use crate::services::audit_log::record_audit_log;
+use crate::services::two_factor::{self, MAX_CHALLENGE_ATTEMPTS};
+use crate::utils::qr::render_qr_svg;
+use crate::utils::rate_limit::rate_limited;
+use crate::utils::totp::otpauth_uri;
+
+const TOTP_ISSUER: &str = "Trackivity";
+/// Lockout bucket for step-up checks; they are tied to the admin, not the client IP
+const STEP_UP_SCOPE: &str = "step-up";
+
+/// Admin login that passed the password step and waits for the second factor
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct PendingAdminLogin {
+    pub user_id: Uuid,
+    pub remember_me: bool,
+    pub ip_address: Option<String>,
+    pub user_agent: String,
+    pub device_info: HashMap<String, Value>,
+    /// Wrong codes entered so far for this login
+    pub attempts: u32,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TwoFactorCodeRequest {
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TwoFactorVerifyRequest {
+    pub challenge_token: String,
+    pub code: String,
+}
+
+/// Store the pending login for whatever is left of its lifetime and return the challenge token
+pub(crate) async fn issue_login_challenge(
+    session_state: &SessionState,
+    login: &PendingAdminLogin,
+) -> anyhow::Result<String> {
+    let ttl_seconds = (login.expires_at - Utc::now()).num_seconds().max(1) as u64;
+    session_state
+        .session_store
+        .create_two_factor_challenge(&serde_json::to_value(login)?, ttl_seconds)
+        .await
+}
+
+// GET /api/admin/auth/2fa
+pub async fn get_two_factor_status(
+    State(session_state): State<SessionState>,
+    admin: AdminUser,
+) -> ApiResult<Json<Value>> {
+    let user_id = admin.session_user.user_id;
+    let enabled = two_factor::is_two_factor_enabled(&session_state.db_pool, user_id)
+        .await
+        .map_err(|_| ApiError::internal("Failed to load two-factor status"))?;
+    let recovery_codes_remaining = if enabled {
+        two_factor::recovery_codes_remaining(&session_state.db_pool, user_id)
+            .await
+            .map_err(|_| ApiError::internal("Failed to load two-factor status"))?
+    } else {
+        0
+    };
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {
+            "enabled": enabled,
+            "recovery_codes_remaining": recovery_codes_remaining,
+        }
+    })))
+}
+
+// POST /api/admin/auth/2fa/enroll - new secret to scan; 2FA stays off until confirmed
+pub async fn enroll_two_factor(
+    State(session_state): State<SessionState>,
+    admin: AdminUser,
+) -> ApiResult<Json<Value>> {
+    let secret = two_factor::begin_enrollment(&session_state.db_pool, admin.session_user.user_id)
+        .await
+        .map_err(|_| ApiError::internal("Failed to start two-factor enrollment"))?
+        .ok_or_else(|| {
+            ApiError::conflict("two_factor_already_enabled", "Two-factor authentication is already enabled")
+        })?;
+
+    let uri = otpauth_uri(TOTP_ISSUER, &admin.session_user.email, &secret);
+    let qr_svg = render_qr_svg(&uri, 240).map_err(|_| ApiError::internal("Failed to render QR code"))?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {
+            "secret": secret,
+            "otpauth_uri": uri,
+            "qr_svg": qr_svg,
+        }
+    })))
+}
+
+// POST /api/admin/auth/2fa/confirm - first valid code enables 2FA and returns the recovery codes
+pub async fn confirm_two_factor(
+    State(session_state): State<SessionState>,
+    admin: AdminUser,
+    ApiJson(request): ApiJson<TwoFactorCodeRequest>,
+) -> ApiResult<Json<Value>> {
+    let user_id = admin.session_user.user_id;
+    let recovery_codes = two_factor::confirm_enrollment(&session_state.db_pool, user_id, &request.code)
+        .await
+        .map_err(|_| ApiError::internal("Failed to confirm two-factor enrollment"))?
+        .ok_or_else(|| {
+            ApiError::bad_request(
+                "invalid_two_factor_code",
+                "Invalid code or no pending two-factor enrollment",
+            )
+        })?;
+
+    record_audit_log(
+        &session_state.db_pool,
+        NewAuditLog::new(user_id, "enable_two_factor", "user", user_id),
+    )
+    .await;
+
+    Ok(Json(json!({
+        "status": "success",
+        "message": "Two-factor authentication enabled",
+        "data": { "recovery_codes": recovery_codes }
+    })))
+}
+
+// POST /api/admin/auth/2fa/recovery-codes - replaces every unused recovery code
+pub async fn regenerate_recovery_codes(
+    State(session_state): State<SessionState>,
+    admin: AdminUser,
+    ApiJson(request): ApiJson<TwoFactorCodeRequest>,
+) -> Result<Json<Value>, Response> {
+    let user_id = admin.session_user.user_id;
+    require_second_factor(&session_state, user_id, &request.code).await?;
+
+    let recovery_codes = two_factor::regenerate_recovery_codes(&session_state.db_pool, user_id)
+        .await
+        .map_err(|_| ApiError::internal("Failed to regenerate recovery codes").into_response())?;
+
+    record_audit_log(
+        &session_state.db_pool,
+        NewAuditLog::new(user_id, "regenerate_recovery_codes", "user", user_id),
+    )
+    .await;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": { "recovery_codes": recovery_codes }
+    })))
+}
+
+// POST /api/admin/auth/2fa/disable
+pub async fn disable_two_factor(
+    State(session_state): State<SessionState>,
+    admin: AdminUser,
+    ApiJson(request): ApiJson<TwoFactorCodeRequest>,
+) -> Result<Json<Value>, Response> {
+    let user_id = admin.session_user.user_id;
+    require_second_factor(&session_state, user_id, &request.code).await?;
+
+    two_factor::disable_two_factor(&session_state.db_pool, user_id)
+        .await
+        .map_err(|_| ApiError::internal("Failed to disable two-factor authentication").into_response())?;
+
+    record_audit_log(
+        &session_state.db_pool,
+        NewAuditLog::new(user_id, "disable_two_factor", "user", user_id),
+    )
+    .await;
+
+    Ok(Json(json!({
+        "status": "success",
+        "message": "Two-factor authentication disabled"
+    })))
+}
+
+// POST /api/admin/auth/2fa/verify - second step of admin login (no session yet)
+pub async fn verify_two_factor_login(
+    State(session_state): State<SessionState>,
+    cookies: Cookies,
+    ApiJson(request): ApiJson<TwoFactorVerifyRequest>,
+) -> Result<Json<LoginResponse>, Response> {
+    let pending = session_state
+        .session_store
+        .consume_two_factor_challenge(&request.challenge_token)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?
+        .and_then(|value| serde_json::from_value::<PendingAdminLogin>(value).ok())
+        .filter(|login| login.expires_at > Utc::now())
+        .ok_or_else(|| {
+            ApiError::unauthorized(
+                "two_factor_challenge_expired",
+                "Two-factor challenge is invalid or has expired; log in again",
+            )
+            .into_response()
+        })?;
+
+    let verified = two_factor::verify_second_factor(&session_state.db_pool, pending.user_id, &request.code)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    if verified.is_none() {
+        let attempts = pending.attempts + 1;
+        if attempts >= MAX_CHALLENGE_ATTEMPTS {
+            return Err(ApiError::unauthorized(
+                "two_factor_attempts_exceeded",
+                "Too many invalid codes; log in again",
+            )
+            .into_response());
+        }
+        // The consumed challenge is replaced so the admin can retry without re-entering the password
+        let challenge_token = issue_login_challenge(&session_state, &PendingAdminLogin { attempts, ..pending })
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+        return Err(ApiError::unauthorized("invalid_two_factor_code", "Invalid two-factor code")
+            .with("challenge_token", json!(challenge_token))
+            .with("attempts_remaining", json!(MAX_CHALLENGE_ATTEMPTS - attempts))
+            .into_response());
+    }
+
+    // The account may have changed while the challenge was open
+    let user = get_user_by_id(&session_state, pending.user_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?
+        .ok_or_else(|| StatusCode::UNAUTHORIZED.into_response())?;
+    ensure_account_active(&user).map_err(IntoResponse::into_response)?;
+
+    let admin_role = get_user_admin_role(&session_state, user.id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+    let Some(role) = admin_role.as_ref() else {
+        return Ok(Json(LoginResponse {
+            success: false,
+            session: None,
+            message: "Access denied. Admin privileges required.".to_string(),
+            two_factor_challenge: None,
+        }));
+    };
+    if role.faculty_id.is_some()
+        && !check_faculty_is_active(&session_state, role.faculty_id)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?
+    {
+        return Ok(Json(LoginResponse {
+            success: false,
+            session: None,
+            message: "Access denied. Your faculty is currently inactive.".to_string(),
+            two_factor_challenge: None,
+        }));
+    }
+
+    finish_admin_login(&session_state, &cookies, &user, admin_role, pending).await
+}
+
+/// Sensitive 2FA changes need a current TOTP or recovery code, not just the session.
+/// Wrong codes count against the admin the same way login challenges do, so a
+/// hijacked session cannot brute-force the six digits.
+async fn require_second_factor(session_state: &SessionState, user_id: Uuid, code: &str) -> Result<(), Response> {
+    let identifier = format!("2fa:{}", user_id);
+    match session_state
+        .session_store
+        .get_login_lockout(&identifier, STEP_UP_SCOPE)
+        .await
+    {
+        Ok(Some(retry_after)) => return Err(rate_limited(retry_after)),
+        Ok(None) => {}
+        Err(e) => tracing::warn!("Failed to check two-factor lockout: {}", e),
+    }
+
+    let verified = two_factor::verify_second_factor(&session_state.db_pool, user_id, code)
+        .await
+        .map_err(|_| ApiError::internal("Failed to verify two-factor code").into_response())?;
+    if verified.is_some() {
+        if let Err(e) = session_state
+            .session_store
+            .clear_failed_logins(&identifier, STEP_UP_SCOPE)
+            .await
+        {
+            tracing::warn!("Failed to clear two-factor attempts: {}", e);
+        }
+        return Ok(());
+    }
+
+    let config = &session_state.app_config;
+    match session_state
+        .session_store
+        .record_failed_login(
+            &identifier,
+            STEP_UP_SCOPE,
+            MAX_CHALLENGE_ATTEMPTS,
+            config.login_attempt_window_seconds,
+            config.login_lockout_seconds,
+        )
+        .await
+    {
+        Ok(Some(retry_after)) => Err(rate_limited(retry_after)),
+        Ok(None) => Err(ApiError::bad_request("invalid_two_factor_code", "Invalid two-factor code").into_response()),
+        Err(e) => {
+            tracing::warn!("Failed to record two-factor attempt: {}", e);
+            Err(ApiError::bad_request("invalid_two_factor_code", "Invalid two-factor code").into_response())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::auth::admin_login;
+    use crate::models::admin_role::AdminLevel;
+    use crate::models::session::SessionLoginRequest;
+    use crate::test_support::{insert_user, test_state};
+    use crate::utils::totp::{current_step, totp_code};
+    use crate::utils::PeerAddr;
+    use axum::http::HeaderMap;
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_admin_login_requires_second_factor_when_enabled() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let student_id = format!("2fa{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let email = format!("{}@test.local", student_id);
+        let user_id = insert_user(&pool, &student_id, Some(AdminLevel::SuperAdmin)).await;
+        sqlx::query("UPDATE users SET password_hash = $2 WHERE id = $1")
+            .bind(user_id)
+            .bind(bcrypt::hash("correct-password", 4).unwrap())
+            .execute(&pool)
+            .await
+            .unwrap();
+        let secret = two_factor::begin_enrollment(&pool, user_id).await.unwrap().unwrap();
+        let step = current_step(Utc::now().timestamp());
+        two_factor::confirm_enrollment(&pool, user_id, &totp_code(&secret, step - 1).unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+
+        let login = || {
+            admin_login(
+                State(state.clone()),
+                Cookies::default(),
+                HeaderMap::new(),
+                PeerAddr::default(),
+                ApiJson(SessionLoginRequest {
+                    email: email.clone(),
+                    password: "correct-password".to_string(),
+                    remember_me: None,
+                    device_info: None,
+                    device_verification_token: None,
+                }),
+            )
+        };
+        let verify = |challenge_token: String, code: String| {
+            verify_two_factor_login(
+                State(state.clone()),
+                Cookies::default(),
+                ApiJson(TwoFactorVerifyRequest { challenge_token, code }),
+            )
+        };
+
+        let Ok(Json(challenged)) = login().await else { panic!("login errored") };
+        let challenge = challenged.two_factor_challenge.clone().unwrap();
+
+        // A wrong code hands back a fresh challenge; the old token is spent
+        let wrong = verify(challenge.clone(), "000000".to_string()).await;
+        let reused = verify(challenge, totp_code(&secret, step).unwrap()).await;
+        let wrong_status = match &wrong {
+            Err(response) => response.status(),
+            Ok(_) => StatusCode::OK,
+        };
+        let Ok(Json(second)) = login().await else { panic!("login errored") };
+        let verified = verify(second.two_factor_challenge.clone().unwrap(), totp_code(&secret, step).unwrap()).await;
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(!challenged.success);
+        assert!(challenged.session.is_none());
+        assert_eq!(wrong_status, StatusCode::UNAUTHORIZED);
+        assert_eq!(reused.err().map(|r| r.status()), Some(StatusCode::UNAUTHORIZED));
+        let Ok(Json(verified)) = verified else { panic!("verification failed") };
+        assert!(verified.success);
+        assert_eq!(verified.session.unwrap().user.user_id, user_id);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_step_up_locks_out_after_repeated_wrong_codes() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let student_id = format!("2fs{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let user_id = insert_user(&pool, &student_id, None).await;
+        let secret = two_factor::begin_enrollment(&pool, user_id).await.unwrap().unwrap();
+        let step = current_step(Utc::now().timestamp());
+        two_factor::confirm_enrollment(&pool, user_id, &totp_code(&secret, step - 1).unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+
+        let mut statuses = Vec::new();
+        for _ in 0..MAX_CHALLENGE_ATTEMPTS {
+            let result = require_second_factor(&state, user_id, "000000").await;
+            statuses.push(result.err().map(|r| r.status()));
+        }
+        // Once locked, even the correct code is refused until the lockout expires
+        let locked = require_second_factor(&state, user_id, &totp_code(&secret, step).unwrap()).await;
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let last = statuses.pop().unwrap();
+        assert!(statuses.iter().all(|s| *s == Some(StatusCode::BAD_REQUEST)));
+        assert_eq!(last, Some(StatusCode::TOO_MANY_REQUESTS));
+        assert_eq!(locked.err().map(|r| r.status()), Some(StatusCode::TOO_MANY_REQUESTS));
+    }
+}
@@ -1,25 +1,37 @@
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
 };
-use bcrypt;
 use chrono::{DateTime, Utc};
 use qrcode::{EcLevel, QrCode};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use sqlx::Row;
 use std::collections::HashMap;
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 // use image::Luma; // Removed unused import
 use base64::{engine::general_purpose, Engine as _};
 
-use crate::middleware::session::{AdminUser, SessionState};
-use crate::models::session::SessionUser;
+use crate::error::{ApiError, ApiJson, ApiResult};
+use crate::middleware::session::{has_faculty_access, AdminUser, SessionState};
+use crate::models::session::{Permission, SessionUser};
 use crate::models::{
-    admin_role::AdminRole,
+    admin_role::{AdminLevel, AdminRole},
+    audit_log::NewAuditLog,
     user::{User, UserResponse, UserPrefix},
 };
+use crate::handlers::admin::{ensure_super_admin_remains, invalidate_dashboard_cache};
+use crate::handlers::auth::SessionInfo;
+use crate::services::audit_log::record_audit_log;
+use crate::utils::csv::{csv_attachment, push_csv_row};
+use crate::utils::ical::{build_calendar, ical_response, IcalEvent};
+use crate::utils::password_hash::{hash_password, verify_password, DISABLED_PASSWORD_HASH};
+use crate::utils::qr::{
+    generate_secret_key, generate_signed_qr_payload, parse_ec_level, render_qr_png,
+    SIGNED_QR_TTL_SECONDS,
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateUserRequest {
@@ -55,12 +67,61 @@ pub struct UserWithDetails {
     pub department_name: Option<String>,
     pub faculty_name: Option<String>,
     pub admin_role: Option<AdminRole>,
+    pub is_active: bool,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
     pub activity_count: i64,
     pub last_activity: Option<DateTime<Utc>>,
 }
 
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct HoursByActivityType {
+    pub activity_type: Option<String>,
+    pub total_hours: i64,
+    pub activity_count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct HoursByAcademicYear {
+    pub academic_year: String,
+    pub total_hours: i64,
+    pub activity_count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TranscriptEntry {
+    pub activity_id: Uuid,
+    pub title: String,
+    pub organizer: String,
+    pub activity_date: chrono::NaiveDate,
+    pub activity_type: Option<String>,
+    pub academic_year: String,
+    pub hours: i32,
+    pub completed_at: DateTime<Utc>,
+}
+
+/// One participation in a user data export, with the activity it belongs to
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ExportedParticipation {
+    pub participation_id: Uuid,
+    pub status: String,
+    pub registered_at: Option<DateTime<Utc>>,
+    pub checked_in_at: Option<DateTime<Utc>>,
+    pub checked_out_at: Option<DateTime<Utc>>,
+    pub notes: Option<String>,
+    pub hours_awarded: Option<i32>,
+    pub activity_id: Uuid,
+    pub activity_title: String,
+    pub activity_type: Option<String>,
+    pub location: String,
+    pub organizer: String,
+    pub academic_year: String,
+    pub start_date: chrono::NaiveDate,
+    pub end_date: chrono::NaiveDate,
+    pub start_time: chrono::NaiveTime,
+    pub end_time: chrono::NaiveTime,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct QrCodeResponse {
     pub qr_data: String,
@@ -99,6 +160,7 @@ pub async fn get_users(
             u.first_name,
             u.last_name,
             u.department_id,
+            u.is_active,
             u.created_at,
             u.updated_at,
             d.name as department_name,
@@ -144,7 +206,7 @@ pub async fn get_users(
         count_query.push_str(&where_clause);
     }
 
-    query.push_str(" GROUP BY u.id, u.student_id, u.email, u.prefix, u.first_name, u.last_name, u.department_id, u.created_at, u.updated_at, d.name, f.name, ar.id, ar.admin_level, ar.faculty_id, ar.permissions, ar.created_at, ar.updated_at");
+    query.push_str(" GROUP BY u.id, u.student_id, u.email, u.prefix, u.first_name, u.last_name, u.department_id, u.is_active, u.created_at, u.updated_at, d.name, f.name, ar.id, ar.admin_level, ar.faculty_id, ar.permissions, ar.created_at, ar.updated_at");
     query.push_str(" ORDER BY u.created_at DESC LIMIT $1 OFFSET $2");
 
     let mut query_builder = sqlx::query(&query).bind(limit).bind(offset);
@@ -209,6 +271,7 @@ pub async fn get_users(
                     department_name: row.get::<Option<String>, _>("department_name"),
                     faculty_name: row.get::<Option<String>, _>("faculty_name"),
                     admin_role,
+                    is_active: row.get("is_active"),
                     created_at: row.get::<Option<DateTime<Utc>>, _>("created_at"),
                     updated_at: row.get::<Option<DateTime<Utc>>, _>("updated_at"),
                     activity_count: row.get::<Option<i64>, _>("activity_count").unwrap_or(0),
@@ -292,7 +355,7 @@ pub async fn get_user(
 pub async fn create_user(
     State(session_state): State<SessionState>,
     _admin: AdminUser,
-    Json(request): Json<CreateUserRequest>,
+    ApiJson(request): ApiJson<CreateUserRequest>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     // Check if user already exists
     let existing_user = sqlx::query_scalar::<_, i64>(
@@ -322,7 +385,7 @@ pub async fn create_user(
     }
 
     // Hash password
-    let password_hash = match bcrypt::hash(&request.password, bcrypt::DEFAULT_COST) {
+    let password_hash = match hash_password(&session_state.app_config, &request.password).await {
         Ok(hash) => hash,
         Err(_) => {
             let error_response = json!({
@@ -341,7 +404,7 @@ pub async fn create_user(
         r#"
         INSERT INTO users (student_id, email, password_hash, prefix, first_name, last_name, qr_secret, department_id)
         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-        RETURNING id, student_id, email, password_hash, prefix, first_name, last_name, qr_secret, department_id, created_at, updated_at
+        RETURNING id, student_id, email, password_hash, prefix, first_name, last_name, qr_secret, department_id, is_active, anonymized_at, created_at, updated_at
         "#
     )
     .bind(&request.student_id)
@@ -357,6 +420,8 @@ pub async fn create_user(
 
     match create_result {
         Ok(user) => {
+            invalidate_dashboard_cache(&session_state).await;
+
             let user_response = UserResponse::from(user);
             let response = json!({
                 "status": "success",
@@ -380,7 +445,7 @@ pub async fn update_user(
     State(session_state): State<SessionState>,
     _admin: AdminUser,
     Path(user_id): Path<Uuid>,
-    Json(request): Json<UpdateUserRequest>,
+    ApiJson(request): ApiJson<UpdateUserRequest>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     // Check if user exists
     let existing_user = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM users WHERE id = $1")
@@ -442,7 +507,7 @@ pub async fn update_user(
     }
 
     if let Some(password) = &request.password {
-        let password_hash = match bcrypt::hash(password, bcrypt::DEFAULT_COST) {
+        let password_hash = match hash_password(&session_state.app_config, password).await {
             Ok(hash) => hash,
             Err(_) => {
                 let error_response = json!({
@@ -457,7 +522,7 @@ pub async fn update_user(
         param_count += 1;
     }
 
-    query.push_str(&format!(" WHERE id = ${} RETURNING id, student_id, email, password_hash, prefix, first_name, last_name, qr_secret, department_id, created_at, updated_at", param_count));
+    query.push_str(&format!(" WHERE id = ${} RETURNING id, student_id, email, password_hash, prefix, first_name, last_name, qr_secret, department_id, is_active, anonymized_at, created_at, updated_at", param_count));
 
     // Execute query with proper parameter binding
     let mut query_builder = sqlx::query_as::<_, User>(&query);
@@ -478,7 +543,7 @@ pub async fn update_user(
         query_builder = query_builder.bind(department_id);
     }
     if let Some(password) = &request.password {
-        let password_hash = bcrypt::hash(password, bcrypt::DEFAULT_COST).unwrap();
+        let password_hash = hash_password(&session_state.app_config, password).await.unwrap();
         query_builder = query_builder.bind(password_hash);
     }
     query_builder = query_builder.bind(user_id);
@@ -504,40 +569,362 @@ pub async fn update_user(
 }
 
 /// Delete user
+///
+/// Users with participation history can't be deleted; deactivate them instead so their
+/// records stay intact
 pub async fn delete_user(
     State(session_state): State<SessionState>,
     _admin: AdminUser,
     Path(user_id): Path<Uuid>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    let delete_result = sqlx::query("DELETE FROM users WHERE id = $1")
+) -> ApiResult<Json<Value>> {
+    let (participation_count, activity_count) = user_history_counts(&session_state.db_pool, user_id)
+        .await
+        .map_err(|_| ApiError::internal("Failed to check user participations"))?;
+
+    if participation_count > 0 {
+        return Err(ApiError::conflict(
+            "user_has_participations",
+            "User has participation history and cannot be deleted; deactivate the account instead",
+        )
+        .with("data", json!({ "participation_count": participation_count })));
+    }
+    // activities.created_by cascades, so deleting the creator would take every student's
+    // participations and hours in those activities with it
+    if activity_count > 0 {
+        return Err(ApiError::conflict(
+            "user_owns_activities",
+            "User created activities and cannot be deleted; deactivate the account instead",
+        )
+        .with("data", json!({ "activity_count": activity_count })));
+    }
+
+    let result = sqlx::query("DELETE FROM users WHERE id = $1")
         .bind(user_id)
         .execute(&session_state.db_pool)
-        .await;
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to delete user {}: {}", user_id, e);
+            ApiError::internal("Failed to delete user")
+        })?;
 
-    match delete_result {
-        Ok(result) => {
-            if result.rows_affected() == 0 {
-                let error_response = json!({
-                    "status": "error",
-                    "message": "User not found"
-                });
-                Err((StatusCode::NOT_FOUND, Json(error_response)))
-            } else {
-                let response = json!({
-                    "status": "success",
-                    "message": "User deleted successfully"
-                });
-                Ok(Json(response))
-            }
+    if result.rows_affected() == 0 {
+        return Err(ApiError::not_found("user_not_found", "User not found"));
+    }
+
+    let response = json!({
+        "status": "success",
+        "message": "User deleted successfully"
+    });
+    Ok(Json(response))
+}
+
+/// (participations, created activities) of a user. Either one means the account must be
+/// deactivated instead of deleted.
+pub(crate) async fn user_history_counts(
+    executor: impl sqlx::PgExecutor<'_>,
+    user_id: Uuid,
+) -> Result<(i64, i64), sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT (SELECT COUNT(*) FROM participations WHERE user_id = $1),
+               (SELECT COUNT(*) FROM activities WHERE created_by = $1)
+        "#,
+    )
+    .bind(user_id)
+    .fetch_one(executor)
+    .await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ToggleUserStatusRequest {
+    pub is_active: bool,
+}
+
+/// Activate or deactivate a user account
+///
+/// Deactivated users can't log in and their open sessions are revoked. Admins other than
+/// SuperAdmin can only change lower-ranked users in their own faculty, and the last active
+/// SuperAdmin can't be deactivated.
+pub async fn toggle_user_status(
+    State(session_state): State<SessionState>,
+    admin: AdminUser,
+    Path(user_id): Path<Uuid>,
+    ApiJson(request): ApiJson<ToggleUserStatusRequest>,
+) -> ApiResult<Json<Value>> {
+    if user_id == admin.session_user.user_id {
+        return Err(ApiError::bad_request(
+            "cannot_deactivate_self",
+            "You cannot change the status of your own account",
+        ));
+    }
+
+    let mut tx = session_state
+        .db_pool
+        .begin()
+        .await
+        .map_err(|_| ApiError::internal("Failed to start transaction"))?;
+
+    let target = sqlx::query(
+        "SELECT d.faculty_id, u.anonymized_at, ar.id AS admin_role_id, ar.admin_level FROM users u
+         LEFT JOIN departments d ON d.id = u.department_id
+         LEFT JOIN admin_roles ar ON ar.user_id = u.id
+         WHERE u.id = $1
+         FOR UPDATE OF u",
+    )
+    .bind(user_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|_| ApiError::internal("Failed to fetch user"))?
+    .ok_or_else(|| ApiError::not_found("user_not_found", "User not found"))?;
+
+    let faculty_id: Option<Uuid> = target.get("faculty_id");
+    let anonymized_at: Option<DateTime<Utc>> = target.get("anonymized_at");
+    let admin_role_id: Option<Uuid> = target.get("admin_role_id");
+    let admin_level: Option<AdminLevel> = target.get("admin_level");
+    if anonymized_at.is_some() && request.is_active {
+        return Err(ApiError::conflict(
+            "user_anonymized",
+            "Anonymized accounts cannot be reactivated",
+        ));
+    }
+
+    let is_super_admin = admin.admin_role.admin_level == AdminLevel::SuperAdmin;
+    if !is_super_admin
+        && !faculty_id.is_some_and(|faculty_id| has_faculty_access(&admin.session_user, faculty_id))
+    {
+        return Err(ApiError::forbidden(
+            "permission_denied",
+            "You can only change the status of users in your faculty",
+        ));
+    }
+
+    // Only SuperAdmins manage peers; everyone else is limited to lower-ranked accounts
+    if !is_super_admin
+        && admin_level
+            .as_ref()
+            .is_some_and(|level| level.rank() >= admin.admin_role.admin_level.rank())
+    {
+        return Err(ApiError::forbidden(
+            "insufficient_admin_level",
+            "You cannot change the status of an admin at or above your own level",
+        ));
+    }
+
+    if !request.is_active && admin_level == Some(AdminLevel::SuperAdmin) {
+        if let Some(admin_role_id) = admin_role_id {
+            ensure_super_admin_remains(&mut tx, &[admin_role_id]).await?;
         }
-        Err(e) => {
-            let error_response = json!({
-                "status": "error",
-                "message": format!("Failed to delete user: {}", e)
-            });
-            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+    }
+
+    let user = sqlx::query_as::<_, User>(
+        "UPDATE users SET is_active = $2, updated_at = NOW() WHERE id = $1 RETURNING *",
+    )
+    .bind(user_id)
+    .bind(request.is_active)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to update status of user {}: {}", user_id, e);
+        ApiError::internal("Failed to update user status")
+    })?;
+
+    tx.commit()
+        .await
+        .map_err(|_| ApiError::internal("Failed to commit transaction"))?;
+
+    // Session validation also rejects inactive users, so a Redis failure here only delays logout
+    let mut revoked_sessions = 0;
+    if !user.is_active {
+        match session_state.session_store.delete_user_sessions(user_id).await {
+            Ok(session_ids) => revoked_sessions = session_ids.len(),
+            Err(e) => tracing::warn!("Failed to revoke sessions of deactivated user {}: {}", user_id, e),
+        }
+    }
+
+    record_audit_log(
+        &session_state.db_pool,
+        NewAuditLog::new(
+            admin.session_user.user_id,
+            if user.is_active { "activate_user" } else { "deactivate_user" },
+            "user",
+            user_id,
+        )
+        .details(json!({ "revoked_sessions": revoked_sessions })),
+    )
+    .await;
+
+    let action = if user.is_active { "activated" } else { "deactivated" };
+    let response = json!({
+        "status": "success",
+        "data": {
+            "user": UserResponse::from(user),
+            "revoked_sessions": revoked_sessions,
+            "action": action
+        },
+        "message": format!("User account {} successfully", action)
+    });
+    Ok(Json(response))
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct AnonymizeUserRequest {
+    /// Required when users anonymize their own account
+    pub password: Option<String>,
+}
+
+/// Anonymize a user instead of deleting them (SuperAdmin, or the user with their password)
+///
+/// Personal data is replaced with tombstone values while participations are kept, so
+/// attendance counts stay intact. The account can't log in afterwards.
+pub async fn anonymize_user(
+    State(session_state): State<SessionState>,
+    user: SessionUser,
+    Path(user_id): Path<Uuid>,
+    request: Option<ApiJson<AnonymizeUserRequest>>,
+) -> ApiResult<Json<Value>> {
+    let ApiJson(request) = request.unwrap_or_default();
+    let is_super_admin = user
+        .admin_role
+        .as_ref()
+        .is_some_and(|role| role.admin_level == AdminLevel::SuperAdmin);
+    if user.user_id != user_id && !is_super_admin {
+        return Err(ApiError::forbidden(
+            "permission_denied",
+            "You can only anonymize your own account",
+        ));
+    }
+
+    let target = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(&session_state.db_pool)
+        .await
+        .map_err(|_| ApiError::internal("Failed to retrieve user"))?
+        .ok_or_else(|| ApiError::not_found("user_not_found", "User not found"))?;
+
+    if !is_super_admin {
+        let password = request.password.as_deref().ok_or_else(|| {
+            ApiError::bad_request("password_required", "Confirm with your password to anonymize your account")
+        })?;
+        let password_ok = verify_password(password, &target.password_hash)
+            .await
+            .map_err(|_| ApiError::internal("Failed to verify password"))?;
+        if !password_ok {
+            return Err(ApiError::forbidden("invalid_password", "Password is incorrect"));
         }
     }
+
+    if target.anonymized_at.is_some() {
+        return Err(ApiError::conflict("user_anonymized", "User has already been anonymized"));
+    }
+
+    let has_admin_role: bool =
+        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM admin_roles WHERE user_id = $1)")
+            .bind(user_id)
+            .fetch_one(&session_state.db_pool)
+            .await
+            .map_err(|_| ApiError::internal("Failed to check admin role"))?;
+    if has_admin_role {
+        return Err(ApiError::conflict(
+            "user_is_admin",
+            "Remove the user's admin role before anonymizing the account",
+        ));
+    }
+
+    let mut tx = session_state
+        .db_pool
+        .begin()
+        .await
+        .map_err(|_| ApiError::internal("Failed to start transaction"))?;
+
+    // Tombstones stay unique (student_id/email are UNIQUE) without revealing anything
+    sqlx::query(
+        r#"
+        UPDATE users
+        SET student_id = 'anon-' || substr(md5(id::text), 1, 15),
+            email = 'anonymized-' || id || '@invalid.local',
+            prefix = 'คุณ',
+            first_name = 'Anonymized',
+            last_name = 'User',
+            password_hash = $2,
+            qr_secret = NULL,
+            ical_token_hash = NULL,
+            is_active = FALSE,
+            anonymized_at = NOW(),
+            updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(user_id)
+    .bind(DISABLED_PASSWORD_HASH)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to anonymize user {}: {}", user_id, e);
+        ApiError::internal("Failed to anonymize user")
+    })?;
+
+    // Sessions and login events carry IP addresses, devices and locations; the other rows
+    // are free-text participation notes and feedback comments (ratings stay in the
+    // averages), personal messages, linked LINE chats or
+    // second-factor secrets
+    for statement in [
+        "UPDATE participations SET notes = NULL WHERE user_id = $1",
+        "UPDATE activity_feedback SET comment = NULL WHERE user_id = $1",
+        "DELETE FROM sessions WHERE user_id = $1",
+        "DELETE FROM login_events WHERE user_id = $1",
+        "DELETE FROM line_recipients WHERE user_id = $1",
+        "DELETE FROM admin_two_factor WHERE user_id = $1",
+        "DELETE FROM admin_recovery_codes WHERE user_id = $1",
+        "DELETE FROM notifications WHERE user_id = $1",
+        "DELETE FROM notification_reads WHERE user_id = $1",
+        "DELETE FROM notification_preferences WHERE user_id = $1",
+        "DELETE FROM subscription_notifications WHERE user_id = $1",
+    ] {
+        sqlx::query(statement)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to anonymize user {}: {}", user_id, e);
+                ApiError::internal("Failed to anonymize user")
+            })?;
+    }
+    // Queued emails carry the old email address
+    sqlx::query("DELETE FROM email_queue WHERE to_email = $1")
+        .bind(&target.email)
+        .execute(&mut *tx)
+        .await
+        .map_err(|_| ApiError::internal("Failed to anonymize user"))?;
+
+    tx.commit()
+        .await
+        .map_err(|_| ApiError::internal("Failed to anonymize user"))?;
+
+    // Session validation rejects inactive users, so a Redis failure here only delays logout
+    let revoked_sessions = match session_state.session_store.delete_user_sessions(user_id).await {
+        Ok(session_ids) => session_ids.len(),
+        Err(e) => {
+            tracing::warn!("Failed to revoke sessions of anonymized user {}: {}", user_id, e);
+            0
+        }
+    };
+
+    record_audit_log(
+        &session_state.db_pool,
+        NewAuditLog::new(user.user_id, "anonymize_user", "user", user_id)
+            .details(json!({ "self_service": user.user_id == user_id })),
+    )
+    .await;
+
+    let response = json!({
+        "status": "success",
+        "data": {
+            "user_id": user_id,
+            "revoked_sessions": revoked_sessions
+        },
+        "message": "User anonymized successfully"
+    });
+    Ok(Json(response))
 }
 
 /// Get user QR code
@@ -547,7 +934,7 @@ pub async fn get_user_qr(
     Path(user_id): Path<Uuid>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     // Check if user is requesting their own QR or is an admin
-    if user.user_id != user_id && !user.permissions.iter().any(|p| p.contains("ManageUsers")) {
+    if user.user_id != user_id && !user.has_permission(&Permission::ManageUsers) {
         let error_response = json!({
             "status": "error",
             "message": "Access denied: You can only access your own QR code"
@@ -555,22 +942,29 @@ pub async fn get_user_qr(
         return Err((StatusCode::FORBIDDEN, Json(error_response)));
     }
 
-    let user_result = sqlx::query("SELECT id, student_id, qr_secret FROM users WHERE id = $1")
+    let user_result = sqlx::query("SELECT id, student_id, qr_secret FROM users WHERE id = $1 AND qr_secret IS NOT NULL")
         .bind(&user_id)
         .fetch_one(&session_state.db_pool)
         .await;
 
     match user_result {
         Ok(user_data) => {
-            // Create QR data (JSON with user info and secret)
-            let qr_data = json!({
-                "user_id": user_data.get::<Uuid, _>("id"),
-                "student_id": user_data.get::<String, _>("student_id"),
-                "secret": user_data.get::<String, _>("qr_secret"),
-                "timestamp": chrono::Utc::now().timestamp()
-            });
-
-            let qr_data_string = qr_data.to_string();
+            // Create signed QR payload (user_id + expiry, HMAC over the user's current secret)
+            let qr_data_string = match generate_signed_qr_payload(
+                &user_data.get::<Uuid, _>("id"),
+                &user_data.get::<String, _>("qr_secret"),
+                &session_state.app_config.qr_signing_secret,
+                SIGNED_QR_TTL_SECONDS,
+            ) {
+                Ok(payload) => payload,
+                Err(_) => {
+                    let error_response = json!({
+                        "status": "error",
+                        "message": "Failed to generate QR code"
+                    });
+                    return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+                }
+            };
 
             // Generate QR code
             let qr_code = match QrCode::with_error_correction_level(&qr_data_string, EcLevel::M) {
@@ -626,3 +1020,996 @@ pub async fn get_user_qr(
         }
     }
 }
+
+/// Get user's QR code as a PNG image
+pub async fn get_user_qr_png(
+    State(session_state): State<SessionState>,
+    user: SessionUser, // Same rules as get_user_qr: own QR, or ManageUsers for any user
+    Path(user_id): Path<Uuid>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    if user.user_id != user_id && !user.has_permission(&Permission::ManageUsers) {
+        let error_response = json!({
+            "status": "error",
+            "message": "Access denied: You can only access your own QR code"
+        });
+        return Err((StatusCode::FORBIDDEN, Json(error_response)));
+    }
+
+    let size = match params.get("size") {
+        Some(size) => match size.parse::<u32>() {
+            Ok(size) => size.clamp(128, 1024),
+            Err(_) => {
+                let error_response = json!({
+                    "status": "error",
+                    "message": "Invalid size parameter"
+                });
+                return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+            }
+        },
+        None => 256,
+    };
+
+    let ec_level = match params.get("ecc") {
+        Some(ecc) => match parse_ec_level(ecc) {
+            Some(level) => level,
+            None => {
+                let error_response = json!({
+                    "status": "error",
+                    "message": "Invalid ecc parameter. Supported levels: L, M, Q, H"
+                });
+                return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+            }
+        },
+        None => EcLevel::M,
+    };
+
+    let user_result = sqlx::query("SELECT id, student_id, qr_secret FROM users WHERE id = $1 AND qr_secret IS NOT NULL")
+        .bind(user_id)
+        .fetch_one(&session_state.db_pool)
+        .await;
+
+    match user_result {
+        Ok(user_data) => {
+            let qr_data = match generate_signed_qr_payload(
+                &user_data.get::<Uuid, _>("id"),
+                &user_data.get::<String, _>("qr_secret"),
+                &session_state.app_config.qr_signing_secret,
+                SIGNED_QR_TTL_SECONDS,
+            ) {
+                Ok(payload) => payload,
+                Err(_) => {
+                    let error_response = json!({
+                        "status": "error",
+                        "message": "Failed to generate QR code"
+                    });
+                    return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+                }
+            };
+
+            match render_qr_png(&qr_data, size, ec_level) {
+                Ok(png) => Ok((
+                    [
+                        (header::CONTENT_TYPE, "image/png"),
+                        (header::CACHE_CONTROL, "no-store"),
+                    ],
+                    png,
+                )
+                    .into_response()),
+                Err(_) => {
+                    let error_response = json!({
+                        "status": "error",
+                        "message": "Failed to generate QR code"
+                    });
+                    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+                }
+            }
+        }
+        Err(sqlx::Error::RowNotFound) => {
+            let error_response = json!({
+                "status": "error",
+                "message": "User not found"
+            });
+            Err((StatusCode::NOT_FOUND, Json(error_response)))
+        }
+        Err(_) => {
+            let error_response = json!({
+                "status": "error",
+                "message": "Failed to retrieve user"
+            });
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+/// Get accumulated activity hours for a user
+pub async fn get_user_hours(
+    State(session_state): State<SessionState>,
+    user: SessionUser, // Own hours, or ManageUsers for any user
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    if user.user_id != user_id && !user.has_permission(&Permission::ManageUsers) {
+        let error_response = json!({
+            "status": "error",
+            "message": "Access denied: You can only view your own activity hours"
+        });
+        return Err((StatusCode::FORBIDDEN, Json(error_response)));
+    }
+
+    let by_activity_type = sqlx::query_as::<_, HoursByActivityType>(
+        r#"
+        SELECT a.activity_type::text as activity_type,
+               COALESCE(SUM(h.hours), 0)::bigint as total_hours,
+               COUNT(*) as activity_count
+        FROM user_activity_hours h
+        JOIN activities a ON a.id = h.activity_id
+        WHERE h.user_id = $1 AND a.deleted_at IS NULL
+        GROUP BY a.activity_type
+        ORDER BY total_hours DESC
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(&session_state.db_pool)
+    .await;
+
+    let by_academic_year = sqlx::query_as::<_, HoursByAcademicYear>(
+        r#"
+        SELECT a.academic_year,
+               COALESCE(SUM(h.hours), 0)::bigint as total_hours,
+               COUNT(*) as activity_count
+        FROM user_activity_hours h
+        JOIN activities a ON a.id = h.activity_id
+        WHERE h.user_id = $1 AND a.deleted_at IS NULL
+        GROUP BY a.academic_year
+        ORDER BY a.academic_year DESC
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(&session_state.db_pool)
+    .await;
+
+    match (by_activity_type, by_academic_year) {
+        (Ok(by_activity_type), Ok(by_academic_year)) => {
+            let total_hours: i64 = by_activity_type.iter().map(|row| row.total_hours).sum();
+            let response = json!({
+                "status": "success",
+                "data": {
+                    "user_id": user_id,
+                    "total_hours": total_hours,
+                    "by_activity_type": by_activity_type,
+                    "by_academic_year": by_academic_year
+                },
+                "message": "Activity hours retrieved successfully"
+            });
+            Ok(Json(response))
+        }
+        _ => {
+            let error_response = json!({
+                "status": "error",
+                "message": "Failed to retrieve activity hours"
+            });
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+/// Get a student's activity transcript (`?format=csv` for a downloadable file)
+pub async fn get_user_transcript(
+    State(session_state): State<SessionState>,
+    user: SessionUser,
+    Path(user_id): Path<Uuid>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    let student = sqlx::query(
+        r#"
+        SELECT u.student_id, u.prefix, u.first_name, u.last_name, d.faculty_id
+        FROM users u
+        LEFT JOIN departments d ON u.department_id = d.id
+        WHERE u.id = $1
+        "#,
+    )
+    .bind(user_id)
+    .fetch_optional(&session_state.db_pool)
+    .await;
+
+    let student = match student {
+        Ok(Some(row)) => row,
+        Ok(None) => {
+            let error_response = json!({
+                "status": "error",
+                "message": "User not found"
+            });
+            return Err((StatusCode::NOT_FOUND, Json(error_response)));
+        }
+        Err(_) => {
+            let error_response = json!({
+                "status": "error",
+                "message": "Failed to retrieve user"
+            });
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+        }
+    };
+
+    // Students see their own transcript; FacultyAdmin+ see students in their faculty
+    let student_faculty_id: Option<Uuid> = student.get("faculty_id");
+    let can_view = user.user_id == user_id
+        || match user.admin_role.as_ref().map(|role| &role.admin_level) {
+            Some(AdminLevel::SuperAdmin) => true,
+            Some(AdminLevel::FacultyAdmin) => {
+                student_faculty_id.is_some_and(|faculty_id| has_faculty_access(&user, faculty_id))
+            }
+            _ => false,
+        };
+
+    if !can_view {
+        let error_response = json!({
+            "status": "error",
+            "message": "Access denied: You can only view transcripts you are responsible for"
+        });
+        return Err((StatusCode::FORBIDDEN, Json(error_response)));
+    }
+
+    let entries = match sqlx::query_as::<_, TranscriptEntry>(
+        r#"
+        SELECT a.id as activity_id, a.title, a.organizer, a.start_date as activity_date,
+               a.activity_type::text as activity_type, a.academic_year, h.hours,
+               COALESCE(p.checked_out_at, h.awarded_at) as completed_at
+        FROM user_activity_hours h
+        JOIN activities a ON a.id = h.activity_id
+        JOIN participations p ON p.id = h.participation_id
+        WHERE h.user_id = $1 AND a.deleted_at IS NULL
+        ORDER BY completed_at DESC
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(&session_state.db_pool)
+    .await
+    {
+        Ok(entries) => entries,
+        Err(_) => {
+            let error_response = json!({
+                "status": "error",
+                "message": "Failed to retrieve transcript"
+            });
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+        }
+    };
+
+    let student_id: String = student.get("student_id");
+    let total_hours: i64 = entries.iter().map(|e| i64::from(e.hours)).sum();
+
+    match params.get("format").map(String::as_str) {
+        Some("csv") => {
+            let mut csv = String::new();
+            push_csv_row(
+                &mut csv,
+                &["title", "organizer", "date", "activity_type", "academic_year", "hours", "completed_at"],
+            );
+            for entry in &entries {
+                push_csv_row(
+                    &mut csv,
+                    &[
+                        entry.title.clone(),
+                        entry.organizer.clone(),
+                        entry.activity_date.to_string(),
+                        entry.activity_type.clone().unwrap_or_default(),
+                        entry.academic_year.clone(),
+                        entry.hours.to_string(),
+                        entry.completed_at.to_rfc3339(),
+                    ],
+                );
+            }
+            push_csv_row(&mut csv, &["Total", "", "", "", "", &total_hours.to_string(), ""]);
+
+            Ok(csv_attachment(&format!("transcript_{}.csv", student_id), csv))
+        }
+        None | Some("json") => {
+            let response = json!({
+                "status": "success",
+                "data": {
+                    "user_id": user_id,
+                    "student_id": student_id,
+                    "full_name_with_prefix": format!(
+                        "{}{} {}",
+                        student.get::<UserPrefix, _>("prefix").to_thai_string(),
+                        student.get::<String, _>("first_name"),
+                        student.get::<String, _>("last_name")
+                    ),
+                    "total_hours": total_hours,
+                    "total_activities": entries.len(),
+                    "activities": entries
+                },
+                "message": "Transcript retrieved successfully"
+            });
+            Ok(Json(response).into_response())
+        }
+        Some(_) => {
+            let error_response = json!({
+                "status": "error",
+                "message": "Invalid format. Supported formats: json, csv"
+            });
+            Err((StatusCode::BAD_REQUEST, Json(error_response)))
+        }
+    }
+}
+
+/// Export everything stored about one user (the user themselves or SuperAdmin)
+///
+/// Secrets such as the password hash, QR secret and calendar token are never included.
+pub async fn export_user_data(
+    State(session_state): State<SessionState>,
+    user: SessionUser,
+    Path(user_id): Path<Uuid>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ApiResult<Response> {
+    let is_super_admin = user
+        .admin_role
+        .as_ref()
+        .is_some_and(|role| role.admin_level == AdminLevel::SuperAdmin);
+    if user.user_id != user_id && !is_super_admin {
+        return Err(ApiError::forbidden(
+            "permission_denied",
+            "You can only export your own data",
+        ));
+    }
+
+    match params.get("format").map(String::as_str) {
+        None | Some("json") => {}
+        Some(_) => {
+            return Err(ApiError::bad_request(
+                "unsupported_format",
+                "Invalid format. Supported formats: json",
+            ))
+        }
+    }
+
+    let pool = &session_state.db_pool;
+    let profile = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| ApiError::internal("Failed to retrieve user"))?
+        .ok_or_else(|| ApiError::not_found("user_not_found", "User not found"))?;
+
+    let department = sqlx::query(
+        r#"
+        SELECT d.id, d.name, d.code, f.id as faculty_id, f.name as faculty_name, f.code as faculty_code
+        FROM departments d
+        JOIN faculties f ON f.id = d.faculty_id
+        WHERE d.id = $1
+        "#,
+    )
+    .bind(profile.department_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|_| ApiError::internal("Failed to retrieve department"))?
+    .map(|row| {
+        json!({
+            "id": row.get::<Uuid, _>("id"),
+            "name": row.get::<String, _>("name"),
+            "code": row.get::<String, _>("code"),
+            "faculty": {
+                "id": row.get::<Uuid, _>("faculty_id"),
+                "name": row.get::<String, _>("faculty_name"),
+                "code": row.get::<String, _>("faculty_code")
+            }
+        })
+    });
+
+    let admin_role = sqlx::query_as::<_, AdminRole>("SELECT * FROM admin_roles WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| ApiError::internal("Failed to retrieve admin role"))?;
+
+    // The export is the user's own record, so participations in soft-deleted activities stay in it
+    let participations = sqlx::query_as::<_, ExportedParticipation>(
+        r#"
+        SELECT p.id as participation_id, p.status::text as status, p.registered_at,
+               p.checked_in_at, p.checked_out_at, p.notes, h.hours as hours_awarded,
+               a.id as activity_id, a.title as activity_title, a.activity_type::text as activity_type,
+               a.location, a.organizer, a.academic_year, a.start_date, a.end_date,
+               a.start_time_only as start_time, a.end_time_only as end_time
+        FROM participations p
+        JOIN activities a ON a.id = p.activity_id
+        LEFT JOIN user_activity_hours h ON h.participation_id = p.id
+        WHERE p.user_id = $1
+        ORDER BY p.registered_at DESC
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to export participations of user {}: {}", user_id, e);
+        ApiError::internal("Failed to retrieve participations")
+    })?;
+
+    let sessions: Vec<SessionInfo> = session_state
+        .session_store
+        .get_user_sessions(user_id)
+        .await
+        .map_err(|_| ApiError::internal("Failed to retrieve sessions"))?
+        .into_iter()
+        .map(|s| SessionInfo {
+            session_id: s.id,
+            device_info: s.device_info,
+            ip_address: s.ip_address,
+            user_agent: s.user_agent,
+            created_at: s.created_at,
+            last_accessed: s.last_accessed,
+            expires_at: s.expires_at,
+        })
+        .collect();
+
+    record_audit_log(
+        pool,
+        NewAuditLog::new(user.user_id, "export_user_data", "user", user_id),
+    )
+    .await;
+
+    let filename = format!("user_export_{}.json", profile.student_id);
+    let response = json!({
+        "status": "success",
+        "data": {
+            "exported_at": Utc::now(),
+            "profile": UserResponse::from(profile),
+            "department": department,
+            "admin_role": admin_role,
+            "participations": participations,
+            "sessions": sessions
+        },
+        "message": "User data exported successfully"
+    });
+
+    Ok((
+        [(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", filename),
+        )],
+        Json(response),
+    )
+        .into_response())
+}
+
+/// Calendar of a user's upcoming commitments: activities they are registered or checked in for
+async fn registered_activities_calendar(
+    session_state: &SessionState,
+    user_id: Uuid,
+) -> Result<String, sqlx::Error> {
+    let time_zone = session_state.app_config.activity_time_zone();
+    let rows = sqlx::query(&format!(
+        r#"
+        SELECT a.id, a.title, a.location, a.description, a.updated_at,
+            {} as start_time,
+            {} as end_time
+        FROM participations p
+        JOIN activities a ON a.id = p.activity_id
+        WHERE p.user_id = $1 AND p.status IN ('registered', 'checked_in') AND a.deleted_at IS NULL
+        ORDER BY a.start_date, a.start_time_only
+        "#,
+        time_zone.start_sql("a"),
+        time_zone.end_sql("a")
+    ))
+    .bind(user_id)
+    .fetch_all(&session_state.db_pool)
+    .await?;
+
+    let events: Vec<IcalEvent> = rows
+        .iter()
+        .map(|row| IcalEvent {
+            uid: row.get("id"),
+            start: row.get("start_time"),
+            end: row.get("end_time"),
+            summary: row.get("title"),
+            location: row.get("location"),
+            description: row.get("description"),
+            updated_at: row.get("updated_at"),
+        })
+        .collect();
+
+    Ok(build_calendar("Trackivity Activities", &events))
+}
+
+fn ical_token_hash(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// iCalendar feed of a user's registered activities (session auth)
+pub async fn get_user_activities_ics(
+    State(session_state): State<SessionState>,
+    user: SessionUser, // Own calendar, or ManageUsers for any user
+    Path(user_id): Path<Uuid>,
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    if user.user_id != user_id && !user.has_permission(&Permission::ManageUsers) {
+        let error_response = json!({
+            "status": "error",
+            "message": "Access denied: You can only view your own calendar"
+        });
+        return Err((StatusCode::FORBIDDEN, Json(error_response)));
+    }
+
+    match registered_activities_calendar(&session_state, user_id).await {
+        Ok(calendar) => Ok(ical_response(calendar)),
+        Err(_) => {
+            let error_response = json!({
+                "status": "error",
+                "message": "Failed to build calendar"
+            });
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+/// Generate (or rotate) the secret token for calendar subscriptions; the old URL stops working
+pub async fn rotate_ical_token(
+    State(session_state): State<SessionState>,
+    user: SessionUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    if user.user_id != user_id {
+        let error_response = json!({
+            "status": "error",
+            "message": "Access denied: You can only manage your own calendar token"
+        });
+        return Err((StatusCode::FORBIDDEN, Json(error_response)));
+    }
+
+    let token = generate_secret_key();
+    let result = sqlx::query("UPDATE users SET ical_token_hash = $1, updated_at = NOW() WHERE id = $2")
+        .bind(ical_token_hash(&token))
+        .bind(user_id)
+        .execute(&session_state.db_pool)
+        .await;
+
+    match result {
+        Ok(_) => {
+            let response = json!({
+                "status": "success",
+                "data": {
+                    "token": token,
+                    "url": format!("/api/ical/{}.ics", token)
+                },
+                "message": "Calendar token generated successfully"
+            });
+            Ok(Json(response))
+        }
+        Err(_) => {
+            let error_response = json!({
+                "status": "error",
+                "message": "Failed to generate calendar token"
+            });
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+/// iCalendar feed via secret token, for calendar apps that can't send session cookies
+pub async fn get_ical_feed(
+    State(session_state): State<SessionState>,
+    Path(file): Path<String>, // "{token}.ics"
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    let not_found = || {
+        let error_response = json!({
+            "status": "error",
+            "message": "Calendar not found"
+        });
+        (StatusCode::NOT_FOUND, Json(error_response))
+    };
+
+    let token = file.strip_suffix(".ics").ok_or_else(not_found)?;
+
+    let user_id = sqlx::query_scalar::<_, Uuid>("SELECT id FROM users WHERE ical_token_hash = $1")
+        .bind(ical_token_hash(token))
+        .fetch_optional(&session_state.db_pool)
+        .await
+        .map_err(|_| {
+            let error_response = json!({
+                "status": "error",
+                "message": "Failed to look up calendar"
+            });
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+        })?
+        .ok_or_else(not_found)?;
+
+    match registered_activities_calendar(&session_state, user_id).await {
+        Ok(calendar) => Ok(ical_response(calendar)),
+        Err(_) => {
+            let error_response = json!({
+                "status": "error",
+                "message": "Failed to build calendar"
+            });
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{insert_user, test_state};
+
+    #[tokio::test]
+    async fn test_export_is_limited_to_self_or_super_admin() {
+        let state = test_state(&sqlx::PgPool::connect_lazy("postgres://localhost/unused").unwrap());
+        let user_id = Uuid::new_v4();
+        let student = SessionUser {
+            user_id,
+            student_id: "6400000000".to_string(),
+            email: "student@test.local".to_string(),
+            first_name: "Test".to_string(),
+            last_name: "Student".to_string(),
+            department_id: None,
+            admin_role: None,
+            session_id: Uuid::new_v4().to_string(),
+            permissions: Vec::new(),
+            faculty_id: None,
+            is_impersonated: false,
+            impersonated_by: None,
+        };
+
+        let other = export_user_data(
+            State(state.clone()),
+            student.clone(),
+            Path(Uuid::new_v4()),
+            Query(HashMap::new()),
+        )
+        .await;
+        match other {
+            Err(ApiError::Forbidden(detail)) => assert_eq!(detail.code, "permission_denied"),
+            other => panic!("expected 403, got {:?}", other.map(|r| r.status())),
+        }
+
+        let csv = export_user_data(
+            State(state),
+            student,
+            Path(user_id),
+            Query(HashMap::from([("format".to_string(), "csv".to_string())])),
+        )
+        .await;
+        match csv {
+            Err(ApiError::BadRequest(detail)) => assert_eq!(detail.code, "unsupported_format"),
+            other => panic!("expected 400, got {:?}", other.map(|r| r.status())),
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_deactivate_instead_of_delete() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+        let admin_id = Uuid::new_v4();
+        let admin_role = AdminRole {
+            id: Uuid::new_v4(),
+            user_id: admin_id,
+            admin_level: AdminLevel::SuperAdmin,
+            faculty_id: None,
+            permissions: Vec::new(),
+            is_enabled: true,
+            created_at: None,
+            updated_at: None,
+        };
+        let admin = || AdminUser {
+            session_user: SessionUser {
+                user_id: admin_id,
+                student_id: "admin".to_string(),
+                email: "admin@test.local".to_string(),
+                first_name: "Test".to_string(),
+                last_name: "Admin".to_string(),
+                department_id: None,
+                admin_role: Some(admin_role.clone()),
+                session_id: Uuid::new_v4().to_string(),
+                permissions: Vec::new(),
+                faculty_id: None,
+                is_impersonated: false,
+                impersonated_by: None,
+            },
+            admin_role: admin_role.clone(),
+        };
+
+        let tag = Uuid::new_v4().simple().to_string()[..8].to_string();
+        let user_id = insert_user(&pool, &format!("deact-{}", tag), None).await;
+        let activity_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO activities (title, description, location, academic_year, organizer,
+                start_date, end_date, start_time_only, end_time_only, hours, status, created_by)
+             VALUES ('Deactivate', 'Deactivate test', 'Lab', '2567', 'Test', CURRENT_DATE, CURRENT_DATE,
+                '09:00', '17:00', 1, 'published', $1)
+             RETURNING id",
+        )
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO participations (user_id, activity_id, status) VALUES ($1, $2, 'registered')")
+            .bind(user_id)
+            .bind(activity_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let refused = delete_user(State(state.clone()), admin(), Path(user_id)).await;
+        let deactivated = toggle_user_status(
+            State(state.clone()),
+            admin(),
+            Path(user_id),
+            ApiJson(ToggleUserStatusRequest { is_active: false }),
+        )
+        .await
+            .map(|Json(body)| body);
+        let is_active: bool = sqlx::query_scalar("SELECT is_active FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let reactivated = toggle_user_status(
+            State(state.clone()),
+            admin(),
+            Path(user_id),
+            ApiJson(ToggleUserStatusRequest { is_active: true }),
+        )
+        .await
+            .map(|Json(body)| body);
+
+        sqlx::query("DELETE FROM activities WHERE id = $1")
+            .bind(activity_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let deleted = delete_user(State(state), admin(), Path(user_id)).await;
+
+        match refused {
+            Err(ApiError::Conflict(detail)) => assert_eq!(detail.code, "user_has_participations"),
+            other => panic!("expected user_has_participations, got {:?}", other),
+        }
+        assert_eq!(deactivated.unwrap()["data"]["action"], "deactivated");
+        assert!(!is_active);
+        assert_eq!(reactivated.unwrap()["data"]["user"]["is_active"], true);
+        assert!(deleted.is_ok());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_faculty_admin_cannot_deactivate_peer_admin() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+        let tag = Uuid::new_v4().simple().to_string()[..8].to_string();
+        let faculty_id: Uuid = sqlx::query_scalar("INSERT INTO faculties (name, code) VALUES ($1, $2) RETURNING id")
+            .bind(format!("Toggle {}", tag))
+            .bind(format!("T{}", tag))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let department_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO departments (name, code, faculty_id) VALUES ($1, $2, $3) RETURNING id",
+        )
+        .bind(format!("Toggle {}", tag))
+        .bind(format!("D{}", tag))
+        .bind(faculty_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        let mut user_ids = Vec::new();
+        for (i, level) in [AdminLevel::FacultyAdmin, AdminLevel::RegularAdmin].into_iter().enumerate() {
+            user_ids.push(insert_user(&pool, &format!("tg{}{}", i, tag), Some(level)).await);
+        }
+        sqlx::query("UPDATE users SET department_id = $1 WHERE id = ANY($2)")
+            .bind(department_id)
+            .bind(&user_ids)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE admin_roles SET faculty_id = $1 WHERE user_id = ANY($2)")
+            .bind(faculty_id)
+            .bind(&user_ids)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let caller_id = Uuid::new_v4();
+        let admin_role = AdminRole {
+            id: Uuid::new_v4(),
+            user_id: caller_id,
+            admin_level: AdminLevel::FacultyAdmin,
+            faculty_id: Some(faculty_id),
+            permissions: Vec::new(),
+            is_enabled: true,
+            created_at: None,
+            updated_at: None,
+        };
+        let caller = || AdminUser {
+            session_user: SessionUser {
+                user_id: caller_id,
+                student_id: "faculty-admin".to_string(),
+                email: "faculty-admin@test.local".to_string(),
+                first_name: "Test".to_string(),
+                last_name: "Admin".to_string(),
+                department_id: None,
+                admin_role: Some(admin_role.clone()),
+                session_id: Uuid::new_v4().to_string(),
+                permissions: Vec::new(),
+                faculty_id: Some(faculty_id),
+                is_impersonated: false,
+                impersonated_by: None,
+            },
+            admin_role: admin_role.clone(),
+        };
+        let deactivate = || ApiJson(ToggleUserStatusRequest { is_active: false });
+
+        let peer = toggle_user_status(State(state.clone()), caller(), Path(user_ids[0]), deactivate()).await;
+        let lower = toggle_user_status(State(state.clone()), caller(), Path(user_ids[1]), deactivate()).await;
+        let peer_active: bool = sqlx::query_scalar("SELECT is_active FROM users WHERE id = $1")
+            .bind(user_ids[0])
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+        sqlx::query("DELETE FROM users WHERE id = ANY($1)")
+            .bind(&user_ids)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM faculties WHERE id = $1")
+            .bind(faculty_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        match peer {
+            Err(ApiError::Forbidden(detail)) => assert_eq!(detail.code, "insufficient_admin_level"),
+            other => panic!("expected insufficient_admin_level, got {:?}", other),
+        }
+        assert!(peer_active);
+        assert_eq!(lower.unwrap().0["data"]["action"], "deactivated");
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_anonymize_keeps_participations() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let tag = Uuid::new_v4().simple().to_string()[..8].to_string();
+        let student_id = format!("erase-{}", tag);
+        let user_id = insert_user(&pool, &student_id, None).await;
+        sqlx::query("UPDATE users SET password_hash = $2 WHERE id = $1")
+            .bind(user_id)
+            .bind(bcrypt::hash("Str0ngPassw0rd!", 4).unwrap())
+            .execute(&pool)
+            .await
+            .unwrap();
+        let activity_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO activities (title, description, location, academic_year, organizer,
+                start_date, end_date, start_time_only, end_time_only, hours, status, created_by)
+             VALUES ('Anonymize', 'Anonymize test', 'Lab', '2567', 'Test', CURRENT_DATE, CURRENT_DATE,
+                '09:00', '17:00', 1, 'published', $1)
+             RETURNING id",
+        )
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO participations (user_id, activity_id, status, notes)
+             VALUES ($1, $2, 'registered', 'Allergic to peanuts')",
+        )
+        .bind(user_id)
+        .bind(activity_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO activity_feedback (activity_id, user_id, rating, comment)
+             VALUES ($1, $2, 4, 'Ask for Somchai at the gate')",
+        )
+        .bind(activity_id)
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let student = SessionUser {
+            user_id,
+            student_id: student_id.clone(),
+            email: format!("{}@test.local", student_id),
+            first_name: "Test".to_string(),
+            last_name: "Student".to_string(),
+            department_id: None,
+            admin_role: None,
+            session_id: Uuid::new_v4().to_string(),
+            permissions: Vec::new(),
+            faculty_id: None,
+            is_impersonated: false,
+            impersonated_by: None,
+        };
+        let anonymize = |password: &str| {
+            anonymize_user(
+                State(state.clone()),
+                student.clone(),
+                Path(user_id),
+                Some(ApiJson(AnonymizeUserRequest {
+                    password: Some(password.to_string()),
+                })),
+            )
+        };
+
+        sqlx::query("INSERT INTO login_events (user_id, ip_address, user_agent, success) VALUES ($1, '203.0.113.7', 'Test', TRUE)")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO notifications (user_id, category, message) VALUES ($1, 'reminders', 'Hello')")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO line_recipients (user_id, line_id) VALUES ($1, 'U0123456789abcdef0123456789abcdef')")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let wrong_password = anonymize("wrong").await;
+        let anonymized = anonymize("Str0ngPassw0rd!").await;
+        let leftover_rows: i64 = sqlx::query_scalar(
+            "SELECT (SELECT COUNT(*) FROM login_events WHERE user_id = $1)
+                  + (SELECT COUNT(*) FROM notifications WHERE user_id = $1)
+                  + (SELECT COUNT(*) FROM line_recipients WHERE user_id = $1)",
+        )
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let participation_notes: Vec<Option<String>> =
+            sqlx::query_scalar("SELECT notes FROM participations WHERE user_id = $1")
+                .bind(user_id)
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        let feedback: (i16, Option<String>) =
+            sqlx::query_as("SELECT rating, comment FROM activity_feedback WHERE user_id = $1")
+                .bind(user_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        let password_ok = verify_password("Str0ngPassw0rd!", &user.password_hash).await.unwrap();
+
+        sqlx::query("DELETE FROM activities WHERE id = $1")
+            .bind(activity_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        match wrong_password {
+            Err(ApiError::Forbidden(detail)) => assert_eq!(detail.code, "invalid_password"),
+            other => panic!("expected invalid_password, got {:?}", other),
+        }
+        assert!(anonymized.is_ok());
+        assert!(user.anonymized_at.is_some());
+        assert!(!user.is_active);
+        assert!(user.qr_secret.is_none());
+        assert!(user.student_id.starts_with("anon-"));
+        assert!(!user.email.contains(&student_id));
+        assert_eq!(user.first_name, "Anonymized");
+        assert!(!password_ok);
+        assert_eq!(participation_notes, vec![None]);
+        assert_eq!(feedback, (4, None));
+        assert_eq!(leftover_rows, 0);
+    }
+}
@@ -1,20 +1,34 @@
 use axum::{
-    extract::{Query, State},
+    body::Bytes,
+    extract::{Path, Query, State},
     http::StatusCode,
     response::Json,
 };
 use chrono::{DateTime, Utc};
+use futures::{StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use sqlx::Row;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
-use crate::middleware::session::{SessionState, SuperAdminUser, FacultyAdminUser};
+use crate::error::{ApiError, ApiJson, ApiResult};
+use crate::middleware::session::{AdminUser, FacultyAdminUser, FacultyScope, SessionState, SuperAdminUser};
 use crate::models::{
     admin_role::{AdminRole, AdminLevel},
+    audit_log::NewAuditLog,
     user::{User, UserResponse},
 };
+use crate::handlers::admin::invalidate_dashboard_cache;
+use crate::handlers::auth::ensure_user_in_admin_scope;
+use crate::services::audit_log::record_audit_log;
+use crate::utils::csv::parse_csv;
+use crate::utils::password_hash::hash_password;
+use crate::utils::qr::generate_secret_key;
+use crate::utils::validation::{
+    generate_password, validate_email, validate_name, validate_password, validate_student_id,
+    PasswordPolicy, RuleViolation, ValidationError,
+};
 
 /// Get system-wide users (SuperAdmin only)
 pub async fn get_system_users(
@@ -251,7 +265,7 @@ pub struct BulkUserOperationRequest {
 pub async fn bulk_user_operations(
     State(session_state): State<SessionState>,
     _admin: SuperAdminUser,
-    Json(request): Json<BulkUserOperationRequest>,
+    ApiJson(request): ApiJson<BulkUserOperationRequest>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     if request.user_ids.is_empty() {
         let error_response = json!({
@@ -296,7 +310,7 @@ pub async fn bulk_user_operations(
                     UPDATE users 
                     SET department_id = $2, updated_at = NOW()
                     WHERE id = $1
-                    RETURNING id, student_id, email, password_hash, prefix, first_name, last_name, qr_secret, department_id, created_at, updated_at
+                    RETURNING id, student_id, email, password_hash, prefix, first_name, last_name, qr_secret, department_id, is_active, anonymized_at, created_at, updated_at
                     "#
                 )
                 .bind(user_id)
@@ -366,25 +380,9 @@ pub async fn get_faculty_user_statistics(
     // Get faculty_id from query parameter or use admin's faculty_id
     let faculty_id = match params.get("faculty_id").and_then(|f| Uuid::parse_str(f).ok()) {
         Some(requested_faculty_id) => {
-            // Validate access to the requested faculty
-            match faculty_admin.admin_role.admin_level {
-                AdminLevel::SuperAdmin => requested_faculty_id, // SuperAdmin can access any faculty
-                AdminLevel::FacultyAdmin | AdminLevel::RegularAdmin => {
-                    // Faculty admins can only access their own faculty
-                    match faculty_admin.faculty_id {
-                        Some(admin_faculty_id) if admin_faculty_id == requested_faculty_id => {
-                            requested_faculty_id
-                        }
-                        _ => {
-                            let error_response = json!({
-                                "status": "error",
-                                "message": "Access denied: You can only view statistics for your own faculty"
-                            });
-                            return Err((StatusCode::FORBIDDEN, Json(error_response)));
-                        }
-                    }
-                }
-            }
+            // Faculty admins can only access their own faculty
+            FacultyScope::for_admin(&faculty_admin.admin_role)?.authorize(requested_faculty_id)?;
+            requested_faculty_id
         }
         None => {
             // No faculty_id provided, use admin's faculty_id if they have one
@@ -653,3 +651,563 @@ pub async fn get_user_statistics(
         }
     }
 }
+
+/// Largest CSV accepted by the user import
+const MAX_IMPORT_ROWS: usize = 1000;
+
+/// Per-row outcome of a user import
+#[derive(Debug, Serialize)]
+pub struct UserImportRow {
+    /// Line in the uploaded file (the header is row 1)
+    pub row: usize,
+    pub student_id: String,
+    pub email: String,
+    /// `created`, `valid` (dry run), `skipped` (already exists) or `error`
+    pub result: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<Uuid>,
+    /// Only set when the row had no password and one was generated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generated_password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<ValidationError>,
+}
+
+struct UserImportRecord {
+    report: UserImportRow,
+    first_name: String,
+    last_name: String,
+    department_id: Option<Uuid>,
+    password: Option<String>,
+}
+
+fn field_error(field: &str, rule: &str, message: &str) -> ValidationError {
+    ValidationError {
+        field: field.to_string(),
+        violations: vec![RuleViolation {
+            rule: rule.to_string(),
+            message: message.to_string(),
+        }],
+    }
+}
+
+/// Parse the uploaded CSV into records; columns are matched by header name, in any order
+fn parse_user_import_csv(body: &[u8]) -> ApiResult<Vec<UserImportRecord>> {
+    let text = std::str::from_utf8(body)
+        .map_err(|_| ApiError::bad_request("invalid_csv", "CSV must be UTF-8 encoded"))?;
+    let mut records = parse_csv(text).into_iter();
+    let header: Vec<String> = records
+        .next()
+        .ok_or_else(|| ApiError::bad_request("empty_upload", "CSV is empty"))?
+        .iter()
+        .map(|name| name.trim().to_ascii_lowercase())
+        .collect();
+
+    let column = |name: &str| header.iter().position(|h| h == name);
+    let missing: Vec<&str> = ["student_id", "email", "first_name", "last_name"]
+        .into_iter()
+        .filter(|name| column(name).is_none())
+        .collect();
+    if !missing.is_empty() {
+        return Err(ApiError::bad_request(
+            "missing_columns",
+            format!("CSV is missing required columns: {}", missing.join(", ")),
+        )
+        .with("missing_columns", json!(missing)));
+    }
+
+    let (student_id, email, first_name, last_name) = (
+        column("student_id").unwrap_or_default(),
+        column("email").unwrap_or_default(),
+        column("first_name").unwrap_or_default(),
+        column("last_name").unwrap_or_default(),
+    );
+    let (department_id, password) = (column("department_id"), column("password"));
+
+    let records: Vec<Vec<String>> = records.collect();
+    if records.is_empty() {
+        return Err(ApiError::bad_request("empty_upload", "CSV has no data rows"));
+    }
+    if records.len() > MAX_IMPORT_ROWS {
+        return Err(ApiError::bad_request(
+            "too_many_rows",
+            format!("At most {} users can be imported at once", MAX_IMPORT_ROWS),
+        ));
+    }
+
+    Ok(records
+        .into_iter()
+        .enumerate()
+        .map(|(index, record)| {
+            let get = |i: Option<usize>| {
+                i.and_then(|i| record.get(i))
+                    .map(|value| value.trim().to_string())
+                    .unwrap_or_default()
+            };
+            let mut errors = Vec::new();
+
+            let raw_department_id = get(department_id);
+            let department_id = if raw_department_id.is_empty() {
+                None
+            } else {
+                match Uuid::parse_str(&raw_department_id) {
+                    Ok(id) => Some(id),
+                    Err(_) => {
+                        errors.push(field_error("department_id", "format", "Department ID must be a UUID"));
+                        None
+                    }
+                }
+            };
+            let password = Some(get(password)).filter(|p| !p.is_empty());
+
+            UserImportRecord {
+                report: UserImportRow {
+                    row: index + 2,
+                    student_id: get(Some(student_id)),
+                    email: get(Some(email)),
+                    result: "error",
+                    user_id: None,
+                    generated_password: None,
+                    reason: None,
+                    errors,
+                },
+                first_name: get(Some(first_name)),
+                last_name: get(Some(last_name)),
+                department_id,
+                password,
+            }
+        })
+        .collect())
+}
+
+/// Import users from CSV (SuperAdmin, or FacultyAdmin into their own faculty's departments)
+///
+/// Columns: `student_id`, `email`, `first_name`, `last_name`, optional `department_id` and `password`.
+/// Rows without a password get a generated one. Users that already exist are skipped; any other
+/// invalid row aborts the whole import. `?validate_only=true` reports without inserting.
+pub async fn import_users(
+    State(session_state): State<SessionState>,
+    admin: FacultyAdminUser,
+    Query(params): Query<HashMap<String, String>>,
+    body: Bytes,
+) -> ApiResult<Json<Value>> {
+    let validate_only = params
+        .get("validate_only")
+        .and_then(|v| v.parse::<bool>().ok())
+        .unwrap_or(false);
+
+    // FacultyAdmin นำเข้าได้เฉพาะภาควิชาในคณะของตัวเอง
+    let faculty_scope = match admin.admin_role.admin_level {
+        AdminLevel::SuperAdmin => None,
+        _ => Some(admin.faculty_id.ok_or_else(|| {
+            ApiError::forbidden("faculty_required", "Faculty admin has no faculty assigned")
+        })?),
+    };
+
+    let mut records = parse_user_import_csv(&body)?;
+    let policy = PasswordPolicy::from_config(&session_state.app_config);
+
+    let student_ids: Vec<String> = records.iter().map(|r| r.report.student_id.clone()).collect();
+    let emails: Vec<String> = records.iter().map(|r| r.report.email.clone()).collect();
+    let department_ids: Vec<Uuid> = records.iter().filter_map(|r| r.department_id).collect();
+
+    let existing: Vec<(String, String)> = sqlx::query_as(
+        "SELECT student_id, email FROM users WHERE student_id = ANY($1) OR email = ANY($2)",
+    )
+    .bind(&student_ids)
+    .bind(&emails)
+    .fetch_all(&session_state.db_pool)
+    .await
+    .map_err(|_| ApiError::internal("Failed to check existing users"))?;
+    let existing_student_ids: HashSet<String> = existing.iter().map(|(s, _)| s.clone()).collect();
+    let existing_emails: HashSet<String> = existing.into_iter().map(|(_, e)| e).collect();
+
+    let departments: HashMap<Uuid, Uuid> =
+        sqlx::query_as::<_, (Uuid, Uuid)>("SELECT id, faculty_id FROM departments WHERE id = ANY($1)")
+            .bind(&department_ids)
+            .fetch_all(&session_state.db_pool)
+            .await
+            .map_err(|_| ApiError::internal("Failed to check departments"))?
+            .into_iter()
+            .collect();
+
+    let mut seen_student_ids = HashSet::new();
+    let mut seen_emails = HashSet::new();
+    for record in records.iter_mut() {
+        let report = &mut record.report;
+
+        if !validate_student_id(&report.student_id) {
+            report.errors.push(field_error("student_id", "format", "Student ID must be 10-13 digits"));
+        }
+        if !validate_email(&report.email) {
+            report.errors.push(field_error("email", "format", "Invalid email address"));
+        }
+        if !validate_name(&record.first_name) {
+            report.errors.push(field_error("first_name", "required", "First name is required (max 100 characters)"));
+        }
+        if !validate_name(&record.last_name) {
+            report.errors.push(field_error("last_name", "required", "Last name is required (max 100 characters)"));
+        }
+        match (record.department_id, faculty_scope) {
+            (Some(department_id), scope) => match departments.get(&department_id) {
+                None => report.errors.push(field_error("department_id", "exists", "Department not found")),
+                Some(faculty_id) if scope.is_some_and(|scope| scope != *faculty_id) => report.errors.push(
+                    field_error("department_id", "faculty_scope", "Department belongs to another faculty"),
+                ),
+                Some(_) => {}
+            },
+            (None, Some(_)) => report.errors.push(field_error(
+                "department_id",
+                "required",
+                "Faculty admins must assign a department in their faculty",
+            )),
+            (None, None) => {}
+        }
+        if let Some(password) = &record.password {
+            if let Err(error) = validate_password(password, &policy) {
+                report.errors.push(error);
+            }
+        }
+
+        if !seen_student_ids.insert(report.student_id.clone()) || !seen_emails.insert(report.email.clone()) {
+            report.errors.push(field_error("row", "duplicate", "Student ID or email appears more than once in the file"));
+        }
+
+        if !report.errors.is_empty() {
+            continue;
+        }
+        if existing_student_ids.contains(&report.student_id) || existing_emails.contains(&report.email) {
+            report.result = "skipped";
+            report.reason = Some("User with this student ID or email already exists".to_string());
+            continue;
+        }
+        report.result = if validate_only { "valid" } else { "created" };
+    }
+
+    let failed = records.iter().filter(|r| r.report.result == "error").count();
+    if failed > 0 || validate_only {
+        let results: Vec<UserImportRow> = records.into_iter().map(|r| r.report).collect();
+        let summary = json!({
+            "validate_only": validate_only,
+            "total_rows": results.len(),
+            "created": 0,
+            "valid": results.iter().filter(|r| r.result == "valid").count(),
+            "skipped": results.iter().filter(|r| r.result == "skipped").count(),
+            "failed": failed,
+            "results": results
+        });
+
+        if failed > 0 {
+            return Err(ApiError::bad_request(
+                "import_validation_failed",
+                format!("{} row(s) failed validation; no users were imported", failed),
+            )
+            .with("data", summary));
+        }
+
+        let response = json!({
+            "status": "success",
+            "data": summary,
+            "message": "Import validated; no users were created"
+        });
+        return Ok(Json(response));
+    }
+
+    // Password hashing is CPU-bound; hash off the async runtime, a few rows at a time
+    let hash_config = &session_state.app_config;
+    let parallelism = std::thread::available_parallelism().map_or(2, |n| n.get());
+    let to_create: Vec<usize> = records
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| r.report.result == "created")
+        .map(|(i, _)| i)
+        .collect();
+    let passwords: Vec<(usize, String, bool)> = to_create
+        .iter()
+        .map(|&i| match &records[i].password {
+            Some(password) => (i, password.clone(), false),
+            None => (i, generate_password(&policy), true),
+        })
+        .collect();
+    let hashed: Vec<(usize, String, Option<String>)> = futures::stream::iter(passwords)
+        .map(|(i, password, generated)| async move {
+            let hash = hash_password(hash_config, &password)
+                .await
+                .map_err(|_| ApiError::internal("Failed to hash password"))?;
+            Ok::<_, ApiError>((i, hash, generated.then_some(password)))
+        })
+        .buffered(parallelism)
+        .try_collect()
+        .await?;
+
+    let mut tx = session_state
+        .db_pool
+        .begin()
+        .await
+        .map_err(|_| ApiError::internal("Failed to start transaction"))?;
+
+    for (i, password_hash, generated_password) in hashed {
+        let record = &mut records[i];
+        let user_id = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            INSERT INTO users (student_id, email, password_hash, first_name, last_name, qr_secret, department_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT DO NOTHING
+            RETURNING id
+            "#,
+        )
+        .bind(&record.report.student_id)
+        .bind(&record.report.email)
+        .bind(&password_hash)
+        .bind(&record.first_name)
+        .bind(&record.last_name)
+        .bind(generate_secret_key())
+        .bind(record.department_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to import user {}: {}", record.report.student_id, e);
+            ApiError::internal("Failed to import users")
+        })?;
+
+        match user_id {
+            Some(user_id) => {
+                record.report.user_id = Some(user_id);
+                record.report.generated_password = generated_password;
+            }
+            // Created concurrently since the duplicate check above
+            None => {
+                record.report.result = "skipped";
+                record.report.reason = Some("User with this student ID or email already exists".to_string());
+            }
+        }
+    }
+
+    tx.commit()
+        .await
+        .map_err(|_| ApiError::internal("Failed to import users"))?;
+
+    let results: Vec<UserImportRow> = records.into_iter().map(|r| r.report).collect();
+    let created = results.iter().filter(|r| r.result == "created").count();
+    let skipped = results.iter().filter(|r| r.result == "skipped").count();
+
+    record_audit_log(
+        &session_state.db_pool,
+        NewAuditLog::new(admin.session_user.user_id, "import_users", "user", "bulk")
+            .details(json!({ "created": created, "skipped": skipped, "faculty_id": faculty_scope })),
+    )
+    .await;
+    invalidate_dashboard_cache(&session_state).await;
+
+    let response = json!({
+        "status": "success",
+        "data": {
+            "validate_only": false,
+            "total_rows": results.len(),
+            "created": created,
+            "valid": 0,
+            "skipped": skipped,
+            "failed": 0,
+            "results": results
+        },
+        "message": format!("Imported {} user(s), skipped {}", created, skipped)
+    });
+    Ok(Json(response))
+}
+
+/// Activities a user registered for but never attended, newest first, with their registration
+/// count so chronic no-shows stand out. Faculty admins only see users of their own faculty.
+pub async fn get_user_no_shows(
+    State(session_state): State<SessionState>,
+    admin: AdminUser,
+    Path(user_id): Path<Uuid>,
+) -> ApiResult<Json<Value>> {
+    ensure_user_in_admin_scope(&session_state, &admin.session_user, user_id).await?;
+
+    let rows = sqlx::query(&format!(
+        r#"
+        SELECT p.id as participation_id, p.activity_id, a.title, p.registered_at,
+               {} as start_time
+        FROM participations p
+        JOIN activities a ON a.id = p.activity_id
+        WHERE p.user_id = $1 AND p.status = 'no_show'
+        ORDER BY a.start_date DESC, a.start_time_only DESC
+        "#,
+        session_state.app_config.activity_time_zone().start_sql("a")
+    ))
+    .bind(user_id)
+    .fetch_all(&session_state.db_pool)
+    .await
+    .map_err(|_| ApiError::internal("Failed to retrieve no-shows"))?;
+
+    let total_registrations: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM participations WHERE user_id = $1 AND status NOT IN ('waitlisted', 'cancelled')",
+    )
+    .bind(user_id)
+    .fetch_one(&session_state.db_pool)
+    .await
+    .map_err(|_| ApiError::internal("Failed to retrieve no-shows"))?;
+
+    let no_shows: Vec<Value> = rows
+        .iter()
+        .map(|row| {
+            json!({
+                "participation_id": row.get::<Uuid, _>("participation_id"),
+                "activity_id": row.get::<Uuid, _>("activity_id"),
+                "activity_title": row.get::<String, _>("title"),
+                "start_time": row.get::<DateTime<Utc>, _>("start_time"),
+                "registered_at": row.get::<DateTime<Utc>, _>("registered_at"),
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {
+            "user_id": user_id,
+            "no_show_count": no_shows.len(),
+            "total_registrations": total_registrations,
+            "no_shows": no_shows
+        }
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::session::SessionUser;
+    use crate::test_support::test_state;
+
+    #[test]
+    fn test_import_csv_requires_columns() {
+        match parse_user_import_csv(b"student_id,email,first_name\n6401234567,a@test.local,A\n") {
+            Err(ApiError::BadRequest(detail)) => {
+                assert_eq!(detail.code, "missing_columns");
+                assert_eq!(detail.extra["missing_columns"], json!(["last_name"]));
+            }
+            other => panic!("expected missing_columns, got {:?}", other.map(|r| r.len())),
+        }
+
+        let records = parse_user_import_csv(
+            b"Email,Last_Name,First_Name,Student_ID\r\nb@test.local,B,Bee,6401234568\r\n",
+        )
+        .unwrap();
+        assert_eq!(records[0].report.row, 2);
+        assert_eq!(records[0].report.student_id, "6401234568");
+        assert_eq!(records[0].first_name, "Bee");
+        assert!(records[0].password.is_none());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_import_users_dry_run_then_import() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+        let admin_id = Uuid::new_v4();
+        let admin = || FacultyAdminUser {
+            session_user: SessionUser {
+                user_id: admin_id,
+                student_id: "admin".to_string(),
+                email: "admin@test.local".to_string(),
+                first_name: "Test".to_string(),
+                last_name: "Admin".to_string(),
+                department_id: None,
+                admin_role: None,
+                session_id: Uuid::new_v4().to_string(),
+                permissions: Vec::new(),
+                faculty_id: None,
+                is_impersonated: false,
+                impersonated_by: None,
+            },
+            admin_role: AdminRole {
+                id: Uuid::new_v4(),
+                user_id: admin_id,
+                admin_level: AdminLevel::SuperAdmin,
+                faculty_id: None,
+                permissions: Vec::new(),
+                is_enabled: true,
+                created_at: None,
+                updated_at: None,
+            },
+            faculty_id: None,
+        };
+
+        // Student IDs must be digits, so derive a unique 12-digit prefix
+        let tag = format!("{:011}", Uuid::new_v4().as_u128() % 100_000_000_000);
+        let existing = format!("{}0", tag);
+        sqlx::query(
+            "INSERT INTO users (student_id, email, password_hash, first_name, last_name, qr_secret)
+             VALUES ($1, $2, 'x', 'Old', 'User', $3)",
+        )
+        .bind(&existing)
+        .bind(format!("{}@import.test", existing))
+        .bind(Uuid::new_v4().to_string())
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let csv = format!(
+            "student_id,email,first_name,last_name,password\n\
+             {tag}0,{tag}0@import.test,Old,User,\n\
+             {tag}1,{tag}1@import.test,New,One,Str0ngPassw0rd!\n\
+             {tag}2,{tag}2@import.test,New,Two,\n"
+        );
+        let invalid = format!("{}\n{tag}3,not-an-email,Bad,Row,\n", csv.trim_end());
+        let query = |validate_only: bool| {
+            Query(HashMap::from([("validate_only".to_string(), validate_only.to_string())]))
+        };
+
+        let rejected = import_users(State(state.clone()), admin(), query(false), Bytes::from(invalid)).await;
+        let dry_run = import_users(State(state.clone()), admin(), query(true), Bytes::from(csv.clone()))
+            .await
+            .map(|Json(body)| body);
+        let count_after_dry_run: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE email LIKE $1")
+                .bind(format!("{}%@import.test", tag))
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        let imported = import_users(State(state.clone()), admin(), query(false), Bytes::from(csv))
+            .await
+            .map(|Json(body)| body);
+        let count_after_import: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE email LIKE $1")
+                .bind(format!("{}%@import.test", tag))
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+
+        sqlx::query("DELETE FROM users WHERE email LIKE $1")
+            .bind(format!("{}%@import.test", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        match rejected {
+            Err(ApiError::BadRequest(detail)) => {
+                assert_eq!(detail.code, "import_validation_failed");
+                assert_eq!(detail.extra["data"]["failed"], 1);
+                assert_eq!(detail.extra["data"]["results"][3]["errors"][0]["field"], "email");
+            }
+            other => panic!("expected import_validation_failed, got {:?}", other),
+        }
+
+        let dry_run = dry_run.unwrap();
+        assert_eq!(dry_run["data"]["valid"], 2);
+        assert_eq!(dry_run["data"]["skipped"], 1);
+        assert_eq!(count_after_dry_run, 1);
+
+        let imported = imported.unwrap();
+        let results = &imported["data"]["results"];
+        assert_eq!(imported["data"]["created"], 2);
+        assert_eq!(results[0]["result"], "skipped");
+        assert_eq!(results[1]["result"], "created");
+        assert!(results[1].get("generated_password").is_none());
+        assert!(results[2]["generated_password"].is_string());
+        assert_eq!(count_after_import, 3);
+    }
+}
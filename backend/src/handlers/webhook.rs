@@ -0,0 +1,267 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::Json,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::error::{ApiError, ApiJson, ApiResult};
+use crate::middleware::session::{SessionState, SuperAdminUser};
+use crate::models::audit_log::NewAuditLog;
+use crate::models::webhook::{CreateWebhookRequest, UpdateWebhookRequest, Webhook, WebhookDelivery, WebhookEvent};
+use crate::services::audit_log::record_audit_log;
+use crate::utils::http_client::resolve_public_url;
+use crate::utils::qr::generate_secret_key;
+
+#[derive(Debug, Deserialize)]
+pub struct DeliveryLogQuery {
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// https only, and the host must resolve to public addresses (checked again on every delivery)
+async fn validate_url(url: &str) -> ApiResult<String> {
+    let url = url.trim();
+    resolve_public_url(url)
+        .await
+        .map_err(|e| ApiError::validation("invalid_webhook_url", e.to_string()))?;
+    Ok(url.to_string())
+}
+
+/// Event names must be known; duplicates are dropped
+fn validate_event_types(event_types: &[String]) -> ApiResult<Vec<String>> {
+    if event_types.is_empty() {
+        return Err(ApiError::validation("invalid_event_types", "At least one event type is required"));
+    }
+    let mut validated: Vec<String> = Vec::new();
+    for raw in event_types {
+        let event = raw
+            .trim()
+            .parse::<WebhookEvent>()
+            .map_err(|e| ApiError::validation("invalid_event_types", e.to_string()))?;
+        if !validated.iter().any(|existing| existing == event.as_str()) {
+            validated.push(event.as_str().to_string());
+        }
+    }
+    Ok(validated)
+}
+
+fn available_events() -> Vec<&'static str> {
+    WebhookEvent::ALL.iter().map(|event| event.as_str()).collect()
+}
+
+async fn fetch_webhook(session_state: &SessionState, id: Uuid) -> ApiResult<Webhook> {
+    sqlx::query_as::<_, Webhook>("SELECT * FROM webhooks WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&session_state.db_pool)
+        .await
+        .map_err(|_| ApiError::internal("Failed to load webhook"))?
+        .ok_or_else(|| ApiError::not_found("webhook_not_found", "Webhook not found"))
+}
+
+// GET /api/admin/webhooks
+pub async fn list_webhooks(
+    State(session_state): State<SessionState>,
+    _admin: SuperAdminUser,
+) -> ApiResult<Json<Value>> {
+    let webhooks = sqlx::query_as::<_, Webhook>("SELECT * FROM webhooks ORDER BY created_at DESC")
+        .fetch_all(&session_state.db_pool)
+        .await
+        .map_err(|_| ApiError::internal("Failed to load webhooks"))?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {
+            "webhooks": webhooks,
+            "available_events": available_events(),
+        }
+    })))
+}
+
+// POST /api/admin/webhooks - the signing secret is only returned here
+pub async fn create_webhook(
+    State(session_state): State<SessionState>,
+    admin: SuperAdminUser,
+    ApiJson(request): ApiJson<CreateWebhookRequest>,
+) -> ApiResult<Json<Value>> {
+    let url = validate_url(&request.url).await?;
+    let event_types = validate_event_types(&request.event_types)?;
+    let secret = generate_secret_key();
+
+    let webhook = sqlx::query_as::<_, Webhook>(
+        r#"
+        INSERT INTO webhooks (url, secret, event_types, description, is_active, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING *
+        "#,
+    )
+    .bind(&url)
+    .bind(&secret)
+    .bind(&event_types)
+    .bind(&request.description)
+    .bind(request.is_active.unwrap_or(true))
+    .bind(admin.session_user.user_id)
+    .fetch_one(&session_state.db_pool)
+    .await
+    .map_err(|_| ApiError::internal("Failed to create webhook"))?;
+
+    record_audit_log(
+        &session_state.db_pool,
+        NewAuditLog::new(admin.session_user.user_id, "create_webhook", "webhook", webhook.id)
+            .details(json!({ "url": url, "event_types": event_types })),
+    )
+    .await;
+
+    let mut data = json!(webhook);
+    data["secret"] = json!(secret);
+    Ok(Json(json!({
+        "status": "success",
+        "data": data,
+        "message": "Webhook created; store the secret now, it will not be shown again"
+    })))
+}
+
+// GET /api/admin/webhooks/{id}
+pub async fn get_webhook(
+    State(session_state): State<SessionState>,
+    _admin: SuperAdminUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<Value>> {
+    let webhook = fetch_webhook(&session_state, id).await?;
+    Ok(Json(json!({ "status": "success", "data": webhook })))
+}
+
+// PUT /api/admin/webhooks/{id}
+pub async fn update_webhook(
+    State(session_state): State<SessionState>,
+    admin: SuperAdminUser,
+    Path(id): Path<Uuid>,
+    ApiJson(request): ApiJson<UpdateWebhookRequest>,
+) -> ApiResult<Json<Value>> {
+    let url = match request.url.as_deref() {
+        Some(url) => Some(validate_url(url).await?),
+        None => None,
+    };
+    let event_types = request.event_types.as_deref().map(validate_event_types).transpose()?;
+
+    let webhook = sqlx::query_as::<_, Webhook>(
+        r#"
+        UPDATE webhooks
+        SET url = COALESCE($2, url),
+            event_types = COALESCE($3, event_types),
+            description = COALESCE($4, description),
+            is_active = COALESCE($5, is_active),
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .bind(&url)
+    .bind(&event_types)
+    .bind(&request.description)
+    .bind(request.is_active)
+    .fetch_optional(&session_state.db_pool)
+    .await
+    .map_err(|_| ApiError::internal("Failed to update webhook"))?
+    .ok_or_else(|| ApiError::not_found("webhook_not_found", "Webhook not found"))?;
+
+    record_audit_log(
+        &session_state.db_pool,
+        NewAuditLog::new(admin.session_user.user_id, "update_webhook", "webhook", webhook.id).details(json!({
+            "url": url,
+            "event_types": event_types,
+            "is_active": request.is_active,
+        })),
+    )
+    .await;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": webhook,
+        "message": "Webhook updated successfully"
+    })))
+}
+
+// DELETE /api/admin/webhooks/{id} - the delivery log goes with it
+pub async fn delete_webhook(
+    State(session_state): State<SessionState>,
+    admin: SuperAdminUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<Value>> {
+    let webhook = fetch_webhook(&session_state, id).await?;
+    sqlx::query("DELETE FROM webhooks WHERE id = $1")
+        .bind(id)
+        .execute(&session_state.db_pool)
+        .await
+        .map_err(|_| ApiError::internal("Failed to delete webhook"))?;
+
+    record_audit_log(
+        &session_state.db_pool,
+        NewAuditLog::new(admin.session_user.user_id, "delete_webhook", "webhook", id)
+            .details(json!({ "url": webhook.url })),
+    )
+    .await;
+
+    Ok(Json(json!({
+        "status": "success",
+        "message": "Webhook deleted successfully"
+    })))
+}
+
+// GET /api/admin/webhooks/{id}/deliveries?status=&limit=&offset=
+pub async fn list_webhook_deliveries(
+    State(session_state): State<SessionState>,
+    _admin: SuperAdminUser,
+    Path(id): Path<Uuid>,
+    Query(query): Query<DeliveryLogQuery>,
+) -> ApiResult<Json<Value>> {
+    fetch_webhook(&session_state, id).await?;
+    if let Some(status) = query.status.as_deref() {
+        if !["pending", "delivered", "failed"].contains(&status) {
+            return Err(ApiError::validation(
+                "invalid_status",
+                "status must be pending, delivered or failed",
+            ));
+        }
+    }
+    let limit = query.limit.unwrap_or(50).clamp(1, 200);
+    let offset = query.offset.unwrap_or(0).max(0);
+
+    let deliveries = sqlx::query_as::<_, WebhookDelivery>(
+        r#"
+        SELECT * FROM webhook_deliveries
+        WHERE webhook_id = $1 AND ($2::TEXT IS NULL OR status = $2)
+        ORDER BY created_at DESC
+        LIMIT $3 OFFSET $4
+        "#,
+    )
+    .bind(id)
+    .bind(&query.status)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(&session_state.db_pool)
+    .await
+    .map_err(|_| ApiError::internal("Failed to load webhook deliveries"))?;
+
+    let total: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM webhook_deliveries WHERE webhook_id = $1 AND ($2::TEXT IS NULL OR status = $2)",
+    )
+    .bind(id)
+    .bind(&query.status)
+    .fetch_one(&session_state.db_pool)
+    .await
+    .map_err(|_| ApiError::internal("Failed to load webhook deliveries"))?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {
+            "deliveries": deliveries,
+            "total": total,
+            "limit": limit,
+            "offset": offset,
+        }
+    })))
+}
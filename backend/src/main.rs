@@ -1,57 +1,38 @@
 mod config;
 mod database;
+mod error;
 mod handlers;
 mod middleware;
 mod models;
 mod routes;
 mod services;
+#[cfg(test)]
+mod test_support;
 mod utils;
 
 use axum::{
-    http::{HeaderName, HeaderValue, Method, Request, Uri},
-    middleware::Next,
-    response::Response,
-    Router,
+    body::Body,
+    extract::DefaultBodyLimit,
+    http::{HeaderName, HeaderValue, Method, Request},
+    Router, ServiceExt,
 };
 use std::net::SocketAddr;
 use std::sync::Arc;
-use tower::ServiceBuilder;
+use tower::{Layer, ServiceBuilder};
 use tower_cookies::CookieManagerLayer;
 use tower_http::cors::CorsLayer;
+use tower_http::limit::RequestBodyLimitLayer;
 use tower_http::trace::TraceLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use crate::config::Config;
 use crate::database::Database;
+use crate::middleware::metrics::HttpMetricsLayer;
+use crate::middleware::normalize_uri::normalize_uri_middleware;
+use crate::middleware::request_id::{make_request_span, request_id_middleware, REQUEST_ID_HEADER};
 use crate::routes::create_routes;
 use crate::services::background_tasks::BackgroundTaskManager;
 
-// Middleware to normalize URIs and fix double slash issues
-async fn normalize_uri_middleware(
-    mut request: Request<axum::body::Body>,
-    next: Next,
-) -> Response {
-    let original_uri = request.uri().clone();
-    let path = original_uri.path();
-    
-    // Normalize double slashes in path
-    if path.contains("//") {
-        let normalized_path = if path.starts_with("//") {
-            // For paths like //api/admin/auth/me -> /api/admin/auth/me
-            path.chars().skip(1).collect::<String>()
-        } else {
-            // For paths like /api//admin/auth/me -> /api/admin/auth/me
-            path.replace("//", "/")
-        };
-        
-        if let Ok(new_uri) = normalized_path.parse::<Uri>() {
-            *request.uri_mut() = new_uri;
-        }
-    }
-    
-    next.run(request).await
-}
-
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // Initialize tracing
@@ -66,27 +47,48 @@ async fn main() -> anyhow::Result<()> {
     // Load configuration
     let config = Config::from_env()?;
 
+    // Optional IP -> location lookups for sessions and login history
+    crate::services::geoip::init_geoip(config.geoip_database_path.as_deref());
+
+    // Certificates embed this font; parse it once instead of on every download
+    crate::handlers::certificate::init_certificate_font(config.certificate_font_path.as_deref())?;
+
     // Initialize database
     let database = Database::new(&config.database_url).await?;
 
     // Create database and run migrations (auto-setup on first run)
     database.create_and_migrate(&config.database_url).await?;
 
+    // Fail fast on a typo instead of erroring on every status update tick
+    if let Some(timezone) = &config.activity_timezone {
+        let known: bool =
+            sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM pg_timezone_names WHERE name = $1)")
+                .bind(timezone)
+                .fetch_one(&database.pool)
+                .await?;
+        anyhow::ensure!(known, "Unknown ACTIVITY_TIMEZONE: {}", timezone);
+    }
+
     // Initialize Redis connection
     let redis_client = redis::Client::open(config.redis_url.clone())?;
     let _redis_manager = redis::aio::ConnectionManager::new(redis_client).await?;
 
     // Build Redis session store
-    let redis_store = Arc::new(crate::services::RedisSessionStore::new(&config.redis_url)?);
+    let session_store = Arc::new(
+        crate::services::RedisSessionStore::new(&config.redis_url)?
+            .with_retry_attempts(config.redis_retry_attempts),
+    );
 
     // Build session state
     let session_state = crate::middleware::session::SessionState {
-        redis_store: redis_store.clone(),
+        session_store,
+        redis_health: Arc::new(crate::services::RedisHealth::default()),
         db_pool: database.pool.clone(),
-        config: crate::services::SessionConfig::default(),
+        config: crate::services::SessionConfig::from_config(&config),
+        app_config: Arc::new(config.clone()),
     };
     // Start background tasks
-    let background_task_manager = BackgroundTaskManager::new(session_state.clone());
+    let mut background_task_manager = BackgroundTaskManager::new(session_state.clone());
     background_task_manager.start_all_tasks().await;
 
     // Build CORS allowed origins from env or defaults
@@ -116,10 +118,15 @@ async fn main() -> anyhow::Result<()> {
     // Build the application with session middleware
     let app = Router::new()
         .merge(create_routes())
-        .layer(axum::middleware::from_fn(normalize_uri_middleware))
+        // MAX_REQUEST_BODY_BYTES is the only body cap; axum's 2 MiB extractor default is turned off
+        .layer(DefaultBodyLimit::disable())
+        .layer(RequestBodyLimitLayer::new(config.max_request_body_bytes))
+        .layer(axum::middleware::map_response(crate::error::body_limit_response))
         .layer(
             ServiceBuilder::new()
-                .layer(TraceLayer::new_for_http())
+                .layer(axum::middleware::from_fn(request_id_middleware))
+                .layer(TraceLayer::new_for_http().make_span_with(make_request_span))
+                .layer(HttpMetricsLayer)
                 .layer(CookieManagerLayer::new())
                 .layer(axum::middleware::from_fn_with_state(
                     session_state.clone(),
@@ -143,12 +150,16 @@ async fn main() -> anyhow::Result<()> {
                             HeaderName::from_static("x-screen-resolution"),
                             HeaderName::from_static("accept-language"),
                             HeaderName::from_static("x-device-type"),
-                            HeaderName::from_static("x-device-info")
+                            HeaderName::from_static("x-device-info"),
+                            HeaderName::from_static(REQUEST_ID_HEADER)
                         ])
+                        .expose_headers([HeaderName::from_static(REQUEST_ID_HEADER)])
                         .allow_credentials(true),
                 ),
         )
         .with_state(session_state);
+    // Wraps the router instead of using Router::layer, which would run after routing
+    let app = axum::middleware::from_fn(normalize_uri_middleware).layer(app);
 
     let addr = SocketAddr::from(([0, 0, 0, 0], config.port));
     tracing::info!("Starting Trackivity server on {}", addr);
@@ -156,7 +167,48 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("Redis session store configured");
 
     let listener = tokio::net::TcpListener::bind(&addr).await?;
-    axum::serve(listener, app).await?;
+    // Connect info gives handlers the peer address; see utils::get_client_info
+    axum::serve(
+        listener,
+        ServiceExt::<Request<Body>>::into_make_service_with_connect_info::<SocketAddr>(app),
+    )
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
+
+    // In-flight requests have drained; stop background work before exiting
+    tracing::info!("HTTP server stopped, shutting down background tasks");
+    background_task_manager.shutdown().await;
 
     Ok(())
 }
+
+// Resolves on Ctrl-C or SIGTERM (sent by docker/systemd on stop)
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => tracing::info!("Received Ctrl-C, starting graceful shutdown"),
+        _ = terminate => tracing::info!("Received SIGTERM, starting graceful shutdown"),
+    }
+}
@@ -1,2 +1,4 @@
-pub mod auth;
+pub mod metrics;
+pub mod normalize_uri;
+pub mod request_id;
 pub mod session;
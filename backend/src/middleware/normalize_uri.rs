@@ -0,0 +1,97 @@
+use axum::{
+    body::Body,
+    http::{uri::PathAndQuery, Request, Uri},
+    middleware::Next,
+    response::Response,
+};
+
+/// Collapse repeated slashes and drop a single trailing slash (except for `/`),
+/// keeping the query string. Returns `None` when the URI is already normal.
+fn normalize_uri(uri: &Uri) -> Option<Uri> {
+    let path = uri.path();
+
+    let mut normalized = String::with_capacity(path.len());
+    for c in path.chars() {
+        if c == '/' && normalized.ends_with('/') {
+            continue;
+        }
+        normalized.push(c);
+    }
+    if normalized.len() > 1 && normalized.ends_with('/') {
+        normalized.pop();
+    }
+
+    if normalized == path {
+        return None;
+    }
+
+    if let Some(query) = uri.query() {
+        normalized.push('?');
+        normalized.push_str(query);
+    }
+
+    let mut parts = uri.clone().into_parts();
+    parts.path_and_query = Some(normalized.parse::<PathAndQuery>().ok()?);
+    Uri::from_parts(parts).ok()
+}
+
+/// Normalize URIs like `//api/admin/auth/me`, `/api//activities?limit=10` or `/api/activities/`
+/// before routing.
+///
+/// Middleware added with `Router::layer` runs after routing, so this has to wrap the whole
+/// router (see `main`) to have any effect.
+pub async fn normalize_uri_middleware(mut request: Request<Body>, next: Next) -> Response {
+    if let Some(uri) = normalize_uri(request.uri()) {
+        *request.uri_mut() = uri;
+    }
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::RawQuery, http::StatusCode, routing::get, Router};
+    use tower::{Layer, ServiceExt};
+
+    fn normalized(uri: &str) -> Option<String> {
+        normalize_uri(&uri.parse().unwrap()).map(|uri| uri.to_string())
+    }
+
+    #[test]
+    fn test_normalize_uri() {
+        assert_eq!(normalized("//api/admin/auth/me").as_deref(), Some("/api/admin/auth/me"));
+        assert_eq!(normalized("/api//activities?limit=10").as_deref(), Some("/api/activities?limit=10"));
+        assert_eq!(normalized("/api/activities/").as_deref(), Some("/api/activities"));
+        assert_eq!(normalized("/api/activities/?limit=10").as_deref(), Some("/api/activities?limit=10"));
+        assert_eq!(normalized("/api/activities?limit=10"), None);
+        assert_eq!(normalized("/"), None);
+    }
+
+    async fn get_status_and_query(uri: &str) -> (StatusCode, String) {
+        let router = Router::new().route(
+            "/api/activities",
+            get(|RawQuery(query): RawQuery| async move { query.unwrap_or_default() }),
+        );
+        let app = axum::middleware::from_fn(normalize_uri_middleware).layer(router);
+
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, String::from_utf8(bytes.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_double_slash_keeps_query_string() {
+        assert_eq!(
+            get_status_and_query("/api//activities?limit=10").await,
+            (StatusCode::OK, "limit=10".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_trailing_slash_matches_route() {
+        assert_eq!(get_status_and_query("/api/activities/").await, (StatusCode::OK, String::new()));
+    }
+}
@@ -0,0 +1,149 @@
+use axum::{
+    body::Body,
+    http::{header, HeaderValue, Request},
+    middleware::Next,
+    response::Response,
+};
+use serde_json::Value;
+use tracing::Span;
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest client-supplied request id that is honoured; longer ones are replaced
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+/// Id correlating a request with its log lines, stored in the request extensions
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestId(pub String);
+
+/// Use the caller's id when it is short, printable ASCII, so a proxy or the frontend can
+/// correlate its own logs; anything else gets a fresh UUID
+fn incoming_request_id(request: &Request<Body>) -> Option<String> {
+    let value = request.headers().get(REQUEST_ID_HEADER)?.to_str().ok()?.trim();
+    let valid = !value.is_empty()
+        && value.len() <= MAX_REQUEST_ID_LENGTH
+        && value.bytes().all(|b| b.is_ascii_graphic());
+    valid.then(|| value.to_string())
+}
+
+/// Assigns an `x-request-id`, echoes it on the response and adds it as `request_id`
+/// to the standard error envelope so users can quote it in bug reports.
+///
+/// Must run outside `TraceLayer` so `make_request_span` can read the id.
+pub async fn request_id_middleware(mut request: Request<Body>, next: Next) -> Response {
+    let request_id = incoming_request_id(&request).unwrap_or_else(|| Uuid::new_v4().to_string());
+    request.extensions_mut().insert(RequestId(request_id.clone()));
+
+    let response = next.run(request).await;
+    let mut response = if response.status().is_client_error() || response.status().is_server_error() {
+        add_request_id_to_error(response, &request_id).await
+    } else {
+        response
+    };
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+async fn add_request_id_to_error(response: Response, request_id: &str) -> Response {
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|value| value.as_bytes().starts_with(b"application/json"));
+    if !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::warn!("Failed to read error response body: {}", e);
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+
+    let body = match serde_json::from_slice::<Value>(&bytes) {
+        Ok(Value::Object(mut map)) if map.get("status").and_then(Value::as_str) == Some("error") => {
+            map.insert("request_id".to_string(), Value::String(request_id.to_string()));
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Body::from(Value::Object(map).to_string())
+        }
+        _ => Body::from(bytes),
+    };
+    Response::from_parts(parts, body)
+}
+
+/// `TraceLayer` span carrying the request id, so every log line of the request includes it
+pub fn make_request_span(request: &Request<Body>) -> Span {
+    let request_id = request
+        .extensions()
+        .get::<RequestId>()
+        .map(|id| id.0.as_str())
+        .unwrap_or("-");
+
+    tracing::info_span!(
+        "request",
+        method = %request.method(),
+        uri = %request.uri(),
+        request_id = %request_id,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ApiError;
+    use axum::{http::StatusCode, routing::get, Router};
+    use tower::ServiceExt;
+
+    async fn call(request_id: Option<&str>, uri: &str) -> (StatusCode, String, Option<Value>) {
+        let app = Router::new()
+            .route("/ok", get(|| async { "ok" }))
+            .route(
+                "/fail",
+                get(|| async { ApiError::not_found("activity_not_found", "Activity not found") }),
+            )
+            .layer(axum::middleware::from_fn(request_id_middleware));
+
+        let mut request = Request::builder().uri(uri);
+        if let Some(request_id) = request_id {
+            request = request.header(REQUEST_ID_HEADER, request_id);
+        }
+        let response = app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+
+        let status = response.status();
+        let header = response.headers()[REQUEST_ID_HEADER].to_str().unwrap().to_string();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, header, serde_json::from_slice(&bytes).ok())
+    }
+
+    #[tokio::test]
+    async fn test_generates_request_id() {
+        let (status, header, _) = call(None, "/ok").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(Uuid::parse_str(&header).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_honours_incoming_request_id() {
+        let (_, header, _) = call(Some("edge-1234"), "/ok").await;
+        assert_eq!(header, "edge-1234");
+
+        let (_, header, _) = call(Some(&"x".repeat(MAX_REQUEST_ID_LENGTH + 1)), "/ok").await;
+        assert!(Uuid::parse_str(&header).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_error_envelope_includes_request_id() {
+        let (status, header, body) = call(Some("edge-5678"), "/fail").await;
+        let body = body.unwrap();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(header, "edge-5678");
+        assert_eq!(body["code"], "activity_not_found");
+        assert_eq!(body["request_id"], "edge-5678");
+    }
+}
@@ -4,25 +4,34 @@ use axum::{
     extract::{Request, State},
     http::{HeaderMap, StatusCode},
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Json, Response},
     RequestPartsExt,
 };
+use serde_json::json;
 use sqlx::PgPool;
+use std::marker::PhantomData;
 use std::sync::Arc;
 use tower_cookies::{Cookie, Cookies};
 use uuid::Uuid;
 
+use crate::config::Config;
+use crate::error::ApiError;
 use crate::models::admin_role::{AdminLevel, AdminRole};
+use crate::models::audit_log::NewAuditLog;
 use crate::models::session::{Permission, SessionUser, SessionValidation};
 use crate::models::user::User;
-use crate::services::{RedisSessionStore, SessionConfig};
+use crate::services::audit_log::record_audit_log;
+use crate::services::{RedisHealth, SessionConfig, SessionStore};
+use crate::utils::{get_client_info, PeerAddr};
 
 // Application state for session management
 #[derive(Clone)]
 pub struct SessionState {
-    pub redis_store: Arc<RedisSessionStore>,
+    pub session_store: Arc<dyn SessionStore>,
+    pub redis_health: Arc<RedisHealth>,
     pub db_pool: PgPool,
     pub config: SessionConfig,
+    pub app_config: Arc<Config>,
 }
 
 // Session middleware for validating and extracting session info
@@ -35,11 +44,17 @@ pub async fn session_middleware(
 ) -> Result<Response, StatusCode> {
     // Extract session ID from cookie or header
     let session_id = extract_session_id(&cookies, &headers);
+    let mut impersonation = None;
 
     if let Some(session_id) = session_id {
+        let peer = PeerAddr::from_extensions(request.extensions());
+        let (client_ip, _) = get_client_info(&headers, peer, &session_state.app_config.trusted_proxies);
         // Validate session and get user data
-        match validate_and_get_session_user(&session_state, &session_id).await {
+        match validate_and_get_session_user(&session_state, &session_id, client_ip.as_deref()).await {
             Ok(SessionValidation::Valid(session_user)) => {
+                if let Some(admin_id) = session_user.impersonated_by {
+                    impersonation = Some((admin_id, session_user.user_id, session_id.clone()));
+                }
                 // Add session user to request extensions
                 request.extensions_mut().insert(session_user);
                 request.extensions_mut().insert(session_id);
@@ -48,6 +63,22 @@ pub async fn session_middleware(
                 // Clear expired session cookie
                 clear_session_cookie(&cookies);
             }
+            Ok(SessionValidation::IdleTimeout) => {
+                clear_session_cookie(&cookies);
+                return Ok(ApiError::unauthorized(
+                    "session_idle_timeout",
+                    "Session timed out due to inactivity",
+                )
+                .into_response());
+            }
+            Ok(SessionValidation::IpMismatch) => {
+                clear_session_cookie(&cookies);
+                return Ok(ApiError::unauthorized(
+                    "session_ip_mismatch",
+                    "Session is bound to a different IP address; please log in again",
+                )
+                .into_response());
+            }
             Ok(SessionValidation::Revoked) => {
                 // Session was revoked by admin
                 clear_session_cookie(&cookies);
@@ -60,7 +91,27 @@ pub async fn session_middleware(
         }
     }
 
-    Ok(next.run(request).await)
+    let Some((admin_id, user_id, session_id)) = impersonation else {
+        return Ok(next.run(request).await);
+    };
+
+    // Every change made while impersonating is attributed to the super admin behind it
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let response = next.run(request).await;
+    if !method.is_safe() {
+        record_audit_log(
+            &session_state.db_pool,
+            NewAuditLog::new(admin_id, "impersonated_request", "user", user_id).details(json!({
+                "method": method.as_str(),
+                "path": path,
+                "status": response.status().as_u16(),
+                "session_id": session_id,
+            })),
+        )
+        .await;
+    }
+    Ok(response)
 }
 
 // Permission-based route guard middleware
@@ -75,7 +126,7 @@ pub fn require_permission(
     move |session_user: SessionUser, request: Request, next: Next| {
         let permission = permission.clone();
         Box::new(async move {
-            if has_permission(&session_user, &permission) {
+            if session_user.has_permission(&permission) {
                 Ok(next.run(request).await)
             } else {
                 Err(StatusCode::FORBIDDEN)
@@ -169,27 +220,62 @@ pub fn extract_session_id(cookies: &Cookies, headers: &HeaderMap) -> Option<Stri
     None
 }
 
+/// `client_ip` is the requester's IP, checked against the login IP when sessions are bound to it
 pub async fn validate_and_get_session_user(
     session_state: &SessionState,
     session_id: &str,
+    client_ip: Option<&str>,
 ) -> Result<SessionValidation, anyhow::Error> {
     // Get session from Redis
-    let session = match session_state.redis_store.get_session(session_id).await? {
+    let session = match session_state
+        .redis_health
+        .observe(session_state.session_store.get_session(session_id).await)?
+    {
         Some(session) => session,
         None => return Ok(SessionValidation::Invalid),
     };
 
-    // Check if session is expired
-    if session.expires_at <= chrono::Utc::now() {
-        session_state.redis_store.delete_session(session_id).await?;
+    // Check if session is expired, either by expires_at or by the absolute lifetime cap
+    let now = chrono::Utc::now();
+    if session.expires_at <= now || session_state.config.absolute_expiry(session.created_at) <= now {
+        session_state.session_store.delete_session(session_id).await?;
         return Ok(SessionValidation::Expired);
     }
 
+    if session_state.config.is_idle(session.last_accessed, now) {
+        session_state.session_store.delete_session(session_id).await?;
+        return Ok(SessionValidation::IdleTimeout);
+    }
+
     // Check if session is active
     if !session.is_active {
         return Ok(SessionValidation::Revoked);
     }
 
+    // A cookie replayed from elsewhere loses the session for good, so the thief can't retry
+    if !session_state
+        .config
+        .ip_binding_allows(session.ip_address.as_deref(), client_ip)
+    {
+        tracing::warn!(
+            "Session IP binding mismatch for user {}: bound to {:?}, request from {:?}",
+            session.user_id,
+            session.ip_address,
+            client_ip
+        );
+        session_state
+            .session_store
+            .revoke_session(session_id, Some("IP binding mismatch".to_string()))
+            .await?;
+        let pool = session_state.db_pool.clone();
+        let entry = NewAuditLog::new(session.user_id, "session_ip_mismatch", "session", session_id).details(json!({
+            "bound_ip": session.ip_address,
+            "client_ip": client_ip,
+        }));
+        tokio::spawn(async move { record_audit_log(&pool, entry).await });
+        return Ok(SessionValidation::IpMismatch);
+    }
+
     // Get user data from database
     let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
         .bind(session.user_id)
@@ -201,24 +287,18 @@ pub async fn validate_and_get_session_user(
         None => return Ok(SessionValidation::Invalid),
     };
 
+    // Sessions are revoked on deactivation, but don't rely on Redis having caught up
+    if !user.is_active {
+        return Ok(SessionValidation::Revoked);
+    }
+
     // Get admin role if exists
     let admin_role = sqlx::query_as::<_, AdminRole>("SELECT * FROM admin_roles WHERE user_id = $1")
         .bind(user.id)
         .fetch_optional(&session_state.db_pool)
         .await?;
 
-    // Build permissions list
-    let permissions = match &admin_role {
-        Some(role) => {
-            let perms = Permission::from_admin_level(&role.admin_level, role.faculty_id);
-            // Convert role permissions from strings to Permission enums if needed
-            let mut perm_strings: Vec<String> =
-                perms.into_iter().map(|p| format!("{:?}", p)).collect();
-            perm_strings.extend(role.permissions.iter().cloned());
-            perm_strings
-        }
-        None => vec!["ViewProfile".to_string(), "UpdateProfile".to_string()],
-    };
+    let permissions = Permission::for_role(admin_role.as_ref());
 
     // Create session user
     let session_user = SessionUser {
@@ -232,11 +312,13 @@ pub async fn validate_and_get_session_user(
         session_id: session_id.to_string(),
         permissions,
         faculty_id: admin_role.as_ref().and_then(|r| r.faculty_id),
+        is_impersonated: session.impersonated_by.is_some(),
+        impersonated_by: session.impersonated_by,
     };
 
-    // Update session activity
+    // Update session activity (last_accessed drives the idle timeout)
     session_state
-        .redis_store
+        .session_store
         .update_session_activity(session_id)
         .await?;
 
@@ -250,11 +332,6 @@ fn clear_session_cookie(cookies: &Cookies) {
     cookies.add(cookie);
 }
 
-fn has_permission(session_user: &SessionUser, permission: &Permission) -> bool {
-    let permission_str = format!("{:?}", permission);
-    session_user.permissions.contains(&permission_str)
-}
-
 fn is_admin_level_sufficient(user_level: &AdminLevel, required_level: &AdminLevel) -> bool {
     match (user_level, required_level) {
         (AdminLevel::SuperAdmin, _) => true,
@@ -306,21 +383,31 @@ impl<S> FromRequestParts<S> for AdminUser
 where
     S: Send + Sync,
 {
-    type Rejection = StatusCode;
+    type Rejection = Response;
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
         let session_user = parts
             .extensions
             .get::<SessionUser>()
             .cloned()
-            .ok_or(StatusCode::UNAUTHORIZED)?;
+            .ok_or_else(|| StatusCode::UNAUTHORIZED.into_response())?;
 
         match session_user.admin_role.clone() {
+            // admin_role is loaded per request in validate_and_get_session_user, so a
+            // toggle_admin_status change takes effect on the very next request
+            Some(admin_role) if !admin_role.is_enabled => Err((
+                StatusCode::FORBIDDEN,
+                Json(json!({
+                    "status": "error",
+                    "message": "Admin account disabled"
+                })),
+            )
+                .into_response()),
             Some(admin_role) => Ok(AdminUser {
                 session_user,
                 admin_role,
             }),
-            None => Err(StatusCode::FORBIDDEN),
+            None => Err(StatusCode::FORBIDDEN.into_response()),
         }
     }
 }
@@ -335,7 +422,7 @@ impl<S> FromRequestParts<S> for SuperAdminUser
 where
     S: Send + Sync,
 {
-    type Rejection = StatusCode;
+    type Rejection = Response;
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
         let admin_user = AdminUser::from_request_parts(parts, _state).await?;
@@ -345,7 +432,7 @@ where
                 session_user: admin_user.session_user,
                 admin_role: admin_user.admin_role,
             }),
-            _ => Err(StatusCode::FORBIDDEN),
+            _ => Err(StatusCode::FORBIDDEN.into_response()),
         }
     }
 }
@@ -361,7 +448,7 @@ impl<S> FromRequestParts<S> for FacultyAdminUser
 where
     S: Send + Sync,
 {
-    type Rejection = StatusCode;
+    type Rejection = Response;
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
         let admin_user = AdminUser::from_request_parts(parts, _state).await?;
@@ -372,8 +459,106 @@ where
                 session_user: admin_user.session_user,
                 admin_role: admin_user.admin_role,
             }),
-            _ => Err(StatusCode::FORBIDDEN),
+            _ => Err(StatusCode::FORBIDDEN.into_response()),
+        }
+    }
+}
+
+/// Permissions a `RequirePermission` guard accepts; holding any one of them is enough
+pub trait PermissionRequirement {
+    const PERMISSIONS: &'static [Permission];
+}
+
+/// Guard for `create_activity`
+pub struct CanCreateActivity;
+
+impl PermissionRequirement for CanCreateActivity {
+    const PERMISSIONS: &'static [Permission] = &[Permission::ManageActivities, Permission::CreateActivity];
+}
+
+/// Permission-checking extractor: rejects with 403 before the handler body runs when the
+/// session user holds none of `P::PERMISSIONS`
+pub struct RequirePermission<P: PermissionRequirement> {
+    pub session_user: SessionUser,
+    _requirement: PhantomData<fn() -> P>,
+}
+
+impl<P, S> FromRequestParts<S> for RequirePermission<P>
+where
+    P: PermissionRequirement,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let session_user = SessionUser::from_request_parts(parts, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+
+        if session_user.admin_role.as_ref().is_some_and(|role| !role.is_enabled) {
+            return Err(ApiError::forbidden("admin_disabled", "Admin account disabled").into_response());
+        }
+
+        if !P::PERMISSIONS.iter().any(|p| session_user.has_permission(p)) {
+            return Err(ApiError::forbidden(
+                "permission_denied",
+                format!("Access denied: requires one of {:?}", P::PERMISSIONS),
+            )
+            .into_response());
         }
+
+        Ok(RequirePermission {
+            session_user,
+            _requirement: PhantomData,
+        })
+    }
+}
+
+/// Faculties an admin may act on: SuperAdmin every faculty, any other admin only their
+/// assigned one. Extracting it rejects non-admins and admins without a faculty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FacultyScope {
+    All,
+    Faculty(Uuid),
+}
+
+impl FacultyScope {
+    pub fn for_admin(admin_role: &AdminRole) -> Result<Self, ApiError> {
+        match (&admin_role.admin_level, admin_role.faculty_id) {
+            (AdminLevel::SuperAdmin, _) => Ok(Self::All),
+            (_, Some(faculty_id)) => Ok(Self::Faculty(faculty_id)),
+            (_, None) => Err(ApiError::forbidden("faculty_required", "Admin has no faculty assigned")),
+        }
+    }
+
+    pub fn authorize(&self, faculty_id: Uuid) -> Result<(), ApiError> {
+        match self {
+            Self::Faculty(own) if *own != faculty_id => Err(ApiError::forbidden(
+                "faculty_access_denied",
+                "Access denied: you can only access your own faculty",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Faculty to filter list queries by; `None` means every faculty
+    pub fn faculty_id(&self) -> Option<Uuid> {
+        match self {
+            Self::All => None,
+            Self::Faculty(faculty_id) => Some(*faculty_id),
+        }
+    }
+}
+
+impl<S> FromRequestParts<S> for FacultyScope
+where
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let admin_user = AdminUser::from_request_parts(parts, state).await?;
+        FacultyScope::for_admin(&admin_user.admin_role).map_err(IntoResponse::into_response)
     }
 }
 
@@ -391,7 +576,7 @@ impl<S> FromRequestParts<S> for FacultyScopedAdminUser
 where
     S: Send + Sync,
 {
-    type Rejection = StatusCode;
+    type Rejection = Response;
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
         // First, ensure we have an admin user
@@ -404,7 +589,7 @@ where
             Err(_) => {
                 // If direct extraction fails, try to parse from URI path
                 extract_faculty_id_from_path(parts.uri.path())
-                    .ok_or(StatusCode::BAD_REQUEST)?
+                    .ok_or_else(|| StatusCode::BAD_REQUEST.into_response())?
             }
         };
 
@@ -428,7 +613,7 @@ where
                             faculty_id,
                         })
                     }
-                    _ => Err(StatusCode::FORBIDDEN),
+                    _ => Err(StatusCode::FORBIDDEN.into_response()),
                 }
             }
         }
@@ -556,6 +741,13 @@ pub fn create_session_cookie(session_id: &str, max_age_seconds: i64) -> Cookie<'
         .build()
 }
 
+/// Session cookie whose Max-Age ends exactly when the session does, so the browser and
+/// Redis TTL agree for both `remember_me` and regular logins
+pub fn create_login_cookie(session_id: &str, expires_at: chrono::DateTime<chrono::Utc>) -> Cookie<'static> {
+    let max_age_seconds = (expires_at - chrono::Utc::now()).num_seconds().max(0);
+    create_session_cookie(session_id, max_age_seconds)
+}
+
 pub fn delete_session_cookie() -> Cookie<'static> {
     Cookie::build(("session_id", ""))
         .path("/")
@@ -563,3 +755,239 @@ pub fn delete_session_cookie() -> Cookie<'static> {
         .http_only(true)
         .build()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_state;
+
+    fn admin_session_user(admin_level: AdminLevel, faculty_id: Option<Uuid>) -> SessionUser {
+        let user_id = Uuid::new_v4();
+        SessionUser {
+            user_id,
+            student_id: "ADMIN001".to_string(),
+            email: "admin@test.local".to_string(),
+            first_name: "Test".to_string(),
+            last_name: "Admin".to_string(),
+            department_id: None,
+            admin_role: Some(AdminRole {
+                id: Uuid::new_v4(),
+                user_id,
+                admin_level,
+                faculty_id,
+                permissions: Vec::new(),
+                is_enabled: true,
+                created_at: None,
+                updated_at: None,
+            }),
+            session_id: Uuid::new_v4().to_string(),
+            permissions: Vec::new(),
+            faculty_id,
+            is_impersonated: false,
+            impersonated_by: None,
+        }
+    }
+
+    #[test]
+    fn test_faculty_admin_scoped_to_own_faculty() {
+        let faculty_id = Uuid::new_v4();
+        let other_faculty_id = Uuid::new_v4();
+        let admin = admin_session_user(AdminLevel::FacultyAdmin, Some(faculty_id));
+
+        let scope = get_accessible_faculty_ids(&admin).unwrap();
+        assert_eq!(scope, vec![faculty_id]);
+        assert!(!scope.contains(&other_faculty_id));
+        assert!(!has_faculty_access(&admin, other_faculty_id));
+    }
+
+    #[test]
+    fn test_faculty_admin_without_faculty_sees_nothing() {
+        let admin = admin_session_user(AdminLevel::FacultyAdmin, None);
+        assert_eq!(get_accessible_faculty_ids(&admin), Some(vec![]));
+    }
+
+    #[test]
+    fn test_super_admin_sees_all_faculties() {
+        let admin = admin_session_user(AdminLevel::SuperAdmin, None);
+        assert_eq!(get_accessible_faculty_ids(&admin), None);
+        assert!(has_faculty_access(&admin, Uuid::new_v4()));
+    }
+
+    #[test]
+    fn test_faculty_scope_authorize() {
+        let faculty_id = Uuid::new_v4();
+        let faculty_admin = admin_session_user(AdminLevel::FacultyAdmin, Some(faculty_id));
+        let scope = FacultyScope::for_admin(faculty_admin.admin_role.as_ref().unwrap()).unwrap();
+        assert_eq!(scope, FacultyScope::Faculty(faculty_id));
+        assert!(scope.authorize(faculty_id).is_ok());
+        assert!(matches!(
+            scope.authorize(Uuid::new_v4()),
+            Err(ApiError::Forbidden(detail)) if detail.code == "faculty_access_denied"
+        ));
+
+        let super_admin = admin_session_user(AdminLevel::SuperAdmin, None);
+        let scope = FacultyScope::for_admin(super_admin.admin_role.as_ref().unwrap()).unwrap();
+        assert_eq!(scope.faculty_id(), None);
+        assert!(scope.authorize(Uuid::new_v4()).is_ok());
+
+        let unassigned = admin_session_user(AdminLevel::RegularAdmin, None);
+        assert!(matches!(
+            FacultyScope::for_admin(unassigned.admin_role.as_ref().unwrap()),
+            Err(ApiError::Forbidden(detail)) if detail.code == "faculty_required"
+        ));
+    }
+
+    async fn create_activity_guard(session_user: Option<SessionUser>) -> StatusCode {
+        let (mut parts, _) = axum::http::Request::new(()).into_parts();
+        if let Some(session_user) = session_user {
+            parts.extensions.insert(session_user);
+        }
+        match RequirePermission::<CanCreateActivity>::from_request_parts(&mut parts, &()).await {
+            Ok(_) => StatusCode::OK,
+            Err(response) => response.status(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_require_permission_rejects_before_handler() {
+        let mut creator = admin_session_user(AdminLevel::RegularAdmin, None);
+        creator.permissions.push(Permission::CreateActivity);
+        let scanner = admin_session_user(AdminLevel::RegularAdmin, None);
+
+        assert_eq!(create_activity_guard(Some(creator)).await, StatusCode::OK);
+        assert_eq!(create_activity_guard(Some(scanner)).await, StatusCode::FORBIDDEN);
+        assert_eq!(create_activity_guard(None).await, StatusCode::UNAUTHORIZED);
+
+        // A session loaded before the role was disabled still carries the old permissions
+        let mut disabled = admin_session_user(AdminLevel::RegularAdmin, None);
+        disabled.permissions.push(Permission::CreateActivity);
+        disabled.admin_role.as_mut().unwrap().is_enabled = false;
+        assert_eq!(create_activity_guard(Some(disabled)).await, StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn test_login_cookie_matches_session_lifetime() {
+        let config = SessionConfig::default();
+        let max_age = |remember_me: bool| {
+            create_login_cookie("session", config.get_session_expiry(None, remember_me))
+                .max_age()
+                .unwrap()
+                .whole_seconds()
+        };
+
+        let regular = config.default_expiry_hours * 3600;
+        let remembered = config.remember_me_expiry_days * 86400;
+        assert!((regular - 1..=regular).contains(&max_age(false)));
+        assert!((remembered - 1..=remembered).contains(&max_age(true)));
+    }
+
+    /// Session state with a fresh in-memory session; the DB is never reached because both
+    /// timeout checks run before the user lookup
+    async fn timeout_state(config: SessionConfig) -> (SessionState, String) {
+        session_state_from_ip(config, None).await
+    }
+
+    /// Same as `timeout_state`, with the session created from `login_ip`
+    async fn session_state_from_ip(config: SessionConfig, login_ip: Option<&str>) -> (SessionState, String) {
+        use crate::services::MemorySessionStore;
+
+        let session_store = Arc::new(MemorySessionStore::default());
+        let session = session_store
+            .create_session(crate::models::session::CreateSession {
+                user_id: Uuid::new_v4(),
+                expires_at: chrono::Utc::now() + chrono::Duration::hours(1),
+                ip_address: login_ip.map(str::to_string),
+                user_agent: None,
+                device_info: Default::default(),
+                impersonated_by: None,
+                impersonator_session_id: None,
+            })
+            .await
+            .unwrap();
+        let state = SessionState {
+            session_store,
+            config,
+            ..test_state(&PgPool::connect_lazy("postgres://localhost:1/unused").unwrap())
+        };
+        (state, session.id)
+    }
+
+    #[tokio::test]
+    async fn test_idle_session_times_out_before_expires_at() {
+        use tower::ServiceExt;
+
+        let config = SessionConfig {
+            idle_timeout: chrono::Duration::zero(),
+            ..SessionConfig::default()
+        };
+        let (state, session_id) = timeout_state(config).await;
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+
+        let app = axum::Router::new()
+            .route("/", axum::routing::get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn_with_state(state.clone(), session_middleware))
+            .layer(tower_cookies::CookieManagerLayer::new());
+        let request = axum::http::Request::builder()
+            .uri("/")
+            .header("X-Session-ID", &session_id)
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["code"], "session_idle_timeout");
+        assert_eq!(body["message"], "Session timed out due to inactivity");
+        assert!(state.session_store.get_session(&session_id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_session_from_other_ip_is_revoked() {
+        use tower::ServiceExt;
+
+        let config = SessionConfig {
+            bind_session_to_ip: true,
+            ip_binding_prefix_v4: 24,
+            ..SessionConfig::default()
+        };
+        let (state, session_id) = session_state_from_ip(config, Some("203.0.113.5")).await;
+
+        let app = axum::Router::new()
+            .route("/", axum::routing::get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn_with_state(state.clone(), session_middleware))
+            .layer(tower_cookies::CookieManagerLayer::new());
+        let request = axum::http::Request::builder()
+            .uri("/")
+            .header("X-Session-ID", &session_id)
+            .header("X-Forwarded-For", "198.51.100.7")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        let session = state.session_store.get_session(&session_id).await.unwrap().unwrap();
+
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["code"], "session_ip_mismatch");
+        assert!(!session.is_active);
+        assert_eq!(session.revocation_reason.as_deref(), Some("IP binding mismatch"));
+    }
+
+    #[tokio::test]
+    async fn test_session_expires_at_absolute_timeout() {
+        let config = SessionConfig {
+            absolute_timeout: chrono::Duration::zero(),
+            ..SessionConfig::default()
+        };
+        assert!(config.get_session_expiry(None, true) <= chrono::Utc::now());
+
+        let (state, session_id) = timeout_state(config).await;
+        let validation = validate_and_get_session_user(&state, &session_id, None).await.unwrap();
+
+        assert!(matches!(validation, SessionValidation::Expired));
+        assert!(state.session_store.get_session(&session_id).await.unwrap().is_none());
+    }
+}
@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
@@ -13,8 +13,129 @@ pub enum ActivityStatus {
     Cancelled,
 }
 
-// Note: activity rows are now read via ad-hoc SELECTs combining date + time-only.
-// The legacy Activity struct mapping has been removed to avoid mismatches.
+/// Who can see an activity in `get_activities`/`get_activity`.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "activity_visibility", rename_all = "snake_case")]
+pub enum ActivityVisibility {
+    #[default]
+    Public,
+    FacultyOnly, // only students of the activity's faculty
+    Private,     // only the creator and admins over the activity's faculty
+}
+
+/// Core activity row as returned by SELECT/RETURNING queries.
+///
+/// Activities store date and time-only columns separately, so queries must project
+/// the combined `start_time`/`end_time` (see [`ActivityTimeZone`]) and cast
+/// `activity_type` to text.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ActivityRecord {
+    pub id: Uuid,
+    pub title: String,
+    pub description: String,
+    pub location: String,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub max_participants: Option<i32>,
+    pub registration_deadline: Option<DateTime<Utc>>,
+    pub publish_at: Option<DateTime<Utc>>,
+    pub status: ActivityStatus,
+    pub visibility: ActivityVisibility,
+    pub activity_type: Option<String>,
+    pub faculty_id: Option<Uuid>,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Check a student's faculty against an activity's `eligible_faculties` JSONB list.
+///
+/// An empty (or non-array) list means the activity is open to every faculty.
+pub fn is_faculty_eligible(eligible_faculties: &serde_json::Value, faculty_id: Option<Uuid>) -> bool {
+    let eligible: Vec<Uuid> = match eligible_faculties.as_array() {
+        Some(values) => values
+            .iter()
+            .filter_map(|v| v.as_str().and_then(|s| Uuid::parse_str(s).ok()))
+            .collect(),
+        None => return true,
+    };
+
+    if eligible.is_empty() {
+        return true;
+    }
+
+    faculty_id.is_some_and(|id| eligible.contains(&id))
+}
+
+/// Zone the activity date and time-only columns are entered in (ACTIVITY_TIMEZONE, default
+/// UTC), and the SQL converting between those wall-clock values and instants.
+///
+/// The zone is checked against `pg_timezone_names` at startup and quoted here, so it is
+/// inlined into queries rather than taking a bind parameter in each of them.
+#[derive(Debug, Clone)]
+pub struct ActivityTimeZone {
+    literal: String,
+}
+
+impl ActivityTimeZone {
+    pub fn new(timezone: Option<&str>) -> Self {
+        Self {
+            literal: format!("'{}'", timezone.unwrap_or("UTC").replace('\'', "''")),
+        }
+    }
+
+    /// `((a.start_date::timestamp + a.start_time_only) AT TIME ZONE '<zone>')` for table
+    /// alias `a`; an empty alias gives unqualified columns
+    pub fn start_sql(&self, alias: &str) -> String {
+        self.columns_sql(alias, "start_date", "start_time_only")
+    }
+
+    pub fn end_sql(&self, alias: &str) -> String {
+        self.columns_sql(alias, "end_date", "end_time_only")
+    }
+
+    /// Instant of a wall-clock `timestamp` expression, e.g. a date and time from a form
+    pub fn instant_sql(&self, local: &str) -> String {
+        format!("({} AT TIME ZONE {})", local, self.literal)
+    }
+
+    /// Wall-clock timestamp of a `timestamptz` expression (usually a placeholder); its
+    /// `::date` and `::time` parts are what the date and time-only columns store
+    pub fn local_sql(&self, instant: &str) -> String {
+        format!("({} AT TIME ZONE {})", instant, self.literal)
+    }
+
+    fn columns_sql(&self, alias: &str, date_column: &str, time_column: &str) -> String {
+        let prefix = if alias.is_empty() { String::new() } else { format!("{}.", alias) };
+        self.instant_sql(&format!("({prefix}{date_column}::timestamp + {prefix}{time_column})"))
+    }
+}
+
+/// Period during which QR check-in scans are accepted for an activity.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct CheckInWindow {
+    pub opens_at: DateTime<Utc>,
+    pub closes_at: DateTime<Utc>,
+}
+
+impl CheckInWindow {
+    /// Window from `opens_before_minutes` before the start to `closes_after_minutes` after the end
+    pub fn new(
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        opens_before_minutes: i64,
+        closes_after_minutes: i64,
+    ) -> Self {
+        Self {
+            opens_at: start_time - Duration::minutes(opens_before_minutes),
+            closes_at: end_time + Duration::minutes(closes_after_minutes),
+        }
+    }
+
+    pub fn contains(&self, now: DateTime<Utc>) -> bool {
+        now >= self.opens_at && now <= self.closes_at
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateActivity {
@@ -40,3 +161,73 @@ pub struct UpdateActivity {
     pub faculty_id: Option<Uuid>,
     pub department_id: Option<Uuid>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_empty_eligible_faculties_is_open() {
+        assert!(is_faculty_eligible(&json!([]), None));
+        assert!(is_faculty_eligible(&serde_json::Value::Null, Some(Uuid::new_v4())));
+    }
+
+    #[test]
+    fn test_eligible_faculty_accepted() {
+        let faculty_id = Uuid::new_v4();
+        let eligible = json!([faculty_id.to_string()]);
+        assert!(is_faculty_eligible(&eligible, Some(faculty_id)));
+    }
+
+    #[test]
+    fn test_ineligible_student_rejected() {
+        let eligible = json!([Uuid::new_v4().to_string()]);
+        assert!(!is_faculty_eligible(&eligible, Some(Uuid::new_v4())));
+        assert!(!is_faculty_eligible(&eligible, None));
+    }
+
+    #[test]
+    fn test_activity_time_zone_sql() {
+        let bangkok = ActivityTimeZone::new(Some("Asia/Bangkok"));
+        assert_eq!(
+            bangkok.start_sql("a"),
+            "((a.start_date::timestamp + a.start_time_only) AT TIME ZONE 'Asia/Bangkok')"
+        );
+        assert_eq!(
+            ActivityTimeZone::new(None).end_sql(""),
+            "((end_date::timestamp + end_time_only) AT TIME ZONE 'UTC')"
+        );
+        assert_eq!(bangkok.local_sql("$7"), "($7 AT TIME ZONE 'Asia/Bangkok')");
+        assert_eq!(bangkok.instant_sql("$1::timestamp"), "($1::timestamp AT TIME ZONE 'Asia/Bangkok')");
+        assert_eq!(ActivityTimeZone::new(Some("x'y")).local_sql("$1"), "($1 AT TIME ZONE 'x''y')");
+    }
+
+    fn window() -> CheckInWindow {
+        let start = DateTime::parse_from_rfc3339("2025-01-10T09:00:00Z").unwrap().with_timezone(&Utc);
+        let end = DateTime::parse_from_rfc3339("2025-01-10T12:00:00Z").unwrap().with_timezone(&Utc);
+        CheckInWindow::new(start, end, 30, 15)
+    }
+
+    #[test]
+    fn test_checkin_before_window_opens() {
+        let window = window();
+        assert!(!window.contains(window.opens_at - Duration::seconds(1)));
+    }
+
+    #[test]
+    fn test_checkin_inside_window() {
+        let window = window();
+        assert_eq!(window.opens_at.to_rfc3339(), "2025-01-10T08:30:00+00:00");
+        assert!(window.contains(window.opens_at));
+        assert!(window.contains(window.opens_at + Duration::hours(2)));
+        assert!(window.contains(window.closes_at));
+    }
+
+    #[test]
+    fn test_checkin_after_window_closes() {
+        let window = window();
+        assert_eq!(window.closes_at.to_rfc3339(), "2025-01-10T12:15:00+00:00");
+        assert!(!window.contains(window.closes_at + Duration::seconds(1)));
+    }
+}
@@ -0,0 +1,23 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A participant's rating of an activity they completed
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ActivityFeedback {
+    pub id: Uuid,
+    pub activity_id: Uuid,
+    pub user_id: Uuid,
+    pub rating: i16,
+    pub comment: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmitActivityFeedback {
+    /// 1–5
+    pub rating: i16,
+    pub comment: Option<String>,
+}
@@ -0,0 +1,65 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Reusable non-schedule fields of an activity; instantiated through
+/// `POST /api/activities/from-template/{template_id}`.
+///
+/// Queries must cast `activity_type` to text, as for `ActivityRecord`.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ActivityTemplate {
+    pub id: Uuid,
+    pub name: String,
+    pub title_pattern: String,
+    pub description: String,
+    pub location: String,
+    pub activity_type: Option<String>,
+    pub hours: i32,
+    pub organizer: String,
+    pub eligible_faculties: serde_json::Value,
+    /// `None` for shared templates
+    pub faculty_id: Option<Uuid>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ActivityTemplate {
+    /// Activity title for an instance starting on `start_date`
+    pub fn render_title(&self, start_date: NaiveDate) -> String {
+        self.title_pattern
+            .replace("{date}", &start_date.format("%Y-%m-%d").to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn template(title_pattern: &str) -> ActivityTemplate {
+        ActivityTemplate {
+            id: Uuid::new_v4(),
+            name: "Weekly seminar".to_string(),
+            title_pattern: title_pattern.to_string(),
+            description: String::new(),
+            location: "Room 101".to_string(),
+            activity_type: Some("Academic".to_string()),
+            hours: 2,
+            organizer: "CS Club".to_string(),
+            eligible_faculties: json!([]),
+            faculty_id: None,
+            created_by: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_render_title_replaces_date() {
+        let date = NaiveDate::from_ymd_opt(2025, 2, 14).unwrap();
+        assert_eq!(template("Seminar {date}").render_title(date), "Seminar 2025-02-14");
+        assert_eq!(template("Seminar").render_title(date), "Seminar");
+    }
+}
@@ -17,6 +17,17 @@ impl Default for AdminLevel {
     }
 }
 
+impl AdminLevel {
+    /// Position in the admin hierarchy, higher outranks lower
+    pub fn rank(&self) -> u8 {
+        match self {
+            AdminLevel::SuperAdmin => 3,
+            AdminLevel::FacultyAdmin => 2,
+            AdminLevel::RegularAdmin => 1,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct AdminRole {
     pub id: Uuid,
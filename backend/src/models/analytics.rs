@@ -195,4 +195,31 @@ pub struct ActivityCompletionRate {
     pub total_completed: i32,
     pub completion_rate: f64,
     pub start_time: DateTime<Utc>,
-}
\ No newline at end of file
+}
+/// Bucket size for time-series analytics; the value is passed to `date_trunc`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrendGranularity {
+    #[default]
+    Day,
+    Week,
+    Month,
+}
+
+impl TrendGranularity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TrendGranularity::Day => "day",
+            TrendGranularity::Week => "week",
+            TrendGranularity::Month => "month",
+        }
+    }
+}
+
+/// One bucket of the registration trend; `period_start` is the first day of the bucket (UTC)
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct RegistrationTrendPoint {
+    pub period_start: chrono::NaiveDate,
+    pub new_users: i64,
+    pub new_participations: i64,
+}
@@ -0,0 +1,32 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A system announcement published to every user at `publish_at`
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ScheduledAnnouncement {
+    pub id: Uuid,
+    pub title: String,
+    pub message: String,
+    pub publish_at: DateTime<Utc>,
+    /// Hidden from the inbox after this; `None` keeps it until cancelled
+    pub display_until: Option<DateTime<Utc>>,
+    /// `scheduled`, `published` or `cancelled`
+    pub status: String,
+    pub published_at: Option<DateTime<Utc>>,
+    pub created_by: Option<Uuid>,
+    pub cancelled_by: Option<Uuid>,
+    pub cancelled_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateAnnouncementRequest {
+    pub title: String,
+    pub message: String,
+    /// Defaults to now, i.e. published on the worker's next run
+    pub publish_at: Option<DateTime<Utc>>,
+    pub display_until: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,37 @@
+use serde_json::Value;
+use uuid::Uuid;
+
+/// An audit entry waiting to be written to `audit_logs`
+/// (read back through `admin_session::get_audit_logs`)
+#[derive(Debug, Clone)]
+pub struct NewAuditLog {
+    pub actor_user_id: Option<Uuid>,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: Option<String>,
+    pub reason: Option<String>,
+    pub details: Value,
+}
+
+impl NewAuditLog {
+    pub fn new(actor_user_id: Uuid, action: &str, target_type: &str, target_id: impl ToString) -> Self {
+        Self {
+            actor_user_id: Some(actor_user_id),
+            action: action.to_string(),
+            target_type: target_type.to_string(),
+            target_id: Some(target_id.to_string()),
+            reason: None,
+            details: Value::Object(Default::default()),
+        }
+    }
+
+    pub fn reason(mut self, reason: Option<String>) -> Self {
+        self.reason = reason;
+        self
+    }
+
+    pub fn details(mut self, details: Value) -> Self {
+        self.details = details;
+        self
+    }
+}
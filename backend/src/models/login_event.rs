@@ -0,0 +1,21 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A login attempt against an account, written by `student_login` and `admin_login`
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LoginEvent {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub device_info: serde_json::Value,
+    /// `false` for a wrong password
+    pub success: bool,
+    pub created_at: DateTime<Utc>,
+    /// Resolved from `ip_address` when GeoIP is configured
+    pub country_code: Option<String>,
+    pub country: Option<String>,
+    pub city: Option<String>,
+}
@@ -1,13 +1,19 @@
 pub mod activity;
+pub mod activity_feedback;
+pub mod activity_template;
 pub mod admin_role;
+pub mod announcement;
+pub mod audit_log;
 pub mod analytics;
 pub mod department;
 pub mod faculty;
+pub mod login_event;
 pub mod notifications;
 pub mod participation;
 pub mod session;
 pub mod subscription;
 pub mod user;
+pub mod webhook;
 
 pub use admin_role::*;
 // Selective imports to avoid naming conflicts
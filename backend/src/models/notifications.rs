@@ -23,6 +23,66 @@ pub enum NotificationStatus {
     Delivered,
 }
 
+/// Notification categories a chat channel (LINE) can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationCategory {
+    Reminders,
+    Announcements,
+    CheckIns,
+}
+
+impl NotificationCategory {
+    pub const ALL: [NotificationCategory; 3] = [
+        NotificationCategory::Reminders,
+        NotificationCategory::Announcements,
+        NotificationCategory::CheckIns,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationCategory::Reminders => "reminders",
+            NotificationCategory::Announcements => "announcements",
+            NotificationCategory::CheckIns => "check_ins",
+        }
+    }
+}
+
+/// LINE chat that receives a user's or a faculty's notifications through the Messaging API
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LineRecipientSettings {
+    pub id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub faculty_id: Option<Uuid>,
+    /// LINE user ID (`U...`), group ID (`C...`) or room ID (`R...`)
+    pub line_id: String,
+    pub categories: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Link or update a LINE chat; `line_id` may be omitted to change only the categories
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetLineRecipientRequest {
+    pub line_id: Option<String>,
+    /// Defaults to every category when a chat is first linked
+    pub categories: Option<Vec<NotificationCategory>>,
+}
+
+/// One entry of a user's in-app notification inbox
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct InboxNotification {
+    pub id: Uuid,
+    /// A `NotificationCategory`, or `subscription` for subscription expiry notices
+    pub category: String,
+    pub title: Option<String>,
+    pub message: String,
+    pub data: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+    pub is_read: bool,
+    pub read_at: Option<DateTime<Utc>>,
+}
+
 /// Subscription Notification Model
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct SubscriptionNotification {
@@ -154,17 +214,76 @@ pub struct AdminAlert {
     pub auto_resolve: bool,
 }
 
-/// Notification Preferences (future enhancement)
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct NotificationPreferences {
-    pub user_id: Uuid,
+/// Delivery channels a user can switch off per category
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationChannel {
+    Email,
+    Sse,
+    Line,
+}
+
+impl NotificationChannel {
+    /// Column of `notification_preferences` holding this channel's toggle
+    pub fn preference_column(&self) -> &'static str {
+        match self {
+            NotificationChannel::Email => "email_enabled",
+            NotificationChannel::Sse => "sse_enabled",
+            NotificationChannel::Line => "line_enabled",
+        }
+    }
+}
+
+/// Channel toggles of one category (all on unless the user opted out)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct CategoryPreferences {
     pub email_enabled: bool,
     pub sse_enabled: bool,
-    pub subscription_alerts: bool,
-    pub system_alerts: bool,
-    pub admin_notices: bool,
-    pub alert_timing: i32, // days before expiry to start alerts
-    pub digest_frequency: String, // daily, weekly, never
+    pub line_enabled: bool,
+}
+
+impl Default for CategoryPreferences {
+    fn default() -> Self {
+        Self {
+            email_enabled: true,
+            sse_enabled: true,
+            line_enabled: true,
+        }
+    }
+}
+
+/// A user's notification preferences, one entry per category
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationPreferences {
+    pub reminders: CategoryPreferences,
+    pub announcements: CategoryPreferences,
+    pub check_ins: CategoryPreferences,
+}
+
+impl NotificationPreferences {
+    pub fn get_mut(&mut self, category: NotificationCategory) -> &mut CategoryPreferences {
+        match category {
+            NotificationCategory::Reminders => &mut self.reminders,
+            NotificationCategory::Announcements => &mut self.announcements,
+            NotificationCategory::CheckIns => &mut self.check_ins,
+        }
+    }
+}
+
+/// Partial update of one category; omitted channels keep their value
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UpdateCategoryPreferences {
+    pub email_enabled: Option<bool>,
+    pub sse_enabled: Option<bool>,
+    pub line_enabled: Option<bool>,
+}
+
+/// `PUT /api/auth/notification-preferences`; omitted categories are left unchanged
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UpdateNotificationPreferences {
+    pub reminders: Option<UpdateCategoryPreferences>,
+    pub announcements: Option<UpdateCategoryPreferences>,
+    pub check_ins: Option<UpdateCategoryPreferences>,
 }
 
 /// Notification Summary for Admin Dashboard
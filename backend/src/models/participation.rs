@@ -5,14 +5,48 @@ use uuid::Uuid;
 
 use crate::models::user::UserPrefix;
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "participation_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
 pub enum ParticipationStatus {
     Registered,
     CheckedIn,
     CheckedOut,
     Completed,
     NoShow,
+    Cancelled,  // activity was cancelled; kept for audit
+    Waitlisted, // activity was full; promoted to Registered when a seat frees up
+}
+
+impl ParticipationStatus {
+    /// Whether a manual (admin) status change from `self` to `target` is allowed
+    pub fn can_transition_to(&self, target: &ParticipationStatus) -> bool {
+        use ParticipationStatus::*;
+
+        matches!(
+            (self, target),
+            (Registered, CheckedIn)
+                | (Registered, NoShow)
+                | (CheckedIn, CheckedOut)
+                | (CheckedIn, Completed)
+                | (CheckedOut, Completed)
+                | (NoShow, CheckedIn)
+                | (NoShow, Registered)
+                // Undo a completion recorded by mistake; the awarded hours are revoked
+                | (Completed, CheckedOut)
+                // Seat changes go through the same capacity check and waitlist promotion as
+                // self-service registration and withdrawal
+                | (Registered, Cancelled)
+                | (Waitlisted, Registered)
+                | (Waitlisted, Cancelled)
+                | (Cancelled, Registered)
+        )
+    }
+
+    /// Whether the participation counts towards `max_participants`
+    pub fn holds_seat(&self) -> bool {
+        !matches!(self, ParticipationStatus::Waitlisted | ParticipationStatus::Cancelled)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -25,6 +59,7 @@ pub struct Participation {
     pub checked_in_at: Option<DateTime<Utc>>,
     pub checked_out_at: Option<DateTime<Utc>>,
     pub notes: Option<String>,
+    pub waitlist_position: Option<i32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +74,19 @@ pub struct UpdateParticipation {
     pub notes: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateParticipationStatus {
+    pub status: ParticipationStatus,
+    pub reason: String,
+    pub notes: Option<String>, // replaces the participation's notes when given
+}
+
+/// Participant's own notes, e.g. dietary requirements; `None` or blank clears them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateParticipationNotes {
+    pub notes: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CheckInRequest {
     pub qr_code: String,
@@ -64,3 +112,32 @@ pub struct ParticipationWithUserDetails {
     pub user_email: String,
     pub user_department_name: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ParticipationStatus::*;
+
+    #[test]
+    fn test_legal_transitions() {
+        assert!(Registered.can_transition_to(&CheckedIn));
+        assert!(Registered.can_transition_to(&NoShow));
+        assert!(CheckedIn.can_transition_to(&CheckedOut));
+        assert!(CheckedIn.can_transition_to(&Completed));
+        assert!(CheckedOut.can_transition_to(&Completed));
+        assert!(NoShow.can_transition_to(&CheckedIn));
+        assert!(Completed.can_transition_to(&CheckedOut));
+        assert!(Waitlisted.can_transition_to(&Registered));
+        assert!(Cancelled.can_transition_to(&Registered));
+    }
+
+    #[test]
+    fn test_illegal_transitions() {
+        assert!(!Completed.can_transition_to(&Registered));
+        assert!(!Completed.can_transition_to(&CheckedIn));
+        assert!(!CheckedOut.can_transition_to(&CheckedIn));
+        assert!(!Registered.can_transition_to(&CheckedOut));
+        assert!(!Registered.can_transition_to(&Registered));
+        assert!(!Registered.can_transition_to(&Waitlisted));
+        assert!(!Completed.can_transition_to(&Cancelled));
+    }
+}
@@ -7,6 +7,14 @@ use uuid::Uuid;
 
 use crate::models::admin_role::{AdminLevel, AdminRole};
 
+/// Where an IP resolves to (see `services::geoip`); every part may be unknown
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct GeoLocation {
+    pub country_code: Option<String>,
+    pub country: Option<String>,
+    pub city: Option<String>,
+}
+
 // Session type classification
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum SessionType {
@@ -82,6 +90,15 @@ pub struct Session {
     pub login_method: LoginMethod,    // Email/StudentID login tracking
     pub sse_connections: Vec<String>, // Track SSE connection IDs
     pub activity_log: Vec<SessionActivity>, // Track important session activities
+    /// Super admin acting as `user_id` through this session (see `handlers::impersonation`)
+    #[serde(default)]
+    pub impersonated_by: Option<Uuid>,
+    /// The admin's own session, restored when impersonation stops
+    #[serde(default)]
+    pub impersonator_session_id: Option<String>,
+    /// Location of `ip_address` at login, when GeoIP is configured
+    #[serde(default)]
+    pub geo_location: Option<GeoLocation>,
 }
 
 // Database model for session tracking
@@ -109,8 +126,20 @@ pub struct SessionUser {
     pub department_id: Option<Uuid>,
     pub admin_role: Option<AdminRole>,
     pub session_id: String,
-    pub permissions: Vec<String>,
+    pub permissions: Vec<Permission>,
     pub faculty_id: Option<Uuid>,
+    /// Lets the frontend show an "acting as this user" banner
+    #[serde(default)]
+    pub is_impersonated: bool,
+    #[serde(default)]
+    pub impersonated_by: Option<Uuid>,
+}
+
+impl SessionUser {
+    /// Exact permission check; never matches on substrings of permission names
+    pub fn has_permission(&self, permission: &Permission) -> bool {
+        self.permissions.contains(permission)
+    }
 }
 
 // Request models
@@ -121,6 +150,10 @@ pub struct CreateSession {
     pub ip_address: Option<String>,
     pub user_agent: Option<String>,
     pub device_info: HashMap<String, Value>,
+    #[serde(default)]
+    pub impersonated_by: Option<Uuid>,
+    #[serde(default)]
+    pub impersonator_session_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -129,6 +162,8 @@ pub struct SessionLoginRequest {
     pub password: String,
     pub remember_me: Option<bool>,
     pub device_info: Option<HashMap<String, Value>>,
+    /// Token from the new-device verification email (`NEW_DEVICE_LOGIN_ACTION=verify`)
+    pub device_verification_token: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -137,6 +172,8 @@ pub struct StudentLoginRequest {
     pub password: String,
     pub remember_me: Option<bool>,
     pub device_info: Option<HashMap<String, Value>>,
+    /// Token from the new-device verification email (`NEW_DEVICE_LOGIN_ACTION=verify`)
+    pub device_verification_token: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -324,6 +361,24 @@ pub enum Permission {
     ReceiveNotifications,
     ReceiveSystemAlerts,
     ReceiveFacultyAlerts,
+
+    // Permissions granted per admin via admin_roles.permissions
+    ManageUsers,
+    ManageActivities,
+    CreateActivity,
+    ViewParticipations,
+    ManageSessions,
+    ViewAllReports,
+}
+
+impl std::str::FromStr for Permission {
+    type Err = serde::de::value::Error;
+
+    /// Parse a stored permission name; only exact variant names are accepted
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use serde::de::IntoDeserializer;
+        Permission::deserialize(s.into_deserializer())
+    }
 }
 
 impl Permission {
@@ -391,6 +446,24 @@ impl Permission {
         }
     }
 
+    /// Permissions of a session: the admin level's defaults plus the role's stored permissions.
+    /// Stored names that aren't a `Permission` are ignored, and a disabled role counts as no role.
+    pub fn for_role(admin_role: Option<&AdminRole>) -> Vec<Permission> {
+        let Some(role) = admin_role.filter(|role| role.is_enabled) else {
+            return vec![Permission::ViewProfile, Permission::UpdateProfile];
+        };
+
+        let mut permissions = Permission::from_admin_level(&role.admin_level, role.faculty_id);
+        for name in &role.permissions {
+            match name.parse::<Permission>() {
+                Ok(permission) if !permissions.contains(&permission) => permissions.push(permission),
+                Ok(_) => {}
+                Err(_) => tracing::debug!("Ignoring unknown permission {:?} on admin role {}", name, role.id),
+            }
+        }
+        permissions
+    }
+
     // Get student permissions (non-admin users)
     pub fn student_permissions() -> Vec<Permission> {
         vec![
@@ -455,6 +528,10 @@ impl Permission {
 pub enum SessionValidation {
     Valid(SessionUser),
     Expired,
+    /// Unused for longer than `SessionConfig::idle_timeout`
+    IdleTimeout,
+    /// Used from an IP outside the login IP's binding (`SessionConfig::bind_session_to_ip`); now revoked
+    IpMismatch,
     Invalid,
     Revoked,
 }
@@ -677,3 +754,67 @@ impl Default for DeviceInfo {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn regular_admin_with(permissions: &[&str]) -> AdminRole {
+        AdminRole {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            admin_level: AdminLevel::RegularAdmin,
+            faculty_id: None,
+            permissions: permissions.iter().map(|p| p.to_string()).collect(),
+            is_enabled: true,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    fn session_user(admin_role: AdminRole) -> SessionUser {
+        SessionUser {
+            user_id: admin_role.user_id,
+            student_id: "ADMIN001".to_string(),
+            email: "admin@test.local".to_string(),
+            first_name: "Test".to_string(),
+            last_name: "Admin".to_string(),
+            department_id: None,
+            permissions: Permission::for_role(Some(&admin_role)),
+            admin_role: Some(admin_role),
+            session_id: Uuid::new_v4().to_string(),
+            faculty_id: None,
+            is_impersonated: false,
+            impersonated_by: None,
+        }
+    }
+
+    #[test]
+    fn test_permission_names_parse_exactly() {
+        assert_eq!("ManageActivities".parse::<Permission>().unwrap(), Permission::ManageActivities);
+        assert!("ManageActivitiesReadonly".parse::<Permission>().is_err());
+        assert!("manageactivities".parse::<Permission>().is_err());
+    }
+
+    #[test]
+    fn test_permission_check_has_no_substring_false_positive() {
+        let readonly = session_user(regular_admin_with(&["ManageActivitiesReadonly"]));
+        assert!(!readonly.has_permission(&Permission::ManageActivities));
+        // Level defaults still apply
+        assert!(readonly.has_permission(&Permission::ScanQrCodes));
+
+        let manager = session_user(regular_admin_with(&["ManageActivities"]));
+        assert!(manager.has_permission(&Permission::ManageActivities));
+        assert!(!manager.has_permission(&Permission::CreateActivity));
+    }
+
+    #[test]
+    fn test_disabled_role_gets_student_permissions() {
+        let mut role = regular_admin_with(&["CreateActivity"]);
+        role.is_enabled = false;
+        let disabled = session_user(role);
+        assert_eq!(disabled.permissions, Permission::for_role(None));
+        assert!(!disabled.has_permission(&Permission::CreateActivity));
+        assert!(!disabled.has_permission(&Permission::ScanQrCodes));
+    }
+}
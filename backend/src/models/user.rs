@@ -51,8 +51,11 @@ pub struct User {
     pub prefix: UserPrefix,
     pub first_name: String,
     pub last_name: String,
-    pub qr_secret: String,
+    /// None once the user has been anonymized
+    pub qr_secret: Option<String>,
     pub department_id: Option<Uuid>,
+    pub is_active: bool,
+    pub anonymized_at: Option<DateTime<Utc>>,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
 }
@@ -93,6 +96,8 @@ pub struct UserResponse {
     pub last_name: String,
     pub full_name_with_prefix: String,
     pub department_id: Option<Uuid>,
+    pub is_active: bool,
+    pub anonymized_at: Option<DateTime<Utc>>,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
 }
@@ -114,6 +119,8 @@ impl From<User> for UserResponse {
             last_name: user.last_name,
             full_name_with_prefix,
             department_id: user.department_id,
+            is_active: user.is_active,
+            anonymized_at: user.anonymized_at,
             created_at: user.created_at,
             updated_at: user.updated_at,
         }
@@ -0,0 +1,107 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Activity lifecycle events that can be delivered to webhooks.
+///
+/// The names are the canonical event taxonomy (`<resource>.<what happened>`); there is no SSE
+/// layer in this service any more, so this enum is the single list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookEvent {
+    ActivityCreated,
+    ActivityUpdated,
+    ActivityCancelled,
+    ActivityStarted,
+    ActivityCompleted,
+    ParticipationRegistered,
+    ParticipationCheckedIn,
+    ParticipationCheckedOut,
+}
+
+impl WebhookEvent {
+    pub const ALL: [WebhookEvent; 8] = [
+        WebhookEvent::ActivityCreated,
+        WebhookEvent::ActivityUpdated,
+        WebhookEvent::ActivityCancelled,
+        WebhookEvent::ActivityStarted,
+        WebhookEvent::ActivityCompleted,
+        WebhookEvent::ParticipationRegistered,
+        WebhookEvent::ParticipationCheckedIn,
+        WebhookEvent::ParticipationCheckedOut,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::ActivityCreated => "activity.created",
+            WebhookEvent::ActivityUpdated => "activity.updated",
+            WebhookEvent::ActivityCancelled => "activity.cancelled",
+            WebhookEvent::ActivityStarted => "activity.started",
+            WebhookEvent::ActivityCompleted => "activity.completed",
+            WebhookEvent::ParticipationRegistered => "participation.registered",
+            WebhookEvent::ParticipationCheckedIn => "participation.checked_in",
+            WebhookEvent::ParticipationCheckedOut => "participation.checked_out",
+        }
+    }
+}
+
+impl std::str::FromStr for WebhookEvent {
+    type Err = anyhow::Error;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        WebhookEvent::ALL
+            .into_iter()
+            .find(|event| event.as_str() == raw)
+            .ok_or_else(|| anyhow::anyhow!("Unknown webhook event: {}", raw))
+    }
+}
+
+/// A registered webhook. The signing secret is deliberately not mapped: it is only
+/// returned once, when the webhook is created.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Webhook {
+    pub id: Uuid,
+    pub url: String,
+    pub event_types: Vec<String>,
+    pub description: Option<String>,
+    pub is_active: bool,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// One event sent (or to be sent) to one webhook; doubles as the delivery log
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    /// `pending`, `delivered` or `failed` (gave up after `max_attempts`)
+    pub status: String,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub response_status: Option<i32>,
+    pub error_message: Option<String>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    pub event_types: Vec<String>,
+    pub description: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+/// Partial update; omitted fields keep their value
+#[derive(Debug, Deserialize)]
+pub struct UpdateWebhookRequest {
+    pub url: Option<String>,
+    pub event_types: Option<Vec<String>>,
+    pub description: Option<String>,
+    pub is_active: Option<bool>,
+}
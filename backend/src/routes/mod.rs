@@ -1,10 +1,18 @@
 use axum::{
+    extract::State,
+    http::StatusCode,
+    response::Json,
     routing::{delete, get, post, put},
     Router,
 };
+use serde_json::{json, Value};
+use std::future::Future;
+use std::time::{Duration, Instant};
 
 use crate::handlers::{
-    activity, admin, admin_session, admin_session_mgmt, auth, department, faculty, qr_activity, user, user_management,
+    activity, activity_template, admin, admin_session, admin_session_mgmt, announcement, auth, certificate, department,
+    faculty, impersonation, line, metrics, notification_preferences, notifications, qr_activity, two_factor, user,
+    user_management, webhook,
 };
 use crate::middleware::session::SessionState;
 
@@ -12,21 +20,50 @@ pub fn create_routes() -> Router<SessionState> {
     Router::new()
         // Bootstrap route (no auth required)
         .route("/api/admin/bootstrap", post(admin::bootstrap_admin))
+        // Public certificate verification (target of the QR printed on certificates)
+        .route("/api/verify/certificate/{token}", get(certificate::verify_certificate))
         // Student Authentication routes
         .route("/api/auth/login", post(auth::student_login))
+        .route("/api/auth/forgot-password", post(auth::forgot_password))
+        .route("/api/auth/reset-password", post(auth::reset_password))
         .route("/api/auth/register", post(auth::student_register))
         .route("/api/auth/logout", post(auth::logout))
         .route("/api/auth/me", get(auth::me))
+        .route("/api/auth/notification-preferences", get(notification_preferences::get_preferences))
+        .route("/api/auth/notification-preferences", put(notification_preferences::update_preferences))
         .route("/api/auth/sessions", get(auth::get_my_sessions))
         .route(
             "/api/auth/sessions/{session_id}",
             delete(auth::revoke_my_session),
         )
         .route("/api/auth/extend", post(auth::extend_session))
+        .route("/api/auth/refresh", post(auth::refresh_session))
+        .route("/api/auth/login-history", get(auth::get_my_login_history))
+        .route("/api/auth/change-password", post(auth::change_password))
         // Admin Authentication routes
         .route("/api/admin/auth/login", post(auth::admin_login))
         .route("/api/admin/auth/logout", post(auth::admin_logout))
         .route("/api/admin/auth/me", get(auth::admin_me))
+        // Admin two-factor authentication (verify completes a login, the rest need a session)
+        .route("/api/admin/auth/2fa/verify", post(two_factor::verify_two_factor_login))
+        .route("/api/admin/auth/2fa", get(two_factor::get_two_factor_status))
+        .route("/api/admin/auth/2fa/enroll", post(two_factor::enroll_two_factor))
+        .route("/api/admin/auth/2fa/confirm", post(two_factor::confirm_two_factor))
+        .route("/api/admin/auth/2fa/recovery-codes", post(two_factor::regenerate_recovery_codes))
+        .route("/api/admin/auth/2fa/disable", post(two_factor::disable_two_factor))
+        // Admin impersonation (SuperAdmin only; stop is called from the impersonated session)
+        .route("/api/admin/impersonate/stop", post(impersonation::stop_impersonation))
+        .route("/api/admin/impersonate/{user_id}", post(impersonation::start_impersonation))
+        // Webhooks (SuperAdmin only)
+        .route("/api/admin/announcements", get(announcement::list_announcements))
+        .route("/api/admin/announcements", post(announcement::create_announcement))
+        .route("/api/admin/announcements/{id}", delete(announcement::cancel_announcement))
+        .route("/api/admin/webhooks", get(webhook::list_webhooks))
+        .route("/api/admin/webhooks", post(webhook::create_webhook))
+        .route("/api/admin/webhooks/{id}", get(webhook::get_webhook))
+        .route("/api/admin/webhooks/{id}", put(webhook::update_webhook))
+        .route("/api/admin/webhooks/{id}", delete(webhook::delete_webhook))
+        .route("/api/admin/webhooks/{id}/deliveries", get(webhook::list_webhook_deliveries))
         // Faculty routes
         .route("/api/faculties", get(faculty::get_faculties))
         .route("/api/admin/faculties", get(faculty::get_all_faculties_admin))
@@ -50,27 +87,84 @@ pub fn create_routes() -> Router<SessionState> {
         .route("/api/faculties/{faculty_id}/admins", get(admin::get_faculty_admins))
         .route("/api/faculties/{faculty_id}/users", get(admin::get_faculty_users))
         .route("/api/faculties/{faculty_id}/admins", post(admin::create_faculty_admin))
+        .route("/api/faculties/{faculty_id}/line", get(line::get_faculty_line))
+        .route("/api/faculties/{faculty_id}/line", put(line::set_faculty_line))
+        .route("/api/faculties/{faculty_id}/line", delete(line::delete_faculty_line))
         // User routes
         .route("/api/users", get(user::get_users))
         .route("/api/users/{id}", get(user::get_user))
         .route("/api/users", post(user::create_user))
         .route("/api/users/{id}", put(user::update_user))
         .route("/api/users/{id}", delete(user::delete_user))
+        .route("/api/users/{id}/toggle-status", put(user::toggle_user_status))
+        .route("/api/users/{id}/anonymize", delete(user::anonymize_user))
         .route("/api/users/{id}/qr", get(user::get_user_qr))
+        .route("/api/users/{id}/qr.png", get(user::get_user_qr_png))
+        .route("/api/users/{id}/hours", get(user::get_user_hours))
+        .route("/api/users/{id}/transcript", get(user::get_user_transcript))
+        .route("/api/users/{id}/export", get(user::export_user_data))
+        .route("/api/users/{id}/activities.ics", get(user::get_user_activities_ics))
+        .route("/api/users/{id}/ical-token", post(user::rotate_ical_token))
+        .route("/api/users/{id}/line", get(line::get_user_line))
+        .route("/api/users/{id}/line", put(line::set_user_line))
+        .route("/api/users/{id}/line", delete(line::delete_user_line))
+        // In-app notification inbox
+        .route("/api/notifications", get(notifications::get_notifications))
+        .route("/api/notifications/read-all", post(notifications::mark_all_read))
+        .route("/api/notifications/{id}/read", post(notifications::mark_read))
+        // Calendar subscription via secret token (no session; apps can't send cookies)
+        .route("/api/ical/{file}", get(user::get_ical_feed))
+        // Public activity board (no auth required)
+        .route("/api/public/activities", get(activity::get_public_activities))
         // Activity routes
         .route("/api/activities", get(activity::get_activities))
         .route("/api/activities/{id}", get(activity::get_activity))
         .route("/api/activities", post(activity::create_activity))
         .route("/api/activities/{id}", put(activity::update_activity))
+        .route("/api/activities/{id}/cancel", post(activity::cancel_activity))
+        .route("/api/activities/{id}/restore", post(activity::restore_activity))
+        .route("/api/activities/{id}/clone", post(activity::clone_activity))
+        .route(
+            "/api/activities/from-template/{template_id}",
+            post(activity_template::create_activity_from_template),
+        )
+        // Activity templates (faculty-scoped for FacultyAdmins)
+        .route(
+            "/api/activity-templates",
+            get(activity_template::get_templates).post(activity_template::create_template),
+        )
+        .route(
+            "/api/activity-templates/{id}",
+            get(activity_template::get_template)
+                .put(activity_template::update_template)
+                .delete(activity_template::delete_template),
+        )
         .route("/api/activities/{id}", delete(activity::delete_activity))
         .route(
             "/api/activities/{id}/participations",
             get(activity::get_activity_participations),
         )
+        .route(
+            // {pid} is a participation id or the student's user id
+            "/api/activities/{id}/participations/{pid}/status",
+            put(activity::update_participation_status),
+        )
         .route(
             "/api/activities/{id}/participate",
-            post(activity::participate),
+            post(activity::participate).delete(activity::withdraw_participation),
         )
+        .route(
+            "/api/activities/{id}/feedback",
+            get(activity::get_activity_feedback).post(activity::submit_activity_feedback),
+        )
+        .route("/api/activities/{id}/certificate", get(certificate::get_activity_certificate))
+        .route(
+            "/api/activities/{id}/participation/notes",
+            put(activity::update_participation_notes),
+        )
+        .route("/api/activities/{id}/waitlist", get(activity::get_activity_waitlist))
+        .route("/api/activities/{id}/analytics", get(activity::get_activity_analytics))
+        .route("/api/activities/{id}/bulk-checkin", post(activity::bulk_checkin))
         .route("/api/activities/{id}/scan", post(activity::scan_qr))
         // Enhanced QR Code routes
         .route(
@@ -79,7 +173,17 @@ pub fn create_routes() -> Router<SessionState> {
         )
         .route("/api/qr/refresh", post(qr_activity::refresh_qr_secret))
         .route("/api/activities/{id}/checkin", post(qr_activity::qr_checkin))
+        .route("/api/activities/{id}/qr", get(qr_activity::get_activity_checkin_qr))
+        .route("/api/activities/{id}/self-checkin", post(qr_activity::self_checkin))
         .route("/api/admin/activities/assigned", get(qr_activity::get_assigned_activities))
+        .route(
+            "/api/activities/{id}/scanners",
+            get(qr_activity::get_activity_scanners).post(qr_activity::assign_activity_scanner),
+        )
+        .route(
+            "/api/activities/{id}/scanners/{user_id}",
+            delete(qr_activity::unassign_activity_scanner),
+        )
         .route("/api/activities/{id}/participants", get(activity::get_activity_participations))
         // Admin routes
         .route("/api/admin/dashboard", get(admin::get_dashboard))
@@ -95,12 +199,17 @@ pub fn create_routes() -> Router<SessionState> {
         // Enhanced admin management routes (SuperAdmin only)
         .route("/api/admin/system-admins", get(admin::get_all_system_admins))
         .route("/api/admin/bulk-operations", post(admin::bulk_admin_operations))
+        .route("/api/admin/audit-logs", get(admin_session::get_audit_logs))
+        .route("/api/admin/email-stats", get(admin::get_email_stats))
+        .route("/api/admin/leaderboard", get(admin::get_leaderboard))
+        .route("/api/admin/analytics/registrations", get(admin::get_registration_trends))
         // Enhanced user management routes (SuperAdmin only)
         .route("/api/admin/system-users", get(user_management::get_system_users))
         .route("/api/admin/user-statistics", get(user_management::get_user_statistics))
         // Faculty-scoped user statistics (FacultyAdmin and SuperAdmin)
         .route("/api/admin/faculty-user-statistics", get(user_management::get_faculty_user_statistics))
         .route("/api/admin/user-bulk-operations", post(user_management::bulk_user_operations))
+        .route("/api/admin/users/import", post(user_management::import_users))
         // Admin session management routes (Super Admin only)
         .route("/api/admin/sessions", get(auth::get_all_sessions))
         .route(
@@ -111,6 +220,11 @@ pub fn create_routes() -> Router<SessionState> {
             "/api/admin/users/{user_id}/sessions",
             delete(auth::admin_revoke_user_sessions),
         )
+        .route(
+            "/api/admin/users/{user_id}/login-history",
+            get(auth::get_user_login_history),
+        )
+        .route("/api/admin/users/{user_id}/no-shows", get(user_management::get_user_no_shows))
         .route(
             "/api/admin/active-sessions",
             get(admin_session::get_active_admin_sessions),
@@ -145,10 +259,106 @@ pub fn create_routes() -> Router<SessionState> {
             post(admin_session_mgmt::force_logout_faculty_sessions),
         )
         // Health check
+        // Liveness stays cheap; readiness checks Postgres and Redis
         .route("/health", get(health_check))
+        .route("/health/ready", get(readiness_check))
+        .route("/metrics", get(metrics::get_metrics))
         .route("/api/health", get(health_check))
 }
 
 async fn health_check() -> &'static str {
     "OK"
 }
+
+/// How long a single dependency check may take before it counts as failed
+const READINESS_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+async fn run_check<F, E>(check: F) -> (bool, Value)
+where
+    F: Future<Output = Result<(), E>>,
+    E: std::fmt::Display,
+{
+    let started = Instant::now();
+    let result = tokio::time::timeout(READINESS_CHECK_TIMEOUT, check).await;
+    let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+    match result {
+        Ok(Ok(())) => (true, json!({ "status": "ok", "latency_ms": latency_ms })),
+        Ok(Err(e)) => (
+            false,
+            json!({ "status": "error", "latency_ms": latency_ms, "error": e.to_string() }),
+        ),
+        Err(_) => (
+            false,
+            json!({ "status": "error", "latency_ms": latency_ms, "error": "timed out" }),
+        ),
+    }
+}
+
+async fn readiness_check(State(session_state): State<SessionState>) -> (StatusCode, Json<Value>) {
+    let database = run_check(async {
+        sqlx::query("SELECT 1")
+            .execute(&session_state.db_pool)
+            .await
+            .map(|_| ())
+    });
+    let redis = run_check(async {
+        session_state
+            .redis_health
+            .observe(session_state.session_store.ping().await)
+    });
+    let ((database_ok, database), (redis_ok, redis)) = tokio::join!(database, redis);
+
+    let failed: Vec<&str> = [("database", database_ok), ("redis", redis_ok)]
+        .into_iter()
+        .filter(|(_, ok)| !ok)
+        .map(|(name, _)| name)
+        .collect();
+
+    let status = if failed.is_empty() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        status,
+        Json(json!({
+            "status": if failed.is_empty() { "ready" } else { "not_ready" },
+            "failed": failed,
+            "checks": {
+                "database": database,
+                "redis": redis
+            }
+        })),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::RedisSessionStore;
+    use crate::test_support::test_state;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_readiness_names_failed_component() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        // Nothing listens on port 1, so the Redis check must fail while Postgres passes
+        let state = SessionState {
+            session_store: Arc::new(RedisSessionStore::new("redis://127.0.0.1:1/").unwrap()),
+            ..test_state(&pool)
+        };
+
+        let (status, Json(body)) = readiness_check(State(state)).await;
+
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["failed"], json!(["redis"]));
+        assert_eq!(body["checks"]["database"]["status"], "ok");
+        assert_eq!(body["checks"]["redis"]["status"], "error");
+        assert!(body["checks"]["database"]["latency_ms"].is_number());
+    }
+}
@@ -0,0 +1,277 @@
+use serde_json::json;
+use sqlx::Row;
+use tokio::time::{interval, Duration};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info};
+use uuid::Uuid;
+
+use crate::middleware::session::SessionState;
+use crate::models::notifications::{NotificationCategory, NotificationChannel};
+use crate::services::email_service::{EmailService, EmailTemplate};
+use crate::services::metrics::metrics;
+use crate::services::notification::{deliver_notification, is_channel_enabled, NotificationTarget};
+use crate::services::notification_inbox::store_notification;
+
+/// How often due reminders are looked for; offsets are matched against a window, so a
+/// reminder can be up to this late but is never skipped
+const REMINDER_CHECK_INTERVAL_SECONDS: u64 = 60;
+
+pub struct ActivityReminderService {
+    session_state: SessionState,
+}
+
+impl ActivityReminderService {
+    pub fn new(session_state: SessionState) -> Self {
+        Self { session_state }
+    }
+
+    /// ส่งอีเมลเตือนกิจกรรมตาม REMINDER_OFFSETS_MINUTES จนกว่า `shutdown` จะถูก cancel
+    pub async fn start_background_task(self, shutdown: CancellationToken) {
+        if self.session_state.app_config.reminder_offsets_minutes.is_empty() {
+            info!("Activity reminders disabled (REMINDER_OFFSETS_MINUTES is empty)");
+            return;
+        }
+
+        let mut interval = interval(Duration::from_secs(REMINDER_CHECK_INTERVAL_SECONDS));
+        info!(
+            "Starting activity reminder task (offsets: {:?} minutes)",
+            self.session_state.app_config.reminder_offsets_minutes
+        );
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.cancelled() => break,
+            }
+
+            let result = self.send_due_reminders().await;
+            metrics().record_task_run("activity_reminders", result.is_ok());
+
+            match result {
+                Ok(sent) if sent > 0 => info!("Queued {} activity reminders", sent),
+                Ok(_) => {}
+                Err(e) => error!("Failed to send activity reminders: {}", e),
+            }
+        }
+    }
+
+    /// Queue reminder emails for every offset that is due; returns how many were queued.
+    ///
+    /// Offsets are handled smallest first. Claiming an offset also claims every larger one,
+    /// so a student who registers an hour before start gets one reminder instead of
+    /// the 1-hour and 24-hour reminders at once.
+    pub async fn send_due_reminders(&self) -> Result<usize, sqlx::Error> {
+        let offsets = &self.session_state.app_config.reminder_offsets_minutes;
+        let start_time = self.session_state.app_config.activity_time_zone().start_sql("a");
+        let mut sent = 0;
+
+        for (i, offset) in offsets.iter().enumerate() {
+            let covered_offsets = &offsets[i..];
+
+            let claimed = sqlx::query(&format!(
+                r#"
+                INSERT INTO activity_reminders (activity_id, user_id, offset_minutes)
+                SELECT p.activity_id, p.user_id, o.offset_minutes
+                FROM participations p
+                JOIN activities a ON a.id = p.activity_id
+                CROSS JOIN unnest($2::int[]) AS o(offset_minutes)
+                WHERE p.status = 'registered'
+                  AND a.status = 'published'
+                  AND a.deleted_at IS NULL
+                  AND {start_time} BETWEEN NOW() AND NOW() + make_interval(mins => $1)
+                ON CONFLICT DO NOTHING
+                RETURNING activity_id, user_id, offset_minutes
+                "#
+            ))
+            .bind(offset)
+            .bind(covered_offsets)
+            .fetch_all(&self.session_state.db_pool)
+            .await?;
+
+            // Only rows for this offset mean the reminder hadn't been sent yet
+            for row in claimed {
+                if row.get::<i32, _>("offset_minutes") != *offset {
+                    continue;
+                }
+
+                let activity_id: Uuid = row.get("activity_id");
+                let user_id: Uuid = row.get("user_id");
+                if self.queue_reminder(activity_id, user_id, *offset).await? {
+                    sent += 1;
+                }
+            }
+        }
+
+        Ok(sent)
+    }
+
+    async fn queue_reminder(
+        &self,
+        activity_id: Uuid,
+        user_id: Uuid,
+        offset_minutes: i32,
+    ) -> Result<bool, sqlx::Error> {
+        let details = sqlx::query(
+            r#"
+            SELECT u.email, u.first_name, u.last_name, a.title, a.location,
+                   to_char(a.start_date + a.start_time_only, 'YYYY-MM-DD HH24:MI') AS starts_at
+            FROM users u, activities a
+            WHERE u.id = $1 AND a.id = $2
+            "#,
+        )
+        .bind(user_id)
+        .bind(activity_id)
+        .fetch_optional(&self.session_state.db_pool)
+        .await?;
+
+        let Some(details) = details else {
+            return Ok(false);
+        };
+
+        let user_name = format!(
+            "{} {}",
+            details.get::<String, _>("first_name"),
+            details.get::<String, _>("last_name")
+        );
+        let (subject, body_text, body_html) = EmailTemplate::activity_reminder_template(
+            &user_name,
+            &details.get::<String, _>("title"),
+            &details.get::<String, _>("starts_at"),
+            &details.get::<String, _>("location"),
+        )
+        .build();
+
+        store_notification(
+            &self.session_state.db_pool,
+            NotificationTarget::User(user_id),
+            NotificationCategory::Reminders,
+            &subject,
+            json!({ "activity_id": activity_id }),
+        )
+        .await;
+        let line_sent = deliver_notification(
+            &self.session_state,
+            NotificationTarget::User(user_id),
+            NotificationCategory::Reminders,
+            &subject,
+        )
+        .await;
+
+        let pool = &self.session_state.db_pool;
+        if !is_channel_enabled(pool, user_id, NotificationCategory::Reminders, NotificationChannel::Email).await {
+            debug!("User {} opted out of reminder emails", user_id);
+            return Ok(line_sent);
+        }
+
+        // The reminder is already claimed; a failed enqueue is logged rather than retried
+        // so the student never gets duplicates
+        match EmailService::new(self.session_state.clone())
+            .queue_email(
+                &details.get::<String, _>("email"),
+                Some(&user_name),
+                &subject,
+                &body_text,
+                body_html.as_deref(),
+                3,
+                Some(json!({
+                    "type": "activity_reminder",
+                    "activity_id": activity_id,
+                    "offset_minutes": offset_minutes
+                })),
+            )
+            .await
+        {
+            Ok(_) => {
+                debug!("Queued {}-minute reminder for activity {} to user {}", offset_minutes, activity_id, user_id);
+                Ok(true)
+            }
+            Err(e) => {
+                error!("Failed to queue reminder for activity {} to user {}: {}", activity_id, user_id, e);
+                Ok(line_sent)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{insert_user, test_state};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_reminder_is_sent_once_per_registration() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let mut app_config = crate::config::Config::from_env().unwrap();
+        app_config.reminder_offsets_minutes = vec![60, 1440];
+        let session_state = SessionState {
+            app_config: Arc::new(app_config),
+            ..test_state(&pool)
+        };
+
+        let tag = Uuid::new_v4().simple().to_string()[..8].to_string();
+        let email = format!("remind-{}@test.local", tag);
+        let user_id = insert_user(&pool, &format!("remind-{}", tag), None).await;
+
+        // Starts in 30 minutes, so both the 1-hour and 24-hour windows contain it
+        let activity_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO activities (title, description, location, academic_year, organizer,
+                start_date, end_date, start_time_only, end_time_only, hours, status, created_by)
+             SELECT 'Reminder', 'Reminder test', 'Lab', '2567', 'Test', s::date, s::date + 1,
+                s::time, s::time, 1, 'published', $1
+             FROM (SELECT (NOW() AT TIME ZONE 'UTC') + INTERVAL '30 minutes' AS s) t
+             RETURNING id",
+        )
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO participations (user_id, activity_id, status) VALUES ($1, $2, 'registered')")
+            .bind(user_id)
+            .bind(activity_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let service = ActivityReminderService::new(session_state);
+        let first = service.send_due_reminders().await.unwrap();
+        let second = service.send_due_reminders().await.unwrap();
+
+        let queued: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM email_queue WHERE to_email = $1")
+            .bind(&email)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let offsets: Vec<i32> = sqlx::query_scalar(
+            "SELECT offset_minutes FROM activity_reminders WHERE activity_id = $1 ORDER BY offset_minutes",
+        )
+        .bind(activity_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+
+        sqlx::query("DELETE FROM email_queue WHERE to_email = $1")
+            .bind(&email)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM activities WHERE id = $1")
+            .bind(activity_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(first >= 1);
+        assert_eq!(second, 0);
+        assert_eq!(queued, 1);
+        assert_eq!(offsets, vec![60, 1440]);
+    }
+}
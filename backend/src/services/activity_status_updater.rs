@@ -1,9 +1,18 @@
 use chrono::{DateTime, Utc};
 use crate::middleware::session::SessionState;
 use crate::models::activity::ActivityStatus;
+use crate::models::webhook::WebhookEvent;
+use crate::models::notifications::NotificationCategory;
+use crate::services::metrics::metrics;
+use crate::services::notification::{notify, NotificationTarget};
+use crate::services::notification_inbox::store_notification;
+use crate::services::webhook::enqueue_webhook_event;
+use serde_json::json;
 use sqlx::Row;
 use tokio::time::{interval, Duration};
+use tokio_util::sync::CancellationToken;
 use tracing::{info, error, debug};
+use uuid::Uuid;
 
 pub struct ActivityStatusUpdater {
     session_state: SessionState,
@@ -15,84 +24,147 @@ impl ActivityStatusUpdater {
     }
 
     /// เริ่มต้น background task สำหรับอัพเดตสถานะกิจกรรมอัตโนมัติ
-    pub async fn start_background_task(self) {
-        let mut interval = interval(Duration::from_secs(60)); // อัพเดตทุก 1 นาที
+    /// หยุดทำงานเมื่อ `shutdown` ถูก cancel (หลังจากรอบที่กำลังทำอยู่เสร็จ)
+    pub async fn start_background_task(self, shutdown: CancellationToken) {
+        // ค่าเริ่มต้นอัพเดตทุก 1 นาที (ACTIVITY_STATUS_INTERVAL_SECONDS)
+        let interval_seconds = self.session_state.app_config.activity_status_interval_seconds.max(1);
+        let mut interval = interval(Duration::from_secs(interval_seconds));
         
-        info!("Starting activity status updater background task");
+        info!("Starting activity status updater background task (every {} seconds)", interval_seconds);
         
         loop {
-            interval.tick().await;
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.cancelled() => break,
+            }
             
-            if let Err(e) = self.update_activity_statuses().await {
+            let result = self.update_activity_statuses().await;
+            metrics().record_task_run("activity_status_update", result.is_ok());
+
+            if let Err(e) = result {
                 error!("Failed to update activity statuses: {}", e);
             }
         }
     }
 
     /// อัพเดตสถานะกิจกรรมทั้งหมดตามวันที่และเวลา
+    /// วันที่/เวลาของกิจกรรมถูกตีความตาม timezone ที่ตั้งค่าไว้ แล้วเทียบกับเวลาปัจจุบันแบบ UTC
     pub async fn update_activity_statuses(&self) -> Result<(), sqlx::Error> {
         let now = Utc::now();
+        let time_zone = self.session_state.app_config.activity_time_zone();
+        let (start_time, end_time) = (time_zone.start_sql(""), time_zone.end_sql(""));
         
         // อัพเดตกิจกรรมที่ยังเป็น 'draft' หรือ 'published' ให้เป็น 'ongoing'
         // เมื่อเวลาปัจจุบันเข้าสู่ช่วงเวลากิจกรรม
-        let ongoing_update_result = sqlx::query(
+        let started_activity_ids: Vec<Uuid> = sqlx::query_scalar(&format!(
             r#"
             UPDATE activities 
             SET status = 'ongoing', updated_at = NOW()
-            WHERE status IN ('draft', 'published')
-            AND (
-                (start_date::timestamp + start_time_only) AT TIME ZONE 'UTC' <= $1
-                AND (end_date::timestamp + end_time_only) AT TIME ZONE 'UTC' > $1
-            )
+            WHERE status IN ('draft', 'published') AND deleted_at IS NULL
+            AND (publish_at IS NULL OR publish_at <= $1)
+            AND {start_time} <= $1 AND {end_time} > $1
+            RETURNING id
             "#
-        )
+        ))
         .bind(now)
-        .execute(&self.session_state.db_pool)
+        .fetch_all(&self.session_state.db_pool)
         .await?;
 
-        if ongoing_update_result.rows_affected() > 0 {
-            debug!("Updated {} activities to 'ongoing' status", ongoing_update_result.rows_affected());
+        if !started_activity_ids.is_empty() {
+            debug!("Updated {} activities to 'ongoing' status", started_activity_ids.len());
+        }
+        for activity_id in &started_activity_ids {
+            enqueue_webhook_event(
+                &self.session_state,
+                WebhookEvent::ActivityStarted,
+                json!({ "activity_id": activity_id, "status": ActivityStatus::Ongoing }),
+            )
+            .await;
         }
 
         // อัพเดตกิจกรรมที่เป็น 'ongoing' ให้เป็น 'completed'
         // เมื่อเวลาปัจจุบันเลยช่วงเวลากิจกรรมแล้ว
-        let completed_update_result = sqlx::query(
+        let completed_activity_ids: Vec<Uuid> = sqlx::query_scalar(&format!(
             r#"
             UPDATE activities 
             SET status = 'completed', updated_at = NOW()
-            WHERE status = 'ongoing'
-            AND (end_date::timestamp + end_time_only) AT TIME ZONE 'UTC' <= $1
+            WHERE status = 'ongoing' AND deleted_at IS NULL
+            AND {end_time} <= $1
+            RETURNING id
             "#
-        )
+        ))
         .bind(now)
-        .execute(&self.session_state.db_pool)
+        .fetch_all(&self.session_state.db_pool)
         .await?;
 
-        if completed_update_result.rows_affected() > 0 {
-            info!("Updated {} activities to 'completed' status", completed_update_result.rows_affected());
+        if !completed_activity_ids.is_empty() {
+            info!("Updated {} activities to 'completed' status", completed_activity_ids.len());
+            for activity_id in &completed_activity_ids {
+                debug!("Activity {} completed (end time passed)", activity_id);
+            }
+
+            let no_shows = self.mark_no_shows(&completed_activity_ids).await?;
+            if no_shows > 0 {
+                info!("Marked {} participations as no-show", no_shows);
+            }
+        }
+        for activity_id in &completed_activity_ids {
+            enqueue_webhook_event(
+                &self.session_state,
+                WebhookEvent::ActivityCompleted,
+                json!({ "activity_id": activity_id, "status": ActivityStatus::Completed }),
+            )
+            .await;
         }
 
-        // อัพเดตกิจกรรมที่ยัง 'draft' หรือ 'published' ให้เป็น 'published' 
-        // สำหรับกิจกรรมที่ยังไม่เริ่ม
-        let published_update_result = sqlx::query(
+        // อัพเดตกิจกรรมที่ยัง 'draft' ให้เป็น 'published' สำหรับกิจกรรมที่ยังไม่เริ่ม
+        // กิจกรรมที่ตั้ง publish_at ไว้จะรอจนถึงเวลานั้นก่อน
+        let published_activities = sqlx::query(&format!(
             r#"
             UPDATE activities 
             SET status = 'published', updated_at = NOW()
-            WHERE status = 'draft'
-            AND (start_date::timestamp + start_time_only) AT TIME ZONE 'UTC' > $1
+            WHERE status = 'draft' AND deleted_at IS NULL
+            AND (publish_at IS NULL OR publish_at <= $1)
+            AND {start_time} > $1
+            RETURNING id, title, faculty_id, publish_at IS NOT NULL AS scheduled
             "#
-        )
+        ))
         .bind(now)
-        .execute(&self.session_state.db_pool)
+        .fetch_all(&self.session_state.db_pool)
         .await?;
 
-        if published_update_result.rows_affected() > 0 {
-            debug!("Updated {} activities to 'published' status", published_update_result.rows_affected());
+        if !published_activities.is_empty() {
+            debug!("Updated {} activities to 'published' status", published_activities.len());
+        }
+        for activity in &published_activities {
+            let title: String = activity.get("title");
+            if activity.get::<bool, _>("scheduled") {
+                // There is no SSE channel in this service yet; this is where a
+                // NewActivityCreated event belongs once one exists
+                info!("Published scheduled activity {} ({})", activity.get::<Uuid, _>("id"), title);
+            }
+            if let Some(faculty_id) = activity.get::<Option<Uuid>, _>("faculty_id") {
+                let message = format!("New activity: \"{}\"", title);
+                store_notification(
+                    &self.session_state.db_pool,
+                    NotificationTarget::Faculty(faculty_id),
+                    NotificationCategory::Announcements,
+                    &message,
+                    json!({ "activity_id": activity.get::<Uuid, _>("id") }),
+                )
+                .await;
+                notify(
+                    &self.session_state,
+                    NotificationTarget::Faculty(faculty_id),
+                    NotificationCategory::Announcements,
+                    message,
+                );
+            }
         }
 
-        let total_updated = ongoing_update_result.rows_affected() 
-            + completed_update_result.rows_affected() 
-            + published_update_result.rows_affected();
+        let total_updated = started_activity_ids.len() as u64
+            + completed_activity_ids.len() as u64 
+            + published_activities.len() as u64;
 
         if total_updated > 0 {
             info!("Activity status update completed: {} activities updated", total_updated);
@@ -101,6 +173,34 @@ impl ActivityStatusUpdater {
         Ok(())
     }
 
+    /// ผู้ที่ยังเป็น 'registered' ตอนกิจกรรมจบคือผู้ที่ไม่มาเข้าร่วม -> เปลี่ยนเป็น 'no_show'
+    /// เปลี่ยนเฉพาะสถานะ 'registered' จึงเรียกซ้ำได้โดยไม่มีผลเพิ่ม
+    pub async fn mark_no_shows(&self, activity_ids: &[Uuid]) -> Result<u64, sqlx::Error> {
+        let marked = sqlx::query(
+            r#"
+            WITH affected AS (
+                SELECT id, activity_id, user_id FROM participations
+                WHERE activity_id = ANY($1) AND status = 'registered'
+                FOR UPDATE
+            ), updated AS (
+                UPDATE participations p SET status = 'no_show'
+                FROM affected WHERE p.id = affected.id
+                RETURNING p.id
+            )
+            INSERT INTO participation_events
+                (participation_id, activity_id, user_id, from_status, to_status, source, reason)
+            SELECT affected.id, affected.activity_id, affected.user_id, 'registered', 'no_show',
+                'no_show', 'Did not check in before the activity ended'
+            FROM affected JOIN updated ON updated.id = affected.id
+            "#,
+        )
+        .bind(activity_ids)
+        .execute(&self.session_state.db_pool)
+        .await?;
+
+        Ok(marked.rows_affected())
+    }
+
     /// ดึงสถิติการอัพเดตสถานะกิจกรรม
     pub async fn get_status_statistics(&self) -> Result<ActivityStatusStats, sqlx::Error> {
         let stats_result = sqlx::query(
@@ -109,6 +209,7 @@ impl ActivityStatusUpdater {
                 status,
                 COUNT(*) as count
             FROM activities
+            WHERE deleted_at IS NULL
             GROUP BY status
             ORDER BY count DESC
             "#
@@ -140,29 +241,36 @@ impl ActivityStatusUpdater {
     /// อัพเดตสถานะของกิจกรรมเฉพาะ ID
     pub async fn update_single_activity_status(&self, activity_id: uuid::Uuid) -> Result<ActivityStatus, sqlx::Error> {
         let now = Utc::now();
+        let time_zone = self.session_state.app_config.activity_time_zone();
         
         // ดึงข้อมูลกิจกรรม
-        let activity_result = sqlx::query(
+        let activity_result = sqlx::query(&format!(
             r#"
             SELECT 
                 id,
                 status,
-                (start_date::timestamp + start_time_only) AT TIME ZONE 'UTC' as start_time,
-                (end_date::timestamp + end_time_only) AT TIME ZONE 'UTC' as end_time
+                publish_at,
+                {} as start_time,
+                {} as end_time
             FROM activities
             WHERE id = $1
-            "#
-        )
+            "#,
+            time_zone.start_sql(""),
+            time_zone.end_sql("")
+        ))
         .bind(activity_id)
         .fetch_one(&self.session_state.db_pool)
         .await?;
 
         let current_status: ActivityStatus = activity_result.get("status");
+        let publish_at: Option<DateTime<Utc>> = activity_result.get("publish_at");
         let start_time: DateTime<Utc> = activity_result.get("start_time");
         let end_time: DateTime<Utc> = activity_result.get("end_time");
 
-        // คำนวณสถานะใหม่
-        let new_status = if now < start_time {
+        // คำนวณสถานะใหม่ (draft ที่ตั้งเวลาเผยแพร่ไว้ยังคงเป็น draft จนถึง publish_at)
+        let new_status = if current_status == ActivityStatus::Draft && publish_at.is_some_and(|at| now < at) {
+            ActivityStatus::Draft
+        } else if now < start_time {
             ActivityStatus::Published
         } else if now >= start_time && now <= end_time {
             ActivityStatus::Ongoing
@@ -181,6 +289,24 @@ impl ActivityStatusUpdater {
             .await?;
             
             info!("Updated activity {} status from {:?} to {:?}", activity_id, current_status, new_status);
+
+            if new_status == ActivityStatus::Completed {
+                self.mark_no_shows(&[activity_id]).await?;
+            }
+
+            let event = match new_status {
+                ActivityStatus::Ongoing => Some(WebhookEvent::ActivityStarted),
+                ActivityStatus::Completed => Some(WebhookEvent::ActivityCompleted),
+                _ => None,
+            };
+            if let Some(event) = event {
+                enqueue_webhook_event(
+                    &self.session_state,
+                    event,
+                    json!({ "activity_id": activity_id, "status": new_status }),
+                )
+                .await;
+            }
         }
 
         Ok(new_status)
@@ -195,4 +321,227 @@ pub struct ActivityStatusStats {
     pub ongoing_count: i64,
     pub completed_count: i64,
     pub cancelled_count: i64,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{insert_user, test_state};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_ongoing_activity_completed_after_end_time() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let student_id = format!("u{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let creator_id = insert_user(&pool, &student_id, None).await;
+
+        // Ended yesterday (UTC) but still marked ongoing
+        let activity_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO activities (title, description, location, academic_year, organizer,
+                start_date, end_date, start_time_only, end_time_only, hours, status, created_by)
+             VALUES ('Past', 'Ended activity', 'Lab', '2567', 'Test',
+                (NOW() AT TIME ZONE 'UTC')::date - 2, (NOW() AT TIME ZONE 'UTC')::date - 1,
+                '09:00', '17:00', 1, 'ongoing', $1)
+             RETURNING id",
+        )
+        .bind(creator_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        ActivityStatusUpdater::new(state)
+            .update_activity_statuses()
+            .await
+            .unwrap();
+
+        let status: ActivityStatus = sqlx::query_scalar("SELECT status FROM activities WHERE id = $1")
+            .bind(activity_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(status, ActivityStatus::Completed);
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(creator_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_registered_participants_marked_no_show_once() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let tag = &Uuid::new_v4().simple().to_string()[..8];
+        let mut user_ids = Vec::new();
+        for prefix in ["c", "r", "i"] {
+            let student_id = format!("{}{}", prefix, tag);
+            let user_id = insert_user(&pool, &student_id, None).await;
+            user_ids.push(user_id);
+        }
+
+        let activity_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO activities (title, description, location, academic_year, organizer,
+                start_date, end_date, start_time_only, end_time_only, hours, status, created_by)
+             VALUES ('Past', 'Ended activity', 'Lab', '2567', 'Test',
+                (NOW() AT TIME ZONE 'UTC')::date - 2, (NOW() AT TIME ZONE 'UTC')::date - 1,
+                '09:00', '17:00', 1, 'ongoing', $1)
+             RETURNING id",
+        )
+        .bind(user_ids[0])
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        for (user_id, status) in [(user_ids[1], "registered"), (user_ids[2], "checked_in")] {
+            sqlx::query(
+                "INSERT INTO participations (user_id, activity_id, status) VALUES ($1, $2, $3::participation_status)",
+            )
+            .bind(user_id)
+            .bind(activity_id)
+            .bind(status)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let updater = ActivityStatusUpdater::new(state);
+        updater.update_activity_statuses().await.unwrap();
+        let marked_again = updater.mark_no_shows(&[activity_id]).await.unwrap();
+
+        let statuses: Vec<String> = sqlx::query_scalar(
+            "SELECT status::text FROM participations WHERE activity_id = $1 ORDER BY status::text",
+        )
+        .bind(activity_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        let events: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM participation_events WHERE activity_id = $1 AND to_status = 'no_show'",
+        )
+        .bind(activity_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        sqlx::query("DELETE FROM users WHERE id = ANY($1)")
+            .bind(&user_ids)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(statuses, vec!["checked_in", "no_show"]);
+        assert_eq!(marked_again, 0);
+        assert_eq!(events, 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_published_activity_becomes_ongoing_in_configured_timezone() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let mut app_config = crate::config::Config::from_env().unwrap();
+        app_config.activity_timezone = Some("Asia/Bangkok".to_string());
+        let state = SessionState {
+            app_config: Arc::new(app_config),
+            ..test_state(&pool)
+        };
+
+        let student_id = format!("u{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let creator_id = insert_user(&pool, &student_id, None).await;
+
+        // Started an hour ago Bangkok time; read as UTC it would still be hours away
+        let activity_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO activities (title, description, location, academic_year, organizer,
+                start_date, end_date, start_time_only, end_time_only, hours, status, created_by)
+             SELECT 'Local', 'Local time activity', 'Lab', '2567', 'Test',
+                s::date, e::date, s::time, e::time, 1, 'published', $1
+             FROM (SELECT (NOW() AT TIME ZONE 'Asia/Bangkok') - INTERVAL '1 hour' AS s,
+                          (NOW() AT TIME ZONE 'Asia/Bangkok') + INTERVAL '2 hours' AS e) t
+             RETURNING id",
+        )
+        .bind(creator_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        ActivityStatusUpdater::new(state)
+            .update_activity_statuses()
+            .await
+            .unwrap();
+
+        let status: ActivityStatus = sqlx::query_scalar("SELECT status FROM activities WHERE id = $1")
+            .bind(activity_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(creator_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(status, ActivityStatus::Ongoing);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_scheduled_draft_published_once_publish_at_passes() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let state = test_state(&pool);
+
+        let student_id = format!("u{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let creator_id = insert_user(&pool, &student_id, None).await;
+
+        // Both start tomorrow; only the first one's publish time has passed
+        let mut activity_ids = Vec::new();
+        for publish_offset in ["-1 minute", "1 hour"] {
+            let activity_id: Uuid = sqlx::query_scalar(
+                "INSERT INTO activities (title, description, location, academic_year, organizer,
+                    start_date, end_date, start_time_only, end_time_only, hours, status, created_by, publish_at)
+                 VALUES ('Scheduled', 'Scheduled publish', 'Lab', '2567', 'Test',
+                    (NOW() AT TIME ZONE 'UTC')::date + 1, (NOW() AT TIME ZONE 'UTC')::date + 1,
+                    '09:00', '17:00', 1, 'draft', $1, NOW() + $2::interval)
+                 RETURNING id",
+            )
+            .bind(creator_id)
+            .bind(publish_offset)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            activity_ids.push(activity_id);
+        }
+
+        ActivityStatusUpdater::new(state)
+            .update_activity_statuses()
+            .await
+            .unwrap();
+
+        let statuses: Vec<ActivityStatus> =
+            sqlx::query_scalar("SELECT status FROM activities WHERE id = ANY($1) ORDER BY publish_at")
+                .bind(&activity_ids)
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(creator_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(statuses, vec![ActivityStatus::Published, ActivityStatus::Draft]);
+    }
+}
@@ -0,0 +1,140 @@
+use sqlx::PgPool;
+use tokio::time::{interval, Duration};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::services::metrics::metrics;
+
+/// Announcements go out at most this late after `publish_at`
+const ANNOUNCEMENT_CHECK_INTERVAL_SECONDS: u64 = 30;
+
+/// Publishes scheduled announcements once `publish_at` has passed
+pub struct AnnouncementPublisher {
+    pool: PgPool,
+}
+
+impl AnnouncementPublisher {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn start_background_task(self, shutdown: CancellationToken) {
+        let mut interval = interval(Duration::from_secs(ANNOUNCEMENT_CHECK_INTERVAL_SECONDS));
+        info!("Started scheduled announcement task");
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.cancelled() => break,
+            }
+
+            let result = publish_due_announcements(&self.pool).await;
+            metrics().record_task_run("scheduled_announcements", result.is_ok());
+
+            if let Err(e) = result {
+                error!("Failed to publish scheduled announcements: {}", e);
+            }
+        }
+    }
+}
+
+/// Mark every due announcement published; returns their ids. An announcement whose
+/// display window already ended (the server was down) is published anyway so the
+/// admin list shows it went out, but it never reaches the inbox.
+pub async fn publish_due_announcements(pool: &PgPool) -> Result<Vec<Uuid>, sqlx::Error> {
+    let published: Vec<(Uuid, String)> = sqlx::query_as(
+        r#"
+        UPDATE scheduled_announcements
+        SET status = 'published', published_at = NOW(), updated_at = NOW()
+        WHERE status = 'scheduled' AND publish_at <= NOW()
+        RETURNING id, title
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for (id, title) in &published {
+        info!("Published announcement {} ({})", id, title);
+    }
+    Ok(published.into_iter().map(|(id, _)| id).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::notification_inbox::{list_notifications, mark_notification_read};
+    use crate::test_support::insert_user;
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_publish_due_announcements() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let tag = Uuid::new_v4().simple().to_string()[..8].to_string();
+        let user_id = insert_user(&pool, &format!("an{}", tag), None).await;
+
+        let mut ids = Vec::new();
+        for (title, publish_offset, display_offset) in [
+            ("due", -60, Some(3600)),
+            ("future", 3600, None),
+            ("expired", -7200, Some(-3600)),
+        ] {
+            let id: Uuid = sqlx::query_scalar(
+                r#"
+                INSERT INTO scheduled_announcements (title, message, publish_at, display_until)
+                VALUES ($1, 'Maintenance tonight',
+                        NOW() + make_interval(secs => $2),
+                        NOW() + make_interval(secs => $3::INT))
+                RETURNING id
+                "#,
+            )
+            .bind(format!("{} {}", title, tag))
+            .bind(publish_offset as f64)
+            .bind(display_offset)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            ids.push(id);
+        }
+        let (due, future, expired) = (ids[0], ids[1], ids[2]);
+
+        let published = publish_due_announcements(&pool).await.unwrap();
+        let visible: Vec<Uuid> = list_notifications(&pool, user_id, false, 100, 0)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|n| n.category == "system")
+            .map(|n| n.id)
+            .collect();
+        let marked = mark_notification_read(&pool, user_id, due).await.unwrap();
+
+        sqlx::query("UPDATE scheduled_announcements SET status = 'cancelled' WHERE id = $1")
+            .bind(due)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let after_cancel = mark_notification_read(&pool, user_id, due).await.unwrap();
+
+        sqlx::query("DELETE FROM scheduled_announcements WHERE id = ANY($1)")
+            .bind(&ids)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(published.contains(&due));
+        assert!(published.contains(&expired));
+        assert!(!published.contains(&future));
+        assert!(visible.contains(&due));
+        assert!(!visible.contains(&future));
+        assert!(!visible.contains(&expired));
+        assert!(marked);
+        assert!(!after_cancel);
+    }
+}
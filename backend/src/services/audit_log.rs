@@ -0,0 +1,36 @@
+use sqlx::PgPool;
+
+use crate::models::audit_log::NewAuditLog;
+
+/// บันทึก audit log ลงฐานข้อมูล
+pub async fn insert_audit_log(pool: &PgPool, entry: &NewAuditLog) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO audit_logs (actor_user_id, action, target_type, target_id, reason, details)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+    )
+    .bind(entry.actor_user_id)
+    .bind(&entry.action)
+    .bind(&entry.target_type)
+    .bind(&entry.target_id)
+    .bind(&entry.reason)
+    .bind(&entry.details)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// บันทึก audit log โดยไม่ทำให้ action หลักล้มเหลว (ใช้หลังจาก commit แล้ว)
+pub async fn record_audit_log(pool: &PgPool, entry: NewAuditLog) {
+    if let Err(e) = insert_audit_log(pool, &entry).await {
+        tracing::warn!(
+            action = %entry.action,
+            target_type = %entry.target_type,
+            target_id = entry.target_id.as_deref().unwrap_or(""),
+            "Failed to write audit log: {}",
+            e
+        );
+    }
+}
@@ -1,64 +1,155 @@
 use chrono::Utc;
+use std::future::Future;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::task::JoinHandle;
 use tokio::time::interval;
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
 use crate::middleware::session::SessionState;
+use crate::services::metrics::metrics;
+use crate::services::activity_reminder::ActivityReminderService;
+use crate::services::announcement::AnnouncementPublisher;
+use crate::services::email_service::EmailService;
+use crate::services::webhook::WebhookDispatcher;
 use crate::services::ActivityStatusUpdater;
 
+/// How long shutdown waits for a task to finish its current run before aborting it
+const TASK_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
 // Background task manager
 pub struct BackgroundTaskManager {
     session_state: SessionState,
+    shutdown_token: CancellationToken,
+    handles: Vec<(&'static str, JoinHandle<()>)>,
 }
 
 impl BackgroundTaskManager {
-    pub fn new(session_state: SessionState) -> Self { Self { session_state } }
+    pub fn new(session_state: SessionState) -> Self {
+        Self {
+            session_state,
+            shutdown_token: CancellationToken::new(),
+            handles: Vec::new(),
+        }
+    }
 
     // Start all background tasks
-    pub async fn start_all_tasks(&self) {
+    pub async fn start_all_tasks(&mut self) {
         let session_state = self.session_state.clone();
 
         // Session cleanup task
         let cleanup_session_state = session_state.clone();
-        tokio::spawn(async move {
-            session_cleanup_task(cleanup_session_state).await;
+        let shutdown = self.shutdown_token.clone();
+        self.spawn("session_cleanup", async move {
+            session_cleanup_task(cleanup_session_state, shutdown).await;
         });
 
         // Database session sync task
         let sync_session_state = session_state.clone();
-        tokio::spawn(async move {
-            database_session_sync_task(sync_session_state).await;
+        let shutdown = self.shutdown_token.clone();
+        self.spawn("database_session_sync", async move {
+            database_session_sync_task(sync_session_state, shutdown).await;
         });
 
 
         // Session activity monitoring task
         let monitoring_session_state = session_state.clone();
-        tokio::spawn(async move {
-            session_activity_monitoring_task(monitoring_session_state).await;
+        let shutdown = self.shutdown_token.clone();
+        self.spawn("session_activity_monitoring", async move {
+            session_activity_monitoring_task(monitoring_session_state, shutdown).await;
         });
 
         // Admin session audit task
         let audit_session_state = session_state.clone();
-        tokio::spawn(async move {
-            admin_session_audit_task(audit_session_state).await;
+        let shutdown = self.shutdown_token.clone();
+        self.spawn("admin_session_audit", async move {
+            admin_session_audit_task(audit_session_state, shutdown).await;
         });
 
         // Activity status updater task
         let activity_session_state = session_state.clone();
-        tokio::spawn(async move {
+        let shutdown = self.shutdown_token.clone();
+        self.spawn("activity_status_update", async move {
             let activity_updater = ActivityStatusUpdater::new(activity_session_state);
-            activity_updater.start_background_task().await;
+            activity_updater.start_background_task(shutdown).await;
+        });
+
+        // Activity reminder task
+        let reminder_session_state = session_state.clone();
+        let shutdown = self.shutdown_token.clone();
+        self.spawn("activity_reminders", async move {
+            ActivityReminderService::new(reminder_session_state)
+                .start_background_task(shutdown)
+                .await;
+        });
+
+        // Email queue worker
+        let email_session_state = session_state.clone();
+        let shutdown = self.shutdown_token.clone();
+        self.spawn("email_queue", async move {
+            EmailService::new(email_session_state)
+                .run_email_processor(shutdown)
+                .await;
+        });
+
+        // Webhook delivery worker
+        let webhook_session_state = session_state.clone();
+        let shutdown = self.shutdown_token.clone();
+        self.spawn("webhook_deliveries", async move {
+            WebhookDispatcher::new(webhook_session_state)
+                .start_background_task(shutdown)
+                .await;
+        });
+
+        // Scheduled announcement publisher
+        let announcement_pool = session_state.db_pool.clone();
+        let shutdown = self.shutdown_token.clone();
+        self.spawn("scheduled_announcements", async move {
+            AnnouncementPublisher::new(announcement_pool)
+                .start_background_task(shutdown)
+                .await;
         });
 
         tracing::info!("All background tasks started successfully");
     }
+
+    fn spawn<F>(&mut self, name: &'static str, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.handles.push((name, tokio::spawn(task)));
+    }
+
+    /// Signal every task to stop after its current run and wait for them to exit
+    pub async fn shutdown(self) {
+        self.shutdown_token.cancel();
+
+        let mut stopped = Vec::new();
+        let mut aborted = Vec::new();
+
+        for (name, mut handle) in self.handles {
+            match tokio::time::timeout(TASK_SHUTDOWN_TIMEOUT, &mut handle).await {
+                Ok(_) => stopped.push(name),
+                Err(_) => {
+                    handle.abort();
+                    aborted.push(name);
+                }
+            }
+        }
+
+        tracing::info!(
+            "Background tasks shut down - stopped: [{}], aborted after {}s: [{}]",
+            stopped.join(", "),
+            TASK_SHUTDOWN_TIMEOUT.as_secs(),
+            aborted.join(", ")
+        );
+    }
 }
 
 // Task 1: Clean up expired sessions from Redis
-async fn session_cleanup_task(session_state: SessionState) {
-    use crate::services::redis_session::SessionConfig;
-    let config = SessionConfig::default();
+async fn session_cleanup_task(session_state: SessionState, shutdown: CancellationToken) {
+    let config = session_state.config.clone();
     let interval_seconds = config.cleanup_interval_minutes * 60; // Convert minutes to seconds
     let mut interval = interval(Duration::from_secs(interval_seconds as u64));
 
@@ -66,9 +157,15 @@ async fn session_cleanup_task(session_state: SessionState) {
                   config.cleanup_interval_minutes / 60);
 
     loop {
-        interval.tick().await;
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown.cancelled() => break,
+        }
+
+        let result = session_state.session_store.cleanup_expired_sessions().await;
+        metrics().record_task_run("session_cleanup", result.is_ok());
 
-        match session_state.redis_store.cleanup_expired_sessions().await {
+        match result {
             Ok(cleaned_count) => {
                 if cleaned_count > 0 {
                     tracing::info!("Cleaned up {} expired sessions", cleaned_count);
@@ -82,15 +179,21 @@ async fn session_cleanup_task(session_state: SessionState) {
 }
 
 // Task 2: Sync session metadata between Redis and Database
-async fn database_session_sync_task(session_state: SessionState) {
+async fn database_session_sync_task(session_state: SessionState, shutdown: CancellationToken) {
     let mut interval = interval(Duration::from_secs(600)); // Every 10 minutes
 
     tracing::info!("Started database session sync task");
 
     loop {
-        interval.tick().await;
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown.cancelled() => break,
+        }
+
+        let result = sync_database_sessions(&session_state).await;
+        metrics().record_task_run("database_session_sync", result.is_ok());
 
-        match sync_database_sessions(&session_state).await {
+        match result {
             Ok(synced_count) => {
                 if synced_count > 0 {
                     tracing::debug!("Synced {} sessions with database", synced_count);
@@ -105,15 +208,21 @@ async fn database_session_sync_task(session_state: SessionState) {
 
 
 // Task 5: Monitor session activity patterns
-async fn session_activity_monitoring_task(session_state: SessionState) {
+async fn session_activity_monitoring_task(session_state: SessionState, shutdown: CancellationToken) {
     let mut interval = interval(Duration::from_secs(3600)); // Every hour
 
     tracing::info!("Started session activity monitoring task");
 
     loop {
-        interval.tick().await;
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown.cancelled() => break,
+        }
+
+        let result = monitor_session_activity(&session_state).await;
+        metrics().record_task_run("session_activity_monitoring", result.is_ok());
 
-        match monitor_session_activity(&session_state).await {
+        match result {
             Ok(stats) => {
                 tracing::info!(
                     "Session activity stats - Total: {}, Admin: {}, Active: {}",
@@ -130,15 +239,21 @@ async fn session_activity_monitoring_task(session_state: SessionState) {
 }
 
 // Task 6: Audit admin session activities
-async fn admin_session_audit_task(session_state: SessionState) {
+async fn admin_session_audit_task(session_state: SessionState, shutdown: CancellationToken) {
     let mut interval = interval(Duration::from_secs(1800)); // Every 30 minutes
 
     tracing::info!("Started admin session audit task");
 
     loop {
-        interval.tick().await;
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown.cancelled() => break,
+        }
 
-        match audit_admin_sessions(&session_state).await {
+        let result = audit_admin_sessions(&session_state).await;
+        metrics().record_task_run("admin_session_audit", result.is_ok());
+
+        match result {
             Ok(audit_count) => {
                 if audit_count > 0 {
                     tracing::debug!("Audited {} admin sessions", audit_count);
@@ -155,12 +270,12 @@ async fn admin_session_audit_task(session_state: SessionState) {
 
 async fn sync_database_sessions(session_state: &SessionState) -> Result<usize, anyhow::Error> {
     // Get active sessions from Redis
-    let active_session_ids = session_state.redis_store.get_active_sessions(None).await?;
+    let active_session_ids = session_state.session_store.get_active_sessions(None).await?;
 
     let mut synced_count = 0;
 
     for session_id in &active_session_ids {
-        if let Some(session) = session_state.redis_store.get_session(session_id).await? {
+        if let Some(session) = session_state.session_store.get_session(session_id).await? {
             // Update database record
             let result = sqlx::query(
                 r#"
@@ -212,7 +327,7 @@ struct SessionActivityStats {
 async fn monitor_session_activity(
     session_state: &SessionState,
 ) -> Result<SessionActivityStats, anyhow::Error> {
-    let session_ids = session_state.redis_store.get_active_sessions(None).await?;
+    let session_ids = session_state.session_store.get_active_sessions(None).await?;
 
     let mut stats = SessionActivityStats {
         total_sessions: session_ids.len(),
@@ -223,7 +338,7 @@ async fn monitor_session_activity(
     };
 
     for session_id in session_ids {
-        if let Some(session) = session_state.redis_store.get_session(&session_id).await? {
+        if let Some(session) = session_state.session_store.get_session(&session_id).await? {
             // Check if session is active (accessed within last 30 minutes)
             let last_activity = Utc::now().timestamp() - session.last_accessed.timestamp();
             if last_activity <= 1800 {
@@ -268,11 +383,11 @@ async fn monitor_session_activity(
 
 async fn audit_admin_sessions(session_state: &SessionState) -> Result<usize, anyhow::Error> {
     // Get all admin sessions
-    let session_ids = session_state.redis_store.get_active_sessions(None).await?;
+    let session_ids = session_state.session_store.get_active_sessions(None).await?;
     let mut audit_count = 0;
 
     for session_id in session_ids {
-        if let Some(session) = session_state.redis_store.get_session(&session_id).await? {
+        if let Some(session) = session_state.session_store.get_session(&session_id).await? {
             // Check if user is admin
             if let Ok(Some(admin_role)) = get_user_admin_role(session_state, session.user_id).await
             {
@@ -342,3 +457,30 @@ async fn get_faculty_by_id(
 
     Ok(faculty)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::RedisSessionStore;
+    use crate::test_support::test_state;
+
+    #[tokio::test]
+    async fn test_shutdown_stops_running_tasks() {
+        // Neither backend is reachable; the task only has to notice cancellation
+        let pool = sqlx::PgPool::connect_lazy("postgres://localhost:1/unused").unwrap();
+        let session_state = SessionState {
+            session_store: Arc::new(RedisSessionStore::new("redis://127.0.0.1:1/").unwrap()),
+            ..test_state(&pool)
+        };
+
+        let mut manager = BackgroundTaskManager::new(session_state.clone());
+        let shutdown = manager.shutdown_token.clone();
+        manager.spawn("session_cleanup", async move {
+            session_cleanup_task(session_state, shutdown).await;
+        });
+
+        tokio::time::timeout(Duration::from_secs(5), manager.shutdown())
+            .await
+            .expect("background task did not stop after cancellation");
+    }
+}
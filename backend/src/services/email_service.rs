@@ -1,11 +1,23 @@
 use anyhow::Result;
+use lettre::message::{header::ContentType, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
 use serde_json::json;
 use std::time::Duration;
 use tokio::time::interval;
+use tokio_util::sync::CancellationToken;
 use tracing::{info, warn, error, debug, instrument};
 
 use crate::middleware::session::SessionState;
-use crate::models::notifications::{EmailQueue, NotificationStatus};
+use crate::models::notifications::{EmailQueue, EmailStats, NotificationStatus};
+use crate::services::metrics::metrics;
+
+/// Longest wait between two attempts at the same email
+const MAX_RETRY_DELAY_SECONDS: u64 = 24 * 60 * 60;
+
+/// Claimed emails are hidden from other workers for this long; if the worker dies mid-batch
+/// they become due again instead of being lost
+const CLAIM_LEASE_SECONDS: i64 = 300;
 
 /// SMTP Email Service
 pub struct EmailService {
@@ -46,6 +58,56 @@ impl Default for SmtpConfig {
     }
 }
 
+type SmtpTransport = AsyncSmtpTransport<Tokio1Executor>;
+
+impl SmtpConfig {
+    /// Connection pool to the relay: implicit TLS on port 465, STARTTLS on other ports, and
+    /// plain SMTP with SMTP_USE_TLS=false (local relays and catchers such as MailHog)
+    fn transport(&self) -> Result<SmtpTransport> {
+        let builder = if !self.use_tls {
+            SmtpTransport::builder_dangerous(&self.host)
+        } else if self.port == 465 {
+            SmtpTransport::relay(&self.host)?
+        } else {
+            SmtpTransport::starttls_relay(&self.host)?
+        };
+
+        let mut builder = builder
+            .port(self.port)
+            .timeout(Some(Duration::from_secs(self.timeout_seconds)));
+        if !self.username.is_empty() {
+            builder = builder.credentials(Credentials::new(self.username.clone(), self.password.clone()));
+        }
+        Ok(builder.build())
+    }
+
+    fn sender_mailbox(&self) -> Result<Mailbox> {
+        Ok(Mailbox::new(Some(self.from_name.clone()), self.from_address.parse()?))
+    }
+}
+
+/// MIME message for a queued email; text only, or text and HTML alternatives
+fn build_message(email: &EmailQueue, smtp_config: &SmtpConfig) -> Result<Message> {
+    let to = Mailbox::new(email.to_name.clone(), email.to_email.parse()?);
+    let builder = Message::builder()
+        .from(smtp_config.sender_mailbox()?)
+        .to(to)
+        .subject(email.subject.clone());
+
+    let message = match &email.body_html {
+        Some(body_html) => builder.multipart(MultiPart::alternative_plain_html(
+            email.body_text.clone(),
+            body_html.clone(),
+        ))?,
+        None => builder.singlepart(
+            SinglePart::builder()
+                .header(ContentType::TEXT_PLAIN)
+                .body(email.body_text.clone()),
+        )?,
+    };
+    Ok(message)
+}
+
 impl EmailService {
     pub fn new(session_state: SessionState) -> Self {
         Self {
@@ -61,16 +123,9 @@ impl EmailService {
         }
     }
 
-    /// Start email processing background task
-    pub async fn start_email_processor(&self) {
-        let session_state = self.session_state.clone();
-        let smtp_config = self.smtp_config.clone();
-
-        tokio::spawn(async move {
-            email_processor_task(session_state, smtp_config).await;
-        });
-
-        info!("Email processor task started");
+    /// Drain the email queue until `shutdown` is cancelled (รันผ่าน BackgroundTaskManager)
+    pub async fn run_email_processor(self, shutdown: CancellationToken) {
+        email_processor_task(self.session_state, self.smtp_config, shutdown).await;
     }
 
     /// Send a single email immediately
@@ -96,8 +151,8 @@ impl EmailService {
         sqlx::query(
             r#"
             INSERT INTO email_queue 
-            (id, to_email, to_name, subject, body_text, body_html, priority, status, scheduled_for, metadata)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW(), $9)
+            (id, to_email, to_name, subject, body_text, body_html, priority, status, max_attempts, scheduled_for, metadata)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, NOW(), $10)
             "#
         )
         .bind(email_id)
@@ -108,6 +163,7 @@ impl EmailService {
         .bind(body_html)
         .bind(priority)
         .bind(NotificationStatus::Pending)
+        .bind(self.session_state.app_config.email_max_attempts)
         .bind(metadata.unwrap_or(json!({})))
         .execute(&self.session_state.db_pool)
         .await?;
@@ -117,35 +173,38 @@ impl EmailService {
     }
 
     /// Get email sending statistics
+    /// `failed` คืออีเมลที่ส่งไม่สำเร็จครบจำนวนครั้งแล้ว (dead letter) จะไม่ถูกส่งซ้ำอัตโนมัติ
     pub async fn get_email_stats(&self) -> Result<EmailStats> {
         let stats_query = sqlx::query!(
             r#"
             SELECT 
-                COUNT(*) FILTER (WHERE DATE(created_at) = CURRENT_DATE) as emails_sent_today,
+                COUNT(*) FILTER (WHERE status = 'sent' AND sent_at >= CURRENT_DATE) as emails_sent_today,
                 COUNT(*) FILTER (WHERE status = 'pending') as emails_pending,
                 COUNT(*) FILTER (WHERE status = 'failed') as emails_failed,
-                AVG(EXTRACT(EPOCH FROM (sent_at - created_at)) * 1000) FILTER (WHERE sent_at IS NOT NULL) as avg_send_time_ms,
+                COUNT(*) FILTER (WHERE status = 'sent' AND sent_at >= CURRENT_DATE - INTERVAL '7 days') as sent_last_7_days,
+                COUNT(*) FILTER (WHERE status = 'failed' AND updated_at >= CURRENT_DATE - INTERVAL '7 days') as failed_last_7_days,
+                AVG(EXTRACT(EPOCH FROM (sent_at - created_at)) * 1000)
+                    FILTER (WHERE sent_at >= CURRENT_DATE - INTERVAL '7 days') as avg_send_time_ms,
                 MAX(sent_at) as last_successful_send
             FROM email_queue
-            WHERE created_at >= CURRENT_DATE - INTERVAL '7 days'
             "#
         )
         .fetch_one(&self.session_state.db_pool)
         .await?;
 
-        let total_sent = stats_query.emails_sent_today.unwrap_or(0) as i32;
-        let failed = stats_query.emails_failed.unwrap_or(0) as i32;
-        let delivery_rate = if total_sent + failed > 0 {
-            (total_sent as f64 / (total_sent + failed) as f64) * 100.0
+        let sent = stats_query.sent_last_7_days.unwrap_or(0);
+        let failed = stats_query.failed_last_7_days.unwrap_or(0);
+        let delivery_rate = if sent + failed > 0 {
+            (sent as f64 / (sent + failed) as f64) * 100.0
         } else {
             0.0
         };
 
         Ok(EmailStats {
-            emails_sent_today: total_sent,
+            emails_sent_today: stats_query.emails_sent_today.unwrap_or(0) as i32,
             emails_pending: stats_query.emails_pending.unwrap_or(0) as i32,
-            emails_failed: failed,
-            bounce_rate: 100.0 - delivery_rate, // Simple bounce rate calculation
+            emails_failed: stats_query.emails_failed.unwrap_or(0) as i32,
+            bounce_rate: if sent + failed > 0 { 100.0 - delivery_rate } else { 0.0 },
             delivery_rate,
             avg_send_time_ms: stats_query.avg_send_time_ms
                 .map(|decimal| decimal.to_string().parse().unwrap_or(0.0))
@@ -158,41 +217,44 @@ impl EmailService {
     // Internal email sending implementation
     #[instrument(skip(self, email))]
     async fn send_email_impl(&self, email: &EmailQueue) -> Result<()> {
-        // For now, simulate email sending
-        // In production, replace with actual SMTP implementation using lettre or similar crate
-        debug!(
-            "Sending email to {} with subject: {}",
-            email.to_email, email.subject
-        );
-
-        // Simulate network delay
-        tokio::time::sleep(Duration::from_millis(100)).await;
-
-        // Simulate 95% success rate
-        if rand::random::<f32>() < 0.05 {
-            return Err(anyhow::anyhow!("Simulated SMTP send failure"));
-        }
-
-        // Log successful send
-        info!(
-            "Email sent successfully to {} (ID: {})",
-            email.to_email, email.id
-        );
-
-        Ok(())
+        let transport = self.smtp_config.transport()?;
+        send_email_smtp(email, &self.smtp_config, &transport).await
     }
 }
 
 /// Background task for processing email queue
-#[instrument(skip(session_state, smtp_config))]
-async fn email_processor_task(session_state: SessionState, smtp_config: SmtpConfig) {
-    let mut interval = interval(Duration::from_secs(60)); // Process every minute
-    info!("Started email processor background task");
+#[instrument(skip(session_state, smtp_config, shutdown))]
+async fn email_processor_task(
+    session_state: SessionState,
+    smtp_config: SmtpConfig,
+    shutdown: CancellationToken,
+) {
+    // Emails stay queued (not marked sent) while there is no usable transport
+    let transport = match smtp_config.transport() {
+        Ok(transport) => transport,
+        Err(e) => {
+            error!("Email processor not started, invalid SMTP configuration: {}", e);
+            return;
+        }
+    };
+
+    let interval_seconds = session_state.app_config.email_queue_interval_seconds.max(1);
+    let mut interval = interval(Duration::from_secs(interval_seconds));
+    info!(
+        "Started email processor background task (every {} seconds, relay {}:{})",
+        interval_seconds, smtp_config.host, smtp_config.port
+    );
 
     loop {
-        interval.tick().await;
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown.cancelled() => break,
+        }
+
+        let result = process_email_queue(&session_state, &smtp_config, &transport).await;
+        metrics().record_task_run("email_queue", result.is_ok());
 
-        match process_email_queue(&session_state, &smtp_config).await {
+        match result {
             Ok(processed_count) => {
                 if processed_count > 0 {
                     debug!("Processed {} emails from queue", processed_count);
@@ -205,42 +267,49 @@ async fn email_processor_task(session_state: SessionState, smtp_config: SmtpConf
     }
 }
 
-#[instrument(skip(session_state, smtp_config))]
+/// Exponential backoff: base, 2x base, 4x base, ... capped at 24 hours
+pub(crate) fn retry_delay(base_seconds: u64, attempts: i32) -> Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 20) as u32;
+    let seconds = base_seconds
+        .saturating_mul(2_u64.pow(exponent))
+        .min(MAX_RETRY_DELAY_SECONDS);
+    Duration::from_secs(seconds)
+}
+
+#[instrument(skip(session_state, smtp_config, transport))]
 async fn process_email_queue(
     session_state: &SessionState,
     smtp_config: &SmtpConfig,
+    transport: &SmtpTransport,
 ) -> Result<usize> {
-    // Get pending emails ordered by priority and scheduled time
-    let pending_emails = sqlx::query_as::<_, EmailQueue>(
-        "SELECT * FROM email_queue 
-         WHERE status = $1 AND scheduled_for <= NOW() 
-         ORDER BY priority DESC, scheduled_for ASC 
-         LIMIT 50"
+    // Claim due emails; SKIP LOCKED keeps concurrent workers from sending the same email twice
+    let mut pending_emails = sqlx::query_as::<_, EmailQueue>(
+        "UPDATE email_queue
+         SET scheduled_for = NOW() + make_interval(secs => $2), updated_at = NOW()
+         WHERE id IN (
+             SELECT id FROM email_queue
+             WHERE status = $1 AND scheduled_for <= NOW()
+             ORDER BY priority DESC, scheduled_for ASC
+             LIMIT 50
+             FOR UPDATE SKIP LOCKED
+         )
+         RETURNING *"
     )
     .bind(NotificationStatus::Pending)
+    .bind(CLAIM_LEASE_SECONDS as f64)
     .fetch_all(&session_state.db_pool)
     .await?;
 
+    pending_emails.sort_by_key(|email| std::cmp::Reverse(email.priority));
+
     let mut processed_count = 0;
 
     for email in pending_emails {
-        // Mark as processing
-        sqlx::query(
-            "UPDATE email_queue SET updated_at = NOW() WHERE id = $1"
-        )
-        .bind(email.id)
-        .execute(&session_state.db_pool)
-        .await?;
-
-        // Send email
-        let send_result = send_email_smtp(&email, smtp_config).await;
-
-        match send_result {
+        match send_email_smtp(&email, smtp_config, transport).await {
             Ok(_) => {
-                // Mark as sent
                 sqlx::query(
                     "UPDATE email_queue 
-                     SET status = $1, sent_at = NOW(), updated_at = NOW()
+                     SET status = $1, attempts = attempts + 1, sent_at = NOW(), error_message = NULL, updated_at = NOW()
                      WHERE id = $2"
                 )
                 .bind(NotificationStatus::Sent)
@@ -251,33 +320,13 @@ async fn process_email_queue(
                 processed_count += 1;
             }
             Err(e) => {
-                // Increment attempts and handle failure
-                let new_attempts = email.attempts + 1;
-                let new_status = if new_attempts >= email.max_attempts {
-                    NotificationStatus::Failed
-                } else {
-                    NotificationStatus::Pending
-                };
-
-                // Reschedule for retry with exponential backoff
-                let retry_delay = Duration::from_secs(60 * 2_u64.pow(new_attempts as u32).min(1440)); // Max 24 hours
-                let next_retry = chrono::Utc::now() + chrono::Duration::from_std(retry_delay)?;
-
-                sqlx::query(
-                    "UPDATE email_queue 
-                     SET attempts = $1, status = $2, error_message = $3, 
-                         scheduled_for = $4, updated_at = NOW()
-                     WHERE id = $5"
+                record_send_failure(
+                    session_state,
+                    &email,
+                    &e.to_string(),
+                    session_state.app_config.email_retry_base_seconds,
                 )
-                .bind(new_attempts)
-                .bind(&new_status)
-                .bind(e.to_string())
-                .bind(if new_status == NotificationStatus::Pending { Some(next_retry) } else { None })
-                .bind(email.id)
-                .execute(&session_state.db_pool)
                 .await?;
-
-                warn!("Email send failed (attempt {}): {}", new_attempts, e);
             }
         }
     }
@@ -285,39 +334,66 @@ async fn process_email_queue(
     Ok(processed_count)
 }
 
-// Placeholder for actual SMTP implementation
-#[instrument(skip(email, _smtp_config))]
-async fn send_email_smtp(email: &EmailQueue, _smtp_config: &SmtpConfig) -> Result<()> {
-    // TODO: Implement actual SMTP sending using lettre crate
-    // This is a placeholder implementation
+/// Reschedule a failed send, or move it to `failed` (dead letter) once max_attempts is reached
+async fn record_send_failure(
+    session_state: &SessionState,
+    email: &EmailQueue,
+    error_message: &str,
+    retry_base_seconds: u64,
+) -> Result<NotificationStatus> {
+    let new_attempts = email.attempts + 1;
 
-    debug!(
-        "SMTP: Sending email to {} with subject: {}",
-        email.to_email, email.subject
-    );
+    let (new_status, next_retry) = if new_attempts >= email.max_attempts {
+        (NotificationStatus::Failed, None)
+    } else {
+        let delay = retry_delay(retry_base_seconds, new_attempts);
+        (
+            NotificationStatus::Pending,
+            Some(chrono::Utc::now() + chrono::Duration::from_std(delay)?),
+        )
+    };
 
-    // Simulate network delay
-    tokio::time::sleep(Duration::from_millis(200)).await;
+    sqlx::query(
+        "UPDATE email_queue 
+         SET attempts = $1, status = $2, error_message = $3, 
+             scheduled_for = COALESCE($4, scheduled_for), updated_at = NOW()
+         WHERE id = $5"
+    )
+    .bind(new_attempts)
+    .bind(&new_status)
+    .bind(error_message)
+    .bind(next_retry)
+    .bind(email.id)
+    .execute(&session_state.db_pool)
+    .await?;
 
-    // Simulate 90% success rate for SMTP
-    if rand::random::<f32>() < 0.1 {
-        return Err(anyhow::anyhow!("SMTP server connection failed"));
+    if new_status == NotificationStatus::Failed {
+        error!(
+            "Email {} to {} failed permanently after {} attempts: {}",
+            email.id, email.to_email, new_attempts, error_message
+        );
+    } else {
+        warn!("Email send failed (attempt {}): {}", new_attempts, error_message);
     }
 
+    Ok(new_status)
+}
+
+#[instrument(skip(email, smtp_config, transport))]
+async fn send_email_smtp(email: &EmailQueue, smtp_config: &SmtpConfig, transport: &SmtpTransport) -> Result<()> {
+    let message = build_message(email, smtp_config)?;
+    debug!("SMTP: Sending email {} to {}", email.id, email.to_email);
+    transport.send(message).await?;
+    info!("Email sent successfully to {} (ID: {})", email.to_email, email.id);
     Ok(())
 }
 
-// Email statistics struct
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
-pub struct EmailStats {
-    pub emails_sent_today: i32,
-    pub emails_pending: i32,
-    pub emails_failed: i32,
-    pub bounce_rate: f64,
-    pub delivery_rate: f64,
-    pub avg_send_time_ms: f64,
-    pub last_successful_send: Option<chrono::DateTime<chrono::Utc>>,
-    pub smtp_status: String,
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
 }
 
 // Email template builder utility
@@ -522,7 +598,541 @@ This is an automated alert from your Trackivity system.
         }
     }
 
+    pub fn password_reset_template(user_name: &str, reset_link: &str, ttl_minutes: u64) -> Self {
+        let subject = "Reset your Trackivity password".to_string();
+
+        let body_text = format!(
+            r#"Hello {},
+
+We received a request to reset your Trackivity password.
+
+Open the link below to choose a new password:
+{}
+
+This link expires in {} minutes and can only be used once.
+If you did not request a password reset, you can ignore this email.
+
+This is an automated message from Trackivity.
+"#,
+            user_name, reset_link, ttl_minutes
+        );
+
+        let body_html = format!(
+            r#"
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <title>Password Reset</title>
+    <style>
+        body {{ font-family: Arial, sans-serif; line-height: 1.6; color: #333; }}
+        .container {{ max-width: 600px; margin: 0 auto; padding: 20px; }}
+        .header {{ background-color: #007bff; color: white; padding: 20px; text-align: center; }}
+        .content {{ padding: 20px; background-color: #f9f9f9; }}
+        .button {{ display: inline-block; padding: 10px 20px; background-color: #007bff; color: white; text-decoration: none; border-radius: 4px; }}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="header">
+            <h1>Password Reset</h1>
+        </div>
+        <div class="content">
+            <p>Hello {},</p>
+            <p>We received a request to reset your Trackivity password.</p>
+            <p><a class="button" href="{}">Reset password</a></p>
+            <p>This link expires in {} minutes and can only be used once.</p>
+            <p>If you did not request a password reset, you can ignore this email.</p>
+        </div>
+    </div>
+</body>
+</html>
+            "#,
+            user_name, reset_link, ttl_minutes
+        );
+
+        Self {
+            subject,
+            body_text,
+            body_html: Some(body_html),
+        }
+    }
+
+    pub fn activity_cancelled_template(user_name: &str, activity_title: &str, reason: &str) -> Self {
+        let subject = format!("Activity Cancelled: {}", activity_title);
+
+        let body_text = format!(
+            r#"Hello {},
+
+The activity "{}" that you registered for has been cancelled.
+
+Reason: {}
+
+Your registration has been kept on record. No further action is required.
+
+This is an automated message from Trackivity.
+"#,
+            user_name, activity_title, reason
+        );
+
+        let body_html = format!(
+            r#"
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <title>Activity Cancelled</title>
+    <style>
+        body {{ font-family: Arial, sans-serif; line-height: 1.6; color: #333; }}
+        .container {{ max-width: 600px; margin: 0 auto; padding: 20px; }}
+        .header {{ background-color: #6c757d; color: white; padding: 20px; text-align: center; }}
+        .content {{ padding: 20px; background-color: #f9f9f9; }}
+        .details {{ background-color: white; padding: 15px; border-left: 4px solid #6c757d; }}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="header">
+            <h1>Activity Cancelled</h1>
+        </div>
+        <div class="content">
+            <p>Hello {},</p>
+            <p>The activity you registered for has been cancelled.</p>
+            <div class="details">
+                <p><strong>Activity:</strong> {}</p>
+                <p><strong>Reason:</strong> {}</p>
+            </div>
+            <p>Your registration has been kept on record. No further action is required.</p>
+        </div>
+    </div>
+</body>
+</html>
+            "#,
+            escape_html(user_name),
+            escape_html(activity_title),
+            escape_html(reason)
+        );
+
+        Self {
+            subject,
+            body_text,
+            body_html: Some(body_html),
+        }
+    }
+
+    pub fn waitlist_promoted_template(user_name: &str, activity_title: &str) -> Self {
+        let subject = format!("You're In: {}", activity_title);
+
+        let body_text = format!(
+            r#"Hello {},
+
+A seat has opened up for "{}" and you have been moved from the waitlist to registered.
+
+Please remember to check in with your QR code at the activity.
+
+This is an automated message from Trackivity.
+"#,
+            user_name, activity_title
+        );
+
+        let body_html = format!(
+            r#"
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <title>Registration Confirmed</title>
+    <style>
+        body {{ font-family: Arial, sans-serif; line-height: 1.6; color: #333; }}
+        .container {{ max-width: 600px; margin: 0 auto; padding: 20px; }}
+        .header {{ background-color: #28a745; color: white; padding: 20px; text-align: center; }}
+        .content {{ padding: 20px; background-color: #f9f9f9; }}
+        .details {{ background-color: white; padding: 15px; border-left: 4px solid #28a745; }}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="header">
+            <h1>Registration Confirmed</h1>
+        </div>
+        <div class="content">
+            <p>Hello {},</p>
+            <p>A seat has opened up and you have been moved from the waitlist to registered.</p>
+            <div class="details">
+                <p><strong>Activity:</strong> {}</p>
+            </div>
+            <p>Please remember to check in with your QR code at the activity.</p>
+        </div>
+    </div>
+</body>
+</html>
+            "#,
+            escape_html(user_name),
+            escape_html(activity_title)
+        );
+
+        Self {
+            subject,
+            body_text,
+            body_html: Some(body_html),
+        }
+    }
+
+    pub fn activity_reminder_template(
+        user_name: &str,
+        activity_title: &str,
+        starts_at: &str,
+        location: &str,
+    ) -> Self {
+        let subject = format!("Reminder: {} starts {}", activity_title, starts_at);
+
+        let body_text = format!(
+            r#"Hello {},
+
+This is a reminder that "{}" starts at {}.
+
+Location: {}
+
+Please remember to check in with your QR code at the activity.
+
+This is an automated message from Trackivity.
+"#,
+            user_name, activity_title, starts_at, location
+        );
+
+        let body_html = format!(
+            r#"
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <title>Activity Reminder</title>
+    <style>
+        body {{ font-family: Arial, sans-serif; line-height: 1.6; color: #333; }}
+        .container {{ max-width: 600px; margin: 0 auto; padding: 20px; }}
+        .header {{ background-color: #007bff; color: white; padding: 20px; text-align: center; }}
+        .content {{ padding: 20px; background-color: #f9f9f9; }}
+        .details {{ background-color: white; padding: 15px; border-left: 4px solid #007bff; }}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="header">
+            <h1>Activity Reminder</h1>
+        </div>
+        <div class="content">
+            <p>Hello {},</p>
+            <p>An activity you registered for is starting soon.</p>
+            <div class="details">
+                <p><strong>Activity:</strong> {}</p>
+                <p><strong>Starts:</strong> {}</p>
+                <p><strong>Location:</strong> {}</p>
+            </div>
+            <p>Please remember to check in with your QR code at the activity.</p>
+        </div>
+    </div>
+</body>
+</html>
+            "#,
+            escape_html(user_name),
+            escape_html(activity_title),
+            escape_html(starts_at),
+            escape_html(location)
+        );
+
+        Self {
+            subject,
+            body_text,
+            body_html: Some(body_html),
+        }
+    }
+
+    /// `user_agent` comes from the login request, so it is escaped in the HTML body
+    pub fn new_device_login_template(user_name: &str, ip_address: &str, user_agent: &str, login_time: &str) -> Self {
+        let subject = "New sign-in to your Trackivity account".to_string();
+
+        let body_text = format!(
+            r#"Hello {},
+
+Your Trackivity account was just signed in to from a device we haven't seen before.
+
+Time: {}
+IP address: {}
+Device: {}
+
+If this was you, no action is needed.
+If it wasn't, change your password right away and sign out your other sessions.
+
+This is an automated message from Trackivity.
+"#,
+            user_name, login_time, ip_address, user_agent
+        );
+
+        let body_html = format!(
+            r#"
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <title>New Sign-in</title>
+    <style>
+        body {{ font-family: Arial, sans-serif; line-height: 1.6; color: #333; }}
+        .container {{ max-width: 600px; margin: 0 auto; padding: 20px; }}
+        .header {{ background-color: #fd7e14; color: white; padding: 20px; text-align: center; }}
+        .content {{ padding: 20px; background-color: #f9f9f9; }}
+        .details {{ background-color: white; padding: 15px; border-left: 4px solid #fd7e14; }}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="header">
+            <h1>New Sign-in</h1>
+        </div>
+        <div class="content">
+            <p>Hello {},</p>
+            <p>Your Trackivity account was just signed in to from a device we haven't seen before.</p>
+            <div class="details">
+                <p><strong>Time:</strong> {}</p>
+                <p><strong>IP address:</strong> {}</p>
+                <p><strong>Device:</strong> {}</p>
+            </div>
+            <p>If this was you, no action is needed.</p>
+            <p>If it wasn't, change your password right away and sign out your other sessions.</p>
+        </div>
+    </div>
+</body>
+</html>
+            "#,
+            user_name,
+            login_time,
+            escape_html(ip_address),
+            escape_html(user_agent)
+        );
+
+        Self {
+            subject,
+            body_text,
+            body_html: Some(body_html),
+        }
+    }
+
+    pub fn new_device_verification_template(
+        user_name: &str,
+        user_agent: &str,
+        verification_link: &str,
+        ttl_minutes: u64,
+    ) -> Self {
+        let subject = "Confirm sign-in from a new device".to_string();
+
+        let body_text = format!(
+            r#"Hello {},
+
+Someone entered your Trackivity password on a device we haven't seen before:
+{}
+
+If this was you, open the link below to finish signing in:
+{}
+
+This link expires in {} minutes and can only be used once.
+If it wasn't you, change your password right away.
+
+This is an automated message from Trackivity.
+"#,
+            user_name, user_agent, verification_link, ttl_minutes
+        );
+
+        let body_html = format!(
+            r#"
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <title>Confirm Sign-in</title>
+    <style>
+        body {{ font-family: Arial, sans-serif; line-height: 1.6; color: #333; }}
+        .container {{ max-width: 600px; margin: 0 auto; padding: 20px; }}
+        .header {{ background-color: #fd7e14; color: white; padding: 20px; text-align: center; }}
+        .content {{ padding: 20px; background-color: #f9f9f9; }}
+        .button {{ display: inline-block; padding: 10px 20px; background-color: #fd7e14; color: white; text-decoration: none; border-radius: 4px; }}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="header">
+            <h1>Confirm Sign-in</h1>
+        </div>
+        <div class="content">
+            <p>Hello {},</p>
+            <p>Someone entered your Trackivity password on a device we haven't seen before:</p>
+            <p><strong>{}</strong></p>
+            <p><a class="button" href="{}">Confirm sign-in</a></p>
+            <p>This link expires in {} minutes and can only be used once.</p>
+            <p>If it wasn't you, change your password right away.</p>
+        </div>
+    </div>
+</body>
+</html>
+            "#,
+            user_name,
+            escape_html(user_agent),
+            verification_link,
+            ttl_minutes
+        );
+
+        Self {
+            subject,
+            body_text,
+            body_html: Some(body_html),
+        }
+    }
+
     pub fn build(self) -> (String, String, Option<String>) {
         (self.subject, self.body_text, self.body_html)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_state;
+
+    #[test]
+    fn test_activity_cancelled_template_escapes_html() {
+        let template = EmailTemplate::activity_cancelled_template(
+            "<b>Somchai</b>",
+            "Workshop & \"Demo\"",
+            "<a href='http://phish'>details</a>",
+        );
+        let html = template.body_html.unwrap();
+        assert!(html.contains("&lt;b&gt;Somchai&lt;/b&gt;"));
+        assert!(html.contains("Workshop &amp; &quot;Demo&quot;"));
+        assert!(html.contains("&lt;a href=&#39;http://phish&#39;&gt;"));
+        assert!(!html.contains("<a href"));
+        // Plain-text body stays unescaped
+        assert!(template.body_text.contains("<b>Somchai</b>"));
+    }
+
+    #[test]
+    fn test_activity_reminder_template_escapes_html() {
+        let template = EmailTemplate::activity_reminder_template(
+            "<b>Somchai</b>",
+            "<script>alert(1)</script>",
+            "2026-10-20 09:00",
+            "Hall \"A\" & B",
+        );
+        let html = template.body_html.unwrap();
+        assert!(html.contains("&lt;b&gt;Somchai&lt;/b&gt;"));
+        assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
+        assert!(html.contains("Hall &quot;A&quot; &amp; B"));
+        assert!(!html.contains("<script>"));
+        assert!(template.body_text.contains("<script>alert(1)</script>"));
+    }
+
+    fn queued_email(to_email: &str, body_html: Option<&str>) -> EmailQueue {
+        let now = chrono::Utc::now();
+        EmailQueue {
+            id: uuid::Uuid::new_v4(),
+            to_email: to_email.to_string(),
+            to_name: Some("สมชาย ใจดี".to_string()),
+            subject: "Activity Reminder".to_string(),
+            body_text: "See you there".to_string(),
+            body_html: body_html.map(str::to_string),
+            priority: 2,
+            status: NotificationStatus::Pending,
+            attempts: 0,
+            max_attempts: 5,
+            scheduled_for: now,
+            sent_at: None,
+            error_message: None,
+            metadata: json!({}),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_build_message() {
+        let smtp_config = SmtpConfig {
+            from_address: "noreply@trackivity.test".to_string(),
+            from_name: "Trackivity".to_string(),
+            ..SmtpConfig::default()
+        };
+
+        let text_only = build_message(&queued_email("student@test.local", None), &smtp_config).unwrap();
+        let text_only = String::from_utf8(text_only.formatted()).unwrap();
+        assert!(text_only.contains("From: Trackivity <noreply@trackivity.test>"));
+        assert!(text_only.contains("student@test.local>"));
+        assert!(text_only.contains("Subject: Activity Reminder"));
+        assert!(text_only.contains("Content-Type: text/plain"));
+
+        let html = build_message(&queued_email("student@test.local", Some("<p>See you</p>")), &smtp_config).unwrap();
+        let html = String::from_utf8(html.formatted()).unwrap();
+        assert!(html.contains("multipart/alternative"));
+        assert!(html.contains("Content-Type: text/html"));
+
+        assert!(build_message(&queued_email("not an address", None), &smtp_config).is_err());
+    }
+
+    #[test]
+    fn test_retry_delay_backoff() {
+        assert_eq!(retry_delay(60, 1), Duration::from_secs(60));
+        assert_eq!(retry_delay(60, 2), Duration::from_secs(120));
+        assert_eq!(retry_delay(60, 4), Duration::from_secs(480));
+        assert_eq!(retry_delay(60, 30), Duration::from_secs(MAX_RETRY_DELAY_SECONDS));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_send_failure_retries_then_dead_letters() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let session_state = test_state(&pool);
+
+        let to_email = format!("retry-{}@test.local", uuid::Uuid::new_v4().simple());
+        let email_id = EmailService::new(session_state.clone())
+            .queue_email(&to_email, None, "Retry", "body", None, 2, None)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE email_queue SET max_attempts = 2 WHERE id = $1")
+            .bind(email_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let load = || async {
+            sqlx::query_as::<_, EmailQueue>("SELECT * FROM email_queue WHERE id = $1")
+                .bind(email_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap()
+        };
+
+        // First failure: still pending, pushed into the future
+        let email = load().await;
+        let status = record_send_failure(&session_state, &email, "SMTP timeout", 60)
+            .await
+            .unwrap();
+        let retried = load().await;
+        assert_eq!(status, NotificationStatus::Pending);
+        assert_eq!(retried.attempts, 1);
+        assert!(retried.scheduled_for > chrono::Utc::now() + chrono::Duration::seconds(50));
+        assert_eq!(retried.error_message.as_deref(), Some("SMTP timeout"));
+
+        // Second failure reaches max_attempts: dead letter, kept for inspection
+        let status = record_send_failure(&session_state, &retried, "SMTP timeout", 60)
+            .await
+            .unwrap();
+        let dead = load().await;
+
+        sqlx::query("DELETE FROM email_queue WHERE id = $1")
+            .bind(email_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(status, NotificationStatus::Failed);
+        assert_eq!(dead.status, NotificationStatus::Failed);
+        assert_eq!(dead.attempts, 2);
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::{BTreeMap, HashMap};
+use std::net::IpAddr;
+use std::sync::{Mutex, OnceLock};
+
+use maxminddb::{geoip2, MaxMindDBError, Reader};
+use tracing::{info, warn};
+
+use crate::models::session::GeoLocation;
+use crate::services::metrics::metrics;
+
+/// Resolved IPs kept in memory; the cache is simply emptied when it fills up
+const GEOIP_CACHE_CAPACITY: usize = 10_000;
+
+static GEOIP: OnceLock<GeoIpResolver> = OnceLock::new();
+
+/// IP -> country/city lookups against a MaxMind GeoLite2 database, with a per-process cache
+pub struct GeoIpResolver {
+    db: Reader<Vec<u8>>,
+    cache: Mutex<HashMap<IpAddr, Option<GeoLocation>>>,
+}
+
+impl GeoIpResolver {
+    pub fn new(db: Reader<Vec<u8>>) -> Self {
+        Self {
+            db,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn lookup(&self, ip: IpAddr) -> Option<GeoLocation> {
+        // The lock only guards the map; concurrent logins must not wait on each other's lookups
+        let cached = self.cache.lock().unwrap_or_else(|e| e.into_inner()).get(&ip).cloned();
+        if let Some(location) = cached {
+            metrics().record_cache_lookup("geoip", true);
+            return location;
+        }
+        metrics().record_cache_lookup("geoip", false);
+
+        let location = match self.db.lookup::<geoip2::City>(ip) {
+            Ok(record) => geo_location_from_record(&record),
+            Err(MaxMindDBError::AddressNotFoundError(_)) => None,
+            Err(e) => {
+                warn!("GeoIP lookup failed for {}: {}", ip, e);
+                None
+            }
+        };
+
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        if cache.len() >= GEOIP_CACHE_CAPACITY {
+            cache.clear();
+        }
+        cache.insert(ip, location.clone());
+        location
+    }
+}
+
+/// Load the database at `path` for [`lookup_ip`]. Without a path, or when the file can't be
+/// read, lookups just return `None`; the server never fails to start over GeoIP.
+pub fn init_geoip(path: Option<&str>) {
+    let Some(path) = path else {
+        info!("GeoIP disabled (GEOIP_DATABASE_PATH is not set)");
+        return;
+    };
+    match Reader::open_readfile(path) {
+        Ok(db) => {
+            let _ = GEOIP.set(GeoIpResolver::new(db));
+            info!("GeoIP database loaded from {}", path);
+        }
+        Err(e) => warn!("GeoIP disabled: failed to load {}: {}", path, e),
+    }
+}
+
+/// Location of `ip` (as recorded on sessions and login events); `None` when GeoIP is off,
+/// the IP is missing or unparsable, or the database doesn't know it
+pub fn lookup_ip(ip: Option<&str>) -> Option<GeoLocation> {
+    let resolver = GEOIP.get()?;
+    let ip = ip?.trim().parse::<IpAddr>().ok()?;
+    resolver.lookup(ip)
+}
+
+/// Pick the English names out of a GeoLite2-City record
+fn geo_location_from_record(record: &geoip2::City) -> Option<GeoLocation> {
+    let country = record.country.as_ref().or(record.registered_country.as_ref());
+    let english = |names: Option<&BTreeMap<&str, &str>>| {
+        names.and_then(|names| names.get("en")).map(|name| name.to_string())
+    };
+
+    let location = GeoLocation {
+        country_code: country.and_then(|c| c.iso_code).map(str::to_string),
+        country: english(country.and_then(|c| c.names.as_ref())),
+        city: english(record.city.as_ref().and_then(|c| c.names.as_ref())),
+    };
+    (location.country_code.is_some() || location.city.is_some()).then_some(location)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    /// Encode a string / unsigned / map / array field in the MaxMind DB data format
+    fn encode(value: &Value) -> Vec<u8> {
+        let header = |type_id: u8, size: usize| -> Vec<u8> {
+            assert!(size < 285);
+            let (size_bits, extra) = if size < 29 { (size as u8, vec![]) } else { (29, vec![(size - 29) as u8]) };
+            let mut out = if type_id <= 7 {
+                vec![(type_id << 5) | size_bits]
+            } else {
+                vec![size_bits, type_id - 7]
+            };
+            out.extend(extra);
+            out
+        };
+        match value {
+            Value::String(text) => [header(2, text.len()), text.as_bytes().to_vec()].concat(),
+            Value::Number(n) => {
+                let bytes: Vec<u8> = n.as_u64().unwrap().to_be_bytes().into_iter().skip_while(|b| *b == 0).collect();
+                [header(6, bytes.len()), bytes].concat()
+            }
+            Value::Object(map) => {
+                let mut out = header(7, map.len());
+                for (key, value) in map {
+                    out.extend(encode(&Value::String(key.clone())));
+                    out.extend(encode(value));
+                }
+                out
+            }
+            Value::Array(items) => {
+                let mut out = header(11, items.len());
+                for item in items {
+                    out.extend(encode(item));
+                }
+                out
+            }
+            other => panic!("unsupported test value {}", other),
+        }
+    }
+
+    /// IPv4 database with 24-bit records where 203.0.113.0/24 maps to `record`; the tree is a
+    /// chain of 24 nodes following the network's bits, every other branch is "not found"
+    fn single_network_db(record: &Value) -> Vec<u8> {
+        let network: u32 = u32::from(std::net::Ipv4Addr::new(203, 0, 113, 0));
+        let node_count = 24usize;
+        // Records past the node count point into the data section, 16 bytes after the tree
+        let data_pointer = node_count + 16;
+
+        let mut out = Vec::new();
+        for node in 0..node_count {
+            let bit = (network >> (31 - node)) & 1;
+            let next = if node + 1 == node_count { data_pointer } else { node + 1 };
+            let (left, right) = if bit == 0 { (next, node_count) } else { (node_count, next) };
+            out.extend(&(left as u32).to_be_bytes()[1..]);
+            out.extend(&(right as u32).to_be_bytes()[1..]);
+        }
+        out.extend([0u8; 16]);
+        out.extend(encode(record));
+        out.extend(b"\xAB\xCD\xEFMaxMind.com");
+        out.extend(encode(&json!({
+            "binary_format_major_version": 2,
+            "binary_format_minor_version": 0,
+            "build_epoch": 1_700_000_000u64,
+            "database_type": "GeoLite2-City",
+            "description": { "en": "Test database" },
+            "ip_version": 4,
+            "languages": ["en"],
+            "node_count": node_count,
+            "record_size": 24,
+        })));
+        out
+    }
+
+    #[test]
+    fn test_resolver_reads_country_and_city() {
+        let record = json!({
+            "city": { "names": { "en": "Bangkok", "th": "กรุงเทพมหานคร" } },
+            "country": { "iso_code": "TH", "names": { "en": "Thailand" } },
+        });
+        let resolver = GeoIpResolver::new(Reader::from_source(single_network_db(&record)).unwrap());
+        let expected = GeoLocation {
+            country_code: Some("TH".to_string()),
+            country: Some("Thailand".to_string()),
+            city: Some("Bangkok".to_string()),
+        };
+
+        assert_eq!(resolver.lookup("203.0.113.9".parse().unwrap()), Some(expected.clone()));
+        // Second lookup is served from the cache
+        assert_eq!(resolver.lookup("203.0.113.9".parse().unwrap()), Some(expected));
+        assert_eq!(resolver.cache.lock().unwrap().len(), 1);
+        assert_eq!(resolver.lookup("198.51.100.1".parse().unwrap()), None);
+        // An IPv6 address against an IPv4-only database is an error, not a panic
+        assert_eq!(resolver.lookup("2001:db8::1".parse().unwrap()), None);
+    }
+
+    #[test]
+    fn test_registered_country_is_used_without_country() {
+        let record = json!({ "registered_country": { "iso_code": "JP", "names": { "en": "Japan" } } });
+        let resolver = GeoIpResolver::new(Reader::from_source(single_network_db(&record)).unwrap());
+
+        let location = resolver.lookup("203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(location.country_code.as_deref(), Some("JP"));
+        assert_eq!(location.city, None);
+    }
+
+    #[test]
+    fn test_rejects_files_without_metadata() {
+        assert!(Reader::from_source(b"not a database".to_vec()).is_err());
+        assert!(Reader::from_source(Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,425 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+use crate::models::admin_role::AdminLevel;
+use crate::models::session::{
+    CreateSession, LoginMethod, Session, SessionActivity, SessionActivityType, SessionType,
+};
+use crate::services::geoip::lookup_ip;
+use crate::services::session_store::SessionStore;
+use crate::utils::qr::generate_secret_key;
+
+/// In-process `SessionStore` for tests: same semantics as the Redis store, no server needed
+#[derive(Default)]
+pub struct MemorySessionStore {
+    sessions: Mutex<HashMap<String, Session>>,
+    /// Short-lived keys (lockouts, scan debounce, reset tokens, cache) with their expiry
+    keys: Mutex<HashMap<String, (Value, DateTime<Utc>)>>,
+}
+
+impl MemorySessionStore {
+    fn insert_new_session(
+        &self,
+        create_req: CreateSession,
+        session_type: SessionType,
+        admin_level: Option<AdminLevel>,
+        faculty_id: Option<Uuid>,
+        permissions: Vec<String>,
+        login_method: LoginMethod,
+    ) -> Result<Session> {
+        if create_req.expires_at <= Utc::now() {
+            return Err(anyhow::anyhow!("Session expiry time is in the past"));
+        }
+
+        let geo_location = lookup_ip(create_req.ip_address.as_deref());
+        let session = Session {
+            id: Uuid::new_v4().to_string(),
+            user_id: create_req.user_id,
+            expires_at: create_req.expires_at,
+            created_at: Utc::now(),
+            last_accessed: Utc::now(),
+            ip_address: create_req.ip_address.clone(),
+            user_agent: create_req.user_agent.clone(),
+            device_info: create_req.device_info,
+            is_active: true,
+            session_type,
+            admin_level,
+            faculty_id,
+            permissions,
+            revoked_by: None,
+            revoked_at: None,
+            revocation_reason: None,
+            login_method,
+            sse_connections: Vec::new(),
+            activity_log: vec![SessionActivity {
+                timestamp: Utc::now(),
+                activity_type: SessionActivityType::Login,
+                details: Some("Session created".to_string()),
+                ip_address: create_req.ip_address,
+                user_agent: create_req.user_agent,
+            }],
+            impersonated_by: create_req.impersonated_by,
+            impersonator_session_id: create_req.impersonator_session_id,
+            geo_location,
+        };
+
+        self.sessions
+            .lock()
+            .unwrap()
+            .insert(session.id.clone(), session.clone());
+        Ok(session)
+    }
+
+    fn get_key(&self, key: &str) -> Option<(Value, DateTime<Utc>)> {
+        let mut keys = self.keys.lock().unwrap();
+        match keys.get(key) {
+            Some((_, expires_at)) if *expires_at <= Utc::now() => {
+                keys.remove(key);
+                None
+            }
+            entry => entry.cloned(),
+        }
+    }
+
+    fn set_key(&self, key: String, value: Value, ttl_seconds: u64) {
+        let expires_at = Utc::now() + Duration::seconds(ttl_seconds as i64);
+        self.keys.lock().unwrap().insert(key, (value, expires_at));
+    }
+
+    fn remove_key(&self, key: &str) -> Option<Value> {
+        self.get_key(key)?;
+        self.keys.lock().unwrap().remove(key).map(|(value, _)| value)
+    }
+
+    /// Active, unexpired sessions matching `filter`, oldest first
+    fn active_session_ids(&self, filter: impl Fn(&Session) -> bool) -> Vec<String> {
+        let now = Utc::now();
+        let sessions = self.sessions.lock().unwrap();
+        let mut active: Vec<&Session> = sessions
+            .values()
+            .filter(|s| s.is_active && s.expires_at > now && filter(s))
+            .collect();
+        active.sort_by_key(|s| s.created_at);
+        active.into_iter().map(|s| s.id.clone()).collect()
+    }
+
+    fn update_session(&self, session_id: &str, update: impl FnOnce(&mut Session)) -> bool {
+        match self.sessions.lock().unwrap().get_mut(session_id) {
+            Some(session) if session.expires_at > Utc::now() => {
+                update(session);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[async_trait]
+impl SessionStore for MemorySessionStore {
+    async fn ping(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn create_session(&self, create_req: CreateSession) -> Result<Session> {
+        self.insert_new_session(create_req, SessionType::Student, None, None, Vec::new(), LoginMethod::StudentId)
+    }
+
+    async fn create_admin_session(
+        &self,
+        create_req: CreateSession,
+        session_type: SessionType,
+        admin_level: AdminLevel,
+        faculty_id: Option<Uuid>,
+        permissions: Vec<String>,
+        login_method: LoginMethod,
+    ) -> Result<Session> {
+        self.insert_new_session(
+            create_req,
+            session_type,
+            Some(admin_level),
+            faculty_id,
+            permissions,
+            login_method,
+        )
+    }
+
+    async fn get_session(&self, session_id: &str) -> Result<Option<Session>> {
+        let mut sessions = self.sessions.lock().unwrap();
+        match sessions.get(session_id) {
+            Some(session) if session.expires_at <= Utc::now() => {
+                sessions.remove(session_id);
+                Ok(None)
+            }
+            session => Ok(session.cloned()),
+        }
+    }
+
+    async fn update_session_activity(&self, session_id: &str) -> Result<()> {
+        self.update_session(session_id, |session| session.last_accessed = Utc::now());
+        Ok(())
+    }
+
+    async fn delete_session(&self, session_id: &str) -> Result<()> {
+        self.sessions.lock().unwrap().remove(session_id);
+        Ok(())
+    }
+
+    async fn delete_user_sessions(&self, user_id: Uuid) -> Result<Vec<String>> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let session_ids: Vec<String> = sessions
+            .values()
+            .filter(|s| s.user_id == user_id)
+            .map(|s| s.id.clone())
+            .collect();
+        for session_id in &session_ids {
+            sessions.remove(session_id);
+        }
+        Ok(session_ids)
+    }
+
+    async fn extend_session(&self, session_id: &str, new_expiry: DateTime<Utc>) -> Result<bool> {
+        if new_expiry <= Utc::now() {
+            return Ok(false);
+        }
+        Ok(self.update_session(session_id, |session| {
+            session.expires_at = new_expiry;
+            session.last_accessed = Utc::now();
+        }))
+    }
+
+    async fn get_user_sessions(&self, user_id: Uuid) -> Result<Vec<Session>> {
+        let now = Utc::now();
+        let sessions = self.sessions.lock().unwrap();
+        Ok(sessions
+            .values()
+            .filter(|s| s.user_id == user_id && s.expires_at > now)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_active_sessions(&self, limit: Option<usize>) -> Result<Vec<String>> {
+        let mut session_ids = self.active_session_ids(|_| true);
+        if let Some(limit) = limit {
+            session_ids.truncate(limit);
+        }
+        Ok(session_ids)
+    }
+
+    async fn cleanup_expired_sessions(&self) -> Result<usize> {
+        let now = Utc::now();
+        let mut sessions = self.sessions.lock().unwrap();
+        let before = sessions.len();
+        sessions.retain(|_, s| s.expires_at > now);
+        Ok(before - sessions.len())
+    }
+
+    async fn get_session_count(&self) -> Result<usize> {
+        Ok(self.active_session_ids(|_| true).len())
+    }
+
+    async fn add_session_activity(
+        &self,
+        session_id: &str,
+        activity_type: SessionActivityType,
+        details: Option<String>,
+        ip_address: Option<String>,
+        user_agent: Option<String>,
+    ) -> Result<()> {
+        self.update_session(session_id, |session| {
+            session.activity_log.push(SessionActivity {
+                timestamp: Utc::now(),
+                activity_type,
+                details,
+                ip_address,
+                user_agent,
+            });
+        });
+        Ok(())
+    }
+
+    async fn revoke_session_by_admin(
+        &self,
+        session_id: &str,
+        reason: Option<String>,
+        revoked_by_admin_id: Option<Uuid>,
+    ) -> Result<bool> {
+        Ok(self.update_session(session_id, |session| {
+            session.is_active = false;
+            session.last_accessed = Utc::now();
+            session.revoked_at = Some(Utc::now());
+            session.revoked_by = revoked_by_admin_id;
+            session.revocation_reason = reason.clone();
+            session.activity_log.push(SessionActivity {
+                timestamp: Utc::now(),
+                activity_type: SessionActivityType::ForceLogout,
+                details: reason.or_else(|| Some("Session revoked".to_string())),
+                ip_address: None,
+                user_agent: None,
+            });
+        }))
+    }
+
+    async fn get_admin_sessions(&self, admin_level_filter: Option<AdminLevel>) -> Result<Vec<String>> {
+        Ok(self.active_session_ids(|s| match (&s.admin_level, &admin_level_filter) {
+            (Some(level), Some(filter)) => level == filter,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }))
+    }
+
+    async fn get_admin_session_count_by_level(&self, admin_level: &AdminLevel) -> Result<usize> {
+        Ok(self
+            .active_session_ids(|s| s.admin_level.as_ref() == Some(admin_level))
+            .len())
+    }
+
+    async fn record_qr_scan(
+        &self,
+        activity_id: Uuid,
+        user_id: Uuid,
+        cooldown_seconds: u64,
+    ) -> Result<Option<DateTime<Utc>>> {
+        if cooldown_seconds == 0 {
+            return Ok(None);
+        }
+
+        let scan_key = format!("qr_scan:{}:{}", activity_id, user_id);
+        if let Some((last_scan, _)) = self.get_key(&scan_key) {
+            return Ok(Some(
+                last_scan
+                    .as_i64()
+                    .and_then(|ts| DateTime::from_timestamp(ts, 0))
+                    .unwrap_or_else(Utc::now),
+            ));
+        }
+
+        self.set_key(scan_key, Value::from(Utc::now().timestamp()), cooldown_seconds);
+        Ok(None)
+    }
+
+    async fn get_login_lockout(&self, identifier: &str, ip_address: &str) -> Result<Option<u64>> {
+        let lockout_key = format!("login_lockout:{}", login_key(identifier, ip_address));
+        Ok(self
+            .get_key(&lockout_key)
+            .map(|(_, expires_at)| (expires_at - Utc::now()).num_seconds().max(1) as u64))
+    }
+
+    async fn record_failed_login(
+        &self,
+        identifier: &str,
+        ip_address: &str,
+        max_attempts: u32,
+        window_seconds: u64,
+        lockout_seconds: u64,
+    ) -> Result<Option<u64>> {
+        if max_attempts == 0 {
+            return Ok(None);
+        }
+
+        let attempts_key = format!("login_attempts:{}", login_key(identifier, ip_address));
+        let attempts = match self.get_key(&attempts_key) {
+            Some((count, expires_at)) => {
+                let attempts = count.as_u64().unwrap_or(0) + 1;
+                self.keys
+                    .lock()
+                    .unwrap()
+                    .insert(attempts_key.clone(), (Value::from(attempts), expires_at));
+                attempts
+            }
+            None => {
+                self.set_key(attempts_key.clone(), Value::from(1), window_seconds);
+                1
+            }
+        };
+
+        if attempts < max_attempts as u64 {
+            return Ok(None);
+        }
+
+        let lockout_key = format!("login_lockout:{}", login_key(identifier, ip_address));
+        self.set_key(lockout_key, Value::from(Utc::now().timestamp()), lockout_seconds);
+        self.remove_key(&attempts_key);
+
+        Ok(Some(lockout_seconds))
+    }
+
+    async fn clear_failed_logins(&self, identifier: &str, ip_address: &str) -> Result<()> {
+        let key = login_key(identifier, ip_address);
+        self.remove_key(&format!("login_attempts:{}", key));
+        self.remove_key(&format!("login_lockout:{}", key));
+        Ok(())
+    }
+
+    async fn create_password_reset_token(&self, user_id: Uuid, ttl_seconds: u64) -> Result<String> {
+        let token = generate_secret_key();
+        self.set_key(
+            format!("password_reset:{}", token),
+            Value::String(user_id.to_string()),
+            ttl_seconds,
+        );
+        Ok(token)
+    }
+
+    async fn consume_password_reset_token(&self, token: &str) -> Result<Option<Uuid>> {
+        Ok(self
+            .remove_key(&format!("password_reset:{}", token))
+            .and_then(|id| id.as_str().and_then(|id| Uuid::parse_str(id).ok())))
+    }
+
+    async fn create_device_verification_token(
+        &self,
+        user_id: Uuid,
+        device_hash: &str,
+        ttl_seconds: u64,
+    ) -> Result<String> {
+        let token = generate_secret_key();
+        self.set_key(
+            format!("device_verification:{}", token),
+            serde_json::json!([user_id, device_hash]),
+            ttl_seconds,
+        );
+        Ok(token)
+    }
+
+    async fn consume_device_verification_token(&self, token: &str) -> Result<Option<(Uuid, String)>> {
+        Ok(self
+            .remove_key(&format!("device_verification:{}", token))
+            .and_then(|value| serde_json::from_value(value).ok()))
+    }
+
+    async fn create_two_factor_challenge(&self, pending_login: &Value, ttl_seconds: u64) -> Result<String> {
+        let token = generate_secret_key();
+        self.set_key(format!("two_factor_challenge:{}", token), pending_login.clone(), ttl_seconds);
+        Ok(token)
+    }
+
+    async fn consume_two_factor_challenge(&self, token: &str) -> Result<Option<Value>> {
+        Ok(self.remove_key(&format!("two_factor_challenge:{}", token)))
+    }
+
+    async fn get_cached_json(&self, key: &str) -> Result<Option<Value>> {
+        Ok(self.get_key(&format!("cache:{}", key)).map(|(value, _)| value))
+    }
+
+    async fn set_cached_json(&self, key: &str, value: &Value, ttl_seconds: u64) -> Result<()> {
+        self.set_key(format!("cache:{}", key), value.clone(), ttl_seconds);
+        Ok(())
+    }
+
+    async fn invalidate_cached_prefix(&self, prefix: &str) -> Result<usize> {
+        let prefix = format!("cache:{}", prefix);
+        let mut keys = self.keys.lock().unwrap();
+        let before = keys.len();
+        keys.retain(|key, _| !key.starts_with(&prefix));
+        Ok(before - keys.len())
+    }
+}
+
+fn login_key(identifier: &str, ip_address: &str) -> String {
+    format!("{}:{}", identifier.trim().to_lowercase(), ip_address)
+}
@@ -0,0 +1,213 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// Histogram buckets (seconds) for HTTP request latency
+const LATENCY_BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+#[derive(Debug, Default, Clone)]
+struct HttpSeries {
+    count: u64,
+    sum_seconds: f64,
+    buckets: [u64; LATENCY_BUCKETS.len()],
+}
+
+/// A gauge sampled at scrape time (pool size, session count, ...)
+#[derive(Debug, Clone)]
+pub struct Gauge {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub value: f64,
+}
+
+/// In-process metrics registry rendered in Prometheus text format
+#[derive(Debug, Default)]
+pub struct Metrics {
+    http: Mutex<BTreeMap<(String, String, u16), HttpSeries>>,
+    task_runs: Mutex<BTreeMap<(String, &'static str), u64>>,
+    cache_lookups: Mutex<BTreeMap<(String, &'static str), u64>>,
+}
+
+/// Global registry shared by the HTTP layer, background tasks and `/metrics`
+pub fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(Metrics::default)
+}
+
+impl Metrics {
+    /// `route` should be the matched route template (e.g. `/api/activities/{id}`), not the raw path,
+    /// so label cardinality stays bounded
+    pub fn record_http_request(&self, method: &str, route: &str, status: u16, elapsed: Duration) {
+        let seconds = elapsed.as_secs_f64();
+        let mut http = self.http.lock().unwrap_or_else(|e| e.into_inner());
+        let series = http
+            .entry((method.to_string(), route.to_string(), status))
+            .or_default();
+
+        series.count += 1;
+        series.sum_seconds += seconds;
+        for (bucket, upper_bound) in series.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if seconds <= upper_bound {
+                *bucket += 1;
+            }
+        }
+    }
+
+    pub fn record_task_run(&self, task: &str, success: bool) {
+        let result = if success { "success" } else { "error" };
+        let mut task_runs = self.task_runs.lock().unwrap_or_else(|e| e.into_inner());
+        *task_runs.entry((task.to_string(), result)).or_default() += 1;
+    }
+
+    /// Count a Redis response-cache lookup; returns the cache's hit rate so far (0.0-1.0)
+    pub fn record_cache_lookup(&self, cache: &str, hit: bool) -> f64 {
+        let result = if hit { "hit" } else { "miss" };
+        let mut cache_lookups = self.cache_lookups.lock().unwrap_or_else(|e| e.into_inner());
+        *cache_lookups.entry((cache.to_string(), result)).or_default() += 1;
+
+        let hits = cache_lookups.get(&(cache.to_string(), "hit")).copied().unwrap_or(0);
+        let misses = cache_lookups.get(&(cache.to_string(), "miss")).copied().unwrap_or(0);
+        hits as f64 / (hits + misses) as f64
+    }
+
+    pub fn render(&self, gauges: &[Gauge]) -> String {
+        let mut out = String::new();
+
+        let http = self.http.lock().unwrap_or_else(|e| e.into_inner()).clone();
+
+        out.push_str("# HELP http_requests_total Total HTTP requests handled\n");
+        out.push_str("# TYPE http_requests_total counter\n");
+        for ((method, route, status), series) in &http {
+            let _ = writeln!(
+                out,
+                "http_requests_total{{method=\"{}\",route=\"{}\",status=\"{}\"}} {}",
+                escape_label(method),
+                escape_label(route),
+                status,
+                series.count
+            );
+        }
+
+        out.push_str("# HELP http_request_duration_seconds HTTP request latency\n");
+        out.push_str("# TYPE http_request_duration_seconds histogram\n");
+        for ((method, route, status), series) in &http {
+            let labels = format!(
+                "method=\"{}\",route=\"{}\",status=\"{}\"",
+                escape_label(method),
+                escape_label(route),
+                status
+            );
+            for (count, upper_bound) in series.buckets.iter().zip(LATENCY_BUCKETS) {
+                let _ = writeln!(
+                    out,
+                    "http_request_duration_seconds_bucket{{{},le=\"{}\"}} {}",
+                    labels, upper_bound, count
+                );
+            }
+            let _ = writeln!(
+                out,
+                "http_request_duration_seconds_bucket{{{},le=\"+Inf\"}} {}",
+                labels, series.count
+            );
+            let _ = writeln!(out, "http_request_duration_seconds_sum{{{}}} {}", labels, series.sum_seconds);
+            let _ = writeln!(out, "http_request_duration_seconds_count{{{}}} {}", labels, series.count);
+        }
+
+        let task_runs = self.task_runs.lock().unwrap_or_else(|e| e.into_inner()).clone();
+
+        out.push_str("# HELP background_task_runs_total Background task runs by outcome\n");
+        out.push_str("# TYPE background_task_runs_total counter\n");
+        for ((task, result), count) in &task_runs {
+            let _ = writeln!(
+                out,
+                "background_task_runs_total{{task=\"{}\",result=\"{}\"}} {}",
+                escape_label(task),
+                result,
+                count
+            );
+        }
+
+        let cache_lookups = self.cache_lookups.lock().unwrap_or_else(|e| e.into_inner()).clone();
+
+        out.push_str("# HELP cache_lookups_total Redis response cache lookups by result\n");
+        out.push_str("# TYPE cache_lookups_total counter\n");
+        for ((cache, result), count) in &cache_lookups {
+            let _ = writeln!(
+                out,
+                "cache_lookups_total{{cache=\"{}\",result=\"{}\"}} {}",
+                escape_label(cache),
+                result,
+                count
+            );
+        }
+
+        for gauge in gauges {
+            let _ = writeln!(out, "# HELP {} {}", gauge.name, gauge.help);
+            let _ = writeln!(out, "# TYPE {} gauge", gauge.name);
+            let _ = writeln!(out, "{} {}", gauge.name, gauge.value);
+        }
+
+        out
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_http_histogram() {
+        let metrics = Metrics::default();
+        metrics.record_http_request("GET", "/api/activities/{id}", 200, Duration::from_millis(20));
+        metrics.record_http_request("GET", "/api/activities/{id}", 200, Duration::from_millis(300));
+
+        let text = metrics.render(&[]);
+        let labels = "method=\"GET\",route=\"/api/activities/{id}\",status=\"200\"";
+        assert!(text.contains(&format!("http_requests_total{{{}}} 2\n", labels)));
+        assert!(text.contains(&format!("http_request_duration_seconds_bucket{{{},le=\"0.025\"}} 1\n", labels)));
+        assert!(text.contains(&format!("http_request_duration_seconds_bucket{{{},le=\"0.5\"}} 2\n", labels)));
+        assert!(text.contains(&format!("http_request_duration_seconds_bucket{{{},le=\"+Inf\"}} 2\n", labels)));
+    }
+
+    #[test]
+    fn test_render_task_runs_and_gauges() {
+        let metrics = Metrics::default();
+        metrics.record_task_run("session_cleanup", true);
+        metrics.record_task_run("session_cleanup", true);
+        metrics.record_task_run("session_cleanup", false);
+
+        let text = metrics.render(&[Gauge {
+            name: "db_pool_connections",
+            help: "Open database connections",
+            value: 4.0,
+        }]);
+        assert!(text.contains("background_task_runs_total{task=\"session_cleanup\",result=\"success\"} 2\n"));
+        assert!(text.contains("background_task_runs_total{task=\"session_cleanup\",result=\"error\"} 1\n"));
+        assert!(text.contains("# TYPE db_pool_connections gauge\ndb_pool_connections 4\n"));
+    }
+
+    #[test]
+    fn test_cache_lookup_hit_rate() {
+        let metrics = Metrics::default();
+        assert_eq!(metrics.record_cache_lookup("dashboard", false), 0.0);
+        assert_eq!(metrics.record_cache_lookup("dashboard", true), 0.5);
+        assert_eq!(metrics.record_cache_lookup("leaderboard", true), 1.0);
+
+        let text = metrics.render(&[]);
+        assert!(text.contains("cache_lookups_total{cache=\"dashboard\",result=\"hit\"} 1\n"));
+        assert!(text.contains("cache_lookups_total{cache=\"dashboard\",result=\"miss\"} 1\n"));
+    }
+
+    #[test]
+    fn test_escape_label() {
+        assert_eq!(escape_label("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
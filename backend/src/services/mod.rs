@@ -1,14 +1,30 @@
 pub mod activity;
+pub mod activity_reminder;
 pub mod activity_status_updater;
 pub mod admin;
-pub mod auth;
+pub mod announcement;
+pub mod audit_log;
 pub mod background_tasks;
 pub mod email_service;
+pub mod geoip;
+pub mod metrics;
+pub mod new_device_login;
+pub mod notification;
+pub mod notification_inbox;
+#[cfg(test)]
+pub mod memory_session;
+pub mod redis_health;
 pub mod redis_session;
 pub mod session;
+pub mod session_store;
+pub mod two_factor;
 pub mod user;
+pub mod webhook;
 
 pub use activity_status_updater::ActivityStatusUpdater;
-pub use auth::*;
+#[cfg(test)]
+pub use memory_session::MemorySessionStore;
+pub use redis_health::RedisHealth;
 pub use redis_session::*;
 pub use session::*;
+pub use session_store::SessionStore;
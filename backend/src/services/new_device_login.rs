@@ -0,0 +1,226 @@
+use chrono::Utc;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use tracing::{error, warn};
+
+use crate::config::NewDeviceLoginAction;
+use crate::error::ApiError;
+use crate::middleware::session::SessionState;
+use crate::models::user::User;
+use crate::services::email_service::{EmailService, EmailTemplate};
+
+/// How many of the latest successful logins count as "known" devices
+const RECENT_LOGINS_CHECKED: i64 = 20;
+
+/// Identifies a device by its user agent; this is what verification tokens are bound to
+pub fn device_hash(user_agent: &str) -> String {
+    hex::encode(Sha256::digest(user_agent.as_bytes()))
+}
+
+/// A device is new when its user agent matches none of the user's recent successful logins.
+/// Users without any login history are never flagged.
+///
+/// IP addresses are not compared: mobile networks change them too often to be a useful signal.
+pub async fn is_new_device(pool: &PgPool, user_id: uuid::Uuid, user_agent: &str) -> Result<bool, sqlx::Error> {
+    let recent: Vec<Option<String>> = sqlx::query_scalar(
+        r#"
+        SELECT user_agent FROM login_events
+        WHERE user_id = $1 AND success
+        ORDER BY created_at DESC
+        LIMIT $2
+        "#,
+    )
+    .bind(user_id)
+    .bind(RECENT_LOGINS_CHECKED)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(!recent.is_empty() && !recent.iter().any(|known| known.as_deref() == Some(user_agent)))
+}
+
+/// Applies `NEW_DEVICE_LOGIN_ACTION` to a login whose password has already been verified.
+/// `login_path` is the frontend page the verification link returns to.
+///
+/// Only `Verify` can fail the login; emails are queued in the background and a lookup or
+/// queue failure is logged and never blocks the login.
+pub async fn check_login_device(
+    session_state: &SessionState,
+    user: &User,
+    ip_address: Option<&str>,
+    user_agent: &str,
+    verification_token: Option<&str>,
+    login_path: &str,
+) -> Result<(), ApiError> {
+    let action = session_state.app_config.new_device_login_action;
+    if action == NewDeviceLoginAction::Off {
+        return Ok(());
+    }
+
+    match is_new_device(&session_state.db_pool, user.id, user_agent).await {
+        Ok(true) => {}
+        Ok(false) => return Ok(()),
+        Err(e) => {
+            warn!("Failed to check login device for {}: {}", user.id, e);
+            return Ok(());
+        }
+    }
+
+    if action == NewDeviceLoginAction::Notify {
+        let (subject, body_text, body_html) = EmailTemplate::new_device_login_template(
+            &user_name(user),
+            ip_address.unwrap_or("unknown"),
+            user_agent,
+            &Utc::now().format("%Y-%m-%d %H:%M UTC").to_string(),
+        )
+        .build();
+        queue_in_background(session_state, user, subject, body_text, body_html, "new_device_login");
+        return Ok(());
+    }
+
+    if let Some(token) = verification_token {
+        let consumed = session_state
+            .session_store
+            .consume_device_verification_token(token)
+            .await
+            .unwrap_or_else(|e| {
+                warn!("Failed to consume device verification token: {}", e);
+                None
+            });
+        if consumed == Some((user.id, device_hash(user_agent))) {
+            return Ok(());
+        }
+    }
+
+    let config = &session_state.app_config;
+    let token = session_state
+        .session_store
+        .create_device_verification_token(user.id, &device_hash(user_agent), config.new_device_verification_ttl_seconds)
+        .await
+        .map_err(|e| {
+            error!("Failed to create device verification token: {}", e);
+            ApiError::internal("Failed to start device verification")
+        })?;
+    let verification_link = format!(
+        "{}{}?device_token={}",
+        config.frontend_url.trim_end_matches('/'),
+        login_path,
+        token
+    );
+    let (subject, body_text, body_html) = EmailTemplate::new_device_verification_template(
+        &user_name(user),
+        user_agent,
+        &verification_link,
+        config.new_device_verification_ttl_seconds / 60,
+    )
+    .build();
+    queue_in_background(session_state, user, subject, body_text, body_html, "new_device_verification");
+
+    Err(ApiError::forbidden(
+        "new_device_verification_required",
+        "Sign-in from a new device. Check your email to confirm it.",
+    ))
+}
+
+fn user_name(user: &User) -> String {
+    format!("{} {}", user.first_name, user.last_name)
+}
+
+fn queue_in_background(
+    session_state: &SessionState,
+    user: &User,
+    subject: String,
+    body_text: String,
+    body_html: Option<String>,
+    email_type: &'static str,
+) {
+    let email_service = EmailService::new(session_state.clone());
+    let email = user.email.clone();
+    let name = user_name(user);
+    let user_id = user.id;
+
+    tokio::spawn(async move {
+        if let Err(e) = email_service
+            .queue_email(
+                &email,
+                Some(&name),
+                &subject,
+                &body_text,
+                body_html.as_deref(),
+                10,
+                Some(json!({ "type": email_type, "user_id": user_id })),
+            )
+            .await
+        {
+            error!("Failed to queue {} email for {}: {}", email_type, user_id, e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{insert_user, test_state};
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_new_device_requires_verification() {
+        let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap()).await.unwrap();
+        let mut app_config = crate::config::Config::from_env().unwrap();
+        app_config.new_device_login_action = NewDeviceLoginAction::Verify;
+        let state = SessionState {
+            app_config: Arc::new(app_config),
+            ..test_state(&pool)
+        };
+
+        let tag = Uuid::new_v4().simple().to_string()[..8].to_string();
+        let user_id = insert_user(&pool, &format!("nd{}", tag), None).await;
+        let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+        // No history yet: the first device is never flagged
+        let first_login = check_login_device(&state, &user, None, "Laptop", None, "/login").await;
+        sqlx::query("INSERT INTO login_events (user_id, user_agent, success) VALUES ($1, 'Laptop', true)")
+            .bind(user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let known = check_login_device(&state, &user, None, "Laptop", None, "/login").await;
+        let unverified = check_login_device(&state, &user, None, "Phone", None, "/login").await;
+        let token = state
+            .session_store
+            .create_device_verification_token(user.id, &device_hash("Phone"), 60)
+            .await
+            .unwrap();
+        let wrong_device = check_login_device(&state, &user, None, "Tablet", Some(&token), "/login").await;
+        let token = state
+            .session_store
+            .create_device_verification_token(user.id, &device_hash("Phone"), 60)
+            .await
+            .unwrap();
+        let verified = check_login_device(&state, &user, None, "Phone", Some(&token), "/login").await;
+        let reused = check_login_device(&state, &user, None, "Phone", Some(&token), "/login").await;
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(first_login.is_ok());
+        assert!(known.is_ok());
+        let is_verification_required = |result: &Result<(), ApiError>| {
+            matches!(result, Err(ApiError::Forbidden(detail)) if detail.code == "new_device_verification_required")
+        };
+        assert!(is_verification_required(&unverified));
+        assert!(is_verification_required(&wrong_device));
+        assert!(verified.is_ok());
+        assert!(is_verification_required(&reused));
+    }
+}
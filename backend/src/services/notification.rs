@@ -0,0 +1,443 @@
+use anyhow::{bail, Result};
+use serde_json::json;
+use sqlx::PgPool;
+use std::time::Duration;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::middleware::session::SessionState;
+use crate::models::notifications::{
+    CategoryPreferences, NotificationCategory, NotificationChannel, NotificationPreferences, UpdateNotificationPreferences,
+};
+use crate::services::notification_inbox::{store_notification, store_user_notifications};
+use crate::utils::http_client::trusted_client;
+
+/// Messaging API limit for one text message
+const LINE_MESSAGE_MAX_CHARS: usize = 5000;
+const LINE_TIMEOUT_SECONDS: u64 = 10;
+
+/// Who a chat notification is for: a user's own chat, or a faculty's group chat
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationTarget {
+    User(Uuid),
+    Faculty(Uuid),
+}
+
+/// LINE Messaging API push channel of the school's Official Account; `to` picks the chat
+pub struct LineMessagingChannel {
+    api_url: String,
+    access_token: String,
+}
+
+impl LineMessagingChannel {
+    pub fn new(api_url: &str, access_token: &str) -> Self {
+        Self {
+            api_url: api_url.to_string(),
+            access_token: access_token.to_string(),
+        }
+    }
+
+    pub async fn send(&self, to: &str, message: &str) -> Result<()> {
+        let text: String = message.chars().take(LINE_MESSAGE_MAX_CHARS).collect();
+        let body = json!({
+            "to": to,
+            "messages": [{ "type": "text", "text": text }],
+        });
+
+        let response = trusted_client()
+            .post(&self.api_url)
+            .bearer_auth(&self.access_token)
+            .timeout(Duration::from_secs(LINE_TIMEOUT_SECONDS))
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .map_err(|e| anyhow::Error::from(e.without_url()))?;
+
+        if !response.status().is_success() {
+            bail!("LINE Messaging API returned HTTP {}", response.status().as_u16());
+        }
+        Ok(())
+    }
+}
+
+/// The user's preferences; categories without a stored row have every channel enabled
+pub async fn get_notification_preferences(pool: &PgPool, user_id: Uuid) -> Result<NotificationPreferences, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (String, bool, bool, bool)>(
+        "SELECT category, email_enabled, sse_enabled, line_enabled FROM notification_preferences WHERE user_id = $1",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut preferences = NotificationPreferences::default();
+    for (category, email_enabled, sse_enabled, line_enabled) in rows {
+        if let Some(category) = NotificationCategory::ALL.into_iter().find(|c| c.as_str() == category) {
+            *preferences.get_mut(category) = CategoryPreferences {
+                email_enabled,
+                sse_enabled,
+                line_enabled,
+            };
+        }
+    }
+    Ok(preferences)
+}
+
+/// Apply a partial update and return the resulting preferences
+pub async fn update_notification_preferences(
+    pool: &PgPool,
+    user_id: Uuid,
+    update: UpdateNotificationPreferences,
+) -> Result<NotificationPreferences, sqlx::Error> {
+    let mut preferences = get_notification_preferences(pool, user_id).await?;
+    let changes = [
+        (NotificationCategory::Reminders, update.reminders),
+        (NotificationCategory::Announcements, update.announcements),
+        (NotificationCategory::CheckIns, update.check_ins),
+    ];
+
+    let mut tx = pool.begin().await?;
+    for (category, change) in changes {
+        let Some(change) = change else { continue };
+        let current = preferences.get_mut(category);
+        current.email_enabled = change.email_enabled.unwrap_or(current.email_enabled);
+        current.sse_enabled = change.sse_enabled.unwrap_or(current.sse_enabled);
+        current.line_enabled = change.line_enabled.unwrap_or(current.line_enabled);
+
+        sqlx::query(
+            r#"
+            INSERT INTO notification_preferences (user_id, category, email_enabled, sse_enabled, line_enabled)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (user_id, category) DO UPDATE
+            SET email_enabled = EXCLUDED.email_enabled, sse_enabled = EXCLUDED.sse_enabled,
+                line_enabled = EXCLUDED.line_enabled, updated_at = NOW()
+            "#,
+        )
+        .bind(user_id)
+        .bind(category.as_str())
+        .bind(current.email_enabled)
+        .bind(current.sse_enabled)
+        .bind(current.line_enabled)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    Ok(preferences)
+}
+
+/// Whether `user_id` still wants `category` on `channel`. Lookup errors count as enabled,
+/// so a database hiccup delays nothing the user asked for.
+pub async fn is_channel_enabled(
+    pool: &PgPool,
+    user_id: Uuid,
+    category: NotificationCategory,
+    channel: NotificationChannel,
+) -> bool {
+    let enabled = sqlx::query_scalar::<_, bool>(&format!(
+        "SELECT {} FROM notification_preferences WHERE user_id = $1 AND category = $2",
+        channel.preference_column()
+    ))
+    .bind(user_id)
+    .bind(category.as_str())
+    .fetch_optional(pool)
+    .await;
+
+    match enabled {
+        Ok(enabled) => enabled.unwrap_or(true),
+        Err(e) => {
+            warn!("Failed to read notification preferences of {}: {}", user_id, e);
+            true
+        }
+    }
+}
+
+/// Send `message` to the target's LINE chat if it has one linked for `category`
+/// and, for users, LINE is enabled for that category in their preferences.
+///
+/// Email and in-app notifications keep their own paths; this is the optional extra channel,
+/// so it is silently skipped when no channel access token is configured or no chat is linked,
+/// and a failed send is only logged.
+pub async fn deliver_notification(
+    session_state: &SessionState,
+    target: NotificationTarget,
+    category: NotificationCategory,
+    message: &str,
+) -> bool {
+    let Some(access_token) = session_state.app_config.line_channel_access_token.as_deref() else {
+        return false;
+    };
+    let (user_id, faculty_id) = match target {
+        NotificationTarget::User(id) => (Some(id), None),
+        NotificationTarget::Faculty(id) => (None, Some(id)),
+    };
+
+    // Faculty chats are shared, so only a user's own opt-out applies
+    let line_id = sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT r.line_id FROM line_recipients r
+        WHERE (r.user_id = $1 OR r.faculty_id = $2) AND $3 = ANY(r.categories)
+          AND NOT EXISTS (
+              SELECT 1 FROM notification_preferences np
+              WHERE np.user_id = r.user_id AND np.category = $3 AND NOT np.line_enabled
+          )
+        "#,
+    )
+    .bind(user_id)
+    .bind(faculty_id)
+    .bind(category.as_str())
+    .fetch_optional(&session_state.db_pool)
+    .await;
+
+    let line_id = match line_id {
+        Ok(Some(line_id)) => line_id,
+        Ok(None) => return false,
+        Err(e) => {
+            warn!("Failed to look up LINE chat for {:?}: {}", target, e);
+            return false;
+        }
+    };
+
+    match LineMessagingChannel::new(&session_state.app_config.line_push_api_url, access_token)
+        .send(&line_id, message)
+        .await
+    {
+        Ok(()) => {
+            debug!("Sent {} notification to {:?} via LINE", category.as_str(), target);
+            true
+        }
+        Err(e) => {
+            warn!("Failed to send LINE notification to {:?}: {}", target, e);
+            false
+        }
+    }
+}
+
+/// Fire-and-forget [`deliver_notification`], for request handlers that must not wait on LINE
+pub fn notify(session_state: &SessionState, target: NotificationTarget, category: NotificationCategory, message: String) {
+    let session_state = session_state.clone();
+    tokio::spawn(async move {
+        deliver_notification(&session_state, target, category, &message).await;
+    });
+}
+
+/// Check-in/check-out notice to the student and to the activity's faculty chat (runs in the background)
+pub fn notify_participation_scan(session_state: &SessionState, activity_id: Uuid, user_id: Uuid, checked_out: bool) {
+    let session_state = session_state.clone();
+    tokio::spawn(async move {
+        let details = sqlx::query_as::<_, (String, Option<Uuid>, String, String)>(
+            r#"
+            SELECT a.title, a.faculty_id, u.first_name, u.last_name
+            FROM activities a, users u
+            WHERE a.id = $1 AND u.id = $2
+            "#,
+        )
+        .bind(activity_id)
+        .bind(user_id)
+        .fetch_optional(&session_state.db_pool)
+        .await;
+
+        let Ok(Some((title, faculty_id, first_name, last_name))) = details else {
+            return;
+        };
+
+        let action = if checked_out { "checked out of" } else { "checked in to" };
+        let message = format!("You {} \"{}\"", action, title);
+        store_notification(
+            &session_state.db_pool,
+            NotificationTarget::User(user_id),
+            NotificationCategory::CheckIns,
+            &message,
+            json!({ "activity_id": activity_id, "checked_out": checked_out }),
+        )
+        .await;
+        deliver_notification(&session_state, NotificationTarget::User(user_id), NotificationCategory::CheckIns, &message).await;
+        if let Some(faculty_id) = faculty_id {
+            let message = format!("{} {} {} \"{}\"", first_name, last_name, action, title);
+            deliver_notification(&session_state, NotificationTarget::Faculty(faculty_id), NotificationCategory::CheckIns, &message)
+                .await;
+        }
+    });
+}
+
+/// Bulk check-in: a notice per student, but a single summary for the faculty chat
+pub fn notify_bulk_check_in(session_state: &SessionState, activity_id: Uuid, user_ids: Vec<Uuid>) {
+    if user_ids.is_empty() {
+        return;
+    }
+    let session_state = session_state.clone();
+    tokio::spawn(async move {
+        let details = sqlx::query_as::<_, (String, Option<Uuid>)>("SELECT title, faculty_id FROM activities WHERE id = $1")
+            .bind(activity_id)
+            .fetch_optional(&session_state.db_pool)
+            .await;
+        let Ok(Some((title, faculty_id))) = details else {
+            return;
+        };
+
+        let message = format!("You checked in to \"{}\"", title);
+        store_user_notifications(
+            &session_state.db_pool,
+            &user_ids,
+            NotificationCategory::CheckIns,
+            &message,
+            json!({ "activity_id": activity_id, "checked_out": false }),
+        )
+        .await;
+        for user_id in &user_ids {
+            deliver_notification(&session_state, NotificationTarget::User(*user_id), NotificationCategory::CheckIns, &message)
+                .await;
+        }
+        if let Some(faculty_id) = faculty_id {
+            let message = format!("{} students were checked in to \"{}\"", user_ids.len(), title);
+            deliver_notification(&session_state, NotificationTarget::Faculty(faculty_id), NotificationCategory::CheckIns, &message)
+                .await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::notifications::UpdateCategoryPreferences;
+    use crate::test_support::{insert_user, test_state};
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_line_notification_respects_linked_chat_and_categories() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut config = crate::config::Config::from_env().unwrap();
+        config.line_push_api_url = format!("http://127.0.0.1:{}/v2/bot/message/push", listener.local_addr().unwrap().port());
+        config.line_channel_access_token = Some("channel-token".to_string());
+        let session_state = SessionState {
+            app_config: Arc::new(config),
+            ..test_state(&pool)
+        };
+
+        let receiver = std::thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 4096];
+            // Read until the JSON body is complete
+            while String::from_utf8_lossy(&request)
+                .split_once("\r\n\r\n")
+                .is_none_or(|(_, body)| serde_json::from_str::<serde_json::Value>(body).is_err())
+            {
+                let n = socket.read(&mut buffer).unwrap();
+                request.extend_from_slice(&buffer[..n]);
+            }
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+                .unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        let tag = Uuid::new_v4().simple().to_string()[..8].to_string();
+        let user_id = insert_user(&pool, &format!("line{}", tag), None).await;
+        let other_user_id = Uuid::new_v4();
+
+        // No linked chat: silently skipped
+        let without_chat = deliver_notification(
+            &session_state,
+            NotificationTarget::User(other_user_id),
+            NotificationCategory::Reminders,
+            "Reminder",
+        )
+        .await;
+
+        let line_id = format!("U{}", Uuid::new_v4().simple());
+        sqlx::query("INSERT INTO line_recipients (user_id, line_id, categories) VALUES ($1, $2, $3)")
+            .bind(user_id)
+            .bind(&line_id)
+            .bind(vec!["reminders".to_string()])
+            .execute(&pool)
+            .await
+            .unwrap();
+        let unsubscribed = deliver_notification(
+            &session_state,
+            NotificationTarget::User(user_id),
+            NotificationCategory::CheckIns,
+            "Checked in",
+        )
+        .await;
+        let sent = deliver_notification(
+            &session_state,
+            NotificationTarget::User(user_id),
+            NotificationCategory::Reminders,
+            "กิจกรรมเริ่ม 09:00",
+        )
+        .await;
+        let request = receiver.join().unwrap();
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(!without_chat);
+        assert!(!unsubscribed);
+        assert!(sent);
+        let (head, body) = request.split_once("\r\n\r\n").unwrap();
+        let head = head.to_ascii_lowercase();
+        assert!(head.starts_with("post /v2/bot/message/push http/1.1\r\n"));
+        assert!(head.contains("authorization: bearer channel-token\r\n"));
+        assert!(head.contains("content-type: application/json\r\n"));
+        let body: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(body["to"], line_id);
+        assert_eq!(body["messages"], json!([{ "type": "text", "text": "กิจกรรมเริ่ม 09:00" }]));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_notification_preferences_default_on_and_partial_update() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let tag = Uuid::new_v4().simple().to_string()[..8].to_string();
+        let user_id = insert_user(&pool, &format!("pref{}", tag), None).await;
+
+        let defaults = get_notification_preferences(&pool, user_id).await.unwrap();
+        let updated = update_notification_preferences(
+            &pool,
+            user_id,
+            UpdateNotificationPreferences {
+                reminders: Some(UpdateCategoryPreferences {
+                    email_enabled: Some(false),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let reloaded = get_notification_preferences(&pool, user_id).await.unwrap();
+        let reminder_email =
+            is_channel_enabled(&pool, user_id, NotificationCategory::Reminders, NotificationChannel::Email).await;
+        let reminder_line =
+            is_channel_enabled(&pool, user_id, NotificationCategory::Reminders, NotificationChannel::Line).await;
+        let announcement_email =
+            is_channel_enabled(&pool, user_id, NotificationCategory::Announcements, NotificationChannel::Email).await;
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(defaults, NotificationPreferences::default());
+        assert!(!updated.reminders.email_enabled);
+        assert!(updated.reminders.line_enabled && updated.reminders.sse_enabled);
+        assert_eq!(updated.announcements, CategoryPreferences::default());
+        assert_eq!(reloaded, updated);
+        assert!(!reminder_email);
+        assert!(reminder_line);
+        assert!(announcement_email);
+    }
+}
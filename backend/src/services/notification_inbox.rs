@@ -0,0 +1,256 @@
+use serde_json::Value;
+use sqlx::PgPool;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::models::notifications::{InboxNotification, NotificationCategory};
+use crate::services::notification::NotificationTarget;
+
+/// Notifications user `$1` can see: addressed to them, addressed to their faculty since they
+/// joined, system announcements inside their display window, and their subscription notices.
+/// Categories with in-app delivery (`sse_enabled`) switched off are hidden.
+const VISIBLE_NOTIFICATIONS: &str = r#"
+    SELECT n.id, n.category::TEXT AS category, NULL::TEXT AS title, n.message, n.data, n.created_at
+    FROM notifications n
+    JOIN users u ON u.id = $1
+    LEFT JOIN departments d ON d.id = u.department_id
+    WHERE (n.user_id = u.id OR (n.faculty_id = d.faculty_id AND n.created_at >= COALESCE(u.created_at, n.created_at)))
+      AND NOT EXISTS (
+          SELECT 1 FROM notification_preferences np
+          WHERE np.user_id = u.id AND np.category = n.category AND NOT np.sse_enabled
+      )
+    UNION ALL
+    SELECT a.id, 'system', a.title::TEXT, a.message,
+           jsonb_build_object('announcement_id', a.id, 'display_until', a.display_until), a.published_at
+    FROM scheduled_announcements a
+    WHERE a.status = 'published' AND (a.display_until IS NULL OR a.display_until > NOW())
+    UNION ALL
+    SELECT s.id, 'subscription', s.title::TEXT, s.message, COALESCE(s.metadata, '{}'), COALESCE(s.created_at, NOW())
+    FROM subscription_notifications s
+    WHERE s.user_id = $1
+"#;
+
+/// Add a notification to the target's inbox. Best effort: failures are only logged.
+pub async fn store_notification(
+    pool: &PgPool,
+    target: NotificationTarget,
+    category: NotificationCategory,
+    message: &str,
+    data: Value,
+) {
+    let (user_id, faculty_id) = match target {
+        NotificationTarget::User(id) => (Some(id), None),
+        NotificationTarget::Faculty(id) => (None, Some(id)),
+    };
+
+    let result = sqlx::query(
+        "INSERT INTO notifications (user_id, faculty_id, category, message, data) VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(user_id)
+    .bind(faculty_id)
+    .bind(category.as_str())
+    .bind(message)
+    .bind(&data)
+    .execute(pool)
+    .await;
+
+    if let Err(e) = result {
+        warn!("Failed to store notification for {:?}: {}", target, e);
+    }
+}
+
+/// The same notification for many users at once (cancellations, bulk check-in)
+pub async fn store_user_notifications(
+    pool: &PgPool,
+    user_ids: &[Uuid],
+    category: NotificationCategory,
+    message: &str,
+    data: Value,
+) {
+    if user_ids.is_empty() {
+        return;
+    }
+
+    let result = sqlx::query(
+        r#"
+        INSERT INTO notifications (user_id, category, message, data)
+        SELECT user_id, $2, $3, $4 FROM unnest($1::uuid[]) AS t(user_id)
+        "#,
+    )
+    .bind(user_ids)
+    .bind(category.as_str())
+    .bind(message)
+    .bind(&data)
+    .execute(pool)
+    .await;
+
+    if let Err(e) = result {
+        warn!("Failed to store notifications for {} users: {}", user_ids.len(), e);
+    }
+}
+
+/// A page of the user's inbox, newest first
+pub async fn list_notifications(
+    pool: &PgPool,
+    user_id: Uuid,
+    unread_only: bool,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<InboxNotification>, sqlx::Error> {
+    sqlx::query_as::<_, InboxNotification>(&format!(
+        r#"
+        SELECT v.*, r.read_at IS NOT NULL AS is_read, r.read_at
+        FROM ({}) v
+        LEFT JOIN notification_reads r ON r.notification_id = v.id AND r.user_id = $1
+        WHERE NOT $2 OR r.read_at IS NULL
+        ORDER BY v.created_at DESC, v.id
+        LIMIT $3 OFFSET $4
+        "#,
+        VISIBLE_NOTIFICATIONS
+    ))
+    .bind(user_id)
+    .bind(unread_only)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await
+}
+
+/// (total, unread) over the whole inbox
+pub async fn count_notifications(pool: &PgPool, user_id: Uuid) -> Result<(i64, i64), sqlx::Error> {
+    sqlx::query_as::<_, (i64, i64)>(&format!(
+        r#"
+        SELECT COUNT(*), COUNT(*) FILTER (WHERE r.read_at IS NULL)
+        FROM ({}) v
+        LEFT JOIN notification_reads r ON r.notification_id = v.id AND r.user_id = $1
+        "#,
+        VISIBLE_NOTIFICATIONS
+    ))
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn unread_notification_count(pool: &PgPool, user_id: Uuid) -> Result<i64, sqlx::Error> {
+    count_notifications(pool, user_id).await.map(|(_, unread)| unread)
+}
+
+/// Mark one notification read; `false` when the user can't see it. Marking twice keeps the first `read_at`.
+pub async fn mark_notification_read(pool: &PgPool, user_id: Uuid, notification_id: Uuid) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar::<_, bool>(&format!(
+        r#"
+        WITH target AS (
+            SELECT v.id FROM ({}) v WHERE v.id = $2
+        ), marked AS (
+            INSERT INTO notification_reads (notification_id, user_id)
+            SELECT id, $1 FROM target
+            ON CONFLICT DO NOTHING
+        )
+        SELECT EXISTS (SELECT 1 FROM target)
+        "#,
+        VISIBLE_NOTIFICATIONS
+    ))
+    .bind(user_id)
+    .bind(notification_id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Mark every visible notification read; returns how many were unread
+pub async fn mark_all_notifications_read(pool: &PgPool, user_id: Uuid) -> Result<u64, sqlx::Error> {
+    sqlx::query(&format!(
+        r#"
+        INSERT INTO notification_reads (notification_id, user_id)
+        SELECT v.id, $1 FROM ({}) v
+        ON CONFLICT DO NOTHING
+        "#,
+        VISIBLE_NOTIFICATIONS
+    ))
+    .bind(user_id)
+    .execute(pool)
+    .await
+    .map(|result| result.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::insert_user;
+    use serde_json::json;
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_inbox_read_tracking() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let tag = Uuid::new_v4().simple().to_string()[..8].to_string();
+        let faculty_id: Uuid = sqlx::query_scalar("INSERT INTO faculties (name, code) VALUES ($1, $2) RETURNING id")
+            .bind(format!("Inbox {}", tag))
+            .bind(&tag[..6])
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let department_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO departments (name, code, faculty_id) VALUES ('Inbox', $1, $2) RETURNING id",
+        )
+        .bind(&tag[..6])
+        .bind(faculty_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let mut user_ids = Vec::new();
+        for prefix in ["ia", "ib"] {
+            user_ids.push(insert_user(&pool, &format!("{}{}", prefix, tag), None).await);
+        }
+        sqlx::query("UPDATE users SET department_id = $1 WHERE id = ANY($2)")
+            .bind(department_id)
+            .bind(&user_ids)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let (user_id, other_user_id) = (user_ids[0], user_ids[1]);
+
+        store_notification(&pool, NotificationTarget::User(user_id), NotificationCategory::Reminders, "Reminder", json!({})).await;
+        store_notification(&pool, NotificationTarget::Faculty(faculty_id), NotificationCategory::Announcements, "New activity", json!({})).await;
+        store_user_notifications(&pool, &[other_user_id], NotificationCategory::CheckIns, "Checked in", json!({})).await;
+
+        // System announcements reach every user, so they are left out of the counts
+        let own = |list: Vec<InboxNotification>| -> Vec<InboxNotification> {
+            list.into_iter().filter(|n| n.category != "system").collect()
+        };
+        let inbox = own(list_notifications(&pool, user_id, false, 20, 0).await.unwrap());
+        let reminder = inbox.iter().find(|n| n.category == "reminders").unwrap().id;
+        let others = list_notifications(&pool, other_user_id, false, 20, 0).await.unwrap();
+        let foreign = others.iter().find(|n| n.category == "check_ins").unwrap().id;
+
+        let marked = mark_notification_read(&pool, user_id, reminder).await.unwrap();
+        let not_visible = mark_notification_read(&pool, user_id, foreign).await.unwrap();
+        let unread = own(list_notifications(&pool, user_id, true, 20, 0).await.unwrap());
+        let marked_all = mark_all_notifications_read(&pool, user_id).await.unwrap();
+        let after = own(list_notifications(&pool, user_id, true, 20, 0).await.unwrap());
+        let other_unread = own(list_notifications(&pool, other_user_id, true, 20, 0).await.unwrap());
+
+        sqlx::query("DELETE FROM users WHERE id = ANY($1)")
+            .bind(&user_ids)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM faculties WHERE id = $1")
+            .bind(faculty_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(inbox.len(), 2);
+        assert!(inbox.iter().all(|n| !n.is_read));
+        assert!(marked);
+        assert!(!not_visible);
+        assert_eq!(unread.len(), 1);
+        assert_eq!(unread[0].message, "New activity");
+        assert!(marked_all >= 1);
+        assert!(after.is_empty());
+        // Faculty announcement plus the check-in, untouched by the first user's reads
+        assert_eq!(other_unread.len(), 2);
+    }
+}
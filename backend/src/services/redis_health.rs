@@ -0,0 +1,61 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use tracing::{info, warn};
+
+/// Tracks consecutive Redis failures so Redis-backed figures can be reported as unavailable
+/// (`redis_available: false`) instead of as misleading zeros.
+///
+/// One failure engages degraded mode and the next successful call clears it; only the
+/// transitions are logged so an outage doesn't flood the log.
+#[derive(Debug, Default)]
+pub struct RedisHealth {
+    consecutive_failures: AtomicU32,
+}
+
+impl RedisHealth {
+    pub fn is_available(&self) -> bool {
+        self.consecutive_failures.load(Ordering::Relaxed) == 0
+    }
+
+    pub fn record_success(&self) {
+        let failures = self.consecutive_failures.swap(0, Ordering::Relaxed);
+        if failures > 0 {
+            info!("Redis is reachable again after {} failed calls; leaving degraded mode", failures);
+        }
+    }
+
+    pub fn record_failure(&self, error: &anyhow::Error) {
+        let failures = self.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if failures == 1 {
+            warn!("Redis call failed, entering degraded mode: {}", error);
+        }
+    }
+
+    /// Record the outcome of a Redis call and pass the result through
+    pub fn observe<T>(&self, result: anyhow::Result<T>) -> anyhow::Result<T> {
+        match &result {
+            Ok(_) => self.record_success(),
+            Err(e) => self.record_failure(e),
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failures_engage_degraded_mode_until_success() {
+        let health = RedisHealth::default();
+        assert!(health.is_available());
+
+        assert!(health.observe::<()>(Err(anyhow::anyhow!("connection refused"))).is_err());
+        assert!(health.observe::<()>(Err(anyhow::anyhow!("connection refused"))).is_err());
+        assert!(!health.is_available());
+        assert_eq!(health.consecutive_failures.load(Ordering::Relaxed), 2);
+
+        assert_eq!(health.observe(Ok(3)).unwrap(), 3);
+        assert!(health.is_available());
+        assert_eq!(health.consecutive_failures.load(Ordering::Relaxed), 0);
+    }
+}
@@ -1,167 +1,106 @@
 use anyhow::Result;
+use async_trait::async_trait;
 use chrono::{DateTime, Duration, Utc};
-use redis::{AsyncCommands, Client};
+use rand::Rng;
+use redis::{AsyncCommands, Client, ErrorKind, ExistenceCheck, SetExpiry, SetOptions};
 use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::future::Future;
+use std::net::IpAddr;
 use uuid::Uuid;
 
+use crate::config::Config;
 use crate::models::admin_role::AdminLevel;
 use crate::models::session::{
-    BatchSessionRevocationRequest, BatchSessionRevocationResponse, CreateSession, LoginMethod, Session, SessionActivity,
-    SessionActivityType, SessionValidation, SessionType, ForceLogoutUserRequest, ForceLogoutFacultyRequest,
+    CreateSession, LoginMethod, Session, SessionActivity, SessionActivityType, SessionValidation, SessionType,
 };
+use crate::services::geoip::lookup_ip;
+use crate::services::session_store::SessionStore;
+use crate::utils::qr::generate_secret_key;
+
+/// Attempts per core session operation unless overridden with `with_retry_attempts`
+const DEFAULT_RETRY_ATTEMPTS: u32 = 3;
+
+/// First retry delay; doubles per attempt with up to 50% jitter on top
+const RETRY_BASE_DELAY_MS: u64 = 50;
 
 pub struct RedisSessionStore {
     client: Client,
+    retry_attempts: u32,
 }
 
 impl RedisSessionStore {
     pub fn new(redis_url: &str) -> Result<Self> {
         let client = Client::open(redis_url)?;
-        Ok(Self { client })
+        Ok(Self {
+            client,
+            retry_attempts: DEFAULT_RETRY_ATTEMPTS,
+        })
     }
 
-    pub async fn get_connection(&self) -> Result<redis::aio::MultiplexedConnection> {
-        Ok(self.client.get_multiplexed_async_connection().await?)
+    /// Total attempts for the core session operations; 1 disables retries
+    pub fn with_retry_attempts(mut self, attempts: u32) -> Self {
+        self.retry_attempts = attempts.max(1);
+        self
     }
 
-    // Session management methods
-    pub async fn create_session(&self, create_req: CreateSession) -> Result<Session> {
-        let session_id = Uuid::new_v4().to_string();
-        let session = Session {
-            id: session_id.clone(),
-            user_id: create_req.user_id,
-            expires_at: create_req.expires_at,
-            created_at: Utc::now(),
-            last_accessed: Utc::now(),
-            ip_address: create_req.ip_address.clone(),
-            user_agent: create_req.user_agent.clone(),
-            device_info: create_req.device_info,
-            is_active: true,
-            // Initialize new fields with defaults
-            session_type: SessionType::Student, // Will be updated by caller
-            admin_level: None,
-            faculty_id: None,
-            permissions: Vec::new(),
-            revoked_by: None,
-            revoked_at: None,
-            revocation_reason: None,
-            login_method: LoginMethod::StudentId, // Will be updated by caller
-            sse_connections: Vec::new(),
-            activity_log: vec![SessionActivity {
-                timestamp: Utc::now(),
-                activity_type: SessionActivityType::Login,
-                details: Some("Session created".to_string()),
-                ip_address: create_req.ip_address.clone(),
-                user_agent: create_req.user_agent.clone(),
-            }],
-        };
-
-        let mut conn = self.get_connection().await?;
-        let session_key = format!("session:{}", session_id);
-        let session_data = serde_json::to_string(&session)?;
-
-        // Calculate TTL in seconds
-        let ttl_seconds = (create_req.expires_at - Utc::now()).num_seconds();
-        if ttl_seconds <= 0 {
-            return Err(anyhow::anyhow!("Session expiry time is in the past"));
+    /// Run `operation` again after a short jittered backoff when it fails with a
+    /// connection-level error. Misses (`Ok(None)`) and logical errors are returned as is.
+    async fn with_retry<T, F, Fut>(&self, name: &str, mut operation: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match operation().await {
+                Err(e) if attempt < self.retry_attempts && is_retryable(&e) => {
+                    let delay = retry_delay(attempt);
+                    tracing::warn!(
+                        "Redis {} failed (attempt {}/{}), retrying in {:?}: {}",
+                        name,
+                        attempt,
+                        self.retry_attempts,
+                        delay,
+                        e
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
         }
-
-        // Set session data with expiration
-        conn.set_ex::<_, _, ()>(&session_key, session_data, ttl_seconds as u64)
-            .await?;
-
-        // Add to user's active sessions set
-        let user_sessions_key = format!("user_sessions:{}", create_req.user_id);
-        conn.sadd::<_, _, ()>(&user_sessions_key, &session_id)
-            .await?;
-        conn.expire::<_, ()>(&user_sessions_key, ttl_seconds as i64)
-            .await?;
-
-        // Track session in global active sessions
-        let active_sessions_key = "active_sessions";
-        conn.zadd::<_, _, _, ()>(active_sessions_key, &session_id, ttl_seconds)
-            .await?;
-
-        Ok(session)
     }
 
-    // Create admin session with enhanced tracking
-    pub async fn create_admin_session(
-        &self,
-        create_req: CreateSession,
-        session_type: SessionType,
-        admin_level: AdminLevel,
-        faculty_id: Option<Uuid>,
-        permissions: Vec<String>,
-        login_method: LoginMethod,
-    ) -> Result<Session> {
-        let session_id = Uuid::new_v4().to_string();
-        let session = Session {
-            id: session_id.clone(),
-            user_id: create_req.user_id,
-            expires_at: create_req.expires_at,
-            created_at: Utc::now(),
-            last_accessed: Utc::now(),
-            ip_address: create_req.ip_address.clone(),
-            user_agent: create_req.user_agent.clone(),
-            device_info: create_req.device_info,
-            is_active: true,
-            // Admin-specific fields
-            session_type,
-            admin_level: Some(admin_level.clone()),
-            faculty_id,
-            permissions,
-            revoked_by: None,
-            revoked_at: None,
-            revocation_reason: None,
-            login_method,
-            sse_connections: Vec::new(),
-            activity_log: vec![SessionActivity {
-                timestamp: Utc::now(),
-                activity_type: SessionActivityType::Login,
-                details: Some(format!(
-                    "Admin session created with level: {:?}",
-                    admin_level
-                )),
-                ip_address: create_req.ip_address.clone(),
-                user_agent: create_req.user_agent.clone(),
-            }],
-        };
+    pub async fn get_connection(&self) -> Result<redis::aio::MultiplexedConnection> {
+        Ok(self.client.get_multiplexed_async_connection().await?)
+    }
 
+    async fn store_new_session(&self, session: &Session, ttl_seconds: i64) -> Result<()> {
         let mut conn = self.get_connection().await?;
-        let session_key = format!("session:{}", session_id);
-        let session_data = serde_json::to_string(&session)?;
-
-        // Calculate TTL in seconds
-        let ttl_seconds = (create_req.expires_at - Utc::now()).num_seconds();
-        if ttl_seconds <= 0 {
-            return Err(anyhow::anyhow!("Session expiry time is in the past"));
-        }
+        let session_key = format!("session:{}", session.id);
+        let session_data = serde_json::to_string(session)?;
 
         // Set session data with expiration
         conn.set_ex::<_, _, ()>(&session_key, session_data, ttl_seconds as u64)
             .await?;
 
         // Add to user's active sessions set
-        let user_sessions_key = format!("user_sessions:{}", create_req.user_id);
-        conn.sadd::<_, _, ()>(&user_sessions_key, &session_id)
+        let user_sessions_key = format!("user_sessions:{}", session.user_id);
+        conn.sadd::<_, _, ()>(&user_sessions_key, &session.id)
             .await?;
-        conn.expire::<_, ()>(&user_sessions_key, ttl_seconds as i64)
+        conn.expire::<_, ()>(&user_sessions_key, ttl_seconds)
             .await?;
 
         // Track session in global active sessions
         let active_sessions_key = "active_sessions";
-        conn.zadd::<_, _, _, ()>(active_sessions_key, &session_id, ttl_seconds)
-            .await?;
-
-        // Track admin session separately
-        self.track_admin_session(&session_id, &admin_level, create_req.expires_at)
+        conn.zadd::<_, _, _, ()>(active_sessions_key, &session.id, ttl_seconds)
             .await?;
 
-        Ok(session)
+        Ok(())
     }
 
-    pub async fn get_session(&self, session_id: &str) -> Result<Option<Session>> {
+    async fn get_session_once(&self, session_id: &str) -> Result<Option<Session>> {
         let mut conn = self.get_connection().await?;
         let session_key = format!("session:{}", session_id);
 
@@ -184,26 +123,6 @@ impl RedisSessionStore {
         }
     }
 
-    pub async fn update_session_activity(&self, session_id: &str) -> Result<()> {
-        let mut conn = self.get_connection().await?;
-        let session_key = format!("session:{}", session_id);
-
-        // Get current session
-        if let Some(mut session) = self.get_session(session_id).await? {
-            session.last_accessed = Utc::now();
-
-            let session_data = serde_json::to_string(&session)?;
-            let ttl_seconds = (session.expires_at - Utc::now()).num_seconds();
-
-            if ttl_seconds > 0 {
-                conn.set_ex::<_, _, ()>(&session_key, session_data, ttl_seconds as u64)
-                    .await?;
-            }
-        }
-
-        Ok(())
-    }
-
     pub async fn validate_session(&self, session_id: &str) -> Result<SessionValidation> {
         match self.get_session(session_id).await? {
             Some(session) => {
@@ -227,448 +146,542 @@ impl RedisSessionStore {
         }
     }
 
-    pub async fn delete_session(&self, session_id: &str) -> Result<()> {
+    async fn get_active_sessions_once(&self, limit: Option<usize>) -> Result<Vec<String>> {
         let mut conn = self.get_connection().await?;
-
-        // Get session to find user_id and admin_level for cleanup
-        if let Some(session) = self.get_session(session_id).await? {
-            let user_sessions_key = format!("user_sessions:{}", session.user_id);
-            conn.srem::<_, _, ()>(&user_sessions_key, session_id)
-                .await?;
-
-            // Remove admin session tracking if it's an admin session
-            if let Some(admin_level) = &session.admin_level {
-                self.untrack_admin_session(session_id, admin_level).await?;
-            }
-
-            // Log session deletion activity
-            self.add_session_activity(
-                session_id,
-                SessionActivityType::Logout,
-                Some("Session deleted".to_string()),
-                None,
-                None,
-            )
-            .await
-            .unwrap_or_default(); // Ignore errors since session is being deleted
-        }
-
-        // Remove session data
-        let session_key = format!("session:{}", session_id);
-        conn.del::<_, ()>(&session_key).await?;
-
-        // Remove from active sessions
         let active_sessions_key = "active_sessions";
-        conn.zrem::<_, _, ()>(active_sessions_key, session_id)
-            .await?;
-
-        Ok(())
-    }
-
-    pub async fn delete_user_sessions(&self, user_id: Uuid) -> Result<Vec<String>> {
-        let mut conn = self.get_connection().await?;
-        let user_sessions_key = format!("user_sessions:{}", user_id);
 
-        // Get all user sessions
-        let session_ids: Vec<String> = conn.smembers(&user_sessions_key).await?;
+        let now = Utc::now().timestamp();
 
-        // Delete each session
-        for session_id in &session_ids {
-            Box::pin(self.delete_session(session_id)).await?;
-        }
+        // Remove expired sessions from sorted set
+        conn.zrembyscore::<_, _, _, ()>(active_sessions_key, 0, now)
+            .await?;
 
-        // Clear user sessions set
-        conn.del::<_, ()>(&user_sessions_key).await?;
+        // Get active session IDs
+        let session_ids: Vec<String> = match limit {
+            Some(n) => {
+                conn.zrange(active_sessions_key, 0, (n as isize) - 1)
+                    .await?
+            }
+            None => conn.zrange(active_sessions_key, 0, -1).await?,
+        };
 
         Ok(session_ids)
     }
 
-    pub async fn extend_session(
+    async fn revoke_session_once(
         &self,
         session_id: &str,
-        new_expiry: DateTime<Utc>,
+        reason: Option<String>,
+        revoked_by_admin_id: Option<Uuid>,
     ) -> Result<bool> {
-        let mut conn = self.get_connection().await?;
         let session_key = format!("session:{}", session_id);
+        let mut conn = self.get_connection().await?;
 
-        if let Some(mut session) = self.get_session(session_id).await? {
-            session.expires_at = new_expiry;
+        if let Some(mut session) = self.get_session_once(session_id).await? {
+            session.is_active = false;
             session.last_accessed = Utc::now();
+            session.revoked_at = Some(Utc::now());
+            session.revoked_by = revoked_by_admin_id;
+            session.revocation_reason = reason.clone();
+
+            // Add revocation reason to device_info if provided (legacy support)
+            if let Some(ref reason) = reason {
+                session.device_info.insert(
+                    "revocation_reason".to_string(),
+                    Value::String(reason.clone()),
+                );
+                session.device_info.insert(
+                    "revoked_at".to_string(),
+                    Value::String(Utc::now().to_rfc3339()),
+                );
+            }
+
+            // Add revocation activity to log
+            session.activity_log.push(SessionActivity {
+                timestamp: Utc::now(),
+                activity_type: SessionActivityType::ForceLogout,
+                details: reason
+                    .clone()
+                    .or_else(|| Some("Session revoked".to_string())),
+                ip_address: None,
+                user_agent: None,
+            });
 
             let session_data = serde_json::to_string(&session)?;
-            let ttl_seconds = (new_expiry - Utc::now()).num_seconds();
+            let ttl_seconds = (session.expires_at - Utc::now()).num_seconds();
 
             if ttl_seconds > 0 {
+                // Keep revoked session for audit purposes but mark as inactive
                 conn.set_ex::<_, _, ()>(&session_key, session_data, ttl_seconds as u64)
                     .await?;
+            }
 
-                // Update user sessions expiry
-                let user_sessions_key = format!("user_sessions:{}", session.user_id);
-                conn.expire::<_, ()>(&user_sessions_key, ttl_seconds as i64)
-                    .await?;
-
-                // Update active sessions score
-                let active_sessions_key = "active_sessions";
-                conn.zadd::<_, _, _, ()>(active_sessions_key, session_id, ttl_seconds)
-                    .await?;
+            // Remove from active sessions
+            let active_sessions_key = "active_sessions";
+            conn.zrem::<_, _, ()>(active_sessions_key, session_id)
+                .await?;
 
-                return Ok(true);
+            // Remove from admin session tracking if it's an admin session
+            if let Some(admin_level) = &session.admin_level {
+                self.untrack_admin_session(session_id, admin_level).await?;
             }
-        }
 
-        Ok(false)
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    pub async fn get_user_session_count(&self, user_id: Uuid) -> Result<usize> {
+        let user_sessions = self.get_user_sessions(user_id).await?;
+        Ok(user_sessions.len())
     }
 
-    pub async fn get_user_sessions(&self, user_id: Uuid) -> Result<Vec<Session>> {
+    // Track admin session in separate sorted set
+    pub async fn track_admin_session(
+        &self,
+        session_id: &str,
+        admin_level: &AdminLevel,
+        expires_at: DateTime<Utc>,
+    ) -> Result<()> {
         let mut conn = self.get_connection().await?;
-        let user_sessions_key = format!("user_sessions:{}", user_id);
+        let admin_sessions_key = "admin_sessions";
+        let level_sessions_key = format!("admin_sessions:{:?}", admin_level);
 
-        let session_ids: Vec<String> = conn.smembers(&user_sessions_key).await?;
-        let mut sessions = Vec::new();
+        let expiry_timestamp = expires_at.timestamp();
 
-        for session_id in session_ids {
-            if let Some(session) = self.get_session(&session_id).await? {
-                sessions.push(session);
-            }
-        }
+        // Add to general admin sessions
+        conn.zadd::<_, _, _, ()>(admin_sessions_key, session_id, expiry_timestamp)
+            .await?;
 
-        Ok(sessions)
+        // Add to level-specific admin sessions
+        conn.zadd::<_, _, _, ()>(&level_sessions_key, session_id, expiry_timestamp)
+            .await?;
+
+        Ok(())
     }
 
-    pub async fn get_active_sessions(&self, limit: Option<usize>) -> Result<Vec<String>> {
+    // Remove admin session tracking
+    pub async fn untrack_admin_session(
+        &self,
+        session_id: &str,
+        admin_level: &AdminLevel,
+    ) -> Result<()> {
         let mut conn = self.get_connection().await?;
-        let active_sessions_key = "active_sessions";
-
-        let now = Utc::now().timestamp();
+        let admin_sessions_key = "admin_sessions";
+        let level_sessions_key = format!("admin_sessions:{:?}", admin_level);
 
-        // Remove expired sessions from sorted set
-        conn.zrembyscore::<_, _, _, ()>(active_sessions_key, 0, now)
+        // Remove from general admin sessions
+        conn.zrem::<_, _, ()>(admin_sessions_key, session_id)
             .await?;
 
-        // Get active session IDs
-        let session_ids: Vec<String> = match limit {
-            Some(n) => {
-                conn.zrange(active_sessions_key, 0, (n as isize) - 1)
-                    .await?
-            }
-            None => conn.zrange(active_sessions_key, 0, -1).await?,
-        };
+        // Remove from level-specific admin sessions
+        conn.zrem::<_, _, ()>(&level_sessions_key, session_id)
+            .await?;
 
-        Ok(session_ids)
+        Ok(())
     }
 
-    pub async fn cleanup_expired_sessions(&self) -> Result<usize> {
-        let mut conn = self.get_connection().await?;
-        let active_sessions_key = "active_sessions";
-
-        let now = Utc::now().timestamp();
+    // Add SSE connection to session
+    pub async fn add_sse_connection(
+        &self,
+        session_id: &str,
+        sse_connection_id: String,
+    ) -> Result<()> {
+        if let Some(mut session) = self.get_session(session_id).await? {
+            if !session.sse_connections.contains(&sse_connection_id) {
+                session.sse_connections.push(sse_connection_id);
 
-        // Get expired session IDs
-        let expired_sessions: Vec<String> = conn.zrangebyscore(active_sessions_key, 0, now).await?;
+                // Update session in Redis
+                let mut conn = self.get_connection().await?;
+                let session_key = format!("session:{}", session_id);
+                let session_data = serde_json::to_string(&session)?;
+                let ttl_seconds = (session.expires_at - Utc::now()).num_seconds();
 
-        let count = expired_sessions.len();
+                if ttl_seconds > 0 {
+                    conn.set_ex::<_, _, ()>(&session_key, session_data, ttl_seconds as u64)
+                        .await?;
+                }
 
-        // Clean up expired sessions
-        for session_id in expired_sessions {
-            Box::pin(self.delete_session(&session_id)).await?;
+                // Log SSE connection activity
+                self.add_session_activity(
+                    session_id,
+                    SessionActivityType::SseConnected,
+                    Some("SSE connection established".to_string()),
+                    None,
+                    None,
+                )
+                .await?;
+            }
         }
 
-        Ok(count)
-    }
-
-    pub async fn revoke_session(&self, session_id: &str, reason: Option<String>) -> Result<bool> {
-        self.revoke_session_by_admin(session_id, reason, None).await
+        Ok(())
     }
 
-    // Enhanced revoke session with admin tracking
-    pub async fn revoke_session_by_admin(
+    // Remove SSE connection from session
+    pub async fn remove_sse_connection(
         &self,
         session_id: &str,
-        reason: Option<String>,
-        revoked_by_admin_id: Option<Uuid>,
-    ) -> Result<bool> {
-        let session_key = format!("session:{}", session_id);
-        let mut conn = self.get_connection().await?;
-
+        sse_connection_id: &str,
+    ) -> Result<()> {
         if let Some(mut session) = self.get_session(session_id).await? {
-            session.is_active = false;
-            session.last_accessed = Utc::now();
-            session.revoked_at = Some(Utc::now());
-            session.revoked_by = revoked_by_admin_id;
-            session.revocation_reason = reason.clone();
-
-            // Add revocation reason to device_info if provided (legacy support)
-            if let Some(ref reason) = reason {
-                session.device_info.insert(
-                    "revocation_reason".to_string(),
-                    Value::String(reason.clone()),
-                );
-                session.device_info.insert(
-                    "revoked_at".to_string(),
-                    Value::String(Utc::now().to_rfc3339()),
-                );
-            }
-
-            // Add revocation activity to log
-            session.activity_log.push(SessionActivity {
-                timestamp: Utc::now(),
-                activity_type: SessionActivityType::ForceLogout,
-                details: reason
-                    .clone()
-                    .or_else(|| Some("Session revoked".to_string())),
-                ip_address: None,
-                user_agent: None,
-            });
+            session.sse_connections.retain(|id| id != sse_connection_id);
 
+            // Update session in Redis
+            let mut conn = self.get_connection().await?;
+            let session_key = format!("session:{}", session_id);
             let session_data = serde_json::to_string(&session)?;
             let ttl_seconds = (session.expires_at - Utc::now()).num_seconds();
 
             if ttl_seconds > 0 {
-                // Keep revoked session for audit purposes but mark as inactive
                 conn.set_ex::<_, _, ()>(&session_key, session_data, ttl_seconds as u64)
                     .await?;
             }
 
-            // Remove from active sessions
-            let active_sessions_key = "active_sessions";
-            conn.zrem::<_, _, ()>(active_sessions_key, session_id)
-                .await?;
-
-            // Remove from admin session tracking if it's an admin session
-            if let Some(admin_level) = &session.admin_level {
-                self.untrack_admin_session(session_id, admin_level).await?;
-            }
-
-            Ok(true)
-        } else {
-            Ok(false)
+            // Log SSE disconnection activity
+            self.add_session_activity(
+                session_id,
+                SessionActivityType::SseDisconnected,
+                Some("SSE connection closed".to_string()),
+                None,
+                None,
+            )
+            .await?;
         }
+
+        Ok(())
     }
 
-    pub async fn get_session_count(&self) -> Result<usize> {
+    // Get sessions with active SSE connections
+    pub async fn get_sessions_with_sse(&self) -> Result<Vec<String>> {
         let mut conn = self.get_connection().await?;
         let active_sessions_key = "active_sessions";
 
         let now = Utc::now().timestamp();
+        let session_ids: Vec<String> = conn
+            .zrangebyscore(active_sessions_key, now + 1, "+inf")
+            .await?;
+        let mut sessions_with_sse = Vec::new();
 
-        // Count only non-expired sessions
-        let count: usize = conn.zcount(active_sessions_key, now + 1, "+inf").await?;
+        for session_id in session_ids {
+            if let Some(session) = self.get_session(&session_id).await? {
+                if !session.sse_connections.is_empty() {
+                    sessions_with_sse.push(session_id);
+                }
+            }
+        }
 
-        Ok(count)
+        Ok(sessions_with_sse)
     }
+}
 
-    pub async fn get_user_session_count(&self, user_id: Uuid) -> Result<usize> {
-        let user_sessions = self.get_user_sessions(user_id).await?;
-        Ok(user_sessions.len())
+#[async_trait]
+impl SessionStore for RedisSessionStore {
+    /// Round-trip a PING, used by the readiness probe
+    async fn ping(&self) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+        let _: String = redis::cmd("PING").query_async(&mut conn).await?;
+        Ok(())
     }
 
-    // ========== ADMIN SESSION MANAGEMENT METHODS ==========
+    // Session management methods
+    async fn create_session(&self, create_req: CreateSession) -> Result<Session> {
+        let session_id = Uuid::new_v4().to_string();
+        let session = Session {
+            id: session_id.clone(),
+            user_id: create_req.user_id,
+            expires_at: create_req.expires_at,
+            created_at: Utc::now(),
+            last_accessed: Utc::now(),
+            ip_address: create_req.ip_address.clone(),
+            user_agent: create_req.user_agent.clone(),
+            device_info: create_req.device_info,
+            is_active: true,
+            // Initialize new fields with defaults
+            session_type: SessionType::Student, // Will be updated by caller
+            admin_level: None,
+            faculty_id: None,
+            permissions: Vec::new(),
+            revoked_by: None,
+            revoked_at: None,
+            revocation_reason: None,
+            login_method: LoginMethod::StudentId, // Will be updated by caller
+            sse_connections: Vec::new(),
+            activity_log: vec![SessionActivity {
+                timestamp: Utc::now(),
+                activity_type: SessionActivityType::Login,
+                details: Some("Session created".to_string()),
+                ip_address: create_req.ip_address.clone(),
+                user_agent: create_req.user_agent.clone(),
+            }],
+            impersonated_by: create_req.impersonated_by,
+            impersonator_session_id: create_req.impersonator_session_id.clone(),
+            geo_location: lookup_ip(create_req.ip_address.as_deref()),
+        };
 
-    // Get all admin sessions with detailed information
-    pub async fn get_admin_sessions(
+        // Calculate TTL in seconds
+        let ttl_seconds = (create_req.expires_at - Utc::now()).num_seconds();
+        if ttl_seconds <= 0 {
+            return Err(anyhow::anyhow!("Session expiry time is in the past"));
+        }
+
+        // The writes are idempotent for a fixed session id, so a retry can't duplicate the session
+        self.with_retry("create_session", || self.store_new_session(&session, ttl_seconds))
+            .await?;
+
+        Ok(session)
+    }
+
+    // Create admin session with enhanced tracking
+    async fn create_admin_session(
         &self,
-        admin_level_filter: Option<AdminLevel>,
-    ) -> Result<Vec<String>> {
+        create_req: CreateSession,
+        session_type: SessionType,
+        admin_level: AdminLevel,
+        faculty_id: Option<Uuid>,
+        permissions: Vec<String>,
+        login_method: LoginMethod,
+    ) -> Result<Session> {
+        let session_id = Uuid::new_v4().to_string();
+        let session = Session {
+            id: session_id.clone(),
+            user_id: create_req.user_id,
+            expires_at: create_req.expires_at,
+            created_at: Utc::now(),
+            last_accessed: Utc::now(),
+            ip_address: create_req.ip_address.clone(),
+            user_agent: create_req.user_agent.clone(),
+            device_info: create_req.device_info,
+            is_active: true,
+            // Admin-specific fields
+            session_type,
+            admin_level: Some(admin_level.clone()),
+            faculty_id,
+            permissions,
+            revoked_by: None,
+            revoked_at: None,
+            revocation_reason: None,
+            login_method,
+            sse_connections: Vec::new(),
+            activity_log: vec![SessionActivity {
+                timestamp: Utc::now(),
+                activity_type: SessionActivityType::Login,
+                details: Some(format!(
+                    "Admin session created with level: {:?}",
+                    admin_level
+                )),
+                ip_address: create_req.ip_address.clone(),
+                user_agent: create_req.user_agent.clone(),
+            }],
+            impersonated_by: create_req.impersonated_by,
+            impersonator_session_id: create_req.impersonator_session_id.clone(),
+            geo_location: lookup_ip(create_req.ip_address.as_deref()),
+        };
+
         let mut conn = self.get_connection().await?;
-        let admin_sessions_key = "admin_sessions";
+        let session_key = format!("session:{}", session_id);
+        let session_data = serde_json::to_string(&session)?;
 
-        let now = Utc::now().timestamp();
+        // Calculate TTL in seconds
+        let ttl_seconds = (create_req.expires_at - Utc::now()).num_seconds();
+        if ttl_seconds <= 0 {
+            return Err(anyhow::anyhow!("Session expiry time is in the past"));
+        }
 
-        // Clean up expired admin sessions
-        conn.zrembyscore::<_, _, _, ()>(admin_sessions_key, 0, now)
+        // Set session data with expiration
+        conn.set_ex::<_, _, ()>(&session_key, session_data, ttl_seconds as u64)
             .await?;
 
-        // Get active admin session IDs
-        let session_ids: Vec<String> = conn.zrange(admin_sessions_key, 0, -1).await?;
+        // Add to user's active sessions set
+        let user_sessions_key = format!("user_sessions:{}", create_req.user_id);
+        conn.sadd::<_, _, ()>(&user_sessions_key, &session_id)
+            .await?;
+        conn.expire::<_, ()>(&user_sessions_key, ttl_seconds)
+            .await?;
 
-        // Filter by admin level if specified
-        if let Some(filter_level) = admin_level_filter {
-            let mut filtered_sessions = Vec::new();
+        // Track session in global active sessions
+        let active_sessions_key = "active_sessions";
+        conn.zadd::<_, _, _, ()>(active_sessions_key, &session_id, ttl_seconds)
+            .await?;
 
-            for session_id in session_ids {
-                if let Some(session) = self.get_session(&session_id).await? {
-                    if let Some(session_admin_level) = &session.admin_level {
-                        if session_admin_level == &filter_level {
-                            filtered_sessions.push(session_id);
-                        }
-                    }
-                }
-            }
+        // Track admin session separately
+        self.track_admin_session(&session_id, &admin_level, create_req.expires_at)
+            .await?;
 
-            return Ok(filtered_sessions);
-        }
+        Ok(session)
+    }
 
-        Ok(session_ids)
+    async fn get_session(&self, session_id: &str) -> Result<Option<Session>> {
+        self.with_retry("get_session", || self.get_session_once(session_id))
+            .await
     }
 
-    // Track admin session in separate sorted set
-    pub async fn track_admin_session(
-        &self,
-        session_id: &str,
-        admin_level: &AdminLevel,
-        expires_at: DateTime<Utc>,
-    ) -> Result<()> {
+    async fn update_session_activity(&self, session_id: &str) -> Result<()> {
         let mut conn = self.get_connection().await?;
-        let admin_sessions_key = "admin_sessions";
-        let level_sessions_key = format!("admin_sessions:{:?}", admin_level);
+        let session_key = format!("session:{}", session_id);
 
-        let expiry_timestamp = expires_at.timestamp();
+        // Get current session
+        if let Some(mut session) = self.get_session(session_id).await? {
+            session.last_accessed = Utc::now();
 
-        // Add to general admin sessions
-        conn.zadd::<_, _, _, ()>(admin_sessions_key, session_id, expiry_timestamp)
-            .await?;
+            let session_data = serde_json::to_string(&session)?;
+            let ttl_seconds = (session.expires_at - Utc::now()).num_seconds();
 
-        // Add to level-specific admin sessions
-        conn.zadd::<_, _, _, ()>(&level_sessions_key, session_id, expiry_timestamp)
-            .await?;
+            if ttl_seconds > 0 {
+                conn.set_ex::<_, _, ()>(&session_key, session_data, ttl_seconds as u64)
+                    .await?;
+            }
+        }
 
         Ok(())
     }
 
-    // Remove admin session tracking
-    pub async fn untrack_admin_session(
-        &self,
-        session_id: &str,
-        admin_level: &AdminLevel,
-    ) -> Result<()> {
+    async fn delete_session(&self, session_id: &str) -> Result<()> {
         let mut conn = self.get_connection().await?;
-        let admin_sessions_key = "admin_sessions";
-        let level_sessions_key = format!("admin_sessions:{:?}", admin_level);
 
-        // Remove from general admin sessions
-        conn.zrem::<_, _, ()>(admin_sessions_key, session_id)
-            .await?;
+        // Get session to find user_id and admin_level for cleanup
+        if let Some(session) = self.get_session(session_id).await? {
+            let user_sessions_key = format!("user_sessions:{}", session.user_id);
+            conn.srem::<_, _, ()>(&user_sessions_key, session_id)
+                .await?;
 
-        // Remove from level-specific admin sessions
-        conn.zrem::<_, _, ()>(&level_sessions_key, session_id)
+            // Remove admin session tracking if it's an admin session
+            if let Some(admin_level) = &session.admin_level {
+                self.untrack_admin_session(session_id, admin_level).await?;
+            }
+
+            // Log session deletion activity
+            self.add_session_activity(
+                session_id,
+                SessionActivityType::Logout,
+                Some("Session deleted".to_string()),
+                None,
+                None,
+            )
+            .await
+            .unwrap_or_default(); // Ignore errors since session is being deleted
+        }
+
+        // Remove session data
+        let session_key = format!("session:{}", session_id);
+        conn.del::<_, ()>(&session_key).await?;
+
+        // Remove from active sessions
+        let active_sessions_key = "active_sessions";
+        conn.zrem::<_, _, ()>(active_sessions_key, session_id)
             .await?;
 
         Ok(())
     }
 
-    // Get session count by admin level
-    pub async fn get_admin_session_count_by_level(
-        &self,
-        admin_level: &AdminLevel,
-    ) -> Result<usize> {
+    async fn delete_user_sessions(&self, user_id: Uuid) -> Result<Vec<String>> {
         let mut conn = self.get_connection().await?;
-        let level_sessions_key = format!("admin_sessions:{:?}", admin_level);
+        let user_sessions_key = format!("user_sessions:{}", user_id);
 
-        let now = Utc::now().timestamp();
+        // Get all user sessions
+        let session_ids: Vec<String> = conn.smembers(&user_sessions_key).await?;
 
-        // Count only non-expired sessions
-        let count: usize = conn.zcount(&level_sessions_key, now + 1, "+inf").await?;
+        // Delete each session
+        for session_id in &session_ids {
+            Box::pin(self.delete_session(session_id)).await?;
+        }
 
-        Ok(count)
+        // Clear user sessions set
+        conn.del::<_, ()>(&user_sessions_key).await?;
+
+        Ok(session_ids)
     }
 
-    // Batch revoke sessions
-    pub async fn batch_revoke_sessions(
+    async fn extend_session(
         &self,
-        request: BatchSessionRevocationRequest,
-    ) -> Result<BatchSessionRevocationResponse> {
-        let mut revoked_sessions = Vec::new();
-        let mut failed_sessions = Vec::new();
-        let mut errors = Vec::new();
-
-        for session_id in &request.session_ids {
-            match self
-                .revoke_session(session_id, request.reason.clone())
-                .await
-            {
-                Ok(true) => revoked_sessions.push(session_id.clone()),
-                Ok(false) => {
-                    failed_sessions.push(session_id.clone());
-                    errors.push(format!("Session {} not found", session_id));
-                }
-                Err(e) => {
-                    failed_sessions.push(session_id.clone());
-                    errors.push(format!("Failed to revoke session {}: {}", session_id, e));
-                }
+        session_id: &str,
+        new_expiry: DateTime<Utc>,
+    ) -> Result<bool> {
+        let mut conn = self.get_connection().await?;
+        let session_key = format!("session:{}", session_id);
+
+        if let Some(mut session) = self.get_session(session_id).await? {
+            session.expires_at = new_expiry;
+            session.last_accessed = Utc::now();
+
+            let session_data = serde_json::to_string(&session)?;
+            let ttl_seconds = (new_expiry - Utc::now()).num_seconds();
+
+            if ttl_seconds > 0 {
+                conn.set_ex::<_, _, ()>(&session_key, session_data, ttl_seconds as u64)
+                    .await?;
+
+                // Update user sessions expiry
+                let user_sessions_key = format!("user_sessions:{}", session.user_id);
+                conn.expire::<_, ()>(&user_sessions_key, ttl_seconds)
+                    .await?;
+
+                // Update active sessions score
+                let active_sessions_key = "active_sessions";
+                conn.zadd::<_, _, _, ()>(active_sessions_key, session_id, ttl_seconds)
+                    .await?;
+
+                return Ok(true);
             }
         }
 
-        let total_revoked = revoked_sessions.len();
-        let success = failed_sessions.is_empty();
-
-        Ok(BatchSessionRevocationResponse {
-            success,
-            revoked_sessions,
-            failed_sessions,
-            total_revoked,
-            errors,
-        })
+        Ok(false)
     }
 
-    // Force logout all sessions for a user
-    pub async fn force_logout_user(&self, request: ForceLogoutUserRequest) -> Result<Vec<String>> {
-        let user_sessions = self.get_user_sessions(request.user_id).await?;
-        let mut revoked_sessions = Vec::new();
+    async fn get_user_sessions(&self, user_id: Uuid) -> Result<Vec<Session>> {
+        let mut conn = self.get_connection().await?;
+        let user_sessions_key = format!("user_sessions:{}", user_id);
 
-        for session in user_sessions {
-            // Skip current session if requested
-            if request.exclude_current_session {
-                // This would need the current session ID to be passed in the request
-                // For now, we'll revoke all sessions
-            }
+        let session_ids: Vec<String> = conn.smembers(&user_sessions_key).await?;
+        let mut sessions = Vec::new();
 
-            if self
-                .revoke_session(&session.id, request.reason.clone())
-                .await?
-            {
-                revoked_sessions.push(session.id);
+        for session_id in session_ids {
+            if let Some(session) = self.get_session(&session_id).await? {
+                sessions.push(session);
             }
         }
 
-        Ok(revoked_sessions)
+        Ok(sessions)
     }
 
-    // Force logout all sessions for a faculty
-    pub async fn force_logout_faculty(
-        &self,
-        request: ForceLogoutFacultyRequest,
-    ) -> Result<Vec<String>> {
+    async fn get_active_sessions(&self, limit: Option<usize>) -> Result<Vec<String>> {
+        self.with_retry("get_active_sessions", || self.get_active_sessions_once(limit))
+            .await
+    }
+
+    async fn cleanup_expired_sessions(&self) -> Result<usize> {
         let mut conn = self.get_connection().await?;
         let active_sessions_key = "active_sessions";
 
         let now = Utc::now().timestamp();
 
-        // Get all active session IDs
-        let session_ids: Vec<String> = conn
-            .zrangebyscore(active_sessions_key, now + 1, "+inf")
-            .await?;
-        let mut revoked_sessions = Vec::new();
+        // Get expired session IDs
+        let expired_sessions: Vec<String> = conn.zrangebyscore(active_sessions_key, 0, now).await?;
 
-        for session_id in session_ids {
-            if let Some(session) = self.get_session(&session_id).await? {
-                // Check if session belongs to the faculty
-                if session.faculty_id == Some(request.faculty_id) {
-                    // Check admin level filter if specified
-                    if let Some(filter_level) = &request.admin_level_filter {
-                        if let Some(session_level) = &session.admin_level {
-                            if session_level != filter_level {
-                                continue;
-                            }
-                        } else {
-                            continue; // Skip non-admin sessions if admin level filter is specified
-                        }
-                    }
+        let count = expired_sessions.len();
 
-                    if self
-                        .revoke_session(&session_id, request.reason.clone())
-                        .await?
-                    {
-                        revoked_sessions.push(session_id);
-                    }
-                }
-            }
+        // Clean up expired sessions
+        for session_id in expired_sessions {
+            Box::pin(self.delete_session(&session_id)).await?;
         }
 
-        Ok(revoked_sessions)
+        Ok(count)
+    }
+
+    async fn get_session_count(&self) -> Result<usize> {
+        let mut conn = self.get_connection().await?;
+        let active_sessions_key = "active_sessions";
+
+        let now = Utc::now().timestamp();
+
+        // Count only non-expired sessions
+        let count: usize = conn.zcount(active_sessions_key, now + 1, "+inf").await?;
+
+        Ok(count)
     }
 
     // Add session activity log
-    pub async fn add_session_activity(
+    async fn add_session_activity(
         &self,
         session_id: &str,
         activity_type: SessionActivityType,
@@ -709,125 +722,592 @@ impl RedisSessionStore {
         Ok(())
     }
 
-    // Add SSE connection to session
-    pub async fn add_sse_connection(
+    // Enhanced revoke session with admin tracking
+    async fn revoke_session_by_admin(
         &self,
         session_id: &str,
-        sse_connection_id: String,
-    ) -> Result<()> {
-        if let Some(mut session) = self.get_session(session_id).await? {
-            if !session.sse_connections.contains(&sse_connection_id) {
-                session.sse_connections.push(sse_connection_id);
+        reason: Option<String>,
+        revoked_by_admin_id: Option<Uuid>,
+    ) -> Result<bool> {
+        self.with_retry("revoke_session", || {
+            self.revoke_session_once(session_id, reason.clone(), revoked_by_admin_id)
+        })
+        .await
+    }
 
-                // Update session in Redis
-                let mut conn = self.get_connection().await?;
-                let session_key = format!("session:{}", session_id);
-                let session_data = serde_json::to_string(&session)?;
-                let ttl_seconds = (session.expires_at - Utc::now()).num_seconds();
+    // ========== ADMIN SESSION MANAGEMENT METHODS ==========
 
-                if ttl_seconds > 0 {
-                    conn.set_ex::<_, _, ()>(&session_key, session_data, ttl_seconds as u64)
-                        .await?;
-                }
+    // Get all admin sessions with detailed information
+    async fn get_admin_sessions(
+        &self,
+        admin_level_filter: Option<AdminLevel>,
+    ) -> Result<Vec<String>> {
+        let mut conn = self.get_connection().await?;
+        let admin_sessions_key = "admin_sessions";
 
-                // Log SSE connection activity
-                self.add_session_activity(
-                    session_id,
-                    SessionActivityType::SseConnected,
-                    Some("SSE connection established".to_string()),
-                    None,
-                    None,
-                )
-                .await?;
+        let now = Utc::now().timestamp();
+
+        // Clean up expired admin sessions
+        conn.zrembyscore::<_, _, _, ()>(admin_sessions_key, 0, now)
+            .await?;
+
+        // Get active admin session IDs
+        let session_ids: Vec<String> = conn.zrange(admin_sessions_key, 0, -1).await?;
+
+        // Filter by admin level if specified
+        if let Some(filter_level) = admin_level_filter {
+            let mut filtered_sessions = Vec::new();
+
+            for session_id in session_ids {
+                if let Some(session) = self.get_session(&session_id).await? {
+                    if let Some(session_admin_level) = &session.admin_level {
+                        if session_admin_level == &filter_level {
+                            filtered_sessions.push(session_id);
+                        }
+                    }
+                }
             }
+
+            return Ok(filtered_sessions);
         }
 
-        Ok(())
+        Ok(session_ids)
     }
 
-    // Remove SSE connection from session
-    pub async fn remove_sse_connection(
+    // Get session count by admin level
+    async fn get_admin_session_count_by_level(
         &self,
-        session_id: &str,
-        sse_connection_id: &str,
-    ) -> Result<()> {
-        if let Some(mut session) = self.get_session(session_id).await? {
-            session.sse_connections.retain(|id| id != sse_connection_id);
+        admin_level: &AdminLevel,
+    ) -> Result<usize> {
+        let mut conn = self.get_connection().await?;
+        let level_sessions_key = format!("admin_sessions:{:?}", admin_level);
 
-            // Update session in Redis
-            let mut conn = self.get_connection().await?;
-            let session_key = format!("session:{}", session_id);
-            let session_data = serde_json::to_string(&session)?;
-            let ttl_seconds = (session.expires_at - Utc::now()).num_seconds();
+        let now = Utc::now().timestamp();
 
-            if ttl_seconds > 0 {
-                conn.set_ex::<_, _, ()>(&session_key, session_data, ttl_seconds as u64)
-                    .await?;
-            }
+        // Count only non-expired sessions
+        let count: usize = conn.zcount(&level_sessions_key, now + 1, "+inf").await?;
 
-            // Log SSE disconnection activity
-            self.add_session_activity(
-                session_id,
-                SessionActivityType::SseDisconnected,
-                Some("SSE connection closed".to_string()),
-                None,
-                None,
-            )
+        Ok(count)
+    }
+
+    // QR scan debounce: record a scan for (activity, user) unless one happened within the cooldown.
+    // Returns the previous scan time when the new scan falls inside the cooldown window.
+    async fn record_qr_scan(
+        &self,
+        activity_id: Uuid,
+        user_id: Uuid,
+        cooldown_seconds: u64,
+    ) -> Result<Option<DateTime<Utc>>> {
+        if cooldown_seconds == 0 {
+            return Ok(None);
+        }
+
+        let mut conn = self.get_connection().await?;
+        let scan_key = format!("qr_scan:{}:{}", activity_id, user_id);
+
+        let options = SetOptions::default()
+            .conditional_set(ExistenceCheck::NX)
+            .with_expiration(SetExpiry::EX(cooldown_seconds));
+        let recorded: Option<String> = conn
+            .set_options(&scan_key, Utc::now().timestamp(), options)
             .await?;
+
+        if recorded.is_some() {
+            return Ok(None);
+        }
+
+        let last_scan: Option<i64> = conn.get(&scan_key).await?;
+        Ok(Some(
+            last_scan
+                .and_then(|ts| DateTime::from_timestamp(ts, 0))
+                .unwrap_or_else(Utc::now),
+        ))
+    }
+
+    /// Remaining lockout (seconds) for a login identifier + IP pair, if locked
+    async fn get_login_lockout(&self, identifier: &str, ip_address: &str) -> Result<Option<u64>> {
+        let mut conn = self.get_connection().await?;
+        let lockout_key = login_lockout_key(identifier, ip_address);
+
+        let ttl: i64 = conn.ttl(&lockout_key).await?;
+        Ok(if ttl > 0 { Some(ttl as u64) } else { None })
+    }
+
+    /// Count a failed login attempt. Returns the lockout duration once the
+    /// attempt limit within the window has been reached.
+    async fn record_failed_login(
+        &self,
+        identifier: &str,
+        ip_address: &str,
+        max_attempts: u32,
+        window_seconds: u64,
+        lockout_seconds: u64,
+    ) -> Result<Option<u64>> {
+        if max_attempts == 0 {
+            return Ok(None);
+        }
+
+        let mut conn = self.get_connection().await?;
+        let attempts_key = login_attempts_key(identifier, ip_address);
+
+        let attempts: u32 = conn.incr(&attempts_key, 1).await?;
+        if attempts == 1 {
+            let _: () = conn.expire(&attempts_key, window_seconds as i64).await?;
+        }
+
+        if attempts < max_attempts {
+            return Ok(None);
         }
 
+        let lockout_key = login_lockout_key(identifier, ip_address);
+        let _: () = conn
+            .set_ex(&lockout_key, Utc::now().timestamp(), lockout_seconds)
+            .await?;
+        let _: () = conn.del(&attempts_key).await?;
+
+        Ok(Some(lockout_seconds))
+    }
+
+    /// Reset failed login counter and lockout after a successful login
+    async fn clear_failed_logins(&self, identifier: &str, ip_address: &str) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+        let _: () = conn
+            .del(&[
+                login_attempts_key(identifier, ip_address),
+                login_lockout_key(identifier, ip_address),
+            ])
+            .await?;
         Ok(())
     }
 
-    // Get sessions with active SSE connections
-    pub async fn get_sessions_with_sse(&self) -> Result<Vec<String>> {
+    /// Store a single-use password reset token. Only the token hash is kept in Redis.
+    async fn create_password_reset_token(&self, user_id: Uuid, ttl_seconds: u64) -> Result<String> {
         let mut conn = self.get_connection().await?;
-        let active_sessions_key = "active_sessions";
+        let token = generate_secret_key();
 
-        let now = Utc::now().timestamp();
-        let session_ids: Vec<String> = conn
-            .zrangebyscore(active_sessions_key, now + 1, "+inf")
+        let _: () = conn
+            .set_ex(password_reset_key(&token), user_id.to_string(), ttl_seconds)
             .await?;
-        let mut sessions_with_sse = Vec::new();
 
-        for session_id in session_ids {
-            if let Some(session) = self.get_session(&session_id).await? {
-                if !session.sse_connections.is_empty() {
-                    sessions_with_sse.push(session_id);
-                }
+        Ok(token)
+    }
+
+    /// Consume a password reset token, returning the user it was issued for
+    async fn consume_password_reset_token(&self, token: &str) -> Result<Option<Uuid>> {
+        let mut conn = self.get_connection().await?;
+
+        let user_id: Option<String> = conn.get_del(password_reset_key(token)).await?;
+        Ok(user_id.and_then(|id| Uuid::parse_str(&id).ok()))
+    }
+
+    async fn create_device_verification_token(
+        &self,
+        user_id: Uuid,
+        device_hash: &str,
+        ttl_seconds: u64,
+    ) -> Result<String> {
+        let mut conn = self.get_connection().await?;
+        let token = generate_secret_key();
+
+        let _: () = conn
+            .set_ex(
+                device_verification_key(&token),
+                format!("{}:{}", user_id, device_hash),
+                ttl_seconds,
+            )
+            .await?;
+
+        Ok(token)
+    }
+
+    async fn consume_device_verification_token(&self, token: &str) -> Result<Option<(Uuid, String)>> {
+        let mut conn = self.get_connection().await?;
+
+        let value: Option<String> = conn.get_del(device_verification_key(token)).await?;
+        Ok(value.and_then(|value| {
+            let (user_id, device_hash) = value.split_once(':')?;
+            Some((Uuid::parse_str(user_id).ok()?, device_hash.to_string()))
+        }))
+    }
+
+    async fn create_two_factor_challenge(&self, pending_login: &Value, ttl_seconds: u64) -> Result<String> {
+        let mut conn = self.get_connection().await?;
+        let token = generate_secret_key();
+
+        let _: () = conn
+            .set_ex(
+                two_factor_challenge_key(&token),
+                serde_json::to_string(pending_login)?,
+                ttl_seconds,
+            )
+            .await?;
+
+        Ok(token)
+    }
+
+    async fn consume_two_factor_challenge(&self, token: &str) -> Result<Option<Value>> {
+        let mut conn = self.get_connection().await?;
+
+        let value: Option<String> = conn.get_del(two_factor_challenge_key(token)).await?;
+        Ok(value.and_then(|raw| serde_json::from_str(&raw).ok()))
+    }
+
+    /// Read a response cached with `set_cached_json`; unparseable entries count as a miss
+    async fn get_cached_json(&self, key: &str) -> Result<Option<Value>> {
+        let mut conn = self.get_connection().await?;
+
+        let cached: Option<String> = conn.get(cache_key(key)).await?;
+        Ok(cached.and_then(|raw| serde_json::from_str(&raw).ok()))
+    }
+
+    /// Cache an expensive, read-heavy response for `ttl_seconds`
+    async fn set_cached_json(&self, key: &str, value: &Value, ttl_seconds: u64) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+        let _: () = conn
+            .set_ex(cache_key(key), serde_json::to_string(value)?, ttl_seconds)
+            .await?;
+        Ok(())
+    }
+
+    /// Drop every cached response whose key starts with `prefix`; returns how many were removed
+    async fn invalidate_cached_prefix(&self, prefix: &str) -> Result<usize> {
+        let mut conn = self.get_connection().await?;
+
+        let keys: Vec<String> = {
+            let mut iter = conn.scan_match::<_, String>(format!("{}*", cache_key(prefix))).await?;
+            let mut keys = Vec::new();
+            while let Some(key) = iter.next_item().await {
+                keys.push(key);
             }
-        }
+            keys
+        };
 
-        Ok(sessions_with_sse)
+        if !keys.is_empty() {
+            let _: () = conn.del(&keys).await?;
+        }
+        Ok(keys.len())
     }
 }
 
+/// Connection-level failures worth another attempt; anything else is a real answer
+fn is_retryable(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<redis::RedisError>().is_some_and(|e| {
+        e.is_io_error()
+            || matches!(
+                e.kind(),
+                ErrorKind::TryAgain | ErrorKind::BusyLoadingError | ErrorKind::MasterDown | ErrorKind::ClusterDown
+            )
+    })
+}
+
+fn retry_delay(attempt: u32) -> std::time::Duration {
+    let base = RETRY_BASE_DELAY_MS << (attempt - 1).min(6);
+    let jitter = rand::thread_rng().gen_range(0..=base / 2);
+    std::time::Duration::from_millis(base + jitter)
+}
+
+fn cache_key(key: &str) -> String {
+    format!("cache:{}", key)
+}
+
+fn password_reset_key(token: &str) -> String {
+    format!("password_reset:{}", hex::encode(Sha256::digest(token.as_bytes())))
+}
+
+fn device_verification_key(token: &str) -> String {
+    format!("device_verification:{}", hex::encode(Sha256::digest(token.as_bytes())))
+}
+
+fn two_factor_challenge_key(token: &str) -> String {
+    format!("two_factor_challenge:{}", hex::encode(Sha256::digest(token.as_bytes())))
+}
+
+fn login_attempts_key(identifier: &str, ip_address: &str) -> String {
+    format!("login_attempts:{}:{}", identifier.trim().to_lowercase(), ip_address)
+}
+
+fn login_lockout_key(identifier: &str, ip_address: &str) -> String {
+    format!("login_lockout:{}:{}", identifier.trim().to_lowercase(), ip_address)
+}
+
 // Session configuration constants
 #[derive(Clone)]
 pub struct SessionConfig {
+    /// Session (and cookie) lifetime for logins without `remember_me` (SESSION_EXPIRY_HOURS)
     pub default_expiry_hours: i64,
     pub max_sessions_per_user: usize,
+    /// Session (and cookie) lifetime for `remember_me` logins (REMEMBER_ME_EXPIRY_DAYS)
     pub remember_me_expiry_days: i64,
     pub cleanup_interval_minutes: i64,
+    pub scan_cooldown_seconds: u64,
+    /// Sessions unused for longer than this expire even if `expires_at` is later
+    pub idle_timeout: Duration,
+    /// Hard cap on a session's lifetime from `created_at`, however often it is used or extended
+    pub absolute_timeout: Duration,
+    /// `/api/auth/refresh` only extends sessions this close to `expires_at`
+    pub refresh_window: Duration,
+    /// Per-role lifetimes that replace both of the defaults above
+    pub role_ttls: RoleSessionTtls,
+    /// Reject and revoke a session used from another IP than its login IP (SESSION_BIND_TO_IP)
+    pub bind_session_to_ip: bool,
+    /// Prefix lengths that count as "the same IP" for the binding, so a phone moving inside its
+    /// carrier's subnet keeps the session (SESSION_IP_BINDING_PREFIX_V4 / _V6; 32 / 128 = exact)
+    pub ip_binding_prefix_v4: u8,
+    pub ip_binding_prefix_v6: u8,
+}
+
+/// Session lifetime overrides by role (SESSION_TTL_HOURS_<ROLE>). An override applies with or
+/// without `remember_me`, so a short admin policy can't be bypassed by ticking the box.
+#[derive(Debug, Clone, Default)]
+pub struct RoleSessionTtls {
+    pub student: Option<Duration>,
+    pub regular_admin: Option<Duration>,
+    pub faculty_admin: Option<Duration>,
+    pub super_admin: Option<Duration>,
+}
+
+impl RoleSessionTtls {
+    /// Override for a user with `admin_level`, or for a student when `None`
+    pub fn for_role(&self, admin_level: Option<&AdminLevel>) -> Option<Duration> {
+        match admin_level {
+            None => self.student,
+            Some(AdminLevel::RegularAdmin) => self.regular_admin,
+            Some(AdminLevel::FacultyAdmin) => self.faculty_admin,
+            Some(AdminLevel::SuperAdmin) => self.super_admin,
+        }
+    }
 }
 
 impl Default for SessionConfig {
     fn default() -> Self {
         Self {
-            default_expiry_hours: 24,     // 24 hours default for inactivity timeout
+            default_expiry_hours: 24,     // 24 hours without remember me
             max_sessions_per_user: 5,     // Max 5 concurrent sessions
             remember_me_expiry_days: 30,  // 30 days for remember me
             cleanup_interval_minutes: 720, // Cleanup every 12 hours (720 minutes)
+            scan_cooldown_seconds: 10,    // Ignore repeated scans of the same QR within 10 seconds
+            idle_timeout: Duration::hours(24),
+            absolute_timeout: Duration::days(30),
+            refresh_window: Duration::minutes(60),
+            role_ttls: RoleSessionTtls::default(),
+            bind_session_to_ip: false,
+            ip_binding_prefix_v4: 32,
+            ip_binding_prefix_v6: 128,
         }
     }
 }
 
 impl SessionConfig {
-    pub fn get_session_expiry(&self, remember_me: bool) -> DateTime<Utc> {
-        if remember_me {
-            Utc::now() + Duration::days(self.remember_me_expiry_days)
-        } else {
-            Utc::now() + Duration::hours(self.default_expiry_hours)
+    /// Session settings from the environment, see `Config::from_env`
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            default_expiry_hours: config.session_expiry_hours,
+            remember_me_expiry_days: config.remember_me_expiry_days,
+            scan_cooldown_seconds: config.scan_cooldown_seconds,
+            idle_timeout: Duration::minutes(config.session_idle_timeout_minutes),
+            absolute_timeout: Duration::hours(config.session_absolute_timeout_hours),
+            refresh_window: Duration::minutes(config.session_refresh_window_minutes),
+            role_ttls: RoleSessionTtls {
+                student: config.session_ttl_hours_student.map(Duration::hours),
+                regular_admin: config.session_ttl_hours_regular_admin.map(Duration::hours),
+                faculty_admin: config.session_ttl_hours_faculty_admin.map(Duration::hours),
+                super_admin: config.session_ttl_hours_super_admin.map(Duration::hours),
+            },
+            bind_session_to_ip: config.session_bind_to_ip,
+            ip_binding_prefix_v4: config.session_ip_binding_prefix_v4,
+            ip_binding_prefix_v6: config.session_ip_binding_prefix_v6,
+            ..Self::default()
+        }
+    }
+
+    /// Lifetime of a new session for the role (`None` = student); the role override wins
+    pub fn session_ttl(&self, admin_level: Option<&AdminLevel>, remember_me: bool) -> Duration {
+        self.role_ttls.for_role(admin_level).unwrap_or_else(|| {
+            if remember_me {
+                Duration::days(self.remember_me_expiry_days)
+            } else {
+                Duration::hours(self.default_expiry_hours)
+            }
+        })
+    }
+
+    pub fn get_session_expiry(&self, admin_level: Option<&AdminLevel>, remember_me: bool) -> DateTime<Utc> {
+        let now = Utc::now();
+        (now + self.session_ttl(admin_level, remember_me)).min(self.absolute_expiry(now))
+    }
+
+    /// Expiry for extending a session by `requested` from `now`: never longer than the role's
+    /// override nor past the absolute lifetime
+    pub fn extended_expiry(
+        &self,
+        admin_level: Option<&AdminLevel>,
+        requested: Duration,
+        created_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> DateTime<Utc> {
+        let ttl = match self.role_ttls.for_role(admin_level) {
+            Some(max) => requested.min(max),
+            None => requested,
+        };
+        (now + ttl).min(self.absolute_expiry(created_at))
+    }
+
+    /// Latest `expires_at` a session created at `created_at` may ever have
+    pub fn absolute_expiry(&self, created_at: DateTime<Utc>) -> DateTime<Utc> {
+        created_at + self.absolute_timeout
+    }
+
+    /// New `expires_at` for a refresh: `None` while the session is outside the refresh window
+    /// or when the absolute cap leaves nothing to extend
+    pub fn refreshed_expiry(
+        &self,
+        admin_level: Option<&AdminLevel>,
+        expires_at: DateTime<Utc>,
+        created_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        if expires_at - now > self.refresh_window {
+            return None;
+        }
+        let new_expiry = (now + self.session_ttl(admin_level, false)).min(self.absolute_expiry(created_at));
+        (new_expiry > expires_at).then_some(new_expiry)
+    }
+
+    pub fn is_idle(&self, last_accessed: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        now - last_accessed > self.idle_timeout
+    }
+
+    /// Whether a request from `client_ip` may use a session created from `bound_ip`. Always true
+    /// with binding off or when no login IP was recorded; a missing or unparsable client IP,
+    /// or one from the other address family, never matches.
+    pub fn ip_binding_allows(&self, bound_ip: Option<&str>, client_ip: Option<&str>) -> bool {
+        if !self.bind_session_to_ip {
+            return true;
+        }
+        let Some(bound) = bound_ip.and_then(|ip| ip.parse::<IpAddr>().ok()) else {
+            return true;
+        };
+        let Some(client) = client_ip.and_then(|ip| ip.parse::<IpAddr>().ok()) else {
+            return false;
+        };
+
+        match (bound.to_canonical(), client.to_canonical()) {
+            (IpAddr::V4(bound), IpAddr::V4(client)) => {
+                let mask = u32::MAX.checked_shl(32 - u32::from(self.ip_binding_prefix_v4)).unwrap_or(0);
+                u32::from(bound) & mask == u32::from(client) & mask
+            }
+            (IpAddr::V6(bound), IpAddr::V6(client)) => {
+                let mask = u128::MAX.checked_shl(128 - u32::from(self.ip_binding_prefix_v6)).unwrap_or(0);
+                u128::from(bound) & mask == u128::from(client) & mask
+            }
+            _ => false,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn test_ip_binding_allows_same_subnet_only() {
+        let exact = SessionConfig {
+            bind_session_to_ip: true,
+            ip_binding_prefix_v4: 32,
+            ip_binding_prefix_v6: 128,
+            ..SessionConfig::default()
+        };
+        let subnet = SessionConfig {
+            ip_binding_prefix_v4: 24,
+            ip_binding_prefix_v6: 64,
+            ..exact.clone()
+        };
+        let off = SessionConfig {
+            bind_session_to_ip: false,
+            ..exact.clone()
+        };
+
+        assert!(exact.ip_binding_allows(Some("203.0.113.5"), Some("203.0.113.5")));
+        assert!(!exact.ip_binding_allows(Some("203.0.113.5"), Some("203.0.113.9")));
+        assert!(subnet.ip_binding_allows(Some("203.0.113.5"), Some("203.0.113.9")));
+        assert!(!subnet.ip_binding_allows(Some("203.0.113.5"), Some("203.0.114.5")));
+        assert!(subnet.ip_binding_allows(Some("2001:db8::1"), Some("2001:db8::ffff:1")));
+        assert!(!subnet.ip_binding_allows(Some("2001:db8::1"), Some("2001:db9::1")));
+        assert!(exact.ip_binding_allows(Some("203.0.113.5"), Some("::ffff:203.0.113.5")));
+        assert!(!exact.ip_binding_allows(Some("203.0.113.5"), Some("2001:db8::1")));
+        assert!(!exact.ip_binding_allows(Some("203.0.113.5"), None));
+        assert!(exact.ip_binding_allows(None, Some("203.0.113.5")));
+        assert!(off.ip_binding_allows(Some("203.0.113.5"), Some("198.51.100.1")));
+    }
+
+    #[test]
+    fn test_session_config_from_config() {
+        let mut config = Config::from_env().unwrap();
+        config.session_expiry_hours = 8;
+        config.scan_cooldown_seconds = 3;
+        config.session_ttl_hours_super_admin = Some(2);
+        config.session_bind_to_ip = true;
+        let session_config = SessionConfig::from_config(&config);
+
+        assert_eq!(session_config.default_expiry_hours, 8);
+        assert_eq!(session_config.scan_cooldown_seconds, 3);
+        assert_eq!(session_config.role_ttls.for_role(Some(&AdminLevel::SuperAdmin)), Some(Duration::hours(2)));
+        assert_eq!(session_config.role_ttls.for_role(None), config.session_ttl_hours_student.map(Duration::hours));
+        assert!(session_config.bind_session_to_ip);
+        assert_eq!(session_config.max_sessions_per_user, SessionConfig::default().max_sessions_per_user);
+    }
+
+    #[test]
+    fn test_only_connection_errors_are_retryable() {
+        let io: anyhow::Error =
+            redis::RedisError::from(std::io::Error::from(std::io::ErrorKind::ConnectionReset)).into();
+        let loading: anyhow::Error =
+            redis::RedisError::from((ErrorKind::BusyLoadingError, "loading")).into();
+        let wrong_type: anyhow::Error = redis::RedisError::from((ErrorKind::TypeError, "wrong type")).into();
+        let decode: anyhow::Error = serde_json::from_str::<Session>("{").unwrap_err().into();
+
+        assert!(is_retryable(&io));
+        assert!(is_retryable(&loading));
+        assert!(!is_retryable(&wrong_type));
+        assert!(!is_retryable(&decode));
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off_with_bounded_jitter() {
+        for attempt in 1..=3 {
+            let base = RETRY_BASE_DELAY_MS << (attempt - 1);
+            let delay = retry_delay(attempt).as_millis() as u64;
+            assert!((base..=base + base / 2).contains(&delay));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_stops_at_configured_attempts() {
+        // Nothing listens on port 1, so every connection attempt is refused
+        let store = RedisSessionStore::new("redis://127.0.0.1:1/")
+            .unwrap()
+            .with_retry_attempts(2);
+        let calls = AtomicU32::new(0);
+
+        let result = store
+            .with_retry("test", || async {
+                calls.fetch_add(1, Ordering::Relaxed);
+                store.get_connection().await.map(|_| ())
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+
+        // Logical errors are returned on the first attempt
+        calls.store(0, Ordering::Relaxed);
+        let result: Result<()> = store
+            .with_retry("test", || async {
+                calls.fetch_add(1, Ordering::Relaxed);
+                Err(anyhow::anyhow!("Session expiry time is in the past"))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+    }
+}
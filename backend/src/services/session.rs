@@ -5,6 +5,7 @@ use serde_json;
 use uuid::Uuid;
 
 use crate::models::{CreateSession, Session};
+use crate::services::geoip::lookup_ip;
 
 pub struct SessionService {
     redis: redis::aio::ConnectionManager,
@@ -44,6 +45,9 @@ impl SessionService {
                 ip_address: data.ip_address.clone(),
                 user_agent: data.user_agent.clone(),
             }],
+            impersonated_by: data.impersonated_by,
+            impersonator_session_id: data.impersonator_session_id.clone(),
+            geo_location: lookup_ip(data.ip_address.as_deref()),
         };
 
         let session_json = serde_json::to_string(&session)?;
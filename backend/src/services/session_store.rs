@@ -0,0 +1,239 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::models::admin_role::AdminLevel;
+use crate::models::session::{
+    BatchSessionRevocationRequest, BatchSessionRevocationResponse, CreateSession, ForceLogoutFacultyRequest,
+    ForceLogoutUserRequest, LoginMethod, Session, SessionActivityType, SessionType,
+};
+
+/// Storage backend behind `SessionState`: sessions plus the short-lived keys that live
+/// next to them (login lockouts, QR scan debounce, password reset tokens, response cache).
+///
+/// `RedisSessionStore` is the production implementation; tests can use the in-memory
+/// `MemorySessionStore` instead of a live Redis.
+#[async_trait]
+pub trait SessionStore: Send + Sync {
+    /// Round-trip to the backend, used by the readiness probe
+    async fn ping(&self) -> Result<()>;
+
+    // ========== SESSIONS ==========
+
+    async fn create_session(&self, create_req: CreateSession) -> Result<Session>;
+
+    async fn create_admin_session(
+        &self,
+        create_req: CreateSession,
+        session_type: SessionType,
+        admin_level: AdminLevel,
+        faculty_id: Option<Uuid>,
+        permissions: Vec<String>,
+        login_method: LoginMethod,
+    ) -> Result<Session>;
+
+    /// Expired sessions are cleaned up and reported as `None`; revoked ones are still returned
+    async fn get_session(&self, session_id: &str) -> Result<Option<Session>>;
+
+    async fn update_session_activity(&self, session_id: &str) -> Result<()>;
+
+    async fn delete_session(&self, session_id: &str) -> Result<()>;
+
+    /// Delete every session of a user, returning the deleted session ids
+    async fn delete_user_sessions(&self, user_id: Uuid) -> Result<Vec<String>>;
+
+    /// Returns `false` when the session doesn't exist or `new_expiry` is already past
+    async fn extend_session(&self, session_id: &str, new_expiry: DateTime<Utc>) -> Result<bool>;
+
+    async fn get_user_sessions(&self, user_id: Uuid) -> Result<Vec<Session>>;
+
+    async fn get_active_sessions(&self, limit: Option<usize>) -> Result<Vec<String>>;
+
+    async fn cleanup_expired_sessions(&self) -> Result<usize>;
+
+    async fn get_session_count(&self) -> Result<usize>;
+
+    async fn add_session_activity(
+        &self,
+        session_id: &str,
+        activity_type: SessionActivityType,
+        details: Option<String>,
+        ip_address: Option<String>,
+        user_agent: Option<String>,
+    ) -> Result<()>;
+
+    // ========== REVOCATION ==========
+
+    /// Mark a session inactive but keep it until expiry for auditing; `false` if not found
+    async fn revoke_session_by_admin(
+        &self,
+        session_id: &str,
+        reason: Option<String>,
+        revoked_by_admin_id: Option<Uuid>,
+    ) -> Result<bool>;
+
+    async fn revoke_session(&self, session_id: &str, reason: Option<String>) -> Result<bool> {
+        self.revoke_session_by_admin(session_id, reason, None).await
+    }
+
+    async fn batch_revoke_sessions(
+        &self,
+        request: BatchSessionRevocationRequest,
+    ) -> Result<BatchSessionRevocationResponse> {
+        let mut revoked_sessions = Vec::new();
+        let mut failed_sessions = Vec::new();
+        let mut errors = Vec::new();
+
+        for session_id in &request.session_ids {
+            match self
+                .revoke_session(session_id, request.reason.clone())
+                .await
+            {
+                Ok(true) => revoked_sessions.push(session_id.clone()),
+                Ok(false) => {
+                    failed_sessions.push(session_id.clone());
+                    errors.push(format!("Session {} not found", session_id));
+                }
+                Err(e) => {
+                    failed_sessions.push(session_id.clone());
+                    errors.push(format!("Failed to revoke session {}: {}", session_id, e));
+                }
+            }
+        }
+
+        let total_revoked = revoked_sessions.len();
+        let success = failed_sessions.is_empty();
+
+        Ok(BatchSessionRevocationResponse {
+            success,
+            revoked_sessions,
+            failed_sessions,
+            total_revoked,
+            errors,
+        })
+    }
+
+    // Force logout all sessions for a user
+    async fn force_logout_user(&self, request: ForceLogoutUserRequest) -> Result<Vec<String>> {
+        let user_sessions = self.get_user_sessions(request.user_id).await?;
+        let mut revoked_sessions = Vec::new();
+
+        for session in user_sessions {
+            // Skip current session if requested
+            if request.exclude_current_session {
+                // This would need the current session ID to be passed in the request
+                // For now, we'll revoke all sessions
+            }
+
+            if self
+                .revoke_session(&session.id, request.reason.clone())
+                .await?
+            {
+                revoked_sessions.push(session.id);
+            }
+        }
+
+        Ok(revoked_sessions)
+    }
+
+    // Force logout all sessions for a faculty
+    async fn force_logout_faculty(&self, request: ForceLogoutFacultyRequest) -> Result<Vec<String>> {
+        let session_ids = self.get_active_sessions(None).await?;
+        let mut revoked_sessions = Vec::new();
+
+        for session_id in session_ids {
+            if let Some(session) = self.get_session(&session_id).await? {
+                // Check if session belongs to the faculty
+                if session.faculty_id != Some(request.faculty_id) {
+                    continue;
+                }
+
+                // Skip non-admin sessions if admin level filter is specified
+                if let Some(filter_level) = &request.admin_level_filter {
+                    if session.admin_level.as_ref() != Some(filter_level) {
+                        continue;
+                    }
+                }
+
+                if self
+                    .revoke_session(&session_id, request.reason.clone())
+                    .await?
+                {
+                    revoked_sessions.push(session_id);
+                }
+            }
+        }
+
+        Ok(revoked_sessions)
+    }
+
+    // ========== ADMIN SESSIONS ==========
+
+    async fn get_admin_sessions(&self, admin_level_filter: Option<AdminLevel>) -> Result<Vec<String>>;
+
+    async fn get_admin_session_count_by_level(&self, admin_level: &AdminLevel) -> Result<usize>;
+
+    // ========== SHORT-LIVED KEYS ==========
+
+    /// QR scan debounce: record a scan for (activity, user) unless one happened within the cooldown.
+    /// Returns the previous scan time when the new scan falls inside the cooldown window.
+    async fn record_qr_scan(
+        &self,
+        activity_id: Uuid,
+        user_id: Uuid,
+        cooldown_seconds: u64,
+    ) -> Result<Option<DateTime<Utc>>>;
+
+    /// Remaining lockout (seconds) for a login identifier + IP pair, if locked
+    async fn get_login_lockout(&self, identifier: &str, ip_address: &str) -> Result<Option<u64>>;
+
+    /// Count a failed login attempt. Returns the lockout duration once the
+    /// attempt limit within the window has been reached.
+    async fn record_failed_login(
+        &self,
+        identifier: &str,
+        ip_address: &str,
+        max_attempts: u32,
+        window_seconds: u64,
+        lockout_seconds: u64,
+    ) -> Result<Option<u64>>;
+
+    /// Reset failed login counter and lockout after a successful login
+    async fn clear_failed_logins(&self, identifier: &str, ip_address: &str) -> Result<()>;
+
+    /// Store a single-use password reset token; only its hash is kept
+    async fn create_password_reset_token(&self, user_id: Uuid, ttl_seconds: u64) -> Result<String>;
+
+    /// Consume a password reset token, returning the user it was issued for
+    async fn consume_password_reset_token(&self, token: &str) -> Result<Option<Uuid>>;
+
+    /// Store a single-use token that lets `user_id` log in from the device identified by
+    /// `device_hash` when new-device verification is on; only the token's hash is kept
+    async fn create_device_verification_token(
+        &self,
+        user_id: Uuid,
+        device_hash: &str,
+        ttl_seconds: u64,
+    ) -> Result<String>;
+
+    /// Consume a device verification token, returning the user and device it was issued for
+    async fn consume_device_verification_token(&self, token: &str) -> Result<Option<(Uuid, String)>>;
+
+    /// Store a short-lived, single-use two-factor login challenge holding the pending login;
+    /// only the token's hash is kept
+    async fn create_two_factor_challenge(&self, pending_login: &Value, ttl_seconds: u64) -> Result<String>;
+
+    /// Consume a two-factor challenge, returning the pending login it was issued for
+    async fn consume_two_factor_challenge(&self, token: &str) -> Result<Option<Value>>;
+
+    /// Read a response cached with `set_cached_json`; unparseable entries count as a miss
+    async fn get_cached_json(&self, key: &str) -> Result<Option<Value>>;
+
+    /// Cache an expensive, read-heavy response for `ttl_seconds`
+    async fn set_cached_json(&self, key: &str, value: &Value, ttl_seconds: u64) -> Result<()>;
+
+    /// Drop every cached response whose key starts with `prefix`; returns how many were removed
+    async fn invalidate_cached_prefix(&self, prefix: &str) -> Result<usize>;
+}
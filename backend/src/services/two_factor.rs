@@ -0,0 +1,270 @@
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::utils::totp::{generate_totp_secret, verify_totp};
+
+/// How long an admin has to enter their code after the password step
+pub const CHALLENGE_TTL_SECONDS: u64 = 300;
+/// Wrong codes allowed per login before the password has to be entered again
+pub const MAX_CHALLENGE_ATTEMPTS: u32 = 5;
+
+const RECOVERY_CODE_COUNT: usize = 10;
+/// No 0/o or 1/l/i so codes can be read back from paper
+const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+#[derive(Debug, FromRow)]
+pub struct TwoFactorRecord {
+    pub secret: String,
+    pub enabled_at: Option<DateTime<Utc>>,
+}
+
+/// Which kind of second factor a code matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecondFactor {
+    Totp,
+    RecoveryCode,
+}
+
+pub async fn get_two_factor(pool: &PgPool, user_id: Uuid) -> Result<Option<TwoFactorRecord>, sqlx::Error> {
+    sqlx::query_as::<_, TwoFactorRecord>(
+        "SELECT secret, enabled_at FROM admin_two_factor WHERE user_id = $1",
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn is_two_factor_enabled(pool: &PgPool, user_id: Uuid) -> Result<bool, sqlx::Error> {
+    Ok(get_two_factor(pool, user_id)
+        .await?
+        .is_some_and(|record| record.enabled_at.is_some()))
+}
+
+/// Start (or restart) enrollment with a fresh secret; `None` when 2FA is already enabled
+pub async fn begin_enrollment(pool: &PgPool, user_id: Uuid) -> Result<Option<String>, sqlx::Error> {
+    let secret = generate_totp_secret();
+    let stored: Option<String> = sqlx::query_scalar(
+        r#"
+        INSERT INTO admin_two_factor (user_id, secret) VALUES ($1, $2)
+        ON CONFLICT (user_id) DO UPDATE
+            SET secret = EXCLUDED.secret, last_used_step = NULL, updated_at = NOW()
+            WHERE admin_two_factor.enabled_at IS NULL
+        RETURNING secret
+        "#,
+    )
+    .bind(user_id)
+    .bind(&secret)
+    .fetch_optional(pool)
+    .await?;
+    Ok(stored)
+}
+
+/// Enable 2FA once the admin proves their app produces valid codes for the pending secret.
+/// Returns the new recovery codes, or `None` when there is no pending enrollment or the code is wrong.
+pub async fn confirm_enrollment(pool: &PgPool, user_id: Uuid, code: &str) -> Result<Option<Vec<String>>, sqlx::Error> {
+    let Some(record) = get_two_factor(pool, user_id).await? else {
+        return Ok(None);
+    };
+    if record.enabled_at.is_some() {
+        return Ok(None);
+    }
+    let Some(step) = verify_totp(&record.secret, code, Utc::now().timestamp()) else {
+        return Ok(None);
+    };
+
+    let mut tx = pool.begin().await?;
+    let enabled = sqlx::query(
+        r#"
+        UPDATE admin_two_factor SET enabled_at = NOW(), last_used_step = $2, updated_at = NOW()
+        WHERE user_id = $1 AND enabled_at IS NULL AND secret = $3
+        "#,
+    )
+    .bind(user_id)
+    .bind(step)
+    .bind(&record.secret)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    if enabled == 0 {
+        return Ok(None);
+    }
+    let codes = replace_recovery_codes(&mut tx, user_id).await?;
+    tx.commit().await?;
+    Ok(Some(codes))
+}
+
+/// Check a TOTP or recovery code for an admin with 2FA enabled. Each TOTP time step and each
+/// recovery code is accepted only once.
+pub async fn verify_second_factor(pool: &PgPool, user_id: Uuid, code: &str) -> Result<Option<SecondFactor>, sqlx::Error> {
+    let Some(record) = get_two_factor(pool, user_id).await? else {
+        return Ok(None);
+    };
+    if record.enabled_at.is_none() {
+        return Ok(None);
+    }
+
+    if let Some(step) = verify_totp(&record.secret, code, Utc::now().timestamp()) {
+        let accepted = sqlx::query(
+            r#"
+            UPDATE admin_two_factor SET last_used_step = $2, updated_at = NOW()
+            WHERE user_id = $1 AND (last_used_step IS NULL OR last_used_step < $2)
+            "#,
+        )
+        .bind(user_id)
+        .bind(step)
+        .execute(pool)
+        .await?
+        .rows_affected();
+        return Ok((accepted == 1).then_some(SecondFactor::Totp));
+    }
+
+    let used = sqlx::query(
+        r#"
+        UPDATE admin_recovery_codes SET used_at = NOW()
+        WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL
+        "#,
+    )
+    .bind(user_id)
+    .bind(recovery_code_hash(code))
+    .execute(pool)
+    .await?
+    .rows_affected();
+    Ok((used > 0).then_some(SecondFactor::RecoveryCode))
+}
+
+/// Invalidate all recovery codes and issue a new set
+pub async fn regenerate_recovery_codes(pool: &PgPool, user_id: Uuid) -> Result<Vec<String>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let codes = replace_recovery_codes(&mut tx, user_id).await?;
+    tx.commit().await?;
+    Ok(codes)
+}
+
+pub async fn recovery_codes_remaining(pool: &PgPool, user_id: Uuid) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar("SELECT COUNT(*) FROM admin_recovery_codes WHERE user_id = $1 AND used_at IS NULL")
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+}
+
+/// Turn 2FA off and drop the secret and recovery codes
+pub async fn disable_two_factor(pool: &PgPool, user_id: Uuid) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM admin_recovery_codes WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM admin_two_factor WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await
+}
+
+async fn replace_recovery_codes(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    user_id: Uuid,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query("DELETE FROM admin_recovery_codes WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut **tx)
+        .await?;
+
+    let codes: Vec<String> = (0..RECOVERY_CODE_COUNT).map(|_| generate_recovery_code()).collect();
+    let hashes: Vec<String> = codes.iter().map(|code| recovery_code_hash(code)).collect();
+    sqlx::query("INSERT INTO admin_recovery_codes (user_id, code_hash) SELECT $1, UNNEST($2::text[])")
+        .bind(user_id)
+        .bind(&hashes)
+        .execute(&mut **tx)
+        .await?;
+    Ok(codes)
+}
+
+/// `xxxxx-xxxxx`
+fn generate_recovery_code() -> String {
+    let mut rng = rand::thread_rng();
+    let mut code: String = (0..10)
+        .map(|_| RECOVERY_CODE_ALPHABET[rng.gen_range(0..RECOVERY_CODE_ALPHABET.len())] as char)
+        .collect();
+    code.insert(5, '-');
+    code
+}
+
+/// Hash of a recovery code ignoring case, dashes and spaces, as users type them back
+fn recovery_code_hash(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|c| *c != '-' && !c.is_whitespace())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    hex::encode(Sha256::digest(normalized.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::insert_user;
+    use crate::utils::totp::{current_step, totp_code};
+
+    #[test]
+    fn test_recovery_code_format_and_normalization() {
+        let code = generate_recovery_code();
+        assert_eq!(code.len(), 11);
+        assert_eq!(code.as_bytes()[5], b'-');
+        assert_eq!(recovery_code_hash(&code), recovery_code_hash(&code.to_uppercase().replace('-', " ")));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_enrollment_codes_and_recovery() {
+        let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap()).await.unwrap();
+        let student_id = format!("tf{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let user_id = insert_user(&pool, &student_id, None).await;
+
+        let secret = begin_enrollment(&pool, user_id).await.unwrap().unwrap();
+        assert!(!is_two_factor_enabled(&pool, user_id).await.unwrap());
+
+        // A code from outside the drift window does not confirm
+        let now = current_step(Utc::now().timestamp());
+        let stale = totp_code(&secret, now - 10).unwrap();
+        assert!(confirm_enrollment(&pool, user_id, &stale).await.unwrap().is_none());
+
+        let codes = confirm_enrollment(&pool, user_id, &totp_code(&secret, now).unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(codes.len(), RECOVERY_CODE_COUNT);
+        assert!(is_two_factor_enabled(&pool, user_id).await.unwrap());
+        assert!(begin_enrollment(&pool, user_id).await.unwrap().is_none());
+
+        // The enrollment code's step is spent; the next step works once
+        let same_step = verify_second_factor(&pool, user_id, &totp_code(&secret, now).unwrap()).await.unwrap();
+        let next = totp_code(&secret, now + 1).unwrap();
+        let first = verify_second_factor(&pool, user_id, &next).await.unwrap();
+        let replay = verify_second_factor(&pool, user_id, &next).await.unwrap();
+
+        let recovery = verify_second_factor(&pool, user_id, &codes[0].to_uppercase()).await.unwrap();
+        let recovery_again = verify_second_factor(&pool, user_id, &codes[0]).await.unwrap();
+        let remaining = recovery_codes_remaining(&pool, user_id).await.unwrap();
+
+        disable_two_factor(&pool, user_id).await.unwrap();
+        let enabled_after_disable = is_two_factor_enabled(&pool, user_id).await.unwrap();
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(same_step, None);
+        assert_eq!(first, Some(SecondFactor::Totp));
+        assert_eq!(replay, None);
+        assert_eq!(recovery, Some(SecondFactor::RecoveryCode));
+        assert_eq!(recovery_again, None);
+        assert_eq!(remaining, RECOVERY_CODE_COUNT as i64 - 1);
+        assert!(!enabled_after_disable);
+    }
+}
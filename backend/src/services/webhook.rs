@@ -0,0 +1,357 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use sqlx::FromRow;
+use std::time::Duration;
+use tokio::time::interval;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::middleware::session::SessionState;
+use crate::models::webhook::WebhookEvent;
+use crate::services::email_service::retry_delay;
+use crate::services::metrics::metrics;
+use crate::utils::http_client::PublicHttpClient;
+
+/// Claimed deliveries are hidden from other workers for this long, as for the email queue
+const CLAIM_LEASE_SECONDS: i64 = 300;
+const BATCH_SIZE: i64 = 50;
+
+/// Queue `event` for every active webhook subscribed to it. Best effort like the audit log:
+/// a failure is logged and never fails the action that raised the event.
+pub async fn enqueue_webhook_event(session_state: &SessionState, event: WebhookEvent, data: Value) {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO webhook_deliveries (webhook_id, event_type, payload, max_attempts)
+        SELECT id, $1, $2, $3 FROM webhooks
+        WHERE is_active AND $1 = ANY(event_types)
+        "#,
+    )
+    .bind(event.as_str())
+    .bind(&data)
+    .bind(session_state.app_config.webhook_max_attempts)
+    .execute(&session_state.db_pool)
+    .await;
+
+    match result {
+        Ok(queued) if queued.rows_affected() > 0 => {
+            debug!("Queued {} webhook deliveries for {}", queued.rows_affected(), event.as_str())
+        }
+        Ok(_) => {}
+        Err(e) => warn!(event = event.as_str(), "Failed to queue webhook deliveries: {}", e),
+    }
+}
+
+/// Common `data` for participation.* events; `source` says which path made the change
+pub fn participation_event_data(participation_id: Uuid, activity_id: Uuid, user_id: Uuid, source: &str) -> Value {
+    json!({
+        "participation_id": participation_id,
+        "activity_id": activity_id,
+        "user_id": user_id,
+        "source": source,
+    })
+}
+
+/// `X-Signature` value: hex HMAC-SHA256 of the exact request body
+pub fn sign_payload(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[derive(Debug, FromRow)]
+struct ClaimedDelivery {
+    id: Uuid,
+    event_type: String,
+    payload: Value,
+    attempts: i32,
+    max_attempts: i32,
+    created_at: DateTime<Utc>,
+    url: String,
+    secret: String,
+    is_active: bool,
+}
+
+/// Sends queued deliveries (รันผ่าน BackgroundTaskManager)
+pub struct WebhookDispatcher {
+    session_state: SessionState,
+    client: PublicHttpClient,
+}
+
+impl WebhookDispatcher {
+    pub fn new(session_state: SessionState) -> Self {
+        let timeout = Duration::from_secs(session_state.app_config.webhook_timeout_seconds.max(1));
+        Self {
+            session_state,
+            client: PublicHttpClient::new(timeout),
+        }
+    }
+
+    pub async fn start_background_task(self, shutdown: CancellationToken) {
+        let interval_seconds = self.session_state.app_config.webhook_queue_interval_seconds.max(1);
+        let mut interval = interval(Duration::from_secs(interval_seconds));
+        info!("Started webhook dispatcher background task (every {} seconds)", interval_seconds);
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.cancelled() => break,
+            }
+
+            let result = self.process_queue().await;
+            metrics().record_task_run("webhook_deliveries", result.is_ok());
+
+            match result {
+                Ok(delivered) if delivered > 0 => debug!("Delivered {} webhooks", delivered),
+                Ok(_) => {}
+                Err(e) => error!("Webhook dispatcher error: {}", e),
+            }
+        }
+    }
+
+    /// Send every due delivery once; returns how many were delivered
+    pub async fn process_queue(&self) -> Result<usize> {
+        // SKIP LOCKED keeps concurrent workers from sending the same delivery twice
+        let deliveries = sqlx::query_as::<_, ClaimedDelivery>(
+            r#"
+            UPDATE webhook_deliveries d
+            SET next_attempt_at = NOW() + make_interval(secs => $1), updated_at = NOW()
+            FROM webhooks w
+            WHERE w.id = d.webhook_id AND d.id IN (
+                SELECT id FROM webhook_deliveries
+                WHERE status = 'pending' AND next_attempt_at <= NOW()
+                ORDER BY next_attempt_at
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING d.id, d.event_type, d.payload, d.attempts, d.max_attempts, d.created_at,
+                      w.url, w.secret, w.is_active
+            "#,
+        )
+        .bind(CLAIM_LEASE_SECONDS as f64)
+        .bind(BATCH_SIZE)
+        .fetch_all(&self.session_state.db_pool)
+        .await?;
+
+        let results =
+            futures::future::join_all(deliveries.iter().map(|delivery| send_delivery(&self.client, delivery))).await;
+
+        let mut delivered = 0;
+        for (delivery, result) in deliveries.iter().zip(results) {
+            match result {
+                Ok(status) if (200..300).contains(&status) => {
+                    sqlx::query(
+                        r#"
+                        UPDATE webhook_deliveries
+                        SET status = 'delivered', attempts = attempts + 1, response_status = $2,
+                            error_message = NULL, delivered_at = NOW(), updated_at = NOW()
+                        WHERE id = $1
+                        "#,
+                    )
+                    .bind(delivery.id)
+                    .bind(status as i32)
+                    .execute(&self.session_state.db_pool)
+                    .await?;
+                    delivered += 1;
+                }
+                // Only the status is kept; the receiver's body is never stored or shown
+                Ok(status) => self.record_failure(delivery, Some(status), &format!("HTTP {}", status)).await?,
+                Err(e) => self.record_failure(delivery, None, &e.to_string()).await?,
+            }
+        }
+
+        Ok(delivered)
+    }
+
+    /// Reschedule with backoff, or give up (`failed`) once max_attempts is reached
+    async fn record_failure(&self, delivery: &ClaimedDelivery, response_status: Option<u16>, error_message: &str) -> Result<()> {
+        let attempts = delivery.attempts + 1;
+        let gave_up = attempts >= delivery.max_attempts || !delivery.is_active;
+        let next_attempt_at = if gave_up {
+            None
+        } else {
+            let delay = retry_delay(self.session_state.app_config.webhook_retry_base_seconds, attempts);
+            Some(Utc::now() + chrono::Duration::from_std(delay)?)
+        };
+
+        sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = $2, attempts = $3, response_status = $4, error_message = $5,
+                next_attempt_at = COALESCE($6, next_attempt_at), updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(delivery.id)
+        .bind(if gave_up { "failed" } else { "pending" })
+        .bind(attempts)
+        .bind(response_status.map(i32::from))
+        .bind(error_message)
+        .bind(next_attempt_at)
+        .execute(&self.session_state.db_pool)
+        .await?;
+
+        if gave_up {
+            error!("Webhook delivery {} failed permanently after {} attempts: {}", delivery.id, attempts, error_message);
+        } else {
+            warn!("Webhook delivery {} failed (attempt {}): {}", delivery.id, attempts, error_message);
+        }
+        Ok(())
+    }
+}
+
+/// JSON body sent to the receiver; `id` lets receivers drop duplicates of a retried delivery
+fn delivery_body(delivery: &ClaimedDelivery) -> Vec<u8> {
+    json!({
+        "id": delivery.id,
+        "event": delivery.event_type,
+        "created_at": delivery.created_at,
+        "data": delivery.payload,
+    })
+    .to_string()
+    .into_bytes()
+}
+
+/// Sends one delivery and returns the receiver's HTTP status
+async fn send_delivery(client: &PublicHttpClient, delivery: &ClaimedDelivery) -> Result<u16> {
+    if !delivery.is_active {
+        anyhow::bail!("Webhook is disabled");
+    }
+
+    let body = delivery_body(delivery);
+    let headers = vec![
+        ("X-Signature", sign_payload(&delivery.secret, &body)),
+        ("X-Webhook-Event", delivery.event_type.clone()),
+        ("X-Webhook-Delivery", delivery.id.to_string()),
+    ];
+    client.post_json(&delivery.url, &headers, body).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_state;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_sign_payload() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign_payload("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    /// Answers each connection with the next status and returns the raw requests
+    fn spawn_receiver(statuses: Vec<u16>) -> (u16, std::thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = std::thread::spawn(move || {
+            statuses
+                .into_iter()
+                .map(|status| {
+                    let (mut socket, _) = listener.accept().unwrap();
+                    let mut request = Vec::new();
+                    let mut buffer = [0u8; 4096];
+                    loop {
+                        let n = socket.read(&mut buffer).unwrap();
+                        request.extend_from_slice(&buffer[..n]);
+                        let text = String::from_utf8_lossy(&request);
+                        if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                            let length: usize = head
+                                .lines()
+                                .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length: ").map(str::to_string))
+                                .and_then(|value| value.parse().ok())
+                                .unwrap_or(0);
+                            if body.len() >= length {
+                                break;
+                            }
+                        }
+                    }
+                    socket
+                        .write_all(format!("HTTP/1.1 {} X\r\nContent-Length: 0\r\n\r\n", status).as_bytes())
+                        .unwrap();
+                    String::from_utf8(request).unwrap()
+                })
+                .collect()
+        });
+        (port, handle)
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL pointing at a migrated database"]
+    async fn test_delivery_is_signed_and_retried_until_accepted() {
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let mut config = crate::config::Config::from_env().unwrap();
+        config.webhook_max_attempts = 3;
+        let session_state = SessionState {
+            app_config: Arc::new(config),
+            ..test_state(&pool)
+        };
+
+        let (port, receiver) = spawn_receiver(vec![500, 204]);
+        let webhook_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO webhooks (url, secret, event_types) VALUES ($1, 'test-secret', $2) RETURNING id",
+        )
+        .bind(format!("http://127.0.0.1:{}/hook", port))
+        .bind(vec!["participation.checked_in".to_string()])
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let activity_id = Uuid::new_v4();
+
+        // Not subscribed: nothing queued
+        enqueue_webhook_event(&session_state, WebhookEvent::ActivityCreated, json!({ "activity_id": activity_id })).await;
+        enqueue_webhook_event(&session_state, WebhookEvent::ParticipationCheckedIn, json!({ "activity_id": activity_id })).await;
+
+        let dispatcher = WebhookDispatcher {
+            session_state: session_state.clone(),
+            client: PublicHttpClient::allowing_private(Duration::from_secs(5)),
+        };
+        let first = dispatcher.process_queue().await.unwrap();
+        // Pull the retry forward instead of waiting for the backoff
+        sqlx::query("UPDATE webhook_deliveries SET next_attempt_at = NOW() WHERE webhook_id = $1")
+            .bind(webhook_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let second = dispatcher.process_queue().await.unwrap();
+
+        let deliveries = sqlx::query_as::<_, crate::models::webhook::WebhookDelivery>(
+            "SELECT * FROM webhook_deliveries WHERE webhook_id = $1",
+        )
+        .bind(webhook_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        sqlx::query("DELETE FROM webhooks WHERE id = $1")
+            .bind(webhook_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let requests = receiver.join().unwrap();
+
+        assert_eq!((first, second), (0, 1));
+        assert_eq!(deliveries.len(), 1);
+        let delivery = &deliveries[0];
+        assert_eq!(delivery.event_type, "participation.checked_in");
+        assert_eq!(delivery.status, "delivered");
+        assert_eq!(delivery.attempts, 2);
+        assert_eq!(delivery.response_status, Some(204));
+
+        let (head, body) = requests[1].split_once("\r\n\r\n").unwrap();
+        let head = head.to_ascii_lowercase();
+        assert!(head.contains(&format!("x-signature: {}", sign_payload("test-secret", body.as_bytes()))));
+        assert!(head.contains("x-webhook-event: participation.checked_in"));
+        let body: Value = serde_json::from_str(body).unwrap();
+        assert_eq!(body["id"], delivery.id.to_string());
+        assert_eq!(body["data"]["activity_id"], activity_id.to_string());
+    }
+}
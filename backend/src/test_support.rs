@@ -0,0 +1,47 @@
+//! Fixtures shared by the database tests
+
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::middleware::session::SessionState;
+use crate::models::admin_role::AdminLevel;
+use crate::services::{MemorySessionStore, RedisHealth, SessionConfig};
+
+/// Session state over `pool` with an in-memory session store and the configuration from the environment
+pub fn test_state(pool: &sqlx::PgPool) -> SessionState {
+    SessionState {
+        session_store: Arc::new(MemorySessionStore::default()),
+        redis_health: Arc::new(RedisHealth::default()),
+        db_pool: pool.clone(),
+        config: SessionConfig::default(),
+        app_config: Arc::new(Config::from_env().unwrap()),
+    }
+}
+
+/// Insert a user named "Test User" with student ID `tag`, email `<tag>@test.local`, an
+/// unusable password hash and a fresh QR secret. With `admin_level` the user also gets an
+/// enabled admin role without a faculty, holding `ManageUsers` so it counts as active.
+pub async fn insert_user(pool: &sqlx::PgPool, tag: &str, admin_level: Option<AdminLevel>) -> Uuid {
+    let user_id = sqlx::query_scalar(
+        "INSERT INTO users (student_id, email, password_hash, first_name, last_name, qr_secret)
+         VALUES ($1, $2, 'x', 'Test', 'User', $3) RETURNING id",
+    )
+    .bind(tag)
+    .bind(format!("{}@test.local", tag))
+    .bind(Uuid::new_v4().to_string())
+    .fetch_one(pool)
+    .await
+    .unwrap();
+
+    if let Some(admin_level) = admin_level {
+        sqlx::query("INSERT INTO admin_roles (user_id, admin_level, permissions) VALUES ($1, $2, ARRAY['ManageUsers'])")
+            .bind(user_id)
+            .bind(admin_level)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    user_id
+}
@@ -0,0 +1,162 @@
+use axum::{
+    body::Body,
+    http::header,
+    response::{IntoResponse, Response},
+};
+use futures::stream::{self, Stream, StreamExt};
+
+/// Quote a CSV field when it contains a delimiter, quote or line break (RFC 4180).
+/// Text that a spreadsheet would run as a formula gets a leading `'` first.
+pub fn escape_csv_field(field: &str) -> String {
+    let field = neutralize_formula(field);
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.into_owned()
+    }
+}
+
+/// Excel and LibreOffice evaluate cells starting with `=`, `+`, `-`, `@`, tab or CR, so a
+/// student named `=HYPERLINK(...)` would run in the admin's export. Plain numbers such as
+/// `-1.5` are left alone.
+fn neutralize_formula(field: &str) -> std::borrow::Cow<'_, str> {
+    let starts_formula = field.starts_with(['=', '+', '-', '@', '\t', '\r']);
+    if starts_formula && field.parse::<f64>().is_err() {
+        format!("'{}", field).into()
+    } else {
+        field.into()
+    }
+}
+
+/// Format one CSV record terminated by CRLF
+pub fn csv_row<S: AsRef<str>>(fields: &[S]) -> String {
+    let row: Vec<String> = fields.iter().map(|f| escape_csv_field(f.as_ref())).collect();
+    format!("{}\r\n", row.join(","))
+}
+
+/// Append one CSV record to a buffer
+pub fn push_csv_row<S: AsRef<str>>(csv: &mut String, fields: &[S]) {
+    csv.push_str(&csv_row(fields));
+}
+
+/// Download response for a generated CSV file
+pub fn csv_attachment(filename: &str, csv: String) -> Response {
+    (
+        csv_headers(filename),
+        // UTF-8 BOM so Excel opens Thai text correctly
+        format!("{}{}", UTF8_BOM, csv),
+    )
+        .into_response()
+}
+
+/// Download response that streams CSV records as they are produced
+pub fn csv_attachment_stream<S>(filename: &str, rows: S) -> Response
+where
+    S: Stream<Item = Result<String, std::io::Error>> + Send + 'static,
+{
+    let body = stream::once(async { Ok(UTF8_BOM.to_string()) }).chain(rows);
+    (csv_headers(filename), Body::from_stream(body)).into_response()
+}
+
+const UTF8_BOM: &str = "\u{feff}";
+
+/// Parse uploaded CSV text into records (RFC 4180 quoting, CRLF or LF line endings).
+/// A leading UTF-8 BOM is ignored and blank lines are skipped.
+pub fn parse_csv(input: &str) -> Vec<Vec<String>> {
+    let input = input.strip_prefix(UTF8_BOM).unwrap_or(input);
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    in_quotes = false;
+                }
+            }
+            '"' if field.is_empty() => in_quotes = true,
+            ',' if !in_quotes => record.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes && chars.peek() == Some(&'\n') => {}
+            '\n' if !in_quotes => {
+                record.push(std::mem::take(&mut field));
+                push_record(&mut records, std::mem::take(&mut record));
+            }
+            c => field.push(c),
+        }
+    }
+
+    record.push(field);
+    push_record(&mut records, record);
+    records
+}
+
+fn push_record(records: &mut Vec<Vec<String>>, record: Vec<String>) {
+    if record.iter().any(|field| !field.trim().is_empty()) {
+        records.push(record);
+    }
+}
+
+fn csv_headers(filename: &str) -> [(header::HeaderName, String); 2] {
+    [
+        (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+        (
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", filename),
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_csv_field() {
+        assert_eq!(escape_csv_field("plain"), "plain");
+        assert_eq!(escape_csv_field("a,b"), "\"a,b\"");
+        assert_eq!(escape_csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(escape_csv_field("line\nbreak"), "\"line\nbreak\"");
+    }
+
+    #[test]
+    fn test_escape_csv_field_neutralizes_formulas() {
+        assert_eq!(escape_csv_field("=HYPERLINK(\"http://x\")"), "\"'=HYPERLINK(\"\"http://x\"\")\"");
+        assert_eq!(escape_csv_field("+cmd|' /C calc'!A0"), "'+cmd|' /C calc'!A0");
+        assert_eq!(escape_csv_field("-2+3"), "'-2+3");
+        assert_eq!(escape_csv_field("@SUM(A1)"), "'@SUM(A1)");
+        assert_eq!(escape_csv_field("\t=1"), "'\t=1");
+        assert_eq!(escape_csv_field("\r=1"), "\"'\r=1\"");
+        assert_eq!(escape_csv_field("-1.5"), "-1.5");
+        assert_eq!(escape_csv_field("a=b"), "a=b");
+    }
+
+    #[test]
+    fn test_push_csv_row() {
+        let mut csv = String::new();
+        push_csv_row(&mut csv, &["title", "hours"]);
+        push_csv_row(&mut csv, &["Workshop, day 1".to_string(), "3".to_string()]);
+        assert_eq!(csv, "title,hours\r\n\"Workshop, day 1\",3\r\n");
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let input = "\u{feff}student_id,name\r\n6401,\"Doe, John\"\r\n\r\n6402,\"say \"\"hi\"\"\"\n6403,\"multi\nline\"";
+        assert_eq!(
+            parse_csv(input),
+            vec![
+                vec!["student_id", "name"],
+                vec!["6401", "Doe, John"],
+                vec!["6402", "say \"hi\""],
+                vec!["6403", "multi\nline"],
+            ]
+        );
+        assert!(parse_csv("").is_empty());
+        assert_eq!(parse_csv("a,,c\n"), vec![vec!["a", "", "c"]]);
+    }
+}
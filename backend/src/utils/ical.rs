@@ -0,0 +1,135 @@
+use axum::{
+    http::header,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// One VEVENT in an iCalendar feed
+#[derive(Debug, Clone)]
+pub struct IcalEvent {
+    pub uid: Uuid,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub summary: String,
+    pub location: String,
+    pub description: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Escape a TEXT value (RFC 5545 section 3.3.11)
+pub fn escape_ical_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace(['\n', '\r'], "\\n")
+}
+
+/// Fold a content line at 75 octets without splitting UTF-8 characters (RFC 5545 section 3.1)
+pub fn fold_ical_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + line.len() / 74 * 3);
+    let mut octets = 0;
+
+    for ch in line.chars() {
+        if octets + ch.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            // The leading space counts towards the next line's length
+            octets = 1;
+        }
+        folded.push(ch);
+        octets += ch.len_utf8();
+    }
+
+    folded
+}
+
+/// Build a VCALENDAR document with CRLF line endings
+pub fn build_calendar(name: &str, events: &[IcalEvent]) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Trackivity//Activities//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape_ical_text(name)),
+    ];
+
+    for event in events {
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}@trackivity", event.uid),
+            format!("DTSTAMP:{}", format_ical_datetime(event.updated_at)),
+            format!("DTSTART:{}", format_ical_datetime(event.start)),
+            format!("DTEND:{}", format_ical_datetime(event.end)),
+            format!("SUMMARY:{}", escape_ical_text(&event.summary)),
+            format!("LOCATION:{}", escape_ical_text(&event.location)),
+            format!("DESCRIPTION:{}", escape_ical_text(&event.description)),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    lines
+        .iter()
+        .map(|line| format!("{}\r\n", fold_ical_line(line)))
+        .collect()
+}
+
+/// Response for a generated calendar; served inline so calendar apps can subscribe
+pub fn ical_response(calendar: String) -> Response {
+    (
+        [(header::CONTENT_TYPE, "text/calendar; charset=utf-8")],
+        calendar,
+    )
+        .into_response()
+}
+
+fn format_ical_datetime(datetime: DateTime<Utc>) -> String {
+    datetime.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_escape_ical_text() {
+        assert_eq!(escape_ical_text("Lab 1, Building A; floor 2"), "Lab 1\\, Building A\\; floor 2");
+        assert_eq!(escape_ical_text("line\nbreak"), "line\\nbreak");
+        assert_eq!(escape_ical_text("back\\slash"), "back\\\\slash");
+    }
+
+    #[test]
+    fn test_fold_ical_line_respects_octets() {
+        let line = format!("DESCRIPTION:{}", "กิจกรรม".repeat(10));
+        let folded = fold_ical_line(&line);
+
+        for part in folded.split("\r\n") {
+            assert!(part.len() <= 75, "line too long: {} octets", part.len());
+        }
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+
+    #[test]
+    fn test_build_calendar() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 10, 9, 0, 0).unwrap();
+        let event = IcalEvent {
+            uid: Uuid::nil(),
+            start,
+            end: Utc.with_ymd_and_hms(2025, 1, 10, 17, 0, 0).unwrap(),
+            summary: "Workshop".to_string(),
+            location: "Lab 1".to_string(),
+            description: "Bring a laptop".to_string(),
+            updated_at: start,
+        };
+
+        let calendar = build_calendar("My Activities", &[event]);
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(calendar.contains("\r\nDTSTART:20250110T090000Z\r\nDTEND:20250110T170000Z\r\n"));
+        assert!(calendar.contains("\r\nUID:00000000-0000-0000-0000-000000000000@trackivity\r\n"));
+        assert!(calendar.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+    }
+}
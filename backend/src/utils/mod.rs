@@ -1,8 +1,166 @@
+pub mod csv;
+pub mod http_client;
+pub mod ical;
+pub mod pagination;
+pub mod password_hash;
+pub mod pdf;
 pub mod qr;
+pub mod rate_limit;
+pub mod totp;
 pub mod validation;
+pub mod xlsx;
 
-pub fn get_client_info() -> (Option<String>, Option<String>) {
-    // In a real implementation, this would extract IP and User-Agent from request
-    // For now, return None values
-    (None, None)
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::request::Parts;
+use axum::http::HeaderMap;
+use ipnet::IpNet;
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+
+/// Address of the TCP peer from `ConnectInfo`: the client itself, or the reverse proxy in
+/// front of the server. `None` when the server runs without connect info, as in tests.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PeerAddr(pub Option<IpAddr>);
+
+impl PeerAddr {
+    pub fn from_extensions(extensions: &axum::http::Extensions) -> Self {
+        PeerAddr(extensions.get::<ConnectInfo<SocketAddr>>().map(|ConnectInfo(addr)| addr.ip()))
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for PeerAddr {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(PeerAddr::from_extensions(&parts.extensions))
+    }
+}
+
+/// Client IP address and User-Agent of a request.
+///
+/// Forwarding headers are only believed when the TCP peer is one of `trusted_proxies`
+/// (TRUSTED_PROXIES). With no trusted proxies, or a request that did not come through one,
+/// the peer address is the client. Otherwise X-Forwarded-For is read right to left, because
+/// each proxy appends the address it received the request from, and the first hop that is
+/// not a trusted proxy is the client; anything left of it may have been sent by the client
+/// and is ignored. X-Real-IP is used only when a trusted proxy sent no X-Forwarded-For.
+pub fn get_client_info(headers: &HeaderMap, peer: PeerAddr, trusted_proxies: &[IpNet]) -> (Option<String>, Option<String>) {
+    let ip_address = client_ip(headers, peer, trusted_proxies).map(|ip| ip.to_string());
+
+    let user_agent = headers
+        .get("user-agent")
+        .and_then(|h| h.to_str().ok())
+        .map(|s| s.to_string());
+
+    (ip_address, user_agent)
+}
+
+fn client_ip(headers: &HeaderMap, PeerAddr(peer): PeerAddr, trusted_proxies: &[IpNet]) -> Option<IpAddr> {
+    // A dual-stack listener reports IPv4 clients as ::ffff:a.b.c.d
+    let peer = peer?.to_canonical();
+    let is_trusted = |ip: &IpAddr| trusted_proxies.iter().any(|net| net.contains(ip));
+    if !is_trusted(&peer) {
+        return Some(peer);
+    }
+
+    // Several X-Forwarded-For headers are one list, in order
+    let hops: Vec<&str> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|h| h.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .filter(|hop| !hop.is_empty())
+        .collect();
+    if hops.is_empty() {
+        return headers
+            .get("x-real-ip")
+            .and_then(|h| h.to_str().ok())
+            .and_then(|value| value.trim().parse::<IpAddr>().ok())
+            .map(|ip| ip.to_canonical())
+            .or(Some(peer));
+    }
+
+    let mut client = peer;
+    for hop in hops.iter().rev() {
+        // A malformed hop cannot be attributed; the last proxy that handed it over is the best answer
+        let Ok(ip) = hop.parse::<IpAddr>().map(|ip| ip.to_canonical()) else {
+            break;
+        };
+        client = ip;
+        if !is_trusted(&ip) {
+            break;
+        }
+    }
+    Some(client)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proxies() -> Vec<IpNet> {
+        vec!["10.0.0.0/8".parse().unwrap(), "172.17.0.1/32".parse().unwrap()]
+    }
+
+    fn peer(ip: &str) -> PeerAddr {
+        PeerAddr(Some(ip.parse().unwrap()))
+    }
+
+    #[test]
+    fn test_get_client_info_forwarded_for_takes_rightmost_untrusted_hop() {
+        let mut headers = HeaderMap::new();
+        // The client claimed 1.1.1.1; the proxy appended the address it actually saw
+        headers.insert("x-forwarded-for", "1.1.1.1, 203.0.113.5, 10.0.0.2".parse().unwrap());
+        headers.insert("x-real-ip", "10.0.0.2".parse().unwrap());
+        headers.insert("user-agent", "Mozilla/5.0".parse().unwrap());
+
+        let (ip, ua) = get_client_info(&headers, peer("172.17.0.1"), &proxies());
+        assert_eq!(ip.as_deref(), Some("203.0.113.5"));
+        assert_eq!(ua.as_deref(), Some("Mozilla/5.0"));
+    }
+
+    #[test]
+    fn test_get_client_info_ignores_forwarding_headers_from_untrusted_peers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", "203.0.113.5".parse().unwrap());
+        headers.insert("x-real-ip", "203.0.113.5".parse().unwrap());
+
+        // No proxy configured: the socket peer is the client
+        let (ip, _) = get_client_info(&headers, peer("198.51.100.9"), &[]);
+        assert_eq!(ip.as_deref(), Some("198.51.100.9"));
+        // Proxy configured, but this request bypassed it
+        let (ip, _) = get_client_info(&headers, peer("198.51.100.9"), &proxies());
+        assert_eq!(ip.as_deref(), Some("198.51.100.9"));
+    }
+
+    #[test]
+    fn test_get_client_info_trusted_proxy_edge_cases() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-real-ip", "198.51.100.7".parse().unwrap());
+        let (ip, _) = get_client_info(&headers, peer("10.0.0.2"), &proxies());
+        assert_eq!(ip.as_deref(), Some("198.51.100.7"));
+
+        // Every hop is a proxy: the leftmost one is as far as we can see
+        headers.insert("x-forwarded-for", "10.0.0.3, 10.0.0.4".parse().unwrap());
+        let (ip, _) = get_client_info(&headers, peer("10.0.0.2"), &proxies());
+        assert_eq!(ip.as_deref(), Some("10.0.0.3"));
+
+        // A garbage hop stops the walk at the proxy that forwarded it
+        headers.insert("x-forwarded-for", "203.0.113.5, unknown, 10.0.0.4".parse().unwrap());
+        let (ip, _) = get_client_info(&headers, peer("10.0.0.2"), &proxies());
+        assert_eq!(ip.as_deref(), Some("10.0.0.4"));
+
+        // IPv4-mapped peers match IPv4 ranges
+        let (ip, _) = get_client_info(&HeaderMap::new(), peer("::ffff:10.0.0.2"), &proxies());
+        assert_eq!(ip.as_deref(), Some("10.0.0.2"));
+    }
+
+    #[test]
+    fn test_get_client_info_without_peer() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", "203.0.113.5".parse().unwrap());
+        assert_eq!(get_client_info(&headers, PeerAddr::default(), &proxies()), (None, None));
+        assert_eq!(get_client_info(&HeaderMap::new(), PeerAddr::default(), &[]), (None, None));
+    }
 }
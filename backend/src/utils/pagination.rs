@@ -0,0 +1,69 @@
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::{NaiveDate, NaiveTime};
+use uuid::Uuid;
+
+/// Keyset cursor for activity listings: the last seen `(start_date, start_time_only, id)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActivityCursor {
+    pub start_date: NaiveDate,
+    pub start_time: NaiveTime,
+    pub id: Uuid,
+}
+
+impl ActivityCursor {
+    /// Encode cursor เป็น base64 (URL-safe) เพื่อส่งกลับให้ client
+    pub fn encode(&self) -> String {
+        let raw = format!(
+            "{}|{}|{}",
+            self.start_date.format("%Y-%m-%d"),
+            self.start_time.format("%H:%M:%S%.f"),
+            self.id
+        );
+        URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    /// Decode cursor ที่ client ส่งมา
+    pub fn decode(cursor: &str) -> Result<Self> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(cursor.trim())
+            .map_err(|_| anyhow!("Invalid cursor encoding"))?;
+        let raw = String::from_utf8(bytes).map_err(|_| anyhow!("Invalid cursor encoding"))?;
+
+        let mut parts = raw.split('|');
+        let (Some(date), Some(time), Some(id), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(anyhow!("Invalid cursor format"));
+        };
+
+        Ok(Self {
+            start_date: NaiveDate::parse_from_str(date, "%Y-%m-%d")?,
+            start_time: NaiveTime::parse_from_str(time, "%H:%M:%S%.f")?,
+            id: Uuid::parse_str(id)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_roundtrip() {
+        let cursor = ActivityCursor {
+            start_date: NaiveDate::from_ymd_opt(2025, 1, 10).unwrap(),
+            start_time: NaiveTime::from_hms_micro_opt(9, 30, 0, 250).unwrap(),
+            id: Uuid::new_v4(),
+        };
+
+        let decoded = ActivityCursor::decode(&cursor.encode()).unwrap();
+        assert_eq!(decoded, cursor);
+    }
+
+    #[test]
+    fn test_invalid_cursor() {
+        assert!(ActivityCursor::decode("not-a-cursor!").is_err());
+        assert!(ActivityCursor::decode(&URL_SAFE_NO_PAD.encode("2025-01-10|09:00:00")).is_err());
+    }
+}
@@ -0,0 +1,253 @@
+use anyhow::{anyhow, Result};
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher as _, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+
+use crate::config::{Config, PasswordHashAlgorithm};
+
+const ARGON2_TAG_LEN: usize = 32;
+
+/// Highest Argon2id costs accepted, both in configuration and in stored hashes, so a
+/// tampered hash can't make a login allocate gigabytes or spin for minutes
+const MAX_ARGON2_MEMORY_KIB: u32 = 1024 * 1024;
+const MAX_ARGON2_ITERATIONS: u32 = 16;
+const MAX_ARGON2_PARALLELISM: u32 = 16;
+
+/// Stored in place of a hash for accounts that must never log in again (anonymized users);
+/// it matches no password
+pub const DISABLED_PASSWORD_HASH: &str = "!";
+
+/// Password hashing scheme; hashes carry their algorithm prefix (`$2b$...`, `$argon2id$...`)
+/// so stored hashes keep verifying after the configured algorithm changes
+pub trait PasswordHasher: Send + Sync {
+    fn hash(&self, password: &str) -> Result<String>;
+    fn verify(&self, password: &str, hash: &str) -> Result<bool>;
+}
+
+pub struct BcryptHasher {
+    pub cost: u32,
+}
+
+impl PasswordHasher for BcryptHasher {
+    fn hash(&self, password: &str) -> Result<String> {
+        Ok(bcrypt::hash(password, self.cost)?)
+    }
+
+    fn verify(&self, password: &str, hash: &str) -> Result<bool> {
+        Ok(bcrypt::verify(password, hash)?)
+    }
+}
+
+/// Cost parameters of an Argon2id hash
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Argon2Params {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Argon2Params {
+    pub fn validate(&self) -> Result<()> {
+        if self.parallelism == 0 || self.parallelism > MAX_ARGON2_PARALLELISM {
+            return Err(anyhow!("Argon2 parallelism must be 1-{}", MAX_ARGON2_PARALLELISM));
+        }
+        if self.iterations == 0 || self.iterations > MAX_ARGON2_ITERATIONS {
+            return Err(anyhow!("Argon2 iterations must be 1-{}", MAX_ARGON2_ITERATIONS));
+        }
+        if self.memory_kib < 8 * self.parallelism || self.memory_kib > MAX_ARGON2_MEMORY_KIB {
+            return Err(anyhow!(
+                "Argon2 memory must be at least 8 KiB per lane and at most {} KiB",
+                MAX_ARGON2_MEMORY_KIB
+            ));
+        }
+        Ok(())
+    }
+
+    fn argon2(&self, tag_len: usize) -> Result<Argon2<'static>> {
+        self.validate()?;
+        let params = Params::new(self.memory_kib, self.iterations, self.parallelism, Some(tag_len))
+            .map_err(|e| anyhow!("Invalid Argon2 parameters: {}", e))?;
+        Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+    }
+}
+
+/// Argon2id in PHC string format: `$argon2id$v=19$m=19456,t=2,p=1$<salt>$<hash>`
+pub struct Argon2idHasher {
+    pub params: Argon2Params,
+}
+
+impl PasswordHasher for Argon2idHasher {
+    fn hash(&self, password: &str) -> Result<String> {
+        let salt = SaltString::generate(&mut OsRng);
+        let hash = self
+            .params
+            .argon2(ARGON2_TAG_LEN)?
+            .hash_password(password.as_bytes(), &salt)
+            .map_err(|e| anyhow!("Failed to hash password: {}", e))?;
+        Ok(hash.to_string())
+    }
+
+    /// Verifies with the parameters stored in the hash, not `self.params`
+    fn verify(&self, password: &str, hash: &str) -> Result<bool> {
+        verify_argon2id(password, hash)
+    }
+}
+
+fn verify_argon2id(password: &str, hash: &str) -> Result<bool> {
+    let (stored, params, tag_len) = parse_argon2id(hash)?;
+    match params.argon2(tag_len)?.verify_password(password.as_bytes(), &stored) {
+        Ok(()) => Ok(true),
+        Err(argon2::password_hash::Error::Password) => Ok(false),
+        Err(e) => Err(anyhow!("Failed to verify password: {}", e)),
+    }
+}
+
+/// Parsed PHC string plus its cost parameters and tag length, rejected when the costs
+/// exceed the accepted maximums
+fn parse_argon2id(hash: &str) -> Result<(PasswordHash<'_>, Argon2Params, usize)> {
+    let stored = PasswordHash::new(hash).map_err(|_| anyhow!("Invalid argon2id hash"))?;
+    if stored.algorithm != argon2::ARGON2ID_IDENT {
+        return Err(anyhow!("Invalid argon2id hash"));
+    }
+    if stored.version != Some(Version::V0x13.into()) {
+        return Err(anyhow!("Unsupported argon2id version"));
+    }
+    let tag_len = stored.hash.map(|tag| tag.len()).ok_or_else(|| anyhow!("Invalid argon2id hash"))?;
+
+    let param = |name: &str| {
+        stored
+            .params
+            .get_decimal(name)
+            .ok_or_else(|| anyhow!("Invalid argon2id hash: missing {}", name))
+    };
+    let params = Argon2Params {
+        memory_kib: param("m")?,
+        iterations: param("t")?,
+        parallelism: param("p")?,
+    };
+    params.validate()?;
+    Ok((stored, params, tag_len))
+}
+
+fn is_bcrypt_hash(hash: &str) -> bool {
+    ["$2a$", "$2b$", "$2x$", "$2y$"].iter().any(|prefix| hash.starts_with(prefix))
+}
+
+pub fn argon2_params(config: &Config) -> Argon2Params {
+    Argon2Params {
+        memory_kib: config.argon2_memory_kib,
+        iterations: config.argon2_iterations,
+        parallelism: config.argon2_parallelism,
+    }
+}
+
+/// Hasher for new hashes, chosen by PASSWORD_HASH_ALGORITHM
+pub fn password_hasher(config: &Config) -> Box<dyn PasswordHasher> {
+    match config.password_hash_algorithm {
+        PasswordHashAlgorithm::Bcrypt => Box::new(BcryptHasher { cost: config.bcrypt_cost }),
+        PasswordHashAlgorithm::Argon2id => Box::new(Argon2idHasher { params: argon2_params(config) }),
+    }
+}
+
+/// Hash with the configured algorithm on the blocking pool; a hash takes tens of milliseconds
+/// of CPU, which would otherwise stall every request sharing the worker thread
+pub async fn hash_password(config: &Config, password: &str) -> Result<String> {
+    let hasher = password_hasher(config);
+    let password = password.to_string();
+    tokio::task::spawn_blocking(move || hasher.hash(&password)).await?
+}
+
+/// Verify against a stored hash of any supported algorithm, on the blocking pool
+pub async fn verify_password(password: &str, hash: &str) -> Result<bool> {
+    let (password, hash) = (password.to_string(), hash.to_string());
+    tokio::task::spawn_blocking(move || verify_any(&password, &hash)).await?
+}
+
+fn verify_any(password: &str, hash: &str) -> Result<bool> {
+    if hash == DISABLED_PASSWORD_HASH {
+        Ok(false)
+    } else if hash.starts_with("$argon2id$") {
+        verify_argon2id(password, hash)
+    } else if is_bcrypt_hash(hash) {
+        // The cost is read from the hash
+        BcryptHasher { cost: bcrypt::DEFAULT_COST }.verify(password, hash)
+    } else {
+        Err(anyhow!("Unknown password hash format"))
+    }
+}
+
+/// Whether a hash that just verified should be replaced: another algorithm or other cost
+/// settings than the configured ones
+pub fn needs_rehash(config: &Config, hash: &str) -> bool {
+    match config.password_hash_algorithm {
+        PasswordHashAlgorithm::Bcrypt => match hash.parse::<bcrypt::HashParts>() {
+            Ok(parts) => is_bcrypt_hash(hash) && parts.get_cost() != config.bcrypt_cost,
+            Err(_) => true,
+        },
+        PasswordHashAlgorithm::Argon2id => match parse_argon2id(hash) {
+            Ok((_, params, tag_len)) => params != argon2_params(config) || tag_len != ARGON2_TAG_LEN,
+            Err(_) => true,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(algorithm: PasswordHashAlgorithm) -> Config {
+        let mut config = Config::from_env().unwrap();
+        config.password_hash_algorithm = algorithm;
+        config.bcrypt_cost = 4;
+        config.argon2_memory_kib = 64;
+        config.argon2_iterations = 1;
+        config.argon2_parallelism = 1;
+        config
+    }
+
+    #[tokio::test]
+    async fn test_argon2id_hash_and_verify() {
+        let config = config(PasswordHashAlgorithm::Argon2id);
+        let hash = hash_password(&config, "Str0ngPassw0rd!").await.unwrap();
+        assert!(hash.starts_with("$argon2id$v=19$m=64,t=1,p=1$"));
+        assert!(verify_password("Str0ngPassw0rd!", &hash).await.unwrap());
+        assert!(!verify_password("wrong-password", &hash).await.unwrap());
+        // A fresh salt per hash
+        assert_ne!(hash, hash_password(&config, "Str0ngPassw0rd!").await.unwrap());
+        assert!(!needs_rehash(&config, &hash));
+
+        let mut stronger = config.clone();
+        stronger.argon2_iterations = 2;
+        assert!(needs_rehash(&stronger, &hash));
+    }
+
+    #[tokio::test]
+    async fn test_bcrypt_hashes_keep_verifying_and_get_upgraded() {
+        let bcrypt_config = config(PasswordHashAlgorithm::Bcrypt);
+        let hash = hash_password(&bcrypt_config, "Str0ngPassw0rd!").await.unwrap();
+        assert!(hash.starts_with("$2b$04$"));
+        assert!(!needs_rehash(&bcrypt_config, &hash));
+
+        let argon2_config = config(PasswordHashAlgorithm::Argon2id);
+        assert!(verify_password("Str0ngPassw0rd!", &hash).await.unwrap());
+        assert!(needs_rehash(&argon2_config, &hash));
+    }
+
+    #[test]
+    fn test_verify_rejects_unknown_and_malformed_hashes() {
+        assert!(verify_any("password", "plaintext").is_err());
+        assert!(verify_any("password", "$argon2id$v=19$m=64,t=1$c2FsdHNhbHQ$AAAAAA").is_err());
+        assert!(verify_any("password", "$argon2id$v=16$m=64,t=1,p=1$c2FsdHNhbHQ$AAAAAA").is_err());
+        // A disabled account is a normal failed login, not an error
+        assert!(!verify_any("!", DISABLED_PASSWORD_HASH).unwrap());
+    }
+
+    #[test]
+    fn test_verify_rejects_excessive_costs() {
+        // Would need 4 GiB and a long run if the stored parameters were trusted
+        let tampered = "$argon2id$v=19$m=4194304,t=1,p=1$c2FsdHNhbHQ$AAAAAAAAAAAAAAAAAAAAAA";
+        assert!(verify_any("password", tampered).is_err());
+        assert!(verify_any("password", "$argon2id$v=19$m=64,t=1000,p=1$c2FsdHNhbHQ$AAAAAAAAAAAAAAAAAAAAAA").is_err());
+        assert!(verify_any("password", "$argon2id$v=19$m=4096,t=1,p=255$c2FsdHNhbHQ$AAAAAAAAAAAAAAAAAAAAAA").is_err());
+    }
+}
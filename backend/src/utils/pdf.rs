@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use owned_ttf_parser::{AsFaceRef, GlyphId, OwnedFace};
+use printpdf::path::PaintMode;
+use printpdf::{
+    FontData, FontMetrics, GlyphMetrics, IndirectFontRef, Mm, PdfDocument, PdfDocumentReference,
+    PdfLayerReference, Pt, Rect,
+};
+
+/// TrueType font read and parsed once, then embedded into every generated PDF
+#[derive(Clone, Debug)]
+pub struct EmbeddedFont {
+    data: Arc<Vec<u8>>,
+    face: Arc<OwnedFace>,
+    /// Glyph -> character map written to the PDF so text can be copied and searched
+    glyph_ids: Arc<HashMap<u16, char>>,
+}
+
+impl EmbeddedFont {
+    pub fn load(path: &str) -> Result<Self> {
+        let data = std::fs::read(path).with_context(|| format!("Failed to read font {}", path))?;
+        Self::parse(data)
+    }
+
+    pub fn parse(data: Vec<u8>) -> Result<Self> {
+        let face = OwnedFace::from_vec(data.clone(), 0).map_err(|e| anyhow!("Invalid font: {}", e))?;
+        if face.as_face_ref().units_per_em() == 0 {
+            return Err(anyhow!("Invalid font: unitsPerEm is 0"));
+        }
+
+        let mut glyph_ids = HashMap::new();
+        if let Some(cmap) = face.as_face_ref().tables().cmap {
+            for subtable in cmap.subtables.into_iter().filter(|subtable| subtable.is_unicode()) {
+                subtable.codepoints(|codepoint| {
+                    let glyph = subtable.glyph_index(codepoint).filter(|glyph| glyph.0 > 0);
+                    if let (Some(c), Some(glyph)) = (char::from_u32(codepoint), glyph) {
+                        glyph_ids.entry(glyph.0).or_insert(c);
+                    }
+                });
+            }
+        }
+
+        Ok(Self {
+            data: Arc::new(data),
+            face: Arc::new(face),
+            glyph_ids: Arc::new(glyph_ids),
+        })
+    }
+
+    pub fn has_glyph(&self, c: char) -> bool {
+        self.face.as_face_ref().glyph_index(c).is_some()
+    }
+
+    /// Width of `text` in points at `size`
+    pub fn text_width(&self, text: &str, size: f32) -> f32 {
+        let face = self.face.as_face_ref();
+        let units: f32 = text
+            .chars()
+            .filter_map(|c| face.glyph_index(c))
+            .filter_map(|glyph| face.glyph_hor_advance(glyph))
+            .map(f32::from)
+            .sum();
+        units * size / f32::from(face.units_per_em())
+    }
+}
+
+impl FontData for EmbeddedFont {
+    fn font_metrics(&self) -> FontMetrics {
+        let face = self.face.as_face_ref();
+        FontMetrics {
+            ascent: face.ascender(),
+            descent: face.descender(),
+            units_per_em: face.units_per_em(),
+        }
+    }
+
+    fn glyph_id(&self, c: char) -> Option<u16> {
+        self.face.as_face_ref().glyph_index(c).map(|glyph| glyph.0)
+    }
+
+    fn glyph_ids(&self) -> HashMap<u16, char> {
+        (*self.glyph_ids).clone()
+    }
+
+    fn glyph_count(&self) -> u16 {
+        self.face.as_face_ref().number_of_glyphs()
+    }
+
+    fn glyph_metrics(&self, glyph_id: u16) -> Option<GlyphMetrics> {
+        let face = self.face.as_face_ref();
+        let glyph = GlyphId(glyph_id);
+        let width = face.glyph_hor_advance(glyph)?;
+        let height = face
+            .glyph_bounding_box(glyph)
+            .map(|bbox| bbox.y_max - bbox.y_min - face.descender())
+            .unwrap_or(1000);
+        Some(GlyphMetrics {
+            width: u32::from(width),
+            height: height.max(0) as u32,
+        })
+    }
+}
+
+/// Single-page PDF with text and filled/stroked rectangles, positioned in points from the
+/// bottom-left corner
+pub struct PdfPage {
+    document: PdfDocumentReference,
+    layer: PdfLayerReference,
+    font: EmbeddedFont,
+    font_ref: IndirectFontRef,
+    width: f32,
+}
+
+impl PdfPage {
+    pub fn new(title: &str, width: f32, height: f32, font: &EmbeddedFont) -> Result<Self> {
+        let (document, page, layer) = PdfDocument::new(title, Mm::from(Pt(width)), Mm::from(Pt(height)), "Page");
+        let layer = document.get_page(page).get_layer(layer);
+        let font_ref = document
+            .add_external_font_data((*font.data).clone(), font.clone())
+            .map_err(|e| anyhow!("Failed to embed font: {}", e))?;
+        Ok(Self {
+            document,
+            layer,
+            font: font.clone(),
+            font_ref,
+            width,
+        })
+    }
+
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    pub fn text_width(&self, text: &str, size: f32) -> f32 {
+        self.font.text_width(text, size)
+    }
+
+    /// Draw `text` with its baseline starting at (`x`, `y`)
+    pub fn text(&self, x: f32, y: f32, size: f32, text: &str) {
+        self.layer
+            .use_text(text, size, Mm::from(Pt(x)), Mm::from(Pt(y)), &self.font_ref);
+    }
+
+    /// Draw `text` horizontally centred on `center_x`
+    pub fn text_centered(&self, center_x: f32, y: f32, size: f32, text: &str) {
+        self.text(center_x - self.text_width(text, size) / 2.0, y, size, text);
+    }
+
+    pub fn fill_rect(&self, x: f32, y: f32, width: f32, height: f32) {
+        self.layer.add_rect(rect(x, y, width, height).with_mode(PaintMode::Fill));
+    }
+
+    pub fn stroke_rect(&self, x: f32, y: f32, width: f32, height: f32, line_width: f32) {
+        self.layer.set_outline_thickness(line_width);
+        self.layer.add_rect(rect(x, y, width, height).with_mode(PaintMode::Stroke));
+    }
+
+    pub fn render(self) -> Result<Vec<u8>> {
+        self.document
+            .save_to_bytes()
+            .map_err(|e| anyhow!("Failed to write PDF: {}", e))
+    }
+}
+
+fn rect(x: f32, y: f32, width: f32, height: f32) -> Rect {
+    Rect::new(
+        Mm::from(Pt(x)),
+        Mm::from(Pt(y)),
+        Mm::from(Pt(x + width)),
+        Mm::from(Pt(y + height)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedded_font_document() {
+        // Systems without DejaVu have nothing to parse; font errors are covered below
+        let Ok(font) = EmbeddedFont::load("/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf") else {
+            return;
+        };
+        assert!(font.has_glyph('A'));
+        assert!(!font.has_glyph('\u{e000}'));
+        assert!(font.text_width("AA", 12.0) > font.text_width("A", 12.0));
+
+        let page = PdfPage::new("Test", 595.0, 842.0, &font).unwrap();
+        page.text_centered(297.5, 400.0, 12.0, "Caf\u{e9} (1)");
+        page.fill_rect(10.0, 10.0, 2.5, 2.5);
+        page.stroke_rect(20.0, 20.0, 100.0, 50.0, 0.5);
+        let pdf = page.render().unwrap();
+
+        assert!(pdf.starts_with(b"%PDF-"));
+        assert!(String::from_utf8_lossy(&pdf).contains("/CIDFontType2"));
+    }
+
+    #[test]
+    fn test_invalid_font_is_rejected() {
+        assert!(EmbeddedFont::parse(b"not a font".to_vec()).is_err());
+        assert!(EmbeddedFont::load("/nonexistent/font.ttf").is_err());
+    }
+}
@@ -1,7 +1,7 @@
 use anyhow::{Result, anyhow};
 // chrono imports removed as DateTime and Utc are unused in this module
 use hmac::{Hmac, Mac};
-use qrcode::QrCode;
+use qrcode::{Color, EcLevel, QrCode};
 use qrcode::render::svg as qrs_svg;
 use serde::{Deserialize, Serialize};
 use sha2::Sha256;
@@ -10,6 +10,28 @@ use uuid::Uuid;
 
 type HmacSha256 = Hmac<Sha256>;
 
+/// เวอร์ชันของ QR payload ที่ส่งให้ client (ใช้ตัดสินใจว่าจะ parse อย่างไร)
+pub const QR_PAYLOAD_VERSION: u32 = 1;
+
+/// รูปแบบผลลัพธ์ของ QR code ที่ client ร้องขอ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QrOutputFormat {
+    Png,
+    Svg,
+    Json,
+}
+
+impl QrOutputFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "png" => Some(Self::Png),
+            "svg" => Some(Self::Svg),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
 /// QR Code data structure ที่จะถูกเข้ารหัสใน QR Code (รุ่นใหม่)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QrData {
@@ -27,6 +49,34 @@ pub struct ClientQrData {
     pub timestamp: u64,
 }
 
+/// Signed QR payload สำหรับการสแกนโดย admin (scan_qr)
+///
+/// `sig` คือ HMAC-SHA256 ของ field อื่น ๆ รวมกับ qr_secret ของ user โดยใช้ server secret เป็น key
+/// การ refresh qr_secret จึงทำให้ QR ที่ออกไปก่อนหน้าใช้ไม่ได้ทันที
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedQrPayload {
+    pub user_id: Uuid,
+    pub issued_at: u64,
+    pub expires_at: u64,
+    pub sig: String,
+}
+
+/// อายุของ signed QR payload (วินาที)
+pub const SIGNED_QR_TTL_SECONDS: u64 = 5 * 60;
+
+/// Token ใน QR ของกิจกรรมที่แสดงบน kiosk ให้นักศึกษาสแกนเพื่อ check-in ด้วยตัวเอง
+///
+/// `sig` คือ HMAC-SHA256 ของ activity_id และ expires_at โดยใช้ server secret เป็น key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityCheckInToken {
+    pub activity_id: Uuid,
+    pub expires_at: u64,
+    pub sig: String,
+}
+
+/// อายุของ activity check-in token (วินาที); kiosk ควรขอ token ใหม่ก่อนหมดอายุ
+pub const ACTIVITY_CHECKIN_TTL_SECONDS: u64 = 60;
+
 /// QR Code generation response
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QrGenerationResponse {
@@ -146,6 +196,43 @@ pub fn render_qr_svg(data: &str, size: u32) -> Result<String> {
     Ok(svg)
 }
 
+/// แปลงค่า error correction level จาก query param (L/M/Q/H)
+pub fn parse_ec_level(value: &str) -> Option<EcLevel> {
+    match value.to_ascii_uppercase().as_str() {
+        "L" => Some(EcLevel::L),
+        "M" => Some(EcLevel::M),
+        "Q" => Some(EcLevel::Q),
+        "H" => Some(EcLevel::H),
+        _ => None,
+    }
+}
+
+/// Render QR code as PNG bytes (grayscale) for image responses
+pub fn render_qr_png(data: &str, size: u32, ec_level: EcLevel) -> Result<Vec<u8>> {
+    let code = QrCode::with_error_correction_level(data.as_bytes(), ec_level)?;
+    let width = code.width() as u32;
+    let quiet_zone = 4;
+    let modules = width + quiet_zone * 2;
+    let scale = (size / modules).max(1);
+    let dimension = modules * scale;
+
+    let colors = code.to_colors();
+    let image = image::GrayImage::from_fn(dimension, dimension, |x, y| {
+        let mx = (x / scale) as i64 - quiet_zone as i64;
+        let my = (y / scale) as i64 - quiet_zone as i64;
+        let is_dark = mx >= 0
+            && my >= 0
+            && (mx as u32) < width
+            && (my as u32) < width
+            && colors[(my as u32 * width + mx as u32) as usize] == Color::Dark;
+        image::Luma([if is_dark { 0 } else { 255 }])
+    });
+
+    let mut bytes = std::io::Cursor::new(Vec::new());
+    image.write_to(&mut bytes, image::ImageOutputFormat::Png)?;
+    Ok(bytes.into_inner())
+}
+
 /// สร้าง unique identifier สำหรับ QR Code (UUID)
 pub fn generate_qr_identifier() -> String {
     Uuid::new_v4().to_string()
@@ -225,6 +312,155 @@ pub fn validate_client_qr_data(qr_json: &str, expected_secret: &str, max_age_sec
     }
 }
 
+fn signed_qr_mac(
+    user_id: &Uuid,
+    issued_at: u64,
+    expires_at: u64,
+    user_secret: &str,
+    server_secret: &str,
+) -> Result<HmacSha256> {
+    let message = format!("{}:{}:{}:{}:qr_signed_v1", user_id, issued_at, expires_at, user_secret);
+    let mut mac = HmacSha256::new_from_slice(server_secret.as_bytes())
+        .map_err(|e| anyhow!("Invalid secret key: {}", e))?;
+    mac.update(message.as_bytes());
+    Ok(mac)
+}
+
+/// สร้าง signed QR payload (JSON string) สำหรับ user
+pub fn generate_signed_qr_payload(
+    user_id: &Uuid,
+    user_secret: &str,
+    server_secret: &str,
+    ttl_seconds: u64,
+) -> Result<String> {
+    let issued_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)?
+        .as_secs();
+    let expires_at = issued_at + ttl_seconds;
+
+    let mac = signed_qr_mac(user_id, issued_at, expires_at, user_secret, server_secret)?;
+    let payload = SignedQrPayload {
+        user_id: *user_id,
+        issued_at,
+        expires_at,
+        sig: hex::encode(mac.finalize().into_bytes()),
+    };
+
+    Ok(serde_json::to_string(&payload)?)
+}
+
+/// Parse signed QR payload (ยังไม่ตรวจสอบ signature)
+pub fn parse_signed_qr_payload(qr_json: &str) -> Option<SignedQrPayload> {
+    serde_json::from_str(qr_json).ok()
+}
+
+impl SignedQrPayload {
+    /// ตรวจสอบ expiry และ signature ของ payload
+    pub fn verify(&self, user_secret: &str, server_secret: &str) -> Result<()> {
+        let current_timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        if current_timestamp > self.expires_at {
+            return Err(anyhow!("QR code has expired"));
+        }
+
+        let signature = hex::decode(&self.sig).map_err(|_| anyhow!("Invalid signature"))?;
+        signed_qr_mac(&self.user_id, self.issued_at, self.expires_at, user_secret, server_secret)?
+            .verify_slice(&signature)
+            .map_err(|_| anyhow!("Invalid signature"))
+    }
+}
+
+fn activity_checkin_mac(activity_id: &Uuid, expires_at: u64, server_secret: &str) -> Result<HmacSha256> {
+    let message = format!("{}:{}:activity_checkin_v1", activity_id, expires_at);
+    let mut mac = HmacSha256::new_from_slice(server_secret.as_bytes())
+        .map_err(|e| anyhow!("Invalid secret key: {}", e))?;
+    mac.update(message.as_bytes());
+    Ok(mac)
+}
+
+/// สร้าง activity check-in token (JSON string) สำหรับแสดงเป็น QR
+pub fn generate_activity_checkin_token(
+    activity_id: &Uuid,
+    server_secret: &str,
+    ttl_seconds: u64,
+) -> Result<QrGenerationResponse> {
+    let expires_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)?
+        .as_secs()
+        + ttl_seconds;
+
+    let mac = activity_checkin_mac(activity_id, expires_at, server_secret)?;
+    let token = ActivityCheckInToken {
+        activity_id: *activity_id,
+        expires_at,
+        sig: hex::encode(mac.finalize().into_bytes()),
+    };
+
+    Ok(QrGenerationResponse {
+        qr_data: serde_json::to_string(&token)?,
+        expires_at,
+    })
+}
+
+/// Parse activity check-in token (ยังไม่ตรวจสอบ signature)
+pub fn parse_activity_checkin_token(token: &str) -> Option<ActivityCheckInToken> {
+    serde_json::from_str(token).ok()
+}
+
+impl ActivityCheckInToken {
+    /// ตรวจสอบว่า token เป็นของกิจกรรมนี้ ยังไม่หมดอายุ และ signature ถูกต้อง
+    pub fn verify(&self, activity_id: &Uuid, server_secret: &str) -> Result<()> {
+        if self.activity_id != *activity_id {
+            return Err(anyhow!("QR code belongs to a different activity"));
+        }
+
+        let current_timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        if current_timestamp > self.expires_at {
+            return Err(anyhow!("QR code has expired"));
+        }
+
+        let signature = hex::decode(&self.sig).map_err(|_| anyhow!("Invalid signature"))?;
+        activity_checkin_mac(&self.activity_id, self.expires_at, server_secret)?
+            .verify_slice(&signature)
+            .map_err(|_| anyhow!("Invalid signature"))
+    }
+}
+
+fn certificate_mac(participation_id: &Uuid, server_secret: &str) -> Result<HmacSha256> {
+    let message = format!("{}:certificate_v1", participation_id);
+    let mut mac = HmacSha256::new_from_slice(server_secret.as_bytes())
+        .map_err(|e| anyhow!("Invalid secret key: {}", e))?;
+    mac.update(message.as_bytes());
+    Ok(mac)
+}
+
+/// Token ใน QR ของใบรับรองการเข้าร่วม: `{participation_id}.{sig}`
+///
+/// ไม่มีวันหมดอายุ; ใบรับรองจะใช้ไม่ได้เมื่อสถานะ participation ไม่ใช่ completed อีกต่อไป
+pub fn generate_certificate_token(participation_id: &Uuid, server_secret: &str) -> Result<String> {
+    let mac = certificate_mac(participation_id, server_secret)?;
+    Ok(format!("{}.{}", participation_id, hex::encode(mac.finalize().into_bytes())))
+}
+
+/// ตรวจสอบ signature ของ certificate token แล้วคืน participation id
+pub fn verify_certificate_token(token: &str, server_secret: &str) -> Option<Uuid> {
+    let (participation_id, sig) = token.split_once('.')?;
+    let participation_id = Uuid::parse_str(participation_id).ok()?;
+    let signature = hex::decode(sig).ok()?;
+    certificate_mac(&participation_id, server_secret)
+        .ok()?
+        .verify_slice(&signature)
+        .ok()?;
+    Some(participation_id)
+}
+
 /// Validate student_id format
 pub fn validate_student_id(student_id: &str) -> bool {
     // ตรวจสอบว่า student_id เป็นตัวอักษรและตัวเลขเท่านั้น และมีความยาวเหมาะสม
@@ -278,14 +514,92 @@ mod tests {
         // สร้าง QR data
         let qr_json = generate_qr_data(student_id, &secret_key).unwrap();
         
-        // รอให้ QR หมดอายุ (ใช้ max_age = 0 เพื่อทดสอบ)
-        thread::sleep(Duration::from_millis(10));
+        // รอให้ QR หมดอายุ (timestamp มีความละเอียดระดับวินาที)
+        thread::sleep(Duration::from_millis(1100));
         let result = validate_qr_data(&qr_json, &secret_key, 0);
         
         assert!(!result.is_valid);
         assert!(result.error_message.unwrap().contains("expired"));
     }
     
+    #[test]
+    fn test_parse_output_format() {
+        assert_eq!(QrOutputFormat::parse("png"), Some(QrOutputFormat::Png));
+        assert_eq!(QrOutputFormat::parse("SVG"), Some(QrOutputFormat::Svg));
+        assert_eq!(QrOutputFormat::parse("json"), Some(QrOutputFormat::Json));
+        assert_eq!(QrOutputFormat::parse("gif"), None);
+    }
+
+    #[test]
+    fn test_render_qr_png() {
+        let png = render_qr_png("payload", 256, EcLevel::M).unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+
+        let image = image::load_from_memory(&png).unwrap();
+        assert!(image.width() <= 256 && image.width() >= 128);
+    }
+
+    #[test]
+    fn test_parse_ec_level() {
+        assert_eq!(parse_ec_level("l"), Some(EcLevel::L));
+        assert_eq!(parse_ec_level("M"), Some(EcLevel::M));
+        assert_eq!(parse_ec_level("q"), Some(EcLevel::Q));
+        assert_eq!(parse_ec_level("H"), Some(EcLevel::H));
+        assert_eq!(parse_ec_level("X"), None);
+    }
+
+    #[test]
+    fn test_render_qr_svg() {
+        let svg = render_qr_svg("payload", 256).unwrap();
+        assert!(svg.contains("<svg"));
+    }
+
+    #[test]
+    fn test_client_qr_json_payload() {
+        let user_id = Uuid::new_v4();
+        let response = generate_client_qr_data(&user_id, "STU004", "secret").unwrap();
+        let payload: ClientQrData = serde_json::from_str(&response.qr_data).unwrap();
+
+        assert_eq!(payload.user_id, user_id);
+        assert_eq!(response.expires_at, payload.timestamp + 5 * 60);
+    }
+
+    #[test]
+    fn test_signed_qr_payload_roundtrip() {
+        let user_id = Uuid::new_v4();
+        let payload = generate_signed_qr_payload(&user_id, "user-secret", "server-secret", 300).unwrap();
+        let parsed = parse_signed_qr_payload(&payload).unwrap();
+
+        assert_eq!(parsed.user_id, user_id);
+        assert_eq!(parsed.expires_at, parsed.issued_at + 300);
+        assert!(parsed.verify("user-secret", "server-secret").is_ok());
+    }
+
+    #[test]
+    fn test_signed_qr_payload_rejects_rotated_secret() {
+        let user_id = Uuid::new_v4();
+        let payload = generate_signed_qr_payload(&user_id, "old-secret", "server-secret", 300).unwrap();
+        let parsed = parse_signed_qr_payload(&payload).unwrap();
+
+        assert!(parsed.verify("new-secret", "server-secret").is_err());
+        assert!(parsed.verify("old-secret", "other-server-secret").is_err());
+    }
+
+    #[test]
+    fn test_signed_qr_payload_rejects_tampering_and_expiry() {
+        let user_id = Uuid::new_v4();
+        let payload = generate_signed_qr_payload(&user_id, "secret", "server-secret", 300).unwrap();
+        let mut parsed = parse_signed_qr_payload(&payload).unwrap();
+        parsed.expires_at += 3600;
+        assert!(parsed.verify("secret", "server-secret").is_err());
+
+        let mut expired = parse_signed_qr_payload(&payload).unwrap();
+        expired.issued_at -= 600;
+        expired.expires_at = expired.issued_at + 1;
+        let err = expired.verify("secret", "server-secret").unwrap_err();
+        assert!(err.to_string().contains("expired"));
+    }
+
     #[test]
     fn test_validate_student_id() {
         assert!(validate_student_id("STU001"));
@@ -294,4 +608,43 @@ mod tests {
         assert!(!validate_student_id(""));    // empty
         assert!(!validate_student_id("STU@001")); // invalid character
     }
+
+    #[test]
+    fn test_activity_checkin_token() {
+        let activity_id = Uuid::new_v4();
+        let generated = generate_activity_checkin_token(&activity_id, "server-secret", 60).unwrap();
+        let token = parse_activity_checkin_token(&generated.qr_data).unwrap();
+        assert_eq!(token.expires_at, generated.expires_at);
+        assert!(token.verify(&activity_id, "server-secret").is_ok());
+
+        assert!(token.verify(&Uuid::new_v4(), "server-secret").is_err());
+        assert!(token.verify(&activity_id, "other-server-secret").is_err());
+
+        let mut tampered = token.clone();
+        tampered.expires_at += 3600;
+        assert!(tampered.verify(&activity_id, "server-secret").is_err());
+
+        let expires_at = token.expires_at - 600;
+        let mac = activity_checkin_mac(&activity_id, expires_at, "server-secret").unwrap();
+        let expired = ActivityCheckInToken {
+            activity_id,
+            expires_at,
+            sig: hex::encode(mac.finalize().into_bytes()),
+        };
+        let err = expired.verify(&activity_id, "server-secret").unwrap_err();
+        assert!(err.to_string().contains("expired"));
+    }
+
+    #[test]
+    fn test_certificate_token() {
+        let participation_id = Uuid::new_v4();
+        let token = generate_certificate_token(&participation_id, "server-secret").unwrap();
+        assert_eq!(verify_certificate_token(&token, "server-secret"), Some(participation_id));
+        assert_eq!(verify_certificate_token(&token, "other-server-secret"), None);
+
+        let (_, sig) = token.split_once('.').unwrap();
+        let forged = format!("{}.{}", Uuid::new_v4(), sig);
+        assert_eq!(verify_certificate_token(&forged, "server-secret"), None);
+        assert_eq!(verify_certificate_token("not-a-token", "server-secret"), None);
+    }
 }
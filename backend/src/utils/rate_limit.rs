@@ -0,0 +1,49 @@
+use axum::{
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use serde_json::json;
+
+/// Standard 429 response used by every rate-limited endpoint.
+///
+/// Always carries a `Retry-After` header (seconds) and the same JSON body shape,
+/// so clients can back off without special-casing individual endpoints.
+pub fn rate_limited(retry_after_secs: u64) -> Response {
+    let retry_after_secs = retry_after_secs.max(1);
+    let body = json!({
+        "status": "error",
+        "error": "RateLimitExceeded",
+        "message": "Too many requests, please try again later",
+        "retry_after": retry_after_secs
+    });
+
+    let mut response = (StatusCode::TOO_MANY_REQUESTS, Json(body)).into_response();
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(retry_after_secs));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limited_sets_retry_after() {
+        let response = rate_limited(30);
+
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let retry_after = response
+            .headers()
+            .get(header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok());
+        assert_eq!(retry_after, Some(30));
+    }
+
+    #[test]
+    fn test_rate_limited_never_zero() {
+        let response = rate_limited(0);
+        assert_eq!(response.headers().get(header::RETRY_AFTER).unwrap(), "1");
+    }
+}
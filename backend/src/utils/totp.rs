@@ -0,0 +1,141 @@
+//! Time-based one-time passwords (RFC 6238: HMAC-SHA1, 30 second steps, 6 digits), the
+//! scheme used by Google Authenticator and similar apps
+
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use sha1::Sha1;
+use subtle::ConstantTimeEq;
+
+use crate::utils::http_client::percent_encode;
+
+const STEP_SECONDS: i64 = 30;
+const DIGITS: u32 = 6;
+/// Steps accepted either side of the current one, for clock drift
+const ALLOWED_DRIFT_STEPS: i64 = 1;
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// New random 160-bit secret, base32-encoded as authenticator apps expect
+pub fn generate_totp_secret() -> String {
+    let bytes: [u8; 20] = rand::thread_rng().gen();
+    base32_encode(&bytes)
+}
+
+/// `otpauth://` URI for enrolling `account` in an authenticator app
+pub fn otpauth_uri(issuer: &str, account: &str, secret: &str) -> String {
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        percent_encode(issuer),
+        percent_encode(account),
+        secret,
+        percent_encode(issuer),
+        DIGITS,
+        STEP_SECONDS
+    )
+}
+
+/// Code for time step `step` (unix time / 30)
+pub fn totp_code(secret: &str, step: i64) -> Option<String> {
+    let key = base32_decode(secret)?;
+    let mut mac = Hmac::<Sha1>::new_from_slice(&key).ok()?;
+    mac.update(&step.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    // Dynamic truncation
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let value = u32::from_be_bytes([digest[offset], digest[offset + 1], digest[offset + 2], digest[offset + 3]])
+        & 0x7fff_ffff;
+    Some(format!("{:0width$}", value % 10u32.pow(DIGITS), width = DIGITS as usize))
+}
+
+pub fn current_step(unix_seconds: i64) -> i64 {
+    unix_seconds.div_euclid(STEP_SECONDS)
+}
+
+/// Check `code` around `unix_seconds`; returns the matching step so callers can refuse
+/// a code from a step that was already used
+pub fn verify_totp(secret: &str, code: &str, unix_seconds: i64) -> Option<i64> {
+    let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+    if code.len() != DIGITS as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let now = current_step(unix_seconds);
+    (now - ALLOWED_DRIFT_STEPS..=now + ALLOWED_DRIFT_STEPS).find(|&step| {
+        totp_code(secret, step).is_some_and(|expected| bool::from(expected.as_bytes().ct_eq(code.as_bytes())))
+    })
+}
+
+/// RFC 4648 base32 without padding
+pub fn base32_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    out
+}
+
+/// Decode base32, ignoring case, spaces and padding
+pub fn base32_decode(encoded: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(encoded.len() * 5 / 8);
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for c in encoded.chars().filter(|c| !c.is_whitespace() && *c != '=') {
+        let value = BASE32_ALPHABET.iter().position(|&a| a as char == c.to_ascii_uppercase())? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base32_round_trip() {
+        assert_eq!(base32_encode(b"foobar"), "MZXW6YTBOI");
+        assert_eq!(base32_decode("mzxw6ytboi======").unwrap(), b"foobar");
+        assert!(base32_decode("not base32!").is_none());
+        let secret = generate_totp_secret();
+        assert_eq!(secret.len(), 32);
+        assert_eq!(base32_decode(&secret).unwrap().len(), 20);
+    }
+
+    #[test]
+    fn test_totp_rfc6238_vectors() {
+        // RFC 6238 appendix B, SHA1 seed "12345678901234567890" (last 6 digits)
+        let secret = base32_encode(b"12345678901234567890");
+        assert_eq!(totp_code(&secret, current_step(59)).unwrap(), "287082");
+        assert_eq!(totp_code(&secret, current_step(1111111109)).unwrap(), "081804");
+        assert_eq!(totp_code(&secret, current_step(2000000000)).unwrap(), "279037");
+    }
+
+    #[test]
+    fn test_verify_totp_allows_one_step_of_drift() {
+        let secret = base32_encode(b"12345678901234567890");
+        assert_eq!(verify_totp(&secret, "287082", 59), Some(1));
+        assert_eq!(verify_totp(&secret, "287 082", 59 + 30), Some(1));
+        assert_eq!(verify_totp(&secret, "287082", 59 + 90), None);
+        assert_eq!(verify_totp(&secret, "28708", 59), None);
+        assert_eq!(verify_totp(&secret, "abcdef", 59), None);
+    }
+
+    #[test]
+    fn test_otpauth_uri() {
+        assert_eq!(
+            otpauth_uri("Trackivity", "admin@test.local", "ABC"),
+            "otpauth://totp/Trackivity:admin%40test.local?secret=ABC&issuer=Trackivity&algorithm=SHA1&digits=6&period=30"
+        );
+    }
+}
@@ -1,4 +1,9 @@
+use axum::{http::StatusCode, response::Json};
 use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::config::Config;
 
 pub fn validate_email(email: &str) -> bool {
     let email_regex = Regex::new(r"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}$").unwrap();
@@ -11,14 +16,163 @@ pub fn validate_student_id(student_id: &str) -> bool {
     student_id_regex.is_match(student_id)
 }
 
-pub fn validate_password(password: &str) -> bool {
-    // At least 8 characters, contains at least one letter and one number
-    password.len() >= 8
-        && password.chars().any(|c| c.is_alphabetic())
-        && password.chars().any(|c| c.is_numeric())
+/// Password strength rules, configured through `Config`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub require_digit: bool,
+    pub require_uppercase: bool,
+    pub require_symbol: bool,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            require_digit: true,
+            require_uppercase: false,
+            require_symbol: false,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            min_length: config.password_min_length,
+            require_digit: config.password_require_digit,
+            require_uppercase: config.password_require_uppercase,
+            require_symbol: config.password_require_symbol,
+        }
+    }
+}
+
+/// A single validation rule that an input failed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleViolation {
+    pub rule: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidationError {
+    pub field: String,
+    pub violations: Vec<RuleViolation>,
+}
+
+impl ValidationError {
+    /// 400 response listing every failed rule so the frontend can show them
+    pub fn to_error_response(&self) -> (StatusCode, Json<Value>) {
+        let error_response = json!({
+            "status": "error",
+            "message": format!("Invalid {}", self.field),
+            "field": self.field,
+            "errors": self.violations
+        });
+        (StatusCode::BAD_REQUEST, Json(error_response))
+    }
+}
+
+pub fn validate_password(password: &str, policy: &PasswordPolicy) -> Result<(), ValidationError> {
+    let mut violations = Vec::new();
+    let mut fail = |rule: &str, message: String| {
+        violations.push(RuleViolation {
+            rule: rule.to_string(),
+            message,
+        })
+    };
+
+    if password.chars().count() < policy.min_length {
+        fail(
+            "min_length",
+            format!("Password must be at least {} characters", policy.min_length),
+        );
+    }
+    if policy.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+        fail("digit", "Password must contain a number".to_string());
+    }
+    if policy.require_uppercase && !password.chars().any(|c| c.is_uppercase()) {
+        fail("uppercase", "Password must contain an uppercase letter".to_string());
+    }
+    if policy.require_symbol && !password.chars().any(|c| !c.is_alphanumeric() && !c.is_whitespace()) {
+        fail("symbol", "Password must contain a symbol".to_string());
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(ValidationError {
+            field: "password".to_string(),
+            violations,
+        })
+    }
+}
+
+/// Random password that satisfies `policy`, for accounts created on someone's behalf
+pub fn generate_password(policy: &PasswordPolicy) -> String {
+    use rand::Rng;
+
+    const CHARSET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz23456789!@#$%*";
+    let length = policy.min_length.max(12);
+    let mut rng = rand::thread_rng();
+
+    loop {
+        let password: String = (0..length)
+            .map(|_| CHARSET[rng.gen_range(0..CHARSET.len())] as char)
+            .collect();
+        if validate_password(&password, policy).is_ok() {
+            return password;
+        }
+    }
 }
 
 pub fn validate_name(name: &str) -> bool {
     // Name should be 1-100 characters and not just whitespace
     !name.trim().is_empty() && name.trim().len() <= 100
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_password_default_policy() {
+        let policy = PasswordPolicy::default();
+        assert!(validate_password("password123", &policy).is_ok());
+
+        let err = validate_password("short", &policy).unwrap_err();
+        let rules: Vec<&str> = err.violations.iter().map(|v| v.rule.as_str()).collect();
+        assert_eq!(rules, vec!["min_length", "digit"]);
+    }
+
+    #[test]
+    fn test_validate_password_strict_policy() {
+        let policy = PasswordPolicy {
+            min_length: 10,
+            require_digit: true,
+            require_uppercase: true,
+            require_symbol: true,
+        };
+        assert!(validate_password("Str0ng!Passw0rd", &policy).is_ok());
+
+        let err = validate_password("weakpassword1", &policy).unwrap_err();
+        let rules: Vec<&str> = err.violations.iter().map(|v| v.rule.as_str()).collect();
+        assert_eq!(rules, vec!["uppercase", "symbol"]);
+        assert_eq!(err.field, "password");
+    }
+
+    #[test]
+    fn test_generate_password_meets_policy() {
+        let policy = PasswordPolicy {
+            min_length: 16,
+            require_digit: true,
+            require_uppercase: true,
+            require_symbol: true,
+        };
+        for _ in 0..20 {
+            let password = generate_password(&policy);
+            assert_eq!(password.len(), 16);
+            assert!(validate_password(&password, &policy).is_ok());
+        }
+    }
+}
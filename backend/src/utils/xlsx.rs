@@ -0,0 +1,128 @@
+use axum::{
+    http::header,
+    response::{IntoResponse, Response},
+};
+use rust_xlsxwriter::{Color, Format, Workbook, XlsxError};
+
+pub const XLSX_CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+
+/// Widest column, in characters, when sizing columns to their content
+const MAX_COLUMN_WIDTH: usize = 50;
+const MIN_COLUMN_WIDTH: usize = 10;
+
+/// Build a single-sheet workbook: a bold, filled header row that stays frozen while scrolling,
+/// followed by `rows`. All cells are written as text so values match the CSV exports exactly.
+pub fn xlsx_workbook<H: AsRef<str>, S: AsRef<str>>(
+    sheet_name: &str,
+    header: &[H],
+    rows: &[Vec<S>],
+) -> Result<Vec<u8>, XlsxError> {
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name(sheet_title(sheet_name))?;
+
+    let header_format = Format::new()
+        .set_bold()
+        .set_font_color(Color::White)
+        .set_background_color(Color::RGB(0x4472C4));
+    for (col, value) in header.iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, value.as_ref(), &header_format)?;
+    }
+    for (row, values) in rows.iter().enumerate() {
+        for (col, value) in values.iter().enumerate() {
+            worksheet.write_string(row as u32 + 1, col as u16, value.as_ref())?;
+        }
+    }
+
+    for (col, width) in column_widths(header, rows).into_iter().enumerate() {
+        let width = (width + 2).clamp(MIN_COLUMN_WIDTH, MAX_COLUMN_WIDTH);
+        worksheet.set_column_width(col as u16, width as f64)?;
+    }
+    worksheet.set_freeze_panes(1, 0)?;
+
+    workbook.save_to_buffer()
+}
+
+/// Download response for a generated workbook
+pub fn xlsx_attachment(filename: &str, workbook: Vec<u8>) -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, XLSX_CONTENT_TYPE.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        workbook,
+    )
+        .into_response()
+}
+
+/// Longest value per column, in characters
+fn column_widths<H: AsRef<str>, S: AsRef<str>>(header: &[H], rows: &[Vec<S>]) -> Vec<usize> {
+    let mut widths: Vec<usize> = header.iter().map(|h| h.as_ref().chars().count()).collect();
+    for row in rows {
+        for (i, value) in row.iter().enumerate() {
+            let len = value.as_ref().chars().count();
+            match widths.get_mut(i) {
+                Some(width) => *width = (*width).max(len),
+                None => widths.push(len),
+            }
+        }
+    }
+    widths
+}
+
+/// Excel limits sheet names to 31 characters and forbids `[]:*?/\`
+fn sheet_title(name: &str) -> String {
+    let title: String = name
+        .chars()
+        .filter(|c| !matches!(c, '[' | ']' | ':' | '*' | '?' | '/' | '\\'))
+        .take(31)
+        .collect();
+    if title.is_empty() {
+        "Sheet1".to_string()
+    } else {
+        title
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Read};
+
+    fn read_part(workbook: &[u8], name: &str) -> String {
+        let mut archive = zip::ZipArchive::new(Cursor::new(workbook)).unwrap();
+        let mut contents = String::new();
+        archive.by_name(name).unwrap().read_to_string(&mut contents).unwrap();
+        contents
+    }
+
+    #[test]
+    fn test_sheet_title() {
+        assert_eq!(sheet_title("Participations: 2567/1"), "Participations 25671");
+        assert_eq!(sheet_title("[]"), "Sheet1");
+        assert_eq!(sheet_title(&"ก".repeat(40)).chars().count(), 31);
+    }
+
+    #[test]
+    fn test_workbook_has_frozen_styled_header() {
+        let workbook = xlsx_workbook(
+            "Participations: 2567/1",
+            &["student_id", "name"],
+            &[vec!["6401234567", "สมชาย & ใจดี"]],
+        )
+        .unwrap();
+
+        assert!(read_part(&workbook, "xl/workbook.xml").contains(r#"<sheet name="Participations 25671""#));
+        let sheet = read_part(&workbook, "xl/worksheets/sheet1.xml");
+        assert!(sheet.contains(r#"state="frozen""#));
+        assert!(sheet.contains(r#"<c r="A1" s="1" t="s">"#));
+        assert!(sheet.contains(r#"<c r="B2" t="s">"#));
+        let strings = read_part(&workbook, "xl/sharedStrings.xml");
+        assert!(strings.contains("student_id"));
+        assert!(strings.contains("สมชาย &amp; ใจดี"));
+        assert!(read_part(&workbook, "xl/styles.xml").contains(r#"<b/>"#));
+    }
+}
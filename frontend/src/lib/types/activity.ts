@@ -1,6 +1,7 @@
 export type ActivityType = 'Academic' | 'Sports' | 'Cultural' | 'Social' | 'Other';
 export type ActivityStatus = 'draft' | 'published' | 'ongoing' | 'completed' | 'cancelled';
-export type ParticipationStatus = 'registered' | 'checked_in' | 'checked_out' | 'completed';
+export type ActivityVisibility = 'Public' | 'FacultyOnly' | 'Private';
+export type ParticipationStatus = 'registered' | 'checked_in' | 'checked_out' | 'completed' | 'cancelled' | 'waitlisted';
 
 // Updated to match backend API response structure
 export interface Activity {
@@ -11,8 +12,10 @@ export interface Activity {
 	start_time: string; // Full ISO datetime from backend
 	end_time: string; // Full ISO datetime from backend
 	max_participants?: number;
+	registration_deadline?: string; // ISO datetime; null means registration closes at start_time
 	current_participants: number;
 	status: ActivityStatus;
+	visibility?: ActivityVisibility;
 	faculty_id?: string;
 	faculty_name?: string;
 	created_by: string;
@@ -49,6 +52,9 @@ export interface ActivityCreateData {
 	max_participants?: number;
 	faculty_id?: string;
 	department_id?: string;
+	allow_waitlist?: boolean;
+	registration_deadline?: string; // ISO datetime
+	visibility?: ActivityVisibility; // FacultyOnly requires faculty_id
 }
 
 // For updating activities
@@ -62,6 +68,9 @@ export interface ActivityUpdateData {
 	status?: ActivityStatus;
 	faculty_id?: string;
 	department_id?: string;
+	allow_waitlist?: boolean;
+	registration_deadline?: string; // ISO datetime
+	visibility?: ActivityVisibility; // FacultyOnly requires faculty_id
 }
 
 // Participation data
@@ -54,7 +54,9 @@ export const actions: Actions = {
 			const response = await api.post(event, '/api/admin/auth/login', {
 				email: form.data.email,
 				password: form.data.password,
-				remember_me: form.data.remember_me
+				remember_me: form.data.remember_me,
+				// Present when arriving from a new-device verification email
+				device_verification_token: event.url.searchParams.get('device_token') ?? undefined
 			});
 
 			console.log('API Response:', {
@@ -80,7 +82,8 @@ export const actions: Actions = {
 					httpOnly: true,
 					secure: process.env.NODE_ENV === 'production',
 					sameSite: 'lax',
-					maxAge: form.data.remember_me ? 30 * 24 * 60 * 60 : 24 * 60 * 60 // 30 days or 1 day
+					// Match the server-side session lifetime (longer when remember_me is set)
+					maxAge: Math.max(0, Math.floor((Date.parse(response.data.session.expires_at) - Date.now()) / 1000))
 				});
 
 				throw redirect(303, '/admin');
@@ -43,7 +43,9 @@ export const actions: Actions = {
             const result = await api.post(event, `/api/auth/login`, {
                 student_id: form.data.student_id,
                 password: form.data.password,
-                remember_me: form.data.remember_me
+                remember_me: form.data.remember_me,
+                // Present when arriving from a new-device verification email
+                device_verification_token: url.searchParams.get('device_token') ?? undefined
             });
 
             if (!result.success) {
@@ -58,7 +60,8 @@ export const actions: Actions = {
                     httpOnly: true,
                     secure: process.env.NODE_ENV === 'production',
                     sameSite: 'lax',
-                    maxAge: form.data.remember_me ? 30 * 24 * 60 * 60 : 24 * 60 * 60
+                    // Match the server-side session lifetime (longer when remember_me is set)
+                    maxAge: Math.max(0, Math.floor((Date.parse(payload.session.expires_at) - Date.now()) / 1000))
                 });
 
                 const redirectTo = url.searchParams.get('redirectTo') || '/';